@@ -0,0 +1,776 @@
+//! A small hand-rolled argument parser.
+//!
+//! Every subcommand and flag is described once, in `COMMANDS` and
+//! `GLOBAL_FLAGS`: the parser checks the command line against these tables, and
+//! the help text is generated from them, so the two can never disagree.
+//!
+//! Global flags come before the subcommand. A command's flags can appear
+//! anywhere after it, in long (`--repeat 3`, `--repeat=3`) or short (`-n 3`)
+//! form. Everything after `--` is positional, even if it starts with a dash.
+
+use crate::completions::{Shell, UnknownShell};
+use crate::output::OutputFormat;
+use crate::style::{ColorChoice, Style};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The version printed by `--version`, taken from Cargo.toml.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// One flag of a command.
+pub struct Flag {
+    pub short: Option<char>,
+    pub long: &'static str,
+    /// The name of the flag's value in the help (`--repeat N`), or `None` for an on/off flag.
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+    /// The values shell completion offers for the flag; empty if any value goes.
+    pub choices: &'static [&'static str],
+}
+
+/// One subcommand: its name, what it does and the flags it accepts.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub about: &'static str,
+    /// The positional arguments, as shown in the usage line.
+    pub positionals: &'static str,
+    pub flags: &'static [Flag],
+    /// The words shell completion offers for the first positional argument.
+    pub choices: &'static [&'static str],
+}
+
+/// Accepted before any subcommand, and `--help` after one too.
+pub const GLOBAL_FLAGS: &[Flag] = &[
+    Flag {
+        short: Some('h'),
+        long: "help",
+        value: None,
+        help: "Show this help message",
+        choices: &[],
+    },
+    Flag {
+        short: Some('V'),
+        long: "version",
+        value: None,
+        help: "Show the version and exit",
+        choices: &[],
+    },
+    Flag {
+        short: Some('o'),
+        long: "output",
+        value: Some("FORMAT"),
+        help: "Format of the argument report: plain, table or json",
+        choices: &["plain", "table", "json"],
+    },
+    Flag {
+        short: None,
+        long: "color",
+        value: Some("WHEN"),
+        help: "Color the output: auto, always or never",
+        choices: &["auto", "always", "never"],
+    },
+];
+
+/// The program itself, as a command whose flags are the global ones.
+const PROGRAM: CommandSpec = CommandSpec {
+    name: "cli-basics",
+    about: "A CLI basics demo",
+    positionals: "[ARGUMENTS]...",
+    flags: GLOBAL_FLAGS,
+    choices: &[],
+};
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "echo",
+        about: "Print the words back",
+        positionals: "[WORDS]...",
+        flags: &[
+            Flag {
+                short: Some('u'),
+                long: "upper",
+                value: None,
+                help: "Print in upper case",
+                choices: &[],
+            },
+            Flag {
+                short: Some('n'),
+                long: "repeat",
+                value: Some("N"),
+                help: "Print the line N times (default 1)",
+                choices: &[],
+            },
+            Flag {
+                short: Some('s'),
+                long: "separator",
+                value: Some("SEP"),
+                help: "Put SEP between the words (default a space)",
+                choices: &[],
+            },
+        ],
+        choices: &[],
+    },
+    CommandSpec {
+        name: "count",
+        about: "Count the words given",
+        positionals: "[WORDS]...",
+        flags: &[
+            Flag {
+                short: Some('c'),
+                long: "chars",
+                value: None,
+                help: "Count the characters too",
+                choices: &[],
+            },
+            Flag {
+                short: Some('m'),
+                long: "min-length",
+                value: Some("LEN"),
+                help: "Only count words of at least LEN characters",
+                choices: &[],
+            },
+        ],
+        choices: &[],
+    },
+    CommandSpec {
+        name: "env",
+        about: "Show environment variables, with secrets hidden",
+        positionals: "[NAMES]...",
+        flags: &[
+            Flag {
+                short: Some('f'),
+                long: "filter",
+                value: Some("PREFIX"),
+                help: "Only the variables whose name starts with PREFIX",
+                choices: &[],
+            },
+            Flag {
+                short: Some('s'),
+                long: "sort",
+                value: None,
+                help: "Sort the variables by name",
+                choices: &[],
+            },
+        ],
+        choices: &[],
+    },
+    CommandSpec {
+        name: "config",
+        about: "Show the settings, or change the config file",
+        positionals: "show | set KEY VALUE",
+        flags: &[],
+        choices: &["show", "set"],
+    },
+    CommandSpec {
+        name: "completions",
+        about: "Print a completion script for bash, zsh or fish",
+        positionals: "SHELL",
+        flags: &[],
+        choices: &["bash", "zsh", "fish"],
+    },
+];
+
+/// A parsed command line.
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub globals: Globals,
+    pub command: Command,
+}
+
+/// The global flags; `None` when not given on the command line.
+#[derive(Debug, Default, PartialEq)]
+pub struct Globals {
+    pub output: Option<OutputFormat>,
+    pub color: Option<ColorChoice>,
+}
+
+/// What the command line asks for.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// No subcommand: report the raw arguments (the original demo). `args` starts
+    /// with the program name, like `std::env::args()`.
+    Report {
+        args: Vec<String>,
+    },
+    /// `-h`, or `<command> --help`: help for the program, or for one command.
+    Help(Option<&'static str>),
+    Version,
+    Echo {
+        words: Vec<String>,
+        upper: bool,
+        repeat: u32,
+        separator: String,
+    },
+    Count {
+        words: Vec<String>,
+        chars: bool,
+        min_length: usize,
+    },
+    Env {
+        names: Vec<String>,
+        filter: Option<String>,
+        sort: bool,
+    },
+    /// `config show`: every setting and where it came from.
+    ConfigShow,
+    /// `config set KEY VALUE`: store a default in the config file.
+    ConfigSet {
+        key: String,
+        value: String,
+    },
+    /// `completions SHELL`: the completion script for that shell.
+    Completions(Shell),
+}
+
+/// Why a command line was rejected.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    UnknownFlag {
+        command: &'static str,
+        flag: String,
+    },
+    MissingValue {
+        flag: String,
+    },
+    /// A value that does not convert; `flag` is the flag, or the positional's
+    /// name in the usage line (`SHELL`).
+    InvalidValue {
+        flag: String,
+        value: String,
+        reason: String,
+    },
+    /// An on/off flag given a value, as in `--upper=yes`.
+    UnexpectedValue {
+        flag: String,
+    },
+    /// Positional arguments that do not fit the command, as in `config set output`.
+    WrongArguments {
+        command: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnknownFlag { command, flag } => {
+                write!(f, "unknown flag '{}' for '{}'", flag, command)
+            }
+            ParseError::MissingValue { flag } => write!(f, "'{}' needs a value", flag),
+            ParseError::InvalidValue {
+                flag,
+                value,
+                reason,
+            } => write!(f, "invalid value '{}' for '{}': {}", value, flag, reason),
+            ParseError::UnexpectedValue { flag } => write!(f, "'{}' does not take a value", flag),
+            ParseError::WrongArguments { command, expected } => {
+                write!(f, "'{}' expects {}", command, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the full command line, program name included (`args[0]`).
+pub fn parse(args: &[String]) -> Result<Cli, ParseError> {
+    let program = args.first().cloned().unwrap_or_default();
+    let mut rest = args.get(1..).unwrap_or_default().iter();
+    let mut globals = Globals::default();
+
+    // The global flags end at the first argument that is not one of them.
+    while let Some(found) = rest
+        .as_slice()
+        .first()
+        .and_then(|arg| find_flag(&PROGRAM, arg).ok().flatten())
+    {
+        rest.next();
+        let long = found.flag.long;
+        let value = found.value(&mut rest)?.unwrap_or_default();
+        let command = match long {
+            "help" => Command::Help(None),
+            "version" => Command::Version,
+            "output" => {
+                globals.output = Some(convert(long, &value)?);
+                continue;
+            }
+            _ => {
+                globals.color = Some(convert(long, &value)?);
+                continue;
+            }
+        };
+        return Ok(Cli { globals, command });
+    }
+
+    let command = parse_command(program, rest.as_slice())?;
+    Ok(Cli { globals, command })
+}
+
+/// Parses what follows the global flags: a subcommand, or else arguments to report.
+fn parse_command(program: String, rest: &[String]) -> Result<Command, ParseError> {
+    let Some(spec) = rest
+        .first()
+        .and_then(|first| COMMANDS.iter().find(|spec| spec.name == *first))
+    else {
+        // Not a subcommand: any arguments at all are fine for the report.
+        let args = std::iter::once(program)
+            .chain(rest.iter().cloned())
+            .collect();
+        return Ok(Command::Report { args });
+    };
+
+    let matches = Matches::parse(spec, &rest[1..])?;
+    if matches.flag("help") {
+        return Ok(Command::Help(Some(spec.name)));
+    }
+    Ok(match spec.name {
+        "echo" => Command::Echo {
+            upper: matches.flag("upper"),
+            repeat: matches.value("repeat")?.unwrap_or(1),
+            separator: matches
+                .value("separator")?
+                .unwrap_or_else(|| " ".to_string()),
+            words: matches.positionals,
+        },
+        "count" => Command::Count {
+            chars: matches.flag("chars"),
+            min_length: matches.value("min-length")?.unwrap_or(0),
+            words: matches.positionals,
+        },
+        "env" => Command::Env {
+            filter: matches.value("filter")?,
+            sort: matches.flag("sort"),
+            names: matches.positionals,
+        },
+        "config" => match matches.positionals.as_slice() {
+            [action] if action == "show" => Command::ConfigShow,
+            [action, key, value] if action == "set" => Command::ConfigSet {
+                key: key.clone(),
+                value: value.clone(),
+            },
+            _ => {
+                return Err(ParseError::WrongArguments {
+                    command: spec.name,
+                    expected: spec.positionals,
+                })
+            }
+        },
+        "completions" => match matches.positionals.as_slice() {
+            [shell] => Command::Completions(shell.parse().map_err(|e: UnknownShell| {
+                ParseError::InvalidValue {
+                    flag: spec.positionals.to_string(),
+                    value: shell.clone(),
+                    reason: e.to_string(),
+                }
+            })?),
+            _ => {
+                return Err(ParseError::WrongArguments {
+                    command: spec.name,
+                    expected: spec.positionals,
+                })
+            }
+        },
+        name => unreachable!("no parser for the '{}' command", name),
+    })
+}
+
+/// The flags and positional arguments found after a subcommand.
+struct Matches {
+    /// Long flag name → its value (`None` for on/off flags).
+    flags: HashMap<&'static str, Option<String>>,
+    positionals: Vec<String>,
+}
+
+impl Matches {
+    fn parse(spec: &CommandSpec, args: &[String]) -> Result<Matches, ParseError> {
+        let mut matches = Matches {
+            flags: HashMap::new(),
+            positionals: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                matches.positionals.extend(args.by_ref().cloned());
+                break;
+            }
+            let Some(found) = find_flag(spec, arg)? else {
+                matches.positionals.push(arg.clone());
+                continue;
+            };
+            let long = found.flag.long;
+            let value = found.value(&mut args)?;
+            // A repeated flag: the last one wins, as in most Unix tools.
+            matches.flags.insert(long, value);
+        }
+        Ok(matches)
+    }
+
+    fn flag(&self, long: &str) -> bool {
+        self.flags.contains_key(long)
+    }
+
+    /// The value of `--<long>`, converted to `T`; `None` if the flag was not given.
+    fn value<T>(&self, long: &str) -> Result<Option<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.flags.get(long) {
+            Some(Some(value)) => convert(long, value).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Converts the value of `--<long>` to `T`.
+fn convert<T>(long: &str, value: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|e: T::Err| ParseError::InvalidValue {
+        flag: format!("--{}", long),
+        value: value.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// A flag recognized on the command line.
+struct Found {
+    /// As typed, for error messages: `-n` or `--repeat`.
+    written: String,
+    flag: &'static Flag,
+    /// A value attached to the flag itself: `--repeat=3`, `-n3`.
+    inline: Option<String>,
+}
+
+impl Found {
+    /// The flag's value: attached to it, or else the next argument. `None` for an
+    /// on/off flag.
+    fn value<'a>(
+        self,
+        args: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<Option<String>, ParseError> {
+        match (self.flag.value, self.inline) {
+            (None, None) => Ok(None),
+            (None, Some(_)) => Err(ParseError::UnexpectedValue { flag: self.written }),
+            (Some(_), Some(value)) => Ok(Some(value)),
+            (Some(_), None) => match args.next() {
+                Some(value) => Ok(Some(value.clone())),
+                None => Err(ParseError::MissingValue { flag: self.written }),
+            },
+        }
+    }
+}
+
+/// Recognizes `arg` as one of the command's flags (or `--help`); `None` for a
+/// positional argument.
+fn find_flag(spec: &CommandSpec, arg: &str) -> Result<Option<Found>, ParseError> {
+    let known = spec.flags.iter().chain(&GLOBAL_FLAGS[..1]);
+    let (written, inline, found) = if let Some(long) = arg.strip_prefix("--") {
+        let (name, inline) = match long.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (long, None),
+        };
+        let found = known.clone().find(|flag| flag.long == name);
+        (format!("--{}", name), inline, found)
+    } else if let Some(short) = arg.strip_prefix('-') {
+        // "-" alone (stdin, by convention) and negative numbers are positional.
+        let mut chars = short.chars();
+        let Some(letter) = chars.next().filter(|c| !c.is_ascii_digit()) else {
+            return Ok(None);
+        };
+        // `-n3` and `-n=3` are `-n 3`.
+        let rest = chars.as_str();
+        let rest = rest.strip_prefix('=').unwrap_or(rest);
+        let inline = (!rest.is_empty()).then(|| rest.to_string());
+        let found = known.clone().find(|flag| flag.short == Some(letter));
+        (format!("-{}", letter), inline, found)
+    } else {
+        return Ok(None);
+    };
+
+    match found {
+        Some(flag) => Ok(Some(Found {
+            written,
+            flag,
+            inline,
+        })),
+        None => Err(ParseError::UnknownFlag {
+            command: spec.name,
+            flag: written,
+        }),
+    }
+}
+
+/// The help of the whole program, or of one command.
+pub fn help(command: Option<&str>, style: Style) -> String {
+    let usage = style.heading("Usage:");
+    let options = style.heading("Options:");
+    match command.and_then(|name| COMMANDS.iter().find(|spec| spec.name == name)) {
+        Some(spec) => {
+            let mut text = format!(
+                "{}\n\n{} cli-basics {} [OPTIONS] {}\n\n{}\n",
+                spec.about, usage, spec.name, spec.positionals, options
+            );
+            text.push_str(&flag_lines(spec.flags.iter().chain(&GLOBAL_FLAGS[..1])));
+            text
+        }
+        None => {
+            let mut text = format!(
+                "{} cli-basics [OPTIONS] [arguments]\n       cli-basics [OPTIONS] <COMMAND> [COMMAND OPTIONS] [ARGS]...\n\n{}\n",
+                usage,
+                style.heading("Commands:")
+            );
+            for spec in COMMANDS {
+                text.push_str(&format!("  {:<22}{}\n", spec.name, spec.about));
+            }
+            text.push_str(&format!("\n{}\n", options));
+            text.push_str(&flag_lines(GLOBAL_FLAGS.iter()));
+            text.push_str("\nRun 'cli-basics <COMMAND> --help' for the options of a command.");
+            text
+        }
+    }
+}
+
+/// One aligned line per flag: `  -n, --repeat N      Print the line N times`.
+fn flag_lines<'a>(flags: impl Iterator<Item = &'a Flag>) -> String {
+    flags
+        .map(|flag| {
+            let short = flag
+                .short
+                .map_or("    ".to_string(), |c| format!("-{}, ", c));
+            let long = match flag.value {
+                Some(value) => format!("--{} {}", flag.long, value),
+                None => format!("--{}", flag.long),
+            };
+            format!("  {}{:<18}{}\n", short, long, flag.help)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_cli(line: &str) -> Result<Cli, ParseError> {
+        let args: Vec<String> = std::iter::once("cli-basics")
+            .chain(line.split_whitespace())
+            .map(String::from)
+            .collect();
+        parse(&args)
+    }
+
+    fn parse_line(line: &str) -> Result<Command, ParseError> {
+        parse_cli(line).map(|cli| cli.command)
+    }
+
+    fn report(args: &[&str]) -> Command {
+        Command::Report {
+            args: std::iter::once("cli-basics")
+                .chain(args.iter().copied())
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_global_flags() {
+        assert_eq!(parse_line(""), Ok(report(&[])));
+        assert_eq!(parse_line("-h"), Ok(Command::Help(None)));
+        assert_eq!(parse_line("--version"), Ok(Command::Version));
+        assert_eq!(parse_line("-o json -V"), Ok(Command::Version));
+        // Not a subcommand: the arguments are reported, whatever they look like.
+        assert_eq!(
+            parse_line("hello --weird -x"),
+            Ok(report(&["hello", "--weird", "-x"]))
+        );
+        assert_eq!(parse_line("echo --help"), Ok(Command::Help(Some("echo"))));
+        assert_eq!(parse_line("count a -h"), Ok(Command::Help(Some("count"))));
+    }
+
+    #[test]
+    fn test_output_and_color() {
+        assert_eq!(
+            parse_cli("--output table a -o b"),
+            Ok(Cli {
+                globals: Globals {
+                    output: Some(OutputFormat::Table),
+                    color: None,
+                },
+                command: report(&["a", "-o", "b"]),
+            })
+        );
+        assert_eq!(
+            parse_cli("-o=plain --color never --output=json").map(|cli| cli.globals),
+            Ok(Globals {
+                output: Some(OutputFormat::Json),
+                color: Some(ColorChoice::Never),
+            })
+        );
+        assert_eq!(
+            parse_line("--color=sometimes").unwrap_err().to_string(),
+            "invalid value 'sometimes' for '--color': expected auto, always or never"
+        );
+        assert_eq!(
+            parse_line("--output"),
+            Err(ParseError::MissingValue {
+                flag: "--output".into()
+            })
+        );
+        assert_eq!(
+            parse_line("-o xml").unwrap_err().to_string(),
+            "invalid value 'xml' for '--output': expected plain, table or json"
+        );
+    }
+
+    #[test]
+    fn test_echo_flags() {
+        assert_eq!(
+            parse_line("echo -u hello --repeat 3 world -s=, -- -n"),
+            Ok(Command::Echo {
+                words: vec!["hello".into(), "world".into(), "-n".into()],
+                upper: true,
+                repeat: 3,
+                separator: ",".into(),
+            })
+        );
+        assert_eq!(
+            parse_line("echo -n2 --repeat=4 hi"),
+            Ok(Command::Echo {
+                words: vec!["hi".into()],
+                upper: false,
+                repeat: 4,
+                separator: " ".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_count_flags() {
+        assert_eq!(
+            parse_line("count --chars -m 3 a bcd -5"),
+            Ok(Command::Count {
+                words: vec!["a".into(), "bcd".into(), "-5".into()],
+                chars: true,
+                min_length: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_env_names() {
+        assert_eq!(
+            parse_line("env HOME PATH"),
+            Ok(Command::Env {
+                names: vec!["HOME".into(), "PATH".into()],
+                filter: None,
+                sort: false,
+            })
+        );
+        assert_eq!(
+            parse_line("env --sort -f CARGO_"),
+            Ok(Command::Env {
+                names: vec![],
+                filter: Some("CARGO_".into()),
+                sort: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_actions() {
+        assert_eq!(parse_line("config show"), Ok(Command::ConfigShow));
+        assert_eq!(
+            parse_line("config set color never"),
+            Ok(Command::ConfigSet {
+                key: "color".into(),
+                value: "never".into(),
+            })
+        );
+        for line in [
+            "config",
+            "config set output",
+            "config show all",
+            "config get output",
+        ] {
+            assert_eq!(
+                parse_line(line).unwrap_err().to_string(),
+                "'config' expects show | set KEY VALUE",
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_completions_shell() {
+        assert_eq!(
+            parse_line("completions zsh"),
+            Ok(Command::Completions(Shell::Zsh))
+        );
+        assert_eq!(
+            parse_line("completions tcsh").unwrap_err().to_string(),
+            "invalid value 'tcsh' for 'SHELL': expected bash, zsh or fish"
+        );
+        assert!(matches!(
+            parse_line("completions"),
+            Err(ParseError::WrongArguments { .. })
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse_line("echo --loud"),
+            Err(ParseError::UnknownFlag {
+                command: "echo",
+                flag: "--loud".into(),
+            })
+        );
+        assert_eq!(
+            parse_line("env -x"),
+            Err(ParseError::UnknownFlag {
+                command: "env",
+                flag: "-x".into(),
+            })
+        );
+        assert_eq!(
+            parse_line("echo --repeat"),
+            Err(ParseError::MissingValue {
+                flag: "--repeat".into()
+            })
+        );
+        let err = parse_line("echo -n many").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value 'many' for '--repeat': invalid digit found in string"
+        );
+        assert_eq!(
+            parse_line("count --chars=yes"),
+            Err(ParseError::UnexpectedValue {
+                flag: "--chars".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_help_lists_commands_and_flags() {
+        let text = help(None, Style::PLAIN);
+        assert!(text.starts_with("Usage: cli-basics"));
+        for spec in COMMANDS {
+            assert!(text.contains(spec.name));
+        }
+        assert!(text.contains("  -V, --version"));
+
+        let echo = help(Some("echo"), Style::PLAIN);
+        assert!(echo.contains("Usage: cli-basics echo [OPTIONS] [WORDS]..."));
+        assert!(echo.contains("  -n, --repeat N        Print the line N times"));
+        assert!(echo.contains("  -h, --help"));
+
+        let colored = help(Some("count"), Style::COLORED);
+        assert!(colored.contains("\x1b[1mUsage:\x1b[0m cli-basics count"));
+        assert!(colored.contains("\x1b[1mOptions:\x1b[0m\n"));
+    }
+}
@@ -0,0 +1,165 @@
+//! What each subcommand prints. Every function returns the output as a string,
+//! so the tests can check it without capturing stdout.
+
+/// `echo`: the words, joined by `separator`, on `repeat` lines.
+pub fn echo(words: &[String], upper: bool, repeat: u32, separator: &str) -> String {
+    let mut line = words.join(separator);
+    if upper {
+        line = line.to_uppercase();
+    }
+    vec![line; repeat as usize].join("\n")
+}
+
+/// `count`: how many words are at least `min_length` characters long and,
+/// with `chars`, how many characters they have in total.
+pub fn count(words: &[String], chars: bool, min_length: usize) -> String {
+    // chars().count(), not len(): "héllo" is 5 characters but 6 bytes.
+    let counted: Vec<usize> = words
+        .iter()
+        .map(|word| word.chars().count())
+        .filter(|&length| length >= min_length)
+        .collect();
+    let mut output = format!("{} word(s)", counted.len());
+    if chars {
+        output.push_str(&format!(", {} character(s)", counted.iter().sum::<usize>()));
+    }
+    output
+}
+
+/// Parts of a variable name that mark its value as a secret.
+const SECRET_WORDS: &[&str] = &[
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "PASS",
+    "KEY",
+    "APIKEY",
+    "CREDENTIAL",
+    "CREDENTIALS",
+    "PRIVATE",
+];
+
+/// What is printed instead of a secret value.
+const REDACTED: &str = "********";
+
+/// `env`: the value of each of `names`, or every variable when no name is given,
+/// optionally only those starting with `filter` and sorted by name. Values that
+/// look like secrets are hidden. `vars` is the environment (`std::env::vars()`
+/// in `main`).
+pub fn env(
+    names: &[String],
+    filter: Option<&str>,
+    sort: bool,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> String {
+    let vars: Vec<(String, String)> = vars.into_iter().collect();
+    let mut lines: Vec<(&str, Option<&str>)> = if names.is_empty() {
+        vars.iter()
+            .map(|(name, value)| (name.as_str(), Some(value.as_str())))
+            .collect()
+    } else {
+        names
+            .iter()
+            .map(|name| {
+                let value = vars.iter().find(|(var, _)| var == name);
+                (name.as_str(), value.map(|(_, value)| value.as_str()))
+            })
+            .collect()
+    };
+    // Case-sensitive, like the names themselves on Unix.
+    if let Some(prefix) = filter {
+        lines.retain(|(name, _)| name.starts_with(prefix));
+    }
+    if sort {
+        lines.sort_by_key(|&(name, _)| name);
+    }
+
+    lines
+        .iter()
+        .map(|&(name, value)| match value {
+            Some(_) if is_secret(name) => format!("{}={}", name, REDACTED),
+            Some(value) => format!("{}={}", name, value),
+            None => format!("{} is not set", name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether a variable probably holds a secret: one of the `_`-separated parts of
+/// its name is a word like TOKEN or PASSWORD. Whole parts only, so `KEYBOARD`
+/// or `MONKEY` are shown while `API_KEY` and `GITHUB_TOKEN` are not.
+fn is_secret(name: &str) -> bool {
+    name.to_uppercase()
+        .split('_')
+        .any(|part| SECRET_WORDS.contains(&part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_echo() {
+        assert_eq!(echo(&words("hello world"), false, 1, " "), "hello world");
+        assert_eq!(echo(&words("a b"), true, 2, ", "), "A, B\nA, B");
+        assert_eq!(echo(&[], false, 1, " "), "");
+        assert_eq!(echo(&words("x"), false, 0, " "), "");
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(count(&words("one two three"), false, 0), "3 word(s)");
+        assert_eq!(
+            count(&words("héllo a bb"), true, 2),
+            "2 word(s), 7 character(s)"
+        );
+        assert_eq!(count(&[], true, 0), "0 word(s), 0 character(s)");
+    }
+
+    #[test]
+    fn test_env() {
+        let vars = || {
+            [
+                ("SHELL", "/bin/zsh"),
+                ("HOME", "/home/ada"),
+                ("GITHUB_TOKEN", "ghp_1234"),
+                ("HOMEBREW_PREFIX", "/opt/homebrew"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        };
+        assert_eq!(
+            env(&[], None, false, vars()),
+            "SHELL=/bin/zsh\nHOME=/home/ada\nGITHUB_TOKEN=********\nHOMEBREW_PREFIX=/opt/homebrew"
+        );
+        assert_eq!(
+            env(&words("SHELL MISSING"), None, false, vars()),
+            "SHELL=/bin/zsh\nMISSING is not set"
+        );
+        assert_eq!(
+            env(&[], Some("HOME"), true, vars()),
+            "HOME=/home/ada\nHOMEBREW_PREFIX=/opt/homebrew"
+        );
+        // A filter that matches nothing prints nothing.
+        assert_eq!(env(&[], Some("home"), false, vars()), "");
+    }
+
+    #[test]
+    fn test_secret_names() {
+        for name in [
+            "GITHUB_TOKEN",
+            "AWS_SECRET_ACCESS_KEY",
+            "db_password",
+            "API_KEY",
+        ] {
+            assert!(is_secret(name), "{}", name);
+        }
+        for name in ["PATH", "KEYBOARD_LAYOUT", "MONKEY", "PASSENGER"] {
+            assert!(!is_secret(name), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Shell completion scripts, generated from the same tables as the parser and
+//! the help, so a new command or flag completes without touching this file.
+//!
+//! Install one by saving it where the shell looks for completions:
+//!
+//! ```text
+//! cli-basics completions bash > ~/.local/share/bash-completion/completions/cli-basics
+//! cli-basics completions zsh  > ~/.zfunc/_cli-basics   # with ~/.zfunc in $fpath
+//! cli-basics completions fish > ~/.config/fish/completions/cli-basics.fish
+//! ```
+
+use crate::args::{CommandSpec, Flag, COMMANDS, GLOBAL_FLAGS};
+use std::fmt;
+use std::str::FromStr;
+
+/// The shells we can write a script for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = UnknownShell;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(UnknownShell),
+        }
+    }
+}
+
+/// `completions` was given a shell we do not support.
+#[derive(Debug)]
+pub struct UnknownShell;
+
+impl fmt::Display for UnknownShell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected bash, zsh or fish")
+    }
+}
+
+/// The completion script for `shell`.
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash(),
+        Shell::Zsh => zsh(),
+        Shell::Fish => fish(),
+    }
+}
+
+/// A command's own flags, plus `--help`, which every command accepts.
+fn flags_of(spec: &CommandSpec) -> impl Iterator<Item = &'static Flag> + Clone {
+    spec.flags.iter().chain(&GLOBAL_FLAGS[..1])
+}
+
+/// `text` as one single-quoted shell word, the same in bash, zsh and fish.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// A `complete -F` function: finds the subcommand typed so far, then offers its
+/// flags (and the choices of a flag's value right after that flag).
+fn bash() -> String {
+    let names: Vec<&str> = COMMANDS.iter().map(|spec| spec.name).collect();
+    let mut script = format!(
+        "# bash completion for cli-basics\n\
+         _cli_basics() {{\n    \
+             local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    \
+             local command= words= i\n    \
+             for ((i = 1; i < COMP_CWORD; i++)); do\n        \
+                 case \"${{COMP_WORDS[i]}}\" in\n            \
+                     {}) command=\"${{COMP_WORDS[i]}}\"; break ;;\n        \
+                 esac\n    \
+             done\n\n    \
+             case \"$command\" in\n",
+        names.join("|")
+    );
+    // The program itself is the case with no subcommand yet.
+    let global: Vec<&Flag> = GLOBAL_FLAGS.iter().collect();
+    script.push_str(&bash_case("\"\"", &global, &names));
+    for spec in COMMANDS {
+        let flags: Vec<&Flag> = flags_of(spec).collect();
+        script.push_str(&bash_case(spec.name, &flags, spec.choices));
+    }
+    script.push_str(
+        "    esac\n    \
+         COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n\
+         }\n\
+         complete -F _cli_basics cli-basics\n",
+    );
+    script
+}
+
+/// One branch of the `case "$command"`: the choices for the value of the flag
+/// just typed, if it takes one, or else the flags and `choices`.
+fn bash_case(pattern: &str, flags: &[&Flag], choices: &[&str]) -> String {
+    let mut case = format!("        {})\n", pattern);
+    let taking_values: Vec<&&Flag> = flags.iter().filter(|flag| flag.value.is_some()).collect();
+    if !taking_values.is_empty() {
+        case.push_str("            case \"$prev\" in\n");
+        for flag in taking_values {
+            let mut spellings = vec![format!("--{}", flag.long)];
+            if let Some(short) = flag.short {
+                spellings.insert(0, format!("-{}", short));
+            }
+            // No choices: the value is free text, so nothing is offered.
+            case.push_str(&format!(
+                "                {}) COMPREPLY=($(compgen -W {} -- \"$cur\")); return ;;\n",
+                spellings.join("|"),
+                quote(&flag.choices.join(" "))
+            ));
+        }
+        case.push_str("            esac\n");
+    }
+
+    let words: Vec<String> = flags
+        .iter()
+        .flat_map(|flag| {
+            flag.short
+                .map(|short| format!("-{}", short))
+                .into_iter()
+                .chain([format!("--{}", flag.long)])
+        })
+        .chain(choices.iter().map(|word| word.to_string()))
+        .collect();
+    case.push_str(&format!(
+        "            words={}\n            ;;\n",
+        quote(&words.join(" "))
+    ));
+    case
+}
+
+/// A `#compdef` function built on `_arguments`: the global flags and a command,
+/// then each command's own flags and positional arguments.
+fn zsh() -> String {
+    let mut script = String::from(
+        "#compdef cli-basics\n\n\
+         _cli_basics() {\n    \
+             local line state\n    \
+             _arguments -C \\\n",
+    );
+    for flag in GLOBAL_FLAGS {
+        script.push_str(&format!("        {} \\\n", zsh_flag(flag)));
+    }
+    script.push_str(
+        "        '1: :->command' \\\n        \
+                 '*:: :->args'\n\n    \
+         case $state in\n        \
+             command)\n            \
+                 local -a commands\n            \
+                 commands=(\n",
+    );
+    for spec in COMMANDS {
+        let entry = format!("{}:{}", spec.name, spec.about);
+        script.push_str(&format!("                {}\n", quote(&entry)));
+    }
+    script.push_str(
+        "            )\n            \
+             _describe 'command' commands\n            \
+             ;;\n        \
+         args)\n            \
+             case $line[1] in\n",
+    );
+    for spec in COMMANDS {
+        script.push_str(&format!(
+            "                {})\n                    _arguments \\\n",
+            spec.name
+        ));
+        for flag in flags_of(spec) {
+            script.push_str(&format!("                        {} \\\n", zsh_flag(flag)));
+        }
+        let positional = if spec.choices.is_empty() {
+            "'*: :'".to_string()
+        } else {
+            format!("'1: :({})' '*: :'", spec.choices.join(" "))
+        };
+        script.push_str(&format!(
+            "                        {}\n                    ;;\n",
+            positional
+        ));
+    }
+    script.push_str(
+        "            esac\n            \
+         ;;\n    \
+         esac\n\
+         }\n\n\
+         _cli_basics \"$@\"\n",
+    );
+    script
+}
+
+/// One `_arguments` spec: `'(-n --repeat)'{-n,--repeat}'[help]:N:'`. The
+/// exclusion list stops zsh from offering `--repeat` once `-n` is given.
+fn zsh_flag(flag: &Flag) -> String {
+    // Brackets would end the description early; a quote would end the word.
+    let help = flag
+        .help
+        .replace('[', r"\[")
+        .replace(']', r"\]")
+        .replace('\'', r"'\''");
+    let mut spec = match flag.short {
+        Some(short) => format!(
+            "'(-{short} --{long})'{{-{short},--{long}}}'[{help}]",
+            short = short,
+            long = flag.long,
+            help = help
+        ),
+        None => format!("'--{}[{}]", flag.long, help),
+    };
+    if let Some(value) = flag.value {
+        spec.push_str(&format!(":{}:", value));
+        if !flag.choices.is_empty() {
+            spec.push_str(&format!("({})", flag.choices.join(" ")));
+        }
+    }
+    spec.push('\'');
+    spec
+}
+
+/// One `complete` line per flag and per command, each with a condition saying
+/// where it applies.
+fn fish() -> String {
+    // Arguments are words, not files.
+    let mut script = String::from("complete -c cli-basics -f\n");
+    let top = "__fish_use_subcommand";
+    for flag in GLOBAL_FLAGS {
+        script.push_str(&fish_flag(top, flag));
+    }
+    for spec in COMMANDS {
+        script.push_str(&format!(
+            "complete -c cli-basics -n {} -a {} -d {}\n",
+            top,
+            spec.name,
+            quote(spec.about)
+        ));
+    }
+    for spec in COMMANDS {
+        let condition = quote(&format!("__fish_seen_subcommand_from {}", spec.name));
+        for flag in flags_of(spec) {
+            script.push_str(&fish_flag(&condition, flag));
+        }
+        if !spec.choices.is_empty() {
+            script.push_str(&format!(
+                "complete -c cli-basics -n {} -a {}\n",
+                condition,
+                quote(&spec.choices.join(" "))
+            ));
+        }
+    }
+    script
+}
+
+/// `complete -c cli-basics -n COND -s n -l repeat -x -d 'help'`; `-x` marks a
+/// flag that takes a value, `-a` lists the choices for it.
+fn fish_flag(condition: &str, flag: &Flag) -> String {
+    let mut line = format!("complete -c cli-basics -n {}", condition);
+    if let Some(short) = flag.short {
+        line.push_str(&format!(" -s {}", short));
+    }
+    line.push_str(&format!(" -l {}", flag.long));
+    if flag.value.is_some() {
+        line.push_str(" -x");
+        if !flag.choices.is_empty() {
+            line.push_str(&format!(" -a {}", quote(&flag.choices.join(" "))));
+        }
+    }
+    line.push_str(&format!(" -d {}\n", quote(flag.help)));
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_cover_every_command_and_flag() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = script(shell);
+            for spec in COMMANDS {
+                assert!(script.contains(spec.name), "{:?}: {}", shell, spec.name);
+                for flag in spec.flags.iter().chain(GLOBAL_FLAGS) {
+                    assert!(script.contains(flag.long), "{:?}: {}", shell, flag.long);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bash() {
+        let script = script(Shell::Bash);
+        assert!(script.contains("echo|count|env|config|completions) command="));
+        assert!(script.contains(
+            "-o|--output) COMPREPLY=($(compgen -W 'plain table json' -- \"$cur\")); return ;;"
+        ));
+        // `-s` takes a value after `echo`, but is an on/off flag after `env`.
+        assert!(script.contains("-s|--separator) COMPREPLY"));
+        assert!(!script.contains("-s|--sort)"));
+        assert!(script.ends_with("complete -F _cli_basics cli-basics\n"));
+    }
+
+    #[test]
+    fn test_zsh() {
+        let script = script(Shell::Zsh);
+        assert!(script.starts_with("#compdef cli-basics\n"));
+        assert!(script.contains(
+            "'--color[Color the output: auto, always or never]:WHEN:(auto always never)'"
+        ));
+        assert!(script.contains("'(-u --upper)'{-u,--upper}'[Print in upper case]'"));
+        assert!(script.contains("'echo:Print the words back'"));
+        assert!(script.contains("'1: :(bash zsh fish)'"));
+    }
+
+    #[test]
+    fn test_fish() {
+        let script = script(Shell::Fish);
+        assert!(script.contains(
+            "complete -c cli-basics -n '__fish_seen_subcommand_from echo' -s n -l repeat -x -d 'Print the line N times (default 1)'\n"
+        ));
+        assert!(script.contains(
+            "complete -c cli-basics -n __fish_use_subcommand -a env -d 'Show environment variables, with secrets hidden'\n"
+        ));
+        assert!(script.contains("-n '__fish_seen_subcommand_from config' -a 'show set'\n"));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+}
@@ -0,0 +1,339 @@
+//! Defaults from `~/.config/cli-basics/config.toml`.
+//!
+//! Each setting is taken from the first place that has it, in this order: the
+//! command line (`--output`, `--color`), an environment variable
+//! (`CLI_BASICS_OUTPUT`, `CLI_BASICS_COLOR`), the config file, and finally the
+//! built-in default. `cli-basics config show` prints where each one came from.
+
+use crate::args::Globals;
+use crate::output::OutputFormat;
+use crate::style::ColorChoice;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The environment variable that overrides the `output` key.
+pub const OUTPUT_VAR: &str = "CLI_BASICS_OUTPUT";
+/// The environment variable that overrides the `color` key.
+pub const COLOR_VAR: &str = "CLI_BASICS_COLOR";
+
+/// The contents of the config file. A missing key means "no preference".
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub output: Option<OutputFormat>,
+    pub color: Option<ColorChoice>,
+}
+
+/// `$XDG_CONFIG_HOME/cli-basics/config.toml`, or `~/.config/cli-basics/config.toml`
+/// when that is not set; `None` if there is no home directory either.
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        // The XDG spec says to ignore a relative path.
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::home_dir().map(|home| home.join(".config")))?;
+    Some(base.join("cli-basics").join("config.toml"))
+}
+
+impl Config {
+    /// Reads the file at `path`. A file that does not exist is an empty config:
+    /// nobody has to create one.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+        toml::from_str(&text).map_err(|e| ConfigError::Syntax(path.to_path_buf(), e))
+    }
+
+    /// Writes the config to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let text = toml::to_string(self).expect("a config always serializes");
+        let io_error = |e| ConfigError::Io(path.to_path_buf(), e);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        fs::write(path, text).map_err(io_error)
+    }
+
+    /// `config set KEY VALUE`: checks the value before storing it, so a typo
+    /// never ends up in the file.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        match key {
+            "output" => self.output = Some(convert(key, value)?),
+            "color" => self.color = Some(convert(key, value)?),
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Where the value of a setting came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Default,
+    File,
+    /// The name of the environment variable.
+    Env(&'static str),
+    /// The long name of the flag.
+    Flag(&'static str),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File => write!(f, "config file"),
+            Source::Env(name) => write!(f, "environment variable {}", name),
+            Source::Flag(long) => write!(f, "--{} flag", long),
+        }
+    }
+}
+
+/// A setting's value and where it came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+/// Every setting, with all the layers applied.
+#[derive(Debug, PartialEq)]
+pub struct Settings {
+    pub output: Setting<OutputFormat>,
+    pub color: Setting<ColorChoice>,
+}
+
+/// Stacks the layers: the file over the defaults, `vars` (the environment) over
+/// the file, and the command line over everything.
+pub fn resolve(
+    file: &Config,
+    vars: &[(String, String)],
+    globals: &Globals,
+) -> Result<Settings, ConfigError> {
+    Ok(Settings {
+        output: layer(
+            OutputFormat::Plain,
+            file.output,
+            (OUTPUT_VAR, vars),
+            ("output", globals.output),
+        )?,
+        color: layer(
+            ColorChoice::Auto,
+            file.color,
+            (COLOR_VAR, vars),
+            ("color", globals.color),
+        )?,
+    })
+}
+
+/// One setting through every layer, from the highest precedence down.
+fn layer<T>(
+    default: T,
+    file: Option<T>,
+    (var, vars): (&'static str, &[(String, String)]),
+    (long, flag): (&'static str, Option<T>),
+) -> Result<Setting<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if let Some(value) = flag {
+        return Ok(Setting {
+            value,
+            source: Source::Flag(long),
+        });
+    }
+    // An empty variable counts as unset, as with NO_COLOR.
+    if let Some((_, value)) = vars
+        .iter()
+        .find(|(name, value)| name == var && !value.is_empty())
+    {
+        return Ok(Setting {
+            value: convert(var, value)?,
+            source: Source::Env(var),
+        });
+    }
+    Ok(match file {
+        Some(value) => Setting {
+            value,
+            source: Source::File,
+        },
+        None => Setting {
+            value: default,
+            source: Source::Default,
+        },
+    })
+}
+
+/// Converts the value of a key or environment variable called `name`.
+fn convert<T>(name: &str, value: &str) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e: T::Err| ConfigError::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// `config show`: the file's path, then each setting, its value and its source.
+pub fn show(path: Option<&Path>, settings: &Settings) -> String {
+    let path = match path {
+        Some(path) if path.exists() => path.display().to_string(),
+        Some(path) => format!("{} (not created yet)", path.display()),
+        None => "none (no home directory)".to_string(),
+    };
+    format!(
+        "Config file: {}\noutput = {:<6}  ({})\ncolor  = {:<6}  ({})",
+        path,
+        settings.output.value,
+        settings.output.source,
+        settings.color.value,
+        settings.color.source
+    )
+}
+
+/// Why the settings could not be read or changed.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    /// The file is not valid TOML, or has a key or value we do not know.
+    Syntax(PathBuf, toml::de::Error),
+    UnknownKey(String),
+    /// A bad value for a key or an environment variable, named by `name`.
+    InvalidValue {
+        name: String,
+        value: String,
+        reason: String,
+    },
+    /// `config set` with no home directory to put the file in.
+    NoPath,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            // toml's message spans several lines and points at the mistake.
+            ConfigError::Syntax(path, e) => write!(f, "{}: {}", path.display(), e),
+            ConfigError::UnknownKey(key) => {
+                write!(f, "unknown config key '{}': expected output or color", key)
+            }
+            ConfigError::InvalidValue {
+                name,
+                value,
+                reason,
+            } => write!(f, "invalid value '{}' for '{}': {}", value, name, reason),
+            ConfigError::NoPath => write!(f, "no home directory to keep the config file in"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_precedence() {
+        let file = Config {
+            output: Some(OutputFormat::Table),
+            color: Some(ColorChoice::Never),
+        };
+        let none = Globals::default();
+
+        let settings = resolve(&Config::default(), &[], &none).unwrap();
+        assert_eq!(settings.output.value, OutputFormat::Plain);
+        assert_eq!(settings.color.source, Source::Default);
+
+        let settings = resolve(&file, &[], &none).unwrap();
+        assert_eq!(
+            settings.output,
+            Setting {
+                value: OutputFormat::Table,
+                source: Source::File
+            }
+        );
+
+        let env = vars(&[(OUTPUT_VAR, "json"), (COLOR_VAR, "")]);
+        let settings = resolve(&file, &env, &none).unwrap();
+        assert_eq!(settings.output.source, Source::Env(OUTPUT_VAR));
+        assert_eq!(settings.output.value, OutputFormat::Json);
+        // The empty variable is ignored.
+        assert_eq!(settings.color.source, Source::File);
+
+        let flags = Globals {
+            output: Some(OutputFormat::Plain),
+            color: None,
+        };
+        let settings = resolve(&file, &env, &flags).unwrap();
+        assert_eq!(settings.output.source, Source::Flag("output"));
+        assert_eq!(settings.output.value, OutputFormat::Plain);
+    }
+
+    #[test]
+    fn test_bad_env_value() {
+        let env = vars(&[(COLOR_VAR, "rainbow")]);
+        let err = resolve(&Config::default(), &env, &Globals::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value 'rainbow' for 'CLI_BASICS_COLOR': expected auto, always or never"
+        );
+    }
+
+    #[test]
+    fn test_set_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cli-basics").join("config.toml");
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        let mut config = Config::default();
+        config.set("output", "json").unwrap();
+        config.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "output = \"json\"\n");
+        assert_eq!(
+            Config::load(&path).unwrap(),
+            Config {
+                output: Some(OutputFormat::Json),
+                color: None,
+            }
+        );
+
+        assert!(matches!(
+            config.set("colour", "never"),
+            Err(ConfigError::UnknownKey(_))
+        ));
+        assert!(config.set("color", "sometimes").is_err());
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "output = \"xml\"\n").unwrap();
+        assert!(matches!(
+            Config::load(&path),
+            Err(ConfigError::Syntax(_, _))
+        ));
+        fs::write(&path, "verbose = true\n").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Everything that can make the program fail, and the exit code for each, so
+//! scripts can tell failures apart with `$?` instead of parsing the message.
+//!
+//! | Code | Meaning                                                 |
+//! |------|---------------------------------------------------------|
+//! | 0    | Success                                                 |
+//! | 2    | Usage error: the command line is wrong                  |
+//! | 3    | I/O error: reading stdin, or reading/writing the config |
+//! | 4    | Bad settings: the config file or a `CLI_BASICS_*` value |
+//!
+//! 2 for usage errors is the convention of most Unix tools (`grep`, `ls`);
+//! 1 is left out, since many programs use it for any failure at all.
+
+use crate::args::ParseError;
+use crate::config::ConfigError;
+use std::fmt;
+use std::io;
+
+/// Why `run` failed.
+#[derive(Debug)]
+pub enum CliError {
+    Usage(ParseError),
+    Stdin(io::Error),
+    Config(ConfigError),
+}
+
+impl CliError {
+    /// The code the process exits with; see the table above.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Stdin(_) | CliError::Config(ConfigError::Io(..)) => 3,
+            CliError::Config(_) => 4,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(e) => write!(f, "{}\n\nRun 'cli-basics --help' for usage.", e),
+            CliError::Stdin(e) => write!(f, "could not read stdin: {}", e),
+            CliError::Config(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CliError::Usage(e) => Some(e),
+            CliError::Stdin(e) => Some(e),
+            CliError::Config(e) => Some(e),
+        }
+    }
+}
+
+impl From<ParseError> for CliError {
+    fn from(e: ParseError) -> Self {
+        CliError::Usage(e)
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(e: ConfigError) -> Self {
+        CliError::Config(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_exit_codes() {
+        let usage = CliError::from(ParseError::MissingValue {
+            flag: "--output".into(),
+        });
+        assert_eq!(usage.exit_code(), 2);
+        assert!(usage
+            .to_string()
+            .ends_with("Run 'cli-basics --help' for usage."));
+
+        let stdin = CliError::Stdin(io::Error::other("broken pipe"));
+        assert_eq!(stdin.exit_code(), 3);
+        assert_eq!(stdin.to_string(), "could not read stdin: broken pipe");
+
+        let unwritable = ConfigError::Io(PathBuf::from("/x"), io::Error::other("denied"));
+        assert_eq!(CliError::from(unwritable).exit_code(), 3);
+        assert_eq!(CliError::from(ConfigError::NoPath).exit_code(), 4);
+    }
+}
@@ -0,0 +1,57 @@
+//! Piped input: `ls | cli-basics` counts what arrives on stdin, like `wc`.
+//!
+//! `main` only reads stdin when it is not a terminal, so running the demo by
+//! hand never waits for input that is not coming.
+
+use serde::Serialize;
+use std::io::{self, BufRead};
+
+/// Lines, words and bytes read from the input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct InputStats {
+    pub lines: usize,
+    pub words: usize,
+    pub bytes: usize,
+}
+
+impl InputStats {
+    /// Reads `input` to the end, one line at a time, so a large pipe is never
+    /// held in memory at once.
+    pub fn read(mut input: impl BufRead) -> io::Result<Self> {
+        let mut stats = InputStats::default();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            // Bytes rather than a String: the input does not have to be valid UTF-8.
+            let read = input.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                return Ok(stats);
+            }
+            stats.lines += 1;
+            stats.bytes += read;
+            stats.words += String::from_utf8_lossy(&line).split_whitespace().count();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let stats = InputStats::read("one two\n\nthree  four five\nlast".as_bytes()).unwrap();
+        assert_eq!(
+            stats,
+            InputStats {
+                lines: 4,
+                words: 6,
+                bytes: 30,
+            }
+        );
+        assert_eq!(InputStats::read(&b""[..]).unwrap(), InputStats::default());
+        // Invalid UTF-8 is still counted.
+        let stats = InputStats::read(&b"caf\xe9 ok\n"[..]).unwrap();
+        assert_eq!((stats.lines, stats.words, stats.bytes), (1, 2, 8));
+    }
+}
@@ -1,40 +1,128 @@
+use args::Command;
+use config::{Config, ConfigError};
+use error::CliError;
+use input::InputStats;
+use output::Report;
 use std::env;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::PathBuf;
+use std::process;
+use style::Style;
+
+mod args;
+mod commands;
+mod completions;
+mod config;
+mod error;
+mod input;
+mod output;
+mod style;
+
+/// What `run` needs from the outside world, so tests can supply their own.
+#[derive(Default)]
+struct Context<'a> {
+    /// The piped input, if stdin is not a terminal: the report counts it too.
+    stdin: Option<&'a mut dyn BufRead>,
+    /// Whether stdout is a terminal, for `--color auto`.
+    stdout_is_terminal: bool,
+    /// The environment: `NO_COLOR`, the `CLI_BASICS_*` overrides, and what
+    /// `env` prints.
+    vars: Vec<(String, String)>,
+    /// Where the config file is (or would be); `None` without a home directory.
+    config_path: Option<PathBuf>,
+}
+
+impl Context<'_> {
+    /// The value of the environment variable `name`, if set.
+    fn var(&self, name: &str) -> Option<&str> {
+        self.vars
+            .iter()
+            .find(|(var, _)| var == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
 
 /// The core logic of the CLI application.
 ///
 /// This function takes a slice of strings (arguments) and returns a formatted
-/// string representing the output to be displayed to the user.
-/// It handles help flags and counts/lists provided arguments.
-fn run(args: &[String]) -> String {
-    // Check if the user specifically asked for help using the -h flag.
-    if args.len() > 1 && args[1] == "-h" {
-        return String::from(
-            "Usage: cli-basics [arguments]\nOptions:\n  -h    Show this help message",
-        );
-    }
-
-    let mut output = String::new();
-    output.push_str(&format!("Received {} arguments.\n", args.len()));
-
-    // args[0] is always the name of the executable itself.
-    if args.len() > 1 {
-        output.push_str("Arguments exceeded 1. Here they are:");
-        for (i, arg) in args.iter().enumerate() {
-            output.push_str(&format!("\n{}: {}", i, arg));
+/// string representing the output to be displayed to the user, or the error
+/// that stopped it; `main` turns the error into an exit code.
+/// The parser in `args` decides what was asked for: help, the version, one of
+/// the subcommands, or (without a subcommand) the report of the arguments.
+fn run(args: &[String], context: Context) -> Result<String, CliError> {
+    let cli = args::parse(args)?;
+    let path = context.config_path.as_deref();
+    let file = path.map(Config::load).transpose()?.unwrap_or_default();
+    let settings = config::resolve(&file, &context.vars, &cli.globals)?;
+    let style = Style::resolve(
+        settings.color.value,
+        context.var("NO_COLOR"),
+        context.stdout_is_terminal,
+    );
+    Ok(match cli.command {
+        Command::Report { args } => {
+            let input = context
+                .stdin
+                .map(InputStats::read)
+                .transpose()
+                .map_err(CliError::Stdin)?;
+            settings.output.value.formatter().format(&Report {
+                args: &args,
+                input,
+                style,
+            })
         }
-    } else {
-        output.push_str("No extra arguments provided. Try running with: cargo run -- args go here");
-    }
-    output
+        Command::Help(command) => args::help(command, style),
+        Command::Version => format!("cli-basics {}", args::VERSION),
+        Command::Echo {
+            words,
+            upper,
+            repeat,
+            separator,
+        } => commands::echo(&words, upper, repeat, &separator),
+        Command::Count {
+            words,
+            chars,
+            min_length,
+        } => commands::count(&words, chars, min_length),
+        Command::Env {
+            names,
+            filter,
+            sort,
+        } => commands::env(&names, filter.as_deref(), sort, context.vars),
+        Command::Completions(shell) => completions::script(shell),
+        Command::ConfigShow => config::show(path, &settings),
+        Command::ConfigSet { key, value } => {
+            let path = path.ok_or(ConfigError::NoPath)?;
+            let mut file = file;
+            file.set(&key, &value)?;
+            file.save(path)?;
+            format!("Set {} = {} in {}", key, value, path.display())
+        }
+    })
 }
 
 /// The entry point of the application.
 /// It collects arguments from the environment and prints the result of the `run` logic.
 fn main() {
-    println!("Hello! This is a CLI basics demo.");
     // env::args() returns an iterator of the arguments passed to the program.
     let args: Vec<String> = env::args().collect();
-    println!("{}", run(&args));
+    // Only piped input is read: on a terminal, the user is not typing any.
+    let stdin = io::stdin();
+    let mut piped = (!stdin.is_terminal()).then(|| stdin.lock());
+    let context = Context {
+        stdin: piped.as_mut().map(|lock| lock as &mut dyn BufRead),
+        stdout_is_terminal: io::stdout().is_terminal(),
+        vars: env::vars().collect(),
+        config_path: config::default_path(),
+    };
+    match run(&args, context) {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(e.exit_code());
+        }
+    }
 }
 
 #[cfg(test)]
@@ -44,7 +132,7 @@ mod tests {
     #[test]
     fn test_help_flag() {
         let args = vec![String::from("program"), String::from("-h")];
-        let output = run(&args);
+        let output = run(&args, Context::default()).unwrap();
         assert!(output.contains("Usage: cli-basics"));
         assert!(output.contains("Show this help message"));
     }
@@ -52,7 +140,7 @@ mod tests {
     #[test]
     fn test_no_extra_args() {
         let args = vec![String::from("program")];
-        let output = run(&args);
+        let output = run(&args, Context::default()).unwrap();
         assert!(output.contains("Received 1 arguments."));
         assert!(output.contains("No extra arguments provided."));
     }
@@ -64,9 +152,157 @@ mod tests {
             String::from("arg1"),
             String::from("arg2"),
         ];
-        let output = run(&args);
+        let output = run(&args, Context::default()).unwrap();
         assert!(output.contains("Received 3 arguments."));
         assert!(output.contains("1: arg1"));
         assert!(output.contains("2: arg2"));
     }
+
+    #[test]
+    fn test_subcommands_and_errors() {
+        let args = |line: &str| -> Vec<String> {
+            std::iter::once("program")
+                .chain(line.split_whitespace())
+                .map(String::from)
+                .collect()
+        };
+        assert_eq!(run(&args("echo -u hi"), Context::default()).unwrap(), "HI");
+        assert_eq!(
+            run(&args("count a bb"), Context::default()).unwrap(),
+            "2 word(s)"
+        );
+        assert_eq!(
+            run(&args("--version"), Context::default()).unwrap(),
+            format!("cli-basics {}", args::VERSION)
+        );
+        assert!(run(&args("echo --help"), Context::default())
+            .unwrap()
+            .contains("Usage: cli-basics echo"));
+        assert!(run(&args("-o table x"), Context::default())
+            .unwrap()
+            .starts_with("Index  Argument"));
+        let err = run(&args("count --frobnicate"), Context::default()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("unknown flag '--frobnicate' for 'count'"));
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let args: Vec<String> = ["program", "x"].map(String::from).to_vec();
+        let terminal = || Context {
+            stdout_is_terminal: true,
+            ..Context::default()
+        };
+        assert!(run(&args, terminal())
+            .unwrap()
+            .contains("\x1b[36m1\x1b[0m: x"));
+        assert!(!run(&args, Context::default()).unwrap().contains('\x1b'));
+        let no_color = Context {
+            vars: vec![("NO_COLOR".to_string(), "1".to_string())],
+            ..terminal()
+        };
+        assert!(!run(&args, no_color).unwrap().contains('\x1b'));
+
+        let args: Vec<String> = ["program", "--color", "always", "x"]
+            .map(String::from)
+            .to_vec();
+        assert!(run(&args, Context::default())
+            .unwrap()
+            .contains("\x1b[36m1\x1b[0m: x"));
+    }
+
+    #[test]
+    fn test_config_file_and_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cli-basics").join("config.toml");
+        let context = |vars: &[(&str, &str)]| Context {
+            vars: vars
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            config_path: Some(path.clone()),
+            ..Context::default()
+        };
+        let args = |line: &str| -> Vec<String> {
+            std::iter::once("program")
+                .chain(line.split_whitespace())
+                .map(String::from)
+                .collect()
+        };
+
+        assert!(run(&args("config show"), context(&[]))
+            .unwrap()
+            .contains("output = plain   (default)"));
+        assert_eq!(
+            run(&args("config set output table"), context(&[])).unwrap(),
+            format!("Set output = table in {}", path.display())
+        );
+        assert!(run(&args("x"), context(&[]))
+            .unwrap()
+            .starts_with("Index  Argument"));
+        // The environment beats the file, and a flag beats both.
+        let json = [("CLI_BASICS_OUTPUT", "json")];
+        assert!(run(&args("x"), context(&json)).unwrap().starts_with('{'));
+        assert!(run(&args("-o plain x"), context(&json))
+            .unwrap()
+            .starts_with("Hello!"));
+        let shown = run(&args("config show"), context(&json)).unwrap();
+        assert!(shown.contains("output = json    (environment variable CLI_BASICS_OUTPUT)"));
+        assert!(shown.contains("color  = auto    (default)"));
+
+        let err = run(&args("config set output xml"), context(&[])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value 'xml' for 'output': expected plain, table or json"
+        );
+        assert_eq!(err.exit_code(), 4);
+        let err = run(&args("x"), context(&[("CLI_BASICS_COLOR", "rainbow")])).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("invalid value 'rainbow' for 'CLI_BASICS_COLOR'"));
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_unreadable_stdin() {
+        struct Broken;
+        impl io::Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("device gone"))
+            }
+        }
+        let mut broken = io::BufReader::new(Broken);
+        let context = Context {
+            stdin: Some(&mut broken),
+            ..Context::default()
+        };
+        let err = run(&[String::from("program")], context).unwrap_err();
+        assert_eq!(err.to_string(), "could not read stdin: device gone");
+        assert_eq!(err.exit_code(), 3);
+    }
+
+    #[test]
+    fn test_piped_input_is_counted() {
+        let args = vec![String::from("program"), String::from("x")];
+        let mut piped = "hello world\nbye\n".as_bytes();
+        let context = Context {
+            stdin: Some(&mut piped),
+            ..Context::default()
+        };
+        let output = run(&args, context).unwrap();
+        assert!(output.contains("1: x"));
+        assert!(output.ends_with("Read from stdin: 2 line(s), 3 word(s), 16 byte(s)."));
+
+        // Subcommands leave stdin alone.
+        let args: Vec<String> = ["program", "echo", "hi"].map(String::from).to_vec();
+        let mut piped = "unread\n".as_bytes();
+        let context = Context {
+            stdin: Some(&mut piped),
+            ..Context::default()
+        };
+        assert_eq!(run(&args, context).unwrap(), "hi");
+        assert_eq!(piped, b"unread\n");
+    }
 }
@@ -0,0 +1,300 @@
+//! The formats of the argument report, picked with `--output plain|table|json`.
+//!
+//! Each format is a `Formatter`. Adding one means a new type implementing the
+//! trait, plus a variant of `OutputFormat` to select it from the command line.
+
+use crate::input::InputStats;
+use crate::style::Style;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// What the report is about.
+pub struct Report<'a> {
+    /// Starts with the program name, like `std::env::args()`.
+    pub args: &'a [String],
+    /// Counts of the piped input, if stdin was not a terminal.
+    pub input: Option<InputStats>,
+    /// Colors for the plain and table formats; JSON is never colored.
+    pub style: Style,
+}
+
+/// A way to print the argument report.
+pub trait Formatter {
+    fn format(&self, report: &Report) -> String;
+}
+
+/// The `--output` choices; also the `output` key of the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Plain,
+    Table,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn formatter(self) -> Box<dyn Formatter> {
+        match self {
+            OutputFormat::Plain => Box::new(Plain),
+            OutputFormat::Table => Box::new(Table),
+            OutputFormat::Json => Box::new(Json),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "plain" => Ok(OutputFormat::Plain),
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(UnknownFormat),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            OutputFormat::Plain => "plain",
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+        })
+    }
+}
+
+/// `--output` was given a format that does not exist.
+#[derive(Debug)]
+pub struct UnknownFormat;
+
+impl fmt::Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected plain, table or json")
+    }
+}
+
+/// The original, human-friendly report.
+pub struct Plain;
+
+impl Formatter for Plain {
+    fn format(&self, report: &Report) -> String {
+        let (args, style) = (report.args, report.style);
+        let mut output = String::from("Hello! This is a CLI basics demo.\n");
+        output.push_str(&style.heading(&format!("Received {} arguments.", args.len())));
+        output.push('\n');
+
+        // args[0] is always the name of the executable itself.
+        if args.len() > 1 {
+            output.push_str("Arguments exceeded 1. Here they are:");
+            for (i, arg) in args.iter().enumerate() {
+                output.push_str(&format!("\n{}: {}", style.index(&i.to_string()), arg));
+            }
+        } else {
+            output.push_str(
+                "No extra arguments provided. Try running with: cargo run -- args go here",
+            );
+        }
+        if let Some(input) = report.input {
+            output.push_str(&format!(
+                "\nRead from stdin: {} line(s), {} word(s), {} byte(s).",
+                input.lines, input.words, input.bytes
+            ));
+        }
+        output
+    }
+}
+
+/// One row per argument, with the columns aligned, and a second table for the
+/// piped input.
+pub struct Table;
+
+impl Formatter for Table {
+    fn format(&self, report: &Report) -> String {
+        let rows: Vec<Vec<String>> = report
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| vec![i.to_string(), arg.clone(), arg.chars().count().to_string()])
+            .collect();
+        let mut output = aligned(&["Index", "Argument", "Length"], &rows, report.style);
+
+        if let Some(input) = report.input {
+            let row = [input.lines, input.words, input.bytes].map(|n| n.to_string());
+            output.push_str("\n\n");
+            output.push_str(&aligned(
+                &["Lines", "Words", "Bytes"],
+                &[row.to_vec()],
+                report.style,
+            ));
+        }
+        output
+    }
+}
+
+/// A table with a header line. Each column is as wide as its widest cell, header
+/// included; the last column holds numbers, so it is right-aligned. Colors are
+/// added after padding: escape codes take no room on screen.
+fn aligned(headers: &[&str], rows: &[Vec<String>], style: Style) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[&str]| {
+        let last = cells.len() - 1;
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, &width))| {
+                if i == last {
+                    format!("{:>width$}", cell)
+                } else {
+                    format!("{:<width$}", cell)
+                }
+            })
+            .collect();
+        cells.join("  ")
+    };
+
+    let mut table = vec![style.heading(&line(headers))];
+    table.extend(rows.iter().map(|row| {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        line(&cells)
+    }));
+    table.join("\n")
+}
+
+/// A JSON object for scripts: `{"program": ..., "args": [...], "counts": {...}}`.
+pub struct Json;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    program: &'a str,
+    args: &'a [String],
+    counts: Counts,
+    /// Left out entirely when nothing was piped in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdin: Option<InputStats>,
+}
+
+#[derive(Serialize)]
+struct Counts {
+    /// Arguments after the program name.
+    args: usize,
+    /// Characters in those arguments.
+    chars: usize,
+}
+
+impl Formatter for Json {
+    fn format(&self, report: &Report) -> String {
+        let (program, rest) = report
+            .args
+            .split_first()
+            .map_or(("", &[][..]), |(program, rest)| (program.as_str(), rest));
+        let report = JsonReport {
+            program,
+            args: rest,
+            counts: Counts {
+                args: rest.len(),
+                chars: rest.iter().map(|arg| arg.chars().count()).sum(),
+            },
+            stdin: report.input,
+        };
+        serde_json::to_string_pretty(&report).expect("a report always serializes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn format(formatter: impl Formatter, args: &[String], input: Option<InputStats>) -> String {
+        formatter.format(&Report {
+            args,
+            input,
+            style: Style::PLAIN,
+        })
+    }
+
+    const PIPED: InputStats = InputStats {
+        lines: 2,
+        words: 5,
+        bytes: 24,
+    };
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            "table".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Table
+        );
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let table = format(Table, &args("prog a longer"), None);
+        assert_eq!(
+            table,
+            "Index  Argument  Length\n\
+             0      prog           4\n\
+             1      a              1\n\
+             2      longer         6"
+        );
+
+        let table = format(Table, &args("prog"), Some(PIPED));
+        assert!(table.ends_with("\n\nLines  Words  Bytes\n2      5         24"));
+    }
+
+    #[test]
+    fn test_colors() {
+        let report = Report {
+            args: &args("prog a"),
+            input: None,
+            style: Style::COLORED,
+        };
+        let plain = Plain.format(&report);
+        assert!(plain.contains("\x1b[1mReceived 2 arguments.\x1b[0m\n"));
+        assert!(plain.ends_with("\n\x1b[36m1\x1b[0m: a"));
+        let table = Table.format(&report);
+        assert!(table.starts_with("\x1b[1mIndex  Argument  Length\x1b[0m\n0      prog"));
+        assert!(!Json.format(&report).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_plain_mentions_stdin_only_when_piped() {
+        assert!(!format(Plain, &args("prog a"), None).contains("stdin"));
+        assert!(format(Plain, &args("prog a"), Some(PIPED))
+            .ends_with("\nRead from stdin: 2 line(s), 5 word(s), 24 byte(s)."));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let json = format(Json, &args("prog héllo x"), None);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "program": "prog",
+                "args": ["héllo", "x"],
+                "counts": { "args": 2, "chars": 6 }
+            })
+        );
+
+        let json = format(Json, &args("prog"), Some(PIPED));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["stdin"],
+            serde_json::json!({ "lines": 2, "words": 5, "bytes": 24 })
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! ANSI colors for headings and argument indices.
+//!
+//! Colors are on when stdout is a terminal and `NO_COLOR` is not set
+//! (<https://no-color.org>), unless `--color always|never` says otherwise.
+//! Everything that prints takes a `Style`, so tests can ask for either output.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The `--color` choices; also the `color` key of the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = UnknownChoice;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(UnknownChoice),
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // pad(), not write!(): `config show` lines the values up with `{:<6}`.
+        f.pad(match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        })
+    }
+}
+
+/// `--color` was given something other than auto, always or never.
+#[derive(Debug)]
+pub struct UnknownChoice;
+
+impl fmt::Display for UnknownChoice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected auto, always or never")
+    }
+}
+
+/// Whether to color the output, decided once in `main`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    colored: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style { colored: false };
+    pub const COLORED: Style = Style { colored: true };
+
+    /// `--color` wins; with `auto`, a set and non-empty `NO_COLOR` turns colors
+    /// off, and so does output that is not a terminal (a pipe or a file).
+    pub fn resolve(choice: ColorChoice, no_color: Option<&str>, is_terminal: bool) -> Style {
+        match choice {
+            ColorChoice::Always => Style::COLORED,
+            ColorChoice::Never => Style::PLAIN,
+            ColorChoice::Auto if is_terminal && no_color.is_none_or(str::is_empty) => {
+                Style::COLORED
+            }
+            ColorChoice::Auto => Style::PLAIN,
+        }
+    }
+
+    /// Bold, for headings.
+    pub fn heading(self, text: &str) -> String {
+        self.paint("1", text)
+    }
+
+    /// Cyan, for argument indices.
+    pub fn index(self, text: &str) -> String {
+        self.paint("36", text)
+    }
+
+    /// `text` between an SGR escape (`ESC [ <code> m`) and the reset (`ESC [ 0 m`).
+    fn paint(self, code: &str, text: &str) -> String {
+        if self.colored {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            Style::resolve(ColorChoice::Auto, None, true),
+            Style::COLORED
+        );
+        assert_eq!(
+            Style::resolve(ColorChoice::Auto, Some("1"), true),
+            Style::PLAIN
+        );
+        // An empty NO_COLOR does not count as set.
+        assert_eq!(
+            Style::resolve(ColorChoice::Auto, Some(""), true),
+            Style::COLORED
+        );
+        assert_eq!(Style::resolve(ColorChoice::Auto, None, false), Style::PLAIN);
+        // An explicit choice beats both.
+        assert_eq!(
+            Style::resolve(ColorChoice::Always, Some("1"), false),
+            Style::COLORED
+        );
+        assert_eq!(Style::resolve(ColorChoice::Never, None, true), Style::PLAIN);
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+    }
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(Style::COLORED.heading("Usage:"), "\x1b[1mUsage:\x1b[0m");
+        assert_eq!(Style::COLORED.index("3"), "\x1b[36m3\x1b[0m");
+        assert_eq!(Style::PLAIN.heading("Usage:"), "Usage:");
+    }
+}
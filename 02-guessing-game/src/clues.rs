@@ -0,0 +1,148 @@
+//! Hints the player can ask for instead of guessing, by typing `hint`.
+//!
+//! They are called clues in the code, to keep them apart from `solver::Hint`
+//! (the higher/lower answers of the reversed game). A clue never costs an
+//! attempt, but every clue takes `COST` points off the score of a win.
+
+use crate::Settings;
+use std::fmt;
+
+/// Points taken off the score for each clue.
+pub const COST: u32 = 50;
+
+/// The divisor of the divisibility clue.
+const DIVISOR: u32 = 3;
+
+/// One fact about the secret.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Clue {
+    Parity {
+        even: bool,
+    },
+    Divisible {
+        by: u32,
+        divisible: bool,
+    },
+    /// Whether the secret is within `distance` of the last guess.
+    Near {
+        guess: u32,
+        distance: u32,
+        near: bool,
+    },
+}
+
+impl Clue {
+    /// The clue for the `given`-th request (counting from 0): parity first, then
+    /// divisibility, then as many "near your last guess" clues as wanted. `None`
+    /// when that clue needs a guess and there is none yet.
+    pub fn next(
+        given: u32,
+        secret: u32,
+        last_guess: Option<u32>,
+        settings: Settings,
+    ) -> Option<Clue> {
+        match given {
+            0 => Some(Clue::Parity {
+                even: secret.is_multiple_of(2),
+            }),
+            1 => Some(Clue::Divisible {
+                by: DIVISOR,
+                divisible: secret.is_multiple_of(DIVISOR),
+            }),
+            _ => {
+                let guess = last_guess?;
+                let distance = near_distance(settings);
+                Some(Clue::Near {
+                    guess,
+                    distance,
+                    near: guess.abs_diff(secret) <= distance,
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Clue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Clue::Parity { even } => {
+                write!(f, "The number is {}.", if even { "even" } else { "odd" })
+            }
+            Clue::Divisible { by, divisible } => write!(
+                f,
+                "The number {} divisible by {}.",
+                if divisible { "is" } else { "is not" },
+                by
+            ),
+            Clue::Near {
+                guess,
+                distance,
+                near,
+            } => write!(
+                f,
+                "The number {} within {} of your last guess ({}).",
+                if near { "is" } else { "is not" },
+                distance,
+                guess
+            ),
+        }
+    }
+}
+
+/// A tenth of the range (at least 1): close enough to help, wide enough to be likely.
+fn near_distance(settings: Settings) -> u32 {
+    ((settings.max - settings.min) / 10).max(1)
+}
+
+/// The score of a win after paying for `clues`; a win is still worth at least 1 point.
+pub fn charge(score: u32, clues: u32) -> u32 {
+    score.saturating_sub(COST.saturating_mul(clues)).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Difficulty;
+
+    #[test]
+    fn test_clues_in_order() {
+        let settings = Difficulty::Medium.settings();
+        assert_eq!(
+            Clue::next(0, 42, None, settings),
+            Some(Clue::Parity { even: true })
+        );
+        assert_eq!(
+            Clue::next(1, 42, None, settings).unwrap().to_string(),
+            "The number is divisible by 3."
+        );
+        // The third clue needs a guess to compare with.
+        assert_eq!(Clue::next(2, 42, None, settings), None);
+        assert_eq!(
+            Clue::next(2, 42, Some(50), settings).unwrap().to_string(),
+            "The number is within 9 of your last guess (50)."
+        );
+        assert_eq!(
+            Clue::next(5, 42, Some(90), settings),
+            Some(Clue::Near {
+                guess: 90,
+                distance: 9,
+                near: false
+            })
+        );
+        assert_eq!(
+            Clue::next(2, 5, Some(6), Settings::new(1, 5, 3)),
+            Some(Clue::Near {
+                guess: 6,
+                distance: 1,
+                near: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_charge() {
+        assert_eq!(charge(700, 0), 700);
+        assert_eq!(charge(700, 3), 550);
+        assert_eq!(charge(100, 5), 1);
+    }
+}
@@ -0,0 +1,652 @@
+use crate::clues;
+use crate::multiplayer::{self, Player};
+use crate::scores::{self, GameResult, ScoreStore};
+use crate::secret::{self, Day, Number, Secret, SecretSource, Verdict};
+use crate::solver::{BinarySearch, Hint, Strategy};
+use crate::word::{self, Word};
+use crate::{player_name, score, Difficulty, Mode, Outcome, Settings};
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+/// What the player typed at the guess prompt.
+enum Turn<G> {
+    Guess(G),
+    Hint,
+}
+
+/// How `Game::guess_loop` ended.
+#[derive(Debug, PartialEq)]
+enum Ending {
+    Solved {
+        attempts: u32,
+        hints: u32,
+        elapsed: Duration,
+    },
+    OutOfAttempts,
+}
+
+/// The interactive part of the game: every prompt, answer and message goes
+/// through `input` and `output` instead of the real stdin and stdout.
+///
+/// `main` plugs in the terminal (`Game::new(stdin.lock(), stdout)`), while tests
+/// feed a script (`&[u8]` is a `BufRead`) and collect what was printed in a
+/// `Vec<u8>`, so whole games run in a unit test without a keyboard.
+/// Every method returns `io::Result`: when the input ends, the error travels up
+/// with `?` and the game stops cleanly.
+pub struct Game<R, W> {
+    input: R,
+    output: W,
+    secrets: SecretSource,
+}
+
+impl<R: BufRead, W: Write> Game<R, W> {
+    /// A game with a different secret every run.
+    pub fn new(input: R, output: W) -> Self {
+        Game {
+            input,
+            output,
+            secrets: SecretSource::random(),
+        }
+    }
+
+    /// Draws the secrets from `secrets` instead, e.g. a seeded source to replay a game.
+    pub fn with_secrets(mut self, secrets: SecretSource) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// The secret number for a new game.
+    pub fn pick_secret(&mut self, settings: Settings) -> u32 {
+        self.secrets.pick(settings)
+    }
+
+    /// Gives the input and output back, e.g. to inspect what a test printed.
+    #[cfg(test)]
+    pub fn into_parts(self) -> (R, W) {
+        (self.input, self.output)
+    }
+
+    /// Prints one line of output.
+    pub fn say(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.output, "{}", message)
+    }
+
+    /// Reads one line. When the input is closed (Ctrl+D, or the end of a piped
+    /// file or test script) there is nothing left to play with: `UnexpectedEof`.
+    pub fn read_line(&mut self) -> io::Result<String> {
+        // Prompts are printed without waiting for a newline, so show them now.
+        self.output.flush()?;
+        let mut input = String::new();
+        if self.input.read_line(&mut input)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "no more input",
+            ));
+        }
+        Ok(input)
+    }
+
+    /// Prints a prompt and reads the answer.
+    pub fn ask(&mut self, prompt: &str) -> io::Result<String> {
+        self.say(prompt)?;
+        self.read_line()
+    }
+
+    /// Runs one game in the given mode. Single-player results go to `store`.
+    pub fn run(&mut self, mode: Mode, store: Option<&ScoreStore>) -> io::Result<()> {
+        match mode {
+            Mode::Single => self.play_single(store),
+            Mode::HotSeat => {
+                let first = player_name(&self.ask("Player 1, enter your name:")?);
+                let second = player_name(&self.ask("Player 2, enter your name:")?);
+                let settings = self.choose_settings()?;
+                let players = vec![Player::human(&first), Player::human(&second)];
+                self.play_together(settings, players)
+            }
+            Mode::VsComputer => {
+                let name = player_name(&self.ask("Enter your name:")?);
+                let settings = self.choose_settings()?;
+                let players = vec![Player::human(&name), Player::computer(settings)];
+                self.play_together(settings, players)
+            }
+            Mode::Reversed => {
+                self.say("Reversed game: you pick the number, I guess it!")?;
+                let settings = self.choose_settings()?;
+                self.play_reversed(settings, &mut BinarySearch::new(settings.min, settings.max))
+            }
+        }
+    }
+
+    pub fn choose_mode(&mut self) -> io::Result<Mode> {
+        self.say("Guess the number!")?;
+        loop {
+            self.say("Choose a mode:")?;
+            self.say("  1) single player")?;
+            self.say("  2) two players, taking turns at this keyboard")?;
+            self.say("  3) you against the computer")?;
+            self.say("  4) reversed: the computer guesses your number")?;
+            if let Some(mode) = Mode::parse(&self.read_line()?) {
+                return Ok(mode);
+            }
+            self.say("Please type a number from 1 to 4!")?;
+        }
+    }
+
+    /// The classic game, with the result saved to the high scores.
+    fn play_single(&mut self, store: Option<&ScoreStore>) -> io::Result<()> {
+        let settings = self.choose_settings()?;
+        self.say(&format!(
+            "Generating secret number between {} and {}...",
+            settings.min, settings.max
+        ))?;
+
+        let secret_number = self.pick_secret(settings);
+        let outcome = self.play(settings, secret_number)?;
+        self.finish_single(settings, outcome, store)
+    }
+
+    /// `--daily`: a medium game on the secret of the day, the same for every
+    /// player, ending with a result line to share.
+    pub fn play_daily(&mut self, day: Day, store: Option<&ScoreStore>) -> io::Result<()> {
+        let settings = Difficulty::Medium.settings();
+        self.say(&format!(
+            "Daily challenge for {}: the number is between {} and {}, and everyone gets the same one today.",
+            day, settings.min, settings.max
+        ))?;
+        let secret_number = self.pick_secret(settings);
+        let outcome = self.play(settings, secret_number)?;
+        let share = secret::share(day, settings, &outcome);
+        self.finish_single(settings, outcome, store)?;
+        self.say("\nShare your result:")?;
+        self.say(&share)
+    }
+
+    /// Announces how a single-player game ended, asks a winner for their name and
+    /// updates the statistics and the high scores. Shared with the `--tui` frontend.
+    pub fn finish_single(
+        &mut self,
+        settings: Settings,
+        outcome: Outcome,
+        store: Option<&ScoreStore>,
+    ) -> io::Result<()> {
+        let (won, attempts) = match outcome {
+            Outcome::Won { attempts, .. } => (true, attempts),
+            Outcome::Lost { .. } => (false, settings.max_attempts),
+        };
+        let result = match outcome {
+            Outcome::Won {
+                attempts,
+                elapsed,
+                score,
+            } => {
+                self.say(&format!(
+                    "You win in {} attempt(s)! Score: {}",
+                    attempts, score
+                ))?;
+                let name = player_name(&self.ask("Enter your name for the high scores:")?);
+                Some(GameResult {
+                    name,
+                    attempts,
+                    seconds: elapsed.as_secs(),
+                    min: settings.min,
+                    max: settings.max,
+                    score,
+                })
+            }
+            Outcome::Lost { secret } => {
+                self.say(&format!(
+                    "Game over: no attempts left. The number was {}.",
+                    secret
+                ))?;
+                None
+            }
+        };
+
+        match store {
+            Some(store) => {
+                self.show_stats(store, won, attempts)?;
+                self.show_leaderboard(store, result)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Counts the game in the statistics and prints a one-line summary. Like the
+    /// scores, a file that cannot be read or written is only reported.
+    fn show_stats(&mut self, store: &ScoreStore, won: bool, attempts: u32) -> io::Result<()> {
+        match store.record_stats(won, attempts) {
+            Ok(stats) => self.say(&format!(
+                "Games played: {}, won: {}%, win streak: {} (see --stats).",
+                stats.played,
+                stats.win_rate(),
+                stats.streak
+            )),
+            Err(e) => self.say(&format!(
+                "Could not update the statistics in {}: {}",
+                store.stats_path().display(),
+                e
+            )),
+        }
+    }
+
+    /// Several players, one secret, taking turns. Each player has the full attempt budget.
+    fn play_together(&mut self, settings: Settings, mut players: Vec<Player>) -> io::Result<()> {
+        let secret = self.pick_secret(settings);
+        self.say(&format!(
+            "The number is between {} and {}; everyone has {} attempt(s).",
+            settings.min, settings.max, settings.max_attempts
+        ))?;
+        let winner = multiplayer::play_turns(self, settings, secret, &mut players)?;
+        multiplayer::announce(self, secret, &players, winner)
+    }
+
+    /// Saves the result (if the game was won) and prints the top 10. Scores are a
+    /// bonus: if the file cannot be read or written, the game just says so.
+    fn show_leaderboard(
+        &mut self,
+        store: &ScoreStore,
+        result: Option<GameResult>,
+    ) -> io::Result<()> {
+        let leaderboard = match result {
+            Some(result) => store.record(result),
+            None => store.load(),
+        };
+        match leaderboard {
+            Ok(leaderboard) => {
+                self.say("\nHigh scores:")?;
+                self.say(&scores::format_leaderboard(&leaderboard))
+            }
+            Err(e) => self.say(&format!(
+                "Could not update the high scores in {}: {}",
+                store.path().display(),
+                e
+            )),
+        }
+    }
+
+    /// The number game: the shared guessing loop, scored for the high scores.
+    pub fn play(&mut self, settings: Settings, secret_number: u32) -> io::Result<Outcome> {
+        self.say(&format!(
+            "Stuck? Type 'hint' for a clue (costs {} points).",
+            clues::COST
+        ))?;
+        let mut secret = Number::new(settings, secret_number);
+        Ok(match self.guess_loop(&mut secret, settings.max_attempts)? {
+            Ending::Solved {
+                attempts,
+                hints,
+                elapsed,
+            } => Outcome::Won {
+                attempts,
+                elapsed,
+                score: clues::charge(score(settings, attempts, elapsed), hints),
+            },
+            Ending::OutOfAttempts => Outcome::Lost {
+                secret: secret_number,
+            },
+        })
+    }
+
+    /// `--mode word`: hangman on a word from the built-in list. A letter in the
+    /// word is free; a wrong letter or a wrong word costs one of the lives.
+    pub fn play_word(&mut self) -> io::Result<()> {
+        let mut secret = Word::new(self.secrets.choose(&word::list()));
+        self.say(&format!(
+            "Guess the word, one letter at a time or all at once: {} wrong guesses and you lose.",
+            word::LIVES
+        ))?;
+        match self.guess_loop(&mut secret, word::LIVES)? {
+            Ending::Solved { attempts, .. } => self.say(&format!(
+                "You found '{}' in {} guess(es)!",
+                secret.reveal(),
+                attempts
+            )),
+            Ending::OutOfAttempts => self.say(&format!(
+                "Game over: no attempts left. The word was '{}'.",
+                secret.reveal()
+            )),
+        }
+    }
+
+    /// The guessing loop shared by every kind of secret: one guess per iteration,
+    /// until the secret is found or `max_attempts` wrong guesses are used up.
+    /// Invalid input does not cost an attempt, and neither do hints: what they
+    /// cost is up to the caller.
+    fn guess_loop<S: Secret>(&mut self, secret: &mut S, max_attempts: u32) -> io::Result<Ending> {
+        let started = Instant::now();
+        let mut attempts = 0;
+        let mut wrong = 0;
+        let mut hints = 0;
+
+        while wrong < max_attempts {
+            if let Some(progress) = secret.progress() {
+                self.say(&progress)?;
+            }
+            let prompt = format!(
+                "Please input your guess ({} attempt(s) left).",
+                max_attempts - wrong
+            );
+            let guess = match self.read_turn(&prompt, secret)? {
+                Turn::Guess(guess) => guess,
+                Turn::Hint => {
+                    match secret.hint(hints) {
+                        Ok(hint) => {
+                            hints += 1;
+                            self.say(&format!("Hint: {} (-{} points)", hint, clues::COST))?;
+                        }
+                        Err(reason) => self.say(&reason)?,
+                    }
+                    continue;
+                }
+            };
+
+            attempts += 1;
+            self.say(&format!("You guessed: {guess}"))?;
+            match secret.judge(guess) {
+                Verdict::Solved => {
+                    return Ok(Ending::Solved {
+                        attempts,
+                        hints,
+                        elapsed: started.elapsed(),
+                    });
+                }
+                Verdict::Right(message) => self.say(&message)?,
+                Verdict::Wrong(message) => {
+                    wrong += 1;
+                    self.say(&message)?;
+                }
+            }
+        }
+
+        Ok(Ending::OutOfAttempts)
+    }
+
+    /// The reversed game: the player keeps a number in mind and answers each of the
+    /// strategy's guesses with higher, lower or correct.
+    pub fn play_reversed(
+        &mut self,
+        settings: Settings,
+        strategy: &mut impl Strategy,
+    ) -> io::Result<()> {
+        self.say(&format!(
+            "Think of a number between {} and {}, and answer h(igher), l(ower) or c(orrect).",
+            settings.min, settings.max
+        ))?;
+        for attempts in 1.. {
+            let guess = match strategy.guess() {
+                Ok(guess) => guess,
+                Err(contradiction) => {
+                    return self.say(&format!(
+                        "That cannot be right: {}, and the number is between {} and {}.",
+                        contradiction, settings.min, settings.max
+                    ));
+                }
+            };
+            let hint = loop {
+                match Hint::parse(&self.ask(&format!("Is it {}?", guess))?) {
+                    Some(hint) => break hint,
+                    None => self.say("Please answer higher, lower or correct!")?,
+                }
+            };
+            if hint == Hint::Correct {
+                return self.say(&format!("Found it in {} guess(es)!", attempts));
+            }
+            strategy.learn(guess, hint);
+        }
+        Ok(())
+    }
+
+    /// Asks for a guess until the player types a number inside the game's range.
+    pub fn read_guess(&mut self, prompt: &str, settings: Settings) -> io::Result<u32> {
+        loop {
+            match secret::parse_number(&self.ask(prompt)?, settings) {
+                Ok(guess) => return Ok(guess),
+                Err(problem) => self.say(&problem)?,
+            }
+        }
+    }
+
+    /// Asks for a guess of `secret` or a hint, until the input is one of them.
+    fn read_turn<S: Secret>(&mut self, prompt: &str, secret: &S) -> io::Result<Turn<S::Guess>> {
+        loop {
+            let input = self.ask(prompt)?;
+            if matches!(input.trim().to_lowercase().as_str(), "hint" | "?") {
+                return Ok(Turn::Hint);
+            }
+            match secret.parse(&input) {
+                Ok(guess) => return Ok(Turn::Guess(guess)),
+                Err(problem) => self.say(&problem)?,
+            }
+        }
+    }
+
+    /// Asks for a difficulty, or a custom range for the players who want to pick their own.
+    pub fn choose_settings(&mut self) -> io::Result<Settings> {
+        loop {
+            let input = self.ask(
+                "Choose a difficulty: easy (1-10), medium (1-100), hard (1-1000) or custom:",
+            )?;
+            if let Some(difficulty) = Difficulty::parse(&input) {
+                return Ok(difficulty.settings());
+            }
+            if matches!(input.trim().to_lowercase().as_str(), "c" | "custom") {
+                let (min, max) = self.get_range()?;
+                return Ok(Settings::custom(min, max));
+            }
+            self.say("Please type easy, medium, hard or custom!")?;
+        }
+    }
+
+    /// Helper function to prompt for and read a numeric input.
+    /// It keeps asking until a valid u32 is provided.
+    fn get_input(&mut self, prompt: &str) -> io::Result<u32> {
+        loop {
+            match self.ask(prompt)?.trim().parse() {
+                Ok(num) => return Ok(num),
+                Err(_) => self.say("Please type a number!")?,
+            }
+        }
+    }
+
+    /// Prompts the user for a minimum and maximum and ensures the range is valid (max > min).
+    fn get_range(&mut self) -> io::Result<(u32, u32)> {
+        loop {
+            let min = self.get_input("Enter minimum number:")?;
+            let max = self.get_input("Enter maximum number:")?;
+            if max > min {
+                return Ok((min, max));
+            }
+            self.say("Max must be greater than min!")?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game reading `script` as the player's input.
+    fn scripted(script: &str) -> Game<&[u8], Vec<u8>> {
+        Game::new(script.as_bytes(), Vec::new())
+    }
+
+    fn printed(game: Game<&[u8], Vec<u8>>) -> String {
+        String::from_utf8(game.into_parts().1).unwrap()
+    }
+
+    #[test]
+    fn test_win_with_invalid_and_out_of_range_input() {
+        let mut game = scripted("abc\n0\n50\n75\n60\n");
+        let outcome = game.play(Difficulty::Medium.settings(), 60).unwrap();
+
+        // Typos and numbers outside 1..=100 cost nothing: 3 real attempts.
+        let Outcome::Won { attempts, .. } = outcome else {
+            panic!("60 was guessed: {:?}", outcome);
+        };
+        assert_eq!(attempts, 3);
+        let output = printed(game);
+        assert!(output.contains("Please type a valid positive number!"));
+        assert!(output.contains("The number is between 1 and 100!"));
+        assert!(output.contains("You guessed: 50\nToo small!"));
+        assert!(output.contains("You guessed: 75\nToo big!"));
+        assert!(output.contains("(6 attempt(s) left)"));
+    }
+
+    #[test]
+    fn test_hints_cost_points_not_attempts() {
+        let settings = Difficulty::Medium.settings();
+        let mut game = scripted("hint\nhint\n?\n50\nHINT\n42\n");
+        let outcome = game.play(settings, 42).unwrap();
+
+        let Outcome::Won {
+            attempts,
+            elapsed,
+            score,
+        } = outcome
+        else {
+            panic!("42 was guessed: {:?}", outcome);
+        };
+        assert_eq!(attempts, 2);
+        assert_eq!(score, crate::score(settings, 2, elapsed) - 3 * clues::COST);
+        let output = printed(game);
+        assert!(output.contains("Hint: The number is even. (-50 points)"));
+        assert!(output.contains("Hint: The number is divisible by 3."));
+        assert!(output.contains("Make a guess first"));
+        assert!(output.contains("Hint: The number is within 9 of your last guess (50)."));
+    }
+
+    #[test]
+    fn test_finished_games_update_the_statistics() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScoreStore::new(dir.path().join("scores.json"));
+        let settings = Difficulty::Easy.settings();
+
+        let mut game = scripted("");
+        game.finish_single(settings, Outcome::Lost { secret: 3 }, Some(&store))
+            .unwrap();
+        let mut game = scripted("Ada\n");
+        let won = Outcome::Won {
+            attempts: 3,
+            elapsed: std::time::Duration::from_secs(4),
+            score: 500,
+        };
+        game.finish_single(settings, won, Some(&store)).unwrap();
+
+        assert!(printed(game).contains("Games played: 2, won: 50%, win streak: 1"));
+        let stats = store.load_stats().unwrap();
+        // The lost game counts all 5 of its attempts.
+        assert_eq!(stats.attempts, 5 + 3);
+        assert_eq!(store.load().unwrap()[0].name, "Ada");
+    }
+
+    #[test]
+    fn test_word_game_reuses_the_loop() {
+        let mut game = scripted("r\nz\nr\nst\n5\nhint\nrust\n");
+        let mut secret = Word::new("rust");
+        let ending = game.guess_loop(&mut secret, word::LIVES).unwrap();
+
+        // Repeated letters and typos are free: 3 guesses, and only 'z' cost a life.
+        let Ending::Solved {
+            attempts, hints, ..
+        } = ending
+        else {
+            panic!("rust was guessed: {:?}", ending);
+        };
+        assert_eq!((attempts, hints), (3, 0));
+        let output = printed(game);
+        assert!(output.contains("_ _ _ _\nPlease input your guess (6 attempt(s) left)."));
+        assert!(output.contains("You guessed: r\nYes, the word has 1 'r'.\nr _ _ _"));
+        assert!(output.contains("You guessed: z\nNo 'z' in the word."));
+        assert!(output.contains("(5 attempt(s) left)"));
+        assert!(output.contains("You already tried 'r'!"));
+        assert!(output.contains("The word has 4 letters!"));
+        assert!(output.contains("Please type a letter or the whole word!"));
+        assert!(output.contains("There are no hints in this game."));
+    }
+
+    #[test]
+    fn test_word_game_lost() {
+        let word = SecretSource::seeded(1).choose(&word::list());
+        // Six letters that are not in the word, then the end of the input.
+        let script: String = ('a'..='z')
+            .filter(|&letter| !word.contains(letter))
+            .take(word::LIVES as usize)
+            .map(|letter| format!("{}\n", letter))
+            .collect();
+        let mut game = scripted(&script).with_secrets(SecretSource::seeded(1));
+        game.play_word().unwrap();
+        let output = printed(game);
+        assert!(output.ends_with(&format!(
+            "Game over: no attempts left. The word was '{}'.\n",
+            word
+        )));
+    }
+
+    #[test]
+    fn test_running_out_of_attempts_loses() {
+        let mut game = scripted("1\n2\n3\n4\n5\n");
+        let outcome = game.play(Difficulty::Easy.settings(), 10).unwrap();
+        assert_eq!(outcome, Outcome::Lost { secret: 10 });
+    }
+
+    #[test]
+    fn test_end_of_input_stops_the_game() {
+        let mut game = scripted("50\n");
+        let err = game.play(Difficulty::Medium.settings(), 10).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_menus_retry_until_valid() {
+        let mut game = scripted("9\n3\nnightmare\ncustom\n20\n10\n5\nx\n500\n");
+        assert_eq!(game.choose_mode().unwrap(), Mode::VsComputer);
+        assert_eq!(game.choose_settings().unwrap(), Settings::custom(5, 500));
+        let output = printed(game);
+        assert!(output.contains("Please type a number from 1 to 4!"));
+        assert!(output.contains("Please type easy, medium, hard or custom!"));
+        assert!(output.contains("Max must be greater than min!"));
+        assert!(output.contains("Please type a number!"));
+    }
+
+    #[test]
+    fn test_seeded_games_repeat() {
+        let settings = Difficulty::Hard.settings();
+        let mut first = scripted("").with_secrets(SecretSource::seeded(7));
+        let mut second = scripted("").with_secrets(SecretSource::seeded(7));
+        for _ in 0..3 {
+            assert_eq!(first.pick_secret(settings), second.pick_secret(settings));
+        }
+    }
+
+    #[test]
+    fn test_daily_game_shares_the_result() {
+        let day = Day::from_days(20_742);
+        let secret = SecretSource::seeded(day.seed()).pick(Difficulty::Medium.settings());
+        let script = format!("{}\nAda\n", secret);
+        let mut game = scripted(&script).with_secrets(SecretSource::seeded(day.seed()));
+        game.play_daily(day, None).unwrap();
+        let output = printed(game);
+        assert!(output.starts_with("Daily challenge for 2026-10-16"));
+        assert!(output.ends_with(
+            "Share your result:\nGuessing game daily 2026-10-16: solved in 1 guess(es) (1/8).\n"
+        ));
+    }
+
+    #[test]
+    fn test_reversed_game_with_scripted_answers() {
+        let settings = Difficulty::Medium.settings();
+        let mut game = scripted("h\nwhat\nl\nc\n");
+        game.play_reversed(settings, &mut BinarySearch::new(1, 100))
+            .unwrap();
+        let output = printed(game);
+        assert!(output.contains(
+            "Is it 50?\nIs it 75?\nPlease answer higher, lower or correct!\nIs it 75?\nIs it 62?"
+        ));
+        assert!(output.ends_with("Found it in 3 guess(es)!\n"));
+
+        let mut game = scripted("h\nl\nh\nl\nh\nl\n");
+        game.play_reversed(settings, &mut BinarySearch::new(1, 100))
+            .unwrap();
+        assert!(printed(game).contains("That cannot be right"));
+    }
+}
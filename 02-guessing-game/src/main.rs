@@ -1,79 +1,264 @@
-use rand::Rng;
+use game::Game;
+use scores::ScoreStore;
+use secret::{Day, SecretSource};
 use std::cmp::Ordering;
-use std::io; // Requires `rand` dependency
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+mod clues;
+mod game;
+mod multiplayer;
+mod network;
+mod scores;
+mod secret;
+mod solver;
+mod tui;
+mod word;
+
+const USAGE: &str = "Usage: guessing-game [--seed N] [--mode number|word | --tui | --serve PORT]
+       guessing-game [--daily | --reverse | --connect ADDR | --stats | --reset-scores]
+
+Without options, a menu offers every game mode.
+
+Options:
+  --seed N        Pick the secrets from seed N: the same N replays the same games
+  --daily         Today's challenge: the same number for everyone, with a result to share
+  --mode MODE     number: the menu of number games (the default); word: hangman
+  --tui           Play single player on a full-screen interface
+  --reverse       You think of a number, the computer guesses it
+  --serve PORT    Host games for other machines on this TCP port
+  --connect ADDR  Play against a server, e.g. --connect 192.168.1.20:7878
+  --stats         Show your statistics over all single-player games and exit
+  --reset-scores  Delete the saved high scores and exit
+  -h, --help      Show this help";
+
+/// How hard a game is: a bigger range and fewer spare attempts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Parses a menu answer: the name or its first letter, in any case.
+    fn parse(input: &str) -> Option<Difficulty> {
+        match input.trim().to_lowercase().as_str() {
+            "e" | "easy" => Some(Difficulty::Easy),
+            "m" | "medium" => Some(Difficulty::Medium),
+            "h" | "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// The range and the number of attempts for this difficulty.
+    fn settings(self) -> Settings {
+        match self {
+            // Binary search needs at most 4 guesses for 1..=10: one spare.
+            Difficulty::Easy => Settings::new(1, 10, 5),
+            // At most 7 for 1..=100: one spare.
+            Difficulty::Medium => Settings::new(1, 100, 8),
+            // At most 10 for 1..=1000: no room for mistakes.
+            Difficulty::Hard => Settings::new(1, 1000, 10),
+        }
+    }
+}
+
+/// The rules of one game: the secret lies in `min..=max`, found in `max_attempts` guesses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Settings {
+    min: u32,
+    max: u32,
+    max_attempts: u32,
+}
+
+impl Settings {
+    fn new(min: u32, max: u32, max_attempts: u32) -> Self {
+        Settings {
+            min,
+            max,
+            max_attempts,
+        }
+    }
+
+    /// A custom range gets one attempt more than a perfect binary search needs.
+    fn custom(min: u32, max: u32) -> Self {
+        Settings::new(min, max, guesses_needed(min, max) + 1)
+    }
+}
+
+/// How a game ended.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Won {
+        attempts: u32,
+        elapsed: Duration,
+        score: u32,
+    },
+    Lost {
+        secret: u32,
+    },
+}
+
+/// The ways to play, offered by the start menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// One player guesses the computer's number.
+    Single,
+    /// Two players at the same keyboard take turns on the same number.
+    HotSeat,
+    /// The player and the computer take turns on the same number.
+    VsComputer,
+    /// The computer guesses the player's number.
+    Reversed,
+}
+
+impl Mode {
+    /// Parses a menu answer: the number of the entry or its first word.
+    fn parse(input: &str) -> Option<Mode> {
+        match input.trim().to_lowercase().as_str() {
+            "1" | "single" => Some(Mode::Single),
+            "2" | "two" => Some(Mode::HotSeat),
+            "3" | "computer" => Some(Mode::VsComputer),
+            "4" | "reversed" => Some(Mode::Reversed),
+            _ => None,
+        }
+    }
+}
 
 /// The entry point of the guessing game.
-/// It introduces the game, asks for a range, generates a secret number,
-/// and enters a loop where the user can guess until they win.
+/// It introduces the game, asks for a mode and a difficulty, and runs that game
+/// on the terminal.
 fn main() {
-    println!("Guess the number!");
-
-    // Ask user to define the range for the secret number.
-    let (min, max) = get_range();
-    println!("Generating secret number between {} and {}...", min, max);
-
-    // thread_rng() gives us the random number generator that's local to the current thread.
-    // gen_range(min..=max) generates a number in the inclusive range [min, max].
-    let secret_number = rand::thread_rng().gen_range(min..=max);
-
-    loop {
-        println!("Please input your guess.");
-
-        let mut guess = String::new();
-
-        // Read user input from standard input.
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line");
-
-        // Parse the string into a u32 number. If parsing fails, skip the rest of the loop.
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => {
-                println!("Please type a valid positive number!");
-                continue;
-            }
-        };
-
-        println!("You guessed: {guess}");
-
-        // Compare the guess to the secret number.
-        match check_guess(guess, secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break; // Exit the loop when the guess is correct.
-            }
+    let store = ScoreStore::in_data_dir();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let seed = take_seed(&mut args);
+    let mut game =
+        Game::new(io::stdin().lock(), io::stdout()).with_secrets(SecretSource::new(seed));
+    let result = match args.as_slice() {
+        [] | ["--mode", "number"] => game
+            .choose_mode()
+            .and_then(|mode| game.run(mode, store.as_ref())),
+        ["--mode", "word"] => game.play_word(),
+        ["--mode", other] => usage_error(&format!("unknown mode '{}'", other)),
+        ["--daily"] if seed.is_some() => usage_error("--daily picks the seed of the day itself"),
+        ["--daily"] => {
+            let day = Day::today();
+            let mut game = game.with_secrets(SecretSource::seeded(day.seed()));
+            game.play_daily(day, store.as_ref())
+        }
+        ["--tui"] => tui::run(&mut game, store.as_ref()),
+        ["--reverse"] => game.run(Mode::Reversed, store.as_ref()),
+        ["--serve", port] => match port.parse() {
+            Ok(port) => host(&mut game, port, SecretSource::new(seed)),
+            Err(_) => usage_error(&format!("'{}' is not a port number", port)),
+        },
+        ["--connect", addr] => join(&mut game, addr),
+        ["--stats"] => {
+            show_stats(store.as_ref());
+            return;
+        }
+        ["--reset-scores"] => {
+            reset_scores(store.as_ref());
+            return;
+        }
+        ["-h" | "--help"] => {
+            println!("{}", USAGE);
+            return;
+        }
+        [other, ..] => usage_error(&format!("unknown or incomplete argument '{}'", other)),
+    };
+
+    match result {
+        Ok(()) => {}
+        // Ctrl+D, or the end of a piped file: nothing left to play with.
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => println!("\nNo more input, bye!"),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
     }
 }
 
-/// Helper function to prompt for and read a numeric input from standard input.
-/// It keeps asking until a valid u32 is provided.
-fn get_input(prompt: &str) -> u32 {
-    loop {
-        println!("{}", prompt);
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
-        match input.trim().parse() {
-            Ok(num) => return num,
-            Err(_) => println!("Please type a number!"),
+fn usage_error(message: &str) -> ! {
+    eprintln!("Error: {}\n\n{}", message, USAGE);
+    std::process::exit(2);
+}
+
+/// Removes `--seed N` from the arguments, wherever it is, and returns `N`.
+fn take_seed(args: &mut Vec<&str>) -> Option<u64> {
+    let at = args.iter().position(|&arg| arg == "--seed")?;
+    let seed = match args.get(at + 1).map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => usage_error(&format!("'{}' is not a seed number", args[at + 1])),
+        None => usage_error("--seed needs a number"),
+    };
+    args.drain(at..=at + 1);
+    Some(seed)
+}
+
+/// `--serve`: the host picks the difficulty, then every player who connects gets
+/// a game of their own, with a secret from `secrets`.
+fn host<R: BufRead, W: Write>(
+    game: &mut Game<R, W>,
+    port: u16,
+    secrets: SecretSource,
+) -> io::Result<()> {
+    let settings = game.choose_settings()?;
+    // 0.0.0.0: reachable from other machines, not only from this one.
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    game.say(&format!(
+        "Waiting for players on port {} (they run: guessing-game --connect <this-machine>:{}).",
+        port, port
+    ))?;
+    network::serve(listener, settings, secrets)
+}
+
+/// `--connect`: plays against a server.
+fn join<R: BufRead, W: Write>(game: &mut Game<R, W>, addr: &str) -> io::Result<()> {
+    let stream = network::connect(addr)?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    network::play_remote(game, &mut reader, &mut writer)
+}
+
+fn show_stats(store: Option<&ScoreStore>) {
+    let Some(store) = store else {
+        println!("No data directory on this system: there are no saved statistics.");
+        return;
+    };
+    match store.load_stats() {
+        Ok(stats) => println!("{}", scores::format_stats(&stats)),
+        Err(e) => {
+            eprintln!("Could not read {}: {}", store.stats_path().display(), e);
+            std::process::exit(1);
         }
     }
 }
 
-/// Prompts the user for a minimum and maximum and ensures the range is valid (max > min).
-fn get_range() -> (u32, u32) {
-    loop {
-        let min = get_input("Enter minimum number:");
-        let max = get_input("Enter maximum number:");
-        if max > min {
-            return (min, max);
+fn reset_scores(store: Option<&ScoreStore>) {
+    let Some(store) = store else {
+        println!("No data directory on this system: there are no saved scores.");
+        return;
+    };
+    match store.reset() {
+        Ok(()) => println!("High scores reset."),
+        Err(e) => {
+            eprintln!("Could not delete {}: {}", store.path().display(), e);
+            std::process::exit(1);
         }
-        println!("Max must be greater than min!");
+    }
+}
+
+/// The name typed after a win, trimmed and shortened to fit the leaderboard.
+fn player_name(input: &str) -> String {
+    let name: String = input.trim().chars().take(16).collect();
+    if name.is_empty() {
+        "Anonymous".to_string()
+    } else {
+        name
     }
 }
 
@@ -82,6 +267,23 @@ fn check_guess(guess: u32, secret: u32) -> Ordering {
     guess.cmp(&secret)
 }
 
+/// The worst case of a binary search over `min..=max`: each guess halves the
+/// range, so `n` guesses cover `2^n - 1` numbers.
+fn guesses_needed(min: u32, max: u32) -> u32 {
+    let size = u64::from(max - min) + 1;
+    // Bits needed to write `size` in binary.
+    u64::BITS - size.leading_zeros()
+}
+
+/// Points for a win: a base that grows with the range, a bonus per unused attempt,
+/// minus one point per second taken. A win is always worth at least 1 point.
+fn score(settings: Settings, attempts: u32, elapsed: Duration) -> u32 {
+    let base = 100 * guesses_needed(settings.min, settings.max);
+    let bonus = 50 * settings.max_attempts.saturating_sub(attempts);
+    let penalty = u32::try_from(elapsed.as_secs()).unwrap_or(u32::MAX);
+    (base + bonus).saturating_sub(penalty).max(1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,6 +304,52 @@ mod tests {
 
         let min = 20;
         let max = 10;
-        assert!(!(max > min));
+        assert!(max <= min);
+    }
+
+    #[test]
+    fn test_difficulty_settings() {
+        assert_eq!(Difficulty::parse(" Hard\n"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::parse("e"), Some(Difficulty::Easy));
+        assert_eq!(Difficulty::parse("impossible"), None);
+
+        // Every difficulty leaves enough attempts for a perfect binary search.
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let settings = difficulty.settings();
+            assert!(settings.max_attempts >= guesses_needed(settings.min, settings.max));
+        }
+        assert_eq!(guesses_needed(1, 10), 4);
+        assert_eq!(guesses_needed(1, 100), 7);
+        assert_eq!(guesses_needed(1, 1000), 10);
+        assert_eq!(Settings::custom(0, u32::MAX).max_attempts, 34);
+    }
+
+    #[test]
+    fn test_mode_parse() {
+        assert_eq!(Mode::parse("1\n"), Some(Mode::Single));
+        assert_eq!(Mode::parse(" Computer "), Some(Mode::VsComputer));
+        assert_eq!(Mode::parse("4"), Some(Mode::Reversed));
+        assert_eq!(Mode::parse("5"), None);
+    }
+
+    #[test]
+    fn test_player_name() {
+        assert_eq!(player_name("  Ada\n"), "Ada");
+        assert_eq!(player_name("\n"), "Anonymous");
+        assert_eq!(player_name(&"x".repeat(40)).len(), 16);
+    }
+
+    #[test]
+    fn test_score() {
+        let medium = Difficulty::Medium.settings();
+        let quick = score(medium, 3, Duration::from_secs(5));
+        assert_eq!(quick, 700 + 5 * 50 - 5);
+        // More attempts or more time: fewer points.
+        assert!(score(medium, 8, Duration::from_secs(5)) < quick);
+        assert!(score(medium, 3, Duration::from_secs(60)) < quick);
+        // Harder ranges pay more for the same effort.
+        assert!(score(Difficulty::Hard.settings(), 3, Duration::from_secs(5)) > quick);
+        // A very slow win still scores.
+        assert_eq!(score(medium, 8, Duration::from_secs(100_000)), 1);
     }
 }
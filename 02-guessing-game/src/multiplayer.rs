@@ -0,0 +1,174 @@
+use crate::game::Game;
+use crate::solver::{BinarySearch, Hint, Strategy};
+use crate::{check_guess, Settings};
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+
+/// Who is behind a seat: someone at the keyboard, or a guessing strategy.
+enum Controller {
+    Human,
+    Computer(BinarySearch),
+}
+
+/// One seat at the table, with its own attempt counter.
+pub struct Player {
+    pub name: String,
+    pub attempts: u32,
+    controller: Controller,
+}
+
+impl Player {
+    pub fn human(name: &str) -> Self {
+        Player {
+            name: name.to_string(),
+            attempts: 0,
+            controller: Controller::Human,
+        }
+    }
+
+    pub fn computer(settings: Settings) -> Self {
+        Player {
+            name: "Computer".to_string(),
+            attempts: 0,
+            controller: Controller::Computer(BinarySearch::new(settings.min, settings.max)),
+        }
+    }
+
+    fn next_guess<R: BufRead, W: Write>(
+        &self,
+        game: &mut Game<R, W>,
+        settings: Settings,
+    ) -> io::Result<u32> {
+        let left = settings.max_attempts - self.attempts;
+        match &self.controller {
+            Controller::Human => game.read_guess(
+                &format!("{}, your guess ({} attempt(s) left):", self.name, left),
+                settings,
+            ),
+            Controller::Computer(strategy) => {
+                // Feedback comes from the real secret, so the answers never contradict.
+                let guess = strategy.guess().unwrap_or(settings.min);
+                game.say(&format!("{} guesses {}.", self.name, guess))?;
+                Ok(guess)
+            }
+        }
+    }
+}
+
+/// Players take turns guessing the same secret; every answer is announced to
+/// the whole table, so the computer learns from everyone's guesses, just like
+/// the humans can. Returns the index of the winner, or `None` if all players ran
+/// out of attempts.
+pub fn play_turns<R: BufRead, W: Write>(
+    game: &mut Game<R, W>,
+    settings: Settings,
+    secret: u32,
+    players: &mut [Player],
+) -> io::Result<Option<usize>> {
+    loop {
+        let mut anyone_played = false;
+        for turn in 0..players.len() {
+            if players[turn].attempts >= settings.max_attempts {
+                continue;
+            }
+            anyone_played = true;
+            let guess = players[turn].next_guess(game, settings)?;
+            players[turn].attempts += 1;
+
+            let hint = match check_guess(guess, secret) {
+                Ordering::Less => {
+                    game.say("Too small!")?;
+                    Hint::Higher
+                }
+                Ordering::Greater => {
+                    game.say("Too big!")?;
+                    Hint::Lower
+                }
+                Ordering::Equal => return Ok(Some(turn)),
+            };
+            for player in players.iter_mut() {
+                if let Controller::Computer(strategy) = &mut player.controller {
+                    strategy.learn(guess, hint);
+                }
+            }
+        }
+        if !anyone_played {
+            return Ok(None);
+        }
+    }
+}
+
+/// The end of game summary: the winner and everyone's attempts.
+pub fn announce<R: BufRead, W: Write>(
+    game: &mut Game<R, W>,
+    secret: u32,
+    players: &[Player],
+    winner: Option<usize>,
+) -> io::Result<()> {
+    match winner {
+        Some(i) => game.say(&format!(
+            "{} wins! The number was {}.",
+            players[i].name, secret
+        ))?,
+        None => game.say(&format!("Nobody found it: the number was {}.", secret))?,
+    }
+    for player in players {
+        game.say(&format!(
+            "  {}: {} attempt(s)",
+            player.name, player.attempts
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computers never read input: an empty script is enough.
+    fn silent_game() -> Game<&'static [u8], Vec<u8>> {
+        Game::new(&b""[..], Vec::new())
+    }
+
+    #[test]
+    fn test_computers_take_turns_and_share_hints() {
+        let settings = Settings::new(1, 100, 7);
+        let mut players = [Player::computer(settings), Player::computer(settings)];
+        players[1].name = "Other computer".to_string();
+
+        // Every hint narrows the range for both: 50 (higher), then 75 (higher),
+        // then the first computer bisects 76..=100 and hits 88.
+        let winner = play_turns(&mut silent_game(), settings, 88, &mut players).unwrap();
+        assert_eq!(winner, Some(0));
+        assert_eq!(players[0].attempts, 2);
+        assert_eq!(players[1].attempts, 1);
+    }
+
+    #[test]
+    fn test_nobody_wins_without_attempts() {
+        let settings = Settings::new(1, 1000, 2);
+        let mut players = [Player::computer(settings), Player::computer(settings)];
+        let winner = play_turns(&mut silent_game(), settings, 999, &mut players).unwrap();
+        assert_eq!(winner, None);
+        assert_eq!(players[0].attempts, 2);
+        assert_eq!(players[1].attempts, 2);
+    }
+
+    #[test]
+    fn test_hot_seat_tracks_attempts_per_player() {
+        let settings = Settings::new(1, 10, 3);
+        // Ada: 5, Bob: 8, Ada: typo then 6, Bob: 7 wins.
+        let mut game = Game::new(&b"5\n8\noops\n6\n7\n"[..], Vec::new());
+        let mut players = [Player::human("Ada"), Player::human("Bob")];
+        let winner = play_turns(&mut game, settings, 7, &mut players).unwrap();
+        assert_eq!(winner, Some(1));
+        assert_eq!((players[0].attempts, players[1].attempts), (2, 2));
+
+        announce(&mut game, 7, &players, winner).unwrap();
+        let output = String::from_utf8(game.into_parts().1).unwrap();
+        assert!(output.contains("Bob, your guess (3 attempt(s) left):"));
+        assert!(output.contains("Ada, your guess (2 attempt(s) left):\nPlease type"));
+        assert!(output
+            .ends_with("Bob wins! The number was 7.\n  Ada: 2 attempt(s)\n  Bob: 2 attempt(s)\n"));
+    }
+}
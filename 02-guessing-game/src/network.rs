@@ -0,0 +1,482 @@
+//! Playing over the network: `--serve <port>` holds the secret, `--connect <addr>`
+//! sends the guesses.
+//!
+//! The protocol is plain text, one message per line, so it can be tried by hand
+//! with `nc localhost 7878`:
+//!
+//! ```text
+//! server: HELLO 1 100 8      range and attempts
+//! client: GUESS 50
+//! server: SMALL              or BIG, WIN <attempts>, LOSE <secret>, ERR <message>
+//! ```
+
+use crate::game::Game;
+use crate::secret::SecretSource;
+use crate::{check_guess, Settings};
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How long the server waits for each guess before giving up on the client.
+const GUESS_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long the client waits to connect and for each answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+/// The longest line the server reads; `GUESS 4294967295` is 16 bytes.
+const MAX_LINE: u64 = 64;
+/// Malformed or out-of-range lines a client may send before it is dropped.
+const MAX_INVALID: u32 = 5;
+/// Players served at once, a thread each; the next ones are turned away.
+const MAX_PLAYERS: usize = 32;
+
+/// A message from the server.
+#[derive(Debug, PartialEq)]
+pub enum Reply {
+    Hello(Settings),
+    Small,
+    Big,
+    Win(u32),
+    Lose(u32),
+    Err(String),
+}
+
+impl fmt::Display for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reply::Hello(s) => write!(f, "HELLO {} {} {}", s.min, s.max, s.max_attempts),
+            Reply::Small => write!(f, "SMALL"),
+            Reply::Big => write!(f, "BIG"),
+            Reply::Win(attempts) => write!(f, "WIN {}", attempts),
+            Reply::Lose(secret) => write!(f, "LOSE {}", secret),
+            Reply::Err(message) => write!(f, "ERR {}", message),
+        }
+    }
+}
+
+impl Reply {
+    pub fn parse(line: &str) -> Option<Reply> {
+        let mut words = line.split_whitespace();
+        let keyword = words.next()?;
+        let mut number = || words.next()?.parse::<u32>().ok();
+        match keyword {
+            "HELLO" => {
+                let (min, max, attempts) = (number()?, number()?, number()?);
+                Some(Reply::Hello(Settings::new(min, max, attempts)))
+            }
+            "SMALL" => Some(Reply::Small),
+            "BIG" => Some(Reply::Big),
+            "WIN" => Some(Reply::Win(number()?)),
+            "LOSE" => Some(Reply::Lose(number()?)),
+            "ERR" => Some(Reply::Err(
+                line.trim().trim_start_matches("ERR").trim().to_string(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a client line: `GUESS <number>`.
+fn parse_guess(line: &str) -> Option<u32> {
+    match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["GUESS", number] => number.parse().ok(),
+        _ => None,
+    }
+}
+
+fn send(writer: &mut impl Write, reply: &Reply) -> io::Result<()> {
+    writeln!(writer, "{}", reply)?;
+    writer.flush()
+}
+
+/// A read that hit the socket's timeout. Depending on the OS that is `WouldBlock`
+/// or `TimedOut`.
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+/// One remote game, seen from the server. Works on any reader and writer, so it
+/// is tested without a socket. Returns the number of attempts if the client won.
+///
+/// A line longer than `MAX_LINE`, or more than `MAX_INVALID` bad ones, ends
+/// the game with an `InvalidData` error: otherwise a client could grow the
+/// line without end, or keep the thread forever with guesses that never count.
+pub fn host_game(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    settings: Settings,
+    secret: u32,
+) -> io::Result<Option<u32>> {
+    send(writer, &Reply::Hello(settings))?;
+    let mut attempts = 0;
+    let mut invalid = 0;
+    while attempts < settings.max_attempts {
+        let mut line = String::new();
+        // One byte more than allowed, to tell a full line from a cut one.
+        let n = reader.by_ref().take(MAX_LINE + 1).read_line(&mut line)?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "client disconnected",
+            ));
+        }
+        if n as u64 > MAX_LINE {
+            let message = format!("lines are at most {} bytes", MAX_LINE);
+            send(writer, &Reply::Err(message.clone()))?;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        // A malformed or out-of-range guess costs no attempt, like at the
+        // keyboard, but only so many are allowed.
+        let guess = match parse_guess(&line) {
+            Some(guess) if guess >= settings.min && guess <= settings.max => Ok(guess),
+            Some(_) => Err(format!(
+                "the number is between {} and {}",
+                settings.min, settings.max
+            )),
+            None => Err("expected GUESS <number>".to_string()),
+        };
+        let guess = match guess {
+            Ok(guess) => guess,
+            Err(_) if invalid == MAX_INVALID => {
+                let message = "too many invalid guesses".to_string();
+                send(writer, &Reply::Err(message.clone()))?;
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            Err(message) => {
+                invalid += 1;
+                send(writer, &Reply::Err(message))?;
+                continue;
+            }
+        };
+        attempts += 1;
+        match check_guess(guess, secret) {
+            Ordering::Less => send(writer, &Reply::Small)?,
+            Ordering::Greater => send(writer, &Reply::Big)?,
+            Ordering::Equal => {
+                send(writer, &Reply::Win(attempts))?;
+                return Ok(Some(attempts));
+            }
+        }
+    }
+    send(writer, &Reply::Lose(secret))?;
+    Ok(None)
+}
+
+/// A place among the players being served, given back when dropped.
+struct Seat(Arc<AtomicUsize>);
+
+impl Seat {
+    /// A seat if fewer than `max` are taken.
+    fn take(taken: &Arc<AtomicUsize>, max: usize) -> Option<Seat> {
+        taken
+            .fetch_update(AtomicOrdering::SeqCst, AtomicOrdering::SeqCst, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Seat(Arc::clone(taken)))
+    }
+}
+
+impl Drop for Seat {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, AtomicOrdering::SeqCst);
+    }
+}
+
+/// Accepts players forever; each connection gets its own secret and thread, so a
+/// slow player never blocks the others. Secrets are drawn in the order players
+/// connect, so a seeded `secrets` hands out the same sequence every time.
+/// Beyond `MAX_PLAYERS` at once, a player is told the server is full.
+pub fn serve(
+    listener: TcpListener,
+    settings: Settings,
+    mut secrets: SecretSource,
+) -> io::Result<()> {
+    let taken = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Could not accept a connection: {}", e);
+                continue;
+            }
+        };
+        let Some(seat) = Seat::take(&taken, MAX_PLAYERS) else {
+            // Best effort: the connection is dropped either way.
+            let _ = send(&mut stream, &Reply::Err("the server is full".to_string()));
+            continue;
+        };
+        let secret = secrets.pick(settings);
+        thread::spawn(move || {
+            let _seat = seat;
+            let peer = stream
+                .peer_addr()
+                .map_or_else(|_| "unknown".to_string(), |addr| addr.to_string());
+            println!("{} joined.", peer);
+            match handle_client(stream, settings, secret) {
+                Ok(Some(attempts)) => println!("{} won in {} attempt(s).", peer, attempts),
+                Ok(None) => println!("{} ran out of attempts.", peer),
+                Err(e) if is_timeout(&e) => println!("{} timed out.", peer),
+                Err(e) => println!("{} left: {}", peer, e),
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_client(stream: TcpStream, settings: Settings, secret: u32) -> io::Result<Option<u32>> {
+    stream.set_read_timeout(Some(GUESS_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let result = host_game(&mut reader, &mut writer, settings, secret);
+    if let Err(e) = &result {
+        if is_timeout(e) {
+            // Best effort: the client may be gone already.
+            let _ = send(
+                &mut writer,
+                &Reply::Err("timed out waiting for a guess".to_string()),
+            );
+        }
+    }
+    result
+}
+
+/// Connects to `addr`, trying each address it resolves to (`localhost` may be
+/// both IPv6 and IPv4).
+pub fn connect(addr: &str) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "address resolved to nothing");
+    let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, REPLY_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// The client side: guesses typed in `game` are sent to the server, and its
+/// answers are shown.
+pub fn play_remote<R: BufRead, W: Write>(
+    game: &mut Game<R, W>,
+    server: &mut impl BufRead,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let settings = match read_reply(server)? {
+        Reply::Hello(settings) => settings,
+        other => return Err(protocol_error(&other)),
+    };
+    game.say(&format!(
+        "Connected! The number is between {} and {}; you have {} attempt(s).",
+        settings.min, settings.max, settings.max_attempts
+    ))?;
+    let mut attempts = 0;
+    while attempts < settings.max_attempts {
+        let prompt = format!(
+            "Please input your guess ({} attempt(s) left).",
+            settings.max_attempts - attempts
+        );
+        let guess = game.read_guess(&prompt, settings)?;
+        writeln!(writer, "GUESS {}", guess)?;
+        writer.flush()?;
+        match read_reply(server)? {
+            Reply::Small => game.say("Too small!")?,
+            Reply::Big => game.say("Too big!")?,
+            Reply::Win(attempts) => {
+                return game.say(&format!("You win in {} attempt(s)!", attempts));
+            }
+            Reply::Lose(secret) => {
+                return game.say(&format!(
+                    "Game over: no attempts left. The number was {}.",
+                    secret
+                ));
+            }
+            // The guess did not count; ask again.
+            Reply::Err(message) => {
+                game.say(&format!("Server: {}", message))?;
+                continue;
+            }
+            other => return Err(protocol_error(&other)),
+        }
+        attempts += 1;
+    }
+    // The last hint is followed by the verdict.
+    match read_reply(server)? {
+        Reply::Lose(secret) => game.say(&format!(
+            "Game over: no attempts left. The number was {}.",
+            secret
+        )),
+        other => Err(protocol_error(&other)),
+    }
+}
+
+fn read_reply(server: &mut impl BufRead) -> io::Result<Reply> {
+    let mut line = String::new();
+    if server.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionAborted,
+            "the server closed the connection",
+        ));
+    }
+    Reply::parse(&line).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unexpected message from the server: {}", line.trim()),
+        )
+    })
+}
+
+fn protocol_error(reply: &Reply) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected message from the server: {}", reply),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_game_protocol() {
+        let settings = Settings::new(1, 10, 3);
+        let mut client = &b"hello\nGUESS 11\nGUESS 5\nGUESS 8\nGUESS 7\n"[..];
+        let mut sent = Vec::new();
+        assert_eq!(
+            host_game(&mut client, &mut sent, settings, 7).unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            String::from_utf8(sent).unwrap(),
+            "HELLO 1 10 3\nERR expected GUESS <number>\nERR the number is between 1 and 10\nSMALL\nBIG\nWIN 3\n"
+        );
+
+        let mut client = &b"GUESS 1\nGUESS 2\n"[..];
+        let mut sent = Vec::new();
+        let err = host_game(&mut client, &mut sent, settings, 7).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            io::ErrorKind::UnexpectedEof,
+            "client left mid-game"
+        );
+
+        let mut client = &b"GUESS 1\nGUESS 2\nGUESS 3\n"[..];
+        let mut sent = Vec::new();
+        assert_eq!(
+            host_game(&mut client, &mut sent, settings, 7).unwrap(),
+            None
+        );
+        assert!(String::from_utf8(sent).unwrap().ends_with("LOSE 7\n"));
+    }
+
+    #[test]
+    fn test_host_game_drops_long_lines() {
+        let settings = Settings::new(1, 10, 3);
+        let long = format!("GUESS {}\n", "1".repeat(MAX_LINE as usize));
+        let mut client = long.as_bytes();
+        let mut sent = Vec::new();
+        let err = host_game(&mut client, &mut sent, settings, 7).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(String::from_utf8(sent)
+            .unwrap()
+            .ends_with("ERR lines are at most 64 bytes\n"));
+
+        // No newline at all: still only MAX_LINE + 1 bytes are read.
+        let mut client = io::repeat(b'1').take(1 << 20);
+        let mut reader = BufReader::new(&mut client);
+        let err = host_game(&mut reader, &mut Vec::new(), settings, 7).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_host_game_drops_clients_that_never_guess() {
+        let settings = Settings::new(1, 10, 3);
+        let lines = "GUESS 0\nnope\n".repeat(MAX_INVALID as usize);
+        let mut client = lines.as_bytes();
+        let mut sent = Vec::new();
+        let err = host_game(&mut client, &mut sent, settings, 7).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let sent = String::from_utf8(sent).unwrap();
+        assert_eq!(sent.lines().count(), 1 + MAX_INVALID as usize + 1);
+        assert!(sent.ends_with("ERR too many invalid guesses\n"));
+    }
+
+    #[test]
+    fn test_seats_are_limited_and_given_back() {
+        let taken = Arc::new(AtomicUsize::new(0));
+        let first = Seat::take(&taken, 2).unwrap();
+        let _second = Seat::take(&taken, 2).unwrap();
+        assert!(Seat::take(&taken, 2).is_none());
+        drop(first);
+        assert!(Seat::take(&taken, 2).is_some());
+    }
+
+    #[test]
+    fn test_reply_round_trip() {
+        for reply in [
+            Reply::Hello(Settings::new(1, 100, 8)),
+            Reply::Small,
+            Reply::Big,
+            Reply::Win(4),
+            Reply::Lose(42),
+            Reply::Err("slow down".to_string()),
+        ] {
+            assert_eq!(Reply::parse(&reply.to_string()), Some(reply));
+        }
+        assert_eq!(Reply::parse("HELLO 1 two 3"), None);
+        assert_eq!(Reply::parse(""), None);
+    }
+
+    #[test]
+    fn test_game_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let settings = Settings::new(1, 100, 8);
+        // A single-client server with a known secret.
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_client(stream, settings, 42).unwrap()
+        });
+
+        let stream = connect(&addr.to_string()).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut game = Game::new(&b"50\nx\n25\n42\n"[..], Vec::new());
+        play_remote(&mut game, &mut reader, &mut writer).unwrap();
+
+        assert_eq!(server.join().unwrap(), Some(3));
+        let output = String::from_utf8(game.into_parts().1).unwrap();
+        assert!(output.starts_with("Connected! The number is between 1 and 100"));
+        assert!(output.contains("Too big!"));
+        assert!(output.contains("Too small!"));
+        assert!(output.ends_with("You win in 3 attempt(s)!\n"));
+    }
+
+    #[test]
+    fn test_client_stops_when_out_of_attempts() {
+        let mut server = &b"HELLO 1 10 2\nSMALL\nBIG\nLOSE 3\n"[..];
+        let mut game = Game::new(&b"1\n9\n5\n"[..], Vec::new());
+        let mut sent = Vec::new();
+        play_remote(&mut game, &mut server, &mut sent).unwrap();
+        assert_eq!(String::from_utf8(sent).unwrap(), "GUESS 1\nGUESS 9\n");
+        let output = String::from_utf8(game.into_parts().1).unwrap();
+        assert!(output.ends_with("Too big!\nGame over: no attempts left. The number was 3.\n"));
+    }
+
+    #[test]
+    fn test_client_reports_a_vanished_server() {
+        let mut server = &b"HELLO 1 10 3\n"[..];
+        let mut game = Game::new(&b"5\n"[..], Vec::new());
+        let err = play_remote(&mut game, &mut server, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionAborted);
+    }
+}
@@ -0,0 +1,286 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How many results the leaderboard keeps.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// One won game, as stored in the scores file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub name: String,
+    pub attempts: u32,
+    pub seconds: u64,
+    pub min: u32,
+    pub max: u32,
+    pub score: u32,
+}
+
+/// Totals over every single-player game, won or lost.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub played: u32,
+    pub won: u32,
+    /// Attempts used over all games; a lost game used all of its attempts.
+    pub attempts: u64,
+    /// Wins in a row up to the last game.
+    pub streak: u32,
+    pub best_streak: u32,
+}
+
+impl Stats {
+    /// Counts one more game.
+    pub fn add(&mut self, won: bool, attempts: u32) {
+        self.played += 1;
+        self.attempts += u64::from(attempts);
+        if won {
+            self.won += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+    }
+
+    /// Attempts per game, or `None` before the first game.
+    pub fn average_attempts(&self) -> Option<f64> {
+        (self.played > 0).then(|| self.attempts as f64 / f64::from(self.played))
+    }
+
+    /// The share of games won, in percent.
+    pub fn win_rate(&self) -> u32 {
+        if self.played == 0 {
+            return 0;
+        }
+        (u64::from(self.won) * 100 / u64::from(self.played)) as u32
+    }
+}
+
+/// The best results, kept in a JSON file between runs, with the statistics in a
+/// second file next to it.
+///
+/// The files live in the user data directory (`~/.local/share/guessing-game/` on
+/// Linux, `~/Library/Application Support/guessing-game/` on macOS), so scores
+/// survive no matter where the game is started from.
+pub struct ScoreStore {
+    path: PathBuf,
+}
+
+impl ScoreStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        ScoreStore { path: path.into() }
+    }
+
+    /// The store in the user data directory, or `None` if the OS has none (rare).
+    pub fn in_data_dir() -> Option<Self> {
+        let dir = dirs::data_dir()?.join("guessing-game");
+        Some(ScoreStore::new(dir.join("scores.json")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `stats.json`, in the same directory as the scores.
+    pub fn stats_path(&self) -> PathBuf {
+        self.path.with_file_name("stats.json")
+    }
+
+    /// The saved results, best first. No file yet simply means no scores.
+    pub fn load(&self) -> io::Result<Vec<GameResult>> {
+        read_json(&self.path)
+    }
+
+    /// Adds a result and saves the top `LEADERBOARD_SIZE`; returns the new leaderboard.
+    pub fn record(&self, result: GameResult) -> io::Result<Vec<GameResult>> {
+        let mut results = self.load()?;
+        results.push(result);
+        rank(&mut results);
+        results.truncate(LEADERBOARD_SIZE);
+        write_json(&self.path, &results)?;
+        Ok(results)
+    }
+
+    /// The saved statistics; all zero before the first game.
+    pub fn load_stats(&self) -> io::Result<Stats> {
+        read_json(&self.stats_path())
+    }
+
+    /// Counts one more game in the statistics; returns the new totals.
+    pub fn record_stats(&self, won: bool, attempts: u32) -> io::Result<Stats> {
+        let mut stats = self.load_stats()?;
+        stats.add(won, attempts);
+        write_json(&self.stats_path(), &stats)?;
+        Ok(stats)
+    }
+
+    /// Deletes every saved score.
+    pub fn reset(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reads a JSON file; a missing file is the default value (no scores, zero games).
+fn read_json<T: DeserializeOwned + Default>(path: &Path) -> io::Result<T> {
+    match fs::read_to_string(path) {
+        // A corrupt file is reported as `InvalidData` rather than silently wiped.
+        Ok(json) => serde_json::from_str(&json).map_err(io::Error::from),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes a temporary file, then renames it over the old one: a crash midway
+/// never leaves a half-written file behind.
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(value).map_err(io::Error::from)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)
+}
+
+/// Best first: highest score, then fewest attempts, then fastest.
+fn rank(results: &mut [GameResult]) {
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.attempts.cmp(&b.attempts))
+            .then(a.seconds.cmp(&b.seconds))
+    });
+}
+
+/// The leaderboard as a text table.
+pub fn format_leaderboard(results: &[GameResult]) -> String {
+    if results.is_empty() {
+        return "No high scores yet.".to_string();
+    }
+    let mut table = format!(
+        "{:>3}  {:<16} {:>6} {:>8} {:>6}  {}\n",
+        "#", "Name", "Score", "Attempts", "Time", "Range"
+    );
+    for (i, r) in results.iter().enumerate() {
+        table.push_str(&format!(
+            "{:>3}  {:<16} {:>6} {:>8} {:>5}s  {}-{}\n",
+            i + 1,
+            r.name,
+            r.score,
+            r.attempts,
+            r.seconds,
+            r.min,
+            r.max
+        ));
+    }
+    table
+}
+
+/// The statistics as a few lines of text.
+pub fn format_stats(stats: &Stats) -> String {
+    let Some(average) = stats.average_attempts() else {
+        return "No games played yet.".to_string();
+    };
+    format!(
+        "Games played:     {}\nGames won:        {} ({}%)\nAverage attempts: {:.1}\nWin streak:       {} (best {})",
+        stats.played,
+        stats.won,
+        stats.win_rate(),
+        average,
+        stats.streak,
+        stats.best_streak
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, score: u32, attempts: u32) -> GameResult {
+        GameResult {
+            name: name.to_string(),
+            attempts,
+            seconds: 10,
+            min: 1,
+            max: 100,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_record_keeps_the_best_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScoreStore::new(dir.path().join("nested/scores.json"));
+        assert_eq!(store.load().unwrap(), Vec::new());
+
+        for i in 0..12 {
+            store
+                .record(result(&format!("p{}", i), i * 100, 5))
+                .unwrap();
+        }
+        let board = store.record(result("tie", 500, 3)).unwrap();
+
+        // Reloaded from disk: best first, only the top 10 kept.
+        assert_eq!(store.load().unwrap(), board);
+        assert_eq!(board.len(), LEADERBOARD_SIZE);
+        assert_eq!(board[0].name, "p11");
+        // Same score: fewer attempts ranks higher.
+        let names: Vec<&str> = board.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names[6..8], ["tie", "p5"]);
+        assert!(!names.contains(&"p0"));
+
+        store.reset().unwrap();
+        assert_eq!(store.load().unwrap(), Vec::new());
+        store.reset().unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScoreStore::new(dir.path().join("scores.json"));
+        fs::write(store.path(), "not json").unwrap();
+        let err = store.load().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_stats_track_streaks() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScoreStore::new(dir.path().join("scores.json"));
+        assert_eq!(store.load_stats().unwrap(), Stats::default());
+        assert_eq!(format_stats(&Stats::default()), "No games played yet.");
+
+        store.record_stats(true, 4).unwrap();
+        store.record_stats(true, 6).unwrap();
+        store.record_stats(false, 8).unwrap();
+        let stats = store.record_stats(true, 2).unwrap();
+
+        assert_eq!(store.load_stats().unwrap(), stats);
+        assert_eq!((stats.played, stats.won), (4, 3));
+        assert_eq!(stats.average_attempts(), Some(5.0));
+        // The loss broke the streak of 2.
+        assert_eq!((stats.streak, stats.best_streak), (1, 2));
+        let text = format_stats(&stats);
+        assert!(text.contains("Games won:        3 (75%)"));
+        assert!(text.contains("Average attempts: 5.0"));
+        assert!(text.ends_with("Win streak:       1 (best 2)"));
+
+        // The statistics have a file of their own: resetting the scores keeps them.
+        store.reset().unwrap();
+        assert_eq!(store.load_stats().unwrap(), stats);
+    }
+
+    #[test]
+    fn test_format_leaderboard() {
+        assert_eq!(format_leaderboard(&[]), "No high scores yet.");
+        let table = format_leaderboard(&[result("Ada", 900, 4)]);
+        assert!(table.contains("Ada"));
+        assert!(table.lines().nth(1).unwrap().starts_with("  1  Ada"));
+        assert!(table.contains("1-100"));
+    }
+}
@@ -0,0 +1,318 @@
+//! The secrets: what the player has to find, and where it comes from.
+//!
+//! A `Secret` is the part of a game that differs between guessing a number and
+//! guessing a word (`word::Word`); `Game::guess_loop` runs the attempts and the
+//! feedback for any of them.
+//!
+//! Every game draws its secrets from a `SecretSource`. Normally it is seeded
+//! from the OS, so each run is different; `--seed <n>` seeds it by hand to
+//! replay the same games, and `--daily` seeds it with today's date so that
+//! everyone playing on the same day gets the same puzzle.
+
+use crate::clues::Clue;
+use crate::{check_guess, Outcome, Settings};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::cmp::Ordering;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What a guess did.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    /// Found: the game is won.
+    Solved,
+    /// Closer to the answer without using an attempt, e.g. a letter of the word.
+    Right(String),
+    /// A wrong guess: it uses an attempt.
+    Wrong(String),
+}
+
+/// Something to find in a limited number of wrong guesses.
+pub trait Secret {
+    /// A parsed guess, printed back as "You guessed: ...".
+    type Guess: fmt::Display;
+
+    /// The guess typed in `input`, or what is wrong with it. An invalid guess
+    /// costs nothing.
+    fn parse(&self, input: &str) -> Result<Self::Guess, String>;
+
+    /// Judges a guess, and remembers what it revealed.
+    fn judge(&mut self, guess: Self::Guess) -> Verdict;
+
+    /// What the player has found so far, shown before each guess (e.g. `r _ s _`).
+    fn progress(&self) -> Option<String> {
+        None
+    }
+
+    /// The `given`-th hint (counting from 0), or why there is none right now.
+    fn hint(&self, given: u32) -> Result<String, String> {
+        let _ = given;
+        Err("There are no hints in this game.".to_string())
+    }
+
+    /// The answer, told to the player who ran out of attempts.
+    fn reveal(&self) -> String;
+}
+
+/// The classic secret: a number in `settings.min..=settings.max`.
+pub struct Number {
+    settings: Settings,
+    value: u32,
+    last_guess: Option<u32>,
+}
+
+impl Number {
+    pub fn new(settings: Settings, value: u32) -> Self {
+        Number {
+            settings,
+            value,
+            last_guess: None,
+        }
+    }
+}
+
+impl Secret for Number {
+    type Guess = u32;
+
+    fn parse(&self, input: &str) -> Result<u32, String> {
+        parse_number(input, self.settings)
+    }
+
+    fn judge(&mut self, guess: u32) -> Verdict {
+        self.last_guess = Some(guess);
+        // Compare the guess to the secret number.
+        match check_guess(guess, self.value) {
+            Ordering::Less => Verdict::Wrong("Too small!".to_string()),
+            Ordering::Greater => Verdict::Wrong("Too big!".to_string()),
+            Ordering::Equal => Verdict::Solved,
+        }
+    }
+
+    fn hint(&self, given: u32) -> Result<String, String> {
+        Clue::next(given, self.value, self.last_guess, self.settings)
+            .map(|clue| clue.to_string())
+            .ok_or_else(|| {
+                "Make a guess first: the next hint compares it with the number.".to_string()
+            })
+    }
+
+    fn reveal(&self) -> String {
+        self.value.to_string()
+    }
+}
+
+/// A typed guess for a number game, or the message explaining why it is not one.
+pub fn parse_number(input: &str, settings: Settings) -> Result<u32, String> {
+    // Parse the string into a u32 number. If parsing fails, ask again.
+    let guess: u32 = input
+        .trim()
+        .parse()
+        .map_err(|_| "Please type a valid positive number!".to_string())?;
+    if guess < settings.min || guess > settings.max {
+        return Err(format!(
+            "The number is between {} and {}!",
+            settings.min, settings.max
+        ));
+    }
+    Ok(guess)
+}
+
+/// A seedable random number generator for secrets.
+///
+/// ChaCha8 rather than `StdRng`: rand documents that `StdRng` may change
+/// algorithm between versions, while ChaCha8 gives the same numbers for the
+/// same seed on every machine and every build. That is what makes a daily
+/// puzzle the same for everyone.
+pub struct SecretSource {
+    rng: ChaCha8Rng,
+}
+
+impl SecretSource {
+    /// Reproducible with `Some(seed)`, different every run with `None`.
+    pub fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => SecretSource::seeded(seed),
+            None => SecretSource::random(),
+        }
+    }
+
+    /// A generator seeded by the OS: a different game every run.
+    pub fn random() -> Self {
+        SecretSource {
+            rng: ChaCha8Rng::from_entropy(),
+        }
+    }
+
+    /// A generator that picks the same secrets every time for the same seed.
+    pub fn seeded(seed: u64) -> Self {
+        SecretSource {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// The next secret, anywhere in `settings.min..=settings.max`.
+    pub fn pick(&mut self, settings: Settings) -> u32 {
+        self.rng.gen_range(settings.min..=settings.max)
+    }
+
+    /// One of `words`, e.g. the secret of a word game. `words` must not be empty.
+    pub fn choose<'a>(&mut self, words: &[&'a str]) -> &'a str {
+        words
+            .choose(&mut self.rng)
+            .expect("a word list is never empty")
+    }
+}
+
+/// A calendar day (UTC), the key of the daily challenge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Day {
+    /// Days since 1970-01-01.
+    days: i64,
+}
+
+impl Day {
+    /// Today in UTC, so players in different time zones share the puzzle of the
+    /// same date at the same moment.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Day::from_days(i64::try_from(seconds / 86_400).unwrap_or(0))
+    }
+
+    pub fn from_days(days: i64) -> Self {
+        Day { days }
+    }
+
+    /// The seed of this day's puzzle: the day number, so `--seed` with it replays
+    /// an old daily challenge.
+    pub fn seed(self) -> u64 {
+        self.days as u64
+    }
+
+    /// The (year, month, day) of the Gregorian calendar.
+    ///
+    /// Howard Hinnant's `civil_from_days`: shift the year to start in March, so
+    /// the leap day is the last day of the year, then count 400-year eras.
+    fn civil(self) -> (i64, u32, u32) {
+        let z = self.days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        (year, month, day)
+    }
+}
+
+impl fmt::Display for Day {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = self.civil();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+/// A one-line summary of a daily challenge to paste into a chat. It never shows
+/// the secret, so it does not spoil the puzzle for others.
+pub fn share(day: Day, settings: Settings, outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Won { attempts, .. } => format!(
+            "Guessing game daily {}: solved in {} guess(es) ({}/{}).",
+            day, attempts, attempts, settings.max_attempts
+        ),
+        Outcome::Lost { .. } => format!(
+            "Guessing game daily {}: not solved (X/{}).",
+            day, settings.max_attempts
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Difficulty;
+    use std::time::Duration;
+
+    #[test]
+    fn test_same_seed_same_secrets() {
+        let settings = Difficulty::Hard.settings();
+        let mut first = SecretSource::seeded(42);
+        let mut second = SecretSource::new(Some(42));
+        let secrets: Vec<u32> = (0..5).map(|_| first.pick(settings)).collect();
+        assert_eq!(
+            secrets,
+            (0..5).map(|_| second.pick(settings)).collect::<Vec<_>>()
+        );
+        assert!(secrets.iter().all(|secret| (1..=1000).contains(secret)));
+
+        // Another seed, another game.
+        let mut other = SecretSource::seeded(43);
+        assert_ne!(
+            secrets,
+            (0..5).map(|_| other.pick(settings)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_number_secret() {
+        let settings = Difficulty::Medium.settings();
+        let mut number = Number::new(settings, 42);
+        assert_eq!(number.parse(" 7\n"), Ok(7));
+        assert_eq!(
+            number.parse("-1"),
+            Err("Please type a valid positive number!".to_string())
+        );
+        assert_eq!(
+            number.parse("101"),
+            Err("The number is between 1 and 100!".to_string())
+        );
+        // The "near" hint needs a guess to compare with.
+        assert!(number.hint(2).is_err());
+        assert_eq!(number.judge(50), Verdict::Wrong("Too big!".to_string()));
+        assert_eq!(
+            number.hint(2),
+            Ok("The number is within 9 of your last guess (50).".to_string())
+        );
+        assert_eq!(number.judge(42), Verdict::Solved);
+        assert_eq!(number.reveal(), "42");
+    }
+
+    #[test]
+    fn test_day_display() {
+        assert_eq!(Day::from_days(0).to_string(), "1970-01-01");
+        assert_eq!(Day::from_days(-1).to_string(), "1969-12-31");
+        // 2000 was a leap year (divisible by 400).
+        assert_eq!(Day::from_days(11_016).to_string(), "2000-02-29");
+        assert_eq!(Day::from_days(11_017).to_string(), "2000-03-01");
+        assert_eq!(Day::from_days(20_742).to_string(), "2026-10-16");
+        assert_eq!(Day::from_days(20_742).seed(), 20_742);
+    }
+
+    #[test]
+    fn test_share() {
+        let day = Day::from_days(20_742);
+        let settings = Difficulty::Medium.settings();
+        let won = Outcome::Won {
+            attempts: 6,
+            elapsed: Duration::from_secs(30),
+            score: 420,
+        };
+        assert_eq!(
+            share(day, settings, &won),
+            "Guessing game daily 2026-10-16: solved in 6 guess(es) (6/8)."
+        );
+        let lost = share(day, settings, &Outcome::Lost { secret: 37 });
+        assert_eq!(lost, "Guessing game daily 2026-10-16: not solved (X/8).");
+        assert!(!lost.contains("37"));
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+/// The player's answer to one of the computer's guesses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hint {
+    /// The secret is higher than the guess.
+    Higher,
+    /// The secret is lower than the guess.
+    Lower,
+    Correct,
+}
+
+impl Hint {
+    /// Parses an answer: the word or its first letter, in any case.
+    pub fn parse(input: &str) -> Option<Hint> {
+        match input.trim().to_lowercase().as_str() {
+            "h" | "higher" => Some(Hint::Higher),
+            "l" | "lower" => Some(Hint::Lower),
+            "c" | "correct" => Some(Hint::Correct),
+            _ => None,
+        }
+    }
+}
+
+/// The answers so far leave no possible number: the player made a mistake (or cheated).
+#[derive(Debug, PartialEq)]
+pub struct Contradiction {
+    /// The largest guess the player called too low, if any.
+    pub above: Option<u32>,
+    /// The smallest guess the player called too high, if any.
+    pub below: Option<u32>,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.above, self.below) {
+            (Some(above), Some(below)) => write!(
+                f,
+                "you said it is higher than {} and lower than {}",
+                above, below
+            ),
+            (Some(above), None) => write!(f, "you said it is higher than {}", above),
+            (None, Some(below)) => write!(f, "you said it is lower than {}", below),
+            (None, None) => write!(f, "the range is empty"),
+        }
+    }
+}
+
+/// A way for the computer to pick its guesses. Keeping it apart from reading and
+/// printing means a strategy can be tested against a simulated player.
+pub trait Strategy {
+    /// The next number to try, or why no number fits the answers so far.
+    fn guess(&self) -> Result<u32, Contradiction>;
+    /// Takes the player's answer to `guess` into account.
+    fn learn(&mut self, guess: u32, hint: Hint);
+}
+
+/// Always guesses the middle of the numbers still possible, halving them each
+/// time: 1..=100 takes at most 7 guesses, 1..=1000 at most 10.
+pub struct BinarySearch {
+    // Signed and wider than `u32`, so `guess + 1` and `guess - 1` never overflow:
+    // "lower than 0" simply makes `high` -1, an empty range.
+    low: i64,
+    high: i64,
+    above: Option<u32>,
+    below: Option<u32>,
+}
+
+impl BinarySearch {
+    pub fn new(min: u32, max: u32) -> Self {
+        BinarySearch {
+            low: i64::from(min),
+            high: i64::from(max),
+            above: None,
+            below: None,
+        }
+    }
+}
+
+impl Strategy for BinarySearch {
+    fn guess(&self) -> Result<u32, Contradiction> {
+        if self.low > self.high {
+            return Err(Contradiction {
+                above: self.above,
+                below: self.below,
+            });
+        }
+        // Both bounds come from `u32` values, so the midpoint fits in a `u32`.
+        Ok(((self.low + self.high) / 2) as u32)
+    }
+
+    fn learn(&mut self, guess: u32, hint: Hint) {
+        // `low` may pass `high` here: `guess` then reports the contradiction.
+        match hint {
+            Hint::Higher => {
+                self.low = self.low.max(i64::from(guess) + 1);
+                self.above = self.above.max(Some(guess));
+            }
+            Hint::Lower => {
+                self.high = self.high.min(i64::from(guess) - 1);
+                self.below = Some(self.below.map_or(guess, |b| b.min(guess)));
+            }
+            Hint::Correct => {
+                self.low = i64::from(guess);
+                self.high = i64::from(guess);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays a whole game against an honest player; returns the number of guesses.
+    fn solve(min: u32, max: u32, secret: u32) -> u32 {
+        let mut strategy = BinarySearch::new(min, max);
+        for attempts in 1.. {
+            let guess = strategy
+                .guess()
+                .expect("an honest player never contradicts");
+            let hint = match guess.cmp(&secret) {
+                std::cmp::Ordering::Less => Hint::Higher,
+                std::cmp::Ordering::Greater => Hint::Lower,
+                std::cmp::Ordering::Equal => return attempts,
+            };
+            strategy.learn(guess, hint);
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_binary_search_finds_every_number() {
+        for secret in 1..=100 {
+            assert!(solve(1, 100, secret) <= 7, "secret {}", secret);
+        }
+        assert!(solve(0, u32::MAX, 0) <= 32);
+        assert!(solve(0, u32::MAX, u32::MAX) <= 33);
+    }
+
+    #[test]
+    fn test_contradiction_is_detected() {
+        let mut strategy = BinarySearch::new(1, 100);
+        strategy.learn(50, Hint::Higher);
+        strategy.learn(75, Hint::Lower);
+        strategy.learn(51, Hint::Lower);
+        let contradiction = strategy.guess().unwrap_err();
+        assert_eq!(contradiction.above, Some(50));
+        assert_eq!(contradiction.below, Some(51));
+        assert_eq!(
+            contradiction.to_string(),
+            "you said it is higher than 50 and lower than 51"
+        );
+
+        // "Lower" than the smallest possible number is a contradiction too.
+        let mut strategy = BinarySearch::new(1, 10);
+        strategy.learn(1, Hint::Lower);
+        assert!(strategy.guess().is_err());
+        let mut strategy = BinarySearch::new(0, 10);
+        strategy.learn(0, Hint::Lower);
+        assert!(strategy.guess().is_err());
+    }
+
+    #[test]
+    fn test_hint_parse() {
+        assert_eq!(Hint::parse("H\n"), Some(Hint::Higher));
+        assert_eq!(Hint::parse(" lower "), Some(Hint::Lower));
+        assert_eq!(Hint::parse("c"), Some(Hint::Correct));
+        assert_eq!(Hint::parse("maybe"), None);
+    }
+}
@@ -0,0 +1,447 @@
+//! `--tui`: the single-player game on a full-screen terminal interface.
+//!
+//! The screen shows the numbers still possible as a bar that shrinks with every
+//! hint, the guesses so far colored from cold (far from the secret) to hot, and
+//! the guess being typed. What is on screen comes from a `Board`, which knows
+//! nothing about terminals: tests drive it directly and draw it on ratatui's
+//! `TestBackend`.
+
+use crate::game::Game;
+use crate::scores::ScoreStore;
+use crate::{check_guess, score, Outcome, Settings};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Ordering;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::Instant;
+
+/// How close a guess was to the secret, relative to the size of the range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Temperature {
+    Hot,
+    Warm,
+    Cool,
+    Cold,
+}
+
+impl Temperature {
+    fn of(guess: u32, secret: u32, settings: Settings) -> Self {
+        let distance = u64::from(guess.abs_diff(secret));
+        let size = u64::from(settings.max - settings.min) + 1;
+        // The distance as a percentage of the range: 5 away is hot in 1..=100,
+        // but still cool in 1..=10.
+        match distance * 100 / size {
+            0..=4 => Temperature::Hot,
+            5..=14 => Temperature::Warm,
+            15..=34 => Temperature::Cool,
+            _ => Temperature::Cold,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Temperature::Hot => "hot",
+            Temperature::Warm => "warm",
+            Temperature::Cool => "cool",
+            Temperature::Cold => "cold",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Temperature::Hot => Color::Red,
+            Temperature::Warm => Color::Yellow,
+            Temperature::Cool => Color::Cyan,
+            Temperature::Cold => Color::Blue,
+        }
+    }
+}
+
+/// One game as shown on screen: the secret, what the hints revealed, and the
+/// guess being typed.
+struct Board {
+    settings: Settings,
+    secret: u32,
+    /// The numbers still possible, `low..=high`, narrowed by every hint.
+    low: u32,
+    high: u32,
+    guesses: Vec<(u32, Ordering)>,
+    input: String,
+    message: String,
+    started: Instant,
+    outcome: Option<Outcome>,
+}
+
+impl Board {
+    fn new(settings: Settings, secret: u32) -> Self {
+        Board {
+            settings,
+            secret,
+            low: settings.min,
+            high: settings.max,
+            guesses: Vec::new(),
+            input: String::new(),
+            message: "Type a number and press Enter.".to_string(),
+            started: Instant::now(),
+            outcome: None,
+        }
+    }
+
+    fn attempts_left(&self) -> u32 {
+        self.settings.max_attempts - self.guesses.len() as u32
+    }
+
+    /// Only digits are accepted; ten of them are enough for any `u32`.
+    fn type_char(&mut self, c: char) {
+        if self.outcome.is_none() && c.is_ascii_digit() && self.input.len() < 10 {
+            self.input.push(c);
+        }
+    }
+
+    fn erase(&mut self) {
+        self.input.pop();
+    }
+
+    /// Plays the typed guess. Like at the plain prompt, invalid and out-of-range
+    /// input does not cost an attempt.
+    fn submit(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+        let input = std::mem::take(&mut self.input);
+        let guess: u32 = match input.parse() {
+            Ok(num) => num,
+            Err(_) => {
+                self.message = "Please type a valid positive number!".to_string();
+                return;
+            }
+        };
+        if guess < self.settings.min || guess > self.settings.max {
+            self.message = format!(
+                "The number is between {} and {}!",
+                self.settings.min, self.settings.max
+            );
+            return;
+        }
+
+        let ordering = check_guess(guess, self.secret);
+        self.guesses.push((guess, ordering));
+        let attempts = self.guesses.len() as u32;
+        // A guess outside `low..=high` may teach nothing new, hence `max` and `min`.
+        // No overflow: a guess below the secret is below `u32::MAX`, one above it is above 0.
+        match ordering {
+            Ordering::Less => {
+                self.low = self.low.max(guess + 1);
+                self.message = format!("{} is too small!", guess);
+            }
+            Ordering::Greater => {
+                self.high = self.high.min(guess - 1);
+                self.message = format!("{} is too big!", guess);
+            }
+            Ordering::Equal => {
+                (self.low, self.high) = (guess, guess);
+                let elapsed = self.started.elapsed();
+                let score = score(self.settings, attempts, elapsed);
+                self.message = format!(
+                    "You win in {} attempt(s)! Score: {}. Press any key.",
+                    attempts, score
+                );
+                self.outcome = Some(Outcome::Won {
+                    attempts,
+                    elapsed,
+                    score,
+                });
+                return;
+            }
+        }
+        if self.attempts_left() == 0 {
+            self.message = format!(
+                "Game over: no attempts left. The number was {}. Press any key.",
+                self.secret
+            );
+            self.outcome = Some(Outcome::Lost {
+                secret: self.secret,
+            });
+        }
+    }
+}
+
+/// `--tui`: the difficulty is picked at the plain prompt, the game is played on
+/// the full screen, then the name and the high scores are back on the plain terminal.
+pub fn run<R: BufRead, W: Write>(
+    game: &mut Game<R, W>,
+    store: Option<&ScoreStore>,
+) -> io::Result<()> {
+    if !io::stdout().is_terminal() {
+        return Err(io::Error::other("--tui needs a terminal"));
+    }
+    let settings = game.choose_settings()?;
+    let secret = game.pick_secret(settings);
+
+    // Raw mode (every key press arrives at once, nothing is echoed) on a separate
+    // screen, so the terminal looks untouched afterwards.
+    let mut terminal = ratatui::try_init()?;
+    let outcome = play(&mut terminal, settings, secret);
+    // Restore the terminal before anything else, even if the game failed.
+    ratatui::restore();
+
+    match outcome? {
+        Some(outcome) => game.finish_single(settings, outcome, store),
+        None => game.say("Bye!"),
+    }
+}
+
+/// The event loop: redraw, wait for a key, update the board. Returns how the game
+/// ended, or `None` if the player left with Esc or Ctrl+C.
+fn play(
+    terminal: &mut DefaultTerminal,
+    settings: Settings,
+    secret: u32,
+) -> io::Result<Option<Outcome>> {
+    let mut board = Board::new(settings, secret);
+    loop {
+        terminal.draw(|frame| draw(frame, &board))?;
+        // Other events (a resized window, ...) just trigger a redraw.
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        // Some terminals (and Windows) also report key releases.
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        // The final screen stays up until the next key.
+        if board.outcome.is_some() {
+            return Ok(board.outcome.take());
+        }
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Char(c) => board.type_char(c),
+            KeyCode::Backspace => board.erase(),
+            KeyCode::Enter => board.submit(),
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, board: &Board) {
+    let [range_area, history_area, input_area, message_area] = Layout::vertical([
+        Constraint::Length(5),
+        Constraint::Min(3),
+        Constraint::Length(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_range(frame, range_area, board);
+    draw_history(frame, history_area, board);
+
+    let input = Paragraph::new(format!("{}_", board.input)).block(
+        Block::bordered()
+            .title(" Your guess ")
+            .title_bottom(" Enter: guess · Backspace: erase · Esc: quit "),
+    );
+    frame.render_widget(input, input_area);
+    frame.render_widget(Paragraph::new(board.message.as_str()).bold(), message_area);
+}
+
+/// The whole range as a bar: bright where the secret may still be, dim where the
+/// hints ruled it out.
+fn draw_range(frame: &mut Frame, area: Rect, board: &Board) {
+    let settings = board.settings;
+    let block = Block::bordered().title(format!(
+        " Guess the number between {} and {} ",
+        settings.min, settings.max
+    ));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let bar: Vec<Span> = range_cells(settings, board.low, board.high, inner.width)
+        .into_iter()
+        .map(|possible| {
+            if possible {
+                Span::styled("█", Style::new().fg(Color::Green))
+            } else {
+                Span::styled("░", Style::new().fg(Color::DarkGray))
+            }
+        })
+        .collect();
+    // The bounds under both ends of the bar.
+    let max = settings.max.to_string();
+    let padding = (inner.width as usize).saturating_sub(max.len());
+    let bounds = format!("{:<padding$}{}", settings.min, max);
+    let still_possible = u64::from(board.high - board.low) + 1;
+    let status = format!(
+        "Still possible: {}-{} ({} number(s)), {} attempt(s) left",
+        board.low,
+        board.high,
+        still_possible,
+        board.attempts_left()
+    );
+
+    let lines = vec![
+        Line::from(bar),
+        Line::from(bounds).dim(),
+        Line::from(status),
+    ];
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Every guess with its hint, colored by how close it was. The latest guesses
+/// stay visible when the list is taller than the panel.
+fn draw_history(frame: &mut Frame, area: Rect, board: &Board) {
+    let block = Block::bordered().title(" History ");
+    let visible = block.inner(area).height as usize;
+    let items: Vec<ListItem> = board
+        .guesses
+        .iter()
+        .enumerate()
+        .skip(board.guesses.len().saturating_sub(visible))
+        .map(|(i, &(guess, ordering))| {
+            let temperature = Temperature::of(guess, board.secret, board.settings);
+            let (hint, style) = match ordering {
+                Ordering::Less => ("too small", Style::new().fg(temperature.color())),
+                Ordering::Greater => ("too big", Style::new().fg(temperature.color())),
+                Ordering::Equal => ("found it!", Style::new().fg(Color::Green).bold()),
+            };
+            let label = if ordering == Ordering::Equal {
+                ""
+            } else {
+                temperature.label()
+            };
+            ListItem::new(format!(
+                "#{:<3} {:>10}  {:<9}  {}",
+                i + 1,
+                guess,
+                hint,
+                label
+            ))
+            .style(style)
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+/// Splits the range into `width` cells, each standing for an equal share of the
+/// numbers; a cell is `true` if one of its numbers is still possible.
+fn range_cells(settings: Settings, low: u32, high: u32, width: u16) -> Vec<bool> {
+    // `u64`: the size of `0..=u32::MAX` does not fit in a `u32`.
+    let min = u64::from(settings.min);
+    let size = u64::from(settings.max - settings.min) + 1;
+    let width = u64::from(width);
+    (0..width)
+        .map(|i| {
+            let first = min + size * i / width;
+            // With more cells than numbers, neighbouring cells share a number.
+            let last = (min + size * (i + 1) / width).saturating_sub(1).max(first);
+            first <= u64::from(high) && last >= u64::from(low)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Difficulty;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn type_guess(board: &mut Board, input: &str) {
+        input.chars().for_each(|c| board.type_char(c));
+        board.submit();
+    }
+
+    #[test]
+    fn test_board_narrows_the_range_until_a_win() {
+        let mut board = Board::new(Difficulty::Medium.settings(), 42);
+        type_guess(&mut board, "50");
+        assert_eq!((board.low, board.high), (1, 49));
+        assert_eq!(board.message, "50 is too big!");
+
+        // Letters are ignored, an empty or out-of-range guess is free.
+        type_guess(&mut board, "x");
+        assert_eq!(board.message, "Please type a valid positive number!");
+        type_guess(&mut board, "1000");
+        assert_eq!(board.message, "The number is between 1 and 100!");
+        type_guess(&mut board, "25");
+        assert_eq!((board.low, board.high), (26, 49));
+        // Guessing outside what is left teaches nothing new.
+        type_guess(&mut board, "10");
+        assert_eq!((board.low, board.high), (26, 49));
+        assert_eq!(board.attempts_left(), 5);
+
+        type_guess(&mut board, "42");
+        assert!(matches!(
+            board.outcome,
+            Some(Outcome::Won { attempts: 4, .. })
+        ));
+        // The game is over: keys do nothing until the board is closed.
+        type_guess(&mut board, "7");
+        assert_eq!(board.guesses.len(), 4);
+    }
+
+    #[test]
+    fn test_board_runs_out_of_attempts() {
+        let mut board = Board::new(Difficulty::Easy.settings(), 10);
+        for guess in ["1", "2", "3", "4"] {
+            type_guess(&mut board, guess);
+            assert!(board.outcome.is_none());
+        }
+        type_guess(&mut board, "5");
+        assert_eq!(board.outcome, Some(Outcome::Lost { secret: 10 }));
+        assert!(board.message.starts_with("Game over"));
+    }
+
+    #[test]
+    fn test_temperature_and_range_cells() {
+        let medium = Difficulty::Medium.settings();
+        assert_eq!(Temperature::of(48, 50, medium), Temperature::Hot);
+        assert_eq!(Temperature::of(60, 50, medium), Temperature::Warm);
+        assert_eq!(Temperature::of(1, 100, medium), Temperature::Cold);
+        // The same distance is colder in a smaller range.
+        assert_eq!(
+            Temperature::of(3, 4, Difficulty::Easy.settings()),
+            Temperature::Warm
+        );
+
+        // 1..=100 on 10 cells: each cell is 10 numbers, 26..=49 touches cells 2 to 4.
+        let cells = range_cells(medium, 26, 49, 10);
+        let expected = [
+            false, false, true, true, true, false, false, false, false, false,
+        ];
+        assert_eq!(cells, expected);
+        // More cells than numbers, and the widest possible range.
+        assert_eq!(range_cells(Settings::new(1, 3, 2), 2, 2, 6).len(), 6);
+        assert!(range_cells(Settings::new(0, u32::MAX, 33), u32::MAX, u32::MAX, 80)[79]);
+    }
+
+    #[test]
+    fn test_draw_shows_range_and_history() {
+        let mut board = Board::new(Difficulty::Medium.settings(), 42);
+        type_guess(&mut board, "50");
+        type_guess(&mut board, "25");
+        board.type_char('4');
+
+        let mut terminal = Terminal::new(TestBackend::new(70, 14)).unwrap();
+        terminal.draw(|frame| draw(frame, &board)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("Guess the number between 1 and 100"));
+        assert!(screen.contains("Still possible: 26-49 (24 number(s)), 6 attempt(s) left"));
+        assert!(screen.contains("#1           50  too big    warm"));
+        assert!(screen.contains("#2           25  too small  cool"));
+        assert!(screen.contains("4_"));
+        assert!(screen.contains("25 is too small!"));
+        assert!(screen.contains('█') && screen.contains('░'));
+    }
+}
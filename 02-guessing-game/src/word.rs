@@ -0,0 +1,160 @@
+//! `--mode word`: hangman on the same guessing loop as the number game.
+//!
+//! The player guesses one letter at a time, or the whole word at once. A letter
+//! that is in the word is free and shows up in the pattern (`r _ s _`); a wrong
+//! letter or a wrong word costs one of the `LIVES`.
+
+use crate::secret::{Secret, Verdict};
+use std::fmt;
+
+/// Wrong guesses allowed: the head, body, two arms and two legs of the hangman.
+pub const LIVES: u32 = 6;
+
+/// One word per line, built into the binary so the game needs no files.
+const WORDS: &str = include_str!("words.txt");
+
+/// The words a game can pick from.
+pub fn list() -> Vec<&'static str> {
+    WORDS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// A guess in the word game.
+#[derive(Debug, PartialEq)]
+pub enum WordGuess {
+    Letter(char),
+    Word(String),
+}
+
+impl fmt::Display for WordGuess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordGuess::Letter(letter) => write!(f, "{}", letter),
+            WordGuess::Word(word) => write!(f, "{}", word),
+        }
+    }
+}
+
+/// The secret word and the letters tried so far.
+pub struct Word {
+    word: String,
+    tried: Vec<char>,
+}
+
+impl Word {
+    pub fn new(word: &str) -> Self {
+        Word {
+            word: word.to_lowercase(),
+            tried: Vec::new(),
+        }
+    }
+
+    fn found_all(&self) -> bool {
+        self.word.chars().all(|letter| self.tried.contains(&letter))
+    }
+}
+
+impl Secret for Word {
+    type Guess = WordGuess;
+
+    fn parse(&self, input: &str) -> Result<WordGuess, String> {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() || !input.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err("Please type a letter or the whole word!".to_string());
+        }
+        let mut letters = input.chars();
+        match (letters.next(), letters.next()) {
+            (Some(letter), None) if self.tried.contains(&letter) => {
+                Err(format!("You already tried '{}'!", letter))
+            }
+            (Some(letter), None) => Ok(WordGuess::Letter(letter)),
+            _ if input.len() == self.word.len() => Ok(WordGuess::Word(input)),
+            _ => Err(format!("The word has {} letters!", self.word.len())),
+        }
+    }
+
+    fn judge(&mut self, guess: WordGuess) -> Verdict {
+        match guess {
+            WordGuess::Letter(letter) => {
+                self.tried.push(letter);
+                let count = self.word.matches(letter).count();
+                if count == 0 {
+                    Verdict::Wrong(format!("No '{}' in the word.", letter))
+                } else if self.found_all() {
+                    Verdict::Solved
+                } else {
+                    Verdict::Right(format!("Yes, the word has {} '{}'.", count, letter))
+                }
+            }
+            WordGuess::Word(word) if word == self.word => Verdict::Solved,
+            WordGuess::Word(word) => Verdict::Wrong(format!("It is not '{}'.", word)),
+        }
+    }
+
+    fn progress(&self) -> Option<String> {
+        let pattern: Vec<String> = self
+            .word
+            .chars()
+            .map(|letter| {
+                if self.tried.contains(&letter) {
+                    letter.to_string()
+                } else {
+                    "_".to_string()
+                }
+            })
+            .collect();
+        Some(pattern.join(" "))
+    }
+
+    fn reveal(&self) -> String {
+        self.word.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_list() {
+        let words = list();
+        assert!(words.len() >= 20);
+        for word in words {
+            // Every word can be typed as a guess, and none is taken for a command.
+            assert!(word.chars().all(|c| c.is_ascii_lowercase()), "{}", word);
+            assert_ne!(word, "hint");
+            assert_eq!(
+                Word::new(word).parse(word),
+                Ok(WordGuess::Word(word.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_letters_fill_the_pattern() {
+        let mut secret = Word::new("Cargo");
+        assert_eq!(secret.progress().unwrap(), "_ _ _ _ _");
+        assert_eq!(
+            secret.judge(WordGuess::Letter('a')),
+            Verdict::Right("Yes, the word has 1 'a'.".to_string())
+        );
+        assert_eq!(
+            secret.judge(WordGuess::Letter('e')),
+            Verdict::Wrong("No 'e' in the word.".to_string())
+        );
+        assert_eq!(secret.progress().unwrap(), "_ a _ _ _");
+        assert_eq!(
+            secret.judge(WordGuess::Word("cargs".into())),
+            Verdict::Wrong("It is not 'cargs'.".to_string())
+        );
+        for letter in ['c', 'r', 'g'] {
+            secret.judge(WordGuess::Letter(letter));
+        }
+        // The last missing letter wins the game.
+        assert_eq!(secret.judge(WordGuess::Letter('o')), Verdict::Solved);
+        assert_eq!(secret.reveal(), "cargo");
+    }
+}
@@ -0,0 +1,250 @@
+//! `MessageBus`: handlers subscribe to a variant of `Message`, and each
+//! message published is handed to the handlers of its variant.
+//!
+//! `Message::call` decides what each variant does with a `match`, fixed at
+//! compile time. A bus decides at run time, from whoever subscribed: the
+//! match only finds the message's `Kind`, the key to look handlers up by.
+//!
+//! Publishing only queues a message; nothing runs until `process`, which
+//! takes the messages in the order they came and gives each to the handlers
+//! of its kind, in the order they subscribed. A handler that fails does not
+//! stop the others: its error goes in the report, next to its message. A
+//! message that no handler wants is reported as well, not silently dropped.
+
+use crate::{Kind, Message};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// What a handler does with a message: a line saying what was done, or why
+/// it could not be.
+pub type Handler = Box<dyn FnMut(&Message) -> Result<String, String>>;
+
+struct Subscription {
+    kind: Kind,
+    name: &'static str,
+    handler: Handler,
+}
+
+#[derive(Default)]
+pub struct MessageBus {
+    /// In the order they subscribed, which is the order they run in.
+    subscriptions: Vec<Subscription>,
+    queue: VecDeque<Message>,
+}
+
+/// A message given to one handler, and what came of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery {
+    pub handler: &'static str,
+    pub message: Message,
+    pub result: Result<String, String>,
+}
+
+/// What one `process` did: every delivery, in the order they ran, and the
+/// messages no handler subscribed to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub deliveries: Vec<Delivery>,
+    pub unhandled: Vec<Message>,
+}
+
+impl MessageBus {
+    pub fn new() -> MessageBus {
+        MessageBus::default()
+    }
+
+    /// Adds `handler`, called `name` in reports, for the messages of `kind`.
+    /// A handler can subscribe to several kinds, under the same name.
+    pub fn subscribe<F>(&mut self, kind: Kind, name: &'static str, handler: F)
+    where
+        F: FnMut(&Message) -> Result<String, String> + 'static,
+    {
+        self.subscriptions.push(Subscription {
+            kind,
+            name,
+            handler: Box::new(handler),
+        });
+    }
+
+    /// Queues `message` for the next `process`.
+    pub fn publish(&mut self, message: Message) {
+        self.queue.push_back(message);
+    }
+
+    /// The messages queued and not processed yet.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Hands every queued message to its handlers, and empties the queue.
+    pub fn process(&mut self) -> Report {
+        let mut report = Report::default();
+        while let Some(message) = self.queue.pop_front() {
+            let kind = message.kind();
+            let mut handled = false;
+            for subscription in self.subscriptions.iter_mut() {
+                if subscription.kind != kind {
+                    continue;
+                }
+                handled = true;
+                let result = (subscription.handler)(&message);
+                report.deliveries.push(Delivery {
+                    handler: subscription.name,
+                    message: message.clone(),
+                    result,
+                });
+            }
+            if !handled {
+                report.unhandled.push(message);
+            }
+        }
+        report
+    }
+}
+
+impl Report {
+    /// The deliveries whose handler failed.
+    pub fn errors(&self) -> impl Iterator<Item = &Delivery> {
+        self.deliveries.iter().filter(|d| d.result.is_err())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for delivery in &self.deliveries {
+            match &delivery.result {
+                Ok(done) => writeln!(f, "{:<10} {}", delivery.handler, done)?,
+                Err(error) => writeln!(
+                    f,
+                    "{:<10} failed on {:?}: {}",
+                    delivery.handler, delivery.message, error
+                )?,
+            }
+        }
+        for message in &self.unhandled {
+            writeln!(f, "{:<10} {:?}", "unhandled", message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn handlers(report: &Report) -> Vec<&str> {
+        report.deliveries.iter().map(|d| d.handler).collect()
+    }
+
+    #[test]
+    fn test_handlers_run_in_subscription_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut bus = MessageBus::new();
+        for name in ["first", "second", "third"] {
+            let log = Rc::clone(&log);
+            bus.subscribe(Kind::Write, name, move |message| {
+                log.borrow_mut().push(format!("{} {:?}", name, message));
+                Ok(String::from("written"))
+            });
+        }
+        bus.publish(Message::Write(String::from("a")));
+        bus.publish(Message::Write(String::from("b")));
+
+        let report = bus.process();
+        assert_eq!(
+            handlers(&report),
+            ["first", "second", "third", "first", "second", "third"]
+        );
+        // Every handler sees a message before any sees the next one.
+        assert_eq!(
+            *log.borrow(),
+            [
+                r#"first Write("a")"#,
+                r#"second Write("a")"#,
+                r#"third Write("a")"#,
+                r#"first Write("b")"#,
+                r#"second Write("b")"#,
+                r#"third Write("b")"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_messages_go_to_their_own_kind() {
+        let mut bus = MessageBus::new();
+        bus.subscribe(Kind::Move, "mover", |message| match message {
+            Message::Move { x, y } => Ok(format!("moved to {}, {}", x, y)),
+            other => Err(format!("not a move: {:?}", other)),
+        });
+        bus.subscribe(Kind::ChangeColor, "painter", |_| {
+            Ok(String::from("painted"))
+        });
+        bus.publish(Message::ChangeColor(0, 0, 0));
+        bus.publish(Message::Move { x: 3, y: 4 });
+
+        let report = bus.process();
+        assert_eq!(handlers(&report), ["painter", "mover"]);
+        assert_eq!(
+            report.deliveries[1].result,
+            Ok(String::from("moved to 3, 4"))
+        );
+        assert_eq!(report.errors().count(), 0);
+    }
+
+    #[test]
+    fn test_a_failing_handler_does_not_stop_the_others() {
+        let mut bus = MessageBus::new();
+        bus.subscribe(Kind::Move, "bounds", |message| match message {
+            Message::Move { x, y } if *x < 0 || *y < 0 => Err(String::from("off the board")),
+            _ => Ok(String::from("in bounds")),
+        });
+        bus.subscribe(Kind::Move, "logger", |_| Ok(String::from("logged")));
+        bus.publish(Message::Move { x: -1, y: 5 });
+        bus.publish(Message::Move { x: 1, y: 5 });
+
+        let report = bus.process();
+        assert_eq!(handlers(&report), ["bounds", "logger", "bounds", "logger"]);
+        let errors: Vec<_> = report.errors().collect();
+        assert_eq!(
+            errors,
+            [&Delivery {
+                handler: "bounds",
+                message: Message::Move { x: -1, y: 5 },
+                result: Err(String::from("off the board")),
+            }]
+        );
+        assert!(report.to_string().contains("bounds     failed on Move"));
+    }
+
+    #[test]
+    fn test_unhandled_messages_are_reported() {
+        let mut bus = MessageBus::new();
+        bus.subscribe(Kind::Write, "writer", |_| Ok(String::from("written")));
+        bus.publish(Message::Quit);
+        bus.publish(Message::Write(String::from("hello")));
+        bus.publish(Message::ChangeColor(1, 2, 3));
+
+        let report = bus.process();
+        assert_eq!(handlers(&report), ["writer"]);
+        assert_eq!(
+            report.unhandled,
+            [Message::Quit, Message::ChangeColor(1, 2, 3)]
+        );
+        assert!(report.to_string().contains("unhandled  Quit"));
+    }
+
+    #[test]
+    fn test_process_empties_the_queue() {
+        let mut bus = MessageBus::new();
+        bus.subscribe(Kind::Quit, "quitter", |_| Ok(String::from("bye")));
+        bus.publish(Message::Quit);
+        bus.publish(Message::Quit);
+        assert_eq!(bus.pending(), 2);
+
+        assert_eq!(bus.process().deliveries.len(), 2);
+        assert_eq!(bus.pending(), 0);
+        assert_eq!(bus.process(), Report::default());
+    }
+}
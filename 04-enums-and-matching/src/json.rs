@@ -0,0 +1,250 @@
+//! `Message` as JSON: each message an object with a "type" field naming its
+//! variant, next to the variant's data: `{"type": "Move", "x": 1, "y": 2}`.
+//!
+//! serde calls this an internally tagged enum, `#[serde(tag = "type")]`. It
+//! cannot tag a tuple variant, whose data has no names to put next to
+//! "type", and `Write(String)` and `ChangeColor(i32, i32, i32)` are two. So
+//! `Message` goes in and out through `Tagged`, the same enum with named
+//! fields (`#[serde(from, into)]` on `Message`), and keeps its own shape.
+//!
+//! `parse_messages` reads an array of messages. serde's errors say what is
+//! wrong but not in which message, so each element is read on its own, and
+//! the error says which one, and whether its type was missing, unknown, or
+//! its data wrong.
+
+use crate::Message;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+
+/// The JSON form of `Message`.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub(crate) enum Tagged {
+    // `{}` rather than a unit variant, which would ignore unknown fields.
+    Quit {},
+    Move { x: i32, y: i32 },
+    Write { text: String },
+    ChangeColor { r: i32, g: i32, b: i32 },
+}
+
+/// The "type" of each variant, as it is in the JSON.
+pub const TYPES: [&str; 4] = ["Quit", "Move", "Write", "ChangeColor"];
+
+impl From<Tagged> for Message {
+    fn from(tagged: Tagged) -> Message {
+        match tagged {
+            Tagged::Quit {} => Message::Quit,
+            Tagged::Move { x, y } => Message::Move { x, y },
+            Tagged::Write { text } => Message::Write(text),
+            Tagged::ChangeColor { r, g, b } => Message::ChangeColor(r, g, b),
+        }
+    }
+}
+
+impl From<Message> for Tagged {
+    fn from(message: Message) -> Tagged {
+        match message {
+            Message::Quit => Tagged::Quit {},
+            Message::Move { x, y } => Tagged::Move { x, y },
+            Message::Write(text) => Tagged::Write { text },
+            Message::ChangeColor(r, g, b) => Tagged::ChangeColor { r, g, b },
+        }
+    }
+}
+
+/// Why `parse_messages` failed; `index` is the message's place in the array.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Not JSON, or not an array.
+    Syntax(serde_json::Error),
+    /// Not an object, or one with no "type".
+    MissingType { index: usize },
+    /// A "type" that is not one of `TYPES`.
+    UnknownType { index: usize, found: String },
+    /// A known type, with data missing, of the wrong type, or unexpected.
+    BadPayload {
+        index: usize,
+        kind: String,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax(e) => write!(f, "not an array of messages: {}", e),
+            ParseError::MissingType { index } => {
+                write!(f, "message {}: no \"type\" field", index)
+            }
+            ParseError::UnknownType { index, found } => write!(
+                f,
+                "message {}: unknown type {}, expected one of {}",
+                index,
+                found,
+                TYPES.join(", ")
+            ),
+            ParseError::BadPayload { index, kind, error } => {
+                write!(f, "message {} ({}): {}", index, kind, error)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Syntax(e) | ParseError::BadPayload { error: e, .. } => Some(e),
+            ParseError::MissingType { .. } | ParseError::UnknownType { .. } => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(e: serde_json::Error) -> Self {
+        ParseError::Syntax(e)
+    }
+}
+
+/// The messages of a JSON array, or the first one that is wrong.
+pub fn parse_messages(json: &str) -> Result<Vec<Message>, ParseError> {
+    let values: Vec<Value> = serde_json::from_str(json)?;
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| parse_message(index, value))
+        .collect()
+}
+
+fn parse_message(index: usize, value: Value) -> Result<Message, ParseError> {
+    let kind = match value.get("type") {
+        None => return Err(ParseError::MissingType { index }),
+        Some(Value::String(kind)) if TYPES.contains(&kind.as_str()) => kind.clone(),
+        // Quoted as it is in the JSON, strings or not: `"Jump"`, `3`.
+        Some(other) => {
+            return Err(ParseError::UnknownType {
+                index,
+                found: other.to_string(),
+            })
+        }
+    };
+    serde_json::from_value(value).map_err(|error| ParseError::BadPayload { index, kind, error })
+}
+
+/// `messages` as a JSON array, the way `parse_messages` reads them.
+pub fn to_json(messages: &[Message]) -> String {
+    serde_json::to_string(messages).expect("a message is always valid JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_variant() -> Vec<Message> {
+        vec![
+            Message::Quit,
+            Message::Move { x: -3, y: 7 },
+            Message::Write(String::from("say \"hi\"")),
+            Message::ChangeColor(0, 128, 255),
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let messages = every_variant();
+        assert_eq!(parse_messages(&to_json(&messages)).unwrap(), messages);
+        assert!(parse_messages("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tagged_json() {
+        assert_eq!(
+            to_json(&every_variant()),
+            r#"[{"type":"Quit"},{"type":"Move","x":-3,"y":7},{"type":"Write","text":"say \"hi\""},{"type":"ChangeColor","r":0,"g":128,"b":255}]"#
+        );
+        // Each type named in `TYPES` is one serde writes.
+        let json = to_json(&every_variant());
+        for kind in TYPES {
+            assert!(json.contains(&format!(r#""type":"{}""#, kind)), "{}", kind);
+        }
+    }
+
+    #[test]
+    fn test_field_order_and_spacing_do_not_matter() {
+        let json = r#"[ { "y": 2, "type": "Move", "x": 1 } ]"#;
+        assert_eq!(
+            parse_messages(json).unwrap(),
+            [Message::Move { x: 1, y: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        for json in ["", "[{", r#"{"type": "Quit"}"#] {
+            let error = parse_messages(json).unwrap_err();
+            assert!(matches!(error, ParseError::Syntax(_)), "{:?}", json);
+            assert!(error.to_string().starts_with("not an array of messages"));
+        }
+    }
+
+    #[test]
+    fn test_missing_type() {
+        for json in [
+            r#"[{"type": "Quit"}, {"x": 1}]"#,
+            r#"[{"type": "Quit"}, 5]"#,
+        ] {
+            let error = parse_messages(json).unwrap_err();
+            assert!(
+                matches!(error, ParseError::MissingType { index: 1 }),
+                "{:?}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        let error = parse_messages(r#"[{"type": "Jump", "height": 3}]"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "message 0: unknown type \"Jump\", expected one of Quit, Move, Write, ChangeColor"
+        );
+        // Case matters, and a type is a string.
+        for json in [r#"[{"type": "quit"}]"#, r#"[{"type": 3}]"#] {
+            let error = parse_messages(json).unwrap_err();
+            assert!(
+                matches!(error, ParseError::UnknownType { .. }),
+                "{:?}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_bad_payload() {
+        let cases = [
+            (r#"{"type": "Move", "x": 1}"#, "missing field `y`"),
+            (
+                r#"{"type": "Write", "text": 5}"#,
+                "invalid type: integer `5`",
+            ),
+            (
+                r#"{"type": "ChangeColor", "r": 1, "g": 2, "b": 3000000000}"#,
+                "invalid value",
+            ),
+            (r#"{"type": "Quit", "now": true}"#, "unknown field `now`"),
+        ];
+        for (message, expected) in cases {
+            let json = format!(r#"[{{"type": "Quit"}}, {}]"#, message);
+            let error = parse_messages(&json).unwrap_err();
+            let ParseError::BadPayload { index, kind, .. } = &error else {
+                panic!("{}: {:?}", message, error);
+            };
+            assert_eq!(*index, 1);
+            assert!(message.contains(kind.as_str()));
+            assert!(error.to_string().contains(expected), "{}", error);
+            assert!(error.source().is_some());
+        }
+    }
+}
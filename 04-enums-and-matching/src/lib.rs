@@ -0,0 +1,66 @@
+//! The `Message` enum, and what is built on it: a message bus that
+//! dispatches each message to the handlers of its variant, and messages as
+//! JSON. Then a state machine for a document's workflow. The binary is a
+//! tour of it.
+
+pub mod bus;
+pub mod json;
+pub mod workflow;
+
+use serde::{Deserialize, Serialize};
+
+/// An enum representing different types of messages.
+/// Enums in Rust can store diverse data in their variants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "json::Tagged", into = "json::Tagged")]
+pub enum Message {
+    Quit,                       // No data
+    Move { x: i32, y: i32 },    // Anonymous struct
+    Write(String),              // Single String
+    ChangeColor(i32, i32, i32), // Tuple of three integers
+}
+
+/// The variant of a `Message`, without its data: what handlers subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Quit,
+    Move,
+    Write,
+    ChangeColor,
+}
+
+impl Message {
+    /// Method on the Message enum that uses pattern matching to handle variants.
+    /// Pattern matching ensures all cases are handled.
+    pub fn call(&self) {
+        match self {
+            Message::Quit => println!("Quit variant triggered."),
+            Message::Move { x, y } => println!("Move to x: {}, y: {}", x, y),
+            Message::Write(text) => println!("Text message: {}", text),
+            Message::ChangeColor(r, g, b) => println!("Change color to R:{}, G:{}, B:{}", r, g, b),
+        }
+    }
+
+    /// `..` and `_` ignore the data: only the variant matters here.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Message::Quit => Kind::Quit,
+            Message::Move { .. } => Kind::Move,
+            Message::Write(_) => Kind::Write,
+            Message::ChangeColor(..) => Kind::ChangeColor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(Message::Quit.kind(), Kind::Quit);
+        assert_eq!(Message::Move { x: 1, y: 2 }.kind(), Kind::Move);
+        assert_eq!(Message::Write(String::new()).kind(), Kind::Write);
+        assert_eq!(Message::ChangeColor(1, 2, 3).kind(), Kind::ChangeColor);
+    }
+}
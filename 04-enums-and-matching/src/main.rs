@@ -1,24 +1,7 @@
-/// An enum representing different types of messages.
-/// Enums in Rust can store diverse data in their variants.
-enum Message {
-    Quit,                       // No data
-    Move { x: i32, y: i32 },    // Anonymous struct
-    Write(String),              // Single String
-    ChangeColor(i32, i32, i32), // Tuple of three integers
-}
-
-impl Message {
-    /// Method on the Message enum that uses pattern matching to handle variants.
-    /// Pattern matching ensures all cases are handled.
-    fn call(&self) {
-        match self {
-            Message::Quit => println!("Quit variant triggered."),
-            Message::Move { x, y } => println!("Move to x: {}, y: {}", x, y),
-            Message::Write(text) => println!("Text message: {}", text),
-            Message::ChangeColor(r, g, b) => println!("Change color to R:{}, G:{}, B:{}", r, g, b),
-        }
-    }
-}
+use enums_and_matching::bus::MessageBus;
+use enums_and_matching::json;
+use enums_and_matching::workflow::{Action, Document};
+use enums_and_matching::{Kind, Message};
 
 fn main() {
     let messages = vec![
@@ -28,10 +11,43 @@ fn main() {
         Message::ChangeColor(0, 255, 255),
     ];
 
-    for msg in messages {
+    for msg in &messages {
         msg.call();
     }
 
+    // The same messages as JSON, an object each, tagged with its variant,
+    // and read back. A message that does not fit is pointed out by place.
+    let encoded = json::to_json(&messages);
+    println!("\n{}", encoded);
+    assert_eq!(json::parse_messages(&encoded).unwrap(), messages);
+    let received = r#"[{"type": "Write", "text": "hi"}, {"type": "Move", "x": 1}]"#;
+    match json::parse_messages(received) {
+        Ok(messages) => println!("received {:?}", messages),
+        Err(error) => println!("rejected: {}", error),
+    }
+
+    // The same messages through a bus: the handlers are chosen at run time,
+    // by the variant each one subscribed to, and their results collected.
+    let mut bus = MessageBus::new();
+    bus.subscribe(Kind::Write, "printer", |message| match message {
+        Message::Write(text) => Ok(format!("printed {:?}", text)),
+        _ => unreachable!("only subscribed to Write"),
+    });
+    bus.subscribe(Kind::Move, "bounds", |message| match message {
+        Message::Move { x, y } if (0..=20).contains(x) && (0..=20).contains(y) => {
+            Ok(format!("({}, {}) is on the board", x, y))
+        }
+        _ => Err(String::from("off the 20 by 20 board")),
+    });
+    bus.subscribe(Kind::Move, "logger", |message| {
+        Ok(format!("logged {:?}", message))
+    });
+    for message in messages {
+        bus.publish(message);
+    }
+    println!("\n{} message(s) queued", bus.pending());
+    print!("{}", bus.process());
+
     // Option enum example
     let some_number = Some(5);
     let _absent_number: Option<i32> = None;
@@ -41,6 +57,23 @@ fn main() {
     } else {
         println!("No number found.");
     }
+
+    // A state machine: each action is allowed in some states only, and a
+    // refused one leaves the document where it was.
+    let mut document = Document::new("Quarterly budget");
+    for action in [
+        Action::Submit,
+        Action::Archive,
+        Action::Reject,
+        Action::Approve,
+        Action::Archive,
+    ] {
+        match document.apply(action) {
+            Ok(state) => println!("{}: {} -> {}", document.title, action, state),
+            Err(error) => println!("{}: {}", document.title, error),
+        }
+    }
+    println!("history: {:?}", document.history());
 }
 
 #[cfg(test)]
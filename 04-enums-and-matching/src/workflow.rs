@@ -0,0 +1,263 @@
+//! A document's workflow as a state machine:
+//! Draft → Submitted → Approved or Rejected → Archived.
+//!
+//! The states are an enum, and so are the actions; `State::apply` is one
+//! `match` on the pair, listing the moves that are allowed, and its last arm
+//! turns everything else into a `TransitionError`. Adding a state or an action
+//! means deciding where it goes in that match, and nowhere else: a move
+//! that is not listed cannot happen by accident.
+//!
+//! `Document` holds a state and the way it came, and only moves when the
+//! transition is allowed: a refused action leaves it as it was.
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Draft,
+    Submitted,
+    Approved,
+    Rejected,
+    Archived,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Submit,
+    Approve,
+    Reject,
+    Archive,
+}
+
+/// An action that is not allowed in a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionError {
+    pub from: State,
+    pub action: Action,
+}
+
+impl State {
+    pub const ALL: [State; 5] = [
+        State::Draft,
+        State::Submitted,
+        State::Approved,
+        State::Rejected,
+        State::Archived,
+    ];
+
+    /// The state after `action`, if `action` is allowed in this one.
+    pub fn apply(self, action: Action) -> Result<State, TransitionError> {
+        match (self, action) {
+            (State::Draft, Action::Submit) => Ok(State::Submitted),
+            (State::Submitted, Action::Approve) => Ok(State::Approved),
+            (State::Submitted, Action::Reject) => Ok(State::Rejected),
+            (State::Approved | State::Rejected, Action::Archive) => Ok(State::Archived),
+            (from, action) => Err(TransitionError { from, action }),
+        }
+    }
+
+    pub fn submit(self) -> Result<State, TransitionError> {
+        self.apply(Action::Submit)
+    }
+
+    pub fn approve(self) -> Result<State, TransitionError> {
+        self.apply(Action::Approve)
+    }
+
+    pub fn reject(self) -> Result<State, TransitionError> {
+        self.apply(Action::Reject)
+    }
+
+    pub fn archive(self) -> Result<State, TransitionError> {
+        self.apply(Action::Archive)
+    }
+
+    /// The actions allowed here; none once archived.
+    pub fn actions(self) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|&action| self.apply(action).is_ok())
+            .collect()
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [
+        Action::Submit,
+        Action::Approve,
+        Action::Reject,
+        Action::Archive,
+    ];
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            State::Draft => "draft",
+            State::Submitted => "submitted",
+            State::Approved => "approved",
+            State::Rejected => "rejected",
+            State::Archived => "archived",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Action::Submit => "submit",
+            Action::Approve => "approve",
+            Action::Reject => "reject",
+            Action::Archive => "archive",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot {} a document that is {}", self.action, self.from)
+    }
+}
+
+impl Error for TransitionError {}
+
+/// A titled document, and the states it went through, the current one last.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub title: String,
+    history: Vec<State>,
+}
+
+impl Document {
+    /// A new document is a draft.
+    pub fn new(title: &str) -> Document {
+        Document {
+            title: title.to_string(),
+            history: vec![State::Draft],
+        }
+    }
+
+    pub fn state(&self) -> State {
+        *self.history.last().expect("the history starts with Draft")
+    }
+
+    pub fn history(&self) -> &[State] {
+        &self.history
+    }
+
+    /// Moves to the state after `action`, or stays put and says why not.
+    pub fn apply(&mut self, action: Action) -> Result<State, TransitionError> {
+        let next = self.state().apply(action)?;
+        self.history.push(next);
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use State::*;
+
+    #[test]
+    fn test_transition_table() {
+        use Action::*;
+        // Every (state, action) pair, with the state it leads to if allowed.
+        let table = [
+            (Draft, Submit, Some(Submitted)),
+            (Draft, Approve, None),
+            (Draft, Reject, None),
+            (Draft, Archive, None),
+            (Submitted, Submit, None),
+            (Submitted, Approve, Some(Approved)),
+            (Submitted, Reject, Some(Rejected)),
+            (Submitted, Archive, None),
+            (Approved, Submit, None),
+            (Approved, Approve, None),
+            (Approved, Reject, None),
+            (Approved, Archive, Some(Archived)),
+            (Rejected, Submit, None),
+            (Rejected, Approve, None),
+            (Rejected, Reject, None),
+            (Rejected, Archive, Some(Archived)),
+            (Archived, Submit, None),
+            (Archived, Approve, None),
+            (Archived, Reject, None),
+            (Archived, Archive, None),
+        ];
+        assert_eq!(table.len(), State::ALL.len() * Action::ALL.len());
+        for (from, action, expected) in table {
+            let expected = expected.ok_or(TransitionError { from, action });
+            assert_eq!(from.apply(action), expected, "{} on {}", action, from);
+        }
+    }
+
+    #[test]
+    fn test_state_pairs() {
+        // Which states lead straight to which; every other pair is refused.
+        let allowed = [
+            (Draft, Submitted),
+            (Submitted, Approved),
+            (Submitted, Rejected),
+            (Approved, Archived),
+            (Rejected, Archived),
+        ];
+        for from in State::ALL {
+            for to in State::ALL {
+                let reachable = Action::ALL
+                    .into_iter()
+                    .any(|action| from.apply(action) == Ok(to));
+                assert_eq!(
+                    reachable,
+                    allowed.contains(&(from, to)),
+                    "{} to {}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_named_transitions() {
+        assert_eq!(Draft.submit().and_then(State::approve), Ok(Approved));
+        assert_eq!(
+            Draft
+                .submit()
+                .and_then(State::reject)
+                .and_then(State::archive),
+            Ok(Archived)
+        );
+        assert_eq!(
+            Draft.approve(),
+            Err(TransitionError {
+                from: Draft,
+                action: Action::Approve
+            })
+        );
+        assert_eq!(Archived.actions(), []);
+        assert_eq!(Submitted.actions(), [Action::Approve, Action::Reject]);
+    }
+
+    #[test]
+    fn test_document_history() {
+        let mut document = Document::new("Budget");
+        assert_eq!(document.state(), Draft);
+        document.apply(Action::Submit).unwrap();
+        document.apply(Action::Reject).unwrap();
+
+        // Refused: the document stays rejected, its history unchanged.
+        let error = document.apply(Action::Approve).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cannot approve a document that is rejected"
+        );
+        assert_eq!(document.history(), [Draft, Submitted, Rejected]);
+
+        document.apply(Action::Archive).unwrap();
+        assert_eq!(document.state(), Archived);
+        assert!(document.apply(Action::Submit).is_err());
+    }
+}
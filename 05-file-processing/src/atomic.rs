@@ -0,0 +1,323 @@
+//! Writing files safely: a file is either entirely the old version or entirely
+//! the new one, never half of each, even if the program crashes or the power
+//! goes out in the middle.
+//!
+//! `File::create(path)` followed by writes gets this wrong: it empties the old
+//! file first, so a crash leaves a truncated one. The safe way is:
+//! 1. write the new contents to a temporary file in the same directory;
+//! 2. `fsync` it, so the data is on disk and not just in the OS's cache;
+//! 3. rename it over the old file. On one file system a rename is atomic:
+//!    anyone opening `path` sees the old file or the new one;
+//! 4. `fsync` the directory, so that the rename itself survives a crash.
+//!
+//! The fsyncs can be turned off for speed when losing the latest write to a
+//! crash is acceptable; the old-or-new guarantee holds either way, as long as
+//! the OS itself does not crash.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How to write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WriteOptions {
+    /// Whether an existing file may be replaced; if not, writing to it fails
+    /// with `AlreadyExists`, even if the file appears while writing. Needs a
+    /// file system with hard links.
+    pub replace: bool,
+    /// Keep the file being replaced as `path.bak`.
+    pub backup: bool,
+    /// Wait until the data and the rename are on disk.
+    pub sync: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            replace: true,
+            backup: false,
+            sync: true,
+        }
+    }
+}
+
+/// Writes `bytes` to `path` atomically, replacing the file if there is one.
+pub fn atomic_write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    atomic_write_with(path, bytes, WriteOptions::default())
+}
+
+pub fn atomic_write_with(path: &Path, bytes: &[u8], options: WriteOptions) -> io::Result<()> {
+    let mut file = AtomicFile::create(path, options)?;
+    file.write_all(bytes)?;
+    file.commit()
+}
+
+/// A file being written under a temporary name, for contents too big to hold
+/// in memory. It takes `path`'s place on `commit`; dropped without a commit
+/// (an error half way through), it is deleted and `path` is left untouched.
+pub struct AtomicFile {
+    path: PathBuf,
+    temporary: PathBuf,
+    /// `None` once committed.
+    writer: Option<BufWriter<File>>,
+    options: WriteOptions,
+}
+
+impl AtomicFile {
+    pub fn create(path: &Path, options: WriteOptions) -> io::Result<AtomicFile> {
+        if !options.replace && path.exists() {
+            return Err(already_exists(path));
+        }
+        let (temporary, file) = create_temporary(path)?;
+        Ok(AtomicFile {
+            path: path.to_path_buf(),
+            temporary,
+            writer: Some(BufWriter::new(file)),
+            options,
+        })
+    }
+
+    /// Puts the new file in place of the old one.
+    pub fn commit(mut self) -> io::Result<()> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let result = self.finish(writer);
+        if result.is_err() {
+            let _ = fs::remove_file(&self.temporary);
+        }
+        result
+    }
+
+    fn finish(&self, writer: BufWriter<File>) -> io::Result<()> {
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        if self.options.sync {
+            file.sync_all()?;
+        }
+        drop(file);
+
+        if !self.options.replace {
+            return self.link_new();
+        }
+        match fs::metadata(&self.path) {
+            Ok(metadata) => {
+                // The new file gets the old one's permissions, not the defaults.
+                fs::set_permissions(&self.temporary, metadata.permissions())?;
+                if self.options.backup {
+                    backup(&self.path)?;
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        fs::rename(&self.temporary, &self.path)?;
+        if self.options.sync {
+            sync_dir(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// Puts the file in place only if `path` does not exist. Checking first and
+    /// then renaming would replace a file created in between; a hard link is
+    /// refused by the file system itself when the name is taken.
+    fn link_new(&self) -> io::Result<()> {
+        fs::hard_link(&self.temporary, &self.path).map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => already_exists(&self.path),
+            _ => e,
+        })?;
+        // The new file is in place under its name; the temporary one is just a second link.
+        let _ = fs::remove_file(&self.temporary);
+        if self.options.sync {
+            sync_dir(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.writer {
+            Some(writer) => writer.write(buf),
+            None => Err(io::Error::other("already committed")),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// For formats that go back to fill in a header, like zip.
+impl Seek for AtomicFile {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        match &mut self.writer {
+            Some(writer) => writer.seek(position),
+            None => Err(io::Error::other("already committed")),
+        }
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.writer.take().is_some() {
+            let _ = fs::remove_file(&self.temporary);
+        }
+    }
+}
+
+/// `path` with `suffix` added to its name: `a.txt` and `.bak` give `a.txt.bak`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name: OsString = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// A new file next to `path`, named after it and hidden (`.a.txt.1234.0.tmp`).
+/// Next to it, because a rename across file systems is not atomic (or fails).
+fn create_temporary(path: &Path) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name"))?;
+    loop {
+        let mut temporary = OsString::from(".");
+        temporary.push(name);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        temporary.push(format!(".{}.{}.tmp", process::id(), count));
+        let temporary = path.with_file_name(temporary);
+        // create_new: never reuse a file someone else is writing.
+        match File::create_new(&temporary) {
+            Ok(file) => return Ok((temporary, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Copies the current `path` to `path.bak`, replacing an older backup. A hard
+/// link where the file system allows one: instant, and no extra space.
+fn backup(path: &Path) -> io::Result<()> {
+    let backup = with_suffix(path, ".bak");
+    match fs::remove_file(&backup) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    fs::hard_link(path, &backup).or_else(|_| fs::copy(path, &backup).map(|_| ()))
+}
+
+/// Makes the directory entry of `path` durable. Windows has no such call:
+/// there the rename is as durable as it gets.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn already_exists(path: &Path) -> io::Error {
+    let message = format!("{} already exists", path.display());
+    io::Error::new(io::ErrorKind::AlreadyExists, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The names in `dir`, sorted: to check that no temporary file is left.
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_atomic_write_replaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        atomic_write(&path, b"one").unwrap();
+        atomic_write(&path, b"two").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+        assert_eq!(names(dir.path()), ["a.txt"]);
+    }
+
+    #[test]
+    fn test_backup_and_no_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "old").unwrap();
+
+        let backup = WriteOptions {
+            backup: true,
+            sync: false,
+            ..WriteOptions::default()
+        };
+        atomic_write_with(&path, b"new", backup).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(fs::read(dir.path().join("a.txt.bak")).unwrap(), b"old");
+
+        let keep = WriteOptions {
+            replace: false,
+            ..WriteOptions::default()
+        };
+        let error = atomic_write_with(&path, b"newer", keep).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+
+        // A file that appears while writing is not replaced either.
+        let other = dir.path().join("b.txt");
+        let mut file = AtomicFile::create(&other, keep).unwrap();
+        file.write_all(b"mine").unwrap();
+        fs::write(&other, "theirs").unwrap();
+        let error = file.commit().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&other).unwrap(), b"theirs");
+
+        let fresh = dir.path().join("c.txt");
+        atomic_write_with(&fresh, b"first", keep).unwrap();
+        assert_eq!(fs::read(&fresh).unwrap(), b"first");
+        assert_eq!(names(dir.path()), ["a.txt", "a.txt.bak", "b.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn test_uncommitted_file_leaves_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "old").unwrap();
+        {
+            let mut file = AtomicFile::create(&path, WriteOptions::default()).unwrap();
+            file.write_all(b"half of the new").unwrap();
+            // Dropped here, as when an error ends the writing early.
+        }
+        assert_eq!(fs::read(&path).unwrap(), b"old");
+        assert_eq!(names(dir.path()), ["a.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions_are_kept() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        atomic_write(&path, b"#!/bin/sh\necho hi\n").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+}
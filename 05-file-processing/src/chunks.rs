@@ -0,0 +1,272 @@
+//! The `head`, `tail` and `split` commands, for files of any size.
+//!
+//! None of them reads a whole file into memory (`read_to_string` on a 4 GB log
+//! needs 4 GB of RAM). They work on the fixed-size chunks a `BufReader` hands
+//! out, count newlines inside each chunk and copy bytes through untouched, so
+//! invalid UTF-8 and very long lines are no problem either. `tail` goes one
+//! step further and reads backwards from the end, so it costs the same on a
+//! small file as on a huge one.
+//!
+//! Each takes the path of its input along with the input itself, for errors.
+
+use crate::error::{FileError, Operation};
+use file_processing::atomic::{AtomicFile, WriteOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// How much `tail` reads at a time, going backwards.
+const CHUNK: usize = 64 * 1024;
+
+/// Copies the first `lines` lines of `reader` to `out`.
+pub fn head(
+    mut reader: impl BufRead,
+    path: &Path,
+    lines: usize,
+    out: &mut impl Write,
+) -> Result<(), FileError> {
+    let mut left = lines;
+    while left > 0 {
+        let chunk = reader
+            .fill_buf()
+            .map_err(|e| FileError::from_io(path, Operation::Read, e))?;
+        if chunk.is_empty() {
+            break;
+        }
+        let end = match line_end(chunk, left) {
+            Ok(end) => {
+                left = 0;
+                end
+            }
+            Err(seen) => {
+                left -= seen;
+                chunk.len()
+            }
+        };
+        out.write_all(&chunk[..end]).map_err(FileError::Output)?;
+        reader.consume(end);
+    }
+    Ok(())
+}
+
+/// Copies the last `lines` lines of `file` to `out`, reading only as much of
+/// the end of the file as they take.
+pub fn tail(
+    mut file: impl Read + Seek,
+    path: &Path,
+    lines: usize,
+    out: &mut impl Write,
+) -> Result<(), FileError> {
+    let reading = |e| FileError::from_io(path, Operation::Read, e);
+    let len = file.seek(SeekFrom::End(0)).map_err(reading)?;
+    let start = tail_start(&mut file, len, lines).map_err(reading)?;
+    file.seek(SeekFrom::Start(start)).map_err(reading)?;
+    // Not io::copy(): a failed read and a failed write are told apart.
+    let mut reader = BufReader::with_capacity(CHUNK, file);
+    loop {
+        let chunk = reader.fill_buf().map_err(reading)?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        out.write_all(chunk).map_err(FileError::Output)?;
+        let n = chunk.len();
+        reader.consume(n);
+    }
+}
+
+/// Where the last `lines` lines start: just past the newline before them.
+fn tail_start(file: &mut (impl Read + Seek), len: u64, lines: usize) -> io::Result<u64> {
+    if lines == 0 {
+        return Ok(len);
+    }
+    let mut buffer = vec![0; CHUNK];
+    let mut seen = 0;
+    let mut end = len;
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK as u64);
+        let chunk = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        for (i, &byte) in chunk.iter().enumerate().rev() {
+            let at = start + i as u64;
+            // The newline that ends the file closes the last line, it does not start one.
+            if byte != b'\n' || at == len - 1 {
+                continue;
+            }
+            seen += 1;
+            if seen == lines {
+                return Ok(at + 1);
+            }
+        }
+        end = start;
+    }
+    // Fewer lines than asked for: all of them.
+    Ok(0)
+}
+
+/// Cuts `reader` into files of `lines` lines each, named `PREFIX000`,
+/// `PREFIX001` and so on (more than a thousand parts get longer numbers).
+/// Returns the files written, in order; an empty input writes none. Each part
+/// appears only once complete.
+pub fn split(
+    mut reader: impl BufRead,
+    path: &Path,
+    lines: usize,
+    prefix: &str,
+) -> Result<Vec<PathBuf>, FileError> {
+    let mut written = Vec::new();
+    let mut part: Option<AtomicFile> = None;
+    let mut left = lines;
+    let writing = |part: &Path, e| FileError::from_io(part, Operation::Write, e);
+    loop {
+        let chunk = reader
+            .fill_buf()
+            .map_err(|e| FileError::from_io(path, Operation::Read, e))?;
+        if chunk.is_empty() {
+            break;
+        }
+        // Opened only once there is something to put in it: no empty last part.
+        let writer = match &mut part {
+            Some(writer) => writer,
+            None => {
+                let name = PathBuf::from(format!("{}{:03}", prefix, written.len()));
+                let file = AtomicFile::create(&name, WriteOptions::default())
+                    .map_err(|e| FileError::from_io(&name, Operation::Create, e))?;
+                written.push(name);
+                part.insert(file)
+            }
+        };
+        let (end, full) = match line_end(chunk, left) {
+            Ok(end) => (end, true),
+            Err(seen) => {
+                left -= seen;
+                (chunk.len(), false)
+            }
+        };
+        let name = &written[written.len() - 1];
+        writer
+            .write_all(&chunk[..end])
+            .map_err(|e| writing(name, e))?;
+        reader.consume(end);
+        if full {
+            if let Some(writer) = part.take() {
+                writer.commit().map_err(|e| writing(name, e))?;
+            }
+            left = lines;
+        }
+    }
+    if let (Some(writer), Some(name)) = (part, written.last()) {
+        writer.commit().map_err(|e| writing(name, e))?;
+    }
+    Ok(written)
+}
+
+/// Where the `n`th line of `chunk` ends, just past its newline; or, if the
+/// chunk holds fewer than `n` newlines, how many it holds.
+fn line_end(chunk: &[u8], n: usize) -> Result<usize, usize> {
+    let mut seen = 0;
+    for (i, &byte) in chunk.iter().enumerate() {
+        if byte == b'\n' {
+            seen += 1;
+            if seen == n {
+                return Ok(i + 1);
+            }
+        }
+    }
+    Err(seen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::io::{BufReader, Cursor};
+
+    /// "line N\n" for every N in `range`.
+    fn numbered(range: std::ops::Range<usize>) -> String {
+        range.map(|i| format!("line {}\n", i)).collect()
+    }
+
+    /// A temp file several megabytes long: many `CHUNK`s and `BufReader` buffers.
+    fn large_file(dir: &tempfile::TempDir, lines: usize) -> PathBuf {
+        let path = dir.path().join("large.txt");
+        fs::write(&path, numbered(0..lines)).unwrap();
+        path
+    }
+
+    fn head_of(input: &str, lines: usize, capacity: usize) -> String {
+        let mut out = Vec::new();
+        let reader = BufReader::with_capacity(capacity, input.as_bytes());
+        head(reader, Path::new("f.txt"), lines, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn tail_of(input: &str, lines: usize) -> String {
+        let mut out = Vec::new();
+        tail(Cursor::new(input), Path::new("f.txt"), lines, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_head() {
+        let text = numbered(0..5);
+        // A 3-byte buffer: lines cut across chunks.
+        assert_eq!(head_of(&text, 2, 3), "line 0\nline 1\n");
+        assert_eq!(head_of(&text, 9, 3), text);
+        assert_eq!(head_of(&text, 0, 3), "");
+        assert_eq!(head_of("a\nb", 5, 8192), "a\nb");
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail_of("a\nb\nc\n", 2), "b\nc\n");
+        assert_eq!(tail_of("a\nb\nc", 2), "b\nc");
+        assert_eq!(tail_of("a\nb\n", 5), "a\nb\n");
+        assert_eq!(tail_of("a\n\n\n", 2), "\n\n");
+        assert_eq!(tail_of("a\nb\n", 0), "");
+        assert_eq!(tail_of("", 3), "");
+    }
+
+    #[test]
+    fn test_head_and_tail_of_a_large_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = large_file(&dir, 500_000);
+
+        let mut out = Vec::new();
+        let reader = BufReader::new(File::open(&path).unwrap());
+        head(reader, &path, 100_000, &mut out).unwrap();
+        assert_eq!(out, numbered(0..100_000).as_bytes());
+
+        // Far more than one chunk from the end.
+        let mut out = Vec::new();
+        tail(File::open(&path).unwrap(), &path, 30_000, &mut out).unwrap();
+        assert_eq!(out, numbered(470_000..500_000).as_bytes());
+    }
+
+    #[test]
+    fn test_split_a_large_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = large_file(&dir, 250_000);
+        let prefix = format!("{}.", path.display());
+
+        let reader = BufReader::new(File::open(&path).unwrap());
+        let parts = split(reader, &path, 100_000, &prefix).unwrap();
+        let names: Vec<_> = parts
+            .iter()
+            .map(|part| part.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["large.txt.000", "large.txt.001", "large.txt.002"]);
+        assert_eq!(
+            fs::read_to_string(&parts[1]).unwrap(),
+            numbered(100_000..200_000)
+        );
+        assert_eq!(
+            fs::read_to_string(&parts[2]).unwrap(),
+            numbered(200_000..250_000)
+        );
+
+        // Lines that fill the last part exactly leave no empty part behind.
+        let parts = split("a\nb\n".as_bytes(), &path, 1, &prefix).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert!(split("".as_bytes(), &path, 1, &prefix).unwrap().is_empty());
+    }
+}
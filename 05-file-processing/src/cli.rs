@@ -0,0 +1,873 @@
+//! The command line: a subcommand, then its flags and paths in any order.
+
+use crate::dedup::Action;
+use crate::delimited::CsvOptions;
+use crate::follow::FollowOptions;
+use crate::hash::Algorithm;
+use crate::walk::WalkOptions;
+use crate::watch::WatchOptions;
+use file_processing::atomic::WriteOptions;
+use glob::Pattern;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const USAGE: &str = "Usage: file-processing <COMMAND> [OPTIONS] PATH...
+
+Commands:
+  read                 Read each file and report its size, with a summary at the end
+  search PATTERN PATH  Print the lines matching the regular expression PATTERN
+                       (exit status: 0 if a line matched, 1 if none, 2 on errors)
+  stats                Count lines, words, bytes and the most common characters
+  csv FILE             Select, filter and aggregate a delimited file ('-' for stdin)
+  head FILE            Print the first lines of FILE
+  tail FILE            Print the last lines of FILE, reading it from the end
+  follow FILE          Print the last lines of FILE, then each line added to it, also
+                       after it is truncated or rotated (Ctrl-C to stop)
+  split FILE [PREFIX]  Cut FILE into files of --lines lines: PREFIX000, PREFIX001...
+                       (default PREFIX: 'FILE.')
+  hash                 Print a checksum of each file, like sha256sum
+  dedup DIR            Find the files with the same contents under DIR
+  compress PATH        Compress each file to FILE.gz, or everything into one --zip archive
+  decompress FILE      Decompress FILE.gz to FILE, or extract FILE.zip
+  encrypt FILE         Encrypt each FILE to FILE.enc with a passphrase (AES-256-GCM)
+  decrypt FILE         Decrypt each FILE.enc back to FILE
+  watch PATH [-- COMMAND...]
+                       Report files created, modified and deleted under PATH, and
+                       run COMMAND after each round of changes
+
+Options:
+  -r, --recursive      Go into directories
+  -g, --glob PATTERN   With --recursive, only the files matching PATTERN
+                       ('*.rs' matches names, 'src/*.rs' whole paths)
+  -h, --help           Show this help
+
+Search options:
+  -i, --ignore-case    Match upper and lower case alike
+  -c, --count          Print the number of matching lines per file instead
+
+Stats options:
+  -j, --threads N      Count on N threads (default: one per core)
+      --top N          Show the N most common characters (default 3)
+
+CSV options:
+  -d, --delimiter C    Field separator (default ','; 'tab' for tabs)
+      --header WHEN    Whether the first row names the columns: auto (default), yes or no
+  -s, --select COLS    Only these columns, comma-separated names or 1-based positions
+  -w, --where COND     Only rows where COND holds: COLUMN=VALUE, or with !=, <, <=, >, >=,
+                       ~ (contains); numbers compare as numbers. Repeat to require several
+  -a, --agg FUNC       count, sum:COLUMN or avg:COLUMN instead of the rows; repeatable
+  -b, --group-by COL   With --agg, one row per value of COL
+  -o, --format FMT     csv (default) or json
+
+Head, tail, follow and split options:
+  -n, --lines N        How many lines to print (default 10), or per file for split
+                       (default 1000)
+      --interval MS    How often follow checks FILE, in milliseconds (default 1000)
+
+Hash options:
+      --algorithm NAME sha256 (default) or blake3
+
+Dedup options:
+      --link           Replace the copies with hard links to the first file
+      --delete         Delete the copies
+      --apply          Really do it; without it, only show what would be done
+  -g works as above: compare only the matching files
+
+Compress options:
+      --zip ARCHIVE    Put the files, and directories with all their files, into ARCHIVE
+      --into DIR       Where decompress extracts a zip archive (default: its name
+                       without .zip)
+      --force          Replace existing files (by default they are left alone)
+      --backup         Replace existing files, keeping each old one as NAME.bak
+  Files appear only once complete: an error half way leaves nothing behind.
+
+Encrypt options:
+      --force, --backup as for compress. The passphrase is asked for on the
+  terminal, or taken from $FILE_PROCESSING_PASSPHRASE.
+
+Watch options:
+      --debounce MS    Wait until nothing has changed for MS milliseconds before
+                       reporting (default 200)
+  -r and -g work as above: watch subdirectories, report only matching files";
+
+/// What the command line asks for.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Help,
+    Read {
+        paths: Vec<PathBuf>,
+        walk: WalkOptions,
+    },
+    Search {
+        /// A regular expression, compiled by the `search` module.
+        pattern: String,
+        paths: Vec<PathBuf>,
+        walk: WalkOptions,
+        ignore_case: bool,
+        count: bool,
+    },
+    Stats {
+        paths: Vec<PathBuf>,
+        walk: WalkOptions,
+        /// `None`: one per core.
+        threads: Option<usize>,
+        top: usize,
+    },
+    Csv {
+        /// `-` for stdin.
+        path: PathBuf,
+        options: CsvOptions,
+    },
+    Head {
+        path: PathBuf,
+        lines: usize,
+    },
+    Tail {
+        path: PathBuf,
+        lines: usize,
+    },
+    Follow {
+        path: PathBuf,
+        options: FollowOptions,
+    },
+    Split {
+        path: PathBuf,
+        /// Lines per part.
+        lines: usize,
+        /// Put before each part's number.
+        prefix: String,
+    },
+    Hash {
+        paths: Vec<PathBuf>,
+        walk: WalkOptions,
+        algorithm: Algorithm,
+    },
+    Dedup {
+        /// Always searched recursively.
+        paths: Vec<PathBuf>,
+        walk: WalkOptions,
+        action: Action,
+        /// `false`: a dry run.
+        apply: bool,
+    },
+    Compress {
+        paths: Vec<PathBuf>,
+        /// `None`: gzip each file on its own.
+        zip: Option<PathBuf>,
+        write: WriteOptions,
+    },
+    Decompress {
+        paths: Vec<PathBuf>,
+        /// For zip archives.
+        into: Option<PathBuf>,
+        write: WriteOptions,
+    },
+    Encrypt {
+        paths: Vec<PathBuf>,
+        write: WriteOptions,
+    },
+    Decrypt {
+        paths: Vec<PathBuf>,
+        write: WriteOptions,
+    },
+    Watch {
+        path: PathBuf,
+        options: WatchOptions,
+    },
+}
+
+/// A command line that makes no sense; printed above the usage.
+#[derive(Debug, PartialEq)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// `args` excludes the program name (`std::env::args().skip(1)`).
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, UsageError> {
+    let mut args = args.into_iter();
+    let command = match args.next() {
+        None => return Err(UsageError("no command given".to_string())),
+        Some(command) => command,
+    };
+    if matches!(command.as_str(), "-h" | "--help" | "help") {
+        return Ok(Command::Help);
+    }
+    let mut args = Args::split(args)?;
+    if args.switch("-h", "--help") {
+        return Ok(Command::Help);
+    }
+
+    match command.as_str() {
+        "read" => {
+            let walk = args.walk()?;
+            let paths = paths(args.finish()?)?;
+            Ok(Command::Read { paths, walk })
+        }
+        "search" => {
+            let ignore_case = args.switch("-i", "--ignore-case");
+            let count = args.switch("-c", "--count");
+            let walk = args.walk()?;
+            let mut positionals = args.finish()?.into_iter();
+            let pattern = positionals
+                .next()
+                .ok_or_else(|| UsageError("no pattern given".to_string()))?;
+            Ok(Command::Search {
+                pattern,
+                paths: paths(positionals.collect())?,
+                walk,
+                ignore_case,
+                count,
+            })
+        }
+        "stats" => {
+            let threads = args.number("-j", "--threads")?;
+            if threads == Some(0) {
+                return Err(UsageError("--threads must be at least 1".to_string()));
+            }
+            let top = args.number("--top", "--top")?.unwrap_or(3);
+            let walk = args.walk()?;
+            Ok(Command::Stats {
+                paths: paths(args.finish()?)?,
+                walk,
+                threads,
+                top,
+            })
+        }
+        "csv" => {
+            let mut options = CsvOptions::default();
+            if let Some(delimiter) = args.value("-d", "--delimiter") {
+                options.delimiter = match delimiter.as_str() {
+                    "tab" | "\\t" => b'\t',
+                    _ if delimiter.len() == 1 => delimiter.as_bytes()[0],
+                    _ => {
+                        let message = format!("--delimiter: '{}' is not a single byte", delimiter);
+                        return Err(UsageError(message));
+                    }
+                };
+            }
+            if let Some(header) = args.parsed("--header", "--header")? {
+                options.header = header;
+            }
+            if let Some(select) = args.value("-s", "--select") {
+                options.select = select.split(',').map(String::from).collect();
+            }
+            options.filters = args.all_parsed("-w", "--where")?;
+            options.aggregates = args.all_parsed("-a", "--agg")?;
+            options.group_by = args.value("-b", "--group-by");
+            if options.group_by.is_some() && options.aggregates.is_empty() {
+                return Err(UsageError(
+                    "--group-by needs at least one --agg".to_string(),
+                ));
+            }
+            if let Some(format) = args.parsed("-o", "--format")? {
+                options.format = format;
+            }
+            let path = file(args.finish()?, "csv")?;
+            Ok(Command::Csv { path, options })
+        }
+        "head" => {
+            let lines = args.number("-n", "--lines")?.unwrap_or(10);
+            let path = file(args.finish()?, "head")?;
+            Ok(Command::Head { path, lines })
+        }
+        "tail" => {
+            let lines = args.number("-n", "--lines")?.unwrap_or(10);
+            let path = file(args.finish()?, "tail")?;
+            Ok(Command::Tail { path, lines })
+        }
+        "follow" => {
+            let lines = args.number("-n", "--lines")?.unwrap_or(10);
+            let interval = args.number("--interval", "--interval")?.unwrap_or(1000);
+            if interval == 0 {
+                return Err(UsageError("--interval must be at least 1".to_string()));
+            }
+            let path = file(args.finish()?, "follow")?;
+            Ok(Command::Follow {
+                path,
+                options: FollowOptions {
+                    lines,
+                    interval: Duration::from_millis(interval as u64),
+                },
+            })
+        }
+        "split" => {
+            let lines = args.number("-n", "--lines")?.unwrap_or(1000);
+            if lines == 0 {
+                return Err(UsageError("--lines must be at least 1".to_string()));
+            }
+            let mut positionals = args.finish()?;
+            let prefix = match positionals.len() {
+                2 => positionals.pop(),
+                _ => None,
+            };
+            let path = file(positionals, "split")?;
+            let prefix = prefix.unwrap_or_else(|| format!("{}.", path.display()));
+            Ok(Command::Split {
+                path,
+                lines,
+                prefix,
+            })
+        }
+        "hash" => {
+            let algorithm = args
+                .parsed("--algorithm", "--algorithm")?
+                .unwrap_or_default();
+            let walk = args.walk()?;
+            Ok(Command::Hash {
+                paths: paths(args.finish()?)?,
+                walk,
+                algorithm,
+            })
+        }
+        "dedup" => {
+            let action = match (
+                args.switch("--link", "--link"),
+                args.switch("--delete", "--delete"),
+            ) {
+                (true, true) => {
+                    return Err(UsageError(
+                        "--link and --delete cannot be used together".to_string(),
+                    ))
+                }
+                (true, false) => Action::Link,
+                (false, true) => Action::Delete,
+                (false, false) => Action::Report,
+            };
+            let apply = args.switch("--apply", "--apply");
+            let walk = WalkOptions {
+                recursive: true,
+                ..args.walk()?
+            };
+            Ok(Command::Dedup {
+                paths: paths(args.finish()?)?,
+                walk,
+                action,
+                apply,
+            })
+        }
+        "compress" => {
+            let zip = args.value("--zip", "--zip").map(PathBuf::from);
+            let write = args.write();
+            Ok(Command::Compress {
+                paths: paths(args.finish()?)?,
+                zip,
+                write,
+            })
+        }
+        "decompress" => {
+            let into = args.value("--into", "--into").map(PathBuf::from);
+            let write = args.write();
+            Ok(Command::Decompress {
+                paths: paths(args.finish()?)?,
+                into,
+                write,
+            })
+        }
+        "encrypt" | "decrypt" => {
+            let write = args.write();
+            let paths = paths(args.finish()?)?;
+            if command == "encrypt" {
+                Ok(Command::Encrypt { paths, write })
+            } else {
+                Ok(Command::Decrypt { paths, write })
+            }
+        }
+        "watch" => {
+            let debounce = args.number("--debounce", "--debounce")?.unwrap_or(200);
+            let walk = args.walk()?;
+            let mut positionals = args.finish()?.into_iter();
+            let path = positionals
+                .next()
+                .ok_or_else(|| UsageError("no path given".to_string()))?;
+            Ok(Command::Watch {
+                path: PathBuf::from(path),
+                options: WatchOptions {
+                    walk,
+                    debounce: Duration::from_millis(debounce as u64),
+                    command: positionals.collect(),
+                },
+            })
+        }
+        other => Err(UsageError(format!("unknown command '{}'", other))),
+    }
+}
+
+fn paths(positionals: Vec<String>) -> Result<Vec<PathBuf>, UsageError> {
+    if positionals.is_empty() {
+        return Err(UsageError("no paths given".to_string()));
+    }
+    Ok(positionals.into_iter().map(PathBuf::from).collect())
+}
+
+/// The one file a command reads.
+fn file(positionals: Vec<String>, command: &str) -> Result<PathBuf, UsageError> {
+    match positionals.as_slice() {
+        [path] => Ok(PathBuf::from(path)),
+        [] => Err(UsageError("no file given".to_string())),
+        _ => Err(UsageError(format!("{} reads one file at a time", command))),
+    }
+}
+
+/// Flags that take a value, in every command; any other flag is on/off.
+const VALUE_FLAGS: &[&str] = &[
+    "-g",
+    "--glob",
+    "-j",
+    "--threads",
+    "--top",
+    "-d",
+    "--delimiter",
+    "--header",
+    "-s",
+    "--select",
+    "-w",
+    "--where",
+    "-a",
+    "--agg",
+    "-b",
+    "--group-by",
+    "-o",
+    "--format",
+    "-n",
+    "--lines",
+    "--interval",
+    "--debounce",
+    "--algorithm",
+    "--zip",
+    "--into",
+];
+
+/// The arguments after the command, split into flags and positionals. Each
+/// command takes the flags it knows; whatever is left is unknown.
+struct Args {
+    /// As typed (`-r`, `--glob`), with the value if the flag takes one.
+    flags: Vec<(String, Option<String>)>,
+    positionals: Vec<String>,
+}
+
+impl Args {
+    fn split(mut args: impl Iterator<Item = String>) -> Result<Args, UsageError> {
+        let mut split = Args {
+            flags: Vec::new(),
+            positionals: Vec::new(),
+        };
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                // Everything after `--` is positional, even `-weird-name.txt`.
+                split.positionals.extend(args.by_ref());
+            } else if let Some((flag, value)) = arg
+                .split_once('=')
+                .filter(|(flag, _)| flag.starts_with("--"))
+            {
+                split
+                    .flags
+                    .push((flag.to_string(), Some(value.to_string())));
+            } else if VALUE_FLAGS.contains(&arg.as_str()) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| UsageError(format!("{} needs a value", arg)))?;
+                split.flags.push((arg, Some(value)));
+            } else if arg.starts_with('-') && arg != "-" {
+                split.flags.push((arg, None));
+            } else {
+                split.positionals.push(arg);
+            }
+        }
+        Ok(split)
+    }
+
+    /// Takes an on/off flag; whether it was given.
+    fn switch(&mut self, short: &str, long: &str) -> bool {
+        let before = self.flags.len();
+        self.flags
+            .retain(|(flag, value)| !(value.is_none() && (flag == short || flag == long)));
+        self.flags.len() != before
+    }
+
+    /// Takes a flag with a value; the last one wins, as in most Unix tools.
+    fn value(&mut self, short: &str, long: &str) -> Option<String> {
+        self.values(short, long).pop()
+    }
+
+    /// Takes a flag that can be repeated (`--where a=1 --where b=2`), in order.
+    fn values(&mut self, short: &str, long: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        self.flags.retain(|(flag, value)| {
+            let ours = flag == short || flag == long;
+            if ours {
+                found.extend(value.clone());
+            }
+            !ours
+        });
+        found
+    }
+
+    /// Takes a flag and converts its value.
+    fn parsed<T: FromStr<Err = String>>(
+        &mut self,
+        short: &str,
+        long: &str,
+    ) -> Result<Option<T>, UsageError> {
+        self.value(short, long)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e| UsageError(format!("{}: {}", long, e)))
+            })
+            .transpose()
+    }
+
+    /// Takes every occurrence of a repeatable flag and converts their values.
+    fn all_parsed<T: FromStr<Err = String>>(
+        &mut self,
+        short: &str,
+        long: &str,
+    ) -> Result<Vec<T>, UsageError> {
+        self.values(short, long)
+            .iter()
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e| UsageError(format!("{}: {}", long, e)))
+            })
+            .collect()
+    }
+
+    /// Takes a flag whose value is a count.
+    fn number(&mut self, short: &str, long: &str) -> Result<Option<usize>, UsageError> {
+        self.value(short, long)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| UsageError(format!("{}: '{}' is not a number", long, value)))
+            })
+            .transpose()
+    }
+
+    /// `--recursive` and `--glob`, for the commands that take paths.
+    fn walk(&mut self) -> Result<WalkOptions, UsageError> {
+        let recursive = self.switch("-r", "--recursive");
+        let glob = self
+            .value("-g", "--glob")
+            .map(|glob| {
+                Pattern::new(&glob).map_err(|e| {
+                    UsageError(format!("'{}' is not a valid glob pattern: {}", glob, e))
+                })
+            })
+            .transpose()?;
+        Ok(WalkOptions { recursive, glob })
+    }
+
+    /// `--force` and `--backup`, for the commands that write files.
+    fn write(&mut self) -> WriteOptions {
+        let force = self.switch("--force", "--force");
+        let backup = self.switch("--backup", "--backup");
+        WriteOptions {
+            replace: force || backup,
+            backup,
+            sync: true,
+        }
+    }
+
+    /// The positionals, once every known flag has been taken.
+    fn finish(self) -> Result<Vec<String>, UsageError> {
+        match self.flags.first() {
+            Some((flag, _)) => Err(UsageError(format!("unknown option '{}'", flag))),
+            None => Ok(self.positionals),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delimited::{Format, Header, Op};
+
+    fn parse_line(line: &str) -> Result<Command, UsageError> {
+        parse(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_read() {
+        assert_eq!(
+            parse_line("read a.txt -r --glob=*.rs src"),
+            Ok(Command::Read {
+                paths: vec!["a.txt".into(), "src".into()],
+                walk: WalkOptions {
+                    recursive: true,
+                    glob: Some(Pattern::new("*.rs").unwrap()),
+                },
+            })
+        );
+        assert_eq!(
+            parse_line("read -- -r"),
+            Ok(Command::Read {
+                paths: vec!["-r".into()],
+                walk: WalkOptions::default(),
+            })
+        );
+        assert_eq!(parse_line("read --help"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_search() {
+        assert_eq!(
+            parse_line("search -i fn\\s+main src -r -c -g *.rs"),
+            Ok(Command::Search {
+                pattern: "fn\\s+main".into(),
+                paths: vec!["src".into()],
+                walk: WalkOptions {
+                    recursive: true,
+                    glob: Some(Pattern::new("*.rs").unwrap()),
+                },
+                ignore_case: true,
+                count: true,
+            })
+        );
+        // -- lets the pattern itself start with a dash.
+        assert!(matches!(
+            parse_line("search -- -x a.txt"),
+            Ok(Command::Search { pattern, .. }) if pattern == "-x"
+        ));
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(
+            parse_line("stats -j 2 --top=5 a b"),
+            Ok(Command::Stats {
+                paths: vec!["a".into(), "b".into()],
+                walk: WalkOptions::default(),
+                threads: Some(2),
+                top: 5,
+            })
+        );
+        assert!(matches!(
+            parse_line("stats a"),
+            Ok(Command::Stats {
+                threads: None,
+                top: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_csv() {
+        let Ok(Command::Csv { path, options }) = parse_line(
+            "csv sales.csv -d ; --header=yes -s a,b -w a>1 -w b~x -a count -a sum:a -b c -o json",
+        ) else {
+            panic!("not a csv command");
+        };
+        assert_eq!(path, PathBuf::from("sales.csv"));
+        assert_eq!(options.delimiter, b';');
+        assert_eq!(options.header, Header::Yes);
+        assert_eq!(options.select, ["a", "b"]);
+        assert_eq!(options.filters.len(), 2);
+        assert_eq!(options.filters[1].op, Op::Contains);
+        assert_eq!(options.aggregates.len(), 2);
+        assert_eq!(options.group_by.as_deref(), Some("c"));
+        assert_eq!(options.format, Format::Json);
+        assert!(matches!(
+            parse_line("csv - -d tab"),
+            Ok(Command::Csv { options, .. }) if options.delimiter == b'\t'
+        ));
+    }
+
+    #[test]
+    fn test_head_tail_and_split() {
+        assert_eq!(
+            parse_line("head big.log"),
+            Ok(Command::Head {
+                path: "big.log".into(),
+                lines: 10,
+            })
+        );
+        assert_eq!(
+            parse_line("tail -n 3 big.log"),
+            Ok(Command::Tail {
+                path: "big.log".into(),
+                lines: 3,
+            })
+        );
+        assert_eq!(
+            parse_line("follow app.log -n 0 --interval 250"),
+            Ok(Command::Follow {
+                path: "app.log".into(),
+                options: FollowOptions {
+                    lines: 0,
+                    interval: Duration::from_millis(250),
+                },
+            })
+        );
+        assert_eq!(
+            parse_line("split --lines 500 big.log"),
+            Ok(Command::Split {
+                path: "big.log".into(),
+                lines: 500,
+                prefix: "big.log.".into(),
+            })
+        );
+        assert!(matches!(
+            parse_line("split big.log part-"),
+            Ok(Command::Split { lines: 1000, prefix, .. }) if prefix == "part-"
+        ));
+    }
+
+    #[test]
+    fn test_hash_and_dedup() {
+        assert_eq!(
+            parse_line("hash --algorithm blake3 -r src"),
+            Ok(Command::Hash {
+                paths: vec!["src".into()],
+                walk: WalkOptions {
+                    recursive: true,
+                    glob: None,
+                },
+                algorithm: Algorithm::Blake3,
+            })
+        );
+        assert!(matches!(
+            parse_line("hash a"),
+            Ok(Command::Hash {
+                algorithm: Algorithm::Sha256,
+                ..
+            })
+        ));
+        assert_eq!(
+            parse_line("dedup photos --link"),
+            Ok(Command::Dedup {
+                paths: vec!["photos".into()],
+                walk: WalkOptions {
+                    recursive: true,
+                    glob: None,
+                },
+                action: Action::Link,
+                apply: false,
+            })
+        );
+        assert!(matches!(
+            parse_line("dedup photos --delete --apply"),
+            Ok(Command::Dedup {
+                action: Action::Delete,
+                apply: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_compress() {
+        assert_eq!(
+            parse_line("compress a.txt src --zip=out.zip"),
+            Ok(Command::Compress {
+                paths: vec!["a.txt".into(), "src".into()],
+                zip: Some("out.zip".into()),
+                write: WriteOptions {
+                    replace: false,
+                    backup: false,
+                    sync: true,
+                },
+            })
+        );
+        assert_eq!(
+            parse_line("decompress out.zip --into tmp --backup"),
+            Ok(Command::Decompress {
+                paths: vec!["out.zip".into()],
+                into: Some("tmp".into()),
+                write: WriteOptions {
+                    replace: true,
+                    backup: true,
+                    sync: true,
+                },
+            })
+        );
+        assert!(matches!(
+            parse_line("compress a.txt --force"),
+            Ok(Command::Compress { write, .. }) if write.replace && !write.backup
+        ));
+    }
+
+    #[test]
+    fn test_encrypt() {
+        assert!(matches!(
+            parse_line("encrypt a.txt b.txt"),
+            Ok(Command::Encrypt { paths, write }) if paths.len() == 2 && !write.replace
+        ));
+        assert!(matches!(
+            parse_line("decrypt a.txt.enc --force"),
+            Ok(Command::Decrypt { write, .. }) if write.replace
+        ));
+    }
+
+    #[test]
+    fn test_watch() {
+        assert_eq!(
+            parse_line("watch src -r --debounce 50 -g *.rs -- cargo test -q"),
+            Ok(Command::Watch {
+                path: "src".into(),
+                options: WatchOptions {
+                    walk: WalkOptions {
+                        recursive: true,
+                        glob: Some(Pattern::new("*.rs").unwrap()),
+                    },
+                    debounce: Duration::from_millis(50),
+                    command: vec!["cargo".into(), "test".into(), "-q".into()],
+                },
+            })
+        );
+        assert!(matches!(
+            parse_line("watch notes.txt"),
+            Ok(Command::Watch { options, .. })
+                if options.command.is_empty() && options.debounce == Duration::from_millis(200)
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        for (line, message) in [
+            ("", "no command given"),
+            ("copy a b", "unknown command 'copy'"),
+            ("read", "no paths given"),
+            ("read -x a", "unknown option '-x'"),
+            ("read a -g", "-g needs a value"),
+            ("read -i a", "unknown option '-i'"),
+            ("search", "no pattern given"),
+            ("search main", "no paths given"),
+            ("stats -j many a", "--threads: 'many' is not a number"),
+            ("stats --threads 0 a", "--threads must be at least 1"),
+            ("csv", "no file given"),
+            ("csv a b", "csv reads one file at a time"),
+            ("csv a -d ::", "--delimiter: '::' is not a single byte"),
+            ("csv a -b x", "--group-by needs at least one --agg"),
+            ("csv a -o xml", "--format: 'xml' is not one of csv or json"),
+            ("head", "no file given"),
+            ("tail a b", "tail reads one file at a time"),
+            ("split a b c", "split reads one file at a time"),
+            ("split -n 0 a", "--lines must be at least 1"),
+            ("follow", "no file given"),
+            ("follow --interval 0 a", "--interval must be at least 1"),
+            ("watch", "no path given"),
+            (
+                "hash --algorithm md5 a",
+                "--algorithm: 'md5' is not one of sha256 or blake3",
+            ),
+            ("dedup", "no paths given"),
+            ("compress --zip out.zip", "no paths given"),
+            ("encrypt", "no paths given"),
+            (
+                "dedup d --link --delete",
+                "--link and --delete cannot be used together",
+            ),
+        ] {
+            assert_eq!(parse_line(line), Err(UsageError(message.to_string())));
+        }
+        assert!(parse_line("read -g [ a").is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! The `compress` and `decompress` commands: gzip for single files, zip for
+//! several files in one archive.
+//!
+//! Everything streams: bytes go from the source through the encoder or
+//! decoder into the destination a buffer at a time with `io::copy`, so a
+//! 10 GB file takes as little memory as a 10 KB one. A counting reader in the
+//! middle reports how far along each file is.
+//!
+//! Every output is written with `AtomicFile`: under a temporary name, then
+//! renamed into place once complete. A corrupt archive or a full disk leaves
+//! no half-written file behind, and an existing file is only replaced by a
+//! complete new one, when `WriteOptions::replace` allows it at all.
+
+use crate::error::{FileError, Operation};
+use crate::walk::{self, WalkOptions};
+use file_processing::atomic::{AtomicFile, WriteOptions};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// How much `copy` moves at a time.
+const BUFFER: usize = 64 * 1024;
+
+/// Called as the bytes of a file go through: the file, bytes done, bytes in all.
+pub type Progress<'a> = &'a mut dyn FnMut(&Path, u64, u64);
+
+/// What was read and written, for the summary line.
+#[derive(Debug, Default, PartialEq)]
+pub struct Sizes {
+    pub files: usize,
+    pub read: u64,
+    pub written: u64,
+}
+
+/// Compresses `path` into `path.gz`, leaving `path` in place; returns the new file.
+pub fn gzip(
+    path: &Path,
+    options: WriteOptions,
+    progress: Progress,
+) -> Result<(PathBuf, Sizes), FileError> {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".gz");
+    let output = PathBuf::from(name);
+
+    let mut input = Counting::open(path, progress)?;
+    let mut encoder = GzEncoder::new(create(&output, options)?, Compression::default());
+    let read = copy(&mut input, path, &mut encoder, &output)?;
+    let writing = |e| FileError::from_io(&output, Operation::Write, e);
+    encoder
+        .finish()
+        .map_err(writing)?
+        .commit()
+        .map_err(writing)?;
+    let written = fs::metadata(&output)
+        .map_err(|e| FileError::from_io(&output, Operation::Access, e))?
+        .len();
+    Ok((
+        output,
+        Sizes {
+            files: 1,
+            read,
+            written,
+        },
+    ))
+}
+
+/// Decompresses `path`, which must end in `.gz`, next to it without the suffix.
+pub fn gunzip(
+    path: &Path,
+    options: WriteOptions,
+    progress: Progress,
+) -> Result<(PathBuf, Sizes), FileError> {
+    let output = match path.extension() {
+        Some(extension) if extension == "gz" => path.with_extension(""),
+        _ => {
+            let error = io::Error::new(io::ErrorKind::InvalidInput, "not a .gz file");
+            return Err(FileError::from_io(path, Operation::Read, error));
+        }
+    };
+    let input = Counting::open(path, progress)?;
+    let read = input.total;
+    // Multi: `cat a.gz b.gz > c.gz` is a valid gzip file, as gzip itself reads it.
+    let mut decoder = MultiGzDecoder::new(BufReader::new(input));
+    let mut writer = create(&output, options)?;
+    // A decoding error is the input's: it is not gzip, or it is damaged.
+    let written = copy(&mut decoder, path, &mut writer, &output)?;
+    writer
+        .commit()
+        .map_err(|e| FileError::from_io(&output, Operation::Write, e))?;
+    Ok((
+        output,
+        Sizes {
+            files: 1,
+            read,
+            written,
+        },
+    ))
+}
+
+/// Puts `paths` into a new zip `archive`; directories go in with everything
+/// under them, each file under its path from the directory's parent
+/// (`compress --zip out.zip src` stores `src/main.rs`).
+pub fn zip(
+    archive: &Path,
+    paths: &[PathBuf],
+    options: WriteOptions,
+    progress: Progress,
+) -> Result<Sizes, FileError> {
+    let writing = |e| FileError::from_io(archive, Operation::Write, e);
+    let mut zip = ZipWriter::new(create(archive, options)?);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let walk = WalkOptions {
+        recursive: true,
+        glob: None,
+    };
+    let mut sizes = Sizes::default();
+    for path in paths {
+        let base = path.parent().unwrap_or(Path::new(""));
+        for file in walk::files(std::slice::from_ref(path), &walk) {
+            let file = file?;
+            // Zip names always use `/`, whatever the OS.
+            let name = entry_name(file.strip_prefix(base).unwrap_or(&file));
+            zip.start_file(name, deflated)
+                .map_err(|e| writing(e.into()))?;
+            let mut input = Counting::open(&file, progress)?;
+            sizes.read += copy(&mut input, &file, &mut zip, archive)?;
+            sizes.files += 1;
+        }
+    }
+    zip.finish()
+        .map_err(|e| writing(e.into()))?
+        .commit()
+        .map_err(writing)?;
+    sizes.written = fs::metadata(archive)
+        .map_err(|e| FileError::from_io(archive, Operation::Access, e))?
+        .len();
+    Ok(sizes)
+}
+
+/// Extracts the zip `archive` into the directory `into`, creating it.
+pub fn unzip(
+    archive: &Path,
+    into: &Path,
+    options: WriteOptions,
+    progress: Progress,
+) -> Result<Sizes, FileError> {
+    let reading = |e| FileError::from_io(archive, Operation::Read, e);
+    let creating = |path: &Path, e| FileError::from_io(path, Operation::Create, e);
+    let file = File::open(archive).map_err(|e| FileError::from_io(archive, Operation::Open, e))?;
+    let read = file
+        .metadata()
+        .map_err(|e| FileError::from_io(archive, Operation::Access, e))?
+        .len();
+    let mut zip = ZipArchive::new(BufReader::new(file)).map_err(|e| reading(e.into()))?;
+    let mut sizes = Sizes {
+        read,
+        ..Sizes::default()
+    };
+    fs::create_dir_all(into).map_err(|e| creating(into, e))?;
+    for index in 0..zip.len() {
+        let entry = zip.by_index(index).map_err(|e| reading(e.into()))?;
+        // A name like `../../.bashrc` would write outside `into` ("zip slip"):
+        // enclosed_name() refuses those.
+        let Some(name) = entry.enclosed_name() else {
+            let message = format!("unsafe path in archive: {}", entry.name());
+            return Err(reading(io::Error::new(io::ErrorKind::InvalidData, message)));
+        };
+        let output = into.join(name);
+        if entry.is_dir() {
+            fs::create_dir_all(&output).map_err(|e| creating(&output, e))?;
+            continue;
+        }
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent).map_err(|e| creating(parent, e))?;
+        }
+        let total = entry.size();
+        let mut input = Counting::new(entry, &output, total, progress);
+        let mut writer = create(&output, options)?;
+        sizes.written += copy(&mut input, archive, &mut writer, &output)?;
+        writer
+            .commit()
+            .map_err(|e| FileError::from_io(&output, Operation::Write, e))?;
+        sizes.files += 1;
+    }
+    Ok(sizes)
+}
+
+fn entry_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn create(path: &Path, options: WriteOptions) -> Result<AtomicFile, FileError> {
+    AtomicFile::create(path, options).map_err(|e| FileError::from_io(path, Operation::Create, e))
+}
+
+/// `io::copy`, but a failed read is `from`'s error and a failed write `to`'s.
+/// Returns the bytes copied.
+fn copy(
+    reader: &mut impl Read,
+    from: &Path,
+    writer: &mut impl Write,
+    to: &Path,
+) -> Result<u64, FileError> {
+    let mut buffer = vec![0; BUFFER];
+    let mut copied = 0;
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(FileError::from_io(from, Operation::Read, e)),
+        };
+        writer
+            .write_all(&buffer[..n])
+            .map_err(|e| FileError::from_io(to, Operation::Write, e))?;
+        copied += n as u64;
+    }
+}
+
+/// A reader that reports its progress, whenever another percent is done.
+struct Counting<'a, R> {
+    inner: R,
+    path: PathBuf,
+    done: u64,
+    total: u64,
+    percent: Option<u64>,
+    progress: Progress<'a>,
+}
+
+impl<'a> Counting<'a, BufReader<File>> {
+    fn open(path: &Path, progress: Progress<'a>) -> Result<Self, FileError> {
+        let file = File::open(path).map_err(|e| FileError::from_io(path, Operation::Open, e))?;
+        let total = file
+            .metadata()
+            .map_err(|e| FileError::from_io(path, Operation::Access, e))?
+            .len();
+        Ok(Counting::new(BufReader::new(file), path, total, progress))
+    }
+}
+
+impl<'a, R> Counting<'a, R> {
+    fn new(inner: R, path: &Path, total: u64, progress: Progress<'a>) -> Self {
+        Counting {
+            inner,
+            path: path.to_path_buf(),
+            done: 0,
+            total,
+            percent: None,
+            progress,
+        }
+    }
+}
+
+impl<R: Read> Read for Counting<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.done += n as u64;
+        let percent = (self.done * 100).checked_div(self.total).unwrap_or(100);
+        // An empty file is 100% done at once.
+        if self.percent != Some(percent) {
+            self.percent = Some(percent);
+            (self.progress)(&self.path, self.done, self.total);
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Chain;
+
+    /// What the command line does without --force or --backup.
+    const KEEP: WriteOptions = WriteOptions {
+        replace: false,
+        backup: false,
+        sync: false,
+    };
+
+    /// Compressible, but not trivially: a few hundred kilobytes of numbered lines.
+    fn text() -> String {
+        (0..40_000).map(|i| format!("line {}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, text()).unwrap();
+
+        let mut reports = Vec::new();
+        let mut progress = |_: &Path, done: u64, total: u64| reports.push((done, total));
+        let (gz, sizes) = gzip(&path, KEEP, &mut progress).unwrap();
+        assert_eq!(gz, dir.path().join("notes.txt.gz"));
+        assert_eq!(sizes.read, text().len() as u64);
+        assert!(sizes.written < sizes.read / 3);
+        // Rising to the whole file, at most once per percent.
+        assert_eq!(reports.last(), Some(&(sizes.read, sizes.read)));
+        assert!(reports.len() <= 101);
+
+        // The original is still there: decompressing over it is refused...
+        let error = gunzip(&gz, KEEP, &mut |_, _, _| {}).unwrap_err();
+        // The output is what is in the way, not the archive.
+        assert!(matches!(
+            &error,
+            FileError::Io { path: output, operation: Operation::Create, .. } if *output == path
+        ));
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        fs::write(&path, "old").unwrap();
+        // ...unless asked for, here keeping the old one.
+        let backup = WriteOptions {
+            replace: true,
+            backup: true,
+            ..KEEP
+        };
+        let (output, sizes) = gunzip(&gz, backup, &mut |_, _, _| {}).unwrap();
+        assert_eq!(output, path);
+        assert_eq!(sizes.written, text().len() as u64);
+        assert_eq!(fs::read_to_string(&path).unwrap(), text());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("notes.txt.bak")).unwrap(),
+            "old"
+        );
+
+        let error = gunzip(&path, KEEP, &mut |_, _, _| {}).unwrap_err();
+        assert_eq!(
+            Chain(&error).to_string(),
+            format!("cannot read {}: not a .gz file", path.display())
+        );
+    }
+
+    #[test]
+    fn test_zip_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("a.txt"), text()).unwrap();
+        fs::write(src.join("nested/b.txt"), "b").unwrap();
+        fs::write(dir.path().join("empty.txt"), "").unwrap();
+
+        let archive = dir.path().join("out.zip");
+        let paths = [src.clone(), dir.path().join("empty.txt")];
+        let sizes = zip(&archive, &paths, KEEP, &mut |_, _, _| {}).unwrap();
+        assert_eq!(sizes.files, 3);
+
+        let into = dir.path().join("extracted");
+        let mut extracted = Vec::new();
+        let mut progress = |path: &Path, done: u64, total: u64| {
+            if done == total {
+                extracted.push(path.strip_prefix(&into).unwrap().to_path_buf());
+            }
+        };
+        let sizes = unzip(&archive, &into, KEEP, &mut progress).unwrap();
+        assert_eq!(sizes.files, 3);
+        assert_eq!(
+            extracted,
+            [
+                Path::new("src/a.txt"),
+                Path::new("src/nested/b.txt"),
+                Path::new("empty.txt")
+            ]
+        );
+        assert_eq!(fs::read_to_string(into.join("src/a.txt")).unwrap(), text());
+        assert_eq!(
+            fs::read_to_string(into.join("src/nested/b.txt")).unwrap(),
+            "b"
+        );
+    }
+
+    #[test]
+    fn test_unzip_refuses_paths_outside() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("evil.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("../evil.txt", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"gotcha").unwrap();
+        zip.finish().unwrap();
+
+        let error = unzip(&archive, &dir.path().join("out"), KEEP, &mut |_, _, _| {}).unwrap_err();
+        assert_eq!(
+            Chain(&error).to_string(),
+            format!(
+                "cannot read {}: unsafe path in archive: ../evil.txt",
+                archive.display()
+            )
+        );
+        assert!(!dir.path().join("evil.txt").exists());
+    }
+}
@@ -0,0 +1,538 @@
+//! The `encrypt` and `decrypt` commands: AES-256-GCM with a key derived from
+//! a passphrase by Argon2id.
+//!
+//! An encrypted file is a header, then the data in chunks:
+//!
+//! ```text
+//! "FPCRYPT" 1       magic and format version   8 bytes
+//! m, t, p           Argon2 costs, u32 LE each  12 bytes
+//! salt              random                     16 bytes
+//! nonce             random                     7 bytes
+//! key check         GCM tag of nothing         16 bytes
+//! chunks            64 KiB + 16-byte tag each
+//! ```
+//!
+//! The chunks follow the STREAM construction: each has its own nonce made of
+//! the random part, a counter and a last-chunk flag, so chunks cannot be
+//! reordered, dropped or cut off at the end without decryption failing. Every
+//! chunk is authenticated together with the header (as associated data), so
+//! the header cannot be changed either. The key check tells a wrong passphrase
+//! apart from a damaged file before any data is decrypted.
+//!
+//! Decrypted chunks are written as soon as they check out, before the later
+//! ones are read: the caller writes to an `AtomicFile` and only commits it
+//! once the whole file has decrypted, so a damaged file leaves nothing behind.
+
+use crate::error::{FileError, Operation};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::aes::cipher::generic_array::GenericArray;
+use aes_gcm::{Aes256Gcm, Key};
+use argon2::{Algorithm, Argon2, Params, Version};
+use file_processing::atomic::{AtomicFile, WriteOptions};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"FPCRYPT\x01";
+const SALT: usize = 16;
+/// The random part of the chunk nonces: 12 bytes minus the 5 of STREAM.
+const NONCE: usize = 7;
+const TAG: usize = 16;
+const HEADER: usize = MAGIC.len() + 12 + SALT + NONCE;
+/// Plaintext per chunk.
+const CHUNK: usize = 64 * 1024;
+/// The nonce of the key check. STREAM nonces end in 0 or 1 (the last-chunk
+/// flag), so this one can never be reused by a chunk.
+const CHECK_NONCE: [u8; 12] = [0xff; 12];
+
+/// The most a header may ask for: 1 GiB, 32 passes, 16 lanes. The costs come
+/// from the file, so without a limit a forged one could ask for terabytes of
+/// memory, or passes that never end, before the passphrase is even checked.
+const MAX_COST: KdfCost = KdfCost {
+    memory: 1024 * 1024,
+    iterations: 32,
+    parallelism: 16,
+};
+
+/// How expensive deriving the key is: each guess of an attacker costs as much.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KdfCost {
+    /// Memory in KiB.
+    pub memory: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfCost {
+    /// The recommendation of OWASP and of the argon2 crate: 19 MiB, 2 passes.
+    fn default() -> Self {
+        KdfCost {
+            memory: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CryptError {
+    /// Opening, reading or writing one of the files.
+    File(FileError),
+    /// Reading the input of `encrypt` or `decrypt`, which do not know its
+    /// path: `encrypt_file` and `decrypt_file` turn it into a `File` error.
+    Read(io::Error),
+    /// Writing the output, likewise.
+    Write(io::Error),
+    /// Reading the passphrase from the terminal.
+    Terminal(io::Error),
+    /// STREAM's counter ran out: 2^32 chunks of 64 KiB, 256 TiB.
+    TooLong,
+    /// The file does not start with the magic bytes.
+    NotEncrypted,
+    UnsupportedVersion(u8),
+    WrongPassphrase,
+    /// Authentication failed at this byte offset: flipped bits, or a file cut short.
+    Damaged {
+        offset: u64,
+    },
+    PassphraseMismatch,
+    EmptyPassphrase,
+    /// Costs Argon2 does not accept, or above `MAX_COST`: from the command
+    /// line, or a forged header.
+    BadCost(String),
+}
+
+impl fmt::Display for CryptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CryptError::File(e) => write!(f, "{}", e),
+            CryptError::Read(_) => write!(f, "cannot read the input"),
+            CryptError::Write(_) => write!(f, "cannot write the output"),
+            CryptError::Terminal(_) => write!(f, "cannot read the passphrase"),
+            CryptError::TooLong => write!(f, "too much data for one file"),
+            CryptError::NotEncrypted => write!(f, "not a file encrypted by this program"),
+            CryptError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "encrypted with format version {}, which this version cannot read",
+                    version
+                )
+            }
+            CryptError::WrongPassphrase => write!(f, "wrong passphrase"),
+            CryptError::Damaged { offset } => write!(
+                f,
+                "the file is damaged or cut short (the data at byte {} does not check out)",
+                offset
+            ),
+            CryptError::PassphraseMismatch => write!(f, "the passphrases do not match"),
+            CryptError::EmptyPassphrase => write!(f, "the passphrase is empty"),
+            CryptError::BadCost(e) => write!(f, "invalid key derivation cost: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CryptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // Says what the file error says, so its cause is the next one.
+            CryptError::File(e) => e.source(),
+            CryptError::Read(e) | CryptError::Write(e) | CryptError::Terminal(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<FileError> for CryptError {
+    fn from(e: FileError) -> Self {
+        CryptError::File(e)
+    }
+}
+
+impl CryptError {
+    /// Puts the paths into a `Read` or `Write` error.
+    fn naming(self, input: &Path, output: &Path) -> CryptError {
+        match self {
+            CryptError::Read(e) => FileError::from_io(input, Operation::Read, e).into(),
+            CryptError::Write(e) => FileError::from_io(output, Operation::Write, e).into(),
+            other => other,
+        }
+    }
+}
+
+/// Opens `path` and creates `output`, for `encrypt_file` and `decrypt_file`.
+fn open_pair(
+    path: &Path,
+    output: &Path,
+    write: WriteOptions,
+) -> Result<(File, AtomicFile), FileError> {
+    let file = AtomicFile::create(output, write)
+        .map_err(|e| FileError::from_io(output, Operation::Create, e))?;
+    let input = File::open(path).map_err(|e| FileError::from_io(path, Operation::Open, e))?;
+    Ok((input, file))
+}
+
+/// Encrypts `path` into `path.enc`; returns the new file.
+pub fn encrypt_file(
+    path: &Path,
+    passphrase: &str,
+    write: WriteOptions,
+) -> Result<PathBuf, CryptError> {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".enc");
+    let output = PathBuf::from(name);
+    let (input, mut file) = open_pair(path, &output, write)?;
+    encrypt(input, &mut file, passphrase, KdfCost::default())
+        .map_err(|e| e.naming(path, &output))?;
+    file.commit()
+        .map_err(|e| FileError::from_io(&output, Operation::Write, e))?;
+    Ok(output)
+}
+
+/// Decrypts `path`, which must end in `.enc`, next to it without the suffix.
+pub fn decrypt_file(
+    path: &Path,
+    passphrase: &str,
+    write: WriteOptions,
+) -> Result<PathBuf, CryptError> {
+    let output = match path.extension() {
+        Some(extension) if extension == "enc" => path.with_extension(""),
+        _ => return Err(CryptError::NotEncrypted),
+    };
+    let (input, mut file) = open_pair(path, &output, write)?;
+    decrypt(input, &mut file, passphrase).map_err(|e| e.naming(path, &output))?;
+    file.commit()
+        .map_err(|e| FileError::from_io(&output, Operation::Write, e))?;
+    Ok(output)
+}
+
+/// Encrypts everything `input` holds into `output`.
+pub fn encrypt(
+    input: impl Read,
+    output: &mut impl Write,
+    passphrase: &str,
+    cost: KdfCost,
+) -> Result<(), CryptError> {
+    let mut salt = [0; SALT];
+    let mut nonce = [0; NONCE];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER);
+    header.extend_from_slice(MAGIC);
+    for value in [cost.memory, cost.iterations, cost.parallelism] {
+        header.extend_from_slice(&value.to_le_bytes());
+    }
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt, cost)?);
+    output.write_all(&header).map_err(CryptError::Write)?;
+    output
+        .write_all(&key_check(&cipher, &header))
+        .map_err(CryptError::Write)?;
+
+    let mut encryptor = EncryptorBE32::from_aead(cipher, GenericArray::from_slice(&nonce));
+    let mut input = BufReader::new(input);
+    let mut buffer = vec![0; CHUNK];
+    loop {
+        let n = read_full(&mut input, &mut buffer).map_err(CryptError::Read)?;
+        let payload = Payload {
+            msg: &buffer[..n],
+            aad: &header,
+        };
+        // The last chunk is marked as such; an empty file is one empty last chunk.
+        if n < CHUNK || input.fill_buf().map_err(CryptError::Read)?.is_empty() {
+            let chunk = encryptor
+                .encrypt_last(payload)
+                .map_err(|_| CryptError::TooLong)?;
+            output.write_all(&chunk).map_err(CryptError::Write)?;
+            return Ok(());
+        }
+        let chunk = encryptor
+            .encrypt_next(payload)
+            .map_err(|_| CryptError::TooLong)?;
+        output.write_all(&chunk).map_err(CryptError::Write)?;
+    }
+}
+
+/// Decrypts what `encrypt` wrote.
+pub fn decrypt(
+    input: impl Read,
+    output: &mut impl Write,
+    passphrase: &str,
+) -> Result<(), CryptError> {
+    let mut input = BufReader::new(input);
+    let mut header = [0; HEADER];
+    let n = read_full(&mut input, &mut header).map_err(CryptError::Read)?;
+    if n < MAGIC.len() - 1 || header[..MAGIC.len() - 1] != MAGIC[..MAGIC.len() - 1] {
+        return Err(CryptError::NotEncrypted);
+    }
+    if header[MAGIC.len() - 1] != MAGIC[MAGIC.len() - 1] {
+        return Err(CryptError::UnsupportedVersion(header[MAGIC.len() - 1]));
+    }
+    let mut check = [0; TAG];
+    if n < HEADER || read_full(&mut input, &mut check).map_err(CryptError::Read)? < TAG {
+        return Err(CryptError::Damaged { offset: n as u64 });
+    }
+
+    let number = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+    let at = MAGIC.len();
+    let cost = KdfCost {
+        memory: number(at),
+        iterations: number(at + 4),
+        parallelism: number(at + 8),
+    };
+    let salt = &header[at + 12..at + 12 + SALT];
+    let nonce = &header[at + 12 + SALT..];
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt, cost)?);
+    if key_check(&cipher, &header) != check {
+        return Err(CryptError::WrongPassphrase);
+    }
+
+    let mut decryptor = DecryptorBE32::from_aead(cipher, GenericArray::from_slice(nonce));
+    let mut buffer = vec![0; CHUNK + TAG];
+    let mut offset = (HEADER + TAG) as u64;
+    loop {
+        let n = read_full(&mut input, &mut buffer).map_err(CryptError::Read)?;
+        let payload = Payload {
+            msg: &buffer[..n],
+            aad: &header,
+        };
+        let damaged = CryptError::Damaged { offset };
+        // A file cut right after a full chunk ends here too: that chunk was
+        // not marked as the last, so it fails.
+        if n < buffer.len() || input.fill_buf().map_err(CryptError::Read)?.is_empty() {
+            let chunk = decryptor.decrypt_last(payload).map_err(|_| damaged)?;
+            output.write_all(&chunk).map_err(CryptError::Write)?;
+            return Ok(());
+        }
+        let chunk = decryptor.decrypt_next(payload).map_err(|_| damaged)?;
+        output.write_all(&chunk).map_err(CryptError::Write)?;
+        offset += n as u64;
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], cost: KdfCost) -> Result<Key<Aes256Gcm>, CryptError> {
+    // Checked before anything is allocated; encrypting is held to the same
+    // limits, so it never writes a file that cannot be decrypted.
+    for (name, value, max) in [
+        ("memory", cost.memory, MAX_COST.memory),
+        ("iterations", cost.iterations, MAX_COST.iterations),
+        ("parallelism", cost.parallelism, MAX_COST.parallelism),
+    ] {
+        if value > max {
+            return Err(CryptError::BadCost(format!(
+                "{} is {}, more than the limit of {}",
+                name, value, max
+            )));
+        }
+    }
+    let bad_cost = |e: argon2::Error| CryptError::BadCost(e.to_string());
+    let params =
+        Params::new(cost.memory, cost.iterations, cost.parallelism, Some(32)).map_err(bad_cost)?;
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(bad_cost)?;
+    Ok(key)
+}
+
+/// The tag of an empty message with the header as associated data: only the
+/// right key gives the one stored in the file.
+fn key_check(cipher: &Aes256Gcm, header: &[u8]) -> Vec<u8> {
+    let payload = Payload {
+        msg: &[],
+        aad: header,
+    };
+    cipher
+        .encrypt(GenericArray::from_slice(&CHECK_NONCE), payload)
+        .expect("an empty message always encrypts")
+}
+
+/// Reads until `buffer` is full or the input ends; returns the bytes read.
+fn read_full(input: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match input.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Asks for the passphrase on the terminal, without echoing it; twice when
+/// encrypting, so a typo does not lock the file away for good.
+/// `FILE_PROCESSING_PASSPHRASE`, if set, is used instead, for scripts.
+pub fn ask_passphrase(confirm: bool) -> Result<String, CryptError> {
+    if let Some(passphrase) = std::env::var("FILE_PROCESSING_PASSPHRASE")
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+    {
+        return Ok(passphrase);
+    }
+    let passphrase = rpassword::prompt_password("Passphrase: ").map_err(CryptError::Terminal)?;
+    if passphrase.is_empty() {
+        return Err(CryptError::EmptyPassphrase);
+    }
+    if confirm
+        && rpassword::prompt_password("Passphrase again: ").map_err(CryptError::Terminal)?
+            != passphrase
+    {
+        return Err(CryptError::PassphraseMismatch);
+    }
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Chain;
+
+    /// As cheap as Argon2 allows: the tests are about the format, not the cost.
+    const CHEAP: KdfCost = KdfCost {
+        memory: 8,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn encrypted(plain: &[u8], passphrase: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        encrypt(plain, &mut out, passphrase, CHEAP).unwrap();
+        out
+    }
+
+    fn decrypted(sealed: &[u8], passphrase: &str) -> Result<Vec<u8>, CryptError> {
+        let mut out = Vec::new();
+        decrypt(sealed, &mut out, passphrase).map(|_| out)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // Empty, short, exactly one chunk, and several chunks.
+        for len in [0, 5, CHUNK, 3 * CHUNK + 7] {
+            let plain: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let sealed = encrypted(&plain, "hunter2");
+            let chunks = len.div_ceil(CHUNK).max(1);
+            assert_eq!(sealed.len(), HEADER + TAG + len + chunks * TAG);
+            assert_eq!(decrypted(&sealed, "hunter2").unwrap(), plain);
+        }
+        // A fresh salt and nonce every time.
+        assert_ne!(encrypted(b"same", "pw"), encrypted(b"same", "pw"));
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let sealed = encrypted(b"secret", "right");
+        assert!(matches!(
+            decrypted(&sealed, "wrong"),
+            Err(CryptError::WrongPassphrase)
+        ));
+        // A changed header is caught by the key check too.
+        let mut forged = sealed.clone();
+        forged[MAGIC.len()] ^= 1;
+        assert!(matches!(
+            decrypted(&forged, "right"),
+            Err(CryptError::WrongPassphrase)
+        ));
+    }
+
+    #[test]
+    fn test_damaged_files() {
+        let plain = vec![7; 2 * CHUNK + 10];
+        let sealed = encrypted(&plain, "pw");
+        let second = (HEADER + TAG + CHUNK + TAG) as u64;
+
+        let mut flipped = sealed.clone();
+        flipped[second as usize + 3] ^= 0x80;
+        assert!(matches!(
+            decrypted(&flipped, "pw"),
+            Err(CryptError::Damaged { offset }) if offset == second
+        ));
+
+        // Cut after the second chunk: it was not the last, so it fails.
+        let cut = &sealed[..(second as usize + CHUNK + TAG)];
+        assert!(matches!(
+            decrypted(cut, "pw"),
+            Err(CryptError::Damaged { offset }) if offset == second
+        ));
+        assert!(matches!(
+            decrypted(&sealed[..20], "pw"),
+            Err(CryptError::Damaged { offset: 20 })
+        ));
+    }
+
+    #[test]
+    fn test_forged_costs_are_refused() {
+        let sealed = encrypted(b"secret", "pw");
+        let at = MAGIC.len();
+        // 4 TiB of memory, endless passes, and lanes beyond the limit: each
+        // is refused from the header alone, without deriving a key.
+        for (offset, expected) in [(0, "memory"), (4, "iterations"), (8, "parallelism")] {
+            let mut forged = sealed.clone();
+            forged[at + offset..at + offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            match decrypted(&forged, "pw") {
+                Err(CryptError::BadCost(message)) => {
+                    assert!(message.starts_with(expected), "{}", message)
+                }
+                other => panic!("{}: {:?}", expected, other.map(|_| ())),
+            }
+        }
+        // Below the limits, but not a cost Argon2 takes: no passes at all.
+        let mut forged = sealed.clone();
+        forged[at + 4..at + 8].copy_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(
+            decrypted(&forged, "pw"),
+            Err(CryptError::BadCost(_))
+        ));
+        // What `encrypt_file` writes is well within the limits.
+        let default = KdfCost::default();
+        assert!(default.memory <= MAX_COST.memory);
+        assert!(default.iterations <= MAX_COST.iterations);
+        assert!(default.parallelism <= MAX_COST.parallelism);
+    }
+
+    #[test]
+    fn test_not_encrypted() {
+        assert!(matches!(
+            decrypted(b"plain text, no header", "pw"),
+            Err(CryptError::NotEncrypted)
+        ));
+        let mut future = encrypted(b"x", "pw");
+        future[MAGIC.len() - 1] = 9;
+        assert!(matches!(
+            decrypted(&future, "pw"),
+            Err(CryptError::UnsupportedVersion(9))
+        ));
+    }
+
+    #[test]
+    fn test_errors_name_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.txt.enc");
+        let error = decrypt_file(&path, "pw", WriteOptions::default()).unwrap_err();
+        assert_eq!(
+            Chain(&error).to_string(),
+            format!("cannot open {}: it does not exist", path.display())
+        );
+
+        // What `encrypt` and `decrypt` cannot say, the `_file` functions add.
+        let (input, output) = (Path::new("a.txt"), Path::new("a.txt.enc"));
+        let full = CryptError::Write(io::Error::other("disk full")).naming(input, output);
+        assert_eq!(
+            Chain(&full).to_string(),
+            "cannot write a.txt.enc: disk full"
+        );
+        let gone = CryptError::Read(io::ErrorKind::NotFound.into()).naming(input, output);
+        assert_eq!(
+            Chain(&gone).to_string(),
+            "cannot read a.txt: it does not exist"
+        );
+    }
+}
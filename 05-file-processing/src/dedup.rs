@@ -0,0 +1,292 @@
+//! The `dedup` command: find the files with the same contents, and optionally
+//! replace the copies with hard links to the original, or delete them.
+//!
+//! Hashing every file would be slow, so the files are first grouped by size,
+//! which takes only their metadata: a file whose size no other file has cannot
+//! have a duplicate. Only the files left are hashed, with BLAKE3, in parallel.
+//!
+//! Nothing is changed unless asked for twice: `--link` or `--delete` says what
+//! to do with the copies, `--apply` says to really do it. Without `--apply`,
+//! the output shows what would happen.
+
+use crate::error::{Chain, FileError, Operation};
+use crate::hash::{self, Algorithm};
+use crate::walk::WalkError;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// What to do with the copies.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Action {
+    /// Only list them.
+    #[default]
+    Report,
+    /// Replace each with a hard link to the original: the data is stored once,
+    /// every name still works.
+    Link,
+    Delete,
+}
+
+/// Files with the same contents: the first in path order is the original.
+#[derive(Debug, PartialEq)]
+pub struct Group {
+    pub size: u64,
+    pub original: PathBuf,
+    pub copies: Vec<PathBuf>,
+}
+
+/// What `run` did, or would do.
+#[derive(Debug, Default, PartialEq)]
+pub struct Outcome {
+    pub copies: usize,
+    /// Taken by the copies, freed by linking or deleting them.
+    pub bytes: u64,
+    /// Copies that could not be linked or deleted.
+    pub failed: usize,
+}
+
+/// The groups of identical files among `files`, sorted by original, and the
+/// files that could not be read. Empty files are left out: there is nothing
+/// to free. So are extra names of a file already hard-linked.
+pub fn find(files: Vec<Result<PathBuf, WalkError>>) -> (Vec<Group>, Vec<FileError>) {
+    let mut errors = Vec::new();
+    let mut by_size: BTreeMap<u64, Vec<PathBuf>> = BTreeMap::new();
+    let mut seen = HashSet::new();
+    for file in files {
+        let metadata = file
+            .map_err(FileError::from)
+            .and_then(|path| match fs::metadata(&path) {
+                Ok(metadata) => Ok((path, metadata)),
+                Err(e) => Err(FileError::from_io(&path, Operation::Access, e)),
+            });
+        match metadata {
+            Ok((path, metadata)) => {
+                let new = identity(&metadata).is_none_or(|id| seen.insert(id));
+                if metadata.len() > 0 && new {
+                    by_size.entry(metadata.len()).or_default().push(path);
+                }
+            }
+            Err(error) => errors.push(error),
+        }
+    }
+
+    let candidates = by_size
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .flat_map(|(size, paths)| paths.into_iter().map(move |path| (size, path)));
+    let (sizes, paths): (Vec<u64>, Vec<_>) =
+        candidates.map(|(size, path)| (size, Ok(path))).unzip();
+
+    let mut by_contents: HashMap<(u64, String), Vec<PathBuf>> = HashMap::new();
+    for (size, hashed) in sizes
+        .into_iter()
+        .zip(hash::hash_files(paths, Algorithm::Blake3))
+    {
+        match hashed {
+            Ok((path, hash)) => by_contents.entry((size, hash)).or_default().push(path),
+            Err(error) => errors.push(error),
+        }
+    }
+    let mut groups: Vec<Group> = by_contents
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|((size, _), mut paths)| {
+            paths.sort();
+            let original = paths.remove(0);
+            Group {
+                size,
+                original,
+                copies: paths,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.original.cmp(&b.original));
+    (groups, errors)
+}
+
+/// What makes two paths the same file on disk, where the OS tells.
+#[cfg(unix)]
+pub fn identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn identity(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Lists the groups and, with `apply`, links or deletes the copies.
+pub fn run(
+    groups: &[Group],
+    action: Action,
+    apply: bool,
+    out: &mut impl Write,
+) -> io::Result<Outcome> {
+    let mut outcome = Outcome::default();
+    for group in groups {
+        writeln!(out, "{} ({} bytes)", group.original.display(), group.size)?;
+        for copy in &group.copies {
+            outcome.copies += 1;
+            let done = match (action, apply) {
+                (Action::Report, _) => Ok(""),
+                (Action::Link, false) => Ok(": would be linked"),
+                (Action::Delete, false) => Ok(": would be deleted"),
+                (Action::Link, true) => link(&group.original, copy).map(|_| ": linked"),
+                (Action::Delete, true) => fs::remove_file(copy)
+                    .map(|_| ": deleted")
+                    .map_err(|e| FileError::from_io(copy, Operation::Remove, e)),
+            };
+            match done {
+                Ok(done) => {
+                    outcome.bytes += group.size;
+                    writeln!(out, "  {}{}", copy.display(), done)?;
+                }
+                Err(e) => {
+                    outcome.failed += 1;
+                    writeln!(out, "  error: {}", Chain(&e))?;
+                }
+            }
+        }
+    }
+
+    let freed = match (action, apply) {
+        (Action::Report, _) => "could be freed with --link or --delete",
+        (_, false) => "would be freed (add --apply to do it)",
+        (_, true) => "freed",
+    };
+    if !groups.is_empty() {
+        writeln!(out)?;
+    }
+    writeln!(
+        out,
+        "{} duplicate(s) of {} file(s): {} byte(s) {}",
+        outcome.copies,
+        groups.len(),
+        outcome.bytes,
+        freed
+    )?;
+    Ok(outcome)
+}
+
+/// Replaces `copy` with a hard link to `original`. The link is made under a
+/// temporary name and renamed over the copy, so the copy is never missing.
+fn link(original: &Path, copy: &Path) -> Result<(), FileError> {
+    let mut name = copy.file_name().unwrap_or_default().to_os_string();
+    name.push(".dedup");
+    let temporary = copy.with_file_name(name);
+    fs::hard_link(original, &temporary)
+        .map_err(|e| FileError::from_io(&temporary, Operation::Link, e))?;
+    fs::rename(&temporary, copy).map_err(|e| {
+        let _ = fs::remove_file(&temporary);
+        FileError::from_io(copy, Operation::Link, e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::walk::{self, WalkOptions};
+
+    /// a.txt, b.txt and sub/c.txt alike; d.txt the same size but different;
+    /// two empty files.
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        for (name, contents) in [
+            ("a.txt", "same"),
+            ("b.txt", "same"),
+            ("sub/c.txt", "same"),
+            ("d.txt", "diff"),
+            ("e.txt", ""),
+            ("f.txt", ""),
+        ] {
+            fs::write(dir.path().join(name), contents).unwrap();
+        }
+        dir
+    }
+
+    fn find_in(dir: &Path) -> Vec<Group> {
+        let walk = WalkOptions {
+            recursive: true,
+            glob: None,
+        };
+        let (groups, errors) = find(walk::files(&[dir.to_path_buf()], &walk));
+        assert!(errors.is_empty());
+        groups
+    }
+
+    #[test]
+    fn test_find() {
+        let dir = tree();
+        assert_eq!(
+            find_in(dir.path()),
+            [Group {
+                size: 4,
+                original: dir.path().join("a.txt"),
+                copies: vec![dir.path().join("b.txt"), dir.path().join("sub/c.txt")],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_dry_run_changes_nothing() {
+        let dir = tree();
+        let groups = find_in(dir.path());
+        let mut out = Vec::new();
+        let outcome = run(&groups, Action::Delete, false, &mut out).unwrap();
+        assert_eq!(
+            outcome,
+            Outcome {
+                copies: 2,
+                bytes: 8,
+                failed: 0
+            }
+        );
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("b.txt: would be deleted\n"));
+        assert!(out.ends_with(
+            "2 duplicate(s) of 1 file(s): 8 byte(s) would be freed (add --apply to do it)\n"
+        ));
+        assert!(dir.path().join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_apply() {
+        let dir = tree();
+        let groups = find_in(dir.path());
+        run(&groups, Action::Link, true, &mut Vec::new()).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.txt")).unwrap(),
+            "same"
+        );
+        // Linked files are one file now: nothing left to deduplicate.
+        if cfg!(unix) {
+            assert!(find_in(dir.path()).is_empty());
+        }
+
+        let dir = tree();
+        let groups = find_in(dir.path());
+        run(&groups, Action::Delete, true, &mut Vec::new()).unwrap();
+        assert!(dir.path().join("a.txt").exists());
+        assert!(!dir.path().join("sub/c.txt").exists());
+    }
+
+    #[test]
+    fn test_failed_copies_are_reported() {
+        let dir = tree();
+        let groups = find_in(dir.path());
+        // Gone between finding and deleting.
+        fs::remove_file(dir.path().join("b.txt")).unwrap();
+        let mut out = Vec::new();
+        let outcome = run(&groups, Action::Delete, true, &mut out).unwrap();
+        assert_eq!((outcome.copies, outcome.failed), (2, 1));
+        let out = String::from_utf8(out).unwrap();
+        let line = out.lines().nth(1).unwrap();
+        assert!(line.starts_with("  error: cannot remove "));
+        assert!(line.ends_with("b.txt: it does not exist"));
+        assert!(out.contains("c.txt: deleted\n"));
+    }
+}
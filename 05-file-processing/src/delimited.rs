@@ -0,0 +1,700 @@
+//! The `csv` command: pick columns, filter rows and aggregate delimited files.
+//!
+//! Rows are read and written one at a time, so a large file is never held in
+//! memory; only `--group-by` keeps something per group (a few numbers each).
+//!
+//! Columns are named by their header, or by their 1-based position (`2`) when
+//! the file has no header or the name is awkward to type.
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+/// Whether the first row names the columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Header {
+    /// Guess from the first row: see `looks_like_header`.
+    #[default]
+    Auto,
+    Yes,
+    No,
+}
+
+impl FromStr for Header {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Header::Auto),
+            "yes" => Ok(Header::Yes),
+            "no" => Ok(Header::No),
+            _ => Err(format!("'{}' is not one of auto, yes or no", value)),
+        }
+    }
+}
+
+/// The output formats.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Csv,
+    /// An array of objects, one per row, keyed by column name.
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("'{}' is not one of csv or json", value)),
+        }
+    }
+}
+
+/// A comparison in `--where`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// `~`: the value contains the text.
+    Contains,
+}
+
+/// One `--where COLUMN<OP>VALUE` condition, like `price>=10` or `name~smith`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub column: String,
+    pub op: Op,
+    pub value: String,
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        // Two-character operators first, so `>=` is not read as `>` and `=value`.
+        const OPS: [(&str, Op); 7] = [
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("=", Op::Eq),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+            ("~", Op::Contains),
+        ];
+        let (at, symbol, op) = OPS
+            .iter()
+            .filter_map(|&(symbol, op)| text.find(symbol).map(|at| (at, symbol, op)))
+            // The leftmost operator; at the same place, the longer one (listed first).
+            .min_by_key(|&(at, _, _)| at)
+            .ok_or_else(|| format!("'{}' has no operator (=, !=, <, <=, >, >=, ~)", text))?;
+        let column = &text[..at];
+        if column.is_empty() {
+            return Err(format!("'{}' has no column before '{}'", text, symbol));
+        }
+        Ok(Filter {
+            column: column.to_string(),
+            op,
+            value: text[at + symbol.len()..].to_string(),
+        })
+    }
+}
+
+impl Filter {
+    /// Numbers compare as numbers (so `9 < 10`), anything else as text.
+    fn holds(&self, field: &str) -> bool {
+        if self.op == Op::Contains {
+            return field.contains(&self.value);
+        }
+        let ordering = match (number(field), number(&self.value)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => Some(field.cmp(&self.value)),
+        };
+        let Some(ordering) = ordering else {
+            // NaN: equal to nothing, not even itself.
+            return self.op == Op::Ne;
+        };
+        match self.op {
+            Op::Eq => ordering.is_eq(),
+            Op::Ne => ordering.is_ne(),
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            Op::Ge => ordering.is_ge(),
+            Op::Contains => unreachable!(),
+        }
+    }
+}
+
+/// What `--agg` computes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Count,
+    Sum,
+    Avg,
+}
+
+/// One `--agg`: `count`, `sum:COLUMN` or `avg:COLUMN`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub function: Function,
+    /// `None` for `count`, which counts rows.
+    pub column: Option<String>,
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (function, column) = match text.split_once(':') {
+            Some((function, column)) => (function, Some(column.to_string())),
+            None => (text, None),
+        };
+        let function = match (function, &column) {
+            ("count", None) => Function::Count,
+            ("sum", Some(_)) => Function::Sum,
+            ("avg", Some(_)) => Function::Avg,
+            _ => return Err(format!("'{}' is not count, sum:COLUMN or avg:COLUMN", text)),
+        };
+        Ok(Aggregate { function, column })
+    }
+}
+
+impl fmt::Display for Aggregate {
+    /// The output column's name: `count`, `sum(price)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.function, &self.column) {
+            (Function::Count, _) => write!(f, "count"),
+            (Function::Sum, Some(column)) => write!(f, "sum({})", column),
+            (Function::Avg, Some(column)) => write!(f, "avg({})", column),
+            (_, None) => unreachable!("sum and avg always have a column"),
+        }
+    }
+}
+
+/// Everything the `csv` command can be asked to do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub header: Header,
+    /// The columns to print, in this order; empty for all of them.
+    pub select: Vec<String>,
+    /// Every one must hold for a row to be kept.
+    pub filters: Vec<Filter>,
+    pub group_by: Option<String>,
+    /// With none, rows are printed; with some, one row per group (or one in all).
+    pub aggregates: Vec<Aggregate>,
+    pub format: Format,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            header: Header::Auto,
+            select: Vec::new(),
+            filters: Vec::new(),
+            group_by: None,
+            aggregates: Vec::new(),
+            format: Format::Csv,
+        }
+    }
+}
+
+/// Why the `csv` command failed.
+#[derive(Debug)]
+pub enum CsvError {
+    /// Malformed input (say, a row with more fields than the others), or a
+    /// failure reading or writing.
+    Csv(csv::Error),
+    Io(io::Error),
+    UnknownColumn(String),
+    /// `sum` or `avg` over a field that is not a number. `line` is 1-based.
+    NotANumber {
+        column: String,
+        value: String,
+        line: u64,
+    },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsvError::Csv(e) => write!(f, "{}", e),
+            CsvError::Io(e) => write!(f, "{}", e),
+            CsvError::UnknownColumn(column) => write!(f, "no column named '{}'", column),
+            CsvError::NotANumber {
+                column,
+                value,
+                line,
+            } => write!(
+                f,
+                "line {}: '{}' in column '{}' is not a number",
+                line, value, column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<csv::Error> for CsvError {
+    fn from(e: csv::Error) -> Self {
+        CsvError::Csv(e)
+    }
+}
+
+impl From<io::Error> for CsvError {
+    fn from(e: io::Error) -> Self {
+        CsvError::Io(e)
+    }
+}
+
+/// Reads delimited rows from `input` and writes the result to `out`.
+pub fn run(input: impl Read, options: &CsvOptions, out: impl Write) -> Result<(), CsvError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        // We decide ourselves whether the first row is a header.
+        .has_headers(false)
+        .from_reader(input);
+    let mut rows = reader.records();
+
+    let Some(first) = rows.next().transpose()? else {
+        // An empty file: nothing to print, and no columns to check names against.
+        return Ok(());
+    };
+    let has_header = match options.header {
+        Header::Yes => true,
+        Header::No => false,
+        Header::Auto => looks_like_header(&first),
+    };
+    let names: Vec<String> = if has_header {
+        first.iter().map(String::from).collect()
+    } else {
+        (1..=first.len()).map(|i| format!("column{}", i)).collect()
+    };
+    let columns = Columns { names: &names };
+
+    let filters = options
+        .filters
+        .iter()
+        .map(|filter| Ok((columns.find(&filter.column)?, filter)))
+        .collect::<Result<Vec<_>, CsvError>>()?;
+    let keep = |row: &csv::StringRecord| {
+        filters
+            .iter()
+            .all(|(at, filter)| filter.holds(row.get(*at).unwrap_or("")))
+    };
+    // The first row is data when it is not a header.
+    let first = (!has_header).then_some(Ok(first));
+    let rows = first.into_iter().chain(rows);
+
+    if options.aggregates.is_empty() {
+        let selected: Vec<usize> = if options.select.is_empty() {
+            (0..names.len()).collect()
+        } else {
+            options
+                .select
+                .iter()
+                .map(|column| columns.find(column))
+                .collect::<Result<_, _>>()?
+        };
+        let header: Vec<&str> = selected.iter().map(|&at| names[at].as_str()).collect();
+        let mut writer = Output::start(options, out, &header, has_header)?;
+        for row in rows {
+            let row = row?;
+            if keep(&row) {
+                let fields: Vec<Field> = selected
+                    .iter()
+                    .map(|&at| Field::Text(row.get(at).unwrap_or("")))
+                    .collect();
+                writer.row(&fields)?;
+            }
+        }
+        writer.finish()
+    } else {
+        let group_by = options
+            .group_by
+            .as_ref()
+            .map(|column| columns.find(column))
+            .transpose()?;
+        let aggregated = options
+            .aggregates
+            .iter()
+            .map(|aggregate| {
+                let at = aggregate
+                    .column
+                    .as_ref()
+                    .map(|column| columns.find(column))
+                    .transpose()?;
+                Ok((aggregate, at))
+            })
+            .collect::<Result<Vec<_>, CsvError>>()?;
+
+        // Groups in the order they first appear, like SQL engines usually print them.
+        let mut order: Vec<String> = Vec::new();
+        let mut groups: HashMap<String, Vec<Total>> = HashMap::new();
+        for row in rows {
+            let row = row?;
+            if !keep(&row) {
+                continue;
+            }
+            let key = group_by.map_or("", |at| row.get(at).unwrap_or(""));
+            let totals = groups.entry(key.to_string()).or_insert_with(|| {
+                order.push(key.to_string());
+                vec![Total::default(); aggregated.len()]
+            });
+            for (total, &(_, at)) in totals.iter_mut().zip(&aggregated) {
+                total.rows += 1;
+                let Some(at) = at else { continue };
+                let field = row.get(at).unwrap_or("");
+                // An empty field is a missing value, as in a spreadsheet.
+                if field.trim().is_empty() {
+                    continue;
+                }
+                let value = number(field).ok_or_else(|| CsvError::NotANumber {
+                    column: names[at].clone(),
+                    value: field.to_string(),
+                    line: row.position().map_or(0, |position| position.line()),
+                })?;
+                total.sum += value;
+                total.numbers += 1;
+            }
+        }
+
+        let mut header: Vec<String> = group_by.map(|at| names[at].clone()).into_iter().collect();
+        header.extend(
+            options
+                .aggregates
+                .iter()
+                .map(|aggregate| aggregate.to_string()),
+        );
+        let header: Vec<&str> = header.iter().map(String::as_str).collect();
+        let mut writer = Output::start(options, out, &header, true)?;
+        // Without --group-by there is a single group; with no rows at all, a
+        // count of 0 is still an answer.
+        if group_by.is_none() && order.is_empty() {
+            order.push(String::new());
+            groups.insert(String::new(), vec![Total::default(); aggregated.len()]);
+        }
+        for key in &order {
+            let totals = &groups[key];
+            let mut fields: Vec<Field> = group_by.map(|_| Field::Text(key)).into_iter().collect();
+            fields.extend(
+                totals
+                    .iter()
+                    .zip(&aggregated)
+                    .map(|(total, (aggregate, _))| match aggregate.function {
+                        Function::Count => Field::Number(Some(total.rows as f64)),
+                        Function::Sum => Field::Number(Some(total.sum)),
+                        Function::Avg => Field::Number(
+                            (total.numbers > 0).then(|| total.sum / total.numbers as f64),
+                        ),
+                    }),
+            );
+            writer.row(&fields)?;
+        }
+        writer.finish()
+    }
+}
+
+/// A guess: a header row has no empty field and no number in it, while data
+/// rows usually have at least one number. A file of text only is taken to have
+/// a header, like most CSV files; `--header no` says otherwise.
+fn looks_like_header(row: &csv::StringRecord) -> bool {
+    row.iter()
+        .all(|field| !field.trim().is_empty() && number(field).is_none())
+}
+
+fn number(field: &str) -> Option<f64> {
+    field.trim().parse().ok()
+}
+
+/// Column names or 1-based positions to indices.
+struct Columns<'a> {
+    names: &'a [String],
+}
+
+impl Columns<'_> {
+    /// A name wins over a position, in case a header is itself a number.
+    fn find(&self, column: &str) -> Result<usize, CsvError> {
+        if let Some(at) = self.names.iter().position(|name| name == column) {
+            return Ok(at);
+        }
+        match column.parse::<usize>() {
+            Ok(position) if (1..=self.names.len()).contains(&position) => Ok(position - 1),
+            _ => Err(CsvError::UnknownColumn(column.to_string())),
+        }
+    }
+}
+
+/// The running numbers of one aggregate in one group.
+#[derive(Debug, Clone, Default)]
+struct Total {
+    rows: usize,
+    sum: f64,
+    /// Non-empty fields, what `avg` divides by.
+    numbers: usize,
+}
+
+/// One output field: text from the input, or a computed number (`None`: no value).
+enum Field<'a> {
+    Text(&'a str),
+    Number(Option<f64>),
+}
+
+/// Writes rows as CSV or as a JSON array, as they come.
+enum Output<W: Write> {
+    // Boxed: the writer carries its buffer inline, ten times the size of the JSON variant.
+    Csv(Box<csv::Writer<W>>),
+    Json {
+        out: W,
+        names: Vec<String>,
+        rows: usize,
+    },
+}
+
+impl<W: Write> Output<W> {
+    /// `names` are the output columns; a CSV gets them as its first row if `header`.
+    fn start(
+        options: &CsvOptions,
+        mut out: W,
+        names: &[&str],
+        header: bool,
+    ) -> Result<Self, CsvError> {
+        match options.format {
+            Format::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .delimiter(options.delimiter)
+                    .from_writer(out);
+                if header {
+                    writer.write_record(names)?;
+                }
+                Ok(Output::Csv(Box::new(writer)))
+            }
+            Format::Json => {
+                write!(out, "[")?;
+                Ok(Output::Json {
+                    out,
+                    names: names.iter().map(|name| name.to_string()).collect(),
+                    rows: 0,
+                })
+            }
+        }
+    }
+
+    fn row(&mut self, fields: &[Field]) -> Result<(), CsvError> {
+        match self {
+            Output::Csv(writer) => {
+                writer.write_record(fields.iter().map(Field::to_string))?;
+            }
+            Output::Json { out, names, rows } => {
+                let object: Map<String, Value> = names
+                    .iter()
+                    .cloned()
+                    .zip(fields.iter().map(Field::to_json))
+                    .collect();
+                let separator = if *rows == 0 { "\n  " } else { ",\n  " };
+                write!(out, "{}{}", separator, Value::Object(object))?;
+                *rows += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), CsvError> {
+        match self {
+            Output::Csv(mut writer) => writer.flush()?,
+            Output::Json { mut out, rows, .. } => {
+                let end = if rows == 0 { "]" } else { "\n]" };
+                writeln!(out, "{}", end)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Field<'_> {
+    fn to_json(&self) -> Value {
+        match *self {
+            Field::Text(text) => Value::from(text),
+            // Whole numbers as integers: a count of 3 is `3`, not `3.0`.
+            Field::Number(Some(n)) if n.fract() == 0.0 && n.abs() < 9e15 => Value::from(n as i64),
+            // from_f64 gives None for NaN and infinities, which JSON cannot hold.
+            Field::Number(Some(n)) => {
+                serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+            }
+            Field::Number(None) => Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for Field<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Field::Text(text) => write!(f, "{}", text),
+            Field::Number(Some(n)) => write!(f, "{}", n),
+            Field::Number(None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALES: &str = "region,product,amount\n\
+                         north,apples,10\n\
+                         south,pears,2.5\n\
+                         north,pears,4\n\
+                         south,apples,\n";
+
+    fn csv(input: &str, options: &CsvOptions) -> Result<String, CsvError> {
+        let mut out = Vec::new();
+        run(input.as_bytes(), options, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn options(line: &str) -> CsvOptions {
+        let mut options = CsvOptions::default();
+        for part in line.split_whitespace() {
+            let (flag, value) = part.split_once('=').unwrap();
+            match flag {
+                "select" => options.select = value.split(',').map(String::from).collect(),
+                "where" => options.filters.push(value.parse().unwrap()),
+                "agg" => options.aggregates.push(value.parse().unwrap()),
+                "by" => options.group_by = Some(value.to_string()),
+                "format" => options.format = value.parse().unwrap(),
+                "header" => options.header = value.parse().unwrap(),
+                _ => panic!("{}", flag),
+            }
+        }
+        options
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        let filter: Filter = "amount>=3".parse().unwrap();
+        assert_eq!(
+            filter,
+            Filter {
+                column: "amount".into(),
+                op: Op::Ge,
+                value: "3".into(),
+            }
+        );
+        assert_eq!("a!=b".parse::<Filter>().unwrap().op, Op::Ne);
+        // Only the first operator counts; the rest is the value.
+        assert_eq!("note~a=b".parse::<Filter>().unwrap().value, "a=b");
+        assert!("amount".parse::<Filter>().is_err());
+        assert!("=3".parse::<Filter>().is_err());
+        assert!("sum".parse::<Aggregate>().is_err());
+        assert!("count:amount".parse::<Aggregate>().is_err());
+    }
+
+    #[test]
+    fn test_select_and_filter() {
+        assert_eq!(
+            csv(SALES, &options("select=product,3 where=region=north")).unwrap(),
+            "product,amount\napples,10\npears,4\n"
+        );
+        // 10 > 4 as numbers, though "10" < "4" as text.
+        assert_eq!(
+            csv(SALES, &options("select=amount where=amount>4")).unwrap(),
+            "amount\n10\n"
+        );
+        assert_eq!(
+            csv(SALES, &options("where=product~ear select=region")).unwrap(),
+            "region\nsouth\nnorth\n"
+        );
+    }
+
+    #[test]
+    fn test_aggregates() {
+        assert_eq!(
+            csv(
+                SALES,
+                &options("agg=count agg=sum:amount agg=avg:amount by=region")
+            )
+            .unwrap(),
+            "region,count,sum(amount),avg(amount)\nnorth,2,14,7\nsouth,2,2.5,2.5\n"
+        );
+        // No group: one row for the whole file, even when no row is left.
+        assert_eq!(
+            csv(SALES, &options("agg=count where=region=east")).unwrap(),
+            "count\n0\n"
+        );
+        let err = csv(SALES, &options("agg=sum:product")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: 'apples' in column 'product' is not a number"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json = csv(SALES, &options("format=json where=amount>=4")).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                { "region": "north", "product": "apples", "amount": "10" },
+                { "region": "north", "product": "pears", "amount": "4" }
+            ])
+        );
+        let json = csv(SALES, &options("format=json agg=avg:amount by=product")).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value[0],
+            serde_json::json!({ "product": "apples", "avg(amount)": 10 })
+        );
+        assert_eq!(
+            csv(SALES, &options("format=json where=region=east")).unwrap(),
+            "[]\n"
+        );
+    }
+
+    #[test]
+    fn test_headers() {
+        let headerless = "1,x\n2,y\n";
+        assert_eq!(
+            csv(headerless, &options("select=2 where=1>1")).unwrap(),
+            "y\n"
+        );
+        let json = csv(headerless, &options("format=json select=column1")).unwrap();
+        assert!(json.contains(r#"{"column1":"1"}"#));
+        // Text only: taken as a header unless told otherwise.
+        assert_eq!(
+            csv("a,b\nc,d\n", &CsvOptions::default()).unwrap(),
+            "a,b\nc,d\n"
+        );
+        assert_eq!(
+            csv("a,b\nc,d\n", &options("header=no agg=count")).unwrap(),
+            "count\n2\n"
+        );
+        assert!(matches!(
+            csv(SALES, &options("select=price")),
+            Err(CsvError::UnknownColumn(column)) if column == "price"
+        ));
+        assert_eq!(csv("", &options("agg=count")).unwrap(), "");
+    }
+
+    #[test]
+    fn test_delimiter() {
+        let options = CsvOptions {
+            delimiter: b'\t',
+            select: vec!["b".into()],
+            ..CsvOptions::default()
+        };
+        assert_eq!(csv("a\tb\n1\t2\n", &options).unwrap(), "b\n2\n");
+    }
+}
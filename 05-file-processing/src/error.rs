@@ -0,0 +1,228 @@
+//! `FileError`: what went wrong with a file, which file, and while doing what.
+//!
+//! A bare `io::Error` prints as "No such file or directory (os error 2)": not
+//! which file, nor whether it was being opened, read or listed. `?` passes it
+//! up unchanged, so by the time it reaches `main` that is all there is to say.
+//! `FileError` adds the path and the operation at the point where they are
+//! known, and gives the cases a caller may want to handle their own variants.
+//!
+//! Each error either says what its cause says or returns the cause from
+//! `source()`, never both; `Chain` then prints the whole chain of causes.
+
+use crate::walk::WalkError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::Utf8Error;
+
+/// What was being done to the file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    /// Finding it, or looking at its metadata.
+    Access,
+    Open,
+    Read,
+    /// Creating a file or directory to write.
+    Create,
+    Write,
+    Link,
+    Remove,
+    /// Watching it for changes.
+    Watch,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verb = match self {
+            Operation::Access => "access",
+            Operation::Open => "open",
+            Operation::Read => "read",
+            Operation::Create => "create",
+            Operation::Write => "write",
+            Operation::Link => "link",
+            Operation::Remove => "remove",
+            Operation::Watch => "watch",
+        };
+        f.write_str(verb)
+    }
+}
+
+#[derive(Debug)]
+pub enum FileError {
+    NotFound {
+        path: PathBuf,
+        operation: Operation,
+    },
+    PermissionDenied {
+        path: PathBuf,
+        operation: Operation,
+    },
+    /// The file was read as text, and is not.
+    InvalidUtf8 {
+        path: PathBuf,
+        operation: Operation,
+        source: Utf8Error,
+    },
+    /// Too big to be read into memory at once.
+    TooLarge {
+        path: PathBuf,
+        operation: Operation,
+        size: u64,
+        limit: u64,
+    },
+    /// Anything else the OS reported.
+    Io {
+        path: PathBuf,
+        operation: Operation,
+        source: io::Error,
+    },
+    /// Writing the command's output (stdout) failed.
+    Output(io::Error),
+}
+
+impl FileError {
+    /// Sorts an `io::Error` into the variant for its kind.
+    pub fn from_io(path: &Path, operation: Operation, error: io::Error) -> FileError {
+        let path = path.to_path_buf();
+        match error.kind() {
+            io::ErrorKind::NotFound => FileError::NotFound { path, operation },
+            io::ErrorKind::PermissionDenied => FileError::PermissionDenied { path, operation },
+            _ => FileError::Io {
+                path,
+                operation,
+                source: error,
+            },
+        }
+    }
+
+    /// The `io::ErrorKind` the error came from, or the closest one.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            FileError::NotFound { .. } => io::ErrorKind::NotFound,
+            FileError::PermissionDenied { .. } => io::ErrorKind::PermissionDenied,
+            FileError::InvalidUtf8 { .. } => io::ErrorKind::InvalidData,
+            FileError::TooLarge { .. } => io::ErrorKind::FileTooLarge,
+            FileError::Io { source, .. } | FileError::Output(source) => source.kind(),
+        }
+    }
+
+    /// Whatever read the output stopped reading (`| head`): not worth a message.
+    pub fn is_broken_pipe(&self) -> bool {
+        matches!(self, FileError::Output(e) if e.kind() == io::ErrorKind::BrokenPipe)
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileError::NotFound { path, operation } => {
+                write!(
+                    f,
+                    "cannot {} {}: it does not exist",
+                    operation,
+                    path.display()
+                )
+            }
+            FileError::PermissionDenied { path, operation } => {
+                write!(
+                    f,
+                    "cannot {} {}: permission denied",
+                    operation,
+                    path.display()
+                )
+            }
+            FileError::InvalidUtf8 {
+                path, operation, ..
+            } => write!(
+                f,
+                "cannot {} {}: it is not UTF-8 text",
+                operation,
+                path.display()
+            ),
+            FileError::TooLarge {
+                path,
+                operation,
+                size,
+                limit,
+            } => write!(
+                f,
+                "cannot {} {}: {} bytes is more than the limit of {}",
+                operation,
+                path.display(),
+                size,
+                limit
+            ),
+            FileError::Io {
+                path, operation, ..
+            } => write!(f, "cannot {} {}", operation, path.display()),
+            FileError::Output(_) => f.write_str("cannot write the output"),
+        }
+    }
+}
+
+impl Error for FileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FileError::InvalidUtf8 { source, .. } => Some(source),
+            FileError::Io { source, .. } | FileError::Output(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// A walk only fails to find or look at a path.
+impl From<WalkError> for FileError {
+    fn from(e: WalkError) -> Self {
+        FileError::from_io(&e.path, Operation::Access, e.error)
+    }
+}
+
+/// An error and its causes: on one line with `{}` ("cannot open a.txt: is a
+/// directory"), one per line with `{:#}`.
+pub struct Chain<'a>(pub &'a dyn Error);
+
+impl fmt::Display for Chain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut cause = self.0.source();
+        while let Some(error) = cause {
+            if f.alternate() {
+                write!(f, "\n  caused by: {}", error)?;
+            } else {
+                write!(f, ": {}", error)?;
+            }
+            cause = error.source();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinds_and_messages() {
+        let path = Path::new("data/a.txt");
+        let missing = io::Error::from(io::ErrorKind::NotFound);
+        let error = FileError::from_io(path, Operation::Open, missing);
+        assert!(matches!(error, FileError::NotFound { .. }));
+        // The cause is in the message, not repeated in the chain.
+        assert_eq!(
+            Chain(&error).to_string(),
+            "cannot open data/a.txt: it does not exist"
+        );
+
+        let other = io::Error::other("disk on fire");
+        let error = FileError::from_io(path, Operation::Read, other);
+        assert_eq!(
+            Chain(&error).to_string(),
+            "cannot read data/a.txt: disk on fire"
+        );
+        assert_eq!(
+            format!("{:#}", Chain(&error)),
+            "cannot read data/a.txt\n  caused by: disk on fire"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! The `follow` command, like `tail -F`: print the last lines of a file, then
+//! every line appended to it as it is written, until Ctrl-C.
+//!
+//! Log files do not only grow. `logrotate` renames `app.log` to `app.log.1`
+//! and creates a new `app.log`; `> app.log` empties it in place. Holding on
+//! to the open file would follow the renamed one forever, or wait for it to
+//! grow back past where it was. So every poll also looks at the path: another
+//! file behind it (another inode) is read from its start once the old one has
+//! been read to its end, and a file shorter than what was read of it is read
+//! again from its start. A file truncated and then refilled past the old
+//! length between two polls goes unnoticed, as with `tail`.
+//!
+//! The loop runs on tokio, which waits for the next poll and for Ctrl-C at the
+//! same time. Lines are written whole: a line written in two goes is printed
+//! once its newline arrives.
+
+use crate::chunks;
+use crate::dedup::identity;
+use crate::error::{FileError, Operation};
+use std::fmt;
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tokio::task;
+use tokio::time::{self, MissedTickBehavior};
+
+/// How much is read at a time.
+const BUFFER: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FollowOptions {
+    /// How many of the lines already there to print first.
+    pub lines: usize,
+    /// How often the file is checked for new lines.
+    pub interval: Duration,
+}
+
+/// Something that happened to the file rather than in it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// Cut shorter than what was read: it is read again from its start.
+    Truncated,
+    /// The path is another file now, which is read from its start.
+    Replaced,
+    /// The path is gone, until a file appears there again.
+    Removed,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Event::Truncated => "file truncated",
+            Event::Replaced => "file replaced, following the new file",
+            Event::Removed => "file removed, waiting for it to come back",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Writes the last lines of `path` to `out`, then the new ones every
+/// `options.interval`, until `stop` completes. Events go to stderr, between
+/// the lines they come between.
+pub async fn follow(
+    path: &Path,
+    options: FollowOptions,
+    out: &mut (impl AsyncWrite + Unpin),
+    stop: impl Future<Output = ()>,
+) -> Result<(), FileError> {
+    let mut follower = Follower::open(path, options.lines, out).await?;
+    let mut ticks = time::interval(options.interval);
+    // A slow poll is not made up for with a burst of them.
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tokio::pin!(stop);
+    loop {
+        // A poll is not cut short by `stop`: only the waiting between polls is.
+        tokio::select! {
+            _ = &mut stop => return Ok(()),
+            _ = ticks.tick() => {
+                if let Some(event) = follower.poll(out).await? {
+                    eprintln!("{}: {}", path.display(), event);
+                }
+            }
+        }
+    }
+}
+
+/// A file being followed, and how far it has been read.
+pub struct Follower {
+    path: PathBuf,
+    file: File,
+    /// Where the next read starts.
+    position: u64,
+    /// Which file `file` is, to notice another one taking its name.
+    identity: Option<(u64, u64)>,
+    /// The start of a line whose newline has not been read yet.
+    partial: Vec<u8>,
+    removed: bool,
+}
+
+impl Follower {
+    /// Opens `path` and writes its last `lines` lines to `out`.
+    pub async fn open(
+        path: &Path,
+        lines: usize,
+        out: &mut (impl AsyncWrite + Unpin),
+    ) -> Result<Follower, FileError> {
+        let file = File::open(path)
+            .await
+            .map_err(|e| FileError::from_io(path, Operation::Open, e))?;
+        let metadata = file
+            .metadata()
+            .await
+            .map_err(|e| FileError::from_io(path, Operation::Access, e))?;
+        let identity = identity(&metadata);
+        let reading = |e| FileError::from_io(path, Operation::Read, e);
+        // `tail` reads backwards with blocking calls: on a thread that may block.
+        let mut std_file = file.into_std().await;
+        let name = path.to_path_buf();
+        let (last, std_file) = task::spawn_blocking(move || {
+            let mut last = Vec::new();
+            chunks::tail(&mut std_file, &name, lines, &mut last).map(|()| (last, std_file))
+        })
+        .await
+        .map_err(|e| reading(io::Error::other(e)))??;
+        let mut file = File::from_std(std_file);
+        let position = file.stream_position().await.map_err(reading)?;
+
+        let mut follower = Follower {
+            path: path.to_path_buf(),
+            file,
+            position,
+            identity,
+            partial: Vec::new(),
+            removed: false,
+        };
+        follower.write_lines(&last, out).await?;
+        Ok(follower)
+    }
+
+    /// Writes the lines added since the last poll, or notices that the file
+    /// was truncated, replaced or removed. After an event, the new contents
+    /// come with the next poll.
+    pub async fn poll(
+        &mut self,
+        out: &mut (impl AsyncWrite + Unpin),
+    ) -> Result<Option<Event>, FileError> {
+        let metadata = match fs::metadata(&self.path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                // Renamed or deleted, the open file can still be read: what
+                // was written to it just before is not lost.
+                self.read_new(out).await?;
+                let first = !self.removed;
+                self.removed = true;
+                return Ok(first.then_some(Event::Removed));
+            }
+            Err(e) => return Err(FileError::from_io(&self.path, Operation::Access, e)),
+        };
+
+        if self.removed || identity(&metadata) != self.identity {
+            // The end of the old file, then the new one.
+            self.read_new(out).await?;
+            self.finish_line(out).await?;
+            let file = File::open(&self.path)
+                .await
+                .map_err(|e| FileError::from_io(&self.path, Operation::Open, e))?;
+            let metadata = file
+                .metadata()
+                .await
+                .map_err(|e| FileError::from_io(&self.path, Operation::Access, e))?;
+            self.identity = identity(&metadata);
+            self.file = file;
+            self.position = 0;
+            self.removed = false;
+            return Ok(Some(Event::Replaced));
+        }
+        if metadata.len() < self.position {
+            self.finish_line(out).await?;
+            self.position = self
+                .file
+                .seek(SeekFrom::Start(0))
+                .await
+                .map_err(|e| FileError::from_io(&self.path, Operation::Read, e))?;
+            return Ok(Some(Event::Truncated));
+        }
+        self.read_new(out).await?;
+        Ok(None)
+    }
+
+    /// Reads `file` to its current end.
+    async fn read_new(&mut self, out: &mut (impl AsyncWrite + Unpin)) -> Result<(), FileError> {
+        let mut buffer = vec![0; BUFFER];
+        loop {
+            let n = self
+                .file
+                .read(&mut buffer)
+                .await
+                .map_err(|e| FileError::from_io(&self.path, Operation::Read, e))?;
+            if n == 0 {
+                return Ok(());
+            }
+            self.position += n as u64;
+            self.write_lines(&buffer[..n], out).await?;
+        }
+    }
+
+    /// Writes the lines `bytes` completes, keeping the rest for later.
+    async fn write_lines(
+        &mut self,
+        bytes: &[u8],
+        out: &mut (impl AsyncWrite + Unpin),
+    ) -> Result<(), FileError> {
+        self.partial.extend_from_slice(bytes);
+        if let Some(end) = self.partial.iter().rposition(|&byte| byte == b'\n') {
+            out.write_all(&self.partial[..=end])
+                .await
+                .map_err(FileError::Output)?;
+            out.flush().await.map_err(FileError::Output)?;
+            self.partial.drain(..=end);
+        }
+        Ok(())
+    }
+
+    /// Writes the unfinished line as it is: the file it came from is done with.
+    async fn finish_line(&mut self, out: &mut (impl AsyncWrite + Unpin)) -> Result<(), FileError> {
+        if !self.partial.is_empty() {
+            self.write_lines(b"\n", out).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    /// What was written to `out` since the last call.
+    fn taken(out: &mut Vec<u8>) -> String {
+        String::from_utf8(std::mem::take(out)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_appended_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        let mut out = Vec::new();
+        let mut follower = Follower::open(&path, 2, &mut out).await.unwrap();
+        assert_eq!(taken(&mut out), "two\nthree\n");
+        assert_eq!(follower.poll(&mut out).await.unwrap(), None);
+        assert_eq!(taken(&mut out), "");
+
+        append(&path, "four\nfi");
+        assert_eq!(follower.poll(&mut out).await.unwrap(), None);
+        assert_eq!(taken(&mut out), "four\n");
+        append(&path, "ve\n");
+        follower.poll(&mut out).await.unwrap();
+        assert_eq!(taken(&mut out), "five\n");
+    }
+
+    #[tokio::test]
+    async fn test_truncation_rotation_and_removal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "a\nb\n").unwrap();
+        let mut out = Vec::new();
+        let mut follower = Follower::open(&path, 10, &mut out).await.unwrap();
+        assert_eq!(taken(&mut out), "a\nb\n");
+
+        // `> app.log`, then a new line.
+        std::fs::write(&path, "c\n").unwrap();
+        assert_eq!(
+            follower.poll(&mut out).await.unwrap(),
+            Some(Event::Truncated)
+        );
+        follower.poll(&mut out).await.unwrap();
+        assert_eq!(taken(&mut out), "c\n");
+
+        // logrotate: a last line to the old file, renamed, then a new file.
+        append(&path, "d\n");
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        std::fs::write(&path, "e\n").unwrap();
+        assert_eq!(
+            follower.poll(&mut out).await.unwrap(),
+            Some(Event::Replaced)
+        );
+        assert_eq!(taken(&mut out), "d\n");
+        follower.poll(&mut out).await.unwrap();
+        assert_eq!(taken(&mut out), "e\n");
+
+        // Deleted, reported once, and created again.
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(follower.poll(&mut out).await.unwrap(), Some(Event::Removed));
+        assert_eq!(follower.poll(&mut out).await.unwrap(), None);
+        std::fs::write(&path, "f\n").unwrap();
+        assert_eq!(
+            follower.poll(&mut out).await.unwrap(),
+            Some(Event::Replaced)
+        );
+        follower.poll(&mut out).await.unwrap();
+        assert_eq!(taken(&mut out), "f\n");
+    }
+
+    #[tokio::test]
+    async fn test_follow_until_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "old\n").unwrap();
+        let options = FollowOptions {
+            lines: 0,
+            interval: Duration::from_millis(10),
+        };
+        // Stands for the writer and then Ctrl-C.
+        let stop = async {
+            time::sleep(Duration::from_millis(30)).await;
+            append(&path, "new\n");
+            time::sleep(Duration::from_millis(50)).await;
+        };
+        let mut out = Vec::new();
+        follow(&path, options, &mut out, stop).await.unwrap();
+        assert_eq!(taken(&mut out), "new\n");
+    }
+}
@@ -0,0 +1,147 @@
+//! The `hash` command: a checksum per file, like `sha256sum` and `b3sum`.
+//!
+//! Files are streamed through the hasher a buffer at a time, so a file of any
+//! size takes the same little memory, and several files are hashed at once on
+//! rayon's thread pool. Unlike `stats`, every thread reads its own file: a
+//! hash is cheap enough per byte that the reading and the hashing go together.
+
+use crate::error::{FileError, Operation};
+use crate::walk::WalkError;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How much of a file is hashed at a time.
+const BUFFER: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Algorithm {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(Algorithm::Sha256),
+            "blake3" => Ok(Algorithm::Blake3),
+            _ => Err(format!("'{}' is not one of sha256 or blake3", s)),
+        }
+    }
+}
+
+/// The hash of everything `reader` holds, in lowercase hexadecimal.
+pub fn digest(mut reader: impl Read, algorithm: Algorithm) -> io::Result<String> {
+    let mut buffer = vec![0; BUFFER];
+    let bytes = match algorithm {
+        Algorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            feed(&mut reader, &mut buffer, |chunk| hasher.update(chunk))?;
+            hasher.finalize().to_vec()
+        }
+        Algorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            feed(&mut reader, &mut buffer, |chunk| {
+                hasher.update(chunk);
+            })?;
+            hasher.finalize().as_bytes().to_vec()
+        }
+    };
+    Ok(bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    }))
+}
+
+/// Hands `reader` to `update` one buffer at a time.
+fn feed(
+    reader: &mut impl Read,
+    buffer: &mut [u8],
+    mut update: impl FnMut(&[u8]),
+) -> io::Result<()> {
+    loop {
+        match reader.read(buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => update(&buffer[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+pub fn digest_file(path: &Path, algorithm: Algorithm) -> Result<String, FileError> {
+    let file = File::open(path).map_err(|e| FileError::from_io(path, Operation::Open, e))?;
+    digest(file, algorithm).map_err(|e| FileError::from_io(path, Operation::Read, e))
+}
+
+/// The hash of every file, in the order given, computed in parallel.
+pub fn hash_files(
+    files: Vec<Result<PathBuf, WalkError>>,
+    algorithm: Algorithm,
+) -> Vec<Result<(PathBuf, String), FileError>> {
+    // par_iter() on a Vec keeps the order in collect(), unlike par_bridge().
+    files
+        .into_par_iter()
+        .map(|file| {
+            let path = file?;
+            let hash = digest_file(&path, algorithm)?;
+            Ok((path, hash))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            digest("abc".as_bytes(), Algorithm::Sha256).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            digest("".as_bytes(), Algorithm::Blake3).unwrap(),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        // More than one buffer.
+        let long = "x".repeat(3 * BUFFER + 1);
+        assert_eq!(
+            digest(long.as_bytes(), Algorithm::Blake3).unwrap(),
+            blake3::hash(long.as_bytes()).to_hex().to_string()
+        );
+    }
+
+    #[test]
+    fn test_hash_files_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = Vec::new();
+        for i in 0..20 {
+            let path = dir.path().join(format!("{:02}.txt", i));
+            fs::write(&path, i.to_string()).unwrap();
+            files.push(Ok(path));
+        }
+        files.push(Ok(dir.path().join("missing.txt")));
+
+        let hashed = hash_files(files, Algorithm::Sha256);
+        assert_eq!(hashed.len(), 21);
+        let (path, hash) = hashed[7].as_ref().unwrap();
+        assert!(path.ends_with("07.txt"));
+        assert_eq!(*hash, digest("7".as_bytes(), Algorithm::Sha256).unwrap());
+        assert!(matches!(
+            hashed[20],
+            Err(FileError::NotFound {
+                operation: Operation::Open,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,6 @@
+//! The reusable part of the file processing example.
+//!
+//! Everything else in this crate is specific to the command line tool
+//! (`src/main.rs`); `atomic` is meant to be used by any program that saves files.
+
+pub mod atomic;
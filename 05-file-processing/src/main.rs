@@ -1,60 +1,351 @@
-use std::fs::{self, File};
-use std::io::{self, Read};
+use cli::{Command, USAGE};
+use crypt::CryptError;
+use error::{Chain, FileError, Operation};
+use file_processing::atomic::WriteOptions;
+use search::{SearchOptions, Searcher};
+use std::fs::File;
+use std::io::{self, BufReader, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use walk::WalkOptions;
 
-/// Reads content from a file named "hello.txt" and returns it as a Result.
-/// demonstrating manual file opening and reading.
-fn read_username_from_file() -> Result<String, io::Error> {
-    // Note: Rust provides a simpler 'fs::read_to_string("hello.txt")'
-    // but the following shows the explicit steps for educational purposes.
-
-    // Attempt to open the file. The '?' operator returns the error early if it fails.
-    let mut username_file = File::open("hello.txt")?;
-    let mut username = String::new();
-
-    // Read the file contents into the string buffer.
-    username_file.read_to_string(&mut username)?;
-
-    // Return the successful string wrapped in Ok.
-    Ok(username)
-}
+mod chunks;
+mod cli;
+mod compress;
+mod crypt;
+mod dedup;
+mod delimited;
+mod error;
+mod follow;
+mod hash;
+mod read;
+mod search;
+mod stats;
+mod walk;
+mod watch;
 
+/// The entry point: parse the command line and run the command. A bad command
+/// line exits with 2; each command picks its own status otherwise.
 fn main() {
-    // Setup a dummy file
-    let path = "hello.txt";
-    let content = "Alice";
+    let command = match cli::parse(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => usage_error(&e.to_string()),
+    };
 
-    println!("Writing '{}' to {}", content, path);
-    fs::write(path, content).expect("Unable to write file");
-
-    match read_username_from_file() {
-        Ok(s) => println!("Read username: {}", s),
-        Err(e) => println!("Error reading file: {:?}", e),
+    match command {
+        Command::Help => println!("{}", USAGE),
+        Command::Read { paths, walk } => {
+            let (output, summary) = read::run(walk::files(&paths, &walk));
+            println!("{}", output);
+            if summary.failed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Search {
+            pattern,
+            paths,
+            walk,
+            ignore_case,
+            count,
+        } => {
+            let options = SearchOptions {
+                ignore_case,
+                count,
+                with_names: paths.len() > 1 || walk.recursive,
+                // Colors only for a person reading a terminal (https://no-color.org).
+                color: io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+            };
+            let searcher = match Searcher::new(&pattern, options) {
+                Ok(searcher) => searcher,
+                Err(e) => usage_error(&format!("invalid pattern: {}", e)),
+            };
+            let files = walk::files(&paths, &walk);
+            let outcome = searcher.run(files, &mut io::stdout().lock(), &mut io::stderr());
+            // grep's statuses: 0 if a line matched, 1 if none did, 2 on errors.
+            let status = match outcome {
+                Ok(outcome) if outcome.failed > 0 => 2,
+                Ok(outcome) if outcome.matched > 0 => 0,
+                Ok(_) => 1,
+                // stdout closed early (`| head`): stop quietly, like grep.
+                Err(e) if e.is_broken_pipe() => 0,
+                Err(e) => {
+                    eprintln!("Error: {:#}", Chain(&e));
+                    2
+                }
+            };
+            std::process::exit(status);
+        }
+        Command::Stats {
+            paths,
+            walk,
+            threads,
+            top,
+        } => {
+            let mut counted = Vec::new();
+            let mut failed = false;
+            for file in stats::count_files(walk::files(&paths, &walk), threads) {
+                match file {
+                    Ok(file) => counted.push(file),
+                    Err(e) => {
+                        eprintln!("error: {}", Chain(&e));
+                        failed = true;
+                    }
+                }
+            }
+            println!("{}", stats::table(&counted, top));
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Command::Csv { path, options } => {
+            let input: Box<dyn Read> = if path.as_os_str() == "-" {
+                Box::new(io::stdin().lock())
+            } else {
+                Box::new(open_or_exit(&path))
+            };
+            // csv buffers its reads itself, so the file is not wrapped in a BufReader.
+            if let Err(e) = delimited::run(input, &options, io::stdout().lock()) {
+                eprintln!("Error: {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        Command::Head { path, lines } => {
+            let file = open_or_exit(&path);
+            let out = &mut io::stdout().lock();
+            exit_on_error(chunks::head(BufReader::new(file), &path, lines, out));
+        }
+        Command::Tail { path, lines } => {
+            let file = open_or_exit(&path);
+            exit_on_error(chunks::tail(file, &path, lines, &mut io::stdout().lock()));
+        }
+        Command::Follow { path, options } => {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("cannot start the tokio runtime");
+            let stop = async {
+                // Ctrl-C ends the command normally, as with `tail -f`.
+                let _ = tokio::signal::ctrl_c().await;
+            };
+            let result = runtime.block_on(follow::follow(
+                &path,
+                options,
+                &mut tokio::io::stdout(),
+                stop,
+            ));
+            exit_on_error(result);
+        }
+        Command::Split {
+            path,
+            lines,
+            prefix,
+        } => {
+            let file = open_or_exit(&path);
+            match chunks::split(BufReader::new(file), &path, lines, &prefix) {
+                Ok(parts) => {
+                    for part in parts {
+                        println!("{}", part.display());
+                    }
+                }
+                Err(e) => exit_on_error(Err(e)),
+            }
+        }
+        Command::Hash {
+            paths,
+            walk,
+            algorithm,
+        } => {
+            let mut failed = false;
+            for file in hash::hash_files(walk::files(&paths, &walk), algorithm) {
+                match file {
+                    Ok((path, hash)) => println!("{}  {}", hash, path.display()),
+                    Err(e) => {
+                        eprintln!("error: {}", Chain(&e));
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Command::Dedup {
+            paths,
+            walk,
+            action,
+            apply,
+        } => {
+            let (groups, errors) = dedup::find(walk::files(&paths, &walk));
+            for e in &errors {
+                eprintln!("error: {}", Chain(e));
+            }
+            let outcome = match dedup::run(&groups, action, apply, &mut io::stdout().lock()) {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    exit_on_error(Err(FileError::Output(e)));
+                    std::process::exit(1);
+                }
+            };
+            if !errors.is_empty() || outcome.failed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Compress { paths, zip, write } => {
+            let mut progress = progress_bar();
+            if let Some(archive) = zip {
+                let result = compress::zip(&archive, &paths, write, &mut progress);
+                finish_progress();
+                match result {
+                    Ok(sizes) => println!(
+                        "{}: {} file(s), {} -> {} bytes",
+                        archive.display(),
+                        sizes.files,
+                        sizes.read,
+                        sizes.written
+                    ),
+                    Err(e) => exit_on_error(Err(e)),
+                }
+                return;
+            }
+            let mut failed = false;
+            for file in walk::files(&paths, &WalkOptions::default()) {
+                let result = file
+                    .map_err(FileError::from)
+                    .and_then(|path| compress::gzip(&path, write, &mut progress));
+                finish_progress();
+                match result {
+                    Ok((output, sizes)) => println!(
+                        "{} -> {} ({} -> {} bytes)",
+                        output.with_extension("").display(),
+                        output.display(),
+                        sizes.read,
+                        sizes.written
+                    ),
+                    Err(e) if e.kind() == io::ErrorKind::IsADirectory => {
+                        eprintln!("error: {} (use --zip to compress it)", Chain(&e));
+                        failed = true;
+                    }
+                    Err(e) => {
+                        eprintln!("error: {}", Chain(&e));
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Command::Decompress { paths, into, write } => {
+            let mut progress = progress_bar();
+            let mut failed = false;
+            for path in paths {
+                let result = if path.extension().is_some_and(|extension| extension == "zip") {
+                    let into = into.clone().unwrap_or_else(|| path.with_extension(""));
+                    compress::unzip(&path, &into, write, &mut progress).map(|sizes| (into, sizes))
+                } else {
+                    compress::gunzip(&path, write, &mut progress)
+                };
+                finish_progress();
+                match result {
+                    Ok((output, sizes)) => println!(
+                        "{} -> {} ({} file(s), {} -> {} bytes)",
+                        path.display(),
+                        output.display(),
+                        sizes.files,
+                        sizes.read,
+                        sizes.written
+                    ),
+                    Err(e) => {
+                        eprintln!("error: {}", Chain(&e));
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Command::Encrypt { paths, write } => crypt_files(&paths, write, true),
+        Command::Decrypt { paths, write } => crypt_files(&paths, write, false),
+        Command::Watch { path, options } => {
+            exit_on_error(watch::watch(&path, &options, &mut io::stdout().lock()));
+        }
     }
-
-    // Cleanup
-    println!("Cleaning up {}", path);
-    fs::remove_file(path).unwrap_or_else(|e| println!("Failed to delete: {}", e));
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
+/// Asks for the passphrase once, then encrypts or decrypts every file with it.
+fn crypt_files(paths: &[PathBuf], write: WriteOptions, encrypting: bool) {
+    let passphrase = match crypt::ask_passphrase(encrypting) {
+        Ok(passphrase) => passphrase,
+        Err(e) => {
+            eprintln!("Error: {:#}", Chain(&e));
+            std::process::exit(1);
+        }
+    };
+    let mut failed = false;
+    for path in paths {
+        let result = if encrypting {
+            crypt::encrypt_file(path, &passphrase, write)
+        } else {
+            crypt::decrypt_file(path, &passphrase, write)
+        };
+        match result {
+            Ok(output) => println!("{} -> {}", path.display(), output.display()),
+            // A file error names the file itself.
+            Err(CryptError::File(e)) => {
+                eprintln!("error: {}", Chain(&e));
+                failed = true;
+            }
+            Err(e) => {
+                eprintln!("error: {}: {}", path.display(), Chain(&e));
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
 
-    #[test]
-    fn test_file_processing() {
-        let test_file = "test_user.txt";
-        let content = "TestUser";
+/// Shows how far along the current file is, on one line of stderr that keeps
+/// being redrawn; nothing when stderr is not a terminal (a log, a pipe).
+fn progress_bar() -> impl FnMut(&Path, u64, u64) {
+    let show = io::stderr().is_terminal();
+    move |path, done, total| {
+        if show {
+            let percent = (done * 100).checked_div(total).unwrap_or(100);
+            eprint!("\r\x1b[K{}: {}%", path.display(), percent);
+        }
+    }
+}
 
-        // Setup
-        fs::write(test_file, content).unwrap();
+/// Clears the progress line, so that the result is printed in its place.
+fn finish_progress() {
+    if io::stderr().is_terminal() {
+        eprint!("\r\x1b[K");
+    }
+}
 
-        // The function expects "hello.txt", so we temporarily rename or mock it
-        // For simplicity in this demo, let's just test the logic inline
-        let read_content = fs::read_to_string(test_file).unwrap();
-        assert_eq!(read_content, content);
+fn open_or_exit(path: &Path) -> File {
+    File::open(path).unwrap_or_else(|e| {
+        let error = FileError::from_io(path, Operation::Open, e);
+        eprintln!("Error: {:#}", Chain(&error));
+        std::process::exit(1);
+    })
+}
 
-        // Cleanup
-        fs::remove_file(test_file).unwrap();
+/// Exits with 1 on an error, except a closed stdout (`| head`), which just ends the output.
+fn exit_on_error(result: Result<(), FileError>) {
+    match result {
+        Err(e) if !e.is_broken_pipe() => {
+            eprintln!("Error: {:#}", Chain(&e));
+            std::process::exit(1);
+        }
+        _ => {}
     }
 }
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("Error: {}\n\n{}", message, USAGE);
+    std::process::exit(2);
+}
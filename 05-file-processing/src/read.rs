@@ -0,0 +1,182 @@
+//! The `read` command: read each file and report what was in it.
+
+use crate::error::{Chain, FileError, Operation};
+use crate::walk::WalkError;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Files bigger than this are not read into memory (`head` and `tail` can
+/// look at them instead).
+pub const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Reads the whole file at `path` and returns it as a Result,
+/// demonstrating manual file opening and reading.
+pub fn read_file(path: &Path) -> Result<String, FileError> {
+    // Note: Rust provides a simpler 'fs::read_to_string(path)'
+    // but the following shows the explicit steps for educational purposes.
+
+    // Attempt to open the file. The '?' operator returns the error early if it fails;
+    // map_err first turns the bare io::Error into one that knows the path and the step.
+    let mut file = File::open(path).map_err(|e| FileError::from_io(path, Operation::Open, e))?;
+
+    // Check the size before reading, rather than run out of memory half way.
+    let size = file
+        .metadata()
+        .map_err(|e| FileError::from_io(path, Operation::Access, e))?
+        .len();
+    if size > MAX_SIZE {
+        return Err(FileError::TooLarge {
+            path: path.to_path_buf(),
+            operation: Operation::Read,
+            size,
+            limit: MAX_SIZE,
+        });
+    }
+
+    // Read the bytes, then check they are text: the error can then say where
+    // the first invalid byte is, which read_to_string's does not.
+    let mut bytes = Vec::with_capacity(size as usize);
+    file.read_to_end(&mut bytes)
+        .map_err(|e| FileError::from_io(path, Operation::Read, e))?;
+    let contents = String::from_utf8(bytes).map_err(|e| FileError::InvalidUtf8 {
+        path: path.to_path_buf(),
+        operation: Operation::Read,
+        source: e.utf8_error(),
+    })?;
+
+    // Return the successful string wrapped in Ok.
+    Ok(contents)
+}
+
+/// What was found in one file.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FileReport {
+    pub lines: usize,
+    pub bytes: usize,
+}
+
+impl FileReport {
+    fn of(contents: &str) -> Self {
+        FileReport {
+            lines: contents.lines().count(),
+            bytes: contents.len(),
+        }
+    }
+}
+
+/// The totals printed after the per-file lines.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub read: usize,
+    pub failed: usize,
+    pub total: FileReport,
+}
+
+impl Summary {
+    fn line(&self) -> String {
+        format!(
+            "{} file(s): {} read, {} failed; {} line(s), {} byte(s) in total",
+            self.read + self.failed,
+            self.read,
+            self.failed,
+            self.total.lines,
+            self.total.bytes
+        )
+    }
+}
+
+/// Reads every file, one output line each (errors included, in place), then
+/// the summary. One unreadable file does not stop the others.
+pub fn run(files: Vec<Result<PathBuf, WalkError>>) -> (String, Summary) {
+    let mut output = String::new();
+    let mut summary = Summary::default();
+    for file in files {
+        let result = file
+            .map_err(FileError::from)
+            .and_then(|path| Ok((FileReport::of(&read_file(&path)?), path)));
+        match result {
+            Ok((report, path)) => {
+                output.push_str(&format!(
+                    "{}: {} line(s), {} byte(s)\n",
+                    path.display(),
+                    report.lines,
+                    report.bytes
+                ));
+                summary.read += 1;
+                summary.total.lines += report.lines;
+                summary.total.bytes += report.bytes;
+            }
+            Err(error) => {
+                output.push_str(&format!("error: {}\n", Chain(&error)));
+                summary.failed += 1;
+            }
+        }
+    }
+    output.push('\n');
+    output.push_str(&summary.line());
+    (output, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::walk::{self, WalkOptions};
+    use std::fs;
+
+    #[test]
+    fn test_read_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello.txt");
+        fs::write(&path, "Alice").unwrap();
+        assert_eq!(read_file(&path).unwrap(), "Alice");
+        let missing = read_file(&dir.path().join("missing.txt")).unwrap_err();
+        assert!(matches!(
+            missing,
+            FileError::NotFound {
+                operation: Operation::Open,
+                ..
+            }
+        ));
+
+        fs::write(&path, b"Al\xffce").unwrap();
+        let binary = read_file(&path).unwrap_err();
+        assert!(matches!(binary, FileError::InvalidUtf8 { .. }));
+        assert!(Chain(&binary).to_string().ends_with(
+            "hello.txt: it is not UTF-8 text: invalid utf-8 sequence of 1 bytes from index 2"
+        ));
+    }
+
+    #[test]
+    fn test_report_and_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        fs::write(dir.path().join("b.bin"), [0xff, 0xfe]).unwrap();
+        let paths = [dir.path().to_path_buf(), dir.path().join("missing.txt")];
+        let files = walk::files(
+            &paths,
+            &WalkOptions {
+                recursive: true,
+                glob: None,
+            },
+        );
+
+        let (output, summary) = run(files);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].ends_with("a.txt: 2 line(s), 8 byte(s)"));
+        // Not UTF-8: read_file refuses it.
+        assert!(lines[1].starts_with("error: cannot read "));
+        assert!(lines[1].contains("b.bin: it is not UTF-8 text"));
+        assert!(lines[2].starts_with("error: cannot access "));
+        assert!(lines[2].ends_with("missing.txt: it does not exist"));
+        assert_eq!(
+            summary,
+            Summary {
+                read: 1,
+                failed: 2,
+                total: FileReport { lines: 2, bytes: 8 },
+            }
+        );
+        assert!(output.ends_with("\n\n3 file(s): 1 read, 2 failed; 2 line(s), 8 byte(s) in total"));
+    }
+}
@@ -0,0 +1,257 @@
+//! The `search` command: print the lines matching a regular expression, like `grep -n`.
+//!
+//! Files are read one line at a time through a `BufReader`, and each match is
+//! written out as soon as it is found, so a multi-gigabyte log takes no more
+//! memory than its longest line.
+
+use crate::error::{Chain, FileError, Operation};
+use crate::walk::WalkError;
+use regex::{Regex, RegexBuilder};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// The SGR escapes grep uses: file names magenta, line numbers green, matches bold red.
+const NAME_COLOR: &str = "\x1b[35m";
+const NUMBER_COLOR: &str = "\x1b[32m";
+const MATCH_COLOR: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// How to search and what to print.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SearchOptions {
+    pub ignore_case: bool,
+    /// Print the number of matching lines instead of the lines.
+    pub count: bool,
+    /// Start each output line with the file name (when searching several files).
+    pub with_names: bool,
+    pub color: bool,
+}
+
+/// What a search found, for the exit status.
+#[derive(Debug, Default, PartialEq)]
+pub struct Outcome {
+    /// Matching lines, over all files.
+    pub matched: usize,
+    /// Files that could not be read.
+    pub failed: usize,
+}
+
+pub struct Searcher {
+    regex: Regex,
+    options: SearchOptions,
+}
+
+impl Searcher {
+    pub fn new(pattern: &str, options: SearchOptions) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(options.ignore_case)
+            .build()?;
+        Ok(Searcher { regex, options })
+    }
+
+    /// Searches every file, writing matches to `out` and unreadable files to
+    /// `errors`. Only a failure to write to `out` (a closed pipe) stops it.
+    pub fn run(
+        &self,
+        files: Vec<Result<PathBuf, WalkError>>,
+        out: &mut impl Write,
+        errors: &mut impl Write,
+    ) -> Result<Outcome, FileError> {
+        let mut outcome = Outcome::default();
+        for file in files {
+            let searched = file.map_err(FileError::from).and_then(|path| {
+                let file =
+                    File::open(&path).map_err(|e| FileError::from_io(&path, Operation::Open, e))?;
+                self.search(BufReader::new(file), &path, out)
+            });
+            match searched {
+                Ok(matched) => outcome.matched += matched,
+                Err(error @ FileError::Output(_)) => return Err(error),
+                // Unreadable, or a read error half way through: report it and
+                // go on with the next file.
+                Err(error) => {
+                    writeln!(errors, "error: {}", Chain(&error)).map_err(FileError::Output)?;
+                    outcome.failed += 1;
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Searches one input line by line; returns the number of matching lines.
+    /// `path` names the input, in the output and in errors.
+    pub fn search(
+        &self,
+        mut reader: impl BufRead,
+        path: &Path,
+        out: &mut impl Write,
+    ) -> Result<usize, FileError> {
+        let prefix = if self.options.with_names {
+            let name = path.display().to_string();
+            format!("{}:", self.paint(NAME_COLOR, &name))
+        } else {
+            String::new()
+        };
+        let mut line = Vec::new();
+        let mut number = 0;
+        let mut matched = 0;
+        loop {
+            line.clear();
+            // Bytes rather than read_line(): a stray invalid byte should not end the search.
+            let n = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| FileError::from_io(path, Operation::Read, e))?;
+            if n == 0 {
+                break;
+            }
+            number += 1;
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches(['\n', '\r']);
+            if !self.regex.is_match(text) {
+                continue;
+            }
+            matched += 1;
+            if !self.options.count {
+                let number = self.paint(NUMBER_COLOR, &number.to_string());
+                writeln!(out, "{}{}:{}", prefix, number, self.highlight(text))
+                    .map_err(FileError::Output)?;
+            }
+        }
+        if self.options.count {
+            writeln!(out, "{}{}", prefix, matched).map_err(FileError::Output)?;
+        }
+        Ok(matched)
+    }
+
+    /// `text` with every match painted.
+    fn highlight(&self, text: &str) -> String {
+        if !self.options.color {
+            return text.to_string();
+        }
+        let mut painted = String::with_capacity(text.len());
+        let mut end = 0;
+        for found in self.regex.find_iter(text) {
+            painted.push_str(&text[end..found.start()]);
+            painted.push_str(&self.paint(MATCH_COLOR, found.as_str()));
+            end = found.end();
+        }
+        painted.push_str(&text[end..]);
+        painted
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.options.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io;
+
+    fn search(pattern: &str, options: SearchOptions, input: &str) -> String {
+        let searcher = Searcher::new(pattern, options).unwrap();
+        let mut out = Vec::new();
+        searcher
+            .search(input.as_bytes(), Path::new("f.txt"), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    const TEXT: &str = "fn main() {\n    let x = 1;\n}\nfn Helper() {}\n";
+
+    #[test]
+    fn test_line_numbers_and_case() {
+        let plain = SearchOptions::default();
+        assert_eq!(
+            search(r"fn \w+", plain, TEXT),
+            "1:fn main() {\n4:fn Helper() {}\n"
+        );
+        assert_eq!(search("helper", plain, TEXT), "");
+        let ignore_case = SearchOptions {
+            ignore_case: true,
+            ..plain
+        };
+        assert_eq!(search("helper", ignore_case, TEXT), "4:fn Helper() {}\n");
+    }
+
+    #[test]
+    fn test_count_and_names() {
+        let options = SearchOptions {
+            count: true,
+            with_names: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(search("fn", options, TEXT), "f.txt:2\n");
+        // A file without a trailing newline, and with Windows line endings.
+        assert_eq!(search("b", SearchOptions::default(), "a\r\nb"), "2:b\n");
+    }
+
+    #[test]
+    fn test_highlight() {
+        let options = SearchOptions {
+            color: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(
+            search("o", options, "foo\n"),
+            "\x1b[32m1\x1b[0m:f\x1b[1;31mo\x1b[0m\x1b[1;31mo\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_run_reports_unreadable_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "needle\nhay\n").unwrap();
+        let files = vec![Ok(path.clone()), Ok(dir.path().join("missing.txt"))];
+        let searcher = Searcher::new("needle", SearchOptions::default()).unwrap();
+        let (mut out, mut errors) = (Vec::new(), Vec::new());
+        let outcome = searcher.run(files, &mut out, &mut errors).unwrap();
+        assert_eq!(
+            outcome,
+            Outcome {
+                matched: 1,
+                failed: 1
+            }
+        );
+        assert_eq!(out, b"1:needle\n");
+        let errors = String::from_utf8(errors).unwrap();
+        assert!(errors.starts_with("error: cannot open "));
+        assert!(errors.ends_with("missing.txt: it does not exist\n"));
+    }
+
+    #[test]
+    fn test_run_stops_when_the_output_fails() {
+        struct Closed;
+        impl Write for Closed {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(
+            &path, "needle
+",
+        )
+        .unwrap();
+        let searcher = Searcher::new("needle", SearchOptions::default()).unwrap();
+        let mut errors = Vec::new();
+        let error = searcher
+            .run(vec![Ok(path)], &mut Closed, &mut errors)
+            .unwrap_err();
+        assert!(error.is_broken_pipe());
+        assert!(errors.is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! The `stats` command: lines, words, bytes and the most common characters of
+//! each file, like `wc` with a little extra, and a total row.
+//!
+//! The work is split by what limits it:
+//! - Reading is I/O-bound: the disk is the bottleneck, and more threads would
+//!   only make them wait on each other. One thread reads the files in order.
+//! - Counting is CPU-bound: it goes as fast as there are cores. The files read
+//!   are handed to rayon's thread pool, one core per file at a time.
+//!
+//! The two are joined by a bounded channel, so the reader stays only a few files
+//! ahead of the counters instead of loading everything into memory at once.
+
+use crate::error::{FileError, Operation};
+use crate::walk::WalkError;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+/// How many files may wait in memory, read but not yet counted.
+const READ_AHEAD: usize = 8;
+
+/// What was counted in one file, or in all of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Counts {
+    /// Newlines, as `wc -l` counts them.
+    pub lines: usize,
+    pub words: usize,
+    pub bytes: usize,
+    /// How often each character appears, whitespace left out.
+    pub chars: HashMap<char, usize>,
+}
+
+impl Counts {
+    pub fn of(bytes: &[u8]) -> Counts {
+        // Lossy: a file that is not valid UTF-8 still gets counted.
+        let text = String::from_utf8_lossy(bytes);
+        let mut chars = HashMap::new();
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            *chars.entry(c).or_insert(0) += 1;
+        }
+        Counts {
+            lines: bytes.iter().filter(|&&b| b == b'\n').count(),
+            words: text.split_whitespace().count(),
+            bytes: bytes.len(),
+            chars,
+        }
+    }
+
+    pub fn add(&mut self, other: &Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.bytes += other.bytes;
+        for (&c, &count) in &other.chars {
+            *self.chars.entry(c).or_insert(0) += count;
+        }
+    }
+
+    /// The `n` most common characters, most common first; ties in character order.
+    pub fn top(&self, n: usize) -> Vec<(char, usize)> {
+        let mut chars: Vec<(char, usize)> = self.chars.iter().map(|(&c, &n)| (c, n)).collect();
+        chars.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        chars.truncate(n);
+        chars
+    }
+}
+
+/// The counts of every file, in the order given, with `threads` counting
+/// threads (`None`: one per core).
+pub fn count_files(
+    files: Vec<Result<PathBuf, WalkError>>,
+    threads: Option<usize>,
+) -> Vec<Result<(PathBuf, Counts), FileError>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.unwrap_or(0))
+        .build()
+        .expect("the OS refused to start the counting threads");
+
+    let (sender, receiver) = mpsc::sync_channel(READ_AHEAD);
+    thread::scope(|scope| {
+        // The I/O side: one reader, blocking on send() when the counters fall behind.
+        scope.spawn(move || {
+            for (index, file) in files.into_iter().enumerate() {
+                let read = file
+                    .map_err(FileError::from)
+                    .and_then(|path| match fs::read(&path) {
+                        Ok(bytes) => Ok((path, bytes)),
+                        Err(e) => Err(FileError::from_io(&path, Operation::Read, e)),
+                    });
+                if sender.send((index, read)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // The CPU side: par_bridge() hands each file to whichever thread is free,
+        // so the results come back out of order; the index puts them back.
+        let mut counted: Vec<_> = pool.install(|| {
+            receiver
+                .into_iter()
+                .par_bridge()
+                .map(|(index, read)| {
+                    let counts = read.map(|(path, bytes)| (path, Counts::of(&bytes)));
+                    (index, counts)
+                })
+                .collect()
+        });
+        counted.sort_by_key(|(index, _)| *index);
+        counted.into_iter().map(|(_, counts)| counts).collect()
+    })
+}
+
+/// The table: one row per file, then the total if there is more than one.
+pub fn table(counted: &[(PathBuf, Counts)], top: usize) -> String {
+    let mut total = Counts::default();
+    let mut rows: Vec<[String; 5]> = counted
+        .iter()
+        .map(|(path, counts)| {
+            total.add(counts);
+            row(counts, top, path.display().to_string())
+        })
+        .collect();
+    if counted.len() > 1 {
+        rows.push(row(&total, top, "total".to_string()));
+    }
+
+    let header = ["lines", "words", "bytes", "most common", "file"].map(String::from);
+    let mut widths = [0; 5];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            // Numbers to the right, text to the left; the last column is not padded.
+            format!(
+                "{:>w0$}  {:>w1$}  {:>w2$}  {:<w3$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn row(counts: &Counts, top: usize, name: String) -> [String; 5] {
+    let common: Vec<String> = counts
+        .top(top)
+        .iter()
+        .map(|(c, n)| format!("{}:{}", c, n))
+        .collect();
+    [
+        counts.lines.to_string(),
+        counts.words.to_string(),
+        counts.bytes.to_string(),
+        common.join(" "),
+        name,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::path::Path;
+
+    #[test]
+    fn test_counts() {
+        let counts = Counts::of("hello world\nbye\n".as_bytes());
+        assert_eq!((counts.lines, counts.words, counts.bytes), (2, 3, 16));
+        // e and o both appear twice: e comes first.
+        assert_eq!(counts.top(2), [('l', 3), ('e', 2)]);
+        // Ties go in character order: b, d, e all appear once.
+        assert_eq!(Counts::of(b"e d b").top(2), [('b', 1), ('d', 1)]);
+    }
+
+    #[test]
+    fn test_parallel_counts_keep_their_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = Vec::new();
+        for i in 0..40 {
+            let path = dir.path().join(format!("{:02}.txt", i));
+            fs::write(&path, "x\n".repeat(i)).unwrap();
+            files.push(Ok(path));
+        }
+        files.insert(
+            3,
+            Err(WalkError {
+                path: "gone".into(),
+                error: io::Error::from(io::ErrorKind::NotFound),
+            }),
+        );
+
+        let counted = count_files(files, Some(4));
+        assert_eq!(counted.len(), 41);
+        assert!(matches!(
+            &counted[3],
+            Err(FileError::NotFound { path, .. }) if path == Path::new("gone")
+        ));
+        let lines: Vec<usize> = counted
+            .iter()
+            .filter_map(|file| file.as_ref().ok())
+            .map(|(_, counts)| counts.lines)
+            .collect();
+        assert_eq!(lines, (0..40).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_table_with_total() {
+        let counted = vec![
+            (PathBuf::from("a.txt"), Counts::of(b"aa b\n")),
+            (PathBuf::from("long-name.txt"), Counts::of(b"c\nc\n")),
+        ];
+        assert_eq!(
+            table(&counted, 2),
+            "lines  words  bytes  most common  file\n\
+             \x20   1      2      5  a:2 b:1      a.txt\n\
+             \x20   2      2      4  c:2          long-name.txt\n\
+             \x20   3      4      9  a:2 c:2      total"
+        );
+        // A single file has no total row.
+        assert_eq!(table(&counted[..1], 1).lines().count(), 2);
+    }
+}
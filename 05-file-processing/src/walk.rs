@@ -0,0 +1,170 @@
+//! Turning the paths from the command line into a list of files.
+//!
+//! A file is taken as it is. A directory is only entered with `--recursive`;
+//! the files found inside it can then be narrowed down with `--glob`.
+
+use glob::Pattern;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How to expand the paths given on the command line.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WalkOptions {
+    pub recursive: bool,
+    /// Only the files in directories whose name matches; files named on the
+    /// command line are always kept, as in `grep -r --include`.
+    pub glob: Option<Pattern>,
+}
+
+impl WalkOptions {
+    /// A pattern with a `/` is matched against the whole path (`src/*.rs`),
+    /// any other against the file name only (`*.rs`), like `find -path` and `find -name`.
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(glob) = &self.glob else {
+            return true;
+        };
+        if glob.as_str().contains('/') {
+            glob.matches_path(path)
+        } else {
+            path.file_name()
+                .is_some_and(|name| glob.matches(&name.to_string_lossy()))
+        }
+    }
+}
+
+/// A path that could not be looked at, and why.
+#[derive(Debug)]
+pub struct WalkError {
+    pub path: PathBuf,
+    pub error: io::Error,
+}
+
+/// Every file under `paths`, in order: each path as given, and the contents of
+/// directories sorted by name, so the output is the same on every run.
+pub fn files(paths: &[PathBuf], options: &WalkOptions) -> Vec<Result<PathBuf, WalkError>> {
+    let mut found = Vec::new();
+    for path in paths {
+        // fs::metadata follows symbolic links: a link named on the command line
+        // is used like the file or directory it points to.
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() && options.recursive => {
+                walk_dir(path, options, &mut found)
+            }
+            Ok(metadata) if metadata.is_dir() => found.push(Err(WalkError {
+                path: path.clone(),
+                error: io::Error::new(
+                    io::ErrorKind::IsADirectory,
+                    "is a directory (use --recursive to go into it)",
+                ),
+            })),
+            Ok(_) => found.push(Ok(path.clone())),
+            Err(error) => found.push(Err(WalkError {
+                path: path.clone(),
+                error,
+            })),
+        }
+    }
+    found
+}
+
+fn walk_dir(dir: &Path, options: &WalkOptions, found: &mut Vec<Result<PathBuf, WalkError>>) {
+    let entries = fs::read_dir(dir).and_then(|entries| entries.collect::<io::Result<Vec<_>>>());
+    let mut entries = match entries {
+        Ok(entries) => entries,
+        Err(error) => {
+            found.push(Err(WalkError {
+                path: dir.to_path_buf(),
+                error,
+            }));
+            return;
+        }
+    };
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        // file_type() does not follow symbolic links, so a link back to a
+        // parent directory cannot send the walk round in circles.
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => walk_dir(&path, options, found),
+            Ok(kind) if kind.is_file() && options.matches(&path) => found.push(Ok(path)),
+            Ok(_) => {}
+            Err(error) => found.push(Err(WalkError { path, error })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a.txt, b.rs, sub/c.rs, sub/deeper/d.txt
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub/deeper")).unwrap();
+        for name in ["a.txt", "b.rs", "sub/c.rs", "sub/deeper/d.txt"] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        dir
+    }
+
+    fn names(dir: &Path, found: Vec<Result<PathBuf, WalkError>>) -> Vec<String> {
+        found
+            .into_iter()
+            .map(|file| {
+                let path = file.unwrap();
+                path.strip_prefix(dir).unwrap().display().to_string()
+            })
+            .collect()
+    }
+
+    fn options(recursive: bool, glob: Option<&str>) -> WalkOptions {
+        WalkOptions {
+            recursive,
+            glob: glob.map(|glob| Pattern::new(glob).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_recursive_walk_is_sorted() {
+        let dir = tree();
+        let found = files(&[dir.path().to_path_buf()], &options(true, None));
+        assert_eq!(
+            names(dir.path(), found),
+            ["a.txt", "b.rs", "sub/c.rs", "sub/deeper/d.txt"]
+        );
+    }
+
+    #[test]
+    fn test_glob_filters_directory_contents() {
+        let dir = tree();
+        let found = files(&[dir.path().to_path_buf()], &options(true, Some("*.rs")));
+        assert_eq!(names(dir.path(), found), ["b.rs", "sub/c.rs"]);
+
+        let pattern = format!("{}/sub/*/*.txt", dir.path().display());
+        let found = files(&[dir.path().to_path_buf()], &options(true, Some(&pattern)));
+        assert_eq!(names(dir.path(), found), ["sub/deeper/d.txt"]);
+
+        // A file named on the command line is kept whatever the pattern.
+        let found = files(&[dir.path().join("a.txt")], &options(false, Some("*.rs")));
+        assert_eq!(names(dir.path(), found), ["a.txt"]);
+    }
+
+    #[test]
+    fn test_errors_are_reported_in_place() {
+        let dir = tree();
+        let found = files(
+            &[dir.path().join("missing"), dir.path().join("sub")],
+            &WalkOptions::default(),
+        );
+        let kinds: Vec<io::ErrorKind> = found
+            .into_iter()
+            .map(|file| file.unwrap_err().error.kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            [io::ErrorKind::NotFound, io::ErrorKind::IsADirectory]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! The `watch` command: report the files created, changed and deleted under a
+//! path, and optionally run a command after each round of changes.
+//!
+//! Saving a file in an editor is rarely a single event: it may write a temp
+//! file, rename it over the original and touch the metadata, all within a few
+//! milliseconds. The events are therefore debounced: collected until nothing
+//! has happened for a quiet period, merged per path, and reported together.
+
+use crate::error::{Chain, FileError, Operation};
+use crate::walk::WalkOptions;
+use notify::event::{AccessKind, AccessMode, MetadataKind, ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// What happened to a path over one round of events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Change::Created => "created",
+            Change::Modified => "modified",
+            Change::Deleted => "deleted",
+        };
+        f.pad(name)
+    }
+}
+
+/// How to watch and what to do on a change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchOptions {
+    /// `recursive` watches subdirectories too; `glob` narrows the files reported.
+    pub walk: WalkOptions,
+    /// How long things must stay quiet before a round of changes is reported.
+    pub debounce: Duration,
+    /// A program and its arguments, run after each round; empty for none.
+    pub command: Vec<String>,
+}
+
+/// The changes of one round, by path, with the errors the watcher reported.
+#[derive(Debug, Default)]
+pub struct Batch {
+    pub changes: BTreeMap<PathBuf, Change>,
+    pub errors: Vec<notify::Error>,
+}
+
+impl Batch {
+    /// Adds a change, merged with what already happened to the path in this round.
+    pub fn add(&mut self, path: PathBuf, change: Change) {
+        use Change::*;
+        let merged = match (self.changes.get(&path), change) {
+            // Created then written to is still just created.
+            (Some(Created), Modified) => Created,
+            // Created then deleted: as far as anyone can tell, nothing happened.
+            (Some(Created), Deleted) => {
+                self.changes.remove(&path);
+                return;
+            }
+            // Deleted then created: an editor replacing the file.
+            (Some(Deleted), Created) => Modified,
+            (_, change) => change,
+        };
+        self.changes.insert(path, merged);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// Watches `path` until the process is stopped, writing each round of changes to `out`.
+pub fn watch(path: &Path, options: &WatchOptions, out: &mut impl Write) -> Result<(), FileError> {
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| watch_error(path, e))?;
+    let mode = if options.walk.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher
+        .watch(path, mode)
+        .map_err(|e| watch_error(path, e))?;
+    writeln!(out, "Watching {} (Ctrl-C to stop)", path.display()).map_err(FileError::Output)?;
+
+    while let Some(batch) = next_batch(&events, options.debounce, &options.walk) {
+        for (path, change) in &batch.changes {
+            writeln!(out, "{:<8}  {}", change, path.display()).map_err(FileError::Output)?;
+        }
+        for error in batch.errors {
+            eprintln!("error: {}", Chain(&watch_error(path, error)));
+        }
+        if !options.command.is_empty() && !batch.changes.is_empty() {
+            run(&options.command, out).map_err(FileError::Output)?;
+        }
+    }
+    Ok(())
+}
+
+/// A watcher error as a `FileError`, about the path it names, or else `path`.
+fn watch_error(path: &Path, error: notify::Error) -> FileError {
+    let path = error
+        .paths
+        .first()
+        .map_or(path, PathBuf::as_path)
+        .to_path_buf();
+    let error = match error.kind {
+        notify::ErrorKind::Io(e) => e,
+        notify::ErrorKind::PathNotFound => io::ErrorKind::NotFound.into(),
+        _ => io::Error::other(error),
+    };
+    FileError::from_io(&path, Operation::Watch, error)
+}
+
+/// Waits for the next round of changes that `walk` lets through; `None` once
+/// the watcher is gone.
+fn next_batch(
+    events: &Receiver<notify::Result<Event>>,
+    quiet: Duration,
+    walk: &WalkOptions,
+) -> Option<Batch> {
+    let mut batch = Batch::default();
+    // Block until something happens, then take events until it stays quiet.
+    let mut event = events.recv().ok()?;
+    loop {
+        match event {
+            Ok(event) => {
+                for (path, change) in changes(&event) {
+                    if walk.matches(&path) {
+                        batch.add(path, change);
+                    }
+                }
+            }
+            Err(error) => batch.errors.push(error),
+        }
+        event = match events.recv_timeout(quiet) {
+            Ok(event) => event,
+            // Nothing left to report (filtered out, or created and deleted): wait again.
+            Err(RecvTimeoutError::Timeout) if batch.is_empty() => events.recv().ok()?,
+            Err(RecvTimeoutError::Timeout) => return Some(batch),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+    }
+    (!batch.is_empty()).then_some(batch)
+}
+
+/// What an event means for each of its paths; reads and access times mean nothing.
+fn changes(event: &Event) -> Vec<(PathBuf, Change)> {
+    let change = match event.kind {
+        EventKind::Create(_) => Change::Created,
+        EventKind::Remove(_) => Change::Deleted,
+        // A rename is the old name going away and the new one appearing.
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => Change::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => Change::Created,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.iter().cloned();
+            return paths
+                .next()
+                .map(|from| (from, Change::Deleted))
+                .into_iter()
+                .chain(paths.map(|to| (to, Change::Created)))
+                .collect();
+        }
+        EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)) => return Vec::new(),
+        EventKind::Modify(_) => Change::Modified,
+        // Closing a file that was open for writing: the write is over.
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => Change::Modified,
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => return Vec::new(),
+    };
+    event
+        .paths
+        .iter()
+        .map(|path| (path.clone(), change))
+        .collect()
+}
+
+/// Runs the command, letting it write straight to the terminal. A command that
+/// fails or cannot be started is reported, and the watching goes on.
+fn run(command: &[String], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "$ {}", command.join(" "))?;
+    out.flush()?;
+    match process::Command::new(&command[0])
+        .args(&command[1..])
+        .status()
+    {
+        Ok(status) if !status.success() => writeln!(out, "{} failed: {}", command[0], status),
+        Ok(_) => Ok(()),
+        Err(e) => writeln!(out, "{}: {}", command[0], e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glob::Pattern;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+
+    fn event(kind: EventKind, paths: &[&str]) -> notify::Result<Event> {
+        let event = paths
+            .iter()
+            .fold(Event::new(kind), |event, path| event.add_path(path.into()));
+        Ok(event)
+    }
+
+    const CREATE: EventKind = EventKind::Create(CreateKind::File);
+    const WRITE: EventKind = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+    const REMOVE: EventKind = EventKind::Remove(RemoveKind::File);
+
+    #[test]
+    fn test_changes_merge_per_path() {
+        let mut batch = Batch::default();
+        batch.add("new.txt".into(), Change::Created);
+        batch.add("new.txt".into(), Change::Modified);
+        batch.add("tmp.txt".into(), Change::Created);
+        batch.add("tmp.txt".into(), Change::Deleted);
+        batch.add("saved.txt".into(), Change::Deleted);
+        batch.add("saved.txt".into(), Change::Created);
+        let changes: Vec<_> = batch.changes.into_iter().collect();
+        assert_eq!(
+            changes,
+            [
+                ("new.txt".into(), Change::Created),
+                ("saved.txt".into(), Change::Modified),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_kinds() {
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+        assert_eq!(
+            changes(&event(rename, &["a.txt", "b.txt"]).unwrap()),
+            [
+                ("a.txt".into(), Change::Deleted),
+                ("b.txt".into(), Change::Created)
+            ]
+        );
+        let read = EventKind::Access(AccessKind::Read);
+        assert!(changes(&event(read, &["a.txt"]).unwrap()).is_empty());
+        assert_eq!(
+            changes(&event(WRITE, &["a.txt"]).unwrap()),
+            [("a.txt".into(), Change::Modified)]
+        );
+    }
+
+    #[test]
+    fn test_next_batch_debounces_and_filters() {
+        let (sender, events) = mpsc::channel();
+        let walk = WalkOptions {
+            recursive: true,
+            glob: Some(Pattern::new("*.rs").unwrap()),
+        };
+        let quiet = Duration::from_millis(50);
+
+        // A burst: one round, merged and filtered.
+        for (kind, path) in [
+            (CREATE, "src/main.rs"),
+            (WRITE, "src/main.rs"),
+            (WRITE, "notes.txt"),
+            (REMOVE, "src/old.rs"),
+        ] {
+            sender.send(event(kind, &[path])).unwrap();
+        }
+        let batch = next_batch(&events, quiet, &walk).unwrap();
+        let changes: Vec<_> = batch.changes.into_iter().collect();
+        assert_eq!(
+            changes,
+            [
+                ("src/main.rs".into(), Change::Created),
+                ("src/old.rs".into(), Change::Deleted),
+            ]
+        );
+
+        // Only filtered-out events: no round at all, the next one is reported.
+        sender.send(event(WRITE, &["notes.txt"])).unwrap();
+        let later = std::thread::spawn(move || {
+            std::thread::sleep(quiet * 3);
+            sender.send(event(WRITE, &["src/lib.rs"])).unwrap();
+        });
+        let batch = next_batch(&events, quiet, &walk).unwrap();
+        assert_eq!(batch.changes.len(), 1);
+        assert_eq!(batch.changes[Path::new("src/lib.rs")], Change::Modified);
+
+        // The watcher gone: no more rounds.
+        later.join().unwrap();
+        assert!(next_batch(&events, quiet, &walk).is_none());
+    }
+}
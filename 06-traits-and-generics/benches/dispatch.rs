@@ -0,0 +1,41 @@
+//! Static against dynamic dispatch: the same tweets summarized from a
+//! `Feed<Tweet>` and from a `MixedFeed` of `Box<dyn Summary>`.
+//!
+//! Run with `cargo bench`. Expect little difference: a vtable call costs about
+//! a nanosecond, and `summarize` allocates a `String`, which costs far more.
+//! What differs is mostly the boxes: every tweet of the `MixedFeed` is a
+//! pointer to follow to somewhere else on the heap.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use traits_and_generics::feed::{Feed, MixedFeed};
+use traits_and_generics::Tweet;
+
+const ITEMS: usize = 1_000;
+
+fn tweets() -> impl Iterator<Item = Tweet> {
+    (0..ITEMS).map(|i| Tweet {
+        username: format!("user{}", i % 10),
+        content: format!("tweet number {}", i),
+        reply: i % 3 == 0,
+        retweet: i % 5 == 0,
+    })
+}
+
+fn dispatch(c: &mut Criterion) {
+    let feed: Feed<Tweet> = tweets().collect();
+    let mut mixed = MixedFeed::new();
+    for tweet in tweets() {
+        mixed.add(tweet);
+    }
+
+    c.bench_function("Feed<Tweet>::summarize_all", |b| {
+        b.iter(|| black_box(&feed).summarize_all())
+    });
+    c.bench_function("MixedFeed::summarize_all", |b| {
+        b.iter(|| black_box(&mixed).summarize_all())
+    });
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
@@ -0,0 +1,189 @@
+//! A small feed aggregator: any number of sources, each producing its own kind
+//! of content, collected into one list, newest first.
+//!
+//! The aggregator knows nothing about articles or tweets. A source is anything
+//! that implements `ContentSource`, and what it produces is anything that
+//! implements `Summary`; both are kept as trait objects, `Box<dyn ContentSource>`
+//! in the registry and `Box<dyn Summary>` in each entry. A new kind of source or
+//! of content needs no change here. The price is a call through a vtable for
+//! each method and a heap allocation for each box: nothing next to fetching a feed.
+
+use crate::Summary;
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fmt;
+
+/// One piece of content, with where and when it was published.
+pub struct Entry {
+    /// The name of the source it came from.
+    pub source: String,
+    /// Seconds since the Unix epoch.
+    pub published: u64,
+    pub item: Box<dyn Summary>,
+}
+
+/// Why a source could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchError(pub String);
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for FetchError {}
+
+/// Somewhere content comes from.
+pub trait ContentSource {
+    /// Shown next to each entry, and in errors.
+    fn name(&self) -> &str;
+
+    /// Everything the source has now, in any order.
+    fn fetch(&self) -> Result<Vec<Entry>, FetchError>;
+}
+
+/// The sources to aggregate, whatever their types.
+#[derive(Default)]
+pub struct Registry {
+    sources: Vec<Box<dyn ContentSource>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Adds a source. It is boxed here, so callers pass the value itself.
+    pub fn register(&mut self, source: impl ContentSource + 'static) {
+        self.sources.push(Box::new(source));
+    }
+
+    /// The names of the sources, in the order they were registered.
+    pub fn names(&self) -> Vec<&str> {
+        self.sources.iter().map(|source| source.name()).collect()
+    }
+
+    /// Fetches every source and sorts what they return, newest first. A source
+    /// that fails is reported, and the others are still aggregated.
+    pub fn aggregate(&self) -> Aggregate {
+        let mut aggregate = Aggregate::default();
+        for source in &self.sources {
+            match source.fetch() {
+                Ok(entries) => aggregate.entries.extend(entries),
+                Err(e) => aggregate.failures.push((source.name().to_string(), e)),
+            }
+        }
+        // A stable sort: entries published at the same time stay in source order.
+        aggregate
+            .entries
+            .sort_by_key(|entry| Reverse(entry.published));
+        aggregate
+    }
+}
+
+/// What the sources returned, together.
+#[derive(Default)]
+pub struct Aggregate {
+    pub entries: Vec<Entry>,
+    /// The source's name and what went wrong.
+    pub failures: Vec<(String, FetchError)>,
+}
+
+impl Aggregate {
+    /// One line per entry, then one per source that failed.
+    pub fn render(&self) -> String {
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| format!("[{}] {}", entry.source, entry.item.summarize()))
+            .collect();
+        for (source, error) in &self.failures {
+            lines.push(format!("[{}] unavailable: {}", source, error));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::{NewsDesk, Timeline};
+    use crate::{NewsArticle, Tweet};
+
+    fn article(headline: &str) -> NewsArticle {
+        NewsArticle {
+            headline: headline.to_string(),
+            location: String::from("Oslo"),
+            author: String::from("Kari"),
+            content: String::new(),
+        }
+    }
+
+    fn tweet(content: &str) -> Tweet {
+        Tweet {
+            username: String::from("rustlang"),
+            content: content.to_string(),
+            reply: false,
+            retweet: false,
+        }
+    }
+
+    /// A source that is always down.
+    struct Offline;
+
+    impl ContentSource for Offline {
+        fn name(&self) -> &str {
+            "offline"
+        }
+
+        fn fetch(&self) -> Result<Vec<Entry>, FetchError> {
+            Err(FetchError(String::from("connection refused")))
+        }
+    }
+
+    #[test]
+    fn test_entries_are_merged_newest_first() {
+        let mut registry = Registry::new();
+        registry.register(NewsDesk::new(
+            "desk",
+            vec![(100, article("Old news")), (300, article("Fresh news"))],
+        ));
+        registry.register(Timeline::new(
+            "rustlang",
+            vec![(200, tweet("In between")), (300, tweet("Same time"))],
+        ));
+        assert_eq!(registry.names(), ["desk", "@rustlang"]);
+
+        let aggregate = registry.aggregate();
+        assert!(aggregate.failures.is_empty());
+        assert_eq!(
+            aggregate.render(),
+            "[desk] Fresh news, by Kari (Oslo)\n\
+             [@rustlang] rustlang: Same time\n\
+             [@rustlang] rustlang: In between\n\
+             [desk] Old news, by Kari (Oslo)"
+        );
+    }
+
+    #[test]
+    fn test_a_failing_source_does_not_stop_the_others() {
+        let mut registry = Registry::new();
+        registry.register(Offline);
+        registry.register(NewsDesk::new("desk", vec![(1, article("Still here"))]));
+
+        let aggregate = registry.aggregate();
+        assert_eq!(aggregate.entries.len(), 1);
+        assert_eq!(
+            aggregate.render(),
+            "[desk] Still here, by Kari (Oslo)\n[offline] unavailable: connection refused"
+        );
+    }
+
+    #[test]
+    fn test_empty_registry() {
+        let aggregate = Registry::new().aggregate();
+        assert!(aggregate.entries.is_empty());
+        assert_eq!(aggregate.render(), "");
+    }
+}
@@ -0,0 +1,196 @@
+//! `Content`: an article or a tweet, as one type that serde can read and write.
+//!
+//! A `Box<dyn Summary>` cannot be deserialized: nothing in the JSON says which
+//! type to build, and the set of types implementing a trait is open. An enum
+//! closes the set, and `#[serde(tag = "type")]` stores the variant in the
+//! object itself:
+//!
+//! ```json
+//! {"type": "tweet", "username": "rustlang", "content": "Rust 1.80 is out"}
+//! ```
+//!
+//! `Content` implements `Summary` by matching on the variant, a third way to
+//! dispatch next to generics and trait objects: static like the first, mixed
+//! like the second, but closed to new types.
+
+use crate::feed::Feed;
+use crate::report::DetailedSummary;
+use crate::{NewsArticle, Summary, Tweet};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Content {
+    Article(NewsArticle),
+    Tweet(Tweet),
+}
+
+impl Summary for Content {
+    fn summarize(&self) -> String {
+        match self {
+            Content::Article(article) => article.summarize(),
+            Content::Tweet(tweet) => tweet.summarize(),
+        }
+    }
+
+    fn summarize_author(&self) -> String {
+        match self {
+            Content::Article(article) => article.summarize_author(),
+            Content::Tweet(tweet) => tweet.summarize_author(),
+        }
+    }
+}
+
+impl DetailedSummary for Content {
+    fn details(&self) -> String {
+        match self {
+            Content::Article(article) => article.details(),
+            Content::Tweet(tweet) => tweet.details(),
+        }
+    }
+}
+
+impl From<NewsArticle> for Content {
+    fn from(article: NewsArticle) -> Self {
+        Content::Article(article)
+    }
+}
+
+impl From<Tweet> for Content {
+    fn from(tweet: Tweet) -> Self {
+        Content::Tweet(tweet)
+    }
+}
+
+/// Why a content file could not be loaded or saved.
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "{}", e),
+            // serde_json says where: "unknown variant `video`, ... at line 3 column 18".
+            StoreError::Json(e) => write!(f, "invalid content: {}", e),
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StoreError::Io(e) => Some(e),
+            StoreError::Json(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::Json(e)
+    }
+}
+
+/// Reads a JSON array of articles and tweets.
+pub fn load(path: &Path) -> Result<Feed<Content>, StoreError> {
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Writes `feed` as a JSON array that `load` reads back.
+pub fn save(path: &Path, feed: &Feed<Content>) -> Result<(), StoreError> {
+    let mut json = serde_json::to_string_pretty(feed)?;
+    json.push('\n');
+    fs::write(path, json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixture() -> &'static Path {
+        Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/content.json"
+        ))
+    }
+
+    #[test]
+    fn test_load_fixture() {
+        let feed = load(fixture()).unwrap();
+        assert_eq!(feed.len(), 4);
+        assert_eq!(
+            feed.summarize_all()[..2],
+            [
+                "Penguins win the Stanley Cup Championship!, by Iceburgh (Pittsburgh, PA, USA)",
+                "horse_ebooks: of course, as you probably already know, people",
+            ]
+        );
+        // Left out of the JSON: false.
+        let Some(Content::Tweet(tweet)) = feed.iter().nth(1) else {
+            panic!("the second item is a tweet");
+        };
+        assert!(!tweet.reply && !tweet.retweet);
+        let authors: Vec<String> = feed.iter().map(Content::summarize_author).collect();
+        assert_eq!(
+            authors,
+            ["Iceburgh", "@horse_ebooks", "@rustlang", "Iceburgh"]
+        );
+    }
+
+    #[test]
+    fn test_tagged_json() {
+        let tweet = Content::from(Tweet {
+            username: String::from("rustlang"),
+            content: String::from("Rust 1.80 is out"),
+            reply: true,
+            retweet: false,
+        });
+        assert_eq!(
+            serde_json::to_value(&tweet).unwrap(),
+            json!({
+                "type": "tweet",
+                "username": "rustlang",
+                "content": "Rust 1.80 is out",
+                "reply": true,
+                "retweet": false,
+            })
+        );
+
+        let error = serde_json::from_str::<Content>(r#"{"type": "video", "url": "x"}"#)
+            .map_err(StoreError::from)
+            .unwrap_err();
+        assert!(error.to_string().starts_with(
+            "invalid content: unknown variant `video`, expected `article` or `tweet`"
+        ));
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let feed = load(fixture()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("content.json");
+        save(&path, &feed).unwrap();
+        let reloaded = load(&path).unwrap();
+        assert!(feed.iter().eq(reloaded.iter()));
+
+        let missing = load(&dir.path().join("missing.json")).unwrap_err();
+        assert!(matches!(missing, StoreError::Io(ref e) if e.kind() == io::ErrorKind::NotFound));
+    }
+}
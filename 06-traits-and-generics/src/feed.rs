@@ -0,0 +1,293 @@
+//! Two ways to keep a list of content: `Feed<T>`, generic over one type, and
+//! `MixedFeed`, of boxed trait objects.
+//!
+//! `Feed<Tweet>` holds tweets and nothing else. The compiler writes a copy of
+//! its methods for each `T` (monomorphization), so `summarize` is a direct
+//! call that can be inlined, and the items sit in the `Vec` itself. Methods can
+//! even exist only for some `T`, with a `where` clause: `report` is there only
+//! when `T: DetailedSummary`.
+//!
+//! `MixedFeed` holds articles and tweets together, as `Box<dyn Summary>`: one
+//! heap allocation per item and a call through a vtable per method, and only
+//! the methods of `Summary` are left. `benches/dispatch.rs` measures the
+//! difference.
+
+use crate::report::{self, DetailedSummary, Verbosity};
+use crate::summarizer::{self, Summarizer};
+use crate::Summary;
+use serde::{Deserialize, Serialize};
+use std::slice;
+
+/// Items of one type, in the order they were added. In JSON, the array of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Feed<T: Summary> {
+    items: Vec<T>,
+}
+
+impl<T: Summary> Feed<T> {
+    pub fn new() -> Self {
+        Feed { items: Vec::new() }
+    }
+
+    pub fn add(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The last `n` items added, the newest first.
+    pub fn latest(&self, n: usize) -> impl Iterator<Item = &T> {
+        self.items.iter().rev().take(n)
+    }
+
+    /// The summary of every item, oldest first.
+    pub fn summarize_all(&self) -> Vec<String> {
+        self.items.iter().map(T::summarize).collect()
+    }
+
+    /// The summary of every item, shortened by `summarizer`.
+    pub fn summarize_all_with<S>(&self, summarizer: &S) -> Vec<String>
+    where
+        S: Summarizer + ?Sized,
+    {
+        self.items
+            .iter()
+            .map(|item| summarizer::summarize_with(summarizer, item))
+            .collect()
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// The items for which `keep` is true, as a new feed.
+    pub fn filter<F>(&self, keep: F) -> Feed<T>
+    where
+        T: Clone,
+        F: Fn(&T) -> bool,
+    {
+        self.items
+            .iter()
+            .filter(|item| keep(item))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Only for feeds of a type with details: `Feed<NewsArticle>` has this
+/// method, a feed of a type with only `Summary` does not.
+impl<T> Feed<T>
+where
+    T: DetailedSummary,
+{
+    pub fn report(&self, title: &str, level: Verbosity) -> String {
+        report::report(title, &self.items, level)
+    }
+}
+
+// Written out: `#[derive(Default)]` would require `T: Default`, which an empty
+// feed does not need.
+impl<T: Summary> Default for Feed<T> {
+    fn default() -> Self {
+        Feed::new()
+    }
+}
+
+impl<T: Summary> FromIterator<T> for Feed<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        Feed {
+            items: items.into_iter().collect(),
+        }
+    }
+}
+
+/// `feed + other`: both feeds in one, the items of `other` after those of `feed`.
+// `std::ops::Add` is not imported: where it is in scope, `feed.add(item)` finds
+// `Add::add`, which takes `self` by value, before the inherent `add`, which
+// takes `&mut self`, and fails to compile.
+impl<T: Summary> std::ops::Add for Feed<T> {
+    type Output = Feed<T>;
+
+    fn add(mut self, other: Feed<T>) -> Feed<T> {
+        self.items.extend(other.items);
+        self
+    }
+}
+
+/// `for item in feed`: takes the items.
+impl<T: Summary> IntoIterator for Feed<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// `for item in &feed`: borrows them.
+impl<'a, T: Summary> IntoIterator for &'a Feed<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Items of any types that implement `Summary`, in the order they were added.
+#[derive(Default)]
+pub struct MixedFeed {
+    items: Vec<Box<dyn Summary>>,
+}
+
+impl MixedFeed {
+    pub fn new() -> Self {
+        MixedFeed::default()
+    }
+
+    /// Takes any `Summary` and boxes it.
+    pub fn add(&mut self, item: impl Summary + 'static) {
+        self.items.push(Box::new(item));
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The last `n` items added, the newest first.
+    pub fn latest(&self, n: usize) -> impl Iterator<Item = &dyn Summary> {
+        self.iter().rev().take(n)
+    }
+
+    /// The summary of every item, oldest first.
+    pub fn summarize_all(&self) -> Vec<String> {
+        self.items.iter().map(|item| item.summarize()).collect()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &dyn Summary> {
+        self.items.iter().map(|item| item.as_ref())
+    }
+}
+
+impl IntoIterator for MixedFeed {
+    type Item = Box<dyn Summary>;
+    type IntoIter = std::vec::IntoIter<Box<dyn Summary>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MixedFeed {
+    type Item = &'a Box<dyn Summary>;
+    type IntoIter = slice::Iter<'a, Box<dyn Summary>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summarizer::TruncateSummarizer;
+    use crate::{NewsArticle, Tweet};
+
+    fn tweet(content: &str) -> Tweet {
+        Tweet {
+            username: String::from("rustlang"),
+            content: content.to_string(),
+            reply: false,
+            retweet: content.starts_with("RT"),
+        }
+    }
+
+    #[test]
+    fn test_feed() {
+        let mut feed = Feed::new();
+        assert!(feed.is_empty());
+        for content in ["one", "RT two", "three"] {
+            feed.add(tweet(content));
+        }
+        assert_eq!(feed.len(), 3);
+
+        let latest: Vec<&str> = feed.latest(2).map(|t| t.content.as_str()).collect();
+        assert_eq!(latest, ["three", "RT two"]);
+        // More than there are: all of them.
+        assert_eq!(feed.latest(10).count(), 3);
+        assert_eq!(
+            feed.summarize_all(),
+            ["rustlang: one", "rustlang: RT two", "rustlang: three"]
+        );
+        let short = TruncateSummarizer { max_chars: 14 };
+        assert_eq!(
+            feed.summarize_all_with(&short),
+            ["rustlang: one", "rustlang: RT…", "rustlang…"]
+        );
+
+        let originals = feed.filter(|tweet| !tweet.retweet);
+        assert_eq!(originals.len(), 2);
+        let mut count = 0;
+        for tweet in &originals {
+            assert!(!tweet.retweet);
+            count += 1;
+        }
+        assert_eq!(count, 2);
+        let contents: Vec<String> = originals.into_iter().map(|t| t.content).collect();
+        assert_eq!(contents, ["one", "three"]);
+    }
+
+    #[test]
+    fn test_add_merges_feeds() {
+        let morning: Feed<Tweet> = ["one", "two"].into_iter().map(tweet).collect();
+        let evening: Feed<Tweet> = ["three"].into_iter().map(tweet).collect();
+        let day = morning + evening;
+        assert_eq!(
+            day.summarize_all(),
+            ["rustlang: one", "rustlang: two", "rustlang: three"]
+        );
+        assert_eq!((day + Feed::new()).len(), 3);
+    }
+
+    #[test]
+    fn test_report_for_detailed_items() {
+        let feed: Feed<Tweet> = ["one", "two"].into_iter().map(tweet).collect();
+        assert_eq!(
+            feed.report("Tweets", Verbosity::Brief),
+            "== Tweets (2) ==\n- rustlang: one\n- rustlang: two"
+        );
+    }
+
+    #[test]
+    fn test_mixed_feed() {
+        let mut feed = MixedFeed::new();
+        feed.add(tweet("one"));
+        feed.add(NewsArticle {
+            headline: String::from("Two"),
+            location: String::from("Oslo"),
+            author: String::from("Kari"),
+            content: String::new(),
+        });
+        feed.add(tweet("three"));
+        assert_eq!(feed.len(), 3);
+        assert_eq!(
+            feed.summarize_all(),
+            ["rustlang: one", "Two, by Kari (Oslo)", "rustlang: three"]
+        );
+        let authors: Vec<String> = feed.latest(2).map(|item| item.summarize_author()).collect();
+        assert_eq!(authors, ["@rustlang", "Kari"]);
+        assert_eq!((&feed).into_iter().count(), 3);
+        assert_eq!(feed.into_iter().count(), 3);
+    }
+}
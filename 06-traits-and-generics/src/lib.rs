@@ -0,0 +1,110 @@
+//! The content types and their traits, and everything built on them: feeds,
+//! sources, an aggregator and reports. The binary is a tour of it; the
+//! benchmarks in `benches/` use it too.
+
+pub mod aggregator;
+pub mod content;
+pub mod feed;
+pub mod ranking;
+pub mod report;
+pub mod rss;
+pub mod sources;
+pub mod summarizer;
+
+use serde::{Deserialize, Serialize};
+
+/// A trait defining a common behavior for summarizing content.
+pub trait Summary {
+    /// Returns a summary string. Each implementing type must provide its own logic.
+    fn summarize(&self) -> String;
+
+    /// Who wrote it. A default method: types that know override it, the others
+    /// get this without writing anything.
+    fn summarize_author(&self) -> String {
+        String::from("(unknown author)")
+    }
+}
+
+/// A news article struct with several metadata fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewsArticle {
+    pub headline: String,
+    pub location: String,
+    pub author: String,
+    pub content: String,
+}
+
+/// Implement the Summary trait for NewsArticle.
+impl Summary for NewsArticle {
+    fn summarize(&self) -> String {
+        format!("{}, by {} ({})", self.headline, self.author, self.location)
+    }
+
+    fn summarize_author(&self) -> String {
+        self.author.clone()
+    }
+}
+
+/// A tweet struct representing a short status update.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tweet {
+    pub username: String,
+    pub content: String,
+    // Most tweets are neither, so the JSON may leave them out.
+    #[serde(default)]
+    pub reply: bool,
+    #[serde(default)]
+    pub retweet: bool,
+}
+
+/// Implement the Summary trait for Tweet.
+impl Summary for Tweet {
+    fn summarize(&self) -> String {
+        format!("{}: {}", self.username, self.content)
+    }
+
+    fn summarize_author(&self) -> String {
+        format!("@{}", self.username)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_article_summary() {
+        let article = NewsArticle {
+            headline: String::from("Rust is awesome"),
+            location: String::from("Global"),
+            author: String::from("Dev"),
+            content: String::from("..."),
+        };
+        assert_eq!(article.summarize(), "Rust is awesome, by Dev (Global)");
+    }
+
+    #[test]
+    fn test_tweet_summary() {
+        let tweet = Tweet {
+            username: String::from("rustlang"),
+            content: String::from("Rust 1.75 is out!"),
+            reply: false,
+            retweet: false,
+        };
+        assert_eq!(tweet.summarize(), "rustlang: Rust 1.75 is out!");
+        assert_eq!(tweet.summarize_author(), "@rustlang");
+    }
+
+    #[test]
+    fn test_default_author() {
+        struct Anonymous;
+
+        impl Summary for Anonymous {
+            fn summarize(&self) -> String {
+                String::from("A note on the fridge")
+            }
+        }
+
+        assert_eq!(Anonymous.summarize_author(), "(unknown author)");
+    }
+}
@@ -1,38 +1,13 @@
-/// A trait defining a common behavior for summarizing content.
-pub trait Summary {
-    /// Returns a summary string. Each implementing type must provide its own logic.
-    fn summarize(&self) -> String;
-}
-
-/// A news article struct with several metadata fields.
-pub struct NewsArticle {
-    pub headline: String,
-    pub location: String,
-    pub author: String,
-    pub content: String,
-}
-
-/// Implement the Summary trait for NewsArticle.
-impl Summary for NewsArticle {
-    fn summarize(&self) -> String {
-        format!("{}, by {} ({})", self.headline, self.author, self.location)
-    }
-}
-
-/// A tweet struct representing a short status update.
-pub struct Tweet {
-    pub username: String,
-    pub content: String,
-    pub reply: bool,
-    pub retweet: bool,
-}
-
-/// Implement the Summary trait for Tweet.
-impl Summary for Tweet {
-    fn summarize(&self) -> String {
-        format!("{}: {}", self.username, self.content)
-    }
-}
+use std::path::Path;
+use traits_and_generics::aggregator::Registry;
+use traits_and_generics::content::{self, Content};
+use traits_and_generics::feed::{Feed, MixedFeed};
+use traits_and_generics::ranking::{self, Engagement, Scored};
+use traits_and_generics::report::{print_report, Verbosity};
+use traits_and_generics::rss::FeedSource;
+use traits_and_generics::sources::{NewsDesk, Timeline};
+use traits_and_generics::summarizer;
+use traits_and_generics::{NewsArticle, Summary, Tweet};
 
 /// A generic function that works with any type T that implements the Summary trait.
 /// This is called "Trait Bound" syntax.
@@ -63,31 +38,145 @@ fn main() {
 
     notify(&article);
     notify(&tweet);
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // More than one line, through the `DetailedSummary` supertrait.
+    println!();
+    print_report("Articles", std::slice::from_ref(&article), Verbosity::Full);
+    print_report("Tweets", std::slice::from_ref(&tweet), Verbosity::Brief);
 
-    #[test]
-    fn test_article_summary() {
-        let article = NewsArticle {
-            headline: String::from("Rust is awesome"),
-            location: String::from("Global"),
-            author: String::from("Dev"),
-            content: String::from("..."),
-        };
-        assert_eq!(article.summarize(), "Rust is awesome, by Dev (Global)");
+    // A feed of one type, and one of any types: static and dynamic dispatch.
+    let articles: Feed<NewsArticle> = [article.clone()].into_iter().collect();
+    println!("{}", articles.report("Feed<NewsArticle>", Verbosity::Brief));
+    let mut mixed = MixedFeed::new();
+    mixed.add(article.clone());
+    mixed.add(tweet.clone());
+    println!("== MixedFeed, latest first ==");
+    for item in mixed.latest(2) {
+        println!("- {}", item.summarize());
     }
 
-    #[test]
-    fn test_tweet_summary() {
-        let tweet = Tweet {
-            username: String::from("rustlang"),
-            content: String::from("Rust 1.75 is out!"),
-            reply: false,
-            retweet: false,
-        };
-        assert_eq!(tweet.summarize(), "rustlang: Rust 1.75 is out!");
+    // Both types again, read from JSON as one enum, and written back.
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/content.json");
+    match content::load(Path::new(fixture)) {
+        Ok(feed) => {
+            println!("{}", feed.report("content.json", Verbosity::Normal));
+            rank(feed.clone());
+            // Strategies picked by name, as a user would type them.
+            for name in ["truncate:30", "first-sentence", "keywords:3"] {
+                let strategy = summarizer::by_name(name).expect("a known summarizer");
+                println!("== {} ==", name);
+                for line in feed.summarize_all_with(strategy.as_ref()) {
+                    println!("- {}", line);
+                }
+            }
+            let copy = std::env::temp_dir().join("traits-and-generics-content.json");
+            match content::save(&copy, &feed) {
+                Ok(()) => println!("Written back to {}", copy.display()),
+                Err(e) => eprintln!("Error: {}: {}", copy.display(), e),
+            }
+        }
+        Err(e) => eprintln!("Error: {}: {}", fixture, e),
+    }
+
+    // The same two types again, now among many items from several sources,
+    // all handled through `dyn ContentSource` and `dyn Summary`.
+    let mut registry = Registry::new();
+    registry.register(NewsDesk::new(
+        "Daily Puck",
+        vec![
+            (1_686_000_000, article.clone()),
+            (
+                1_686_090_000,
+                NewsArticle {
+                    headline: String::from("Zamboni driver retires after 40 years"),
+                    location: String::from("Pittsburgh, PA, USA"),
+                    author: String::from("Iceburgh"),
+                    content: String::from("He never once hit the boards."),
+                },
+            ),
+        ],
+    ));
+    registry.register(Timeline::new(
+        "horse_ebooks",
+        vec![
+            (1_686_050_000, tweet.clone()),
+            (
+                1_686_095_000,
+                Tweet {
+                    username: String::from("horse_ebooks"),
+                    content: String::from("Everything happens so much"),
+                    reply: false,
+                    retweet: false,
+                },
+            ),
+        ],
+    ));
+
+    // Real feeds: those named on the command line, or the two bundled ones.
+    let feeds: Vec<String> = std::env::args().skip(1).collect();
+    if feeds.is_empty() {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+        registry.register(FeedSource::file(
+            "Rust Blog",
+            format!("{}/rust-blog.rss", fixtures),
+        ));
+        registry.register(FeedSource::file(
+            "This Week in Rust",
+            format!("{}/this-week.atom", fixtures),
+        ));
+    }
+    for feed in &feeds {
+        registry.register(feed_source(feed));
+    }
+
+    let aggregate = registry.aggregate();
+    println!(
+        "\n{} items from {}:",
+        aggregate.entries.len(),
+        registry.names().join(", ")
+    );
+    println!("{}", aggregate.render());
+}
+
+/// Ranks `feed`, with made-up engagement, merged with a feed of one more tweet.
+fn rank(feed: Feed<Content>) {
+    let scored: Feed<Scored<Content>> = feed
+        .into_iter()
+        .zip([(320, 41, 12), (3_400, 900, 210), (12, 1, 0), (85, 2, 30)])
+        .map(|(item, (likes, shares, comments))| {
+            let engagement = Engagement {
+                likes,
+                shares,
+                comments,
+            };
+            Scored::new(item, engagement)
+        })
+        .collect();
+    let late = Tweet {
+        username: String::from("rustlang"),
+        content: String::from("Rust 1.81 is out"),
+        reply: false,
+        retweet: false,
+    };
+    let extra: Feed<Scored<Content>> = [Scored::new(
+        Content::from(late),
+        Engagement {
+            likes: 800,
+            shares: 150,
+            comments: 40,
+        },
+    )]
+    .into_iter()
+    .collect();
+    println!("== Top 3 by engagement ==");
+    println!("{}", ranking::ranking(scored + extra, 3));
+}
+
+/// A feed named on the command line: a URL with the `http` feature, else a file.
+fn feed_source(location: &str) -> FeedSource {
+    #[cfg(feature = "http")]
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return FeedSource::url(location, location);
     }
+    FeedSource::file(location, location)
 }
@@ -0,0 +1,220 @@
+//! Ranking content by engagement, with the standard operator traits.
+//!
+//! `Scored<T>` puts likes, shares and comments next to any `Summary`. Ordering
+//! it is `Ord`, so `sort`, `max` and `BinaryHeap` all rank by engagement;
+//! printing it is `Display`, so `{}` and `to_string()` show the score with the
+//! summary. `top_k` needs nothing else from its items than these two traits.
+
+use crate::Summary;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fmt;
+
+/// What readers did with a piece of content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Engagement {
+    pub likes: u32,
+    pub shares: u32,
+    pub comments: u32,
+}
+
+impl Engagement {
+    /// A share reaches new readers and a comment takes effort, so they weigh
+    /// more than a like.
+    pub fn score(&self) -> u64 {
+        u64::from(self.likes) + 2 * u64::from(self.comments) + 3 * u64::from(self.shares)
+    }
+}
+
+/// An item with its engagement.
+#[derive(Debug, Clone)]
+pub struct Scored<T> {
+    pub item: T,
+    pub engagement: Engagement,
+}
+
+impl<T> Scored<T> {
+    pub fn new(item: T, engagement: Engagement) -> Self {
+        Scored { item, engagement }
+    }
+
+    pub fn score(&self) -> u64 {
+        self.engagement.score()
+    }
+}
+
+/// A scored item summarizes as the item does, so it can go in a `Feed` too.
+impl<T: Summary> Summary for Scored<T> {
+    fn summarize(&self) -> String {
+        self.item.summarize()
+    }
+
+    fn summarize_author(&self) -> String {
+        self.item.summarize_author()
+    }
+}
+
+/// By score; equal scores by summary, in reverse alphabetical order, so that a
+/// ranking (highest first) lists them alphabetically.
+///
+/// `Ord` must agree with `Eq`: two items the order cannot tell apart are
+/// equal. So equality is defined by the same comparison, and not derived,
+/// which would compare fields that the order ignores.
+impl<T: Summary> Ord for Scored<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score()
+            .cmp(&other.score())
+            .then_with(|| other.summarize().cmp(&self.summarize()))
+    }
+}
+
+impl<T: Summary> PartialOrd for Scored<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Summary> PartialEq for Scored<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Summary> Eq for Scored<T> {}
+
+/// `{}`: the score and the summary; `{:#}` adds the counts behind the score.
+impl<T: Summary> fmt::Display for Scored<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{:>4}] {}", self.score(), self.summarize())?;
+        if f.alternate() {
+            let Engagement {
+                likes,
+                shares,
+                comments,
+            } = self.engagement;
+            write!(
+                f,
+                "\n       {} likes, {} shares, {} comments",
+                likes, shares, comments
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The `k` greatest items, the greatest first. Only `k` items are kept at a
+/// time, so this works on an iterator of any length.
+pub fn top_k<T, I>(items: I, k: usize) -> Vec<T>
+where
+    T: Ord,
+    I: IntoIterator<Item = T>,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+    // A min-heap (BinaryHeap is a max-heap, `Reverse` turns it around) of the
+    // best so far: each new item only has to beat the worst of them.
+    let mut best = BinaryHeap::with_capacity(k + 1);
+    for item in items {
+        best.push(Reverse(item));
+        if best.len() > k {
+            best.pop();
+        }
+    }
+    best.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(item)| item)
+        .collect()
+}
+
+/// The `k` greatest items as a numbered list.
+pub fn ranking<T, I>(items: I, k: usize) -> String
+where
+    T: Ord + fmt::Display,
+    I: IntoIterator<Item = T>,
+{
+    top_k(items, k)
+        .iter()
+        .enumerate()
+        .map(|(i, item)| format!("{}. {}", i + 1, item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tweet;
+
+    fn scored(content: &str, likes: u32, shares: u32, comments: u32) -> Scored<Tweet> {
+        let tweet = Tweet {
+            username: String::from("rustlang"),
+            content: content.to_string(),
+            reply: false,
+            retweet: false,
+        };
+        Scored::new(
+            tweet,
+            Engagement {
+                likes,
+                shares,
+                comments,
+            },
+        )
+    }
+
+    #[test]
+    fn test_score_and_order() {
+        // 10 likes; 1 share and 2 comments: 3 + 4.
+        assert_eq!(scored("a", 10, 0, 0).score(), 10);
+        assert_eq!(scored("b", 0, 1, 2).score(), 7);
+        assert!(scored("a", 10, 0, 0) > scored("b", 0, 1, 2));
+        assert_eq!(Engagement::default().score(), 0);
+
+        // Same score, different summaries: ordered, not equal.
+        let (apple, zebra) = (scored("apple", 3, 0, 0), scored("zebra", 0, 1, 0));
+        assert_ne!(apple, zebra);
+        assert!(apple > zebra);
+        // Same score and summary: equal, whatever the counts.
+        assert_eq!(scored("same", 3, 0, 0), scored("same", 0, 1, 0));
+    }
+
+    #[test]
+    fn test_top_k() {
+        let items = || {
+            vec![
+                scored("low", 1, 0, 0),
+                scored("high", 50, 0, 0),
+                scored("mid", 20, 0, 0),
+                scored("also mid", 20, 0, 0),
+            ]
+        };
+        let names = |top: Vec<Scored<Tweet>>| -> Vec<String> {
+            top.into_iter().map(|s| s.item.content).collect()
+        };
+        assert_eq!(names(top_k(items(), 2)), ["high", "also mid"]);
+        assert_eq!(
+            names(top_k(items(), 10)),
+            ["high", "also mid", "mid", "low"]
+        );
+        assert!(top_k(items(), 0).is_empty());
+        assert!(top_k(Vec::<Scored<Tweet>>::new(), 3).is_empty());
+        // Anything `Ord` will do.
+        assert_eq!(top_k([3, 1, 4, 1, 5, 9, 2, 6], 3), [9, 6, 5]);
+    }
+
+    #[test]
+    fn test_display() {
+        let item = scored("Rust 1.80 is out!", 120, 30, 4);
+        assert_eq!(item.to_string(), "[ 218] rustlang: Rust 1.80 is out!");
+        assert_eq!(
+            format!("{:#}", item),
+            "[ 218] rustlang: Rust 1.80 is out!\n       120 likes, 30 shares, 4 comments"
+        );
+        assert_eq!(
+            ranking(vec![scored("b", 1, 0, 0), scored("a", 2, 0, 0)], 5),
+            "1. [   2] rustlang: a\n2. [   1] rustlang: b"
+        );
+        assert_eq!(ranking(Vec::<Scored<Tweet>>::new(), 5), "");
+    }
+}
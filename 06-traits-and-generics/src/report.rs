@@ -0,0 +1,143 @@
+//! `DetailedSummary`: a supertrait of `Summary`, for content that can say more
+//! than one line about itself.
+//!
+//! `trait DetailedSummary: Summary` means a type must implement `Summary` to
+//! implement `DetailedSummary`, and in exchange the methods of `Summary` can
+//! be called on any `DetailedSummary`. So `summarize_verbose` is written once,
+//! here, out of `summarize` and `summarize_author`, and each type only says
+//! what its details are.
+
+use crate::{NewsArticle, Summary, Tweet};
+
+/// How much `summarize_verbose` says.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// The summary alone.
+    Brief,
+    /// The summary and the author.
+    Normal,
+    /// Everything: the summary, the author and the details.
+    Full,
+}
+
+pub trait DetailedSummary: Summary {
+    /// What the summary leaves out: the body of an article, what kind of tweet
+    /// a tweet is.
+    fn details(&self) -> String;
+
+    /// One, two or three lines, the later ones indented.
+    fn summarize_verbose(&self, level: Verbosity) -> String {
+        let mut text = self.summarize();
+        if level >= Verbosity::Normal {
+            text.push_str(&format!("\n    by {}", self.summarize_author()));
+        }
+        if level >= Verbosity::Full {
+            text.push_str(&format!("\n    {}", self.details()));
+        }
+        text
+    }
+}
+
+impl DetailedSummary for NewsArticle {
+    fn details(&self) -> String {
+        self.content.clone()
+    }
+}
+
+impl DetailedSummary for Tweet {
+    fn details(&self) -> String {
+        let kind = match (self.reply, self.retweet) {
+            (false, false) => "an original tweet",
+            (true, false) => "a reply",
+            (false, true) => "a retweet",
+            (true, true) => "a retweeted reply",
+        };
+        format!("{}, {} characters", kind, self.content.chars().count())
+    }
+}
+
+/// A titled list of `items`, at `level`. Generic: any `DetailedSummary` will
+/// do, and through it `summarize()` and `summarize_author()` as well.
+pub fn report<T: DetailedSummary>(title: &str, items: &[T], level: Verbosity) -> String {
+    let mut report = format!("== {} ({}) ==", title, items.len());
+    for item in items {
+        report.push_str(&format!("\n- {}", item.summarize_verbose(level)));
+    }
+    report
+}
+
+pub fn print_report<T: DetailedSummary>(title: &str, items: &[T], level: Verbosity) {
+    println!("{}", report(title, items, level));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article() -> NewsArticle {
+        NewsArticle {
+            headline: String::from("Rust is awesome"),
+            location: String::from("Global"),
+            author: String::from("Dev"),
+            content: String::from("Memory safety without garbage collection."),
+        }
+    }
+
+    fn tweet(reply: bool, retweet: bool) -> Tweet {
+        Tweet {
+            username: String::from("rustlang"),
+            content: String::from("Rust 1.75 is out!"),
+            reply,
+            retweet,
+        }
+    }
+
+    #[test]
+    fn test_levels() {
+        let article = article();
+        assert_eq!(
+            article.summarize_verbose(Verbosity::Brief),
+            article.summarize()
+        );
+        assert_eq!(
+            article.summarize_verbose(Verbosity::Normal),
+            "Rust is awesome, by Dev (Global)\n    by Dev"
+        );
+        assert_eq!(
+            article.summarize_verbose(Verbosity::Full),
+            "Rust is awesome, by Dev (Global)\n    by Dev\n    Memory safety without garbage collection."
+        );
+    }
+
+    #[test]
+    fn test_tweet_details() {
+        assert_eq!(
+            tweet(false, false).details(),
+            "an original tweet, 17 characters"
+        );
+        assert_eq!(
+            tweet(true, true).details(),
+            "a retweeted reply, 17 characters"
+        );
+        assert_eq!(
+            tweet(false, true).summarize_verbose(Verbosity::Full),
+            "rustlang: Rust 1.75 is out!\n    by @rustlang\n    a retweet, 17 characters"
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let tweets = [tweet(false, false), tweet(true, false)];
+        assert_eq!(
+            report("Tweets", &tweets, Verbosity::Normal),
+            "== Tweets (2) ==\n\
+             - rustlang: Rust 1.75 is out!\n    by @rustlang\n\
+             - rustlang: Rust 1.75 is out!\n    by @rustlang"
+        );
+        let none: [NewsArticle; 0] = [];
+        assert_eq!(
+            report("Articles", &none, Verbosity::Full),
+            "== Articles (0) =="
+        );
+    }
+}
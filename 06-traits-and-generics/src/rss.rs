@@ -0,0 +1,232 @@
+//! `FeedSource`: a news feed, RSS 2.0 or Atom, read as `NewsArticle`s.
+//!
+//! The two formats say the same things under different names: RSS has
+//! `<item>`, `<pubDate>` and `<description>`, Atom `<entry>`, `<published>`
+//! and `<summary>`. Elements are matched by their local name, so namespaced
+//! ones such as `<dc:creator>` are found without knowing their prefix. Each
+//! entry becomes a `NewsArticle`, and the rest of the program only ever sees
+//! a `Summary`.
+
+use crate::aggregator::{ContentSource, Entry, FetchError};
+use crate::NewsArticle;
+use chrono::DateTime;
+use roxmltree::{Document, Node};
+use std::fs;
+use std::path::PathBuf;
+
+/// Where a feed is read from.
+enum Location {
+    File(PathBuf),
+    #[cfg(feature = "http")]
+    Url(String),
+}
+
+/// A feed, read again on every fetch.
+pub struct FeedSource {
+    name: String,
+    location: Location,
+}
+
+impl FeedSource {
+    pub fn file(name: &str, path: impl Into<PathBuf>) -> Self {
+        FeedSource {
+            name: name.to_string(),
+            location: Location::File(path.into()),
+        }
+    }
+
+    /// A feed downloaded over HTTP(S); only with the `http` feature.
+    #[cfg(feature = "http")]
+    pub fn url(name: &str, url: &str) -> Self {
+        FeedSource {
+            name: name.to_string(),
+            location: Location::Url(url.to_string()),
+        }
+    }
+
+    fn read(&self) -> Result<String, FetchError> {
+        match &self.location {
+            Location::File(path) => fs::read_to_string(path)
+                .map_err(|e| FetchError(format!("cannot read {}: {}", path.display(), e))),
+            #[cfg(feature = "http")]
+            Location::Url(url) => ureq::get(url)
+                .call()
+                .map_err(|e| FetchError(format!("cannot download {}: {}", url, e)))?
+                .into_string()
+                .map_err(|e| FetchError(format!("cannot download {}: {}", url, e))),
+        }
+    }
+}
+
+impl ContentSource for FeedSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self) -> Result<Vec<Entry>, FetchError> {
+        let articles = parse(&self.read()?)?;
+        Ok(articles
+            .into_iter()
+            .map(|(published, article)| Entry {
+                source: self.name.clone(),
+                published,
+                item: Box::new(article),
+            })
+            .collect())
+    }
+}
+
+/// The articles of an RSS 2.0 or Atom feed, each with its publication time.
+///
+/// The feed's title goes in each article's `location`, and an article with no
+/// author of its own gets the feed's, or "anonymous". A missing or unreadable date is 0, so
+/// the article sorts after all the dated ones.
+pub fn parse(xml: &str) -> Result<Vec<(u64, NewsArticle)>, FetchError> {
+    let document = Document::parse(xml).map_err(|e| FetchError(format!("invalid XML: {}", e)))?;
+    let root = document.root_element();
+    // Where the feed's own title and author are, and what its entries are called.
+    let (feed, entry) = match root.tag_name().name() {
+        "rss" => (child(root, "channel").unwrap_or(root), "item"),
+        "feed" => (root, "entry"),
+        other => {
+            return Err(FetchError(format!(
+                "not an RSS or Atom feed: the root element is <{}>",
+                other
+            )))
+        }
+    };
+    let feed_title = text(feed, "title").unwrap_or_default();
+    let feed_author = author(feed).unwrap_or_else(|| String::from("anonymous"));
+
+    Ok(feed
+        .children()
+        .filter(|node| node.tag_name().name() == entry)
+        .map(|node| {
+            let published = ["pubDate", "published", "updated"]
+                .into_iter()
+                .find_map(|name| text(node, name))
+                .and_then(|date| timestamp(&date))
+                .unwrap_or(0);
+            let article = NewsArticle {
+                headline: text(node, "title").unwrap_or_default(),
+                location: feed_title.clone(),
+                author: author(node).unwrap_or_else(|| feed_author.clone()),
+                content: ["description", "summary", "content"]
+                    .into_iter()
+                    .find_map(|name| text(node, name))
+                    .unwrap_or_default(),
+            };
+            (published, article)
+        })
+        .collect())
+}
+
+/// The first child element called `name`, whatever its namespace.
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// The trimmed text of the child called `name`, if there is some.
+fn text(node: Node, name: &str) -> Option<String> {
+    let text = child(node, name)?.text()?.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// RSS: `<author>` (an email address) or `<dc:creator>`; Atom: `<author><name>`.
+fn author(node: Node) -> Option<String> {
+    child(node, "author")
+        .and_then(|author| text(author, "name"))
+        .or_else(|| text(node, "creator"))
+        .or_else(|| text(node, "author"))
+}
+
+/// Seconds since the Unix epoch, from an RSS (RFC 2822) or Atom (RFC 3339) date.
+fn timestamp(date: &str) -> Option<u64> {
+    let date = DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()?;
+    u64::try_from(date.timestamp()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Summary;
+
+    const RUST_BLOG: &str = include_str!("../fixtures/rust-blog.rss");
+    const THIS_WEEK: &str = include_str!("../fixtures/this-week.atom");
+
+    #[test]
+    fn test_rss() {
+        let articles = parse(RUST_BLOG).unwrap();
+        assert_eq!(articles.len(), 3);
+
+        let (published, article) = &articles[1];
+        // Thu, 25 Jul 2024 00:00:00 +0000
+        assert_eq!(*published, 1_721_865_600);
+        assert_eq!(
+            article.summarize(),
+            "Announcing Rust 1.80.0, by The Rust Release Team (Rust Blog)"
+        );
+        // CDATA and escaped markup both come out as the markup itself.
+        assert_eq!(
+            article.content,
+            "<p>LazyCell and LazyLock are now stable.</p>"
+        );
+        assert!(articles[0].1.content.starts_with("<p>The Rust team"));
+
+        let (published, article) = &articles[2];
+        assert_eq!(*published, 0);
+        assert_eq!(article.author, "anonymous");
+    }
+
+    #[test]
+    fn test_atom() {
+        let articles = parse(THIS_WEEK).unwrap();
+        assert_eq!(articles.len(), 2);
+
+        let (published, article) = &articles[0];
+        // 2024-07-31T04:00:00-04:00 is 08:00 UTC; <published> wins over <updated>.
+        assert_eq!(*published, 1_722_412_800);
+        assert_eq!(
+            article.summarize(),
+            "This Week in Rust 558, by TWiR Contributors (This Week in Rust)"
+        );
+        assert_eq!(
+            article.content,
+            "Hello and welcome to another issue of This Week in Rust!"
+        );
+
+        // Its own author rather than the feed's, and the date from <updated>.
+        let (published, article) = &articles[1];
+        assert_eq!(*published, 1_721_808_000);
+        assert_eq!(article.author, "Nell Shamrell-Harrington");
+        assert_eq!(article.content, "<p>Updates from Rust Community</p>");
+    }
+
+    #[test]
+    fn test_not_a_feed() {
+        assert_eq!(
+            parse("<html><body/></html>").unwrap_err().to_string(),
+            "not an RSS or Atom feed: the root element is <html>"
+        );
+        assert!(parse("<rss><channel>")
+            .unwrap_err()
+            .to_string()
+            .starts_with("invalid XML: "));
+    }
+
+    #[test]
+    fn test_feed_source_from_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/this-week.atom");
+        let source = FeedSource::file("twir", path);
+        let entries = source.fetch().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.source == "twir"));
+
+        let missing = FeedSource::file("gone", "fixtures/missing.xml");
+        let error = missing.fetch().err().unwrap();
+        assert!(error.0.starts_with("cannot read fixtures/missing.xml: "));
+    }
+}
@@ -0,0 +1,71 @@
+//! The sources of the demo: content kept in memory, as a real source would
+//! hold what it last downloaded.
+
+use crate::aggregator::{ContentSource, Entry, FetchError};
+use crate::{NewsArticle, Tweet};
+
+/// The articles of one newspaper, with their publication times.
+pub struct NewsDesk {
+    name: String,
+    articles: Vec<(u64, NewsArticle)>,
+}
+
+impl NewsDesk {
+    pub fn new(name: &str, articles: Vec<(u64, NewsArticle)>) -> Self {
+        NewsDesk {
+            name: name.to_string(),
+            articles,
+        }
+    }
+}
+
+impl ContentSource for NewsDesk {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self) -> Result<Vec<Entry>, FetchError> {
+        Ok(self
+            .articles
+            .iter()
+            .map(|(published, article)| Entry {
+                source: self.name.clone(),
+                published: *published,
+                item: Box::new(article.clone()),
+            })
+            .collect())
+    }
+}
+
+/// The tweets of one account, named `@username`.
+pub struct Timeline {
+    name: String,
+    tweets: Vec<(u64, Tweet)>,
+}
+
+impl Timeline {
+    pub fn new(username: &str, tweets: Vec<(u64, Tweet)>) -> Self {
+        Timeline {
+            name: format!("@{}", username),
+            tweets,
+        }
+    }
+}
+
+impl ContentSource for Timeline {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self) -> Result<Vec<Entry>, FetchError> {
+        Ok(self
+            .tweets
+            .iter()
+            .map(|(published, tweet)| Entry {
+                source: self.name.clone(),
+                published: *published,
+                item: Box::new(tweet.clone()),
+            })
+            .collect())
+    }
+}
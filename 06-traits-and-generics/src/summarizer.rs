@@ -0,0 +1,254 @@
+//! Summarizer strategies: generics over behavior rather than data.
+//!
+//! `Summary` is implemented by the data: each type says how it summarizes.
+//! A `Summarizer` is the other way around: one way of shortening text that
+//! works on every `Summary`, whatever its type. `summarize_with` is generic
+//! over both, so any strategy applies to any content, and a new strategy or a
+//! new content type needs no change to the other.
+//!
+//! The trait is object safe (no generic methods, no `Self` in return types),
+//! so a strategy can also be picked at runtime, by name, as a
+//! `Box<dyn Summarizer>`.
+
+use crate::Summary;
+use std::collections::HashMap;
+
+pub trait Summarizer {
+    /// Shortens `text`, the summary of an item.
+    fn shorten(&self, text: &str) -> String;
+}
+
+/// `item`'s summary, shortened by `summarizer`. `?Sized` on both: a
+/// `dyn Summarizer` or a `dyn Summary` will do as well as a concrete type.
+pub fn summarize_with<S, T>(summarizer: &S, item: &T) -> String
+where
+    S: Summarizer + ?Sized,
+    T: Summary + ?Sized,
+{
+    summarizer.shorten(&item.summarize())
+}
+
+/// The first `max_chars` characters, cut at a word if there is one, with `…`.
+pub struct TruncateSummarizer {
+    pub max_chars: usize,
+}
+
+impl Summarizer for TruncateSummarizer {
+    fn shorten(&self, text: &str) -> String {
+        if text.chars().count() <= self.max_chars {
+            return text.to_string();
+        }
+        // Room for the `…`. Counted in characters, not bytes: slicing a &str
+        // in the middle of a character (`é` is two bytes) panics.
+        let keep = self.max_chars.saturating_sub(1);
+        let end = text.char_indices().nth(keep).map_or(text.len(), |(i, _)| i);
+        let cut = &text[..end];
+        let cut = match cut.rfind(' ') {
+            Some(space) if space > 0 => &cut[..space],
+            _ => cut,
+        };
+        format!("{}…", cut.trim_end_matches([' ', ',', ';', ':']))
+    }
+}
+
+/// Up to the end of the first sentence: a `.`, `!` or `?` followed by a space
+/// or the end of the text. `Rust 1.80.0 is out.` is one sentence.
+pub struct FirstSentenceSummarizer;
+
+impl Summarizer for FirstSentenceSummarizer {
+    fn shorten(&self, text: &str) -> String {
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let ends_sentence = matches!(c, '.' | '!' | '?')
+                && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+            if ends_sentence {
+                return text[..i + c.len_utf8()].to_string();
+            }
+        }
+        text.to_string()
+    }
+}
+
+/// The `count` most frequent words of four letters or more, most frequent
+/// first; between words as frequent, the one that appears first.
+pub struct KeywordSummarizer {
+    pub count: usize,
+}
+
+/// Long enough to pass the length test, too common to say anything.
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "again", "also", "been", "before", "from", "have", "into", "just", "more",
+    "once", "only", "over", "same", "some", "than", "that", "their", "them", "then", "there",
+    "they", "this", "very", "were", "what", "when", "which", "will", "with", "your",
+];
+
+impl Summarizer for KeywordSummarizer {
+    fn shorten(&self, text: &str) -> String {
+        // word -> (times seen, position of the first time)
+        let mut seen: HashMap<String, (usize, usize)> = HashMap::new();
+        let words = text
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|word| word.chars().count() >= 4 && !STOP_WORDS.contains(&word.as_str()));
+        for (position, word) in words.enumerate() {
+            seen.entry(word).or_insert((0, position)).0 += 1;
+        }
+        let mut keywords: Vec<(String, (usize, usize))> = seen.into_iter().collect();
+        keywords.sort_by(|(_, (a_count, a_first)), (_, (b_count, b_first))| {
+            b_count.cmp(a_count).then(a_first.cmp(b_first))
+        });
+        keywords
+            .into_iter()
+            .take(self.count)
+            .map(|(word, _)| word)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The names `by_name` knows, for help texts and errors.
+pub const NAMES: &str = "truncate[:N], first-sentence or keywords[:N]";
+
+/// A strategy from its name, as typed by a user: `truncate:40`, `keywords`...
+pub fn by_name(spec: &str) -> Result<Box<dyn Summarizer>, String> {
+    let (name, argument) = match spec.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (spec, None),
+    };
+    let number = |default: usize| -> Result<usize, String> {
+        match argument {
+            None => Ok(default),
+            Some(argument) => match argument.parse() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(format!("'{}' is not a positive number", argument)),
+            },
+        }
+    };
+    match name {
+        "truncate" => Ok(Box::new(TruncateSummarizer {
+            max_chars: number(40)?,
+        })),
+        "first-sentence" if argument.is_none() => Ok(Box::new(FirstSentenceSummarizer)),
+        "keywords" => Ok(Box::new(KeywordSummarizer { count: number(3)? })),
+        _ => Err(format!("unknown summarizer '{}': use {}", spec, NAMES)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NewsArticle, Tweet};
+
+    fn tweet(content: &str) -> Tweet {
+        Tweet {
+            username: String::from("rustlang"),
+            content: content.to_string(),
+            reply: false,
+            retweet: false,
+        }
+    }
+
+    #[test]
+    fn test_truncate() {
+        let truncate = TruncateSummarizer { max_chars: 20 };
+        assert_eq!(truncate.shorten("short enough"), "short enough");
+        assert_eq!(
+            truncate.shorten("exactly twenty chars"),
+            "exactly twenty chars"
+        );
+        // At a word: not "Rust 1.80 is out, wi…".
+        assert_eq!(
+            truncate.shorten("Rust 1.80 is out, with LazyCell"),
+            "Rust 1.80 is out…"
+        );
+        // One long word is cut where it must be.
+        assert_eq!(
+            TruncateSummarizer { max_chars: 5 }.shorten("Supercalifragilistic"),
+            "Supe…"
+        );
+        // Characters, not bytes.
+        assert_eq!(
+            TruncateSummarizer { max_chars: 4 }.shorten("éééééé"),
+            "ééé…"
+        );
+        assert_eq!(TruncateSummarizer { max_chars: 0 }.shorten("abc"), "…");
+    }
+
+    #[test]
+    fn test_first_sentence() {
+        let first = FirstSentenceSummarizer;
+        assert_eq!(
+            first.shorten("Rust 1.80.0 is out. It brings LazyCell."),
+            "Rust 1.80.0 is out."
+        );
+        assert_eq!(first.shorten("Wow! Really?"), "Wow!");
+        assert_eq!(first.shorten("No end at all"), "No end at all");
+        assert_eq!(first.shorten("Ends here."), "Ends here.");
+        assert_eq!(first.shorten(""), "");
+    }
+
+    #[test]
+    fn test_keywords() {
+        let keywords = KeywordSummarizer { count: 3 };
+        assert_eq!(
+            keywords.shorten("Rust is fast. Rust is safe. Safe code with Rust, fast builds."),
+            "rust, fast, safe"
+        );
+        // Short words and stop words are left out.
+        assert_eq!(keywords.shorten("it is what it is"), "");
+        assert_eq!(
+            KeywordSummarizer { count: 1 }.shorten("Penguins, penguins everywhere"),
+            "penguins"
+        );
+    }
+
+    #[test]
+    fn test_any_strategy_on_any_content() {
+        let article = NewsArticle {
+            headline: String::from("Penguins win the Stanley Cup. Again."),
+            location: String::from("Pittsburgh"),
+            author: String::from("Iceburgh"),
+            content: String::new(),
+        };
+        let strategies: Vec<Box<dyn Summarizer>> = ["truncate:20", "first-sentence", "keywords:2"]
+            .into_iter()
+            .map(|name| by_name(name).unwrap())
+            .collect();
+        let shortened: Vec<String> = strategies
+            .iter()
+            .map(|strategy| summarize_with(strategy.as_ref(), &article))
+            .collect();
+        assert_eq!(
+            shortened,
+            [
+                "Penguins win the…",
+                "Penguins win the Stanley Cup.",
+                "penguins, stanley"
+            ]
+        );
+        // A concrete strategy, a trait object of content.
+        let item: &dyn Summary = &tweet("Hello. World.");
+        assert_eq!(
+            summarize_with(&FirstSentenceSummarizer, item),
+            "rustlang: Hello."
+        );
+    }
+
+    #[test]
+    fn test_by_name_errors() {
+        for (spec, message) in [
+            (
+                "bogus",
+                "unknown summarizer 'bogus': use truncate[:N], first-sentence or keywords[:N]",
+            ),
+            ("truncate:ten", "'ten' is not a positive number"),
+            ("keywords:0", "'0' is not a positive number"),
+            (
+                "first-sentence:2",
+                "unknown summarizer 'first-sentence:2': use truncate[:N], first-sentence or keywords[:N]",
+            ),
+        ] {
+            assert_eq!(by_name(spec).err().unwrap(), message, "{}", spec);
+        }
+    }
+}
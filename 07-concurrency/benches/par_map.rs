@@ -0,0 +1,48 @@
+//! `par_map` against a plain sequential `map`, counting the primes below
+//! 200 000 in 32 ranges.
+//!
+//! Run with `cargo bench --bench par_map`. On N cores, expect up to N times
+//! faster with N workers, a little less for the threads to start and the
+//! channels to carry items; more workers than cores only add switching.
+
+use concurrency::parallel::par_map;
+use concurrency::workload::{self, count_primes};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::convert::Infallible;
+use std::hint::black_box;
+
+const LIMIT: u64 = 200_000;
+const CHUNKS: u64 = 32;
+
+fn par_map_against_sequential(c: &mut Criterion) {
+    let chunks = workload::chunks(LIMIT, CHUNKS);
+    let mut group = c.benchmark_group("count primes");
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            black_box(&chunks)
+                .iter()
+                .map(|&(from, to)| count_primes(from, to))
+                .collect::<Vec<_>>()
+        })
+    });
+    for workers in [1, 2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("par_map", workers),
+            &workers,
+            |b, &workers| {
+                b.iter(|| {
+                    par_map(
+                        black_box(&chunks),
+                        |&(from, to)| Ok::<_, Infallible>(count_primes(from, to)),
+                        workers,
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, par_map_against_sequential);
+criterion_main!(benches);
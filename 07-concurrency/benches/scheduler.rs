@@ -0,0 +1,47 @@
+//! The work-stealing scheduler against `ThreadPool`, counting the primes
+//! below 400 000 in pieces of `grain` numbers.
+//!
+//! Run with `cargo bench --bench scheduler`. The pool cannot split work from
+//! inside a job, so the pieces are cut up front and each is submitted; the
+//! scheduler splits the range recursively, as it runs. Both start their
+//! threads inside the measured loop. The finer the grain, the more jobs go
+//! through the pool's single locked queue, and the more the scheduler's
+//! per-worker queues pay off; with coarse pieces, the two are about even.
+
+use concurrency::pool::ThreadPool;
+use concurrency::steal;
+use concurrency::workload::{self, count_primes};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+const LIMIT: u64 = 400_000;
+const WORKERS: usize = 4;
+
+fn scheduler_against_pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("count primes");
+    for grain in [500, 5_000, 50_000] {
+        group.bench_with_input(
+            BenchmarkId::new("work stealing", grain),
+            &grain,
+            |b, &grain| b.iter(|| steal::count_primes(WORKERS, 0, black_box(LIMIT), grain).0),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("thread pool", grain),
+            &grain,
+            |b, &grain| {
+                b.iter(|| {
+                    let pool = ThreadPool::new(WORKERS);
+                    let handles: Vec<_> = workload::chunks(black_box(LIMIT), LIMIT / grain)
+                        .into_iter()
+                        .map(|(from, to)| pool.submit(move || count_primes(from, to)))
+                        .collect();
+                    handles.into_iter().map(|h| h.join().unwrap()).sum::<u64>()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scheduler_against_pool);
+criterion_main!(benches);
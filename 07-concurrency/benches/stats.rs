@@ -0,0 +1,59 @@
+//! `Stats` (atomics) against `MutexStats` (one lock), recording 100 000
+//! values split between 1, 2, 4 and 8 threads.
+//!
+//! Run with `cargo bench --bench stats`. On one thread the lock is never
+//! contended and costs about as much as the atomics. With more threads, every
+//! thread waiting for the lock is stalled, while the atomics only retry the
+//! few compare-and-swaps that lose a race; on a single core there is no
+//! contention to speak of, and the two stay close.
+
+use concurrency::stats::{MutexStats, Recorder, Stats};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+use std::thread;
+
+const VALUES: u64 = 100_000;
+
+fn record(stats: &impl Recorder, threads: u64) {
+    thread::scope(|scope| {
+        for t in 0..threads {
+            scope.spawn(move || {
+                for value in (t..VALUES).step_by(threads as usize) {
+                    stats.record(black_box(value));
+                }
+            });
+        }
+    });
+}
+
+fn atomics_against_mutex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("record");
+    for threads in [1, 2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("atomics", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let stats = Stats::new();
+                    record(&stats, threads);
+                    stats.snapshot()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("mutex", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let stats = MutexStats::new();
+                    record(&stats, threads);
+                    stats.snapshot()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, atomics_against_mutex);
+criterion_main!(benches);
@@ -0,0 +1,152 @@
+//! Backpressure: a bounded channel slowing a fast producer down to the pace
+//! of its consumer.
+//!
+//! `mpsc::channel` has no limit: a producer faster than its consumer fills
+//! memory with messages nobody has read yet. `mpsc::sync_channel(capacity)`
+//! holds at most `capacity` of them, and `send` blocks on a full channel until
+//! the consumer takes one. The producer then runs at the consumer's pace, and
+//! the time it spends blocked is the measure of how much faster it would go.
+//! With a capacity of 0, every `send` waits for a `recv`: a rendezvous.
+
+use std::fmt;
+use std::sync::mpsc::{self, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Messages the channel holds before `send` blocks.
+    pub capacity: usize,
+    /// Messages the producer sends, then stops.
+    pub items: usize,
+    /// Time to produce one message, before sending it.
+    pub produce_every: Duration,
+    /// Time to handle one message, after receiving it.
+    pub consume_every: Duration,
+}
+
+impl Default for Config {
+    /// A producer four times as fast as its consumer.
+    fn default() -> Self {
+        Config {
+            capacity: 4,
+            items: 20,
+            produce_every: Duration::from_millis(5),
+            consume_every: Duration::from_millis(20),
+        }
+    }
+}
+
+/// What happened on the producer's side of the channel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    pub sent: usize,
+    pub received: usize,
+    /// Sends that found the channel full and had to wait.
+    pub blocked_sends: usize,
+    /// The time spent waiting in those sends, in total and the longest.
+    pub blocked_time: Duration,
+    pub longest_block: Duration,
+    pub elapsed: Duration,
+}
+
+/// Runs a producer and a consumer over a `sync_channel` of `config.capacity`.
+pub fn run(config: &Config) -> Metrics {
+    let (sender, receiver) = mpsc::sync_channel::<usize>(config.capacity);
+    let start = Instant::now();
+    let consume_every = config.consume_every;
+    let consumer = thread::spawn(move || {
+        let mut received = 0;
+        for _item in receiver {
+            thread::sleep(consume_every);
+            received += 1;
+        }
+        received
+    });
+
+    let mut metrics = Metrics::default();
+    for item in 0..config.items {
+        thread::sleep(config.produce_every);
+        // `try_send` first, only to tell a send that blocks from one that
+        // does not; then the blocking `send`, timed.
+        match sender.try_send(item) {
+            Ok(()) => {}
+            Err(TrySendError::Full(item)) => {
+                let blocked = Instant::now();
+                sender.send(item).expect("the consumer stopped early");
+                let blocked = blocked.elapsed();
+                metrics.blocked_sends += 1;
+                metrics.blocked_time += blocked;
+                metrics.longest_block = metrics.longest_block.max(blocked);
+            }
+            Err(TrySendError::Disconnected(_)) => panic!("the consumer stopped early"),
+        }
+        metrics.sent += 1;
+    }
+    // Ends the consumer's loop once it has read what is left.
+    drop(sender);
+    metrics.received = consumer.join().unwrap();
+    metrics.elapsed = start.elapsed();
+    metrics
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "sent {}, received {} in {:?}",
+            self.sent, self.received, self.elapsed
+        )?;
+        write!(
+            f,
+            "{} send(s) blocked, for {:?} in total, {:?} at most",
+            self.blocked_sends, self.blocked_time, self.longest_block
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(capacity: usize, produce_ms: u64, consume_ms: u64) -> Config {
+        Config {
+            capacity,
+            items: 10,
+            produce_every: Duration::from_millis(produce_ms),
+            consume_every: Duration::from_millis(consume_ms),
+        }
+    }
+
+    #[test]
+    fn test_room_for_everything_never_blocks() {
+        // The consumer is slow, but all ten fit in the channel.
+        let metrics = run(&config(10, 0, 5));
+        assert_eq!((metrics.sent, metrics.received), (10, 10));
+        assert_eq!(metrics.blocked_sends, 0);
+        assert_eq!(metrics.blocked_time, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_slow_consumer_blocks_the_producer() {
+        let metrics = run(&config(1, 0, 10));
+        assert_eq!(metrics.received, 10);
+        // The consumer holds one, the channel one: the third send waits, and
+        // every one after it, for about a message's handling each.
+        assert!(metrics.blocked_sends >= 7, "{:?}", metrics);
+        assert!(
+            metrics.blocked_time >= Duration::from_millis(50),
+            "{:?}",
+            metrics
+        );
+        // Backpressure: the whole run takes as long as the consumer needs.
+        assert!(metrics.elapsed >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_rendezvous() {
+        let metrics = run(&config(0, 0, 2));
+        assert_eq!(metrics.received, 10);
+        assert!(metrics.blocked_sends > 0);
+    }
+}
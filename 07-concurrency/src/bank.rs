@@ -0,0 +1,250 @@
+//! Shared state: a bank whose accounts many threads move money between.
+//!
+//! Each account is an `Arc<Mutex<u64>>`, so two transfers between different
+//! accounts run at the same time. The list of accounts is behind an `RwLock`:
+//! opening an account writes to it, and everything else only reads, so
+//! transfers do not wait for each other there.
+//!
+//! A transfer holds two locks at once, and that is how deadlocks happen. If
+//! one thread locks A then B while another locks B then A, each can end up
+//! holding the lock the other waits for, and both wait forever. The fix is
+//! to always take locks in the same order, here by account number: whoever
+//! gets the first lock is sure to get the second. `deadlock_demo` shows both.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc;
+use std::sync::{Arc, Barrier, Mutex, MutexGuard, RwLock};
+use std::thread;
+use std::time::Duration;
+
+pub type AccountId = usize;
+
+#[derive(Default)]
+pub struct Bank {
+    accounts: RwLock<Vec<Arc<Mutex<u64>>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BankError {
+    UnknownAccount(AccountId),
+    SameAccount(AccountId),
+    InsufficientFunds {
+        account: AccountId,
+        balance: u64,
+        amount: u64,
+    },
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BankError::UnknownAccount(id) => write!(f, "no account #{}", id),
+            BankError::SameAccount(id) => write!(f, "cannot transfer from #{} to itself", id),
+            BankError::InsufficientFunds {
+                account,
+                balance,
+                amount,
+            } => write!(
+                f,
+                "account #{} has {}, cannot transfer {}",
+                account, balance, amount
+            ),
+        }
+    }
+}
+
+impl Error for BankError {}
+
+/// The order a transfer takes its two locks in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockOrder {
+    /// The lower account number first, whichever way the money goes.
+    ByAccount,
+    /// The account the money comes from first: deadlocks.
+    FromThenTo,
+}
+
+impl Bank {
+    pub fn new() -> Self {
+        Bank::default()
+    }
+
+    /// Opens an account with `balance` and returns its number.
+    pub fn open(&self, balance: u64) -> AccountId {
+        let mut accounts = self.accounts.write().unwrap();
+        accounts.push(Arc::new(Mutex::new(balance)));
+        accounts.len() - 1
+    }
+
+    pub fn balance(&self, id: AccountId) -> Result<u64, BankError> {
+        Ok(*self.account(id)?.lock().unwrap())
+    }
+
+    /// The money in all accounts, at one instant: every account is locked, in
+    /// order, before any is read, so no transfer is seen half done.
+    pub fn total(&self) -> u64 {
+        let accounts = self.accounts.read().unwrap();
+        let guards: Vec<MutexGuard<u64>> = accounts.iter().map(|a| a.lock().unwrap()).collect();
+        guards.iter().map(|balance| **balance).sum()
+    }
+
+    /// Moves `amount` from one account to another, or nothing at all.
+    pub fn transfer(&self, from: AccountId, to: AccountId, amount: u64) -> Result<(), BankError> {
+        self.transfer_with(from, to, amount, LockOrder::ByAccount, || {})
+    }
+
+    /// Looks the account up; the list's lock is released on return, so only
+    /// the account's own lock is held from then on.
+    fn account(&self, id: AccountId) -> Result<Arc<Mutex<u64>>, BankError> {
+        let accounts = self.accounts.read().unwrap();
+        accounts
+            .get(id)
+            .cloned()
+            .ok_or(BankError::UnknownAccount(id))
+    }
+
+    /// `transfer`, taking the locks in `order` and calling `between_locks`
+    /// with the first one held.
+    fn transfer_with(
+        &self,
+        from: AccountId,
+        to: AccountId,
+        amount: u64,
+        order: LockOrder,
+        between_locks: impl FnOnce(),
+    ) -> Result<(), BankError> {
+        if from == to {
+            return Err(BankError::SameAccount(from));
+        }
+        let (from_account, to_account) = (self.account(from)?, self.account(to)?);
+        let first_is_from = match order {
+            LockOrder::ByAccount => from < to,
+            LockOrder::FromThenTo => true,
+        };
+        let (first, second) = if first_is_from {
+            (&from_account, &to_account)
+        } else {
+            (&to_account, &from_account)
+        };
+        let mut first = first.lock().unwrap();
+        between_locks();
+        let mut second = second.lock().unwrap();
+        let (from_balance, to_balance) = if first_is_from {
+            (&mut *first, &mut *second)
+        } else {
+            (&mut *second, &mut *first)
+        };
+
+        if *from_balance < amount {
+            return Err(BankError::InsufficientFunds {
+                account: from,
+                balance: *from_balance,
+                amount,
+            });
+        }
+        *from_balance -= amount;
+        *to_balance += amount;
+        Ok(())
+    }
+}
+
+/// Two transfers the opposite ways between the same two accounts, at the
+/// same time, each holding its first lock long enough for the other to take
+/// its own. Locking `FromThenTo`, they deadlock: after `timeout`, this gives
+/// up and returns `false`, leaving both threads stuck for good. `ByAccount`,
+/// they both finish and this returns `true`.
+pub fn deadlock_demo(order: LockOrder, timeout: Duration) -> bool {
+    let bank = Arc::new(Bank::new());
+    let (a, b) = (bank.open(100), bank.open(100));
+    let start = Arc::new(Barrier::new(2));
+    let (done_sender, done_receiver) = mpsc::channel();
+    for (from, to) in [(a, b), (b, a)] {
+        let (bank, start, done) = (Arc::clone(&bank), Arc::clone(&start), done_sender.clone());
+        // Not joined: a deadlocked thread never returns.
+        thread::spawn(move || {
+            start.wait();
+            let hold = || thread::sleep(Duration::from_millis(50));
+            bank.transfer_with(from, to, 10, order, hold).unwrap();
+            let _ = done.send(());
+        });
+    }
+    (0..2).all(|_| done_receiver.recv_timeout(timeout).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer() {
+        let bank = Bank::new();
+        let (a, b) = (bank.open(100), bank.open(0));
+        bank.transfer(a, b, 30).unwrap();
+        // The higher number first: locked the other way round, same result.
+        bank.transfer(b, a, 10).unwrap();
+        assert_eq!((bank.balance(a), bank.balance(b)), (Ok(80), Ok(20)));
+
+        assert_eq!(
+            bank.transfer(b, a, 21),
+            Err(BankError::InsufficientFunds {
+                account: b,
+                balance: 20,
+                amount: 21
+            })
+        );
+        assert_eq!(bank.transfer(a, 7, 1), Err(BankError::UnknownAccount(7)));
+        assert_eq!(bank.transfer(a, a, 1), Err(BankError::SameAccount(a)));
+        // Nothing moved by a failed transfer.
+        assert_eq!(bank.total(), 100);
+    }
+
+    #[test]
+    fn test_1000_concurrent_transfers_conserve_money() {
+        const ACCOUNTS: usize = 10;
+        const THREADS: usize = 8;
+        const TRANSFERS: usize = 1000;
+        let bank = Bank::new();
+        for _ in 0..ACCOUNTS {
+            bank.open(1_000);
+        }
+        let total = bank.total();
+
+        thread::scope(|scope| {
+            for t in 0..THREADS {
+                let bank = &bank;
+                scope.spawn(move || {
+                    // A small linear congruential generator: pseudo-random
+                    // pairs and amounts, different for each thread.
+                    let mut seed = t as u64 + 1;
+                    let mut next = |n: u64| {
+                        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                        (seed >> 33) % n
+                    };
+                    for _ in 0..TRANSFERS / THREADS {
+                        let from = next(ACCOUNTS as u64) as usize;
+                        let to = (from + 1 + next(ACCOUNTS as u64 - 1) as usize) % ACCOUNTS;
+                        // Some fail for lack of funds, which is fine.
+                        let _ = bank.transfer(from, to, next(300));
+                    }
+                });
+            }
+            // Meanwhile: never a total with money in flight.
+            for _ in 0..50 {
+                assert_eq!(bank.total(), total);
+            }
+        });
+
+        assert_eq!(bank.total(), total);
+    }
+
+    #[test]
+    fn test_deadlock_and_its_fix() {
+        assert!(deadlock_demo(LockOrder::ByAccount, Duration::from_secs(5)));
+        // Leaves two threads deadlocked until the tests end.
+        assert!(!deadlock_demo(
+            LockOrder::FromThenTo,
+            Duration::from_millis(500)
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+//! Stopping work early: a `CancellationToken`, and `run_with_timeout`.
+//!
+//! A thread cannot be killed from outside: it would die holding locks, with
+//! data half written. It has to stop itself, so it is asked to: the token is a
+//! flag the work checks between steps, and returns when it is set. Checking is
+//! an atomic load, cheap enough for every iteration of a loop.
+//!
+//! Work that waits rather than computes sleeps on the token instead
+//! (`wait_timeout`): a `Condvar` wakes it as soon as the token is cancelled,
+//! not at the end of its sleep.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A flag shared by its clones: cancelling one cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Read by `is_cancelled`, without a lock.
+    cancelled: AtomicBool,
+    /// The same flag again, for the `Condvar`, which waits on a `Mutex`.
+    waiting: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Asks the work to stop, and wakes whoever waits on the token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        *self.inner.waiting.lock().unwrap() = true;
+        self.inner.wake.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Sleeps for `timeout`, or until the token is cancelled; true if it is.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let cancelled = self.inner.waiting.lock().unwrap();
+        let (cancelled, _) = self
+            .inner
+            .wake
+            .wait_timeout_while(cancelled, timeout, |cancelled| !*cancelled)
+            .unwrap();
+        *cancelled
+    }
+}
+
+/// What `run_with_timeout` got back.
+#[derive(Debug, PartialEq)]
+pub struct Outcome<R> {
+    /// The result of every worker, in order, finished or cut short.
+    pub results: Vec<R>,
+    /// Whether the deadline passed, and the workers were asked to stop.
+    pub cancelled: bool,
+}
+
+/// Runs `work(i, token)` on `workers` threads, and cancels the token once
+/// `timeout` has passed if they are not all done. Returns when every worker
+/// has returned: the deadline only asks them to, so work that never checks
+/// its token is waited for all the same.
+pub fn run_with_timeout<F, R>(workers: usize, timeout: Duration, work: F) -> Outcome<R>
+where
+    F: Fn(usize, &CancellationToken) -> R + Sync,
+    R: Send,
+{
+    let token = CancellationToken::new();
+    let deadline = Instant::now() + timeout;
+    let (done_sender, done_receiver) = mpsc::channel::<()>();
+    let (work, token_ref) = (&work, &token);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|i| {
+                let done = done_sender.clone();
+                scope.spawn(move || {
+                    let result = work(i, token_ref);
+                    let _ = done.send(());
+                    result
+                })
+            })
+            .collect();
+        drop(done_sender);
+
+        // One message per worker done; a worker that panics drops its
+        // sender without sending, which ends the wait too, once all are gone.
+        let mut finished = 0;
+        while finished < workers {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match done_receiver.recv_timeout(left) {
+                Ok(()) => finished += 1,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    token.cancel();
+                    break;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        Outcome {
+            results: handles.into_iter().map(|h| h.join().unwrap()).collect(),
+            cancelled: token.is_cancelled(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        // Not cancelled: sleeps the whole time.
+        assert!(!clone.wait_timeout(Duration::from_millis(10)));
+        token.cancel();
+        assert!(clone.is_cancelled());
+        // Already cancelled: does not sleep at all.
+        let start = Instant::now();
+        assert!(clone.wait_timeout(Duration::from_secs(10)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_cancel_wakes_a_waiting_thread() {
+        let token = CancellationToken::new();
+        let waiter = {
+            let token = token.clone();
+            thread::spawn(move || {
+                let start = Instant::now();
+                assert!(token.wait_timeout(Duration::from_secs(10)));
+                start.elapsed()
+            })
+        };
+        thread::sleep(Duration::from_millis(20));
+        token.cancel();
+        assert!(waiter.join().unwrap() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_endless_workers_stop_at_the_deadline() {
+        let start = Instant::now();
+        let outcome = run_with_timeout(4, Duration::from_millis(100), |_, token| {
+            // Would run forever without the token.
+            let mut steps = 0u64;
+            while !token.is_cancelled() {
+                steps += 1;
+                thread::sleep(Duration::from_millis(1));
+            }
+            steps
+        });
+        let elapsed = start.elapsed();
+        assert!(outcome.cancelled);
+        assert_eq!(outcome.results.len(), 4);
+        assert!(outcome.results.iter().all(|&steps| steps > 0));
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_secs(5), "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn test_work_done_in_time_is_not_cancelled() {
+        let start = Instant::now();
+        let outcome = run_with_timeout(3, Duration::from_secs(10), |i, token| {
+            // Sleeps on the token, as a worker waiting for something would.
+            token.wait_timeout(Duration::from_millis(10 * i as u64));
+            i * 2
+        });
+        assert_eq!(
+            outcome,
+            Outcome {
+                results: vec![0, 2, 4],
+                cancelled: false
+            }
+        );
+        // Returned when the work was done, not at the deadline.
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,113 @@
+//! The command line: which demos to tune, and how.
+
+use concurrency::backpressure;
+use std::time::Duration;
+
+pub const USAGE: &str = "usage: concurrency [OPTIONS]
+
+Options:
+  --workers N       threads in the pool (default: one per core)
+  --capacity N      messages the bounded channel holds (default: 4)
+  --produce-ms MS   time to produce a message (default: 5)
+  --consume-ms MS   time to consume a message (default: 20)
+  --deadlock        lock accounts in the order that deadlocks, and watch it
+  -h, --help        show this help";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Options),
+    Help,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    /// `None`: one per core.
+    pub workers: Option<usize>,
+    pub backpressure: backpressure::Config,
+    /// Run the bank's deadlock demo without its fix.
+    pub deadlock: bool,
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--deadlock" => {
+                options.deadlock = true;
+                continue;
+            }
+            _ => {}
+        }
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--workers" => options.workers = Some(positive(&arg, &value()?)?),
+            "--capacity" => options.backpressure.capacity = number(&arg, &value()?)?,
+            "--produce-ms" => {
+                options.backpressure.produce_every = millis(&arg, &value()?)?;
+            }
+            "--consume-ms" => {
+                options.backpressure.consume_every = millis(&arg, &value()?)?;
+            }
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+    Ok(Command::Run(options))
+}
+
+fn number(flag: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{}: '{}' is not a number", flag, value))
+}
+
+fn positive(flag: &str, value: &str) -> Result<usize, String> {
+    match number(flag, value)? {
+        0 => Err(format!("{} must be at least 1", flag)),
+        n => Ok(n),
+    }
+}
+
+fn millis(flag: &str, value: &str) -> Result<Duration, String> {
+    Ok(Duration::from_millis(number(flag, value)? as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(line: &str) -> Result<Command, String> {
+        parse(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_line(""), Ok(Command::Run(Options::default())));
+        let Ok(Command::Run(options)) =
+            parse_line("--workers 3 --capacity 0 --produce-ms 1 --consume-ms 50")
+        else {
+            panic!("valid options");
+        };
+        assert_eq!(options.workers, Some(3));
+        assert_eq!(options.backpressure.capacity, 0);
+        assert_eq!(options.backpressure.produce_every, Duration::from_millis(1));
+        assert_eq!(
+            options.backpressure.consume_every,
+            Duration::from_millis(50)
+        );
+        assert_eq!(parse_line("--capacity 2 -h"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (line, message) in [
+            ("--workers 0", "--workers must be at least 1"),
+            ("--workers", "--workers needs a value"),
+            ("--consume-ms soon", "--consume-ms: 'soon' is not a number"),
+            ("--bogus", "unknown argument '--bogus'"),
+        ] {
+            assert_eq!(parse_line(line), Err(message.to_string()), "{}", line);
+        }
+    }
+}
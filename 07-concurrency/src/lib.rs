@@ -0,0 +1,14 @@
+//! The reusable parts of the concurrency examples; `main.rs` runs them, and
+//! `benches/` measures them.
+
+pub mod backpressure;
+pub mod bank;
+pub mod cancel;
+pub mod parallel;
+pub mod pipeline;
+pub mod pool;
+pub mod progress;
+pub mod scoped;
+pub mod stats;
+pub mod steal;
+pub mod workload;
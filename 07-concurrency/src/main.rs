@@ -1,56 +1,288 @@
-use std::sync::mpsc;
+mod cli;
+
+use cli::{Command, USAGE};
+use concurrency::backpressure;
+use concurrency::bank::{self, Bank, LockOrder};
+use concurrency::cancel;
+use concurrency::parallel::par_map;
+use concurrency::pipeline;
+use concurrency::pool::ThreadPool;
+use concurrency::progress::{ProgressReporter, Style};
+use concurrency::scoped;
+use concurrency::stats::{Recorder, Stats};
+use concurrency::steal;
+use concurrency::workload::{self, count_primes};
+use std::convert::Infallible;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::process;
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Primes are counted below this, in `CHUNKS` ranges of equal size.
+const LIMIT: u64 = 2_000_000;
+const CHUNKS: u64 = 16;
 
 fn main() {
-    // --- 1. Simple Thread Spawning ---
-    // thread::spawn takes a closure and runs it in a separate operating system thread.
-    let handle = thread::spawn(|| {
-        for i in 1..5 {
-            println!("hi number {} from the spawned thread!", i);
-            thread::sleep(Duration::from_millis(1));
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return;
         }
-    });
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    // --- 1. A Sample Workload, One Thread ---
+    let start = Instant::now();
+    let expected = count_primes(0, LIMIT);
+    println!(
+        "{} primes below {}, on one thread: {:?}",
+        expected,
+        LIMIT,
+        start.elapsed()
+    );
+
+    // --- 2. The Same Workload on a Thread Pool ---
+    // The range is cut in chunks, and each chunk is a job. `submit` returns a
+    // handle at once; `join` waits for that job's result.
+    let pool = match options.workers {
+        Some(workers) => ThreadPool::new(workers),
+        None => ThreadPool::with_available_parallelism(),
+    };
+    let start = Instant::now();
+    let handles: Vec<_> = workload::chunks(LIMIT, CHUNKS)
+        .into_iter()
+        .map(|(from, to)| pool.submit(move || count_primes(from, to)))
+        .collect();
+    let total: u64 = handles.into_iter().map(|h| h.join().unwrap()).sum();
+    println!(
+        "{} primes below {}, in {} jobs on {} worker(s): {:?}",
+        total,
+        LIMIT,
+        CHUNKS,
+        pool.size(),
+        start.elapsed()
+    );
+    assert_eq!(total, expected);
+
+    // --- 3. Parallel Map ---
+    // No pool to keep: `par_map` starts its threads, maps, and joins them.
+    // The results come back in the order of the chunks.
+    let start = Instant::now();
+    let counts = par_map(
+        workload::chunks(LIMIT, CHUNKS),
+        |(from, to)| Ok::<_, Infallible>(count_primes(from, to)),
+        pool.size(),
+    )
+    .unwrap();
+    println!(
+        "{} primes below {}, with par_map: {:?}",
+        counts.iter().sum::<u64>(),
+        LIMIT,
+        start.elapsed()
+    );
+    println!("per chunk: {:?}", counts);
+
+    // The first error in input order, whichever thread saw it first.
+    let parsed = par_map(
+        ["12", "7", "seven", "3", "three"],
+        |s| s.parse::<u64>().map_err(|e| format!("'{}': {}", s, e)),
+        pool.size(),
+    );
+    println!("parsing in parallel: {:?}", parsed);
+
+    // --- 4. Work Stealing ---
+    // The same count, split recursively while it runs: each job halves its
+    // range and spawns one half, and idle workers steal what others spawned.
+    let start = Instant::now();
+    let (total, stats) = steal::count_primes(pool.size(), 0, LIMIT, 20_000);
+    println!(
+        "{} primes below {}, by work stealing: {:?}",
+        total,
+        LIMIT,
+        start.elapsed()
+    );
+    println!(
+        "jobs run per worker: {:?}, {} stolen",
+        stats.executed, stats.stolen
+    );
+
+    // --- 5. A Panicking Job ---
+    // The panic is caught in the worker: the job fails, the pool goes on.
+    let failed = pool.submit(|| -> u64 { panic!("this job always fails") });
+    match failed.join() {
+        Ok(_) => unreachable!(),
+        Err(e) => println!("{}", e),
+    }
+    let after = pool.submit(|| count_primes(0, 100)).join().unwrap();
+    println!("and the next job still runs: {} primes below 100", after);
+    println!("{} job(s) panicked", pool.panicked());
+
+    // --- 6. Shared State ---
+    // Jobs on the pool move money between accounts; each account has its own
+    // lock, so the money in the bank is the same whatever the interleaving.
+    let bank = Arc::new(Bank::new());
+    let accounts: Vec<_> = (0..5).map(|_| bank.open(1_000)).collect();
+    let handles: Vec<_> = (0..1_000)
+        .map(|i| {
+            let bank = Arc::clone(&bank);
+            // Never the same account twice: `to` is 1 to 4 places after `from`.
+            let (from, to) = (accounts[i % 5], accounts[(i + 1 + i % 4) % 5]);
+            pool.submit(move || bank.transfer(from, to, (i % 7) as u64 * 10))
+        })
+        .collect();
+    let failed = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .filter(Result::is_err)
+        .count();
+    let balances: Vec<u64> = accounts.iter().map(|&a| bank.balance(a).unwrap()).collect();
+    println!(
+        "\n1000 transfers, {} refused; balances {:?}, {} in total",
+        failed,
+        balances,
+        bank.total()
+    );
+
+    // Runs what is left in the queue and joins every worker.
+    pool.shutdown();
 
-    // The main thread continues to run concurrently with the spawned thread.
-    for i in 1..5 {
-        println!("hi number {} from the main thread!", i);
-        thread::sleep(Duration::from_millis(1));
+    // Two transfers locking the same two accounts in opposite orders.
+    let order = if options.deadlock {
+        LockOrder::FromThenTo
+    } else {
+        LockOrder::ByAccount
+    };
+    if bank::deadlock_demo(order, Duration::from_secs(2)) {
+        println!("locking {:?}: both transfers went through", order);
+    } else {
+        println!(
+            "locking {:?}: deadlocked, each transfer waits for the other's lock",
+            order
+        );
     }
 
-    // handle.join() blocks the current thread until the spawned thread finishes.
-    handle.join().unwrap();
-
-    // --- 2. Message Passing using Channels ---
-    // channel() returns a transmitter (tx) and a receiver (rx).
-    let (tx, rx) = mpsc::channel();
-
-    // Spawn another thread to send messages back to the main thread.
-    thread::spawn(move || {
-        let vals = vec![
-            String::from("hi"),
-            String::from("from"),
-            String::from("the"),
-            String::from("thread"),
-        ];
-
-        for val in vals {
-            // .send() transfers ownership of the value to the receiver.
-            tx.send(val).unwrap();
-            thread::sleep(Duration::from_millis(200));
+    // --- 7. Scoped Threads ---
+    // The threads borrow chunks of `numbers`, owned right here: no `Arc`, no
+    // copy, and no `'static`, since the scope joins them before returning.
+    let numbers: Vec<u64> = (0..10_000_000).collect();
+    let workers = options.workers.unwrap_or(4);
+    println!(
+        "\nsum of 0..{}: {} on {} scoped threads",
+        numbers.len(),
+        scoped::par_sum(&numbers, workers),
+        workers
+    );
+    let target = 7_654_321;
+    println!(
+        "{} found at index {:?}",
+        target,
+        scoped::par_position(&numbers, workers, |&n| n == target)
+    );
+
+    // --- 8. Lock-Free Statistics ---
+    // The gaps between consecutive primes, recorded from every thread into
+    // one `Stats` with no lock. (Gaps across two chunks are left out.)
+    let gaps = Stats::new();
+    let ranges = workload::chunks(1_000_000, workers as u64);
+    scoped::map_chunks(&ranges, workers, |ranges| {
+        for &(from, to) in ranges {
+            let mut primes = (from..to).filter(|&n| workload::is_prime(n));
+            let mut previous = primes.next();
+            for prime in primes {
+                gaps.record(prime - previous.unwrap());
+                previous = Some(prime);
+            }
+        }
+    });
+    let gaps = gaps.snapshot();
+    println!(
+        "{} gaps between primes below 1000000: {} to {}, {:.2} on average",
+        gaps.count,
+        gaps.min.unwrap(),
+        gaps.max.unwrap(),
+        gaps.mean().unwrap()
+    );
+
+    // --- 9. Cancellation ---
+    // Each worker counts primes from its own starting point, with no end: it
+    // stops when the token says so, after 200 ms, with what it has found.
+    let outcome = cancel::run_with_timeout(4, Duration::from_millis(200), |i, token| {
+        let mut n = i as u64 * 1_000_000_000;
+        let mut found = 0;
+        while !token.is_cancelled() {
+            found += count_primes(n, n + 1_000);
+            n += 1_000;
         }
+        found
     });
+    println!(
+        "\nprimes found in 200 ms from 0, 1e9, 2e9 and 3e9: {:?} (cancelled: {})",
+        outcome.results, outcome.cancelled
+    );
 
-    println!("\nReceived:");
-    // The receiver (rx) can be treated as an iterator that waits for messages.
-    for received in rx {
-        println!("Got: {}", received);
+    // --- 10. Progress ---
+    // Four workers of different sizes report to one reporter, which draws
+    // bars on a terminal, and prints lines otherwise.
+    let sizes = [400_000, 300_000, 200_000, 100_000];
+    let reporter = ProgressReporter::new(sizes.to_vec());
+    let workers: Vec<_> = (0..sizes.len())
+        .map(|i| {
+            let mut progress = reporter.handle(i);
+            let from = i as u64 * 1_000_000;
+            thread::spawn(move || {
+                for start in (from..from + sizes[i]).step_by(10_000) {
+                    count_primes(start, start + 10_000);
+                    progress.advance(10_000);
+                }
+            })
+        })
+        .collect();
+    let style = if io::stdout().is_terminal() {
+        Style::Bars
+    } else {
+        Style::Text
+    };
+    println!();
+    reporter.run(style, &mut io::stdout()).unwrap();
+    for worker in workers {
+        worker.join().unwrap();
     }
+
+    // --- 11. Backpressure ---
+    // A bounded channel: when it is full, the producer waits for the consumer.
+    // Try `--capacity 100`, or `--consume-ms 1`, and the waiting goes away.
+    let config = &options.backpressure;
+    println!(
+        "\nproducing every {:?}, consuming every {:?}, through a channel of {}:",
+        config.produce_every, config.consume_every, config.capacity
+    );
+    println!("{}", backpressure::run(config));
+
+    // --- 12. A Pipeline ---
+    // Parse, transform and aggregate an access log, each stage on a pool of
+    // its own, all three running at once; the end of the log shuts them down
+    // one after the other.
+    let log = (0..100_000u64).map(|i| match i % 97 {
+        0 => String::from("not a log line"),
+        n => format!(
+            "GET /{} {} {}",
+            ["", "about", "blog", "contact"][(i % 4) as usize],
+            if n % 13 == 0 { 500 } else { 200 },
+            i % 50
+        ),
+    });
+    println!("\n{}", pipeline::run(log, &pipeline::Config::default()));
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use std::thread;
 
     #[test]
     fn test_thread_spawn() {
@@ -0,0 +1,158 @@
+//! `par_map`: `items.into_iter().map(f).collect::<Result<Vec<_>, _>>()`, on
+//! several threads.
+//!
+//! The items go into a channel, numbered; each worker takes the next one, maps
+//! it, and sends the result back on a second channel with the item's number.
+//! Results arrive in whatever order the workers finish, and the number puts
+//! each back in its place.
+//!
+//! The workers are scoped threads (`thread::scope`): they are joined before
+//! `par_map` returns, so `f` can borrow from the caller's stack, with no
+//! `Arc` and no `'static` bound.
+//!
+//! On an error, the workers stop taking items, and the error returned is that
+//! of the first failing item in input order, as sequentially: items are taken
+//! in order, so every item before a failing one has already been taken and
+//! runs to the end.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+
+/// Maps every item with `f` on `workers` threads; the results are in the
+/// order of `items`, or the error is that of the first item that failed.
+///
+/// # Panics
+///
+/// If `workers` is 0, or if `f` panics.
+pub fn par_map<I, T, R, E, F>(items: I, f: F, workers: usize) -> Result<Vec<R>, E>
+where
+    I: IntoIterator<Item = T>,
+    T: Send,
+    R: Send,
+    E: Send,
+    F: Fn(T) -> Result<R, E> + Sync,
+{
+    assert!(workers > 0, "par_map needs at least one worker");
+    let (item_sender, item_receiver) = mpsc::channel();
+    let mut count = 0;
+    for item in items.into_iter() {
+        // The receiver is right here: sending cannot fail.
+        item_sender.send((count, item)).unwrap();
+        count += 1;
+    }
+    // The workers stop once the queue is empty, not waiting for more.
+    drop(item_sender);
+    let item_receiver = Mutex::new(item_receiver);
+    let failed = AtomicBool::new(false);
+
+    let (results, first_error) = thread::scope(|scope| {
+        let (result_sender, result_receiver) = mpsc::channel();
+        for _ in 0..workers.min(count) {
+            let result_sender = result_sender.clone();
+            let (item_receiver, failed, f) = (&item_receiver, &failed, &f);
+            scope.spawn(move || {
+                while !failed.load(Ordering::Relaxed) {
+                    let item = item_receiver.lock().unwrap().recv();
+                    let Ok((index, item)) = item else {
+                        return;
+                    };
+                    let result = f(item);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    // The receiver lives until every sender is gone.
+                    result_sender.send((index, result)).unwrap();
+                }
+            });
+        }
+        // Only the workers' senders are left: the loop below ends with them.
+        drop(result_sender);
+
+        let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+        let mut first_error: Option<(usize, E)> = None;
+        for (index, result) in result_receiver {
+            match result {
+                Ok(value) => results[index] = Some(value),
+                Err(e) if first_error.as_ref().is_none_or(|(first, _)| index < *first) => {
+                    first_error = Some((index, e))
+                }
+                Err(_) => {}
+            }
+        }
+        (results, first_error)
+        // A worker that panicked panics `scope` here, before any result is
+        // looked at.
+    });
+
+    match first_error {
+        Some((_, e)) => Err(e),
+        None => Ok(results
+            .into_iter()
+            .map(|result| result.expect("without an error, every item has a result"))
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    #[test]
+    fn test_results_in_input_order() {
+        // The first items take longest, so they finish last.
+        let squares = par_map(
+            (0..20u64).collect::<Vec<_>>(),
+            |n| {
+                thread::sleep(Duration::from_millis(20 - n));
+                Ok::<_, Infallible>(n * n)
+            },
+            4,
+        )
+        .unwrap();
+        assert_eq!(squares, (0..20u64).map(|n| n * n).collect::<Vec<_>>());
+
+        let empty = par_map(Vec::<u64>::new(), Ok::<_, Infallible>, 4).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_borrows_from_the_caller() {
+        let words = ["zero", "one", "two", "three"];
+        let lengths = par_map(0..4, |i| Ok::<_, Infallible>(words[i].len()), 2).unwrap();
+        assert_eq!(lengths, [4, 3, 3, 5]);
+    }
+
+    #[test]
+    fn test_first_error_in_input_order() {
+        let inputs = ["1", "2", "x", "4", "y", "6"];
+        let mapped = AtomicUsize::new(0);
+        for workers in [1, 3, 8] {
+            let error = par_map(
+                inputs,
+                |s| {
+                    mapped.fetch_add(1, Ordering::SeqCst);
+                    s.parse::<u32>().map_err(|_| format!("not a number: {}", s))
+                },
+                workers,
+            )
+            .unwrap_err();
+            assert_eq!(error, "not a number: x", "{} workers", workers);
+        }
+        // On one worker, nothing after "x" is mapped.
+        mapped.store(0, Ordering::SeqCst);
+        let _ = par_map(
+            inputs,
+            |s| {
+                mapped.fetch_add(1, Ordering::SeqCst);
+                s.parse::<u32>()
+            },
+            1,
+        );
+        assert_eq!(mapped.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,371 @@
+//! A pipeline of three stages connected by channels: parse, transform,
+//! aggregate, over the lines of an access log.
+//!
+//! Each stage has a `ThreadPool` of its own, whose workers take items from
+//! the stage's input channel and send results to the next one. The stages run
+//! at the same time, on different items: while one line is parsed, the one
+//! before is transformed and the one before that counted. The channels are
+//! bounded (see `backpressure`), so a fast stage waits for a slow one rather
+//! than filling memory.
+//!
+//! Shutting down needs no signal of its own. When the source runs out, `run`
+//! drops its sender; once the parse workers have emptied their channel, their
+//! `recv` fails and they return, dropping their senders; that closes the
+//! transform stage's input, and so on down to the aggregate.
+
+use crate::pool::ThreadPool;
+use crate::stats::{Recorder, Snapshot, Stats};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Items each channel holds before its sender waits.
+const CAPACITY: usize = 1_024;
+
+/// A line of the log: `GET /index.html 200 12`, the last number in ms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub millis: u64,
+}
+
+/// The requests to one path that succeeded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PathTotals {
+    pub requests: u64,
+    pub millis: u64,
+}
+
+/// Workers per stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    pub parse: usize,
+    pub transform: usize,
+    pub aggregate: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            parse: 2,
+            transform: 2,
+            aggregate: 1,
+        }
+    }
+}
+
+/// How a stage did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageMetrics {
+    pub name: &'static str,
+    pub workers: usize,
+    /// The time taken by each item, in nanoseconds; `count` is the items.
+    pub items: Snapshot,
+    /// Items taken in and not passed on: malformed lines, failed requests.
+    pub rejected: u64,
+    /// Workers that panicked, and stopped early.
+    pub panicked: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub totals: BTreeMap<String, PathTotals>,
+    pub stages: Vec<StageMetrics>,
+}
+
+/// A stage's pool, and what its workers measure.
+struct Stage {
+    name: &'static str,
+    pool: ThreadPool,
+    timings: Arc<Stats>,
+    rejected: Arc<AtomicU64>,
+}
+
+impl Stage {
+    /// Starts `workers` workers, each running `work` with the stage's input
+    /// and its two counters until `work` returns.
+    fn start<I, W>(name: &'static str, workers: usize, input: Receiver<I>, work: W) -> Stage
+    where
+        I: Send + 'static,
+        W: Fn(&Mutex<Receiver<I>>, &Stats, &AtomicU64) + Send + Sync + 'static,
+    {
+        let stage = Stage {
+            name,
+            pool: ThreadPool::new(workers),
+            timings: Arc::new(Stats::new()),
+            rejected: Arc::new(AtomicU64::new(0)),
+        };
+        // The workers take turns on the input, as the pool's own do on its queue.
+        let input = Arc::new(Mutex::new(input));
+        let work = Arc::new(work);
+        for _ in 0..workers {
+            let (input, work) = (Arc::clone(&input), Arc::clone(&work));
+            let (timings, rejected) = (Arc::clone(&stage.timings), Arc::clone(&stage.rejected));
+            stage
+                .pool
+                .execute(move || work(&input, &timings, &rejected));
+        }
+        stage
+    }
+
+    /// A stage that maps each item with `f`, passing on the `Some`s.
+    fn map<I, O, F>(
+        name: &'static str,
+        workers: usize,
+        input: Receiver<I>,
+        output: SyncSender<O>,
+        f: F,
+    ) -> Stage
+    where
+        I: Send + 'static,
+        O: Send + 'static,
+        F: Fn(I) -> Option<O> + Send + Sync + 'static,
+    {
+        // `output` moves into the closure, which the workers share: it is
+        // dropped with the last of them, and that closes the next stage's input.
+        Stage::start(name, workers, input, move |input, timings, rejected| {
+            while let Some(item) = next(input) {
+                let start = Instant::now();
+                let result = f(item);
+                timings.record(start.elapsed().as_nanos() as u64);
+                match result {
+                    // Gone downstream: nothing more to do here either.
+                    Some(item) => {
+                        if output.send(item).is_err() {
+                            return;
+                        }
+                    }
+                    None => {
+                        rejected.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        })
+    }
+
+    fn metrics(&self) -> StageMetrics {
+        StageMetrics {
+            name: self.name,
+            workers: self.pool.size(),
+            items: self.timings.snapshot(),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            panicked: self.pool.panicked(),
+        }
+    }
+}
+
+/// The next item of a stage's input, or `None` once upstream is done.
+fn next<I>(input: &Mutex<Receiver<I>>) -> Option<I> {
+    // The guard goes at the end of the statement: not held while working.
+    input.lock().unwrap().recv().ok()
+}
+
+/// Runs every line of `lines` through the three stages; returns once the
+/// last one is counted and every worker has stopped.
+///
+/// # Panics
+///
+/// If a stage has no workers.
+pub fn run(lines: impl IntoIterator<Item = String>, config: &Config) -> Report {
+    let (source, lines_in) = mpsc::sync_channel::<String>(CAPACITY);
+    let (parsed_out, parsed_in) = mpsc::sync_channel::<Request>(CAPACITY);
+    let (timed_out, timed_in) = mpsc::sync_channel::<(String, u64)>(CAPACITY);
+    // One partial total per aggregate worker, sent when it is done.
+    let (totals_out, totals_in) = mpsc::channel::<BTreeMap<String, PathTotals>>();
+
+    let parse = Stage::map("parse", config.parse, lines_in, parsed_out, |line| {
+        parse_line(&line)
+    });
+    let transform = Stage::map(
+        "transform",
+        config.transform,
+        parsed_in,
+        timed_out,
+        |request| (request.status < 400).then_some((request.path, request.millis)),
+    );
+    let aggregate = Stage::start(
+        "aggregate",
+        config.aggregate,
+        timed_in,
+        move |input, timings, _| {
+            let mut totals: BTreeMap<String, PathTotals> = BTreeMap::new();
+            while let Some((path, millis)) = next(input) {
+                let start = Instant::now();
+                let entry = totals.entry(path).or_default();
+                entry.requests += 1;
+                entry.millis += millis;
+                timings.record(start.elapsed().as_nanos() as u64);
+            }
+            let _ = totals_out.send(totals);
+        },
+    );
+
+    for line in lines {
+        if source.send(line).is_err() {
+            // Every parse worker is gone (they panicked): stop feeding them.
+            break;
+        }
+    }
+    // The end of the source: from here, each stage stops after the one before.
+    drop(source);
+
+    let mut totals: BTreeMap<String, PathTotals> = BTreeMap::new();
+    for partial in totals_in {
+        for (path, partial) in partial {
+            let entry = totals.entry(path).or_default();
+            entry.requests += partial.requests;
+            entry.millis += partial.millis;
+        }
+    }
+    // Every worker has returned by now; the pools are dropped after this,
+    // joining their threads.
+    Report {
+        totals,
+        stages: vec![parse.metrics(), transform.metrics(), aggregate.metrics()],
+    }
+}
+
+/// `METHOD PATH STATUS MILLIS`, separated by whitespace; `None` if malformed.
+pub fn parse_line(line: &str) -> Option<Request> {
+    let mut fields = line.split_whitespace();
+    let request = Request {
+        method: fields.next()?.to_string(),
+        path: fields.next()?.to_string(),
+        status: fields.next()?.parse().ok()?,
+        millis: fields.next()?.parse().ok()?,
+    };
+    fields.next().is_none().then_some(request)
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for stage in &self.stages {
+            write!(
+                f,
+                "{:<10} {} worker(s), {:>7} items, {:>5} rejected, {:>6.0} ns/item",
+                stage.name,
+                stage.workers,
+                stage.items.count,
+                stage.rejected,
+                stage.items.mean().unwrap_or(0.0)
+            )?;
+            if stage.panicked > 0 {
+                write!(f, ", {} panicked", stage.panicked)?;
+            }
+            writeln!(f)?;
+        }
+        for (path, totals) in &self.totals {
+            writeln!(
+                f,
+                "{:<16} {:>6} requests, {:>4} ms on average",
+                path,
+                totals.requests,
+                totals.millis / totals.requests
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("GET /index.html 200 12"),
+            Some(Request {
+                method: String::from("GET"),
+                path: String::from("/index.html"),
+                status: 200,
+                millis: 12,
+            })
+        );
+        for malformed in ["", "GET /", "GET / ok 12", "GET / 200 12 extra"] {
+            assert_eq!(parse_line(malformed), None, "{:?}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_three_stages() {
+        let log = lines(
+            "GET / 200 10\n\
+             GET /about 200 30\n\
+             garbage\n\
+             POST /login 500 90\n\
+             GET / 304 20\n\
+             GET /about 404 5",
+        );
+        let report = run(log, &Config::default());
+        assert_eq!(
+            report.totals,
+            BTreeMap::from([
+                (
+                    String::from("/"),
+                    PathTotals {
+                        requests: 2,
+                        millis: 30
+                    }
+                ),
+                (
+                    String::from("/about"),
+                    PathTotals {
+                        requests: 1,
+                        millis: 30
+                    }
+                ),
+            ])
+        );
+        let [parse, transform, aggregate] = &report.stages[..] else {
+            panic!("three stages");
+        };
+        // Each stage sees what the one before passed on.
+        assert_eq!((parse.items.count, parse.rejected), (6, 1));
+        assert_eq!((transform.items.count, transform.rejected), (5, 2));
+        assert_eq!((aggregate.items.count, aggregate.rejected), (3, 0));
+        assert!(report.stages.iter().all(|stage| stage.panicked == 0));
+    }
+
+    #[test]
+    fn test_many_workers_many_lines() {
+        let log: Vec<String> = (0..10_000)
+            .map(|i| {
+                format!(
+                    "GET /page{} {} {}",
+                    i % 4,
+                    if i % 10 == 0 { 500 } else { 200 },
+                    i % 7
+                )
+            })
+            .collect();
+        let config = Config {
+            parse: 3,
+            transform: 4,
+            aggregate: 2,
+        };
+        let report = run(log, &config);
+        // 1 in 10 failed; the others split evenly between four pages.
+        assert_eq!(report.totals.len(), 4);
+        let requests: u64 = report.totals.values().map(|t| t.requests).sum();
+        assert_eq!(requests, 9_000);
+        assert_eq!(report.stages[2].items.count, 9_000);
+        assert_eq!(report.stages[2].workers, 2);
+    }
+
+    #[test]
+    fn test_empty_source_shuts_down() {
+        let report = run(Vec::new(), &Config::default());
+        assert!(report.totals.is_empty());
+        assert!(report.stages.iter().all(|stage| stage.items.count == 0));
+    }
+}
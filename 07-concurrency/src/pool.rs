@@ -0,0 +1,243 @@
+//! `ThreadPool`: a fixed number of worker threads taking jobs from one queue.
+//!
+//! Spawning a thread per task costs a system call and a new stack each time,
+//! and a thousand tasks make a thousand threads fighting over a few cores. A
+//! pool starts its threads once; `execute` only puts a job in the queue, and
+//! whichever worker is free takes it.
+//!
+//! The queue is an `mpsc` channel. Its receiver cannot be shared as it is, so
+//! the workers take turns on it behind an `Arc<Mutex<..>>`; the lock is held
+//! only while taking a job, never while running it. Dropping the sender is
+//! the signal to stop: once the queue is empty, `recv` fails and each worker
+//! returns. A job that panics is caught and counted, and its worker goes on
+//! to the next job.
+
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+pub struct ThreadPool {
+    workers: Vec<JoinHandle<()>>,
+    /// `None` once the pool is shutting down.
+    sender: Option<Sender<Job>>,
+    panicked: Arc<AtomicUsize>,
+}
+
+impl ThreadPool {
+    /// A pool of `size` workers.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is 0: such a pool would queue jobs forever and run none.
+    pub fn new(size: usize) -> ThreadPool {
+        assert!(size > 0, "a thread pool needs at least one worker");
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let panicked = Arc::new(AtomicUsize::new(0));
+        let workers = (0..size)
+            .map(|id| {
+                let receiver = Arc::clone(&receiver);
+                let panicked = Arc::clone(&panicked);
+                thread::Builder::new()
+                    // Shown in panic messages: "thread 'worker-3' panicked at ...".
+                    .name(format!("worker-{}", id))
+                    .spawn(move || work(&receiver, &panicked))
+                    .expect("cannot start a worker thread")
+            })
+            .collect();
+        ThreadPool {
+            workers,
+            sender: Some(sender),
+            panicked,
+        }
+    }
+
+    /// A worker per core, or a single one where the number of cores is unknown.
+    pub fn with_available_parallelism() -> ThreadPool {
+        ThreadPool::new(thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Queues `job` for the next free worker.
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.sender
+            .as_ref()
+            .expect("the sender is only taken on shutdown")
+            .send(Box::new(job))
+            // Workers only stop once the sender is gone, so one is listening.
+            .expect("the workers stopped early");
+    }
+
+    /// Queues `job` and returns a handle to wait for what it returns.
+    pub fn submit<F, R>(&self, job: F) -> JobHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let panicked = Arc::clone(&self.panicked);
+        self.execute(move || {
+            // The panic is caught here rather than in the worker, to hand it
+            // to the handle; it is counted all the same.
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            if result.is_err() {
+                panicked.fetch_add(1, Ordering::Relaxed);
+            }
+            // Nobody waiting: the handle was dropped, which is fine.
+            let _ = sender.send(result.map_err(JobPanicked::from_payload));
+        });
+        JobHandle { receiver }
+    }
+
+    /// How many jobs have panicked so far.
+    pub fn panicked(&self) -> usize {
+        self.panicked.load(Ordering::Relaxed)
+    }
+
+    /// Runs every job already queued, then stops the workers and waits for
+    /// them. Dropping the pool does the same; this only makes it visible.
+    pub fn shutdown(self) {}
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // No more jobs: once the queue is empty, every `recv` fails.
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            // Jobs' panics are caught, so a worker can only have returned.
+            let _ = worker.join();
+        }
+    }
+}
+
+/// The loop of each worker: take a job, run it, again, until the queue closes.
+fn work(receiver: &Mutex<Receiver<Job>>, panicked: &AtomicUsize) {
+    loop {
+        // The guard is dropped at the end of this statement, before the job
+        // runs, so the other workers can take jobs meanwhile.
+        let job = receiver
+            .lock()
+            .expect("the lock is never held while running a job")
+            .recv();
+        let Ok(job) = job else {
+            return;
+        };
+        // `AssertUnwindSafe`: the job is gone after a panic, so nothing it
+        // left half-updated is used again by this worker.
+        if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+            panicked.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// What `submit` returns: the job's result, once it is done.
+pub struct JobHandle<R> {
+    receiver: Receiver<Result<R, JobPanicked>>,
+}
+
+impl<R> JobHandle<R> {
+    /// Waits for the job to finish.
+    pub fn join(self) -> Result<R, JobPanicked> {
+        self.receiver
+            .recv()
+            .expect("a submitted job always sends its result")
+    }
+}
+
+/// A job that panicked, with its panic message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobPanicked(pub String);
+
+impl JobPanicked {
+    /// `panic!("...")` panics with a `&str` or a `String`; anything else has
+    /// no message to show.
+    fn from_payload(payload: Box<dyn Any + Send>) -> JobPanicked {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => String::from("(no message)"),
+            },
+        };
+        JobPanicked(message)
+    }
+}
+
+impl fmt::Display for JobPanicked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the job panicked: {}", self.0)
+    }
+}
+
+impl std::error::Error for JobPanicked {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    #[test]
+    fn test_runs_every_job_before_shutdown() {
+        let pool = ThreadPool::new(4);
+        assert_eq!(pool.size(), 4);
+        let done = Arc::new(AtomicUsize::new(0));
+        for _ in 0..100 {
+            let done = Arc::clone(&done);
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(1));
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        // Returns once the queue is empty and every worker has stopped.
+        pool.shutdown();
+        assert_eq!(done.load(Ordering::SeqCst), 100);
+    }
+
+    #[test]
+    fn test_jobs_run_on_several_workers() {
+        let pool = ThreadPool::new(3);
+        let handles: Vec<_> = (0..30)
+            .map(|_| {
+                pool.submit(|| {
+                    thread::sleep(Duration::from_millis(5));
+                    thread::current().name().unwrap().to_string()
+                })
+            })
+            .collect();
+        let names: HashSet<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(names.len() > 1, "only {:?} ran jobs", names);
+        assert!(names.iter().all(|name| name.starts_with("worker-")));
+    }
+
+    #[test]
+    fn test_a_panicking_job_does_not_kill_its_worker() {
+        // One worker: if the panic took it down, nothing would run after.
+        let pool = ThreadPool::new(1);
+        let crash = pool.submit(|| -> u32 { panic!("boom {}", 42) });
+        pool.execute(|| panic!("static message"));
+        let after = pool.submit(|| 7);
+
+        assert_eq!(crash.join(), Err(JobPanicked(String::from("boom 42"))));
+        assert_eq!(after.join(), Ok(7));
+        assert_eq!(pool.panicked(), 1 + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one worker")]
+    fn test_zero_workers() {
+        ThreadPool::new(0);
+    }
+}
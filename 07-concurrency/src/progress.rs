@@ -0,0 +1,222 @@
+//! Progress from many workers, shown in one place: fan-in over a channel.
+//!
+//! Each worker gets a `Progress` handle holding a clone of one `Sender`, and
+//! reports through it; the thread running `ProgressReporter::run` is the only
+//! one reading, and the only one writing to the terminal, so the lines of
+//! different workers never mix. The channel closes when the last handle is
+//! dropped, which is how the reporter knows every worker is done.
+//!
+//! A handle only sends when its percentage changes: a worker can report
+//! after every item, and the channel still carries at most a hundred
+//! messages per worker.
+
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// How the reporter shows progress.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    /// A bar per worker and one for the total, redrawn in place with ANSI
+    /// escape codes: for a terminal.
+    Bars,
+    /// A line of percentages per update, nothing redrawn: for logs and tests.
+    Text,
+}
+
+struct Update {
+    worker: usize,
+    done: u64,
+}
+
+pub struct ProgressReporter {
+    totals: Vec<u64>,
+    sender: Sender<Update>,
+    receiver: Receiver<Update>,
+}
+
+/// A worker's end: how much of its `total` it has done.
+pub struct Progress {
+    worker: usize,
+    total: u64,
+    done: u64,
+    reported: Option<u64>,
+    sender: Sender<Update>,
+}
+
+impl ProgressReporter {
+    /// A reporter for workers with these amounts of work, one each.
+    pub fn new(totals: Vec<u64>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        ProgressReporter {
+            totals,
+            sender,
+            receiver,
+        }
+    }
+
+    /// The handle for worker number `worker`, to move to its thread.
+    ///
+    /// # Panics
+    ///
+    /// If there is no such worker.
+    pub fn handle(&self, worker: usize) -> Progress {
+        Progress {
+            worker,
+            total: self.totals[worker],
+            done: 0,
+            reported: None,
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Shows the updates as they come, until every handle is dropped, and
+    /// returns what each worker had done by then.
+    pub fn run(self, style: Style, out: &mut impl Write) -> io::Result<Vec<u64>> {
+        // Only the handles' senders are left: the loop ends with the last one.
+        drop(self.sender);
+        let mut done = vec![0; self.totals.len()];
+        let mut first = true;
+        for update in self.receiver {
+            done[update.worker] = update.done;
+            match style {
+                Style::Text => writeln!(out, "{}", render_text(&done, &self.totals))?,
+                Style::Bars => {
+                    if !first {
+                        // Back to the first bar, to draw over the old ones.
+                        write!(out, "\x1b[{}A", self.totals.len() + 1)?;
+                    }
+                    write!(out, "{}", render_bars(&done, &self.totals))?;
+                }
+            }
+            out.flush()?;
+            first = false;
+        }
+        Ok(done)
+    }
+}
+
+impl Progress {
+    /// Adds `amount` to the work done, and reports it if the percentage
+    /// changed. Never goes past the total.
+    pub fn advance(&mut self, amount: u64) {
+        self.done = (self.done + amount).min(self.total);
+        let percent = percent(self.done, self.total);
+        if self.reported != Some(percent) {
+            self.reported = Some(percent);
+            // The reporter stopped listening: nothing to show progress on.
+            let _ = self.sender.send(Update {
+                worker: self.worker,
+                done: self.done,
+            });
+        }
+    }
+}
+
+/// Nothing to do is all done.
+fn percent(done: u64, total: u64) -> u64 {
+    (done * 100).checked_div(total).unwrap_or(100)
+}
+
+/// `#0 25% | #1 100% | total 62%`
+pub fn render_text(done: &[u64], totals: &[u64]) -> String {
+    let workers = done
+        .iter()
+        .zip(totals)
+        .enumerate()
+        .map(|(i, (&done, &total))| format!("#{} {}%", i, percent(done, total)));
+    let total = format!("total {}%", percent(done.iter().sum(), totals.iter().sum()));
+    workers.chain([total]).collect::<Vec<_>>().join(" | ")
+}
+
+const BAR_WIDTH: u64 = 20;
+
+/// A line per worker and one for the total, each cleared to its end
+/// (`\x1b[K`) so that a shorter line leaves nothing of the one below it.
+pub fn render_bars(done: &[u64], totals: &[u64]) -> String {
+    let bar = |label: &str, done: u64, total: u64| {
+        let percent = percent(done, total);
+        let filled = (percent * BAR_WIDTH / 100) as usize;
+        format!(
+            "{:<9}[{}{}] {:>3}%\x1b[K\n",
+            label,
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH as usize - filled),
+            percent
+        )
+    };
+    let mut lines: String = done
+        .iter()
+        .zip(totals)
+        .enumerate()
+        .map(|(i, (&done, &total))| bar(&format!("worker {}", i), done, total))
+        .collect();
+    lines.push_str(&bar("total", done.iter().sum(), totals.iter().sum()));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render_text(&[25, 50], &[100, 50]),
+            "#0 25% | #1 100% | total 50%"
+        );
+        assert_eq!(render_text(&[0], &[0]), "#0 100% | total 100%");
+        assert_eq!(
+            render_bars(&[5, 0], &[10, 10]),
+            "worker 0 [##########----------]  50%\x1b[K\n\
+             worker 1 [--------------------]   0%\x1b[K\n\
+             total    [#####---------------]  25%\x1b[K\n"
+        );
+    }
+
+    #[test]
+    fn test_handle_sends_only_on_a_new_percentage() {
+        let reporter = ProgressReporter::new(vec![1_000]);
+        let mut progress = reporter.handle(0);
+        for _ in 0..1_000 {
+            progress.advance(1);
+        }
+        // Past the total: clamped, and 100% was already sent.
+        progress.advance(5);
+        drop(progress);
+        let mut out = Vec::new();
+        assert_eq!(reporter.run(Style::Text, &mut out).unwrap(), [1_000]);
+        let out = String::from_utf8(out).unwrap();
+        // 0% to 100%: one line each.
+        assert_eq!(out.lines().count(), 101);
+        assert_eq!(out.lines().last(), Some("#0 100% | total 100%"));
+    }
+
+    #[test]
+    fn test_fan_in_from_threads() {
+        let totals = vec![300, 200, 100];
+        let reporter = ProgressReporter::new(totals.clone());
+        let workers: Vec<_> = (0..totals.len())
+            .map(|i| {
+                let mut progress = reporter.handle(i);
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        progress.advance(3);
+                    }
+                })
+            })
+            .collect();
+        let mut out = Vec::new();
+        // Returns once every worker has dropped its handle.
+        let done = reporter.run(Style::Text, &mut out).unwrap();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(done, totals);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out.lines().last(),
+            Some("#0 100% | #1 100% | #2 100% | total 100%")
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Scoped threads: splitting a slice the caller owns between threads.
+//!
+//! `thread::spawn` needs `'static` closures, because the thread may outlive
+//! the function that started it; sharing data with it means an `Arc`, and
+//! often a copy. `thread::scope` joins every thread spawned in it before it
+//! returns, so the threads can borrow from the caller's stack: each one gets
+//! a `&[T]` chunk of the caller's slice, and returns its result through its
+//! join handle.
+
+use std::iter::Sum;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// `f` of each chunk, on a thread per chunk, in the order of the chunks.
+/// `data` is cut in at most `workers` chunks of the same size, the last one
+/// possibly shorter.
+///
+/// # Panics
+///
+/// If `workers` is 0, or if `f` panics.
+pub fn map_chunks<T, R, F>(data: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&[T]) -> R + Sync,
+{
+    assert!(workers > 0, "at least one worker is needed");
+    if data.is_empty() {
+        return Vec::new();
+    }
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = data
+            .chunks(data.len().div_ceil(workers))
+            .map(|chunk| scope.spawn(move || f(chunk)))
+            .collect();
+        // Joined in order, so results come in the order of the chunks;
+        // `unwrap` passes a panic on.
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+/// The sum of `data`, each worker summing a chunk.
+pub fn par_sum<T>(data: &[T], workers: usize) -> T
+where
+    T: Sum + for<'a> Sum<&'a T> + Send + Sync,
+{
+    map_chunks(data, workers, |chunk| chunk.iter().sum::<T>())
+        .into_iter()
+        .sum()
+}
+
+/// The index of the first item for which `predicate` is true, as
+/// `iter().position()` would find it.
+///
+/// A match in one chunk does not make the other chunks useless: an earlier
+/// chunk may still have one. But every chunk after the earliest match found
+/// so far can stop, and each worker checks that before every item.
+pub fn par_position<T, P>(data: &[T], workers: usize, predicate: P) -> Option<usize>
+where
+    T: Sync,
+    P: Fn(&T) -> bool + Sync,
+{
+    // `usize::MAX`: nothing found yet.
+    let earliest = AtomicUsize::new(usize::MAX);
+    let size = data.len().div_ceil(workers.max(1)).max(1);
+    let chunks: Vec<(usize, &[T])> = data
+        .chunks(size)
+        .enumerate()
+        .map(|(i, chunk)| (i * size, chunk))
+        .collect();
+    map_chunks(&chunks, workers, |chunks| {
+        // One chunk per worker: `map_chunks` gets as many as workers.
+        let &[(start, chunk)] = chunks else {
+            unreachable!("one chunk per worker")
+        };
+        for (offset, item) in chunk.iter().enumerate() {
+            let index = start + offset;
+            if earliest.load(Ordering::Relaxed) < index {
+                return None;
+            }
+            if predicate(item) {
+                earliest.fetch_min(index, Ordering::Relaxed);
+                return Some(index);
+            }
+        }
+        None
+    })
+    .into_iter()
+    .flatten()
+    .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_chunks() {
+        let data: Vec<u32> = (1..=10).collect();
+        assert_eq!(
+            map_chunks(&data, 3, |chunk| chunk.to_vec()),
+            [vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10]]
+        );
+        // More workers than items: one item each.
+        assert_eq!(map_chunks(&data[..2], 8, |chunk| chunk.len()), [1, 1]);
+        assert!(map_chunks(&[] as &[u32], 4, |chunk| chunk.len()).is_empty());
+    }
+
+    #[test]
+    fn test_par_sum() {
+        let data: Vec<u64> = (1..=100_000).collect();
+        let expected: u64 = data.iter().sum();
+        for workers in [1, 2, 3, 7, 16] {
+            assert_eq!(par_sum(&data, workers), expected, "{} workers", workers);
+        }
+        assert_eq!(par_sum::<u64>(&[], 4), 0);
+        assert_eq!(par_sum(&[1.5, 2.5], 2), 4.0);
+    }
+
+    #[test]
+    fn test_par_position() {
+        let mut data = vec![0u32; 10_000];
+        // In several chunks: the first one in the data wins.
+        data[2_600] = 1;
+        data[7_000] = 1;
+        data[9_999] = 1;
+        for workers in [1, 2, 4, 5, 16] {
+            assert_eq!(
+                par_position(&data, workers, |&x| x == 1),
+                Some(2_600),
+                "{} workers",
+                workers
+            );
+        }
+        assert_eq!(par_position(&data, 4, |&x| x == 2), None);
+        assert_eq!(par_position(&[] as &[u32], 4, |_| true), None);
+        assert_eq!(par_position(&[5, 6, 7], 8, |&x| x > 5), Some(1));
+    }
+}
@@ -0,0 +1,227 @@
+//! Statistics updated from many threads with atomics, and no lock.
+//!
+//! A `Mutex` makes each update wait for the one before it, even when the
+//! updates touch different things. `Stats` keeps each figure in an
+//! `AtomicU64` of its own: a count or a sum is one `fetch_add`. A minimum is
+//! a read, a comparison and a write, which another thread may slip between;
+//! `compare_exchange` writes only if the value is still the one that was
+//! read, and the loop tries again when it is not. (`fetch_min` does the same
+//! loop, or uses an instruction that does it; `update_min` writes it out.)
+//!
+//! Each figure is exact, but they are not updated together: a snapshot taken
+//! while values are being recorded may count a value whose sum is not in yet.
+//! Once the writers are done, it is exact. `MutexStats` is the locked version
+//! that `benches/stats.rs` compares it with.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// The figures at one point.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Snapshot {
+    pub count: u64,
+    pub sum: u64,
+    /// `None` until a value is recorded.
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl Snapshot {
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+
+    fn record(&mut self, value: u64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+}
+
+/// What both versions do, so that tests and benchmarks take either.
+pub trait Recorder: Sync {
+    fn record(&self, value: u64);
+    fn snapshot(&self) -> Snapshot;
+}
+
+pub struct Stats {
+    count: AtomicU64,
+    sum: AtomicU64,
+    /// `u64::MAX` and 0 while empty: any value replaces them.
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Stats {
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats::new()
+    }
+}
+
+/// Sets `target` to `value` if it is smaller, however many threads try at once.
+fn update_min(target: &AtomicU64, value: u64) {
+    let mut current = target.load(Ordering::Relaxed);
+    while value < current {
+        // `_weak` may fail even when the value matches, which only costs a
+        // turn of the loop, and is cheaper on some processors.
+        match target.compare_exchange_weak(current, value, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return,
+            // Changed meanwhile: compare with what it is now.
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+fn update_max(target: &AtomicU64, value: u64) {
+    let mut current = target.load(Ordering::Relaxed);
+    while value > current {
+        match target.compare_exchange_weak(current, value, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+// `Relaxed` everywhere: each figure is on its own, and no other memory is
+// published through them, so no ordering between them is needed.
+impl Recorder for Stats {
+    fn record(&self, value: u64) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        update_min(&self.min, value);
+        update_max(&self.max, value);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let count = self.count.load(Ordering::Relaxed);
+        let (min, max) = if count == 0 {
+            (None, None)
+        } else {
+            (
+                Some(self.min.load(Ordering::Relaxed)),
+                Some(self.max.load(Ordering::Relaxed)),
+            )
+        };
+        Snapshot {
+            count,
+            sum: self.sum.load(Ordering::Relaxed),
+            min,
+            max,
+        }
+    }
+}
+
+/// The same figures behind one lock.
+#[derive(Default)]
+pub struct MutexStats {
+    inner: Mutex<Snapshot>,
+}
+
+impl MutexStats {
+    pub fn new() -> Self {
+        MutexStats::default()
+    }
+}
+
+impl Recorder for MutexStats {
+    fn record(&self, value: u64) {
+        self.inner.lock().unwrap().record(value);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        *self.inner.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const THREADS: u64 = 8;
+    const VALUES: u64 = 10_000;
+
+    /// Each thread records its own values, `t * VALUES + 1` up to
+    /// `(t + 1) * VALUES`: together, every number from 1 to THREADS * VALUES.
+    fn record_concurrently(stats: &impl Recorder) {
+        thread::scope(|scope| {
+            for t in 0..THREADS {
+                scope.spawn(move || {
+                    for value in t * VALUES + 1..=(t + 1) * VALUES {
+                        stats.record(value);
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_exact_under_contention() {
+        let n = THREADS * VALUES;
+        let expected = Snapshot {
+            count: n,
+            sum: n * (n + 1) / 2,
+            min: Some(1),
+            max: Some(n),
+        };
+        let atomic = Stats::new();
+        record_concurrently(&atomic);
+        assert_eq!(atomic.snapshot(), expected);
+
+        let locked = MutexStats::new();
+        record_concurrently(&locked);
+        assert_eq!(locked.snapshot(), expected);
+    }
+
+    #[test]
+    fn test_min_max_races() {
+        // Every thread records the extremes in the opposite order to the
+        // previous one, so the CAS loops keep losing races to each other.
+        let stats = Stats::new();
+        thread::scope(|scope| {
+            for t in 0..THREADS {
+                let stats = &stats;
+                scope.spawn(move || {
+                    for i in 0..VALUES {
+                        let value = if (i + t) % 2 == 0 {
+                            500 + i
+                        } else {
+                            500 - i % 500
+                        };
+                        stats.record(value);
+                    }
+                });
+            }
+        });
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.min, Some(1));
+        assert_eq!(snapshot.max, Some(500 + VALUES - 1));
+    }
+
+    #[test]
+    fn test_empty_and_mean() {
+        assert_eq!(Stats::new().snapshot(), Snapshot::default());
+        assert_eq!(Stats::new().snapshot().mean(), None);
+        let stats = Stats::new();
+        for value in [2, 4, 9] {
+            stats.record(value);
+        }
+        assert_eq!(stats.snapshot().mean(), Some(5.0));
+        assert_eq!(stats.snapshot().min, Some(2));
+        // 0 is a value like any other, not "empty".
+        stats.record(0);
+        assert_eq!(stats.snapshot().min, Some(0));
+    }
+}
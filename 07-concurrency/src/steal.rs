@@ -0,0 +1,222 @@
+//! A work-stealing scheduler, for work that splits itself up as it runs.
+//!
+//! `ThreadPool` has one queue for all its workers, behind one lock: fine for
+//! a few big jobs, a bottleneck for many small ones, and a job cannot queue
+//! more jobs. Here each worker has a queue of its own (a `crossbeam_deque`
+//! `Worker`), and a job can `spawn` more jobs onto its worker's queue, with
+//! no lock. A worker with nothing left to do takes jobs from the global
+//! queue (the `Injector`, where the first job goes), then steals from the
+//! other workers' queues.
+//!
+//! A worker takes its own jobs newest first (LIFO): the last half split off
+//! is the one whose data is still in cache. Thieves take the oldest, which in
+//! divide and conquer are the biggest pieces left, so one theft moves a lot
+//! of work and thefts stay rare.
+
+use crate::workload;
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Job = Box<dyn FnOnce(&Context) + Send>;
+
+/// What a job gets: the way to queue more jobs on its worker.
+pub struct Context<'a> {
+    local: &'a Worker<Job>,
+    pending: &'a AtomicUsize,
+}
+
+impl Context<'_> {
+    /// Queues `job` on this worker; an idle worker may steal it.
+    pub fn spawn<F>(&self, job: F)
+    where
+        F: FnOnce(&Context) + Send + 'static,
+    {
+        // Counted before it is queued, so the count cannot reach 0 while
+        // the job is still to run.
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        self.local.push(Box::new(job));
+    }
+}
+
+/// Who did the work: jobs run by each worker, and jobs taken from another.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub executed: Vec<usize>,
+    pub stolen: usize,
+}
+
+/// Runs `root`, and every job spawned from it, on `workers` threads; returns
+/// once they are all done.
+///
+/// # Panics
+///
+/// If `workers` is 0, or if a job panicked: once the other jobs are done,
+/// the first panic is passed on.
+pub fn run<F>(workers: usize, root: F) -> Stats
+where
+    F: FnOnce(&Context) + Send + 'static,
+{
+    assert!(workers > 0, "at least one worker is needed");
+    let injector: Injector<Job> = Injector::new();
+    injector.push(Box::new(root));
+    // Jobs queued or running; the workers stop when it is 0.
+    let pending = AtomicUsize::new(1);
+    let stolen = AtomicUsize::new(0);
+    let panicked: Mutex<Option<Box<dyn Any + Send>>> = Mutex::new(None);
+    let locals: Vec<Worker<Job>> = (0..workers).map(|_| Worker::new_lifo()).collect();
+    let stealers: Vec<Stealer<Job>> = locals.iter().map(Worker::stealer).collect();
+
+    let executed = thread::scope(|scope| {
+        let handles: Vec<_> = locals
+            .into_iter()
+            .enumerate()
+            .map(|(id, local)| {
+                let (injector, stealers) = (&injector, &stealers);
+                let (pending, stolen, panicked) = (&pending, &stolen, &panicked);
+                scope.spawn(move || {
+                    let context = Context {
+                        local: &local,
+                        pending,
+                    };
+                    let mut executed = 0;
+                    loop {
+                        match next_job(id, &local, injector, stealers, stolen) {
+                            Some(job) => {
+                                // Caught, so that the count still goes down
+                                // and the other workers do not wait forever.
+                                let result =
+                                    panic::catch_unwind(AssertUnwindSafe(|| job(&context)));
+                                if let Err(payload) = result {
+                                    panicked.lock().unwrap().get_or_insert(payload);
+                                }
+                                executed += 1;
+                                pending.fetch_sub(1, Ordering::AcqRel);
+                            }
+                            None if pending.load(Ordering::Acquire) == 0 => return executed,
+                            // Others are still running jobs that may spawn more.
+                            None => thread::yield_now(),
+                        }
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    if let Some(payload) = panicked.into_inner().unwrap() {
+        panic::resume_unwind(payload);
+    }
+    Stats {
+        executed,
+        stolen: stolen.into_inner(),
+    }
+}
+
+/// The worker's own newest job; or a batch from the global queue; or the
+/// oldest job of another worker. `Retry` means a race with another thread,
+/// not an empty queue, so the search starts over until it is all `Empty`.
+fn next_job(
+    id: usize,
+    local: &Worker<Job>,
+    injector: &Injector<Job>,
+    stealers: &[Stealer<Job>],
+    stolen: &AtomicUsize,
+) -> Option<Job> {
+    if let Some(job) = local.pop() {
+        return Some(job);
+    }
+    loop {
+        let mut retry = false;
+        match injector.steal_batch_and_pop(local) {
+            Steal::Success(job) => return Some(job),
+            Steal::Retry => retry = true,
+            Steal::Empty => {}
+        }
+        for (other, stealer) in stealers.iter().enumerate() {
+            if other == id {
+                continue;
+            }
+            match stealer.steal() {
+                Steal::Success(job) => {
+                    stolen.fetch_add(1, Ordering::Relaxed);
+                    return Some(job);
+                }
+                Steal::Retry => retry = true,
+                Steal::Empty => {}
+            }
+        }
+        if !retry {
+            return None;
+        }
+    }
+}
+
+/// The primes in `from..to`, by divide and conquer: a range longer than
+/// `grain` is cut in two, one half spawned and the other split again on the
+/// spot, until the pieces are small enough to count.
+pub fn count_primes(workers: usize, from: u64, to: u64, grain: u64) -> (u64, Stats) {
+    let total = Arc::new(AtomicU64::new(0));
+    let root_total = Arc::clone(&total);
+    let stats = run(workers, move |context| {
+        split(context, from, to, grain.max(1), root_total)
+    });
+    (total.load(Ordering::Relaxed), stats)
+}
+
+fn split(context: &Context, from: u64, to: u64, grain: u64, total: Arc<AtomicU64>) {
+    if to - from <= grain {
+        total.fetch_add(workload::count_primes(from, to), Ordering::Relaxed);
+        return;
+    }
+    let middle = from + (to - from) / 2;
+    let right_total = Arc::clone(&total);
+    context.spawn(move |context| split(context, middle, to, grain, right_total));
+    split(context, from, middle, grain, total);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_count_primes() {
+        let expected = workload::count_primes(0, 100_000);
+        for workers in [1, 2, 4] {
+            let (count, stats) = count_primes(workers, 0, 100_000, 1_000);
+            assert_eq!(count, expected, "{} workers", workers);
+            // 128 leaves (100 000 / 1 000, rounded up to a power of two)
+            // and 127 splits above them.
+            assert_eq!(stats.executed.iter().sum::<usize>(), 1 + 127);
+            assert_eq!(stats.executed.len(), workers);
+        }
+        assert_eq!(count_primes(2, 0, 0, 10).0, 0);
+    }
+
+    #[test]
+    fn test_idle_workers_steal() {
+        // One root job spawns slow jobs on its own worker's queue: the only
+        // way for the others to get any is to steal them.
+        let stats = run(4, |context| {
+            for _ in 0..16 {
+                context.spawn(|_| thread::sleep(Duration::from_millis(5)));
+            }
+        });
+        assert_eq!(stats.executed.iter().sum::<usize>(), 17);
+        assert!(stats.stolen > 0);
+        assert!(stats.executed.iter().filter(|&&n| n > 0).count() > 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "job failed")]
+    fn test_a_panicking_job_is_passed_on() {
+        run(2, |context| {
+            context.spawn(|_| panic!("job failed"));
+            context.spawn(|_| {});
+        });
+    }
+}
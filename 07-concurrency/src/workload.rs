@@ -0,0 +1,59 @@
+//! A CPU-bound workload for the demos and the benchmarks: counting primes.
+//!
+//! Trial division is slow on purpose, and every range can be counted on its
+//! own, so the work splits into as many independent jobs as wanted. Ranges of
+//! the same size are not the same amount of work, though: testing a larger
+//! number takes more divisions.
+
+/// The number of primes `p` with `from <= p < to`.
+pub fn count_primes(from: u64, to: u64) -> u64 {
+    (from..to).filter(|&n| is_prime(n)).count() as u64
+}
+
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    (2..)
+        .take_while(|d| d * d <= n)
+        .all(|d| !n.is_multiple_of(d))
+}
+
+/// `0..limit` cut in `chunks` ranges, the last one taking what is left over.
+pub fn chunks(limit: u64, chunks: u64) -> Vec<(u64, u64)> {
+    let size = limit / chunks;
+    (0..chunks)
+        .map(|i| {
+            let to = if i + 1 == chunks {
+                limit
+            } else {
+                (i + 1) * size
+            };
+            (i * size, to)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_primes() {
+        assert_eq!(count_primes(0, 100), 25);
+        // Chunks add up to the whole.
+        assert_eq!(count_primes(0, 50) + count_primes(50, 100), 25);
+        assert_eq!(count_primes(0, 2), 0);
+        assert!(is_prime(7919) && !is_prime(7917));
+    }
+
+    #[test]
+    fn test_chunks() {
+        assert_eq!(chunks(10, 3), [(0, 3), (3, 6), (6, 10)]);
+        let total: u64 = chunks(1000, 7)
+            .into_iter()
+            .map(|(from, to)| count_primes(from, to))
+            .sum();
+        assert_eq!(total, count_primes(0, 1000));
+    }
+}
@@ -0,0 +1,68 @@
+//! The arena graph against the `Rc` graph: building 10 000 nodes with three
+//! edges each, and walking everything reachable from the first.
+//!
+//! Run with `cargo bench --bench graph`. Building costs about the same: each
+//! node has a label and a list of edges to allocate either way, which the
+//! `Rc` node's own allocation adds little to, and the arena's `Vec` is copied
+//! as it grows. Walking is where they part, by about ten times: each edge of
+//! the `Rc` graph is a `Weak` to upgrade and a node elsewhere on the heap, and
+//! its visited set is a hash of addresses instead of a `Vec<bool>`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use smart_pointers::arena::Id;
+use smart_pointers::graph::{ArenaGraph, Node, RcGraph};
+use std::hint::black_box;
+
+const NODES: usize = 10_000;
+
+/// Each node to the next, to its double and to a third of the way around:
+/// cycles everywhere.
+fn edges(i: usize) -> [usize; 3] {
+    [(i + 1) % NODES, (i * 2) % NODES, (i + NODES / 3) % NODES]
+}
+
+fn build_arena() -> ArenaGraph {
+    let mut graph = ArenaGraph::new();
+    let ids: Vec<Id<Node>> = (0..NODES)
+        .map(|i| graph.add_node(&format!("node {}", i)))
+        .collect();
+    for i in 0..NODES {
+        for to in edges(i) {
+            graph.add_edge(ids[i], ids[to]);
+        }
+    }
+    graph
+}
+
+fn build_rc() -> RcGraph {
+    let mut graph = RcGraph::new();
+    let nodes: Vec<_> = (0..NODES)
+        .map(|i| graph.add_node(&format!("node {}", i)))
+        .collect();
+    for i in 0..NODES {
+        for to in edges(i) {
+            RcGraph::add_edge(&nodes[i], &nodes[to]);
+        }
+    }
+    graph
+}
+
+fn arena_against_rc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("graph");
+    group.bench_function("build/arena", |b| b.iter(build_arena));
+    group.bench_function("build/Rc", |b| b.iter(build_rc));
+
+    let arena = build_arena();
+    let rc = build_rc();
+    let (first, _) = arena.nodes().next().unwrap();
+    group.bench_function("walk/arena", |b| {
+        b.iter(|| black_box(&arena).reachable(first).len())
+    });
+    group.bench_function("walk/Rc", |b| {
+        b.iter(|| black_box(&rc).reachable(&rc.nodes()[0]).len())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, arena_against_rc);
+criterion_main!(benches);
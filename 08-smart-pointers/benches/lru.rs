@@ -0,0 +1,59 @@
+//! `LruCache` (a `RefCell`) against `SyncLruCache` (an `Arc<Mutex>`), on one
+//! thread: 10 000 lookups of 1 000 keys, most often a few of them, putting
+//! each key missed.
+//!
+//! Run with `cargo bench --bench lru`. The difference is what a lock costs
+//! when nobody else wants it: an atomic operation to take it and one to give
+//! it back, against the plain counter of a `RefCell` borrow. A bigger cache
+//! misses less, and each miss costs an eviction and a `put`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use smart_pointers::lru::{LruCache, SyncLruCache};
+use std::hint::black_box;
+
+const LOOKUPS: u64 = 10_000;
+
+/// Skewed keys: the squares modulo 1 000 take some values much more often.
+fn keys() -> impl Iterator<Item = u64> {
+    (0..LOOKUPS).map(|i| i * i % 1_000)
+}
+
+fn refcell_against_mutex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lru");
+    for capacity in [50, 500] {
+        group.bench_with_input(
+            BenchmarkId::new("RefCell", capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter(|| {
+                    let cache = LruCache::new(capacity);
+                    for key in keys() {
+                        if cache.get(black_box(&key)).is_none() {
+                            cache.put(key, key);
+                        }
+                    }
+                    cache.stats()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("Arc<Mutex>", capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter(|| {
+                    let cache = SyncLruCache::new(capacity);
+                    for key in keys() {
+                        if cache.get(black_box(&key)).is_none() {
+                            cache.put(key, key);
+                        }
+                    }
+                    cache.stats()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, refcell_against_mutex);
+criterion_main!(benches);
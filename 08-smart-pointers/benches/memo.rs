@@ -0,0 +1,65 @@
+//! `RwLockMemo` against `DashMemo`, with 1, 4 and 8 threads sharing one
+//! cache: 20 000 lookups in all over 1 000 keys.
+//!
+//! Run with `cargo bench --bench memo`. `warm` starts from a full cache, so
+//! every lookup is a hit: read locks only, which the `RwLock` lets threads
+//! share, though they all still write to the one lock's reader count. `cold`
+//! starts empty, so the first lookup of each key inserts it, under the
+//! `RwLock`'s single write lock against one shard's of the `DashMap`. The
+//! shards only pay off when threads run at the same time: on a single core
+//! they take turns, and the two stay close.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use smart_pointers::memo::{DashMemo, Memo, RwLockMemo};
+use std::hint::black_box;
+use std::thread;
+
+const LOOKUPS: u64 = 20_000;
+const KEYS: u64 = 1_000;
+
+fn lookups(memo: &impl Memo<u64, u64>, threads: u64) -> u64 {
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                scope.spawn(move || {
+                    (t..LOOKUPS)
+                        .step_by(threads as usize)
+                        .map(|i| memo.get_or_compute(black_box(i * 7 % KEYS), |key| key * key))
+                        .sum::<u64>()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
+fn bench_memo<M, F>(c: &mut Criterion, name: &str, new: F)
+where
+    M: Memo<u64, u64>,
+    F: Fn() -> M,
+{
+    let mut group = c.benchmark_group(name);
+    for threads in [1, 4, 8] {
+        let warm = new();
+        lookups(&warm, 1);
+        group.bench_with_input(
+            BenchmarkId::new("warm", threads),
+            &threads,
+            |b, &threads| b.iter(|| lookups(&warm, threads)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("cold", threads),
+            &threads,
+            |b, &threads| b.iter(|| lookups(&new(), threads)),
+        );
+    }
+    group.finish();
+}
+
+fn rwlock_against_dashmap(c: &mut Criterion) {
+    bench_memo(c, "RwLock", RwLockMemo::new);
+    bench_memo(c, "DashMap", DashMemo::new);
+}
+
+criterion_group!(benches, rwlock_against_dashmap);
+criterion_main!(benches);
@@ -0,0 +1,149 @@
+//! `Arena<T>`: values in one `Vec`, referred to by index.
+//!
+//! With `Rc`, each value is its own allocation, owned by whoever holds an
+//! `Rc` to it, and cycles need `Weak` to be freed. An arena owns every value
+//! itself; the rest of the program holds `Id`s, plain indices that are
+//! `Copy` and own nothing. A cycle of `Id`s is just numbers, so it leaks
+//! nothing, and everything is freed at once with the arena.
+//!
+//! The price: a value cannot be freed on its own, only with the whole
+//! arena, and an `Id` is only meaningful for the arena that gave it.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// A handle to a value of an `Arena<T>`. The type parameter keeps an
+/// `Id<Node>` from indexing an `Arena<Edge>`.
+pub struct Id<T> {
+    index: usize,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+// Written out: derives would require `T: Copy`, `T: Eq`... though an `Id`
+// holds no `T`.
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.index)
+    }
+}
+
+pub struct Arena<T> {
+    items: Vec<T>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Arena { items: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Moves `value` into the arena, for as long as the arena lives.
+    pub fn alloc(&mut self, value: T) -> Id<T> {
+        self.items.push(value);
+        Id {
+            index: self.items.len() - 1,
+            _type: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Every value with its `Id`, in the order they were allocated.
+    pub fn iter(&self) -> impl Iterator<Item = (Id<T>, &T)> {
+        self.items.iter().enumerate().map(|(index, item)| {
+            let id = Id {
+                index,
+                _type: PhantomData,
+            };
+            (id, item)
+        })
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+/// `arena[id]`. Panics on an `Id` from a bigger arena, like a `Vec` does on
+/// an index out of bounds.
+impl<T> Index<Id<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, id: Id<T>) -> &T {
+        &self.items[id.index]
+    }
+}
+
+impl<T> IndexMut<Id<T>> for Arena<T> {
+    fn index_mut(&mut self, id: Id<T>) -> &mut T {
+        &mut self.items[id.index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alloc_and_index() {
+        let mut arena = Arena::new();
+        assert!(arena.is_empty());
+        let a = arena.alloc(String::from("a"));
+        let b = arena.alloc(String::from("b"));
+        assert_ne!(a, b);
+        assert_eq!((a.index(), b.index()), (0, 1));
+        assert_eq!(arena[b], "b");
+
+        arena[a].push('!');
+        assert_eq!(arena[a], "a!");
+        // Copies of a handle refer to the same value.
+        let a_again = a;
+        assert_eq!(arena[a_again], "a!");
+        assert_eq!(format!("{:?}", a), "#0");
+        let all: Vec<(Id<String>, &String)> = arena.iter().collect();
+        assert_eq!(all, [(a, &String::from("a!")), (b, &String::from("b"))]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Smart pointers of our own: `MyBox<T>` and `TrackedBox<T>`.
+//!
+//! What makes `Box` a smart pointer is two traits. `Deref` (and `DerefMut`)
+//! make `*b` reach the value inside, and let the compiler insert those
+//! dereferences itself where types demand it: a `&MyBox<String>` passed to a
+//! function taking `&str` becomes `&String`, then `&str` (deref coercion).
+//! `Drop` runs code when the pointer goes away.
+//!
+//! `TrackedBox` records its allocation and its drop in a shared `Recorder`,
+//! which makes the drop order visible: locals in reverse order of
+//! declaration, struct fields and the items of arrays and `Vec`s in order, and `drop(x)` at once.
+
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+/// The `Box` of the book: a value, and the two traits.
+#[derive(Debug)]
+pub struct MyBox<T>(T);
+
+impl<T> MyBox<T> {
+    pub fn new(value: T) -> MyBox<T> {
+        MyBox(value)
+    }
+}
+
+impl<T> Deref for MyBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for MyBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Allocated(String),
+    Dropped(String),
+}
+
+/// A log shared by every `TrackedBox` given a clone of it.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    events: Rc<RefCell<Vec<Event>>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder::default()
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+
+    /// The names of the boxes dropped so far, in the order they were.
+    pub fn dropped(&self) -> Vec<String> {
+        self.events
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Dropped(name) => Some(name.clone()),
+                Event::Allocated(_) => None,
+            })
+            .collect()
+    }
+
+    fn record(&self, event: Event) {
+        self.events.borrow_mut().push(event);
+    }
+}
+
+/// A value on the heap, named, that reports when it is allocated and dropped.
+#[derive(Debug)]
+pub struct TrackedBox<T> {
+    name: String,
+    value: Box<T>,
+    recorder: Recorder,
+}
+
+impl<T> TrackedBox<T> {
+    pub fn new(name: &str, value: T, recorder: &Recorder) -> TrackedBox<T> {
+        recorder.record(Event::Allocated(name.to_string()));
+        TrackedBox {
+            name: name.to_string(),
+            value: Box::new(value),
+            recorder: recorder.clone(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<T> Deref for TrackedBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for TrackedBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// Runs before the fields are dropped: the value is still there to look at.
+impl<T> Drop for TrackedBox<T> {
+    fn drop(&mut self) {
+        self.recorder.record(Event::Dropped(self.name.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(name: &str) -> String {
+        format!("Hello, {}!", name)
+    }
+
+    #[test]
+    fn test_deref_coercion() {
+        let name = MyBox::new(String::from("Rust"));
+        // &MyBox<String> -> &String -> &str, inserted by the compiler.
+        assert_eq!(hello(&name), "Hello, Rust!");
+        // What it saves writing.
+        assert_eq!(hello(&(*name)[..]), "Hello, Rust!");
+        // Twice over: &MyBox<MyBox<String>> -> ... -> &str.
+        assert_eq!(hello(&MyBox::new(name)), "Hello, Rust!");
+
+        let recorder = Recorder::new();
+        let tracked = TrackedBox::new("greeting", String::from("world"), &recorder);
+        assert_eq!(hello(&tracked), "Hello, world!");
+        // Methods are found through `Deref` too.
+        assert_eq!(tracked.len(), 5);
+    }
+
+    #[test]
+    fn test_deref_mut() {
+        let mut count = MyBox::new(1);
+        *count += 1;
+        assert_eq!(*count, 2);
+
+        let recorder = Recorder::new();
+        let mut text = TrackedBox::new("text", String::from("Hello"), &recorder);
+        text.push_str(", world");
+        assert_eq!(*text, "Hello, world");
+    }
+
+    #[test]
+    fn test_drop_order() {
+        let recorder = Recorder::new();
+        {
+            let _first = TrackedBox::new("first", 1, &recorder);
+            let _second = TrackedBox::new("second", 2, &recorder);
+            let early = TrackedBox::new("early", 3, &recorder);
+            let _items = [
+                TrackedBox::new("item 0", 0, &recorder),
+                TrackedBox::new("item 1", 1, &recorder),
+            ];
+            drop(early);
+        }
+        assert_eq!(
+            recorder.dropped(),
+            ["early", "item 0", "item 1", "second", "first"]
+        );
+        assert_eq!(
+            recorder.events()[0],
+            Event::Allocated(String::from("first"))
+        );
+        assert_eq!(recorder.events().len(), 10);
+    }
+
+    #[test]
+    fn test_moved_box_drops_once() {
+        let recorder = Recorder::new();
+        let outer = {
+            let inner = TrackedBox::new("moved", vec![1, 2, 3], &recorder);
+            // Moved out of the block: not dropped at its end.
+            inner
+        };
+        assert!(recorder.dropped().is_empty());
+        assert_eq!(outer.name(), "moved");
+        drop(outer);
+        assert_eq!(recorder.dropped(), ["moved"]);
+    }
+}
@@ -0,0 +1,203 @@
+//! A reference cycle made on purpose, the leak it causes, and the `Weak` fix.
+//!
+//! `Rc` frees a value when its strong count drops to 0. Two values holding
+//! an `Rc` to each other keep each other's count at 1 at least: once the
+//! program drops its own handles, nothing can reach them, and nothing frees
+//! them either. Rust calls this safe (a leak is not undefined behaviour), and
+//! the compiler does not stop it.
+//!
+//! The fix is to decide which direction owns. Here a pair is an owner and a
+//! partner: the owner holds the partner with an `Rc`, the partner refers
+//! back with a `Weak`, which does not count.
+//!
+//! Whether something was freed is seen two ways: a `DropFlag` set by `Drop`,
+//! and a `LeakDetector` holding a `Weak` to each value, which still upgrades
+//! if the value is still alive.
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+/// Set when the value holding it is dropped; clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct DropFlag(Rc<Cell<bool>>);
+
+impl DropFlag {
+    pub fn new() -> Self {
+        DropFlag::default()
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.0.get()
+    }
+
+    fn set(&self) {
+        self.0.set(true);
+    }
+}
+
+/// Half of a pair that holds the other half strongly: two of these leak.
+pub struct Leaky {
+    pub name: String,
+    pub partner: RefCell<Option<Rc<Leaky>>>,
+    flag: DropFlag,
+}
+
+impl Drop for Leaky {
+    fn drop(&mut self) {
+        self.flag.set();
+    }
+}
+
+impl Leaky {
+    /// Two values pointing at each other with `Rc`; the flags are set when
+    /// each is dropped, which, left alone, they never are.
+    pub fn pair(flags: [&DropFlag; 2]) -> (Rc<Leaky>, Rc<Leaky>) {
+        let [a, b] = [("a", flags[0]), ("b", flags[1])].map(|(name, flag)| {
+            Rc::new(Leaky {
+                name: name.to_string(),
+                partner: RefCell::new(None),
+                flag: flag.clone(),
+            })
+        });
+        *a.partner.borrow_mut() = Some(Rc::clone(&b));
+        *b.partner.borrow_mut() = Some(Rc::clone(&a));
+        (a, b)
+    }
+
+    /// The fix by hand: takes the partner out, which breaks the cycle. It
+    /// has to be called on a value still reachable, and not forgotten.
+    pub fn break_cycle(&self) {
+        self.partner.borrow_mut().take();
+    }
+}
+
+/// Half of a pair where only one side owns.
+pub struct Fixed {
+    pub name: String,
+    /// The owner's partner, kept alive by it.
+    pub partner: RefCell<Option<Rc<Fixed>>>,
+    /// The partner's owner, not kept alive by it.
+    pub owner: RefCell<Weak<Fixed>>,
+    flag: DropFlag,
+}
+
+impl Drop for Fixed {
+    fn drop(&mut self) {
+        self.flag.set();
+    }
+}
+
+impl Fixed {
+    /// The same pair as `Leaky::pair`, the link back a `Weak`.
+    pub fn pair(flags: [&DropFlag; 2]) -> (Rc<Fixed>, Rc<Fixed>) {
+        let [owner, partner] = [("owner", flags[0]), ("partner", flags[1])].map(|(name, flag)| {
+            Rc::new(Fixed {
+                name: name.to_string(),
+                partner: RefCell::new(None),
+                owner: RefCell::new(Weak::new()),
+                flag: flag.clone(),
+            })
+        });
+        *owner.partner.borrow_mut() = Some(Rc::clone(&partner));
+        *partner.owner.borrow_mut() = Rc::downgrade(&owner);
+        (owner, partner)
+    }
+}
+
+/// Watches values without keeping them alive, to tell which are still
+/// allocated once the program thinks it is done with them.
+pub struct LeakDetector<T> {
+    watched: Vec<(String, Weak<T>)>,
+}
+
+impl<T> LeakDetector<T> {
+    pub fn new() -> Self {
+        LeakDetector {
+            watched: Vec::new(),
+        }
+    }
+
+    pub fn watch(&mut self, name: &str, value: &Rc<T>) {
+        self.watched.push((name.to_string(), Rc::downgrade(value)));
+    }
+
+    /// The values still alive, each with its strong count. Called once every
+    /// handle of the program's own is dropped, what is left is leaked: owned
+    /// only by other leaked values.
+    pub fn alive(&self) -> Vec<(String, usize)> {
+        self.watched
+            .iter()
+            .filter(|(_, value)| value.strong_count() > 0)
+            .map(|(name, value)| (name.clone(), value.strong_count()))
+            .collect()
+    }
+}
+
+impl<T> Default for LeakDetector<T> {
+    fn default() -> Self {
+        LeakDetector::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_cycle_leaks() {
+        let flags = [DropFlag::new(), DropFlag::new()];
+        let (a, b) = Leaky::pair([&flags[0], &flags[1]]);
+        // Ours, and the partner's.
+        assert_eq!(Rc::strong_count(&a), 2);
+        assert_eq!(Rc::strong_count(&b), 2);
+        let mut detector = LeakDetector::new();
+        detector.watch("a", &a);
+        detector.watch("b", &b);
+
+        // Keep a way back in, to clean up after the test.
+        let a_again = Rc::downgrade(&a);
+        drop((a, b));
+        // Unreachable from the program, and still allocated.
+        assert!(!flags[0].is_dropped() && !flags[1].is_dropped());
+        assert_eq!(
+            detector.alive(),
+            [(String::from("a"), 1), (String::from("b"), 1)]
+        );
+
+        // The cycle broken by hand: both freed.
+        a_again.upgrade().unwrap().break_cycle();
+        assert!(flags[0].is_dropped() && flags[1].is_dropped());
+        assert!(detector.alive().is_empty());
+    }
+
+    #[test]
+    fn test_weak_fix_is_reclaimed() {
+        let flags = [DropFlag::new(), DropFlag::new()];
+        let (owner, partner) = Fixed::pair([&flags[0], &flags[1]]);
+        assert_eq!(Rc::strong_count(&owner), 1);
+        assert_eq!(Rc::weak_count(&owner), 1);
+        assert_eq!(Rc::strong_count(&partner), 2);
+        assert_eq!(partner.owner.borrow().upgrade().unwrap().name, "owner");
+        let mut detector = LeakDetector::new();
+        detector.watch("owner", &owner);
+        detector.watch("partner", &partner);
+
+        drop((owner, partner));
+        assert!(flags[0].is_dropped());
+        assert!(flags[1].is_dropped());
+        assert!(detector.alive().is_empty());
+    }
+
+    #[test]
+    fn test_partner_outliving_its_owner() {
+        let flags = [DropFlag::new(), DropFlag::new()];
+        let (owner, partner) = Fixed::pair([&flags[0], &flags[1]]);
+        drop(owner);
+        // The owner is gone, and the partner can tell.
+        assert!(flags[0].is_dropped());
+        assert!(partner.owner.borrow().upgrade().is_none());
+        assert!(!flags[1].is_dropped());
+        drop(partner);
+        assert!(flags[1].is_dropped());
+    }
+}
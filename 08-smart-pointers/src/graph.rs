@@ -0,0 +1,215 @@
+//! A directed graph, cycles allowed, two ways: nodes in an `Arena` linked by
+//! `Id`, and nodes in `Rc`s linked by `Weak`.
+//!
+//! In `RcGraph`, the graph's `Vec` owns the nodes and edges are `Weak`: with
+//! `Rc` edges, a cycle would keep itself alive. Following an edge means an
+//! `upgrade` (a check and a count to raise, then lower), a pointer to chase to
+//! wherever the node was allocated, and a `RefCell` borrow to read its edges.
+//!
+//! `ArenaGraph` needs none of this: an edge is an `Id`, following it is
+//! indexing a `Vec`, and the nodes sit next to each other in memory. What it
+//! gives up is freeing a node on its own, and handing a node around without
+//! the arena. `to_arena` and `to_rc` convert between the two;
+//! `benches/graph.rs` compares building and walking them.
+
+use crate::arena::{Arena, Id};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::{Rc, Weak};
+
+pub struct Node {
+    pub label: String,
+    pub edges: Vec<Id<Node>>,
+}
+
+#[derive(Default)]
+pub struct ArenaGraph {
+    nodes: Arena<Node>,
+}
+
+impl ArenaGraph {
+    pub fn new() -> Self {
+        ArenaGraph::default()
+    }
+
+    pub fn add_node(&mut self, label: &str) -> Id<Node> {
+        self.nodes.alloc(Node {
+            label: label.to_string(),
+            edges: Vec::new(),
+        })
+    }
+
+    pub fn add_edge(&mut self, from: Id<Node>, to: Id<Node>) {
+        self.nodes[from].edges.push(to);
+    }
+
+    pub fn node(&self, id: Id<Node>) -> &Node {
+        &self.nodes[id]
+    }
+
+    /// Every node with its `Id`, in the order they were added.
+    pub fn nodes(&self) -> impl Iterator<Item = (Id<Node>, &Node)> {
+        self.nodes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The labels of the nodes reachable from `start`, itself included, in
+    /// breadth-first order.
+    pub fn reachable(&self, start: Id<Node>) -> Vec<&str> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue = VecDeque::from([start]);
+        seen[start.index()] = true;
+        let mut labels = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            let node = &self.nodes[id];
+            labels.push(node.label.as_str());
+            for &next in &node.edges {
+                if !std::mem::replace(&mut seen[next.index()], true) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        labels
+    }
+
+    /// The same graph with `Rc` nodes, in the same order.
+    pub fn to_rc(&self) -> RcGraph {
+        let mut graph = RcGraph::new();
+        let nodes: Vec<Rc<RcNode>> = self
+            .nodes
+            .iter()
+            .map(|(_, node)| graph.add_node(&node.label))
+            .collect();
+        for (id, node) in self.nodes.iter() {
+            for &to in &node.edges {
+                RcGraph::add_edge(&nodes[id.index()], &nodes[to.index()]);
+            }
+        }
+        graph
+    }
+}
+
+pub struct RcNode {
+    pub label: String,
+    pub edges: RefCell<Vec<Weak<RcNode>>>,
+}
+
+/// Owns its nodes; they live as long as the graph, or an `Rc` to them.
+#[derive(Default)]
+pub struct RcGraph {
+    nodes: Vec<Rc<RcNode>>,
+}
+
+impl RcGraph {
+    pub fn new() -> Self {
+        RcGraph::default()
+    }
+
+    pub fn add_node(&mut self, label: &str) -> Rc<RcNode> {
+        let node = Rc::new(RcNode {
+            label: label.to_string(),
+            edges: RefCell::new(Vec::new()),
+        });
+        self.nodes.push(Rc::clone(&node));
+        node
+    }
+
+    /// No `&mut self`: the edges are behind a `RefCell` in the node.
+    pub fn add_edge(from: &Rc<RcNode>, to: &Rc<RcNode>) {
+        from.edges.borrow_mut().push(Rc::downgrade(to));
+    }
+
+    pub fn nodes(&self) -> &[Rc<RcNode>] {
+        &self.nodes
+    }
+
+    /// As `ArenaGraph::reachable`. Nodes are told apart by address.
+    pub fn reachable(&self, start: &Rc<RcNode>) -> Vec<String> {
+        let mut seen: HashSet<*const RcNode> = HashSet::new();
+        let mut queue = VecDeque::from([Rc::clone(start)]);
+        seen.insert(Rc::as_ptr(start));
+        let mut labels = Vec::new();
+        while let Some(node) = queue.pop_front() {
+            labels.push(node.label.clone());
+            for next in node.edges.borrow().iter().filter_map(Weak::upgrade) {
+                if seen.insert(Rc::as_ptr(&next)) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        labels
+    }
+
+    /// The same graph in an arena, the nodes in the same order. Edges to
+    /// nodes no longer alive are left out.
+    pub fn to_arena(&self) -> ArenaGraph {
+        let mut graph = ArenaGraph::new();
+        let ids: HashMap<*const RcNode, Id<Node>> = self
+            .nodes
+            .iter()
+            .map(|node| (Rc::as_ptr(node), graph.add_node(&node.label)))
+            .collect();
+        for node in &self.nodes {
+            let from = ids[&Rc::as_ptr(node)];
+            for to in node.edges.borrow().iter() {
+                if let Some(&to) = ids.get(&to.as_ptr()) {
+                    graph.add_edge(from, to);
+                }
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a -> b -> c -> a, and c -> d.
+    fn cyclic() -> ArenaGraph {
+        let mut graph = ArenaGraph::new();
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|label| graph.add_node(label));
+        for (from, to) in [(a, b), (b, c), (c, a), (c, d)] {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_cyclic_arena_graph() {
+        let graph = cyclic();
+        let ids: Vec<Id<Node>> = graph.nodes().map(|(id, _)| id).collect();
+        assert_eq!(graph.reachable(ids[0]), ["a", "b", "c", "d"]);
+        assert_eq!(graph.reachable(ids[3]), ["d"]);
+        assert_eq!(graph.node(ids[2]).edges, [ids[0], ids[3]]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let rc = cyclic().to_rc();
+        assert_eq!(rc.nodes().len(), 4);
+        assert_eq!(rc.reachable(&rc.nodes()[1]), ["b", "c", "a", "d"]);
+
+        let back = rc.to_arena();
+        let ids: Vec<Id<Node>> = back.nodes().map(|(id, _)| id).collect();
+        assert_eq!(back.reachable(ids[1]), ["b", "c", "a", "d"]);
+        assert_eq!(back.node(ids[2]).edges, [ids[0], ids[3]]);
+    }
+
+    #[test]
+    fn test_rc_cycle_is_freed() {
+        let rc = cyclic().to_rc();
+        let weak: Vec<Weak<RcNode>> = rc.nodes().iter().map(Rc::downgrade).collect();
+        // Only the graph owns them, cycle or not.
+        assert!(weak.iter().all(|node| node.strong_count() == 1));
+        drop(rc);
+        assert!(weak.iter().all(|node| node.upgrade().is_none()));
+    }
+}
@@ -0,0 +1,12 @@
+//! The reusable parts of the smart pointer examples; `main.rs` runs them, and
+//! `benches/` measures them.
+
+pub mod arena;
+pub mod boxes;
+pub mod cycle;
+pub mod graph;
+pub mod linked_list;
+pub mod lru;
+pub mod memo;
+pub mod normalize;
+pub mod tree;
@@ -0,0 +1,308 @@
+//! `DoublyLinkedList<T>`: the cons list of `main.rs`, grown links both ways.
+//!
+//! A doubly linked list is a cycle by construction: each node points at the
+//! next one, which points back. With `Rc` both ways, every node would keep its
+//! neighbours alive and the list would never be freed. So only one direction
+//! owns: `next` is an `Rc`, `prev` a `Weak`, and so is `tail`. Every node has
+//! exactly one strong reference (the `head`, or the `next` of the node before
+//! it), and dropping the head drops them all.
+//!
+//! Nodes are shared, so they are mutated through `RefCell`. A `RefCell` lends
+//! its value only for as long as the borrow guard lives, so this list cannot
+//! hand out `&T` that outlive a call: `peek_front` returns a `Ref`, and `iter`
+//! clones the values.
+
+use std::cell::{Ref, RefCell};
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+type WeakLink<T> = Option<Weak<RefCell<Node<T>>>>;
+
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+    prev: WeakLink<T>,
+}
+
+pub struct DoublyLinkedList<T> {
+    head: Link<T>,
+    tail: WeakLink<T>,
+    len: usize,
+}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        DoublyLinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            next: self.head.take(),
+            prev: None,
+        }));
+        match &node.borrow().next {
+            Some(old_head) => old_head.borrow_mut().prev = Some(Rc::downgrade(&node)),
+            None => self.tail = Some(Rc::downgrade(&node)),
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            next: None,
+            prev: self.tail.clone(),
+        }));
+        let tail = Some(Rc::downgrade(&node));
+        match self.tail.as_ref().and_then(Weak::upgrade) {
+            // The old tail owns the new one.
+            Some(old_tail) => old_tail.borrow_mut().next = Some(node),
+            None => self.head = Some(node),
+        }
+        self.tail = tail;
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head.take()?;
+        match head.borrow_mut().next.take() {
+            Some(next) => {
+                next.borrow_mut().prev = None;
+                self.head = Some(next);
+            }
+            None => self.tail = None,
+        }
+        self.len -= 1;
+        Some(into_value(head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail.take()?.upgrade()?;
+        // Unlinking from the node before is what drops the last strong
+        // reference besides `tail` itself.
+        match tail
+            .borrow_mut()
+            .prev
+            .take()
+            .and_then(|prev| prev.upgrade())
+        {
+            Some(prev) => {
+                prev.borrow_mut().next = None;
+                self.tail = Some(Rc::downgrade(&prev));
+            }
+            None => self.head = None,
+        }
+        self.len -= 1;
+        Some(into_value(tail))
+    }
+
+    /// The first value, borrowed for as long as the `Ref` is kept.
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    /// The last value. A `Ref` cannot outlive the `Rc` it borrows from, and
+    /// the tail is only a `Weak`, upgraded here; so this is a copy.
+    pub fn peek_back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let tail = self.tail.as_ref()?.upgrade()?;
+        let value = tail.borrow().value.clone();
+        Some(value)
+    }
+
+    /// Copies of the values, front to back.
+    pub fn iter(&self) -> Iter<T>
+    where
+        T: Clone,
+    {
+        Iter {
+            next: self.head.clone(),
+        }
+    }
+}
+
+/// The value of a node that nothing else points to any more.
+fn into_value<T>(node: Rc<RefCell<Node<T>>>) -> T {
+    match Rc::try_unwrap(node) {
+        Ok(node) => node.into_inner().value,
+        Err(_) => unreachable!("an unlinked node has no other strong reference"),
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        DoublyLinkedList::new()
+    }
+}
+
+/// Written out: the default drop would free `head`, whose drop frees its
+/// `next`, and so on, one stack frame per node; a long list would overflow
+/// the stack. Unlinking the nodes one at a time takes none.
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            next = node.borrow_mut().next.take();
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut list = DoublyLinkedList::new();
+        for value in values {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug + Clone> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<T> {
+    next: Link<T>,
+}
+
+impl<T: Clone> Iterator for Iter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = self.next.take()?;
+        let node = node.borrow();
+        self.next = node.next.clone();
+        Some(node.value.clone())
+    }
+}
+
+/// Takes the values, front to back, or back to front with `.rev()`.
+pub struct IntoIter<T>(DoublyLinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_and_pop_at_both_ends() {
+        let mut list = DoublyLinkedList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_back(2);
+        list.push_back(3);
+        list.push_front(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(*list.peek_front().unwrap(), 1);
+        assert_eq!(list.peek_back(), Some(3));
+        assert_eq!(list.iter().collect::<Vec<_>>(), [1, 2, 3]);
+
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(1));
+        // One node left: both ends at once.
+        assert_eq!(list.peek_back(), Some(2));
+        assert_eq!(list.pop_back(), Some(2));
+        assert!(list.is_empty());
+        assert!(list.peek_front().is_none() && list.peek_back().is_none());
+
+        // Empty again, and still usable from either end.
+        list.push_front(4);
+        assert_eq!(list.pop_back(), Some(4));
+    }
+
+    #[test]
+    fn test_iterators() {
+        let list: DoublyLinkedList<String> =
+            ["a", "b", "c"].map(String::from).into_iter().collect();
+        assert_eq!(format!("{:?}", list), r#"["a", "b", "c"]"#);
+        let mut values = list.into_iter();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values.next_back().as_deref(), Some("c"));
+        assert_eq!(values.collect::<Vec<_>>(), ["a", "b"]);
+
+        let list: DoublyLinkedList<i32> = (1..=4).collect();
+        assert_eq!(list.into_iter().rev().collect::<Vec<_>>(), [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_no_cycle_leaks() {
+        let mut list: DoublyLinkedList<u32> = (0..5).collect();
+        let mut nodes = Vec::new();
+        let mut next = list.head.clone();
+        while let Some(node) = next {
+            nodes.push(Rc::downgrade(&node));
+            next = node.borrow().next.clone();
+        }
+        // One owner each (the head, or the node before), one `Weak` each (the
+        // node after, or the tail), and the `Weak` taken here.
+        for node in &nodes {
+            assert_eq!(node.strong_count(), 1);
+            assert_eq!(node.weak_count(), 2);
+        }
+
+        // A popped node is freed at once.
+        list.pop_back();
+        assert_eq!(nodes[4].strong_count(), 0);
+        assert!(nodes[4].upgrade().is_none());
+
+        drop(list);
+        assert!(nodes.iter().all(|node| node.upgrade().is_none()));
+    }
+
+    #[test]
+    fn test_long_list_drops_without_overflow() {
+        let list: DoublyLinkedList<u32> = (0..200_000).collect();
+        drop(list);
+    }
+}
@@ -0,0 +1,333 @@
+//! `LruCache`: a cache of fixed capacity that evicts the least recently used
+//! entry, and counts its hits and misses.
+//!
+//! Reading a cache changes it: `get` moves the entry to the front and counts a
+//! hit or a miss. Taking `&mut self` for that would make every reader an
+//! exclusive borrower, and a cache shared by several parts of a program could
+//! not be read by any of them while another holds it. So the state is behind
+//! a `RefCell` and `get` takes `&self`: interior mutability, checked at run
+//! time. `SyncLruCache` is the same behind an `Arc<Mutex<..>>`, to share
+//! between threads: a `RefCell` is not `Sync`, so an `LruCache` cannot be.
+//!
+//! A `RefCell` lends nothing past the end of a call, so `get` returns a clone
+//! of the value; put an `Rc` or `Arc` in the cache for values costly to clone.
+//!
+//! Recency is a counter: each use stamps the entry with the next tick, and a
+//! `BTreeMap` from tick to key keeps the entries from the oldest use to the
+//! newest. The least recently used is its first entry.
+
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// The share of `get`s that found their key; `None` before the first.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// The cache itself, used by both wrappers through `&mut`.
+#[derive(Debug)]
+struct Lru<K, V> {
+    capacity: usize,
+    /// Each value with the tick of its last use.
+    entries: HashMap<K, (V, u64)>,
+    /// Tick of last use -> key, oldest first.
+    by_use: BTreeMap<u64, K>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl<K, V> Lru<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "a cache needs room for at least one entry");
+        Lru {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            by_use: BTreeMap::new(),
+            tick: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let tick = self.next_tick();
+        let Some((value, used)) = self.entries.get_mut(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        let old = std::mem::replace(used, tick);
+        let key = self.by_use.remove(&old).expect("every entry is in by_use");
+        self.by_use.insert(tick, key);
+        Some(value.clone())
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        let tick = self.next_tick();
+        // Already there: a new value and a new use, nothing to evict.
+        if let Some((old_value, used)) = self.entries.get_mut(&key) {
+            *old_value = value;
+            let old = std::mem::replace(used, tick);
+            self.by_use.remove(&old);
+            self.by_use.insert(tick, key);
+            return None;
+        }
+        let evicted = if self.entries.len() == self.capacity {
+            let (_, oldest) = self.by_use.pop_first().expect("a full cache has entries");
+            let (value, _) = self
+                .entries
+                .remove(&oldest)
+                .expect("by_use only has entries");
+            self.stats.evictions += 1;
+            Some((oldest, value))
+        } else {
+            None
+        };
+        self.by_use.insert(tick, key.clone());
+        self.entries.insert(key, (value, tick));
+        evicted
+    }
+
+    fn peek<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|(value, _)| value.clone())
+    }
+
+    /// The keys, the least recently used first.
+    fn keys(&self) -> Vec<K> {
+        self.by_use.values().cloned().collect()
+    }
+}
+
+/// An LRU cache for one thread.
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    lru: RefCell<Lru<K, V>>,
+}
+
+/// An LRU cache to share between threads: clones are the same cache.
+#[derive(Debug)]
+pub struct SyncLruCache<K, V> {
+    lru: Arc<Mutex<Lru<K, V>>>,
+}
+
+impl<K, V> Clone for SyncLruCache<K, V> {
+    fn clone(&self) -> Self {
+        SyncLruCache {
+            lru: Arc::clone(&self.lru),
+        }
+    }
+}
+
+// One API for both, each method borrowing or locking the state for its call
+// only. A `RefCell` borrow cannot be held across calls here, so `get` never
+// finds the cache already borrowed.
+macro_rules! cache_api {
+    ($cache:ident, $wrap:expr) => {
+        impl<K, V> $cache<K, V>
+        where
+            K: Hash + Eq + Clone,
+            V: Clone,
+        {
+            /// A cache holding up to `capacity` entries.
+            ///
+            /// # Panics
+            ///
+            /// If `capacity` is 0.
+            pub fn new(capacity: usize) -> Self {
+                $cache {
+                    lru: $wrap(Lru::new(capacity)),
+                }
+            }
+
+            /// The value for `key`, which becomes the most recently used.
+            pub fn get<Q>(&self, key: &Q) -> Option<V>
+            where
+                K: Borrow<Q>,
+                Q: Hash + Eq + ?Sized,
+            {
+                self.state().get(key)
+            }
+
+            /// Inserts or replaces the value for `key`; returns the entry
+            /// evicted to make room, if one was.
+            pub fn put(&self, key: K, value: V) -> Option<(K, V)> {
+                self.state().put(key, value)
+            }
+
+            /// The value for `key`, without counting as a use or a lookup.
+            pub fn peek<Q>(&self, key: &Q) -> Option<V>
+            where
+                K: Borrow<Q>,
+                Q: Hash + Eq + ?Sized,
+            {
+                self.state().peek(key)
+            }
+
+            /// The keys, from the next to be evicted to the last used.
+            pub fn keys(&self) -> Vec<K> {
+                self.state().keys()
+            }
+
+            pub fn len(&self) -> usize {
+                self.state().entries.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            pub fn capacity(&self) -> usize {
+                self.state().capacity
+            }
+
+            pub fn stats(&self) -> CacheStats {
+                self.state().stats
+            }
+        }
+    };
+}
+
+cache_api!(LruCache, RefCell::new);
+cache_api!(SyncLruCache, |lru| Arc::new(Mutex::new(lru)));
+
+impl<K, V> LruCache<K, V> {
+    fn state(&self) -> std::cell::RefMut<'_, Lru<K, V>> {
+        self.lru.borrow_mut()
+    }
+}
+
+impl<K, V> SyncLruCache<K, V> {
+    fn state(&self) -> std::sync::MutexGuard<'_, Lru<K, V>> {
+        self.lru.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = LruCache::new(3);
+        for (key, value) in [("a", 1), ("b", 2), ("c", 3)] {
+            assert_eq!(cache.put(key.to_string(), value), None);
+        }
+        // `a` used: `b` is now the oldest.
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.keys(), ["b", "c", "a"]);
+        assert_eq!(
+            cache.put(String::from("d"), 4),
+            Some((String::from("b"), 2))
+        );
+        assert_eq!(cache.get("b"), None);
+
+        // Replacing counts as a use, and evicts nothing.
+        assert_eq!(cache.put(String::from("c"), 30), None);
+        assert_eq!(cache.keys(), ["a", "d", "c"]);
+        assert_eq!(
+            cache.put(String::from("e"), 5),
+            Some((String::from("a"), 1))
+        );
+        assert_eq!(cache.keys(), ["d", "c", "e"]);
+        assert_eq!(cache.len(), cache.capacity());
+    }
+
+    #[test]
+    fn test_stats() {
+        let cache = LruCache::new(2);
+        assert_eq!(cache.stats().hit_rate(), None);
+        cache.put(1, "one");
+        cache.put(2, "two");
+        assert_eq!(cache.get(&1), Some("one"));
+        assert_eq!(cache.get(&3), None);
+        // Peeking is neither a use nor a lookup.
+        assert_eq!(cache.peek(&2), Some("two"));
+        cache.put(3, "three");
+        assert_eq!(cache.keys(), [1, 3]);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 1
+            }
+        );
+        assert_eq!(cache.stats().hit_rate(), Some(0.5));
+    }
+
+    #[test]
+    fn test_shared_reads_through_rc() {
+        // Two owners, both reading and writing through `&`.
+        let cache = std::rc::Rc::new(LruCache::new(1));
+        let other = std::rc::Rc::clone(&cache);
+        cache.put('x', 1);
+        assert_eq!(other.get(&'x'), Some(1));
+        other.put('y', 2);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&'x'), None);
+    }
+
+    #[test]
+    fn test_sync_cache_between_threads() {
+        let cache = SyncLruCache::new(100);
+        let workers: Vec<_> = (0..4)
+            .map(|t| {
+                let cache = cache.clone();
+                thread::spawn(move || {
+                    for i in 0..100 {
+                        let key = (t * 100 + i) % 150;
+                        if cache.get(&key).is_none() {
+                            cache.put(key, key * 2);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(cache.len(), 100);
+        let stats = cache.stats();
+        assert_eq!(stats.hits + stats.misses, 400);
+        assert!(cache
+            .keys()
+            .iter()
+            .all(|&key| cache.peek(&key) == Some(key * 2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one entry")]
+    fn test_zero_capacity() {
+        LruCache::<u32, u32>::new(0);
+    }
+}
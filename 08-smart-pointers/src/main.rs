@@ -1,5 +1,15 @@
+use smart_pointers::boxes::{MyBox, Recorder, TrackedBox};
+use smart_pointers::cycle::{DropFlag, Fixed, LeakDetector, Leaky};
+use smart_pointers::graph::ArenaGraph;
+use smart_pointers::linked_list::DoublyLinkedList;
+use smart_pointers::lru::LruCache;
+use smart_pointers::memo::{DashMemo, Memo, RwLockMemo};
+use smart_pointers::normalize::{escape_html, normalize};
+use smart_pointers::tree::TreeNode;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::thread;
 
 #[derive(Debug)]
 enum List {
@@ -9,6 +19,17 @@ enum List {
 
 use List::{Cons, Nil};
 
+/// The values of a cons list, front to back.
+fn values(list: &List) -> Vec<i32> {
+    let mut values = Vec::new();
+    let mut list = list;
+    while let Cons(value, rest) = list {
+        values.push(*value);
+        list = rest;
+    }
+    values
+}
+
 fn main() {
     // --- 1. Box<T> for allocating on the heap ---
     // Box provides exclusive ownership of heap-allocated data.
@@ -19,6 +40,7 @@ fn main() {
     // Rc allows multiple pointers to own the same heap allocation.
     // It keeps track of the number of references to decide when to drop the data.
     let a = Rc::new(Cons(5, Rc::new(Cons(10, Rc::new(Nil)))));
+    println!("a = {:?}", values(&a));
     println!("count after creating a = {}", Rc::strong_count(&a));
 
     // Rc::clone increment the reference count instead of deep copying.
@@ -43,6 +65,179 @@ fn main() {
     // borrow_mut() returns a RefMut smart pointer, allowing us to change the value.
     *x.borrow_mut() += 1;
     println!("x after: {:?}", x);
+
+    // --- 4. Rc<RefCell<T>> and Weak: a doubly linked list ---
+    // The cons list only links forward. Links both ways would be a cycle of
+    // Rc, never freed; so `next` owns (Rc) and `prev` only refers (Weak).
+    let mut list: DoublyLinkedList<i32> = (1..=3).collect();
+    list.push_front(0);
+    list.push_back(4);
+    println!("\nlist = {:?}, {} values", list, list.len());
+    println!(
+        "popped {:?} from the front and {:?} from the back",
+        list.pop_front(),
+        list.pop_back()
+    );
+    println!(
+        "backwards: {:?}",
+        list.into_iter().rev().collect::<Vec<_>>()
+    );
+
+    // --- 5. Rc and Weak: a tree that knows its parents ---
+    // Parents own their children (Rc); children only refer to their parent
+    // (Weak), so a tree is freed when its root is dropped.
+    let root = TreeNode::new("/");
+    let usr = root.add("usr");
+    let bin = usr.add("bin");
+    let rustc = bin.add("rustc");
+    let path: Vec<&str> = rustc.ancestors().map(|node| node.value).collect();
+    println!(
+        "\nrustc is at depth {}, under {:?}; the tree has {} nodes",
+        rustc.depth(),
+        path,
+        root.size()
+    );
+    println!(
+        "usr: {} strong (root's child, `usr`), {} weak (bin's parent)",
+        Rc::strong_count(&usr),
+        Rc::weak_count(&usr)
+    );
+    drop((usr, bin));
+    drop(root);
+    println!(
+        "root dropped: rustc's parent is {:?}",
+        rustc.parent().map(|p| p.value)
+    );
+
+    // --- 6. Deref and Drop: smart pointers of our own ---
+    // Deref lets `&MyBox<String>` go where a `&str` is expected; Drop runs
+    // when a value goes away, here to log it.
+    let name = MyBox::new(String::from("Rust"));
+    println!();
+    greet(&name);
+    let recorder = Recorder::new();
+    {
+        let _a = TrackedBox::new("a", 1, &recorder);
+        let b = TrackedBox::new("b", String::from("bee"), &recorder);
+        let _c = TrackedBox::new("c", 3, &recorder);
+        greet(&b);
+        drop(b);
+    }
+    println!("dropped in this order: {:?}", recorder.dropped());
+
+    // --- 7. RefCell inside: an LRU cache read through `&` ---
+    // `get` changes the cache (recency, statistics), yet takes `&self`.
+    println!();
+    let cache = LruCache::new(3);
+    let mut lengths = Vec::new();
+    for word in ["box", "rc", "refcell", "box", "weak", "arc", "box", "rc"] {
+        let length = match cache.get(word) {
+            Some(length) => length,
+            None => {
+                let length = word.chars().count();
+                if let Some((evicted, _)) = cache.put(word.to_string(), length) {
+                    println!("evicted {:?} to make room for {:?}", evicted, word);
+                }
+                length
+            }
+        };
+        lengths.push(length);
+    }
+    let stats = cache.stats();
+    println!(
+        "lengths {:?}: {} hits, {} misses, kept {:?}",
+        lengths,
+        stats.hits,
+        stats.misses,
+        cache.keys()
+    );
+
+    // --- 8. No Rc at all: a cyclic graph in an arena ---
+    // Nodes live in one Vec and edges are indices: a cycle is just numbers,
+    // and nothing leaks. The same graph with Rc nodes needs Weak edges.
+    let mut graph = ArenaGraph::new();
+    let [rust, cargo, crates] = ["rust", "cargo", "crates.io"].map(|label| graph.add_node(label));
+    graph.add_edge(rust, cargo);
+    graph.add_edge(cargo, crates);
+    graph.add_edge(crates, rust);
+    println!("\nfrom cargo, in the arena: {:?}", graph.reachable(cargo));
+    let rc_graph = graph.to_rc();
+    println!(
+        "from cargo, with Rc nodes: {:?}",
+        rc_graph.reachable(&rc_graph.nodes()[1])
+    );
+
+    // --- 9. A reference cycle, and the Weak fix ---
+    // Two values holding an Rc to each other are never freed; with a Weak
+    // one way, they are.
+    let flags = [DropFlag::new(), DropFlag::new()];
+    let (a, b) = Leaky::pair([&flags[0], &flags[1]]);
+    let mut leaks = LeakDetector::new();
+    leaks.watch("a", &a);
+    leaks.watch("b", &b);
+    drop((a, b));
+    println!(
+        "\nRc both ways, handles dropped: still alive {:?}, dropped: {}",
+        leaks.alive(),
+        flags.iter().all(DropFlag::is_dropped)
+    );
+
+    let flags = [DropFlag::new(), DropFlag::new()];
+    let (owner, partner) = Fixed::pair([&flags[0], &flags[1]]);
+    let mut leaks = LeakDetector::new();
+    leaks.watch("owner", &owner);
+    leaks.watch("partner", &partner);
+    drop((owner, partner));
+    println!(
+        "Weak one way, handles dropped: still alive {:?}, dropped: {}",
+        leaks.alive(),
+        flags.iter().all(DropFlag::is_dropped)
+    );
+
+    // --- 10. Cow<str>: allocate only to change something ---
+    // A clean string comes back borrowed, a view of the input; only one that
+    // needs cleaning is copied.
+    println!();
+    for input in ["already clean", "  tabs\tand  spaces ", "<b>bold</b>"] {
+        for (step, output) in [
+            ("normalize", normalize(input)),
+            ("escape_html", escape_html(input)),
+        ] {
+            let how = match output {
+                Cow::Borrowed(_) => "borrowed",
+                Cow::Owned(_) => "owned",
+            };
+            println!("{}({:?}) = {:?} ({})", step, input, output, how);
+        }
+    }
+
+    // --- 11. Arc and locks: a memo cache shared between threads ---
+    // The same cache across four threads, behind one RwLock or in a sharded
+    // DashMap; the later threads find the squares the earlier ones computed.
+    println!();
+    let rwlock: RwLockMemo<u64, u64> = RwLockMemo::new();
+    let dash: DashMemo<u64, u64> = DashMemo::new();
+    thread::scope(|scope| {
+        for t in 0..4 {
+            let (rwlock, dash) = (&rwlock, &dash);
+            scope.spawn(move || {
+                for key in t..t + 10 {
+                    rwlock.get_or_compute(key, |n| n * n);
+                    dash.get_or_compute(key, |n| n * n);
+                }
+            });
+        }
+    });
+    println!(
+        "40 lookups by 4 threads: {} keys behind the RwLock, {} in the DashMap; 12 squared: {:?}",
+        rwlock.len(),
+        dash.len(),
+        dash.get(&12)
+    );
+}
+
+fn greet(name: &str) {
+    println!("Hello, {}!", name);
 }
 
 #[cfg(test)]
@@ -65,6 +260,13 @@ mod tests {
         assert_eq!(Rc::strong_count(&a), 2);
     }
 
+    #[test]
+    fn test_cons_values() {
+        let list = Cons(1, Rc::new(Cons(2, Rc::new(Nil))));
+        assert_eq!(values(&list), [1, 2]);
+        assert!(values(&Nil).is_empty());
+    }
+
     #[test]
     fn test_refcell_mutation() {
         let x = RefCell::new(10);
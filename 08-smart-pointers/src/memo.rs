@@ -0,0 +1,235 @@
+//! A memoization cache shared between threads: `Arc` instead of `Rc`, a lock
+//! instead of a `RefCell`.
+//!
+//! `Memo` is the small interface the rest of the code sees: the value for a
+//! key, computed the first time it is asked for. Two types implement it:
+//!
+//! - `RwLockMemo`: one `HashMap` behind an `RwLock`. Readers share the lock,
+//!   but each insertion takes it alone and stops every reader for that time.
+//! - `DashMemo`: a `DashMap`, which splits its entries into shards, each with
+//!   its own lock. Two threads only wait for each other when their keys fall
+//!   in the same shard.
+//!
+//! Both are `Clone`: a clone is another handle to the same entries, as with
+//! the `Arc` inside.
+//!
+//! Neither holds a lock while computing: a slow computation would stop every
+//! thread that wants the lock, even for other keys. The price is that two
+//! threads missing the same key at once both compute it. The first value
+//! stored is kept, and both return it, so callers always agree on the value.
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+/// A thread-safe memoization cache.
+pub trait Memo<K, V>: Send + Sync {
+    /// The value stored for `key`, if there is one.
+    fn get(&self, key: &K) -> Option<V>;
+
+    /// The value stored for `key`, or `compute(&key)`, stored first.
+    fn get_or_compute<F>(&self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&K) -> V;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One map behind one `RwLock`.
+pub struct RwLockMemo<K, V> {
+    map: Arc<RwLock<HashMap<K, V>>>,
+}
+
+impl<K, V> RwLockMemo<K, V> {
+    pub fn new() -> Self {
+        RwLockMemo {
+            map: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+// Written out: derived, these would require `K: Default` and `K: Clone`.
+impl<K, V> Default for RwLockMemo<K, V> {
+    fn default() -> Self {
+        RwLockMemo::new()
+    }
+}
+
+impl<K, V> Clone for RwLockMemo<K, V> {
+    fn clone(&self) -> Self {
+        RwLockMemo {
+            map: Arc::clone(&self.map),
+        }
+    }
+}
+
+impl<K, V> Memo<K, V> for RwLockMemo<K, V>
+where
+    K: Eq + Hash + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn get(&self, key: &K) -> Option<V> {
+        self.map.read().unwrap().get(key).cloned()
+    }
+
+    fn get_or_compute<F>(&self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = compute(&key);
+        // Another thread may have stored one meanwhile: that one stays.
+        self.map
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert(value)
+            .clone()
+    }
+
+    fn len(&self) -> usize {
+        self.map.read().unwrap().len()
+    }
+}
+
+/// A `DashMap`: a lock per shard.
+pub struct DashMemo<K: Eq + Hash, V> {
+    map: Arc<DashMap<K, V>>,
+}
+
+impl<K: Eq + Hash, V> DashMemo<K, V> {
+    pub fn new() -> Self {
+        DashMemo {
+            map: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Default for DashMemo<K, V> {
+    fn default() -> Self {
+        DashMemo::new()
+    }
+}
+
+impl<K: Eq + Hash, V> Clone for DashMemo<K, V> {
+    fn clone(&self) -> Self {
+        DashMemo {
+            map: Arc::clone(&self.map),
+        }
+    }
+}
+
+impl<K, V> Memo<K, V> for DashMemo<K, V>
+where
+    K: Eq + Hash + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn get(&self, key: &K) -> Option<V> {
+        self.map.get(key).map(|entry| entry.value().clone())
+    }
+
+    fn get_or_compute<F>(&self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = compute(&key);
+        self.map.entry(key).or_insert(value).value().clone()
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+    use std::thread;
+
+    fn memoizes(memo: impl Memo<u64, String>) {
+        assert!(memo.is_empty());
+        assert_eq!(memo.get(&7), None);
+        let calls = AtomicUsize::new(0);
+        let describe = |n: &u64| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            format!("#{}", n)
+        };
+        assert_eq!(memo.get_or_compute(7, describe), "#7");
+        assert_eq!(memo.get_or_compute(7, describe), "#7");
+        assert_eq!(memo.get_or_compute(8, describe), "#8");
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert_eq!(memo.get(&7).as_deref(), Some("#7"));
+        assert_eq!(memo.len(), 2);
+    }
+
+    /// Eight threads asking for the same keys at the same time.
+    fn agrees_across_threads<M>(memo: M)
+    where
+        M: Memo<u64, u64> + Clone + 'static,
+    {
+        let barrier = Arc::new(Barrier::new(8));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let (memo, barrier, calls) = (memo.clone(), barrier.clone(), calls.clone());
+                thread::spawn(move || {
+                    barrier.wait();
+                    (0..100)
+                        .map(|key| {
+                            memo.get_or_compute(key, |key| {
+                                calls.fetch_add(1, Ordering::Relaxed);
+                                // Differs between threads: a key computed twice
+                                // would show if both values were returned.
+                                key * 1000 + t
+                            })
+                        })
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect();
+        let results: Vec<Vec<u64>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        // Every thread got the value stored first.
+        assert!(results.iter().all(|values| *values == results[0]));
+        assert_eq!(memo.len(), 100);
+        let calls = calls.load(Ordering::Relaxed);
+        assert!((100..=800).contains(&calls), "{} computations", calls);
+    }
+
+    #[test]
+    fn test_rwlock_memo() {
+        memoizes(RwLockMemo::new());
+        agrees_across_threads(RwLockMemo::new());
+    }
+
+    #[test]
+    fn test_dash_memo() {
+        memoizes(DashMemo::new());
+        agrees_across_threads(DashMemo::new());
+    }
+
+    #[test]
+    fn test_clones_share_entries() {
+        let memo: RwLockMemo<u64, u64> = RwLockMemo::new();
+        let other = memo.clone();
+        thread::spawn(move || other.get_or_compute(2, |n| n * n))
+            .join()
+            .unwrap();
+        assert_eq!(memo.get(&2), Some(4));
+
+        let memo: DashMemo<u64, u64> = DashMemo::new();
+        memo.clone().get_or_compute(3, |n| n * n);
+        assert_eq!(memo.get(&3), Some(9));
+    }
+}
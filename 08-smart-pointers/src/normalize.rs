@@ -0,0 +1,174 @@
+//! String clean-up that allocates only when there is something to clean.
+//!
+//! Most input is already clean: a name with no stray spaces, a comment with
+//! no `<`. A function returning `String` copies it anyway. One returning
+//! `Cow<str>` hands back `Cow::Borrowed`, a view of its input, when nothing
+//! changes, and `Cow::Owned` with a new string only when something does. The
+//! caller uses either as a `&str`, and calls `into_owned` only if it has to
+//! keep the result.
+//!
+//! A borrowed result can still differ from the input: `collapse_whitespace`
+//! trims by returning a shorter slice of it, with no allocation either.
+
+use std::borrow::Cow;
+
+/// `text` without control characters: tabs, newlines, `\0`, escape codes...
+pub fn strip_control(text: &str) -> Cow<'_, str> {
+    if text.chars().any(char::is_control) {
+        Cow::Owned(text.chars().filter(|c| !c.is_control()).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// `text` trimmed, with each run of whitespace inside it made one space.
+pub fn collapse_whitespace(text: &str) -> Cow<'_, str> {
+    let trimmed = text.trim();
+    let mut previous = 'x';
+    let clean = trimmed.chars().all(|c| {
+        let ok = !c.is_whitespace() || (c == ' ' && previous != ' ');
+        previous = c;
+        ok
+    });
+    if clean {
+        Cow::Borrowed(trimmed)
+    } else {
+        let mut collapsed = String::with_capacity(trimmed.len());
+        for word in trimmed.split_whitespace() {
+            if !collapsed.is_empty() {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        Cow::Owned(collapsed)
+    }
+}
+
+/// `text` with `&`, `<`, `>`, `"` and `'` escaped, to put in HTML.
+pub fn escape_html(text: &str) -> Cow<'_, str> {
+    let special = |c: char| matches!(c, '&' | '<' | '>' | '"' | '\'');
+    let Some(first) = text.find(special) else {
+        return Cow::Borrowed(text);
+    };
+    // The clean start is copied as it is, the rest character by character.
+    let mut escaped = String::with_capacity(text.len() + 16);
+    escaped.push_str(&text[..first]);
+    for c in text[first..].chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Control characters out, then whitespace collapsed: one line of text,
+/// borrowed if it was one already.
+///
+/// Tabs and line breaks are control characters too: they become spaces
+/// first, so that `"a\tb"` is `"a b"` and not `"ab"`.
+pub fn normalize(text: &str) -> Cow<'_, str> {
+    let spaced = if text.contains(['\t', '\n', '\r']) {
+        Cow::Owned(text.replace(['\t', '\n', '\r'], " "))
+    } else {
+        Cow::Borrowed(text)
+    };
+    then(then(spaced, strip_control), collapse_whitespace)
+}
+
+/// Applies `step` to `text`, still borrowed if both were.
+fn then<'a>(text: Cow<'a, str>, step: impl Fn(&str) -> Cow<'_, str>) -> Cow<'a, str> {
+    match text {
+        Cow::Borrowed(text) => step(text),
+        // Already ours: kept as it is if the step changed nothing, instead of
+        // copied again.
+        Cow::Owned(text) => {
+            let changed = match step(&text) {
+                Cow::Borrowed(same) if same.len() == text.len() => None,
+                result => Some(result.into_owned()),
+            };
+            Cow::Owned(changed.unwrap_or(text))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_control() {
+        let clean = strip_control("plain text");
+        assert!(matches!(clean, Cow::Borrowed(_)));
+        assert_eq!(clean, "plain text");
+
+        let stripped = strip_control("bell\u{7} and \u{1b}[31mred");
+        assert!(matches!(stripped, Cow::Owned(_)));
+        assert_eq!(stripped, "bell and [31mred");
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        let clean = collapse_whitespace("one two three");
+        assert!(matches!(clean, Cow::Borrowed(_)));
+
+        // Only trimmed: still borrowed, a slice of the input.
+        let input = "  padded  ";
+        let trimmed = collapse_whitespace(input);
+        assert!(matches!(trimmed, Cow::Borrowed(_)));
+        assert_eq!(trimmed, "padded");
+        assert_eq!(trimmed.as_ptr(), input[2..].as_ptr());
+
+        for messy in ["one  two", " one\ttwo ", "one\n two"] {
+            let collapsed = collapse_whitespace(messy);
+            assert!(matches!(collapsed, Cow::Owned(_)), "{:?}", messy);
+            assert_eq!(collapsed, "one two");
+        }
+        assert!(matches!(collapse_whitespace("   "), Cow::Borrowed(_)));
+        assert_eq!(collapse_whitespace("   "), "");
+    }
+
+    #[test]
+    fn test_escape_html() {
+        let clean = escape_html("no markup, déjà vu");
+        assert!(matches!(clean, Cow::Borrowed(_)));
+
+        let escaped = escape_html("déjà <b>\"Tom\" & 'Jerry'</b>");
+        assert!(matches!(escaped, Cow::Owned(_)));
+        assert_eq!(
+            escaped,
+            "déjà &lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let input = "already clean";
+        let clean = normalize(input);
+        assert!(matches!(clean, Cow::Borrowed(_)));
+        assert_eq!(clean.as_ptr(), input.as_ptr());
+        assert!(matches!(normalize(" trimmed only "), Cow::Borrowed(_)));
+
+        assert_eq!(normalize("tab\tand\r\nnewline"), "tab and newline");
+        assert_eq!(normalize("  bell\u{7}  ring "), "bell ring");
+        let owned = normalize("one\u{0}two");
+        assert!(matches!(owned, Cow::Owned(_)));
+        assert_eq!(owned, "onetwo");
+    }
+
+    #[test]
+    fn test_then_keeps_an_owned_string() {
+        let owned: Cow<str> = Cow::Owned(String::from("unchanged"));
+        let address = owned.as_ptr();
+        let result = then(owned, strip_control);
+        // The same allocation, not a copy.
+        assert_eq!(result.as_ptr(), address);
+
+        let trimmed = then(Cow::Owned(String::from(" x ")), collapse_whitespace);
+        assert_eq!(trimmed, "x");
+    }
+}
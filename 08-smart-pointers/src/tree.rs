@@ -0,0 +1,215 @@
+//! `TreeNode`: a tree whose nodes know their parent, the canonical use of
+//! `Rc` with `Weak`.
+//!
+//! A parent owns its children: `Rc`. A child refers to its parent, but must
+//! not keep it alive, or parent and child would keep each other alive for
+//! ever: `Weak`. A `Weak` does not count as an owner; `upgrade` gives an `Rc`
+//! if the parent still exists, and `None` once it is gone.
+//!
+//! Nodes are handed around as `Rc<TreeNode<T>>`, and changed through `&self`:
+//! the parent and the children are in `RefCell`s.
+
+use std::cell::{Ref, RefCell};
+use std::mem;
+use std::rc::{Rc, Weak};
+
+pub struct TreeNode<T> {
+    pub value: T,
+    parent: RefCell<Weak<TreeNode<T>>>,
+    children: RefCell<Vec<Rc<TreeNode<T>>>>,
+}
+
+impl<T> TreeNode<T> {
+    /// A node with no parent and no children: the root of its own tree.
+    pub fn new(value: T) -> Rc<TreeNode<T>> {
+        Rc::new(TreeNode {
+            value,
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Makes `child` the last child of this node, moving it, with its
+    /// subtree, from its current parent if it has one.
+    ///
+    /// # Panics
+    ///
+    /// If `child` is this node or one of its ancestors: the tree would
+    /// become a cycle.
+    pub fn add_child(self: &Rc<Self>, child: Rc<TreeNode<T>>) {
+        assert!(
+            !Rc::ptr_eq(self, &child) && !self.ancestors().any(|a| Rc::ptr_eq(&a, &child)),
+            "a node cannot be its own descendant"
+        );
+        child.detach();
+        self.attach(child);
+    }
+
+    /// A new node with `value`, added as the last child; returns it.
+    pub fn add(self: &Rc<Self>, value: T) -> Rc<TreeNode<T>> {
+        let child = TreeNode::new(value);
+        // A new node cannot be an ancestor: no need to walk up and check.
+        self.attach(Rc::clone(&child));
+        child
+    }
+
+    fn attach(self: &Rc<Self>, child: Rc<TreeNode<T>>) {
+        *child.parent.borrow_mut() = Rc::downgrade(self);
+        self.children.borrow_mut().push(child);
+    }
+
+    /// The parent, if there is one and it still exists.
+    pub fn parent(&self) -> Option<Rc<TreeNode<T>>> {
+        self.parent.borrow().upgrade()
+    }
+
+    pub fn children(&self) -> Ref<'_, [Rc<TreeNode<T>>]> {
+        Ref::map(self.children.borrow(), Vec::as_slice)
+    }
+
+    /// The parent, its parent, and so on up to the root.
+    pub fn ancestors(&self) -> Ancestors<T> {
+        Ancestors {
+            next: self.parent(),
+        }
+    }
+
+    /// How many ancestors: 0 for a root.
+    pub fn depth(&self) -> usize {
+        self.ancestors().count()
+    }
+
+    /// The nodes of the subtree, this one included.
+    pub fn size(&self) -> usize {
+        1 + self
+            .children()
+            .iter()
+            .map(|child| child.size())
+            .sum::<usize>()
+    }
+
+    /// Takes this node, with its subtree, out of its parent's children. The
+    /// subtree is freed when the last `Rc` to this node is dropped.
+    pub fn detach(self: &Rc<Self>) {
+        let Some(parent) = self.parent() else {
+            return;
+        };
+        parent
+            .children
+            .borrow_mut()
+            .retain(|child| !Rc::ptr_eq(child, self));
+        *self.parent.borrow_mut() = Weak::new();
+    }
+}
+
+/// Written out: by default, a node frees its children, whose drops free
+/// theirs, one stack frame per level; a deep enough tree would overflow the
+/// stack. Here the nodes to free wait on a `Vec` instead.
+impl<T> Drop for TreeNode<T> {
+    fn drop(&mut self) {
+        let mut to_free = mem::take(self.children.get_mut());
+        while let Some(child) = to_free.pop() {
+            // Only the nodes this was the last owner of; the children of each
+            // are taken before it is dropped, so its own drop has nothing to do.
+            if let Ok(mut child) = Rc::try_unwrap(child) {
+                to_free.append(child.children.get_mut());
+            }
+        }
+    }
+}
+
+pub struct Ancestors<T> {
+    next: Option<Rc<TreeNode<T>>>,
+}
+
+impl<T> Iterator for Ancestors<T> {
+    type Item = Rc<TreeNode<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+        self.next = node.parent();
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strong_and_weak_counts() {
+        let branch = TreeNode::new("branch");
+        let leaf = branch.add("leaf");
+        // `leaf` here, and in the branch's children.
+        assert_eq!(Rc::strong_count(&leaf), 2);
+        assert_eq!(Rc::weak_count(&leaf), 0);
+        // Only `branch` here; the leaf's parent link is the one `Weak`.
+        assert_eq!(Rc::strong_count(&branch), 1);
+        assert_eq!(Rc::weak_count(&branch), 1);
+        assert_eq!(leaf.parent().unwrap().value, "branch");
+
+        // The parent does not outlive its last owner because of its child.
+        drop(branch);
+        assert!(leaf.parent().is_none());
+        assert_eq!(Rc::strong_count(&leaf), 1);
+    }
+
+    #[test]
+    fn test_depth_and_ancestors() {
+        let root = TreeNode::new("/");
+        let usr = root.add("usr");
+        let bin = usr.add("bin");
+        let rustc = bin.add("rustc");
+        root.add("etc");
+
+        assert_eq!(root.depth(), 0);
+        assert_eq!(rustc.depth(), 3);
+        let path: Vec<&str> = rustc.ancestors().map(|node| node.value).collect();
+        assert_eq!(path, ["bin", "usr", "/"]);
+        assert_eq!(root.size(), 5);
+        let names: Vec<&str> = root.children().iter().map(|c| c.value).collect();
+        assert_eq!(names, ["usr", "etc"]);
+    }
+
+    #[test]
+    fn test_detach_and_move_subtrees() {
+        let root = TreeNode::new(0);
+        let a = root.add(1);
+        let b = root.add(2);
+        let a_child = Rc::downgrade(&a.add(10));
+
+        // Moved, with its child, from the root to `b`.
+        b.add_child(Rc::clone(&a));
+        assert_eq!(root.children().len(), 1);
+        assert_eq!(a.parent().unwrap().value, 2);
+        assert_eq!(a_child.upgrade().unwrap().depth(), 3);
+
+        // Detached and no longer owned: the whole subtree is freed.
+        a.detach();
+        assert!(a.parent().is_none());
+        assert_eq!(b.children().len(), 0);
+        drop(a);
+        assert!(a_child.upgrade().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "its own descendant")]
+    fn test_no_cycles() {
+        let root = TreeNode::new(0);
+        let child = root.add(1);
+        child.add_child(root);
+    }
+
+    #[test]
+    fn test_deep_tree_drops_without_overflow() {
+        let root = TreeNode::new(0);
+        let mut node = Rc::clone(&root);
+        for i in 1..200_000 {
+            node = node.add(i);
+        }
+        let deepest = Rc::downgrade(&node);
+        drop(node);
+        drop(root);
+        assert!(deepest.upgrade().is_none());
+    }
+}
@@ -0,0 +1,48 @@
+//! Counting the matching lines of a 100 000-line file: `count_matching`
+//! (`tokio::fs`, a stream of lines) against `count_matching_blocking`
+//! (`std::io` in one `spawn_blocking`).
+//!
+//! Run with `cargo bench --bench files`. The blocking version wins, by
+//! about a tenth here: the async one makes a trip to the blocking pool and
+//! back for each 8 KiB read, some 600 for this file, and polls the stream for
+//! each line, where the blocking one reads the whole file on one thread.
+//! Most of the time goes to splitting and searching the lines, the same work
+//! in both. `tokio::fs` is there to keep the executor free while the disk is
+//! slow, not to read faster.
+
+use async_await::files::{count_matching, count_matching_blocking, write_lines};
+use criterion::{criterion_group, criterion_main, Criterion};
+use tokio::runtime::Runtime;
+
+fn async_against_blocking(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log.txt");
+    let log = (0..100_000).map(|i| {
+        let level = if i % 7 == 0 { "ERROR" } else { "INFO" };
+        format!(
+            "2024-07-25T12:00:00Z {} request {} took {} ms",
+            level,
+            i,
+            i % 250
+        )
+    });
+    runtime.block_on(write_lines(&path, log)).unwrap();
+
+    let mut group = c.benchmark_group("count_matching");
+    group.bench_function("tokio::fs", |b| {
+        b.iter(|| runtime.block_on(count_matching(&path, "ERROR")).unwrap())
+    });
+    group.bench_function("spawn_blocking", |b| {
+        b.iter(|| {
+            let (path, needle) = (path.clone(), String::from("ERROR"));
+            runtime
+                .block_on(count_matching_blocking(path, needle))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, async_against_blocking);
+criterion_main!(benches);
@@ -0,0 +1,194 @@
+//! Stopping async work: a time limit, a race, and a cancellation token.
+//!
+//! A future only runs while something polls it, so stopping one is dropping
+//! it: `with_timeout` drops the future when its time is up, and `race` drops
+//! the loser as soon as the other finishes. Whatever the future was waiting
+//! on is dropped with it: no thread to kill, no flag to check.
+//!
+//! A spawned task is different: the runtime polls it, and nobody else holds
+//! the future. It is asked to stop with a `CancellationToken` instead, which
+//! it waits on next to its work, in a `select!`, and stops at the next
+//! `.await` after the cancel. Tokens form a tree: cancelling one cancels its
+//! children, so stopping a whole service is one call on the root, while each
+//! task can still be stopped on its own through its child token.
+
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// The error of `with_timeout`, with the limit that was reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut(pub Duration);
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "timed out after {:?}", self.0)
+    }
+}
+
+impl Error for TimedOut {}
+
+/// The output of `future`, unless it takes longer than `limit`: then it is
+/// dropped, unfinished.
+pub async fn with_timeout<F: Future>(limit: Duration, future: F) -> Result<F::Output, TimedOut> {
+    time::timeout(limit, future)
+        .await
+        .map_err(|_| TimedOut(limit))
+}
+
+/// Which of two futures finished first, and with what.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Winner<A, B> {
+    First(A),
+    Second(B),
+}
+
+/// Runs both futures until one finishes, and drops the other.
+///
+/// `biased`: `first` is polled first each time, so if both are ready at once,
+/// `first` wins, every time, instead of a random one.
+pub async fn race<A, B>(first: A, second: B) -> Winner<A::Output, B::Output>
+where
+    A: Future,
+    B: Future,
+{
+    tokio::select! {
+        biased;
+        a = first => Winner::First(a),
+        b = second => Winner::Second(b),
+    }
+}
+
+/// The output of `future`, or `None` if `token` is cancelled first.
+pub async fn until_cancelled<F: Future>(token: &CancellationToken, future: F) -> Option<F::Output> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => None,
+        output = future => Some(output),
+    }
+}
+
+/// A spawned task that counts ticks until it is cancelled.
+pub struct Ticker {
+    token: CancellationToken,
+    handle: JoinHandle<u64>,
+}
+
+impl Ticker {
+    /// Spawns the task with a child of `parent`: cancelling `parent` stops it,
+    /// and `stop` stops it alone.
+    pub fn spawn(parent: &CancellationToken, every: Duration) -> Ticker {
+        let token = parent.child_token();
+        let task_token = token.clone();
+        let handle = tokio::spawn(async move {
+            let mut ticks = 0;
+            while until_cancelled(&task_token, time::sleep(every))
+                .await
+                .is_some()
+            {
+                ticks += 1;
+            }
+            ticks
+        });
+        Ticker { token, handle }
+    }
+
+    pub fn stop(&self) {
+        self.token.cancel();
+    }
+
+    /// Waits for the task to stop, and returns its count.
+    ///
+    /// # Panics
+    ///
+    /// If the task panicked.
+    pub async fn join(self) -> u64 {
+        self.handle.await.expect("the ticker panicked")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    async fn after<T>(millis: u32, value: T) -> T {
+        time::sleep(MS * millis).await;
+        value
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_with_timeout() {
+        assert_eq!(with_timeout(MS * 50, after(20, "done")).await, Ok("done"));
+
+        let start = Instant::now();
+        let error = with_timeout(MS * 50, after(100, "late")).await.unwrap_err();
+        assert_eq!(error, TimedOut(MS * 50));
+        assert_eq!(error.to_string(), "timed out after 50ms");
+        // Given up at the limit, not when the future would have finished.
+        assert_eq!(start.elapsed(), MS * 50);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_race() {
+        assert_eq!(
+            race(after(30, 1), after(10, "b")).await,
+            Winner::Second("b")
+        );
+        assert_eq!(race(after(10, 1), after(30, "b")).await, Winner::First(1));
+        // A tie goes to the first.
+        assert_eq!(race(after(10, 1), after(10, "b")).await, Winner::First(1));
+
+        // The loser is dropped: its side effect never happens.
+        let mut finished = false;
+        let slow = async {
+            time::sleep(MS * 100).await;
+            finished = true;
+        };
+        let start = Instant::now();
+        assert_eq!(race(slow, after(10, ())).await, Winner::Second(()));
+        assert_eq!(start.elapsed(), MS * 10);
+        assert!(!finished);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_until_cancelled() {
+        let token = CancellationToken::new();
+        assert_eq!(until_cancelled(&token, after(10, 7)).await, Some(7));
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            time::sleep(MS * 20).await;
+            canceller.cancel();
+        });
+        let start = Instant::now();
+        assert_eq!(until_cancelled(&token, after(100, 7)).await, None);
+        assert_eq!(start.elapsed(), MS * 20);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tickers_stop_alone_or_together() {
+        let root = CancellationToken::new();
+        let tickers: Vec<Ticker> = (0..3).map(|_| Ticker::spawn(&root, MS * 10)).collect();
+
+        // Ticks at 10, 20 and 30; the first ticker is stopped at 35.
+        time::sleep(MS * 35).await;
+        tickers[0].stop();
+        assert!(!root.is_cancelled());
+
+        // Two more ticks for the others, then all stopped from the root.
+        time::sleep(MS * 20).await;
+        root.cancel();
+        let mut counts = Vec::new();
+        for ticker in tickers {
+            counts.push(ticker.join().await);
+        }
+        assert_eq!(counts, [3, 5, 5]);
+    }
+}
@@ -0,0 +1,322 @@
+//! `fetch_all`: many downloads at once, streamed to disk, with progress.
+//!
+//! Each download is a job for a `Runner`, so no more than `limit` are open at
+//! a time. A body is written as it arrives, chunk by chunk: a file larger
+//! than memory downloads as well as a small one, and the progress of each is
+//! known as it goes. While one download waits for the network, the others
+//! write theirs.
+//!
+//! A failed download does not stop the others. Each ends up in the
+//! `Summary`, downloaded or failed, and a failed one leaves no partial file
+//! behind.
+
+use crate::runner::Runner;
+use reqwest::{Client, StatusCode, Url};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// A file downloaded in full.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Download {
+    pub url: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Why a download failed.
+#[derive(Debug)]
+pub enum FetchError {
+    /// No response, or the connection broke during the body.
+    Http(reqwest::Error),
+    /// A response, but not a success.
+    Status(StatusCode),
+    /// The file could not be written.
+    Io(io::Error),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // reqwest's own message is vague ("error sending request"): the
+            // reason is in its sources, such as "dns error" or "connection refused".
+            FetchError::Http(e) => {
+                write!(f, "{}", e)?;
+                let mut source = e.source();
+                while let Some(cause) = source {
+                    write!(f, ": {}", cause)?;
+                    source = cause.source();
+                }
+                Ok(())
+            }
+            FetchError::Status(status) => write!(f, "the server answered {}", status),
+            FetchError::Io(e) => write!(f, "cannot write the file: {}", e),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FetchError::Http(e) => Some(e),
+            FetchError::Status(_) => None,
+            FetchError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        FetchError::Http(e)
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(e: io::Error) -> Self {
+        FetchError::Io(e)
+    }
+}
+
+/// A download that failed, and why.
+#[derive(Debug)]
+pub struct Failure {
+    pub url: String,
+    pub error: FetchError,
+}
+
+/// Every download of a `fetch_all`, in the order they ended.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub downloaded: Vec<Download>,
+    pub failed: Vec<Failure>,
+}
+
+impl Summary {
+    pub fn bytes(&self) -> u64 {
+        self.downloaded.iter().map(|download| download.bytes).sum()
+    }
+}
+
+/// Where a download stands, sent after each chunk written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The index of the URL in the list given to `fetch_all`.
+    pub index: usize,
+    pub received: u64,
+    /// The size announced by the server, if it did.
+    pub total: Option<u64>,
+}
+
+/// Downloads each of `urls` into `dir`, `limit` at a time, sending the
+/// progress of each to `progress`; dropping its receiver is fine.
+///
+/// The files are named after the last segment of their URL, after their
+/// index so that two URLs ending alike do not overwrite each other:
+/// `0-index.html`, `1-logo.svg`.
+///
+/// # Panics
+///
+/// If `limit` is 0.
+pub async fn fetch_all(
+    client: &Client,
+    urls: &[String],
+    dir: &Path,
+    limit: usize,
+    progress: UnboundedSender<Progress>,
+) -> Summary {
+    let jobs = urls.iter().enumerate().map(|(index, url)| {
+        let (client, url, progress) = (client.clone(), url.clone(), progress.clone());
+        let path = dir.join(file_name(index, &url));
+        async move {
+            match download(&client, &url, &path, index, &progress).await {
+                Ok(bytes) => Ok(Download { url, path, bytes }),
+                Err(error) => {
+                    // Whatever was written of it is of no use.
+                    let _ = fs::remove_file(&path).await;
+                    Err(Failure { url, error })
+                }
+            }
+        }
+    });
+    let outcome = Runner::new(limit).run(jobs).await;
+
+    let mut summary = Summary::default();
+    for (_, result) in outcome.finished {
+        match result {
+            Ok(download) => summary.downloaded.push(download),
+            Err(failure) => summary.failed.push(failure),
+        }
+    }
+    summary
+}
+
+/// Streams the body of `url` into a new file at `path`; returns its size.
+async fn download(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    index: usize,
+    progress: &UnboundedSender<Progress>,
+) -> Result<u64, FetchError> {
+    let mut response = client.get(url).send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::Status(response.status()));
+    }
+    let total = response.content_length();
+    let mut file = File::create(path).await?;
+    let mut received = 0;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+        received += chunk.len() as u64;
+        let _ = progress.send(Progress {
+            index,
+            received,
+            total,
+        });
+    }
+    // A `tokio::fs::File` writes in the background: flushed, the last write
+    // is done, and its error, if any, is seen here.
+    file.flush().await?;
+    Ok(received)
+}
+
+/// `3-logo.svg` for `https://example.com/img/logo.svg?v=2`; `3-download` if
+/// the URL has no file name.
+fn file_name(index: usize, url: &str) -> String {
+    let name = Url::parse(url).ok().and_then(|url| {
+        let last = url.path_segments()?.next_back()?;
+        (!last.is_empty()).then(|| last.to_string())
+    });
+    format!("{}-{}", index, name.as_deref().unwrap_or("download"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    /// The size of `/big`, sent in several chunks.
+    const BIG: usize = 200_000;
+
+    /// A tiny HTTP server: `/big` and `/small` are files, anything else 404.
+    /// Returns its address, `http://127.0.0.1:PORT`.
+    async fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let n = socket.read(&mut buffer).await.unwrap();
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buffer[..n]);
+                    }
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split(' ').nth(1).unwrap_or("/");
+                    let (status, body) = match path {
+                        "/big" => ("200 OK", vec![b'x'; BIG]),
+                        "/small" => ("200 OK", b"hello".to_vec()),
+                        _ => ("404 Not Found", b"not found".to_vec()),
+                    };
+                    let head = format!(
+                        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        body.len()
+                    );
+                    socket.write_all(head.as_bytes()).await.unwrap();
+                    socket.write_all(&body).await.unwrap();
+                });
+            }
+        });
+        address
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name(0, "https://example.com/a/logo.svg"), "0-logo.svg");
+        assert_eq!(file_name(1, "https://example.com/a.txt?v=2#top"), "1-a.txt");
+        assert_eq!(file_name(2, "https://example.com/dir/"), "2-download");
+        assert_eq!(file_name(3, "https://example.com"), "3-download");
+        assert_eq!(file_name(4, "not a url"), "4-download");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all() {
+        let address = serve().await;
+        let urls: Vec<String> = ["/big", "/missing", "/small", "/big"]
+            .iter()
+            .map(|path| format!("{}{}", address, path))
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let (progress, mut updates) = mpsc::unbounded_channel();
+        let summary = fetch_all(&Client::new(), &urls, dir.path(), 2, progress).await;
+
+        assert_eq!(summary.downloaded.len(), 3);
+        assert_eq!(summary.bytes(), 2 * BIG as u64 + 5);
+        for download in &summary.downloaded {
+            let written = std::fs::metadata(&download.path).unwrap().len();
+            assert_eq!(written, download.bytes);
+        }
+        let small = dir.path().join("2-small");
+        assert_eq!(std::fs::read_to_string(small).unwrap(), "hello");
+
+        let [failure] = &summary.failed[..] else {
+            panic!("one failure: {:?}", summary.failed);
+        };
+        assert!(failure.url.ends_with("/missing"));
+        assert!(matches!(
+            failure.error,
+            FetchError::Status(StatusCode::NOT_FOUND)
+        ));
+        assert_eq!(
+            failure.error.to_string(),
+            "the server answered 404 Not Found"
+        );
+        assert!(!dir.path().join("1-missing").exists());
+
+        // Every update of the big files is announced with its size, and the
+        // last one of each is complete.
+        let mut last = [0; 4];
+        while let Ok(update) = updates.try_recv() {
+            assert!(update.received > last[update.index]);
+            last[update.index] = update.received;
+            if update.index != 2 {
+                assert_eq!(update.total, Some(BIG as u64));
+            }
+        }
+        assert_eq!(last, [BIG as u64, 0, 5, BIG as u64]);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable() {
+        // Bound, then closed at once: nothing listens on that port.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        drop(listener);
+        let dir = tempfile::tempdir().unwrap();
+        let (progress, _) = mpsc::unbounded_channel();
+        let summary = fetch_all(&Client::new(), &[url], dir.path(), 1, progress).await;
+        assert!(summary.downloaded.is_empty());
+        let error = &summary.failed[0].error;
+        assert!(matches!(error, FetchError::Http(_)));
+        assert!(
+            error
+                .to_string()
+                .to_lowercase()
+                .contains("connection refused"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Async file I/O with `tokio::fs`, and the lines of a file as a `Stream`.
+//!
+//! Operating systems have no portable async API for files, so `tokio::fs`
+//! runs each operation on tokio's blocking thread pool and awaits the result:
+//! the executor's threads are never stuck on a slow disk, but each call pays
+//! for a trip to another thread. Buffering (`BufReader`, `BufWriter`) keeps
+//! the trips few, one per 8 KiB rather than one per line.
+//!
+//! `lines_stream` gives the lines one at a time, as they are read: a `Stream`
+//! is to async code what an `Iterator` is to sync code, and `StreamExt` has
+//! the same adapters, `filter`, `map`, `take`..., whose closures may
+//! themselves await. A file of any size is read in constant memory.
+//!
+//! `count_matching_blocking` does the same count with `std::io` inside one
+//! `spawn_blocking`: a single trip for the whole file. `benches/files.rs`
+//! compares the two.
+
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio_stream::wrappers::LinesStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Writes `lines` to a new file at `path`, each followed by `\n`, replacing
+/// any file there.
+pub async fn write_lines<I>(path: &Path, lines: I) -> io::Result<()>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut file = BufWriter::new(File::create(path).await?);
+    for line in lines {
+        file.write_all(line.as_ref().as_bytes()).await?;
+        file.write_all(b"\n").await?;
+    }
+    // Dropping a `BufWriter` does not write what it holds: only `flush` does.
+    file.flush().await
+}
+
+/// Adds `line` at the end of the file at `path`, created if need be.
+pub async fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(format!("{}\n", line).as_bytes()).await?;
+    file.flush().await
+}
+
+/// Every line of the file at `path`, all at once.
+pub async fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    lines_stream(path).await?.collect().await
+}
+
+/// The lines of the file at `path`, without their `\n`, read as the stream
+/// is polled.
+pub async fn lines_stream(path: &Path) -> io::Result<impl Stream<Item = io::Result<String>>> {
+    let file = File::open(path).await?;
+    Ok(LinesStream::new(BufReader::new(file).lines()))
+}
+
+/// The lines of the file at `path` that contain `needle`.
+pub async fn count_matching(path: &Path, needle: &str) -> io::Result<usize> {
+    let mut lines = lines_stream(path).await?;
+    let mut count = 0;
+    while let Some(line) = lines.next().await {
+        if line?.contains(needle) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// `count_matching` with `std::io`, on the blocking pool in one go.
+///
+/// # Panics
+///
+/// If the blocking task panicked.
+pub async fn count_matching_blocking(path: PathBuf, needle: String) -> io::Result<usize> {
+    tokio::task::spawn_blocking(move || {
+        let file = io::BufReader::new(fs::File::open(path)?);
+        let mut count = 0;
+        for line in file.lines() {
+            if line?.contains(&needle) {
+                count += 1;
+            }
+        }
+        Ok(count)
+    })
+    .await
+    .expect("the blocking count panicked")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_append_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        write_lines(&path, ["first", "second"]).await.unwrap();
+        append_line(&path, "third").await.unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(&path).await.unwrap(),
+            "first\nsecond\nthird\n"
+        );
+        assert_eq!(
+            read_lines(&path).await.unwrap(),
+            ["first", "second", "third"]
+        );
+
+        // Replaced, not added to.
+        write_lines(&path, Vec::<String>::new()).await.unwrap();
+        assert!(read_lines(&path).await.unwrap().is_empty());
+
+        let appended = dir.path().join("new.txt");
+        append_line(&appended, "only").await.unwrap();
+        assert_eq!(read_lines(&appended).await.unwrap(), ["only"]);
+    }
+
+    #[tokio::test]
+    async fn test_lines_stream_with_adapters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.txt");
+        let log: Vec<String> = (0..1000)
+            .map(|i| format!("{} {}", if i % 3 == 0 { "ERROR" } else { "INFO" }, i))
+            .collect();
+        write_lines(&path, &log).await.unwrap();
+
+        let errors: Vec<String> = lines_stream(&path)
+            .await
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|line| line.starts_with("ERROR"))
+            .take(3)
+            .collect()
+            .await;
+        assert_eq!(errors, ["ERROR 0", "ERROR 3", "ERROR 6"]);
+
+        assert_eq!(count_matching(&path, "ERROR").await.unwrap(), 334);
+        assert_eq!(
+            count_matching_blocking(path.clone(), String::from("ERROR"))
+                .await
+                .unwrap(),
+            334
+        );
+        // A last line without `\n` is a line all the same, and `\r\n` is cut.
+        tokio::fs::write(&path, "a\r\nb").await.unwrap();
+        assert_eq!(read_lines(&path).await.unwrap(), ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.txt");
+        let error = count_matching(&missing, "x").await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let error = count_matching_blocking(missing, String::from("x"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
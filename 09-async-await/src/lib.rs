@@ -0,0 +1,11 @@
+//! The reusable parts of the async examples; `main.rs` runs them.
+
+pub mod cancel;
+pub mod fetch;
+pub mod files;
+pub mod pipeline;
+pub mod runner;
+pub mod scheduler;
+pub mod stream;
+pub mod supervisor;
+pub mod weather;
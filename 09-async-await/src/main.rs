@@ -1,9 +1,23 @@
+use async_await::cancel::{self, Ticker};
+use async_await::fetch::{self, Progress};
+use async_await::files;
+use async_await::pipeline::{self, Config};
+use async_await::runner::Runner;
+use async_await::scheduler::{MissedTickBehavior, Scheduler, Spec};
+use async_await::stream::{self, Sequence};
+use async_await::supervisor::{Restart, RestartPolicy, Supervisor};
+use async_await::weather::{self, MockProvider, OpenMeteo, WeatherProvider};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio_stream::StreamExt;
+use tokio_util::sync::CancellationToken;
 
 /// The entry point of an async Rust program using the Tokio runtime.
 /// The #[tokio::main] macro sets up the executor that runs async tasks.
 #[tokio::main]
 async fn main() {
+    // --- 1. Spawning Tasks and Waiting for Them ---
     println!("Starting async tasks...");
 
     // tokio::spawn schedules a task to run concurrently on the executor.
@@ -19,6 +33,289 @@ async fn main() {
     let _ = tokio::join!(task1, task2);
 
     println!("All tasks completed.");
+
+    // --- 2. Many Jobs, a Few at a Time ---
+    // Eight simulated downloads, three at a time. Results arrive in the order
+    // the jobs finish, not the order they were given.
+    let sizes = [300, 100, 200, 50, 250, 150, 100, 50];
+    let jobs = |fail: Option<usize>| {
+        sizes
+            .into_iter()
+            .enumerate()
+            .map(move |(i, ms)| download(i, ms, fail == Some(i)))
+    };
+    let runner = Runner::new(3);
+    let outcome = runner.run(jobs(None)).await;
+    let order: Vec<usize> = outcome.finished.iter().map(|(i, _)| *i).collect();
+    println!(
+        "\n{} at a time, finished in the order {:?}",
+        runner.limit(),
+        order
+    );
+
+    // The same with one failure: by default the others still run; with
+    // `cancel_on_error` the ones left are stopped.
+    for runner in [Runner::new(3), Runner::new(3).cancel_on_error()] {
+        let outcome = runner.run(jobs(Some(2))).await;
+        println!(
+            "{:?}: {} finished, cancelled {:?}",
+            outcome.first_error().unwrap(),
+            outcome.finished.len(),
+            outcome.cancelled
+        );
+    }
+
+    // --- 3. Timeouts, Races and Cancellation ---
+    // A future that is no longer wanted is dropped; a spawned task is asked to
+    // stop through its token.
+    println!();
+    let slow = download(0, 500, false);
+    match cancel::with_timeout(Duration::from_millis(100), slow).await {
+        Ok(file) => println!("Downloaded {:?}", file),
+        Err(error) => println!("Download: {}", error),
+    }
+    let winner = cancel::race(download(1, 200, false), download(2, 50, false)).await;
+    println!("Mirror race: {:?}", winner);
+
+    let root = CancellationToken::new();
+    let tickers: Vec<Ticker> = (0..3)
+        .map(|_| Ticker::spawn(&root, Duration::from_millis(20)))
+        .collect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    tickers[0].stop();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    root.cancel();
+    let mut ticks = Vec::new();
+    for ticker in tickers {
+        ticks.push(ticker.join().await);
+    }
+    println!("Ticks, the first ticker stopped halfway: {:?}", ticks);
+
+    // --- 4. An Async Pipeline, Shut Down Gracefully ---
+    // An endless log, a line every millisecond, parsed slower than that. At
+    // the signal, the source stops, and what is queued is still counted.
+    let paths = ["/", "/about", "/login", "/missing"];
+    let log = (0..).map(move |i: usize| {
+        let status = if i % 4 == 3 { 404 } else { 200 };
+        format!("GET {} {} {}", paths[i % 4], status, 5 + i % 20)
+    });
+    let config = Config {
+        capacity: 32,
+        workers: 4,
+        produce_every: Duration::from_millis(1),
+        parse_time: Duration::from_millis(20),
+    };
+    let shutdown = CancellationToken::new();
+    let signal = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        signal.cancel();
+    });
+    println!("\n{}", pipeline::run(log, config, shutdown).await);
+
+    // --- 5. Downloads, Streamed to Disk ---
+    // The URLs given on the command line, or a few pages of rust-lang.org and
+    // one that does not exist, two at a time. Without a network, each fails,
+    // and the failures are listed with their reason.
+    let mut urls: Vec<String> = std::env::args().skip(1).collect();
+    if urls.is_empty() {
+        urls = [
+            "https://www.rust-lang.org/",
+            "https://www.rust-lang.org/learn",
+            "https://www.rust-lang.org/no-such-page",
+            "https://www.rust-lang.org/static/images/rust-logo-blk.svg",
+        ]
+        .map(String::from)
+        .to_vec();
+    }
+    let dir = std::env::temp_dir().join("async-await-downloads");
+    if let Err(error) = tokio::fs::create_dir_all(&dir).await {
+        println!("Cannot create {}: {}", dir.display(), error);
+        return;
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("cannot build the HTTP client");
+    let (progress, mut updates) = tokio::sync::mpsc::unbounded_channel::<Progress>();
+    let printer = tokio::spawn(async move {
+        while let Some(update) = updates.recv().await {
+            if Some(update.received) == update.total {
+                println!("  [{}] {} bytes, complete", update.index, update.received);
+            }
+        }
+    });
+    let summary = fetch::fetch_all(&client, &urls, &dir, 2, progress).await;
+    let _ = printer.await;
+    println!(
+        "{} downloaded ({} bytes) into {}, {} failed",
+        summary.downloaded.len(),
+        summary.bytes(),
+        dir.display(),
+        summary.failed.len()
+    );
+    for failure in &summary.failed {
+        println!("  {}: {}", failure.url, failure.error);
+    }
+
+    // --- 6. Async File I/O and a Stream of Lines ---
+    // A log written with tokio::fs, then read back a line at a time, with the
+    // iterator-like adapters of StreamExt.
+    let log_path = dir.join("app.log");
+    let lines = (0..500).map(|i| {
+        let level = if i % 50 == 0 { "ERROR" } else { "INFO" };
+        format!("{} request {}", level, i)
+    });
+    let result = async {
+        files::write_lines(&log_path, lines).await?;
+        files::append_line(&log_path, "ERROR shutting down").await?;
+        let errors: Vec<String> = files::lines_stream(&log_path)
+            .await?
+            .filter_map(Result::ok)
+            .filter(|line| line.starts_with("ERROR"))
+            .skip(8)
+            .collect()
+            .await;
+        let total = files::count_matching(&log_path, "request").await?;
+        Ok::<_, std::io::Error>((errors, total))
+    }
+    .await;
+    match result {
+        Ok((errors, total)) => {
+            println!("\n{} requests logged, the last errors: {:?}", total, errors)
+        }
+        Err(error) => println!("\nCannot use {}: {}", log_path.display(), error),
+    }
+
+    // --- 7. Jobs on a Schedule ---
+    // A heartbeat every 50 ms, and a slow report every 100 ms that takes 150:
+    // it never overlaps itself, and skips the ticks it misses.
+    let mut scheduler = Scheduler::new();
+    let heartbeat = Spec::parse("every 50ms").expect("a valid schedule");
+    let report = Spec::parse("every 100ms after 20ms")
+        .expect("a valid schedule")
+        .missed(MissedTickBehavior::Skip);
+    let jobs = [
+        ("heartbeat", heartbeat, Duration::ZERO),
+        ("report", report, Duration::from_millis(150)),
+    ];
+    for (name, spec, takes) in jobs {
+        let added = scheduler.add(name, spec, move || tokio::time::sleep(takes));
+        added.expect("the names differ");
+    }
+    tokio::time::sleep(Duration::from_millis(240)).await;
+    scheduler.pause("heartbeat");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    println!();
+    for job in scheduler.jobs() {
+        println!(
+            "{:<10} {} runs, {} running{}",
+            job.name,
+            job.runs,
+            job.running,
+            if job.paused { ", paused" } else { "" }
+        );
+    }
+    scheduler.shutdown().await;
+
+    // --- 8. An Async Service Behind a Trait ---
+    // The same comparison against the real weather API, and against a mock.
+    // Without a network, the first only lists its failures.
+    let cities = ["Oslo", "Lisbon", "Cairo"];
+    let providers: Vec<(&str, Arc<dyn WeatherProvider>)> = vec![
+        (
+            "open-meteo.com",
+            Arc::new(OpenMeteo::new().expect("cannot build the HTTP client")),
+        ),
+        (
+            "the mock",
+            Arc::new(
+                MockProvider::new()
+                    .with("Oslo", 4.5, 12.0)
+                    .with("Lisbon", 19.0, 8.0)
+                    .failing("Cairo", "rate limited"),
+            ),
+        ),
+    ];
+    println!();
+    for (name, provider) in providers {
+        let comparison = weather::compare(provider, &cities).await;
+        match comparison.warmest() {
+            Some(warmest) => println!(
+                "From {}: warmest in {}, {:.1} °C",
+                name, warmest.city, warmest.temperature_c
+            ),
+            None => println!("From {}: no weather at all", name),
+        }
+        for (city, error) in &comparison.failed {
+            println!("  {}: {}", city, error);
+        }
+    }
+
+    // --- 9. Supervised Tasks ---
+    // A connection that fails twice before it holds, and a cache that is
+    // killed from outside: both restarted. Then a worker that panics every
+    // 10 ms, more often than the policy allows: the supervisor gives up.
+    println!();
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let connection = move || {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        async move {
+            if attempt < 3 {
+                return Err(format!("connection refused (attempt {})", attempt));
+            }
+            std::future::pending().await
+        }
+    };
+    let supervisor = Supervisor::new(RestartPolicy::default())
+        .child("connection", Restart::Permanent, connection)
+        .child("cache", Restart::Permanent, std::future::pending)
+        .start();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    supervisor.kill("cache");
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    println!("Restarts: {:?}", supervisor.restarts());
+    let _ = supervisor.shutdown().await;
+
+    // The default panic hook would print each of the worker's panics; they
+    // are caught and reported by the supervisor, so a silent one is set.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let crashing = Supervisor::new(RestartPolicy {
+        max_restarts: 3,
+        within: Duration::from_secs(1),
+    })
+    .child("worker", Restart::Permanent, || async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        panic!("worker crashed");
+    })
+    .start();
+    if let Err(escalation) = crashing.join().await {
+        println!("Escalated: {}", escalation);
+    }
+    std::panic::set_hook(default_hook);
+
+    // --- 10. Streams ---
+    // A counter written by hand, ticking every 5 ms, throttled to one item
+    // per 10 ms, buffered 8 ahead of a consumer that takes 50 ms per batch,
+    // in batches of up to 4, each collected for at most 25 ms.
+    println!();
+    let sequence = Sequence::new(Duration::from_millis(5));
+    let produced = sequence.produced();
+    let throttled = sequence.throttle(Duration::from_millis(10));
+    let batches = stream::buffer(throttled, 8).chunks_timeout(4, Duration::from_millis(25));
+    tokio::pin!(batches);
+    let start = std::time::Instant::now();
+    for _ in 0..4 {
+        let batch = batches.next().await.unwrap();
+        println!(
+            "Batch {:?} at {:>3} ms, {:>2} items produced",
+            batch,
+            start.elapsed().as_millis(),
+            produced.get()
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
 }
 
 /// A simulated asynchronous workload.
@@ -31,6 +328,16 @@ async fn do_work(name: &str, seconds: u64) {
     println!("{} finished.", name);
 }
 
+/// Pretends to download file `i` in `millis` ms, and fails if `fail`.
+async fn download(i: usize, millis: u64, fail: bool) -> Result<String, String> {
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    if fail {
+        Err(format!("file {} failed", i))
+    } else {
+        Ok(format!("file {}", i))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,398 @@
+//! An async pipeline over bounded `mpsc` channels, and a shutdown that
+//! finishes what was started.
+//!
+//! The same access log as the threaded pipeline of `07-concurrency`: lines
+//! are produced, parsed by a few workers, and counted per path. Each stage is
+//! a task, not a thread, and a full channel makes `send` wait with `.await`,
+//! which frees the executor for the stages downstream instead of blocking a
+//! thread.
+//!
+//! Shutting down is two steps. The shutdown token stops the producer only:
+//! it sends no more lines, and drops its sender. What is already in the
+//! channels is still taken and processed, and each stage stops once its input
+//! is closed and empty, as in `07`, down to the counter. Nothing accepted is
+//! lost; `Report::drained` says how much was still queued at the signal.
+//!
+//! Each channel's depth is sampled at every send: always at its capacity
+//! means the stage after it is the bottleneck, always near 0 that it waits
+//! for the stage before.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// A line of the log: `GET /index.html 200 12`, the last number in ms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub millis: u64,
+}
+
+/// The requests to one path that succeeded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PathTotals {
+    pub requests: u64,
+    pub millis: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// Items each channel holds before its sender waits.
+    pub capacity: usize,
+    /// Parse workers.
+    pub workers: usize,
+    /// The time between two lines of the source.
+    pub produce_every: Duration,
+    /// The time a worker takes to parse a line.
+    pub parse_time: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            capacity: 16,
+            workers: 2,
+            produce_every: Duration::ZERO,
+            parse_time: Duration::ZERO,
+        }
+    }
+}
+
+/// How full a channel was, sampled at each send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueMetrics {
+    pub name: &'static str,
+    pub capacity: usize,
+    pub sent: u64,
+    pub max_depth: usize,
+    pub mean_depth: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub totals: BTreeMap<String, PathTotals>,
+    /// Lines taken from the source.
+    pub produced: u64,
+    /// Lines that did not parse, and requests that failed.
+    pub rejected: u64,
+    /// Items in the channels when the shutdown came, processed all the same.
+    pub drained: usize,
+    pub queues: Vec<QueueMetrics>,
+}
+
+/// A sender that records the depth of its channel after each send.
+struct MeteredSender<T> {
+    sender: Sender<T>,
+    depth: Arc<Depth>,
+}
+
+#[derive(Default)]
+struct Depth {
+    sent: AtomicU64,
+    sum: AtomicU64,
+    max: AtomicUsize,
+}
+
+// Written out: derived, it would require `T: Clone`.
+impl<T> Clone for MeteredSender<T> {
+    fn clone(&self) -> Self {
+        MeteredSender {
+            sender: self.sender.clone(),
+            depth: Arc::clone(&self.depth),
+        }
+    }
+}
+
+impl<T> MeteredSender<T> {
+    /// Waits for room in the channel; `false` if the receiver is gone.
+    async fn send(&self, item: T) -> bool {
+        if self.sender.send(item).await.is_err() {
+            return false;
+        }
+        let depth = self.sender.max_capacity() - self.sender.capacity();
+        self.depth.sent.fetch_add(1, Ordering::Relaxed);
+        self.depth.sum.fetch_add(depth as u64, Ordering::Relaxed);
+        self.depth.max.fetch_max(depth, Ordering::Relaxed);
+        true
+    }
+
+    /// The items in the channel now.
+    fn len(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+}
+
+/// A bounded channel whose sender is metered; the handle reads the metrics.
+fn channel<T>(capacity: usize) -> (MeteredSender<T>, Receiver<T>, Arc<Depth>) {
+    let (sender, receiver) = mpsc::channel(capacity);
+    let depth = Arc::new(Depth::default());
+    let sender = MeteredSender {
+        sender,
+        depth: Arc::clone(&depth),
+    };
+    (sender, receiver, depth)
+}
+
+impl Depth {
+    fn metrics(&self, name: &'static str, capacity: usize) -> QueueMetrics {
+        let sent = self.sent.load(Ordering::Relaxed);
+        let sum = self.sum.load(Ordering::Relaxed);
+        QueueMetrics {
+            name,
+            capacity,
+            sent,
+            max_depth: self.max.load(Ordering::Relaxed),
+            mean_depth: if sent == 0 {
+                0.0
+            } else {
+                sum as f64 / sent as f64
+            },
+        }
+    }
+}
+
+/// Runs the lines of `source` through the pipeline until the source runs
+/// out or `shutdown` is cancelled, then drains the channels and returns.
+///
+/// # Panics
+///
+/// If `config` has no workers or a capacity of 0, or if a stage panics.
+pub async fn run<I>(source: I, config: Config, shutdown: CancellationToken) -> Report
+where
+    I: IntoIterator<Item = String> + Send + 'static,
+    I::IntoIter: Send,
+{
+    assert!(
+        config.workers > 0,
+        "the pipeline needs at least one parse worker"
+    );
+    let (lines_out, lines_in, lines_depth) = channel::<String>(config.capacity);
+    let (parsed_out, mut parsed_in, parsed_depth) = channel::<Request>(config.capacity);
+    let rejected = Arc::new(AtomicU64::new(0));
+
+    // The producer: stops at the end of the source, or at the signal. It
+    // returns how much it produced, and what was still queued at the signal.
+    let (queued_lines, queued_requests) = (lines_out.clone(), parsed_out.clone());
+    let producer = tokio::spawn(async move {
+        let mut produced = 0;
+        for line in source {
+            let wait = time::sleep(config.produce_every);
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => {
+                    let drained = queued_lines.len() + queued_requests.len();
+                    return (produced, drained);
+                }
+                _ = wait => {}
+            }
+            if !lines_out.send(line).await {
+                break;
+            }
+            produced += 1;
+        }
+        (produced, 0)
+    });
+
+    // The parse workers share the input; each holds the lock only while
+    // waiting for a line, and lets go of it to parse it.
+    let lines_in = Arc::new(Mutex::new(lines_in));
+    let mut workers = JoinSet::new();
+    for _ in 0..config.workers {
+        let (input, output) = (Arc::clone(&lines_in), parsed_out.clone());
+        let rejected = Arc::clone(&rejected);
+        workers.spawn(async move {
+            loop {
+                let Some(line) = input.lock().await.recv().await else {
+                    return;
+                };
+                time::sleep(config.parse_time).await;
+                match parse_line(&line).filter(|request| request.status < 400) {
+                    Some(request) => {
+                        if !output.send(request).await {
+                            return;
+                        }
+                    }
+                    None => {
+                        rejected.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        });
+    }
+    // Only the workers' clones are left: the aggregate's input closes when
+    // the last of them returns.
+    drop(parsed_out);
+
+    let aggregate = tokio::spawn(async move {
+        let mut totals: BTreeMap<String, PathTotals> = BTreeMap::new();
+        while let Some(request) = parsed_in.recv().await {
+            let entry = totals.entry(request.path).or_default();
+            entry.requests += 1;
+            entry.millis += request.millis;
+        }
+        totals
+    });
+
+    let (produced, drained) = producer.await.expect("the producer panicked");
+    while let Some(worker) = workers.join_next().await {
+        worker.expect("a parse worker panicked");
+    }
+    let totals = aggregate.await.expect("the aggregate panicked");
+    Report {
+        totals,
+        produced,
+        rejected: rejected.load(Ordering::Relaxed),
+        drained,
+        queues: vec![
+            lines_depth.metrics("lines", config.capacity),
+            parsed_depth.metrics("requests", config.capacity),
+        ],
+    }
+}
+
+/// `METHOD PATH STATUS MILLIS`, separated by whitespace; `None` if malformed.
+pub fn parse_line(line: &str) -> Option<Request> {
+    let mut fields = line.split_whitespace();
+    let request = Request {
+        method: fields.next()?.to_string(),
+        path: fields.next()?.to_string(),
+        status: fields.next()?.parse().ok()?,
+        millis: fields.next()?.parse().ok()?,
+    };
+    fields.next().is_none().then_some(request)
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} lines produced, {} rejected, {} drained after the shutdown",
+            self.produced, self.rejected, self.drained
+        )?;
+        for queue in &self.queues {
+            writeln!(
+                f,
+                "{:<10} {:>6} sent, depth {:>4.1} on average, {} at most of {}",
+                queue.name, queue.sent, queue.mean_depth, queue.max_depth, queue.capacity
+            )?;
+        }
+        for (path, totals) in &self.totals {
+            writeln!(
+                f,
+                "{:<16} {:>6} requests, {:>4} ms on average",
+                path,
+                totals.requests,
+                totals.millis / totals.requests
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` lines over two paths, every tenth a failed request.
+    fn log(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| {
+                let path = if i % 2 == 0 { "/" } else { "/about" };
+                let status = if i % 10 == 9 { 500 } else { 200 };
+                format!("GET {} {} 10", path, status)
+            })
+            .collect()
+    }
+
+    fn requests(report: &Report) -> u64 {
+        report.totals.values().map(|totals| totals.requests).sum()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("GET /index.html 200 12"),
+            Some(Request {
+                method: String::from("GET"),
+                path: String::from("/index.html"),
+                status: 200,
+                millis: 12,
+            })
+        );
+        for malformed in ["", "GET /", "GET / ok 12", "GET / 200 12 extra"] {
+            assert_eq!(parse_line(malformed), None, "{:?}", malformed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_whole_source() {
+        let mut source = log(100);
+        source.push(String::from("garbage"));
+        let report = run(source, Config::default(), CancellationToken::new()).await;
+        assert_eq!(report.produced, 101);
+        assert_eq!(report.rejected, 11);
+        assert_eq!(
+            report.totals["/"],
+            PathTotals {
+                requests: 50,
+                millis: 500
+            }
+        );
+        assert_eq!(report.totals["/about"].requests, 40);
+        assert_eq!(report.drained, 0);
+        assert_eq!(report.queues[0].sent, 101);
+        assert_eq!(report.queues[1].sent, 90);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_drains_what_was_sent() {
+        // A line every ms, parsed in 10 ms by 2 workers: the lines channel
+        // fills up, and is full when the signal comes.
+        let config = Config {
+            capacity: 8,
+            workers: 2,
+            produce_every: Duration::from_millis(1),
+            parse_time: Duration::from_millis(10),
+        };
+        let shutdown = CancellationToken::new();
+        let signal = shutdown.clone();
+        tokio::spawn(async move {
+            time::sleep(Duration::from_millis(100)).await;
+            signal.cancel();
+        });
+        let report = run(log(10_000), config, shutdown).await;
+
+        assert!(report.produced < 10_000);
+        assert_eq!(report.drained, 8);
+        // Every line produced was parsed and counted, the queued ones too.
+        assert_eq!(report.queues[0].sent, report.produced);
+        assert_eq!(requests(&report) + report.rejected, report.produced);
+        // The parse stage is the bottleneck: its input stays full.
+        assert_eq!(report.queues[0].max_depth, 8);
+        assert!(report.queues[0].mean_depth > 6.0);
+        assert!(report.queues[1].max_depth <= 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_before_the_first_line() {
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        let config = Config {
+            produce_every: Duration::from_millis(1),
+            ..Config::default()
+        };
+        let report = run(log(10), config, shutdown).await;
+        assert_eq!(report.produced, 0);
+        assert!(report.totals.is_empty());
+        assert_eq!(report.queues[0].mean_depth, 0.0);
+    }
+}
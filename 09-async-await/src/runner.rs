@@ -0,0 +1,254 @@
+//! `Runner`: many async jobs, at most `limit` of them at a time.
+//!
+//! Spawning every job at once is easy with tokio, and a thousand downloads
+//! started together open a thousand connections. A `Semaphore` with `limit`
+//! permits bounds that: each job is spawned at once, but waits for a permit
+//! before it starts, and gives it back when it ends. A `JoinSet` owns the
+//! tasks, hands back each result as soon as its task finishes, and aborts
+//! whatever is left when asked to, or when it is dropped.
+//!
+//! A failed job either changes nothing, and the others run to the end, or,
+//! with `cancel_on_error`, stops the rest: the running jobs are aborted at
+//! their next `.await`, and the waiting ones never start.
+
+use std::future::Future;
+use std::panic;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::{Id, JoinSet};
+
+/// Runs jobs concurrently, `limit` at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct Runner {
+    limit: usize,
+    cancel_on_error: bool,
+}
+
+/// What became of the jobs of one `run`.
+#[derive(Debug)]
+pub struct Outcome<T, E> {
+    /// Each finished job's index and result, in the order they finished.
+    pub finished: Vec<(usize, Result<T, E>)>,
+    /// The indices of the jobs stopped by an error, in order; always empty
+    /// without `cancel_on_error`.
+    pub cancelled: Vec<usize>,
+}
+
+impl<T, E> Outcome<T, E> {
+    /// The first error to happen, if any did.
+    pub fn first_error(&self) -> Option<&E> {
+        self.finished
+            .iter()
+            .find_map(|(_, result)| result.as_ref().err())
+    }
+}
+
+impl Runner {
+    /// # Panics
+    ///
+    /// If `limit` is 0: no job would ever start.
+    pub fn new(limit: usize) -> Runner {
+        assert!(
+            limit > 0,
+            "a runner needs to run at least one job at a time"
+        );
+        Runner {
+            limit,
+            cancel_on_error: false,
+        }
+    }
+
+    /// Stops the jobs left at the first error, instead of running them all.
+    pub fn cancel_on_error(mut self) -> Runner {
+        self.cancel_on_error = true;
+        self
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Runs `jobs` and waits for all of them, or, with `cancel_on_error`,
+    /// until one fails.
+    ///
+    /// A future does nothing until polled, so a job can be passed as the
+    /// future itself: `runner.run(urls.map(|url| fetch(url)))`.
+    ///
+    /// # Panics
+    ///
+    /// If a job panics: the panic is resumed here, once the others are
+    /// aborted.
+    pub async fn run<I, F, T, E>(&self, jobs: I) -> Outcome<T, E>
+    where
+        I: IntoIterator<Item = F>,
+        F: Future<Output = Result<T, E>> + Send + 'static,
+        T: Send + 'static,
+        E: Send + 'static,
+    {
+        let permits = Arc::new(Semaphore::new(self.limit));
+        let mut tasks = JoinSet::new();
+        // The task ids, in the order of the jobs: an aborted task only
+        // comes back with its id.
+        let ids: Vec<Id> = jobs
+            .into_iter()
+            .map(|job| {
+                let permits = Arc::clone(&permits);
+                tasks
+                    .spawn(async move {
+                        // Held until the job returns, or its task is aborted.
+                        let _permit = permits.acquire_owned().await.expect("never closed");
+                        job.await
+                    })
+                    .id()
+            })
+            .collect();
+        let index = |id: Id| ids.iter().position(|&other| other == id).unwrap();
+
+        let mut outcome = Outcome {
+            finished: Vec::new(),
+            cancelled: Vec::new(),
+        };
+        while let Some(joined) = tasks.join_next_with_id().await {
+            match joined {
+                Ok((id, result)) => {
+                    if result.is_err() && self.cancel_on_error {
+                        tasks.abort_all();
+                    }
+                    outcome.finished.push((index(id), result));
+                }
+                Err(error) if error.is_cancelled() => outcome.cancelled.push(index(error.id())),
+                Err(error) => {
+                    tasks.abort_all();
+                    panic::resume_unwind(error.into_panic());
+                }
+            }
+        }
+        outcome.cancelled.sort_unstable();
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::time::{self, Instant};
+
+    /// Counts the jobs running at once, and the most there were.
+    #[derive(Default)]
+    struct Gauge {
+        now: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    /// One running job, for as long as it lives: dropped when the job
+    /// returns, and also when its task is aborted.
+    struct Running(Arc<Gauge>);
+
+    impl Running {
+        fn start(gauge: Arc<Gauge>) -> Running {
+            let now = gauge.now.fetch_add(1, Ordering::SeqCst) + 1;
+            gauge.peak.fetch_max(now, Ordering::SeqCst);
+            Running(gauge)
+        }
+    }
+
+    impl Drop for Running {
+        fn drop(&mut self) {
+            self.0.now.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Sleeps `millis`, then fails if `fail`: the index either way.
+    async fn job(gauge: Arc<Gauge>, index: usize, millis: u64, fail: bool) -> Result<usize, usize> {
+        let _running = Running::start(gauge);
+        time::sleep(Duration::from_millis(millis)).await;
+        if fail {
+            Err(index)
+        } else {
+            Ok(index)
+        }
+    }
+
+    fn indices<T, E>(outcome: &Outcome<T, E>) -> Vec<usize> {
+        outcome.finished.iter().map(|(i, _)| *i).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_limit_and_completion_order() {
+        let gauge = Arc::new(Gauge::default());
+        let millis = [40, 10, 30, 20, 10, 10];
+        let start = Instant::now();
+        let outcome = Runner::new(2)
+            .run(
+                millis
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &ms)| job(gauge.clone(), i, ms, false)),
+            )
+            .await;
+        assert_eq!(gauge.peak.load(Ordering::SeqCst), 2);
+        // 0 runs alone from 0 to 40; the others follow each other in the
+        // second slot: 1 ends at 10, 2 at 40, 3 at 60, 4 at 50 after 0, 5 at 60.
+        assert_eq!(indices(&outcome), [1, 0, 2, 4, 3, 5]);
+        assert_eq!(start.elapsed(), Duration::from_millis(60));
+        assert!(outcome.cancelled.is_empty());
+        assert!(outcome.first_error().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_errors_without_cancelling() {
+        let gauge = Arc::new(Gauge::default());
+        let outcome = Runner::new(3)
+            .run((0..6).map(|i| job(gauge.clone(), i, 10 * (i as u64 + 1), i == 1)))
+            .await;
+        assert_eq!(outcome.finished.len(), 6);
+        assert!(outcome.cancelled.is_empty());
+        assert_eq!(outcome.first_error(), Some(&1));
+        let failed: Vec<usize> = outcome
+            .finished
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(i, _)| *i)
+            .collect();
+        assert_eq!(failed, [1]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancel_on_error() {
+        let gauge = Arc::new(Gauge::default());
+        let start = Instant::now();
+        // 0, 1 and 2 start together; 1 fails at 20, while 0 and 2 still run,
+        // 3 takes the permit 1 gave back, and 4 and 5 wait.
+        let outcome = Runner::new(3)
+            .cancel_on_error()
+            .run((0..6).map(|i| job(gauge.clone(), i, if i == 1 { 20 } else { 100 }, i == 1)))
+            .await;
+        assert_eq!(indices(&outcome), [1]);
+        assert_eq!(outcome.cancelled, [0, 2, 3, 4, 5]);
+        assert_eq!(outcome.first_error(), Some(&1));
+        // Nothing waited for the aborted jobs, and none is left running.
+        assert_eq!(start.elapsed(), Duration::from_millis(20));
+        assert_eq!(gauge.now.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "job 2 panicked")]
+    async fn test_panic_is_resumed() {
+        Runner::new(2)
+            .run((0..4).map(|i| async move {
+                if i == 2 {
+                    panic!("job {} panicked", i);
+                }
+                Ok::<_, ()>(i)
+            }))
+            .await;
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one job")]
+    fn test_zero_limit() {
+        Runner::new(0);
+    }
+}
@@ -0,0 +1,492 @@
+//! `Scheduler`: named async jobs run at intervals, on the tokio runtime.
+//!
+//! Each job has a driver task with a `tokio::time::Interval`, which ticks
+//! every period from the job's first run. By default a job runs inside its
+//! driver, so a run that outlasts its period cannot overlap the next one: the
+//! next tick is simply late. What the interval does about the ticks missed
+//! meanwhile is the job's `MissedTickBehavior`:
+//!
+//! - `Burst`: it makes them all up, one run after the other;
+//! - `Delay`: one run now, and the period counts again from there;
+//! - `Skip`: one run now, and the next on the original schedule.
+//!
+//! A job that allows overlap is instead spawned at each tick, whether or not
+//! the last run has ended, and no tick is missed.
+//!
+//! A paused job still has its ticks, and lets them pass. Shutting down stops
+//! the drivers, and waits for the runs under way to end.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::{self, Instant, Interval};
+use tokio_util::sync::CancellationToken;
+
+pub use tokio::time::MissedTickBehavior;
+
+type Job = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// When a job runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spec {
+    pub every: Duration,
+    /// The wait before the first run.
+    pub after: Duration,
+    pub missed: MissedTickBehavior,
+    pub overlap: bool,
+}
+
+impl Spec {
+    /// Every `period`, from now, one run at a time, missed ticks made up.
+    ///
+    /// # Panics
+    ///
+    /// If `period` is zero.
+    pub fn every(period: Duration) -> Spec {
+        assert!(!period.is_zero(), "a job's period must not be zero");
+        Spec {
+            every: period,
+            after: Duration::ZERO,
+            missed: MissedTickBehavior::Burst,
+            overlap: false,
+        }
+    }
+
+    pub fn after(mut self, delay: Duration) -> Spec {
+        self.after = delay;
+        self
+    }
+
+    pub fn missed(mut self, behavior: MissedTickBehavior) -> Spec {
+        self.missed = behavior;
+        self
+    }
+
+    /// Starts a run at each tick, even if the last one is still running.
+    pub fn allow_overlap(mut self) -> Spec {
+        self.overlap = true;
+        self
+    }
+
+    /// `every 30s`, `every 5m after 1m`: a number and a unit, `ms`, `s`, `m`
+    /// or `h`, for the period and the optional delay.
+    pub fn parse(spec: &str) -> Result<Spec, String> {
+        let words: Vec<&str> = spec.split_whitespace().collect();
+        match words[..] {
+            ["every", period] => Ok(Spec::every(duration(period)?)),
+            ["every", period, "after", delay] => {
+                Ok(Spec::every(duration(period)?).after(duration(delay)?))
+            }
+            _ => Err(format!(
+                "'{}' is not a schedule: use 'every N<unit>' or 'every N<unit> after N<unit>'",
+                spec
+            )),
+        }
+    }
+}
+
+/// `250ms`, `30s`, `5m`, `2h`; not zero.
+fn duration(text: &str) -> Result<Duration, String> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let n: u64 = match number.parse() {
+        Ok(n) if n > 0 => n,
+        _ => return Err(format!("'{}' is not a positive duration", text)),
+    };
+    match unit {
+        "ms" => Ok(Duration::from_millis(n)),
+        "s" => Ok(Duration::from_secs(n)),
+        "m" => Ok(Duration::from_secs(n * 60)),
+        "h" => Ok(Duration::from_secs(n * 3600)),
+        _ => Err(format!("'{}' has no unit: use ms, s, m or h", text)),
+    }
+}
+
+/// Where a job stands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobInfo {
+    pub name: String,
+    pub spec: Spec,
+    /// Runs started.
+    pub runs: u64,
+    /// Runs under way: at most 1 without overlap.
+    pub running: usize,
+    pub paused: bool,
+    pub last_run: Option<Instant>,
+    /// When the next tick is due; it comes later if a run is still under way.
+    pub next_run: Instant,
+}
+
+/// A job's state, shared by the scheduler and the driver.
+struct Shared {
+    info: Mutex<JobInfo>,
+}
+
+pub struct Scheduler {
+    jobs: BTreeMap<String, Arc<Shared>>,
+    drivers: JoinSet<()>,
+    shutdown: CancellationToken,
+}
+
+impl Scheduler {
+    pub fn new() -> Scheduler {
+        Scheduler {
+            jobs: BTreeMap::new(),
+            drivers: JoinSet::new(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Schedules `job` under `name`; it first runs `spec.after` from now.
+    pub fn add<F, Fut>(&mut self, name: &str, spec: Spec, job: F) -> Result<(), String>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if self.jobs.contains_key(name) {
+            return Err(format!("a job is already called '{}'", name));
+        }
+        let start = Instant::now() + spec.after;
+        let shared = Arc::new(Shared {
+            info: Mutex::new(JobInfo {
+                name: name.to_string(),
+                spec,
+                runs: 0,
+                running: 0,
+                paused: false,
+                last_run: None,
+                next_run: start,
+            }),
+        });
+        let mut interval = time::interval_at(start, spec.every);
+        interval.set_missed_tick_behavior(spec.missed);
+        let job: Job = Arc::new(move || Box::pin(job()));
+        self.drivers.spawn(drive(
+            interval,
+            job,
+            Arc::clone(&shared),
+            self.shutdown.clone(),
+        ));
+        self.jobs.insert(name.to_string(), shared);
+        Ok(())
+    }
+
+    /// Lets the job's ticks pass without running it; `false` if there is no
+    /// such job.
+    pub fn pause(&self, name: &str) -> bool {
+        self.set_paused(name, true)
+    }
+
+    pub fn resume(&self, name: &str) -> bool {
+        self.set_paused(name, false)
+    }
+
+    fn set_paused(&self, name: &str, paused: bool) -> bool {
+        match self.jobs.get(name) {
+            Some(shared) => {
+                shared.info.lock().unwrap().paused = paused;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn info(&self, name: &str) -> Option<JobInfo> {
+        let shared = self.jobs.get(name)?;
+        Some(shared.info.lock().unwrap().clone())
+    }
+
+    /// Every job, by name.
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.jobs
+            .values()
+            .map(|shared| shared.info.lock().unwrap().clone())
+            .collect()
+    }
+
+    /// Stops scheduling runs, and waits for those under way.
+    ///
+    /// # Panics
+    ///
+    /// If a job panicked.
+    pub async fn shutdown(mut self) {
+        self.shutdown.cancel();
+        while let Some(driver) = self.drivers.join_next().await {
+            if let Err(error) = driver {
+                std::panic::resume_unwind(error.into_panic());
+            }
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Scheduler::new()
+    }
+}
+
+/// A job's driver: waits for each tick, and runs the job, or spawns it.
+async fn drive(mut interval: Interval, job: Job, shared: Arc<Shared>, shutdown: CancellationToken) {
+    let mut overlapping = JoinSet::new();
+    loop {
+        let scheduled = tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            scheduled = interval.tick() => scheduled,
+        };
+        let now = Instant::now();
+        let overlap = {
+            let mut info = shared.info.lock().unwrap();
+            info.next_run = next_tick(scheduled, now, info.spec.every, info.spec.missed);
+            if info.paused {
+                continue;
+            }
+            info.runs += 1;
+            info.running += 1;
+            info.last_run = Some(now);
+            info.spec.overlap
+        };
+        let run = job();
+        let shared = Arc::clone(&shared);
+        let finish = async move {
+            run.await;
+            shared.info.lock().unwrap().running -= 1;
+        };
+        if overlap {
+            overlapping.spawn(finish);
+        } else {
+            finish.await;
+        }
+    }
+    while let Some(run) = overlapping.join_next().await {
+        if let Err(error) = run {
+            std::panic::resume_unwind(error.into_panic());
+        }
+    }
+}
+
+/// When the tick after one `scheduled` at that time, and given at `now`, is
+/// due: what `Interval` does, for each behavior.
+fn next_tick(
+    scheduled: Instant,
+    now: Instant,
+    period: Duration,
+    missed: MissedTickBehavior,
+) -> Instant {
+    let late = now.saturating_duration_since(scheduled);
+    if late.is_zero() {
+        return scheduled + period;
+    }
+    match missed {
+        // The missed ticks keep their times, and come at once.
+        MissedTickBehavior::Burst => scheduled + period,
+        MissedTickBehavior::Delay => now + period,
+        // The next time on the original grid.
+        MissedTickBehavior::Skip => {
+            let period_nanos = period.as_nanos();
+            let into = Duration::from_nanos((late.as_nanos() % period_nanos) as u64);
+            now + (period - into)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// Adds a job that notes the time of each of its runs, in ms from
+    /// `start`, and takes `first` to run the first time, no time after.
+    fn add_noting(
+        scheduler: &mut Scheduler,
+        spec: Spec,
+        start: Instant,
+        first: Duration,
+    ) -> Arc<Mutex<Vec<u64>>> {
+        let times = Arc::new(Mutex::new(Vec::new()));
+        let noted = Arc::clone(&times);
+        let job = move || {
+            let times = Arc::clone(&noted);
+            async move {
+                let elapsed = start.elapsed().as_millis() as u64;
+                let is_first = {
+                    let mut times = times.lock().unwrap();
+                    times.push(elapsed);
+                    times.len() == 1
+                };
+                if is_first {
+                    time::sleep(first).await;
+                }
+            }
+        };
+        scheduler.add("noting", spec, job).unwrap();
+        times
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Spec::parse("every 30s"),
+            Ok(Spec::every(Duration::from_secs(30)))
+        );
+        assert_eq!(
+            Spec::parse("every 5m after 250ms"),
+            Ok(Spec::every(Duration::from_secs(300)).after(MS * 250))
+        );
+        assert_eq!(
+            Spec::parse("every 2h").unwrap().every,
+            Duration::from_secs(7200)
+        );
+        for (spec, error) in [
+            (
+                "every",
+                "'every' is not a schedule: use 'every N<unit>' or 'every N<unit> after N<unit>'",
+            ),
+            ("every 0s", "'0s' is not a positive duration"),
+            ("every 10", "'10' has no unit: use ms, s, m or h"),
+            ("every 1d", "'1d' has no unit: use ms, s, m or h"),
+            ("every 1s after x", "'x' is not a positive duration"),
+        ] {
+            assert_eq!(Spec::parse(spec), Err(String::from(error)), "{}", spec);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_runs_and_info() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new();
+        let times = add_noting(
+            &mut scheduler,
+            Spec::every(MS * 10).after(MS * 5),
+            start,
+            Duration::ZERO,
+        );
+        assert_eq!(
+            scheduler.add("noting", Spec::every(MS), || async {}),
+            Err(String::from("a job is already called 'noting'"))
+        );
+
+        time::sleep(MS * 40).await;
+        assert_eq!(*times.lock().unwrap(), [5, 15, 25, 35]);
+        let info = scheduler.info("noting").unwrap();
+        assert_eq!(info.runs, 4);
+        assert_eq!(info.running, 0);
+        assert_eq!(info.last_run, Some(start + MS * 35));
+        assert_eq!(info.next_run, start + MS * 45);
+        assert!(scheduler.info("other").is_none());
+        scheduler.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pause_and_resume() {
+        let mut scheduler = Scheduler::new();
+        let runs = Arc::new(AtomicU64::new(0));
+        let counted = Arc::clone(&runs);
+        scheduler
+            .add("count", Spec::every(MS * 10), move || {
+                let counted = Arc::clone(&counted);
+                async move {
+                    counted.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .unwrap();
+
+        // At 0, 10 and 20; not at 30 to 50; again at 60 and 70.
+        time::sleep(MS * 25).await;
+        assert!(scheduler.pause("count"));
+        assert!(scheduler.info("count").unwrap().paused);
+        time::sleep(MS * 30).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert!(scheduler.resume("count"));
+        time::sleep(MS * 20).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 5);
+        assert!(!scheduler.pause("missing"));
+        scheduler.shutdown().await;
+    }
+
+    /// Every 10 ms, the first run taking 35: the ticks at 10, 20 and 30 are
+    /// missed. The run times until 60, for each behavior.
+    async fn missed(behavior: MissedTickBehavior) -> Vec<u64> {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new();
+        let spec = Spec::every(MS * 10).missed(behavior);
+        let times = add_noting(&mut scheduler, spec, start, MS * 35);
+        time::sleep(MS * 65).await;
+        scheduler.shutdown().await;
+        let times = times.lock().unwrap().clone();
+        times
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_missed_tick_behaviors() {
+        assert_eq!(
+            missed(MissedTickBehavior::Burst).await,
+            [0, 35, 35, 35, 40, 50, 60]
+        );
+        assert_eq!(missed(MissedTickBehavior::Delay).await, [0, 35, 45, 55]);
+        assert_eq!(missed(MissedTickBehavior::Skip).await, [0, 35, 40, 50, 60]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_overlap_prevented_or_allowed() {
+        // Each run takes 25 ms, the period is 10.
+        let slow = || async { time::sleep(MS * 25).await };
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add(
+                "one at a time",
+                Spec::every(MS * 10).missed(MissedTickBehavior::Skip),
+                slow,
+            )
+            .unwrap();
+        scheduler
+            .add("overlapping", Spec::every(MS * 10).allow_overlap(), slow)
+            .unwrap();
+        time::sleep(MS * 45).await;
+        let [alone, overlapping] = &scheduler.jobs()[..] else {
+            panic!("two jobs");
+        };
+        // At 0 and 30 (the ticks at 10 and 20 missed, 40 not yet due); at 0,
+        // 10, 20, 30 and 40, three of them still running.
+        assert_eq!((alone.runs, alone.running), (2, 1));
+        assert_eq!((overlapping.runs, overlapping.running), (5, 3));
+
+        // The runs under way end before `shutdown` returns.
+        let start = Instant::now();
+        scheduler.shutdown().await;
+        assert_eq!(start.elapsed(), MS * 20);
+    }
+
+    #[test]
+    fn test_next_tick() {
+        let t0 = Instant::now();
+        let period = MS * 10;
+        for behavior in [
+            MissedTickBehavior::Burst,
+            MissedTickBehavior::Delay,
+            MissedTickBehavior::Skip,
+        ] {
+            assert_eq!(next_tick(t0, t0, period, behavior), t0 + period);
+        }
+        let now = t0 + MS * 25;
+        assert_eq!(
+            next_tick(t0, now, period, MissedTickBehavior::Burst),
+            t0 + MS * 10
+        );
+        assert_eq!(
+            next_tick(t0, now, period, MissedTickBehavior::Delay),
+            t0 + MS * 35
+        );
+        assert_eq!(
+            next_tick(t0, now, period, MissedTickBehavior::Skip),
+            t0 + MS * 30
+        );
+    }
+}
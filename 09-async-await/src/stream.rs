@@ -0,0 +1,227 @@
+//! A `Stream` written by hand, and the adapters that buffer, throttle and
+//! batch it.
+//!
+//! A `Stream` is to `Iterator` what a future is to a plain value: its
+//! `poll_next` either has the next item ready, or registers the task to be
+//! woken when it will, and returns `Pending`. `Sequence` counts 0, 1, 2, ...
+//! on a timer, and does nothing between polls: an item is only made when
+//! someone asks for one. That is where backpressure comes from for free: a
+//! slow consumer polls less often, so the source produces less often.
+//!
+//! The adapters keep that property, or change it on purpose. `throttle` and
+//! `chunks_timeout` (from `tokio_stream::StreamExt`) only poll the stream
+//! they wrap when they need an item. `buffer` is the exception: a task of
+//! its own pulls items into a bounded channel ahead of the consumer, so a
+//! slow source and a slow consumer overlap, but only by as many items as the
+//! channel holds.
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{self, Interval, MissedTickBehavior};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Counts 0, 1, 2, ... at most once every `every`, with no end.
+pub struct Sequence {
+    interval: Interval,
+    next: u64,
+    produced: Produced,
+}
+
+/// How many items a `Sequence` has made so far, readable after the stream
+/// has moved into an adapter.
+#[derive(Debug, Clone, Default)]
+pub struct Produced(Arc<AtomicU64>);
+
+impl Produced {
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Sequence {
+    /// The first item is ready at once.
+    ///
+    /// # Panics
+    ///
+    /// If `every` is zero, as `tokio::time::interval` does.
+    pub fn new(every: Duration) -> Sequence {
+        let mut interval = time::interval(every);
+        // A consumer that comes back late gets one item, then the usual
+        // pace: not a burst of the ticks it missed.
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Sequence {
+            interval,
+            next: 0,
+            produced: Produced::default(),
+        }
+    }
+
+    pub fn produced(&self) -> Produced {
+        self.produced.clone()
+    }
+}
+
+impl Stream for Sequence {
+    type Item = u64;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u64>> {
+        // `poll_tick` registers the waker with the timer when it is not time
+        // yet; `ready!` returns that `Pending` to the caller.
+        ready!(self.interval.poll_tick(cx));
+        let n = self.next;
+        self.next += 1;
+        self.produced.0.fetch_add(1, Ordering::SeqCst);
+        Poll::Ready(Some(n))
+    }
+}
+
+/// The items of `stream`, pulled by a task of their own into a channel of
+/// `capacity`, ahead of the consumer.
+///
+/// When the channel is full the task waits, holding one more item, so the
+/// source is never more than `capacity + 1` items ahead. Dropping the
+/// returned stream stops the task at its next send.
+///
+/// # Panics
+///
+/// If `capacity` is 0, or when called outside a tokio runtime.
+pub fn buffer<S>(stream: S, capacity: usize) -> ReceiverStream<S::Item>
+where
+    S: Stream + Send + 'static,
+    S::Item: Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(capacity);
+    tokio::spawn(async move {
+        tokio::pin!(stream);
+        while let Some(item) = stream.next().await {
+            if sender.send(item).await.is_err() {
+                return;
+            }
+        }
+    });
+    ReceiverStream::new(receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sequence_counts_on_time() {
+        let start = Instant::now();
+        let mut sequence = Sequence::new(Duration::from_millis(10));
+        for expected in 0..4 {
+            assert_eq!(sequence.next().await, Some(expected));
+            assert_eq!(
+                start.elapsed(),
+                Duration::from_millis(10 * expected),
+                "item {}",
+                expected
+            );
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sequence_only_produces_when_polled() {
+        let mut sequence = Sequence::new(Duration::from_millis(1));
+        let produced = sequence.produced();
+        time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(produced.get(), 0);
+
+        // A consumer a hundred times slower than the source sets the pace.
+        for _ in 0..3 {
+            sequence.next().await;
+            time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(produced.get(), 3);
+        // And no burst of the ticks missed meanwhile.
+        let start = Instant::now();
+        sequence.next().await;
+        sequence.next().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_buffer_runs_ahead_by_its_capacity() {
+        let sequence = Sequence::new(Duration::from_millis(1));
+        let produced = sequence.produced();
+        let mut buffered = buffer(sequence, 4);
+
+        assert_eq!(buffered.next().await, Some(0));
+        time::sleep(Duration::from_secs(1)).await;
+        // 4 in the channel, and 1 more waiting to be sent.
+        assert_eq!(produced.get(), 1 + 4 + 1);
+
+        // The buffered items come at once, in order.
+        let start = Instant::now();
+        for expected in 1..=4 {
+            assert_eq!(buffered.next().await, Some(expected));
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_buffer_stops_when_dropped() {
+        let sequence = Sequence::new(Duration::from_millis(1));
+        let produced = sequence.produced();
+        let mut buffered = buffer(sequence, 2);
+        buffered.next().await;
+        drop(buffered);
+
+        time::sleep(Duration::from_millis(10)).await;
+        let stopped_at = produced.get();
+        time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(produced.get(), stopped_at);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_buffer_ends_with_its_stream() {
+        let buffered = buffer(tokio_stream::iter(0..5), 2);
+        assert_eq!(buffered.collect::<Vec<_>>().await, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_throttle_holds_the_source_back() {
+        let sequence = Sequence::new(Duration::from_millis(1));
+        let produced = sequence.produced();
+        let start = Instant::now();
+        let times: Vec<_> = sequence
+            .throttle(Duration::from_millis(10))
+            .take(4)
+            .map(|_| start.elapsed().as_millis())
+            .collect()
+            .await;
+        assert_eq!(times, vec![0, 10, 20, 30]);
+        // Throttling waits before polling, so nothing is made and thrown away.
+        assert_eq!(produced.get(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_chunks_timeout_fills_batches() {
+        let batches: Vec<_> = Sequence::new(Duration::from_millis(5))
+            .chunks_timeout(3, Duration::from_millis(100))
+            .take(2)
+            .collect()
+            .await;
+        assert_eq!(batches, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_chunks_timeout_sends_partial_batches_on_time() {
+        let start = Instant::now();
+        let batches: Vec<_> = Sequence::new(Duration::from_millis(20))
+            .chunks_timeout(3, Duration::from_millis(30))
+            .take(2)
+            .map(|batch| (batch, start.elapsed().as_millis()))
+            .collect()
+            .await;
+        // Each batch waits 30 ms from its first item, which fits only two.
+        assert_eq!(batches, vec![(vec![0, 1], 30), (vec![2, 3], 70)]);
+    }
+}
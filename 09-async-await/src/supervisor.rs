@@ -0,0 +1,438 @@
+//! A supervisor: child tasks started again when they fail, up to a limit.
+//!
+//! The idea comes from Erlang: rather than guard every task against every
+//! failure, let a task fail, and have its supervisor start a fresh one, with
+//! fresh state. A `JoinHandle` tells how a task ended: it returned, it
+//! panicked, or it was aborted. A panic in a tokio task is caught by the
+//! runtime and becomes a `JoinError`, so the supervisor sees it like any
+//! other exit.
+//!
+//! Which exits are restarted depends on the child's `Restart`. How many
+//! restarts are too many is the supervisor's `RestartPolicy`: at most
+//! `max_restarts` in any `within`. A child failing faster than that will not
+//! be cured by one more restart, so the supervisor gives up: it stops every
+//! child and returns an `Escalation`, for whoever started it to deal with.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::{AbortHandle, Id, JoinError, JoinHandle, JoinSet};
+use tokio::time::Instant;
+
+type Child =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync>;
+
+/// Which exits of a child are restarted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restart {
+    /// All of them: the child should run for as long as the supervisor.
+    Permanent,
+    /// Failures only: a child that returns `Ok` is done.
+    Transient,
+    /// None.
+    Temporary,
+}
+
+/// At most `max_restarts` restarts in any `within`, all children together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RestartPolicy {
+    pub max_restarts: usize,
+    pub within: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            max_restarts: 3,
+            within: Duration::from_secs(5),
+        }
+    }
+}
+
+/// How a child's task ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exit {
+    Completed,
+    Failed(String),
+    Panicked(String),
+    Killed,
+}
+
+impl Exit {
+    fn of(result: Result<Result<(), String>, JoinError>) -> Exit {
+        match result {
+            Ok(Ok(())) => Exit::Completed,
+            Ok(Err(error)) => Exit::Failed(error),
+            Err(error) if error.is_cancelled() => Exit::Killed,
+            Err(error) => {
+                let payload = error.into_panic();
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| String::from("a panic without a message"));
+                Exit::Panicked(message)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Exit::Completed => write!(f, "completed"),
+            Exit::Failed(error) => write!(f, "failed: {}", error),
+            Exit::Panicked(message) => write!(f, "panicked: {}", message),
+            Exit::Killed => write!(f, "killed"),
+        }
+    }
+}
+
+/// The supervisor gave up: `child` exited once more than the policy allows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Escalation {
+    pub child: String,
+    pub exit: Exit,
+    pub policy: RestartPolicy,
+}
+
+impl fmt::Display for Escalation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "'{}' {}, after {} restarts in {:?}: giving up",
+            self.child, self.exit, self.policy.max_restarts, self.policy.within
+        )
+    }
+}
+
+impl Error for Escalation {}
+
+/// Each child's restarts, by name.
+pub type Restarts = BTreeMap<String, usize>;
+
+pub struct Supervisor {
+    policy: RestartPolicy,
+    children: Vec<(String, Restart, Child)>,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Supervisor {
+        Supervisor {
+            policy,
+            children: Vec::new(),
+        }
+    }
+
+    /// Adds a child: `start` is called for its first run and for each restart.
+    pub fn child<F, Fut>(mut self, name: &str, restart: Restart, start: F) -> Supervisor
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let child: Child = Arc::new(move || Box::pin(start()));
+        self.children.push((name.to_string(), restart, child));
+        self
+    }
+
+    /// Spawns the supervisor, which spawns the children.
+    pub fn start(self) -> Handle {
+        let (commands, received) = mpsc::unbounded_channel();
+        let restarts: Arc<Mutex<Restarts>> = Arc::new(Mutex::new(
+            self.children
+                .iter()
+                .map(|(name, _, _)| (name.clone(), 0))
+                .collect(),
+        ));
+        let task = tokio::spawn(supervise(self, received, Arc::clone(&restarts)));
+        Handle {
+            commands,
+            restarts,
+            task,
+        }
+    }
+}
+
+enum Command {
+    Kill(String),
+    Shutdown,
+}
+
+/// The running supervisor.
+pub struct Handle {
+    commands: UnboundedSender<Command>,
+    restarts: Arc<Mutex<Restarts>>,
+    task: JoinHandle<Result<Restarts, Escalation>>,
+}
+
+impl Handle {
+    /// Aborts the child's current run, as a crash would.
+    pub fn kill(&self, child: &str) {
+        let _ = self.commands.send(Command::Kill(child.to_string()));
+    }
+
+    /// Each child's restarts so far.
+    pub fn restarts(&self) -> Restarts {
+        self.restarts.lock().unwrap().clone()
+    }
+
+    /// Stops every child, and returns their restarts; or the escalation, if
+    /// the supervisor had already given up.
+    pub async fn shutdown(self) -> Result<Restarts, Escalation> {
+        let _ = self.commands.send(Command::Shutdown);
+        self.join().await
+    }
+
+    /// Waits for the supervisor to end by itself: when no child is left to
+    /// restart, or when it gives up.
+    ///
+    /// # Panics
+    ///
+    /// If the supervisor itself panicked.
+    pub async fn join(self) -> Result<Restarts, Escalation> {
+        self.task.await.expect("the supervisor panicked")
+    }
+}
+
+/// The children's tasks, and which child each runs.
+struct Running {
+    set: JoinSet<Result<(), String>>,
+    children: HashMap<Id, usize>,
+    /// By child: its current task, if it has one.
+    aborts: Vec<Option<AbortHandle>>,
+}
+
+impl Running {
+    fn spawn(&mut self, index: usize, start: &Child) {
+        let abort = self.set.spawn(start());
+        self.children.insert(abort.id(), index);
+        self.aborts[index] = Some(abort);
+    }
+}
+
+async fn supervise(
+    supervisor: Supervisor,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    restarts: Arc<Mutex<Restarts>>,
+) -> Result<Restarts, Escalation> {
+    let Supervisor { policy, children } = supervisor;
+    let mut running = Running {
+        set: JoinSet::new(),
+        children: HashMap::new(),
+        aborts: vec![None; children.len()],
+    };
+    for (index, (_, _, start)) in children.iter().enumerate() {
+        running.spawn(index, start);
+    }
+
+    // When the last restarts happened, the oldest first.
+    let mut recent: VecDeque<Instant> = VecDeque::new();
+    let mut commands_open = true;
+    loop {
+        let (id, result) = tokio::select! {
+            command = commands.recv(), if commands_open => {
+                match command {
+                    Some(Command::Kill(name)) => {
+                        let index = children.iter().position(|(child, _, _)| *child == name);
+                        if let Some(Some(abort)) = index.map(|i| &running.aborts[i]) {
+                            abort.abort();
+                        }
+                    }
+                    Some(Command::Shutdown) => {
+                        running.set.shutdown().await;
+                        break;
+                    }
+                    // The handle is gone: the children run on, unsupervised
+                    // from outside.
+                    None => commands_open = false,
+                }
+                continue;
+            }
+            joined = running.set.join_next_with_id() => match joined {
+                Some(Ok((id, result))) => (id, Ok(result)),
+                Some(Err(error)) => (error.id(), Err(error)),
+                None => break,
+            },
+        };
+        let index = running.children.remove(&id).expect("a task of ours");
+        running.aborts[index] = None;
+        let (name, restart, start) = &children[index];
+        let exit = Exit::of(result);
+        let restart = match restart {
+            Restart::Permanent => true,
+            Restart::Transient => exit != Exit::Completed,
+            Restart::Temporary => false,
+        };
+        if !restart {
+            continue;
+        }
+
+        let now = Instant::now();
+        while recent
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= policy.within)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= policy.max_restarts {
+            running.set.shutdown().await;
+            return Err(Escalation {
+                child: name.clone(),
+                exit,
+                policy,
+            });
+        }
+        recent.push_back(now);
+        *restarts.lock().unwrap().get_mut(name).unwrap() += 1;
+        running.spawn(index, start);
+    }
+    let restarts = restarts.lock().unwrap().clone();
+    Ok(restarts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::time;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// Runs until killed.
+    async fn forever() -> Result<(), String> {
+        std::future::pending().await
+    }
+
+    fn policy(max_restarts: usize, within_ms: u32) -> RestartPolicy {
+        RestartPolicy {
+            max_restarts,
+            within: MS * within_ms,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_killed_children_are_restarted() {
+        let starts = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&starts);
+        let handle = Supervisor::new(policy(3, 1000))
+            .child("worker", Restart::Permanent, move || {
+                counted.fetch_add(1, Ordering::SeqCst);
+                forever()
+            })
+            .child("other", Restart::Permanent, forever)
+            .start();
+
+        time::sleep(MS).await;
+        handle.kill("worker");
+        time::sleep(MS).await;
+        handle.kill("worker");
+        time::sleep(MS).await;
+        assert_eq!(starts.load(Ordering::SeqCst), 3);
+        let restarts = handle.shutdown().await.unwrap();
+        assert_eq!(restarts["worker"], 2);
+        assert_eq!(restarts["other"], 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_restart_kinds() {
+        // Each fails once, then completes.
+        let once = |runs: Arc<AtomicUsize>| {
+            move || {
+                let first = runs.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    if first {
+                        Err(String::from("first run"))
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+        };
+        let runs: Vec<Arc<AtomicUsize>> = (0..3).map(|_| Arc::default()).collect();
+        let handle = Supervisor::new(policy(10, 1000))
+            .child("transient", Restart::Transient, once(runs[0].clone()))
+            .child("temporary", Restart::Temporary, once(runs[1].clone()))
+            .child(
+                "permanent",
+                Restart::Permanent,
+                // Completes every 10 ms; restarted all the same.
+                move || {
+                    runs[2].fetch_add(1, Ordering::SeqCst);
+                    async {
+                        time::sleep(MS * 10).await;
+                        Ok(())
+                    }
+                },
+            )
+            .start();
+        time::sleep(MS * 35).await;
+        let restarts = handle.restarts();
+        assert_eq!(restarts["transient"], 1);
+        assert_eq!(restarts["temporary"], 0);
+        assert_eq!(restarts["permanent"], 3);
+        handle.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_panics_are_restarted_then_escalated() {
+        let handle = Supervisor::new(policy(2, 100))
+            .child("crashing", Restart::Permanent, || async {
+                time::sleep(MS * 10).await;
+                panic!("out of cheese");
+            })
+            .start();
+        // Runs at 0, 10 and 20: the third exit is one more than 2 in 100 ms.
+        let escalation = handle.join().await.unwrap_err();
+        assert_eq!(
+            escalation,
+            Escalation {
+                child: String::from("crashing"),
+                exit: Exit::Panicked(String::from("out of cheese")),
+                policy: policy(2, 100),
+            }
+        );
+        assert_eq!(
+            escalation.to_string(),
+            "'crashing' panicked: out of cheese, after 2 restarts in 100ms: giving up"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_restarts_spread_out_are_allowed() {
+        let handle = Supervisor::new(policy(2, 100))
+            .child("steady", Restart::Permanent, forever)
+            .start();
+        // Five kills, 60 ms apart: never more than 2 restarts in 100 ms.
+        for _ in 0..5 {
+            time::sleep(MS * 60).await;
+            handle.kill("steady");
+        }
+        time::sleep(MS).await;
+        assert_eq!(handle.restarts()["steady"], 5);
+
+        // Three in a row: too many.
+        for _ in 0..3 {
+            handle.kill("steady");
+            time::sleep(MS).await;
+        }
+        let escalation = handle.join().await.unwrap_err();
+        assert_eq!(escalation.exit, Exit::Killed);
+    }
+
+    #[tokio::test]
+    async fn test_ends_when_no_child_is_left() {
+        let handle = Supervisor::new(RestartPolicy::default())
+            .child("done", Restart::Transient, || async { Ok(()) })
+            .child("failed", Restart::Temporary, || async {
+                Err(String::from("no"))
+            })
+            .start();
+        let restarts = handle.join().await.unwrap();
+        assert_eq!(restarts.values().sum::<usize>(), 0);
+    }
+}
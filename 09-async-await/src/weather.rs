@@ -0,0 +1,327 @@
+//! `WeatherProvider`: an async service behind a trait, a real implementation
+//! over HTTP and a mock, and code that uses either without knowing which.
+//!
+//! `compare` asks for the weather of several cities at once and finds the
+//! warmest. It takes an `Arc<dyn WeatherProvider>`: the program passes
+//! `OpenMeteo`, which calls a public API, and the tests a `MockProvider`,
+//! which answers from a table, at once, always the same, without a network.
+//! This is the dependency injection of `11-api-iaas`, where the services take
+//! an `Arc<dyn ServerRepository>`, in a few lines.
+//!
+//! `#[async_trait]` is what lets an `async fn` in a trait be called through
+//! `dyn`: it turns each into a function returning a boxed future, whose type
+//! is the same for every implementation. Native `async fn` in traits (Rust
+//! 1.75) returns a different type per implementation, which rules out `dyn`.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// The weather in a city, now.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weather {
+    pub city: String,
+    pub temperature_c: f64,
+    pub wind_kmh: f64,
+}
+
+#[derive(Debug)]
+pub enum WeatherError {
+    /// The provider knows no city of that name.
+    UnknownCity(String),
+    /// The provider could not be reached, or answered nonsense.
+    Http(reqwest::Error),
+    /// The provider answered, with an error of its own.
+    Unavailable(String),
+}
+
+impl fmt::Display for WeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WeatherError::UnknownCity(city) => write!(f, "no city called '{}'", city),
+            WeatherError::Http(e) => write!(f, "{}", e),
+            WeatherError::Unavailable(reason) => write!(f, "unavailable: {}", reason),
+        }
+    }
+}
+
+impl Error for WeatherError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WeatherError::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for WeatherError {
+    fn from(e: reqwest::Error) -> Self {
+        WeatherError::Http(e)
+    }
+}
+
+#[async_trait]
+pub trait WeatherProvider: Send + Sync {
+    async fn current(&self, city: &str) -> Result<Weather, WeatherError>;
+}
+
+/// The free API of open-meteo.com: no key, two calls per city, one to find
+/// where it is and one for the weather there.
+pub struct OpenMeteo {
+    client: reqwest::Client,
+    geocoding_url: String,
+    forecast_url: String,
+}
+
+#[derive(Deserialize)]
+struct Places {
+    #[serde(default)]
+    results: Vec<Place>,
+}
+
+#[derive(Deserialize)]
+struct Place {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct Forecast {
+    current: Current,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    temperature_2m: f64,
+    wind_speed_10m: f64,
+}
+
+impl OpenMeteo {
+    pub fn new() -> Result<OpenMeteo, WeatherError> {
+        OpenMeteo::with_urls(
+            "https://geocoding-api.open-meteo.com/v1/search",
+            "https://api.open-meteo.com/v1/forecast",
+        )
+    }
+
+    /// The same API at other addresses: a mirror, or a test server.
+    pub fn with_urls(geocoding_url: &str, forecast_url: &str) -> Result<OpenMeteo, WeatherError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        Ok(OpenMeteo {
+            client,
+            geocoding_url: geocoding_url.to_string(),
+            forecast_url: forecast_url.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for OpenMeteo {
+    async fn current(&self, city: &str) -> Result<Weather, WeatherError> {
+        let places: Places = self
+            .client
+            .get(&self.geocoding_url)
+            .query(&[("name", city), ("count", "1")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let Some(place) = places.results.into_iter().next() else {
+            return Err(WeatherError::UnknownCity(city.to_string()));
+        };
+        let forecast: Forecast = self
+            .client
+            .get(&self.forecast_url)
+            .query(&[
+                ("latitude", place.latitude.to_string()),
+                ("longitude", place.longitude.to_string()),
+                ("current", String::from("temperature_2m,wind_speed_10m")),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(Weather {
+            city: place.name,
+            temperature_c: forecast.current.temperature_2m,
+            wind_kmh: forecast.current.wind_speed_10m,
+        })
+    }
+}
+
+/// A provider that answers from a table, and notes what it was asked.
+#[derive(Default)]
+pub struct MockProvider {
+    weather: HashMap<String, Weather>,
+    failing: HashMap<String, String>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl MockProvider {
+    pub fn new() -> MockProvider {
+        MockProvider::default()
+    }
+
+    /// Answers `city` with these readings.
+    pub fn with(mut self, city: &str, temperature_c: f64, wind_kmh: f64) -> MockProvider {
+        let weather = Weather {
+            city: city.to_string(),
+            temperature_c,
+            wind_kmh,
+        };
+        self.weather.insert(city.to_string(), weather);
+        self
+    }
+
+    /// Answers `city` with `WeatherError::Unavailable(reason)`.
+    pub fn failing(mut self, city: &str, reason: &str) -> MockProvider {
+        self.failing.insert(city.to_string(), reason.to_string());
+        self
+    }
+
+    /// The cities asked for, in the order they were.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for MockProvider {
+    async fn current(&self, city: &str) -> Result<Weather, WeatherError> {
+        self.calls.lock().unwrap().push(city.to_string());
+        if let Some(reason) = self.failing.get(city) {
+            return Err(WeatherError::Unavailable(reason.clone()));
+        }
+        self.weather
+            .get(city)
+            .cloned()
+            .ok_or_else(|| WeatherError::UnknownCity(city.to_string()))
+    }
+}
+
+/// The weather of several cities, side by side.
+#[derive(Debug)]
+pub struct Comparison {
+    /// In the order of the cities asked for.
+    pub weather: Vec<Weather>,
+    /// The cities that could not be had, with why.
+    pub failed: Vec<(String, WeatherError)>,
+}
+
+impl Comparison {
+    pub fn warmest(&self) -> Option<&Weather> {
+        self.weather
+            .iter()
+            .max_by(|a, b| a.temperature_c.total_cmp(&b.temperature_c))
+    }
+}
+
+/// Asks `provider` for the weather of every city at once.
+///
+/// # Panics
+///
+/// If the provider panics.
+pub async fn compare(provider: Arc<dyn WeatherProvider>, cities: &[&str]) -> Comparison {
+    let mut requests = JoinSet::new();
+    for (i, city) in cities.iter().enumerate() {
+        let (provider, city) = (Arc::clone(&provider), city.to_string());
+        requests.spawn(async move { (i, city.clone(), provider.current(&city).await) });
+    }
+    let mut answers = Vec::new();
+    while let Some(answer) = requests.join_next().await {
+        answers.push(answer.expect("the weather provider panicked"));
+    }
+    answers.sort_by_key(|(i, _, _)| *i);
+
+    let mut comparison = Comparison {
+        weather: Vec::new(),
+        failed: Vec::new(),
+    };
+    for (_, city, answer) in answers {
+        match answer {
+            Ok(weather) => comparison.weather.push(weather),
+            Err(error) => comparison.failed.push((city, error)),
+        }
+    }
+    comparison
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> Arc<MockProvider> {
+        Arc::new(
+            MockProvider::new()
+                .with("Oslo", 4.5, 12.0)
+                .with("Lisbon", 19.0, 8.0)
+                .with("Cairo", 27.5, 15.0)
+                .failing("Reykjavik", "rate limited"),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_compare() {
+        let mock = provider();
+        let comparison = compare(mock.clone(), &["Oslo", "Cairo", "Lisbon"]).await;
+        let cities: Vec<&str> = comparison.weather.iter().map(|w| w.city.as_str()).collect();
+        assert_eq!(cities, ["Oslo", "Cairo", "Lisbon"]);
+        assert_eq!(comparison.warmest().unwrap().city, "Cairo");
+        assert!(comparison.failed.is_empty());
+
+        let mut calls = mock.calls();
+        calls.sort();
+        assert_eq!(calls, ["Cairo", "Lisbon", "Oslo"]);
+    }
+
+    #[tokio::test]
+    async fn test_compare_with_failures() {
+        let comparison = compare(provider(), &["Reykjavik", "Oslo", "Atlantis"]).await;
+        assert_eq!(comparison.warmest().unwrap().city, "Oslo");
+        let failures: Vec<String> = comparison
+            .failed
+            .iter()
+            .map(|(city, error)| format!("{}: {}", city, error))
+            .collect();
+        assert_eq!(
+            failures,
+            [
+                "Reykjavik: unavailable: rate limited",
+                "Atlantis: no city called 'Atlantis'"
+            ]
+        );
+
+        let nothing = compare(provider(), &[]).await;
+        assert!(nothing.warmest().is_none());
+    }
+
+    #[test]
+    fn test_open_meteo_answers() {
+        let places: Places = serde_json::from_str(
+            r#"{"results": [{"id": 3143244, "name": "Oslo", "latitude": 59.91273,
+                "longitude": 10.74609, "country": "Norway"}], "generationtime_ms": 0.5}"#,
+        )
+        .unwrap();
+        assert_eq!(places.results[0].name, "Oslo");
+        // No city found: no `results` at all.
+        let none: Places = serde_json::from_str(r#"{"generationtime_ms": 0.3}"#).unwrap();
+        assert!(none.results.is_empty());
+
+        let forecast: Forecast = serde_json::from_str(
+            r#"{"latitude": 59.9, "current": {"time": "2024-07-25T12:00",
+                "temperature_2m": 21.3, "wind_speed_10m": 9.7}}"#,
+        )
+        .unwrap();
+        assert_eq!(forecast.current.temperature_2m, 21.3);
+    }
+}
@@ -0,0 +1,420 @@
+use askama::Template;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use warp::http::{header, StatusCode, Uri};
+use warp::{Filter, Rejection, Reply};
+
+use crate::pages::html;
+
+/// Name of the cookie carrying the session ID.
+pub const SESSION_COOKIE: &str = "session";
+/// How long a login lasts; the cookie's `Max-Age` and the server-side record agree.
+pub const SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Template)]
+#[template(path = "login.html")]
+struct LoginPage<'a> {
+    error: Option<&'a str>,
+}
+
+#[derive(Template)]
+#[template(path = "admin.html")]
+struct AdminPage<'a> {
+    user: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "unauthorized.html")]
+struct UnauthorizedPage;
+
+/// Fields of the `POST /login` form (`application/x-www-form-urlencoded`).
+#[derive(Debug, Deserialize)]
+struct LoginForm {
+    username: String,
+    password: String,
+}
+
+/// One logged-in browser.
+struct Session {
+    user: String,
+    expires_at: Instant,
+}
+
+/// SESSIONS: Signed cookies backed by a server-side store
+///
+/// --- Good to know ---
+/// The cookie holds `{id}.{signature}`: a random session ID plus an HMAC-SHA256 of
+/// it, keyed with a secret that never leaves the server. A forged or edited cookie
+/// fails the signature check before the store is even consulted. The store maps
+/// IDs to users and expiry times, which is what makes logout real: the ID is
+/// deleted, so a copied cookie stops working even though it is still signed.
+/// The key is random per process; sessions live in memory and end with it anyway.
+///
+/// Comparison:
+/// - Go: Like `gorilla/sessions` with a server-side store.
+/// - Python: Like Flask-Login on top of Flask's signed `session` cookie.
+#[derive(Clone)]
+pub struct Auth {
+    inner: Arc<AuthInner>,
+}
+
+struct AuthInner {
+    username: String,
+    password: String,
+    key: [u8; 32],
+    ttl: Duration,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Auth {
+    pub fn new(username: String, password: String, ttl: Duration) -> Self {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self {
+            inner: Arc::new(AuthInner {
+                username,
+                password,
+                key,
+                ttl,
+                sessions: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.inner.key).expect("HMAC accepts keys of any length")
+    }
+
+    fn sign(&self, value: &str) -> Vec<u8> {
+        let mut mac = self.mac();
+        mac.update(value.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// Constant-time comparison: both sides are MACed, then compared with
+    /// `verify_slice`, so response timing does not leak how many bytes matched.
+    fn same_secret(&self, given: &str, expected: &str) -> bool {
+        let mut mac = self.mac();
+        mac.update(given.as_bytes());
+        mac.verify_slice(&self.sign(expected)).is_ok()
+    }
+
+    fn check_credentials(&self, username: &str, password: &str) -> bool {
+        // `&` (not `&&`): always compare both, so a wrong username is not faster.
+        self.same_secret(username, &self.inner.username)
+            & self.same_secret(password, &self.inner.password)
+    }
+
+    /// Starts a session and returns the signed cookie value.
+    fn login(&self, user: &str) -> String {
+        let mut id = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut id);
+        let id = URL_SAFE_NO_PAD.encode(id);
+        let now = Instant::now();
+
+        let mut sessions = self.inner.sessions.lock().unwrap();
+        // Expired sessions are only found on use; sweep them here so the map stays small.
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(
+            id.clone(),
+            Session {
+                user: user.to_string(),
+                expires_at: now + self.inner.ttl,
+            },
+        );
+        format!("{}.{}", id, URL_SAFE_NO_PAD.encode(self.sign(&id)))
+    }
+
+    /// The session ID in a cookie value, if its signature is valid.
+    fn verified_id<'a>(&self, cookie: &'a str) -> Option<&'a str> {
+        let (id, signature) = cookie.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        let mut mac = self.mac();
+        mac.update(id.as_bytes());
+        mac.verify_slice(&signature).ok()?;
+        Some(id)
+    }
+
+    fn session_user(&self, cookie: &str) -> Option<String> {
+        let id = self.verified_id(cookie)?;
+        let mut sessions = self.inner.sessions.lock().unwrap();
+        match sessions.get(id) {
+            Some(session) if session.expires_at > Instant::now() => Some(session.user.clone()),
+            Some(_) => {
+                sessions.remove(id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn logout(&self, cookie: &str) {
+        if let Some(id) = self.verified_id(cookie) {
+            self.inner.sessions.lock().unwrap().remove(id);
+        }
+    }
+
+    /// `Authorization: Basic base64(user:password)`, for scripts and `curl -u`.
+    fn basic_user(&self, authorization: &str) -> Option<String> {
+        let encoded = authorization.strip_prefix("Basic ")?;
+        let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        self.check_credentials(username, password)
+            .then(|| username.to_string())
+    }
+
+    /// A valid session cookie wins; otherwise Basic credentials are tried.
+    fn current_user(&self, cookie: Option<&str>, authorization: Option<&str>) -> Option<String> {
+        cookie
+            .and_then(|cookie| self.session_user(cookie))
+            .or_else(|| authorization.and_then(|header| self.basic_user(header)))
+    }
+
+    fn session_cookie(&self, value: &str) -> String {
+        // HttpOnly: scripts cannot read it. SameSite=Strict: other sites cannot make the
+        // browser send it, which also protects the login/logout forms from CSRF.
+        format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}",
+            SESSION_COOKIE,
+            value,
+            // Round sub-second test TTLs up: `Max-Age=0` would delete the cookie at once.
+            self.inner.ttl.as_secs().max(1)
+        )
+    }
+}
+
+fn see_other(location: &'static str, cookie: String) -> warp::reply::Response {
+    let redirect = warp::redirect::see_other(Uri::from_static(location));
+    warp::reply::with_header(redirect, header::SET_COOKIE, cookie).into_response()
+}
+
+/// AUTHENTICATION: `/login`, `/logout` and the protected `/admin` page
+///
+/// --- Good to know ---
+/// `/admin` accepts a session cookie (browsers, after the login form) or HTTP Basic
+/// credentials (`curl -u admin:secret`). Anything else gets `401` with a
+/// `WWW-Authenticate` header, as HTTP requires, and a link to the login form.
+/// Successful form posts answer `303 See Other`, so a browser refresh does not
+/// re-submit the password (Post/Redirect/Get).
+///
+/// Comparison:
+/// - Go: Like login/logout handlers plus an auth check at the top of the admin handler.
+/// - Python: Like Flask's `@login_required` on the admin view.
+pub fn routes(auth: Auth) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let with_auth = warp::any().map(move || auth.clone());
+
+    let login_form = warp::path!("login")
+        .and(warp::get())
+        .map(|| html(&LoginPage { error: None }, StatusCode::OK));
+
+    let login = warp::path!("login")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(4 * 1024))
+        .and(warp::body::form())
+        .and(with_auth.clone())
+        .map(|form: LoginForm, auth: Auth| {
+            if !auth.check_credentials(&form.username, &form.password) {
+                let page = LoginPage {
+                    error: Some("Invalid username or password."),
+                };
+                return html(&page, StatusCode::UNAUTHORIZED);
+            }
+            let session = auth.login(&form.username);
+            see_other("/admin", auth.session_cookie(&session))
+        });
+
+    let logout = warp::path!("logout")
+        .and(warp::post())
+        .and(warp::cookie::optional::<String>(SESSION_COOKIE))
+        .and(with_auth.clone())
+        .map(|cookie: Option<String>, auth: Auth| {
+            if let Some(cookie) = cookie {
+                auth.logout(&cookie);
+            }
+            // Max-Age=0 tells the browser to delete the cookie right away.
+            see_other(
+                "/login",
+                format!(
+                    "{}=; Path=/; HttpOnly; SameSite=Strict; Max-Age=0",
+                    SESSION_COOKIE
+                ),
+            )
+        });
+
+    let admin = warp::path!("admin")
+        .and(warp::get())
+        .and(warp::cookie::optional::<String>(SESSION_COOKIE))
+        .and(warp::header::optional::<String>("authorization"))
+        .and(with_auth)
+        .and_then(admin_page);
+
+    login_form.or(login).or(logout).or(admin)
+}
+
+async fn admin_page(
+    cookie: Option<String>,
+    authorization: Option<String>,
+    auth: Auth,
+) -> Result<warp::reply::Response, Infallible> {
+    Ok(
+        match auth.current_user(cookie.as_deref(), authorization.as_deref()) {
+            Some(user) => html(&AdminPage { user: &user }, StatusCode::OK),
+            None => {
+                let page = html(&UnauthorizedPage, StatusCode::UNAUTHORIZED);
+                warp::reply::with_header(
+                    page,
+                    header::WWW_AUTHENTICATE,
+                    r#"Basic realm="admin", charset="UTF-8""#,
+                )
+                .into_response()
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::test::request;
+
+    fn auth(ttl: Duration) -> Auth {
+        Auth::new("admin".to_string(), "s3cret".to_string(), ttl)
+    }
+
+    /// Logs in through the form and returns the `name=value` part of the cookie.
+    async fn log_in(
+        api: &(impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone + 'static),
+    ) -> String {
+        let resp = request()
+            .method("POST")
+            .path("/login")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("username=admin&password=s3cret")
+            .reply(api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+        assert_eq!(resp.headers()["location"], "/admin");
+        let cookie = resp.headers()["set-cookie"].to_str().unwrap();
+        assert!(cookie.contains("HttpOnly"), "{}", cookie);
+        cookie.split(';').next().unwrap().to_string()
+    }
+
+    async fn admin_status(
+        api: &(impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone + 'static),
+        cookie: &str,
+    ) -> StatusCode {
+        request()
+            .path("/admin")
+            .header("cookie", cookie)
+            .reply(api)
+            .await
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_login_admin_logout() {
+        let api = routes(auth(SESSION_TTL));
+
+        let resp = request().path("/admin").reply(&api).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.headers()["www-authenticate"]
+            .to_str()
+            .unwrap()
+            .starts_with("Basic"));
+
+        let cookie = log_in(&api).await;
+        let resp = request()
+            .path("/admin")
+            .header("cookie", &cookie)
+            .reply(&api)
+            .await;
+        assert_eq!(
+            auth(SESSION_TTL).session_cookie("v"),
+            "session=v; Path=/; HttpOnly; SameSite=Strict; Max-Age=1800"
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(std::str::from_utf8(resp.body())
+            .unwrap()
+            .contains("Welcome, admin"));
+
+        let resp = request()
+            .method("POST")
+            .path("/logout")
+            .header("cookie", &cookie)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+        assert!(resp.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .contains("Max-Age=0"));
+        // The old cookie is still correctly signed, but its session is gone.
+        assert_eq!(admin_status(&api, &cookie).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_credentials_and_cookies() {
+        let api = routes(auth(SESSION_TTL));
+
+        let resp = request()
+            .method("POST")
+            .path("/login")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("username=admin&password=guess")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.headers().get("set-cookie").is_none());
+
+        // Changing one character of the ID breaks the signature.
+        let cookie = log_in(&api).await;
+        let tampered = cookie.replacen("session=", "session=x", 1);
+        assert_eq!(
+            admin_status(&api, &tampered).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            admin_status(&api, "session=not-signed").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_expires() {
+        let api = routes(auth(Duration::from_millis(50)));
+        let cookie = log_in(&api).await;
+        assert_eq!(admin_status(&api, &cookie).await, StatusCode::OK);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(admin_status(&api, &cookie).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth() {
+        let api = routes(auth(SESSION_TTL));
+        let basic = |credentials: &str| format!("Basic {}", STANDARD.encode(credentials));
+
+        let resp = request()
+            .path("/admin")
+            .header("authorization", basic("admin:s3cret"))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = request()
+            .path("/admin")
+            .header("authorization", basic("admin:nope"))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
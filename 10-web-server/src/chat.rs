@@ -0,0 +1,382 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use warp::http::StatusCode;
+use warp::ws::{Message, WebSocket, Ws};
+use warp::{Filter, Rejection, Reply};
+
+/// Events buffered per connection; a client further behind than this skips ahead.
+const HUB_CAPACITY: usize = 256;
+/// Longest accepted nickname, in characters.
+const MAX_NICK_LEN: usize = 32;
+
+/// Everything the server sends, as one JSON text frame per event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatEvent {
+    Join {
+        nick: String,
+    },
+    Leave {
+        nick: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    Message {
+        nick: String,
+        text: String,
+    },
+    /// Sent only to the client that caused it, never broadcast.
+    Error {
+        message: String,
+    },
+}
+
+/// `GET /chat?nick=alice`: without a nickname the server picks `guest-N`.
+#[derive(Debug, Deserialize)]
+struct JoinParams {
+    nick: Option<String>,
+}
+
+/// BROADCAST HUB: One channel, many connections
+///
+/// --- Good to know ---
+/// A `tokio::sync::broadcast` channel delivers every event to every subscriber:
+/// each connection calls `subscribe()` and gets its own receiver. A slow reader
+/// never blocks the others; once it falls `HUB_CAPACITY` events behind, it gets
+/// `RecvError::Lagged` and skips the events it missed.
+/// The nickname set uses a `std` `Mutex`: it is never held across an `.await`.
+///
+/// Comparison:
+/// - Go: Like a hub goroutine fanning out to one `chan` per client.
+/// - Python: Like `websockets.broadcast()` over a `set` of connections.
+#[derive(Clone)]
+pub struct ChatHub {
+    events: broadcast::Sender<ChatEvent>,
+    nicks: Arc<Mutex<HashSet<String>>>,
+    guests: Arc<AtomicU64>,
+}
+
+impl Default for ChatHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatHub {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(HUB_CAPACITY);
+        Self {
+            events,
+            nicks: Arc::new(Mutex::new(HashSet::new())),
+            guests: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn broadcast(&self, event: ChatEvent) {
+        // `send` only fails when nobody is connected, which is fine for a chat room.
+        let _ = self.events.send(event);
+    }
+
+    /// Reserves a nickname; the returned guard releases it when dropped.
+    fn claim(&self, nick: &str) -> Result<Nick, ClaimError> {
+        let nick = valid_nick(nick)?;
+        if !self.nicks.lock().unwrap().insert(nick.clone()) {
+            return Err(ClaimError::Taken(nick));
+        }
+        Ok(Nick {
+            name: nick,
+            hub: self.clone(),
+        })
+    }
+
+    fn claim_guest(&self) -> Nick {
+        loop {
+            let n = self.guests.fetch_add(1, Ordering::Relaxed) + 1;
+            // Someone may have picked `guest-N` by hand; try the next number.
+            if let Ok(nick) = self.claim(&format!("guest-{}", n)) {
+                return nick;
+            }
+        }
+    }
+
+    /// Swaps the reservation for `to`, keeping the old one if `to` is not available.
+    fn rename(&self, nick: &mut Nick, to: &str) -> Result<(), ClaimError> {
+        let to = valid_nick(to)?;
+        let mut nicks = self.nicks.lock().unwrap();
+        if !nicks.insert(to.clone()) {
+            return Err(ClaimError::Taken(to));
+        }
+        nicks.remove(&nick.name);
+        nick.name = to;
+        Ok(())
+    }
+
+    /// Drives one connection until the client leaves.
+    async fn run(self, socket: WebSocket, mut nick: Nick) {
+        // Subscribe before announcing, so the new client sees its own join.
+        let mut events = self.events.subscribe();
+        self.broadcast(ChatEvent::Join {
+            nick: nick.name.clone(),
+        });
+        let (mut outgoing, mut incoming) = socket.split();
+
+        // One task serves both directions: `select!` waits for whichever side is ready first.
+        loop {
+            let reply = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                message = incoming.next() => match message {
+                    Some(Ok(message)) if message.is_text() => {
+                        match self.handle(&mut nick, message.to_str().unwrap_or_default()) {
+                            Some(error) => error,
+                            None => continue,
+                        }
+                    }
+                    Some(Ok(message)) if message.is_close() => break,
+                    // Pings are answered by the library; binary frames are ignored.
+                    Some(Ok(_)) => continue,
+                    Some(Err(_)) | None => break,
+                },
+            };
+            let Ok(frame) = serde_json::to_string(&reply) else {
+                continue;
+            };
+            if outgoing.send(Message::text(frame)).await.is_err() {
+                break;
+            }
+        }
+
+        self.broadcast(ChatEvent::Leave {
+            nick: nick.name.clone(),
+        });
+        // `nick` is dropped here, which frees the name for someone else.
+    }
+
+    /// Handles one line from a client: `/nick NAME` renames, anything else is said
+    /// to the room. Returns an error event meant for that client only.
+    fn handle(&self, nick: &mut Nick, line: &str) -> Option<ChatEvent> {
+        if let Some(to) = line.strip_prefix("/nick ") {
+            let from = nick.name.clone();
+            return match self.rename(nick, to) {
+                Ok(()) => {
+                    self.broadcast(ChatEvent::Rename {
+                        from,
+                        to: nick.name.clone(),
+                    });
+                    None
+                }
+                Err(e) => Some(ChatEvent::Error {
+                    message: e.to_string(),
+                }),
+            };
+        }
+        let text = line.trim();
+        if !text.is_empty() {
+            self.broadcast(ChatEvent::Message {
+                nick: nick.name.clone(),
+                text: text.to_string(),
+            });
+        }
+        None
+    }
+}
+
+/// A reserved nickname. Dropping it (connection closed, or the upgrade never
+/// happened) releases the name, so the set cannot leak.
+struct Nick {
+    name: String,
+    hub: ChatHub,
+}
+
+impl Drop for Nick {
+    fn drop(&mut self) {
+        self.hub.nicks.lock().unwrap().remove(&self.name);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ClaimError {
+    Invalid,
+    Taken(String),
+}
+
+impl std::fmt::Display for ClaimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClaimError::Invalid => write!(
+                f,
+                "nicknames are 1-{} letters, digits, '-' or '_'",
+                MAX_NICK_LEN
+            ),
+            ClaimError::Taken(nick) => write!(f, "nickname '{}' is already taken", nick),
+        }
+    }
+}
+
+fn valid_nick(nick: &str) -> Result<String, ClaimError> {
+    let nick = nick.trim();
+    let allowed = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    if nick.is_empty() || nick.chars().count() > MAX_NICK_LEN || !nick.chars().all(allowed) {
+        return Err(ClaimError::Invalid);
+    }
+    Ok(nick.to_string())
+}
+
+/// WEBSOCKETS: `GET /chat`
+///
+/// --- Good to know ---
+/// `warp::ws()` matches the HTTP upgrade handshake. The nickname is checked
+/// *before* upgrading, so a bad or taken name gets a normal `400`/`409` response
+/// instead of a socket that closes right away.
+///
+/// Comparison:
+/// - Go: Like `websocket.Upgrader.Upgrade` (gorilla/websocket) inside a handler.
+/// - Python: Like a FastAPI `@app.websocket("/chat")` endpoint.
+pub fn routes(hub: ChatHub) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("chat")
+        .and(warp::ws())
+        .and(warp::query::<JoinParams>())
+        .and(warp::any().map(move || hub.clone()))
+        .and_then(join)
+}
+
+async fn join(
+    ws: Ws,
+    params: JoinParams,
+    hub: ChatHub,
+) -> Result<warp::reply::Response, Infallible> {
+    let nick = match params.nick {
+        Some(nick) => match hub.claim(&nick) {
+            Ok(nick) => nick,
+            Err(e) => {
+                let status = match e {
+                    ClaimError::Invalid => StatusCode::BAD_REQUEST,
+                    ClaimError::Taken(_) => StatusCode::CONFLICT,
+                };
+                return Ok(warp::reply::with_status(e.to_string(), status).into_response());
+            }
+        },
+        None => hub.claim_guest(),
+    };
+    Ok(ws
+        .on_upgrade(move |socket| hub.run(socket, nick))
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::test::WsClient;
+
+    async fn connect(
+        api: &(impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone + Send + Sync + 'static),
+        path: &str,
+    ) -> WsClient {
+        warp::test::ws()
+            .path(path)
+            .handshake(api.clone())
+            .await
+            .expect("handshake")
+    }
+
+    async fn next_event(client: &mut WsClient) -> ChatEvent {
+        let message = client.recv().await.expect("event");
+        serde_json::from_str(message.to_str().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_chat_join_message_rename_leave() {
+        let hub = ChatHub::new();
+        let api = routes(hub.clone());
+
+        let mut alice = connect(&api, "/chat?nick=alice").await;
+        assert_eq!(
+            next_event(&mut alice).await,
+            ChatEvent::Join {
+                nick: "alice".into()
+            }
+        );
+
+        let mut guest = connect(&api, "/chat").await;
+        assert_eq!(
+            next_event(&mut guest).await,
+            ChatEvent::Join {
+                nick: "guest-1".into()
+            }
+        );
+        assert_eq!(
+            next_event(&mut alice).await,
+            ChatEvent::Join {
+                nick: "guest-1".into()
+            }
+        );
+
+        guest.send_text("hello everyone").await;
+        let said = ChatEvent::Message {
+            nick: "guest-1".into(),
+            text: "hello everyone".into(),
+        };
+        assert_eq!(next_event(&mut alice).await, said);
+        assert_eq!(next_event(&mut guest).await, said);
+
+        // A taken name is refused to that client only; a free one is announced.
+        guest.send_text("/nick alice").await;
+        assert!(matches!(
+            next_event(&mut guest).await,
+            ChatEvent::Error { .. }
+        ));
+        guest.send_text("/nick bob").await;
+        let renamed = ChatEvent::Rename {
+            from: "guest-1".into(),
+            to: "bob".into(),
+        };
+        assert_eq!(next_event(&mut alice).await, renamed);
+        assert_eq!(next_event(&mut guest).await, renamed);
+
+        drop(guest);
+        assert_eq!(
+            next_event(&mut alice).await,
+            ChatEvent::Leave { nick: "bob".into() }
+        );
+        // The departed nickname is free again.
+        assert!(hub.claim("bob").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_bad_nicknames_before_upgrading() {
+        let api = routes(ChatHub::new());
+        let _alice = connect(&api, "/chat?nick=alice").await;
+
+        // A raw handshake request, to look at the status code instead of a failed upgrade.
+        let handshake = |path: &str| {
+            warp::test::request()
+                .path(path)
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", "13")
+                .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+        };
+        assert_eq!(
+            handshake("/chat?nick=alice").reply(&api).await.status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            handshake("/chat?nick=a%20b").reply(&api).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            valid_nick(&"x".repeat(MAX_NICK_LEN + 1)),
+            Err(ClaimError::Invalid)
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use warp::http::Uri;
+
+use crate::ratelimit::RateLimitConfig;
+use crate::tls::TlsConfig;
+
+/// Environment variable holding the listen address, e.g. `0.0.0.0:8080`.
+pub const BIND_ADDR_VAR: &str = "BIND_ADDR";
+/// Used when neither `--bind` nor `BIND_ADDR` is given: local connections only.
+pub const DEFAULT_BIND_ADDR: &str = "127.0.0.1:3030";
+/// Environment variables holding the `/admin` login; without a password one is generated.
+pub const ADMIN_USER_VAR: &str = "ADMIN_USER";
+pub const ADMIN_PASSWORD_VAR: &str = "ADMIN_PASSWORD";
+/// Environment variable holding the server `/proxy/...` forwards to, e.g. `http://127.0.0.1:8080`.
+pub const PROXY_UPSTREAM_VAR: &str = "PROXY_UPSTREAM";
+/// Environment variables for `POST /upload`: target directory and largest request in bytes.
+pub const UPLOAD_DIR_VAR: &str = "UPLOAD_DIR";
+pub const UPLOAD_MAX_BYTES_VAR: &str = "UPLOAD_MAX_BYTES";
+/// Environment variables tuning the per-IP limits (see `ratelimit`).
+pub const RATE_LIMIT_RPS_VAR: &str = "RATE_LIMIT_RPS";
+pub const RATE_LIMIT_BURST_VAR: &str = "RATE_LIMIT_BURST";
+pub const RATE_LIMIT_MAX_IN_FLIGHT_VAR: &str = "RATE_LIMIT_MAX_IN_FLIGHT";
+/// Environment variables holding PEM file paths; setting both serves HTTPS.
+pub const TLS_CERT_VAR: &str = "TLS_CERT";
+pub const TLS_KEY_VAR: &str = "TLS_KEY";
+
+pub const USAGE: &str = "Usage: web-server [--bind ADDR] [--self-signed]
+
+Options:
+  -b, --bind ADDR   Address to listen on (default: 127.0.0.1:3030, env: BIND_ADDR)
+      --self-signed Serve HTTPS with a certificate generated for localhost (development only)
+  -h, --help        Show this help
+
+Environment:
+  PROXY_UPSTREAM    http:// URL that /proxy/... forwards to (unset: /proxy answers 503)
+  ADMIN_USER        Username for /login and /admin (default: admin)
+  ADMIN_PASSWORD    Its password (unset: a random one is printed at startup)
+  UPLOAD_DIR        Where POST /upload stores files (default: ./uploads)
+  UPLOAD_MAX_BYTES  Largest accepted upload request (default: 10485760, i.e. 10 MiB)
+  RATE_LIMIT_RPS    Requests per second allowed per client IP (default: 10, 0: off)
+  RATE_LIMIT_BURST  Requests a client IP may send at once (default: 20)
+  RATE_LIMIT_MAX_IN_FLIGHT
+                    Concurrent requests per client IP (default: 8, 0: no cap)
+  TLS_CERT, TLS_KEY PEM certificate chain and private key: serve HTTPS and HTTP/2
+  LOG_FORMAT        Request log format: pretty (default) or json";
+
+/// CONFIGURATION: Command line first, then environment, then defaults
+///
+/// --- Good to know ---
+/// `parse` takes the arguments and an env lookup as parameters instead of reading
+/// `std::env` itself, so tests can feed it anything without touching global state.
+/// Use port `0` to let the OS pick a free port (the server prints the real one).
+///
+/// Comparison:
+/// - Go: Like `flag.String("bind", os.Getenv("BIND_ADDR"), ...)`.
+/// - Python: Like `argparse` with `default=os.environ.get("BIND_ADDR")`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub bind_addr: SocketAddr,
+    pub proxy_upstream: Option<Uri>,
+    pub admin_user: String,
+    pub admin_password: Option<String>,
+    pub upload_dir: PathBuf,
+    pub upload_max_bytes: u64,
+    pub rate_limit: RateLimitConfig,
+    /// `None`: plain HTTP.
+    pub tls: Option<TlsConfig>,
+}
+
+/// Why the server should not start: a bad value, or the user just asked for help.
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Help,
+    Invalid(String),
+}
+
+impl Config {
+    /// `args` excludes the program name (`std::env::args().skip(1)`).
+    pub fn parse<I, E>(args: I, env: E) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = String>,
+        E: Fn(&str) -> Option<String>,
+    {
+        let mut bind = None;
+        let mut self_signed = false;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Err(ConfigError::Help),
+                "--self-signed" => self_signed = true,
+                "-b" | "--bind" => match args.next() {
+                    Some(value) => bind = Some(value),
+                    None => return Err(ConfigError::Invalid(format!("{} needs an address", arg))),
+                },
+                _ => match arg.strip_prefix("--bind=") {
+                    Some(value) => bind = Some(value.to_string()),
+                    None => {
+                        return Err(ConfigError::Invalid(format!("unknown argument '{}'", arg)))
+                    }
+                },
+            }
+        }
+
+        let (source, value) = match (bind, env(BIND_ADDR_VAR)) {
+            (Some(value), _) => ("--bind", value),
+            (None, Some(value)) => (BIND_ADDR_VAR, value),
+            (None, None) => ("default", DEFAULT_BIND_ADDR.to_string()),
+        };
+        let bind_addr = value.trim().parse().map_err(|_| {
+            ConfigError::Invalid(format!(
+                "{}: '{}' is not an address like 127.0.0.1:3030",
+                source, value
+            ))
+        })?;
+        let proxy_upstream = env(PROXY_UPSTREAM_VAR)
+            .map(|value| parse_upstream(&value))
+            .transpose()?;
+        let admin_user = env(ADMIN_USER_VAR).unwrap_or_else(|| "admin".to_string());
+        let admin_password = env(ADMIN_PASSWORD_VAR).filter(|password| !password.is_empty());
+        let upload_dir =
+            env(UPLOAD_DIR_VAR).map_or_else(|| PathBuf::from("uploads"), PathBuf::from);
+        let upload_max_bytes = parse_number(&env, UPLOAD_MAX_BYTES_VAR, 10 * 1024 * 1024)?;
+        let defaults = RateLimitConfig::default();
+        let rate_limit = RateLimitConfig {
+            requests_per_second: parse_number(
+                &env,
+                RATE_LIMIT_RPS_VAR,
+                defaults.requests_per_second,
+            )?,
+            burst: parse_number(&env, RATE_LIMIT_BURST_VAR, defaults.burst)?,
+            max_in_flight: parse_number(
+                &env,
+                RATE_LIMIT_MAX_IN_FLIGHT_VAR,
+                defaults.max_in_flight,
+            )?,
+        };
+        if !(rate_limit.requests_per_second >= 0.0 && rate_limit.requests_per_second.is_finite()) {
+            let message = format!("{} must be a positive number or 0", RATE_LIMIT_RPS_VAR);
+            return Err(ConfigError::Invalid(message));
+        }
+        let tls = match (self_signed, env(TLS_CERT_VAR), env(TLS_KEY_VAR)) {
+            (true, _, _) => Some(TlsConfig::SelfSigned),
+            (false, Some(cert), Some(key)) => Some(TlsConfig::Files {
+                cert: cert.into(),
+                key: key.into(),
+            }),
+            (false, None, None) => None,
+            (false, _, _) => {
+                let message = format!("{} and {} must be set together", TLS_CERT_VAR, TLS_KEY_VAR);
+                return Err(ConfigError::Invalid(message));
+            }
+        };
+        Ok(Config {
+            bind_addr,
+            proxy_upstream,
+            admin_user,
+            admin_password,
+            upload_dir,
+            upload_max_bytes,
+            rate_limit,
+            tls,
+        })
+    }
+
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::parse(std::env::args().skip(1), |name| std::env::var(name).ok())
+    }
+}
+
+/// A numeric variable, or `default` when unset.
+fn parse_number<T: FromStr>(
+    env: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    default: T,
+) -> Result<T, ConfigError> {
+    match env(name) {
+        Some(value) => value.trim().parse().map_err(|_| {
+            ConfigError::Invalid(format!("{}: '{}' is not a valid number", name, value))
+        }),
+        None => Ok(default),
+    }
+}
+
+/// The proxy's client speaks plain HTTP, so only absolute `http://` URLs are accepted.
+fn parse_upstream(value: &str) -> Result<Uri, ConfigError> {
+    let invalid = || {
+        ConfigError::Invalid(format!(
+            "{}: '{}' is not a URL like http://127.0.0.1:8080",
+            PROXY_UPSTREAM_VAR, value
+        ))
+    };
+    let uri: Uri = value.trim().parse().map_err(|_| invalid())?;
+    match (uri.scheme_str(), uri.authority()) {
+        (Some("http"), Some(_)) => Ok(uri),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `env` is the value of `BIND_ADDR`; other variables are unset.
+    fn parse(args: &[&str], env: Option<&str>) -> Result<Config, ConfigError> {
+        let env = env.map(str::to_string);
+        Config::parse(args.iter().map(|a| a.to_string()), |name| {
+            env.clone().filter(|_| name == BIND_ADDR_VAR)
+        })
+    }
+
+    #[test]
+    fn test_config_precedence() {
+        let addr = |a: &str| {
+            Ok(Config {
+                bind_addr: a.parse().unwrap(),
+                proxy_upstream: None,
+                admin_user: "admin".to_string(),
+                admin_password: None,
+                upload_dir: PathBuf::from("uploads"),
+                upload_max_bytes: 10 * 1024 * 1024,
+                rate_limit: RateLimitConfig::default(),
+                tls: None,
+            })
+        };
+        assert_eq!(parse(&[], None), addr(DEFAULT_BIND_ADDR));
+        assert_eq!(parse(&[], Some("0.0.0.0:8080")), addr("0.0.0.0:8080"));
+        assert_eq!(
+            parse(&["--bind", "[::1]:9000"], Some("0.0.0.0:8080")),
+            addr("[::1]:9000")
+        );
+        assert_eq!(parse(&["--bind=127.0.0.1:0"], None), addr("127.0.0.1:0"));
+    }
+
+    #[test]
+    fn test_config_errors() {
+        assert_eq!(parse(&["--help"], None), Err(ConfigError::Help));
+        assert!(matches!(
+            parse(&["--bind"], None),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse(&["--port", "80"], None),
+            Err(ConfigError::Invalid(_))
+        ));
+        // The message names where the bad value came from.
+        let Err(ConfigError::Invalid(message)) = parse(&[], Some("localhost")) else {
+            panic!("a host without a port is not a socket address");
+        };
+        assert!(message.starts_with("BIND_ADDR: 'localhost'"), "{}", message);
+    }
+
+    #[test]
+    fn test_proxy_upstream() {
+        let parse = |value: &str| {
+            let value = value.to_string();
+            Config::parse(Vec::new(), |name| {
+                (name == PROXY_UPSTREAM_VAR).then(|| value.clone())
+            })
+        };
+        let config = parse("http://127.0.0.1:8080/api").unwrap();
+        assert_eq!(config.proxy_upstream.unwrap().path(), "/api");
+        assert!(matches!(
+            parse("https://example.com"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(parse("/relative"), Err(ConfigError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_rate_limit_settings() {
+        let parse = |vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            Config::parse(Vec::new(), move |name| {
+                vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+            })
+        };
+        let config = parse(&[(RATE_LIMIT_RPS_VAR, "0.5"), (RATE_LIMIT_BURST_VAR, "3")]).unwrap();
+        assert_eq!(config.rate_limit.requests_per_second, 0.5);
+        assert_eq!(config.rate_limit.burst, 3);
+        assert_eq!(
+            config.rate_limit.max_in_flight,
+            RateLimitConfig::default().max_in_flight
+        );
+
+        assert!(matches!(
+            parse(&[(RATE_LIMIT_BURST_VAR, "-1")]),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse(&[(RATE_LIMIT_RPS_VAR, "-2")]),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse(&[(RATE_LIMIT_RPS_VAR, "NaN")]),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_tls_settings() {
+        let parse = |args: &[&str], vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            Config::parse(args.iter().map(|a| a.to_string()), move |name| {
+                vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+            })
+        };
+        let files = [(TLS_CERT_VAR, "cert.pem"), (TLS_KEY_VAR, "key.pem")];
+        assert_eq!(
+            parse(&[], &files).unwrap().tls,
+            Some(TlsConfig::Files {
+                cert: "cert.pem".into(),
+                key: "key.pem".into()
+            })
+        );
+        assert_eq!(
+            parse(&["--self-signed"], &[]).unwrap().tls,
+            Some(TlsConfig::SelfSigned)
+        );
+        assert!(matches!(
+            parse(&[], &[(TLS_CERT_VAR, "cert.pem")]),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,6 @@
+//! The reusable part of the web server example.
+//!
+//! Everything else in this crate is specific to the demo binary (`src/main.rs`);
+//! `metrics` is meant to be imported by other warp services.
+
+pub mod metrics;
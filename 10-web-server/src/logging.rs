@@ -0,0 +1,143 @@
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::{Method, StatusCode};
+use warp::log::{Info, Log};
+
+/// Environment variable selecting the log line format: `pretty` (default) or `json`.
+pub const LOG_FORMAT_VAR: &str = "LOG_FORMAT";
+
+/// How each request is written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One human-readable line: `GET /todos -> 200 OK (0.42 ms)`.
+    Pretty,
+    /// One JSON object per line, ready for a log shipper (Loki, Elasticsearch...).
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "pretty" => Some(Self::Pretty),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Reads `LOG_FORMAT`; unset or unknown values fall back to `Pretty`.
+    pub fn from_env() -> Self {
+        match std::env::var(LOG_FORMAT_VAR) {
+            Ok(value) => Self::parse(&value).unwrap_or_else(|| {
+                eprintln!("Unknown {}={:?}, using 'pretty'", LOG_FORMAT_VAR, value);
+                Self::Pretty
+            }),
+            Err(_) => Self::Pretty,
+        }
+    }
+}
+
+/// Formats one request in the chosen format.
+fn format_line(
+    format: LogFormat,
+    method: &Method,
+    path: &str,
+    status: StatusCode,
+    elapsed: Duration,
+) -> String {
+    let millis = elapsed.as_secs_f64() * 1000.0;
+    match format {
+        LogFormat::Pretty => format!("{} {} -> {} ({:.2} ms)", method, path, status, millis),
+        LogFormat::Json => json!({
+            "method": method.as_str(),
+            "path": path,
+            "status": status.as_u16(),
+            "duration_ms": millis,
+        })
+        .to_string(),
+    }
+}
+
+/// MIDDLEWARE: Request logging
+///
+/// --- Good to know ---
+/// `warp::log::custom` wraps a whole filter tree via `.with(...)`: it starts a timer
+/// when the request comes in and calls our function once the response (or the
+/// rejection, e.g. a `404`) is known. Every line goes to `sink`, so `main` can print
+/// to stderr while tests collect lines in a `Vec`.
+///
+/// Comparison:
+/// - Go: Like an `http.Handler` wrapper around `next.ServeHTTP` with a response-writer spy.
+/// - Python: Like a Flask `after_request` hook or an ASGI middleware.
+pub fn request_log<S>(format: LogFormat, sink: S) -> Log<impl Fn(Info<'_>) + Clone + Send + Sync>
+where
+    S: Fn(String) + Send + Sync + 'static,
+{
+    let sink = Arc::new(sink);
+    warp::log::custom(move |info: Info<'_>| {
+        sink(format_line(
+            format,
+            info.method(),
+            info.path(),
+            info.status(),
+            info.elapsed(),
+        ));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use warp::Filter;
+
+    /// Runs one request through a logged route and returns the lines it produced.
+    async fn logged_lines(format: LogFormat, method: &str, path: &str) -> Vec<String> {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        let route = warp::path!("hello" / String)
+            .map(|name| format!("Hello, {}!", name))
+            .with(request_log(format, move |line| {
+                sink.lock().unwrap().push(line)
+            }));
+
+        warp::test::request()
+            .method(method)
+            .path(path)
+            .reply(&route)
+            .await;
+        let lines = lines.lock().unwrap().clone();
+        lines
+    }
+
+    #[test]
+    fn test_log_format_parse() {
+        assert_eq!(LogFormat::parse("json"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse(" Pretty "), Some(LogFormat::Pretty));
+        assert_eq!(LogFormat::parse("xml"), None);
+    }
+
+    #[tokio::test]
+    async fn test_pretty_log_line() {
+        let lines = logged_lines(LogFormat::Pretty, "GET", "/hello/rust").await;
+        assert_eq!(lines.len(), 1);
+        assert!(
+            lines[0].starts_with("GET /hello/rust -> 200 OK ("),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[0].ends_with(" ms)"), "{}", lines[0]);
+    }
+
+    #[tokio::test]
+    async fn test_json_log_line_includes_rejections() {
+        // Requests that no route accepts are logged too, with the rejection's status.
+        let lines = logged_lines(LogFormat::Json, "POST", "/nowhere").await;
+        assert_eq!(lines.len(), 1);
+        let entry: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(entry["method"], "POST");
+        assert_eq!(entry["path"], "/nowhere");
+        assert_eq!(entry["status"], 404);
+        assert!(entry["duration_ms"].as_f64().unwrap() >= 0.0);
+    }
+}
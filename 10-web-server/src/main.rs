@@ -1,18 +1,166 @@
+use config::{Config, ConfigError};
+use futures_util::FutureExt;
 use warp::Filter;
+use web_server::metrics::{self, Metrics};
+
+mod auth;
+mod chat;
+mod config;
+mod logging;
+mod pages;
+mod proxy;
+mod ratelimit;
+mod tls;
+mod todos;
+mod uploads;
 
 #[tokio::main]
 async fn main() {
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(ConfigError::Help) => {
+            println!("{}", config::USAGE);
+            return;
+        }
+        Err(ConfigError::Invalid(message)) => {
+            eprintln!("Error: {}\n\n{}", message, config::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // No password configured: make one up for this run rather than ship a default.
+    let admin_password = config.admin_password.clone().unwrap_or_else(|| {
+        use base64::Engine;
+        use rand::RngCore;
+        let mut bytes = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    });
+    let auth = auth::Auth::new(
+        config.admin_user.clone(),
+        admin_password.clone(),
+        auth::SESSION_TTL,
+    );
+
     // Define a route: GET /hello/{name}
     // warp::path! is a macro to easily define path segments.
     // The following route captures a String from the path and maps it to a greeting.
     let hello = warp::path!("hello" / String).map(|name| format!("Hello, {}!", name));
 
-    println!("Starting server at http://127.0.0.1:3030");
-    println!("Try visiting: http://127.0.0.1:3030/hello/world");
+    // The JSON todo API keeps its state in memory: it is lost when the server stops.
+    // .or() combines filters: a request is handled by the first route that matches.
+    // .with() wraps every route in the logging middleware (LOG_FORMAT=json for JSON lines).
+    let log = logging::request_log(logging::LogFormat::from_env(), |line| eprintln!("{}", line));
+    let routes = hello
+        .or(todos::routes(todos::Db::new()))
+        .or(chat::routes(chat::ChatHub::new()))
+        .or(pages::routes())
+        .or(auth::routes(auth))
+        .or(uploads::routes(uploads::Uploads::new(
+            config.upload_dir.clone(),
+            config.upload_max_bytes,
+        )))
+        .or(proxy::routes(config.proxy_upstream.clone().map(
+            |upstream| {
+                let scheme = if config.tls.is_some() {
+                    "https"
+                } else {
+                    "http"
+                };
+                proxy::Proxy::new(upstream).with_scheme(scheme)
+            },
+        )));
+
+    // Every route shares the per-IP limits; throttled requests never reach a handler.
+    // /metrics and /healthz come first, outside the limit: monitoring is never throttled.
+    // metrics::track counts every request, whatever answered it.
+    let metrics = Metrics::new("web_server").expect("metric names are valid");
+    let limiter = ratelimit::RateLimiter::new(config.rate_limit);
+    let routes = metrics::routes(metrics.clone())
+        .or(ratelimit::limit(limiter, routes))
+        // Unknown paths get an HTML 404 page and throttled clients a 429;
+        // recover before logging so the log sees the final status.
+        .recover(pages::not_found)
+        .recover(ratelimit::too_many_requests)
+        .with(log)
+        .with(metrics::track(metrics));
+
+    // try_bind_with_graceful_shutdown binds right away (so the real port is known, even
+    // for port 0) and returns a future that completes once `shutdown_signal` resolves
+    // and the requests in flight have been answered.
+    // With TLS the server type differs, so both futures are boxed into one type.
+    let server = warp::serve(routes);
+    let bound = match &config.tls {
+        None => server
+            .try_bind_with_graceful_shutdown(config.bind_addr, shutdown_signal())
+            .map(|(addr, server)| (addr, server.boxed())),
+        Some(tls) => {
+            let identity = match tls::Identity::load(tls) {
+                Ok(identity) => identity,
+                Err(e) => {
+                    eprintln!("Error: cannot load the TLS certificate: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            server
+                .tls()
+                .cert(identity.cert_pem)
+                .key(identity.key_pem)
+                .try_bind_with_graceful_shutdown(config.bind_addr, shutdown_signal())
+                .map(|(addr, server)| (addr, server.boxed()))
+        }
+    };
+    let (addr, server) = match bound {
+        Ok(bound) => bound,
+        Err(e) => {
+            eprintln!("Error: cannot listen on {}: {}", config.bind_addr, e);
+            std::process::exit(1);
+        }
+    };
+
+    let (http, ws) = match config.tls {
+        Some(_) => ("https", "wss"),
+        None => ("http", "ws"),
+    };
+    println!("Starting server at {}://{} (Ctrl+C to stop)", http, addr);
+    if config.tls == Some(tls::TlsConfig::SelfSigned) {
+        println!("The certificate is self-signed: expect a browser warning, use curl -k");
+    }
+    println!("Try visiting: {}://{}/hello/world", http, addr);
+    println!("Or the todo API: curl -X POST -H 'content-type: application/json' -d '{{\"title\":\"Learn Rust\"}}' {}://{}/todos", http, addr);
+    println!("Or a rendered HTML page: {}://{}/greet/world", http, addr);
+    println!(
+        "Or chat over WebSocket: websocat '{}://{}/chat?nick=alice'",
+        ws, addr
+    );
+    println!(
+        "Or log in at {}://{}/login as {}",
+        http, addr, config.admin_user
+    );
+    if config.admin_password.is_none() {
+        // Stderr, once: stdout tends to end up in shared logs.
+        eprintln!(
+            "Warning: ADMIN_PASSWORD is not set; the password of {} for this run only is {}",
+            config.admin_user, admin_password
+        );
+    }
+    println!(
+        "Or scrape metrics: {}://{}/metrics (health check: /healthz)",
+        http, addr
+    );
+
+    // The .await is needed because running the server is an asynchronous operation.
+    server.await;
+    println!("Server stopped.");
+}
 
-    // Start the server on the specified address and port.
-    // The .await is needed because starting the server is an asynchronous operation.
-    warp::serve(hello).run(([127, 0, 0, 1], 3030)).await;
+/// Resolves on Ctrl+C (SIGINT), which starts the graceful shutdown.
+async fn shutdown_signal() {
+    // ctrl_c() only fails if the handler cannot be installed: then run until killed.
+    if tokio::signal::ctrl_c().await.is_err() {
+        std::future::pending::<()>().await;
+    }
+    println!("Shutting down: finishing in-flight requests...");
 }
 
 #[cfg(test)]
@@ -32,4 +180,39 @@ mod tests {
         assert_eq!(resp.status(), 200);
         assert_eq!(resp.body(), "Hello, rust!");
     }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_resolves() {
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let hello = warp::path!("hello" / String).map(|name| format!("Hello, {}!", name));
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        // Port 0: the OS picks a free port, so tests never collide.
+        let (addr, server) =
+            warp::serve(hello).bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async {
+                stopped.await.ok();
+            });
+        let server = tokio::spawn(server);
+
+        // The server answers real TCP connections until it is told to stop.
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /hello/shutdown HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.ends_with("Hello, shutdown!"), "{}", response);
+
+        stop.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("the server future resolves after the shutdown signal")
+            .unwrap();
+        assert!(
+            tokio::net::TcpStream::connect(addr).await.is_err(),
+            "the listener is closed"
+        );
+    }
 }
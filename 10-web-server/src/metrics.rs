@@ -0,0 +1,213 @@
+//! Prometheus metrics and a health check, packaged as warp filters.
+//!
+//! This module is the crate's library part (`src/lib.rs`), so other warp services
+//! can reuse it with `web-server = { path = "../10-web-server" }`:
+//!
+//! ```no_run
+//! use warp::Filter;
+//! use web_server::metrics::{self, Metrics};
+//!
+//! # async fn run() -> Result<(), prometheus::Error> {
+//! let metrics = Metrics::new("my_app")?;
+//! let app = warp::path!("hello").map(|| "hi");
+//! let routes = metrics::routes(metrics.clone()).or(app).with(metrics::track(metrics));
+//! warp::serve(routes).run(([127, 0, 0, 1], 3030)).await;
+//! # Ok(())
+//! # }
+//! ```
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+use warp::http::{header, Method, StatusCode};
+use warp::log::{Info, Log};
+use warp::{Filter, Rejection, Reply};
+
+/// Latency buckets in seconds: from a cached page (1 ms) to a slow upstream (10 s).
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0,
+];
+
+/// OBSERVABILITY: Prometheus metrics
+///
+/// --- Good to know ---
+/// Prometheus *pulls*: it scrapes `GET /metrics` every few seconds and gets every
+/// metric as text. Two series describe the traffic:
+/// - `{namespace}_http_requests_total{method,route,status}`: a counter, for rates and error ratios.
+/// - `{namespace}_http_request_duration_seconds{method,route}`: a histogram, for latency percentiles.
+///
+/// Labels must stay few: every distinct value is a new time series. So the
+/// route label is only the first path segment (`/todos/42` -> `/todos`), and
+/// requests no route matched are all counted as `unmatched`.
+/// `Metrics` is a handle: clones share the same registry.
+///
+/// Comparison:
+/// - Go: Like `prometheus/client_golang` with `promhttp.Handler()`.
+/// - Python: Like `prometheus_client` with `make_asgi_app()`.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    durations: HistogramVec,
+    started: Instant,
+}
+
+impl Metrics {
+    /// `namespace` prefixes every metric name, e.g. `web_server_http_requests_total`.
+    pub fn new(namespace: &str) -> Result<Self, prometheus::Error> {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "HTTP requests handled, by route and status.",
+            )
+            .namespace(namespace),
+            &["method", "route", "status"],
+        )?;
+        let durations = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time to produce a response, by route.",
+            )
+            .namespace(namespace)
+            .buckets(LATENCY_BUCKETS.to_vec()),
+            &["method", "route"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(durations.clone()))?;
+        Ok(Self {
+            registry,
+            requests,
+            durations,
+            started: Instant::now(),
+        })
+    }
+
+    /// Records one finished request.
+    pub fn observe(&self, method: &Method, path: &str, status: StatusCode, elapsed: Duration) {
+        let route = route_label(path, status);
+        self.requests
+            .with_label_values(&[method.as_str(), route, status.as_str()])
+            .inc();
+        self.durations
+            .with_label_values(&[method.as_str(), route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        // Encoding into a Vec cannot fail for the text format.
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// The first path segment, or `unmatched` when no route answered (a `404` path is
+/// chosen by the client, and must not become a label).
+fn route_label(path: &str, status: StatusCode) -> &str {
+    if status == StatusCode::NOT_FOUND {
+        return "unmatched";
+    }
+    match path.trim_start_matches('/').split('/').next() {
+        Some("") | None => "/",
+        Some(segment) => &path[..segment.len() + 1],
+    }
+}
+
+/// Wraps a route tree (`.with(track(metrics))`) to record every request, the same
+/// way `logging::request_log` does.
+pub fn track(metrics: Metrics) -> Log<impl Fn(Info<'_>) + Clone + Send + Sync> {
+    warp::log::custom(move |info: Info<'_>| {
+        metrics.observe(info.method(), info.path(), info.status(), info.elapsed());
+    })
+}
+
+/// `GET /metrics` (Prometheus text format) and `GET /healthz` (liveness probe:
+/// answering at all means the process is up).
+pub fn routes(metrics: Metrics) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let scrape = {
+        let metrics = metrics.clone();
+        warp::path!("metrics").and(warp::get()).map(move || {
+            warp::reply::with_header(
+                metrics.render(),
+                header::CONTENT_TYPE,
+                prometheus::TEXT_FORMAT,
+            )
+        })
+    };
+    let healthz = warp::path!("healthz").and(warp::get()).map(move || {
+        warp::reply::json(&json!({
+            "status": "ok",
+            "uptime_seconds": metrics.uptime().as_secs(),
+        }))
+    });
+    scrape.or(healthz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::test::request;
+
+    #[tokio::test]
+    async fn test_requests_are_counted_and_exposed() {
+        let metrics = Metrics::new("test").unwrap();
+        let app = warp::path!("todos" / u64).map(|id| format!("todo {}", id));
+        let api = routes(metrics.clone()).or(app).with(track(metrics.clone()));
+
+        request().path("/todos/1").reply(&api).await;
+        request().path("/todos/2").reply(&api).await;
+        request().path("/random-scanner-path").reply(&api).await;
+
+        let resp = request().path("/metrics").reply(&api).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let body = std::str::from_utf8(resp.body()).unwrap();
+        assert!(
+            body.contains(
+                r#"test_http_requests_total{method="GET",route="/todos",status="200"} 2"#
+            ),
+            "{}",
+            body
+        );
+        assert!(body.contains(
+            r#"test_http_requests_total{method="GET",route="unmatched",status="404"} 1"#
+        ));
+        assert!(body.contains(
+            r#"test_http_request_duration_seconds_count{method="GET",route="/todos"} 2"#
+        ));
+        assert!(
+            !body.contains("random-scanner-path"),
+            "client-chosen paths never become labels"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_healthz() {
+        let resp = request()
+            .path("/healthz")
+            .reply(&routes(Metrics::new("test").unwrap()))
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["status"], "ok");
+    }
+
+    #[test]
+    fn test_route_label() {
+        assert_eq!(route_label("/", StatusCode::OK), "/");
+        assert_eq!(route_label("/todos", StatusCode::OK), "/todos");
+        assert_eq!(route_label("/proxy/a/b", StatusCode::BAD_GATEWAY), "/proxy");
+        assert_eq!(route_label("/todos/99", StatusCode::NOT_FOUND), "unmatched");
+    }
+}
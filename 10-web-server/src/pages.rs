@@ -0,0 +1,99 @@
+use askama::Template;
+use percent_encoding::percent_decode_str;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+/// SERVER-SIDE RENDERING: Compile-time templates
+///
+/// --- Good to know ---
+/// `#[derive(Template)]` compiles `templates/greet.html` into Rust code at build
+/// time: a typo in a variable name is a compile error, not a blank on the page.
+/// Templates ending in `.html` escape every `{{ value }}` by default, so a name
+/// like `<script>` is shown as text instead of being run.
+/// `{% extends "layout.html" %}` shares the page skeleton between templates.
+///
+/// Comparison:
+/// - Go: Like `html/template`, but checked by the compiler.
+/// - Python: Like Jinja2 (same syntax), but compiled instead of interpreted.
+#[derive(Template)]
+#[template(path = "greet.html")]
+struct GreetPage<'a> {
+    name: &'a str,
+}
+
+/// Shown when no route matches the request.
+#[derive(Template)]
+#[template(path = "not_found.html")]
+struct NotFoundPage;
+
+/// Turns a template into an HTML reply with the given status.
+pub fn html(template: &impl Template, status: StatusCode) -> warp::reply::Response {
+    match template.render() {
+        Ok(body) => warp::reply::with_status(warp::reply::html(body), status).into_response(),
+        // Only a failing `Display` impl could get here: templates are checked at compile time.
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// GET /greet/{name}. Path segments arrive percent-encoded (`%20` for a space).
+pub fn routes() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("greet" / String)
+        .and(warp::get())
+        .map(|name: String| {
+            let name = percent_decode_str(&name).decode_utf8_lossy();
+            html(&GreetPage { name: &name }, StatusCode::OK)
+        })
+}
+
+/// For `.recover()`: renders the 404 template when no route matched at all. Other
+/// rejections (a `405`, a `400` for a bad JSON body...) keep warp's default response.
+pub async fn not_found(rejection: Rejection) -> Result<warp::reply::Response, Rejection> {
+    if rejection.is_not_found() {
+        Ok(html(&NotFoundPage, StatusCode::NOT_FOUND))
+    } else {
+        Err(rejection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::test::request;
+
+    #[tokio::test]
+    async fn test_greet_page_escapes_the_name() {
+        let resp = request()
+            .path("/greet/%3Cscript%3Ealert(1)%3C%2Fscript%3E")
+            .reply(&routes())
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()["content-type"], "text/html; charset=utf-8");
+        let body = std::str::from_utf8(resp.body()).unwrap();
+        assert!(
+            body.contains("<title>Hello, &#60;script&#62;alert(1)&#60;/script&#62;!</title>"),
+            "{}",
+            body
+        );
+        assert!(!body.contains("<script>"));
+        // The layout wraps the page.
+        assert!(body.contains("<footer>"));
+    }
+
+    #[tokio::test]
+    async fn test_not_found_template_only_replaces_404s() {
+        let api = routes().recover(not_found);
+
+        let resp = request().path("/nowhere").reply(&api).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert!(std::str::from_utf8(resp.body())
+            .unwrap()
+            .contains("404: Page not found"));
+
+        let resp = request()
+            .method("POST")
+            .path("/greet/rust")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
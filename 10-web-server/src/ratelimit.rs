@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use warp::http::{header, StatusCode};
+use warp::{Filter, Rejection, Reply};
+
+/// Past this many tracked IPs, buckets that have refilled completely are forgotten.
+const MAX_TRACKED_IPS: usize = 10_000;
+
+/// Limits applied to every client IP.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// Sustained rate: tokens added to each bucket per second. `0` disables rate limiting.
+    pub requests_per_second: f64,
+    /// Bucket size: how many requests may arrive at once after a quiet period.
+    pub burst: u32,
+    /// Requests from one IP being handled at the same time. `0` means no cap.
+    pub max_in_flight: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 10.0,
+            burst: 20,
+            max_in_flight: 8,
+        }
+    }
+}
+
+/// Why a request was turned away; `recover` turns it into a `429`.
+#[derive(Debug)]
+pub struct RateLimited {
+    retry_after: Duration,
+}
+
+impl warp::reject::Reject for RateLimited {}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    buckets: HashMap<IpAddr, Bucket>,
+    in_flight: HashMap<IpAddr, usize>,
+}
+
+/// RATE LIMITING: A token bucket per client IP
+///
+/// --- Good to know ---
+/// Each IP owns a bucket of `burst` tokens that refills at `requests_per_second`.
+/// A request takes one token; an empty bucket means `429 Too Many Requests` with a
+/// `Retry-After` header saying when the next token arrives. This allows short
+/// bursts (a page loading its assets) while capping the sustained rate.
+/// The in-flight cap bounds how many requests one IP can have open at once, so a
+/// client with slow uploads cannot tie up every worker. It counts requests, not TCP
+/// connections: `warp::serve` does not expose its accept loop.
+/// Behind a reverse proxy every request shares the proxy's IP; a real deployment
+/// would key on a trusted `X-Forwarded-For` instead.
+///
+/// Comparison:
+/// - Go: Like `golang.org/x/time/rate.Limiter`, one per IP in a map.
+/// - Python: Like `slowapi` / Flask-Limiter with the default remote-address key.
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    state: Arc<Mutex<State>>,
+}
+
+/// Proof that a request was admitted. Dropping it frees the IP's in-flight slot.
+pub struct Permit {
+    ip: IpAddr,
+    limiter: Option<RateLimiter>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let Some(limiter) = &self.limiter else { return };
+        let mut state = limiter.state.lock().unwrap();
+        if let Some(count) = state.in_flight.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                state.in_flight.remove(&self.ip);
+            }
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Takes a token and an in-flight slot for `ip`, or says how long to wait.
+    fn admit(&self, ip: IpAddr, now: Instant) -> Result<Permit, RateLimited> {
+        let mut state = self.state.lock().unwrap();
+
+        if self.config.max_in_flight > 0
+            && state.in_flight.get(&ip).copied().unwrap_or(0) >= self.config.max_in_flight
+        {
+            // No way to know when a slot frees up; one second is a polite guess.
+            return Err(RateLimited {
+                retry_after: Duration::from_secs(1),
+            });
+        }
+
+        if self.config.requests_per_second > 0.0 {
+            let (rate, burst) = (
+                self.config.requests_per_second,
+                f64::from(self.config.burst),
+            );
+            if state.buckets.len() >= MAX_TRACKED_IPS {
+                state.buckets.retain(|_, b| {
+                    b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < burst
+                });
+            }
+            let bucket = state.buckets.entry(ip).or_insert(Bucket {
+                tokens: burst,
+                updated: now,
+            });
+            // Refill lazily: add what accrued since the last request, up to the bucket size.
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+            bucket.updated = now;
+            if bucket.tokens < 1.0 {
+                return Err(RateLimited {
+                    retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / rate),
+                });
+            }
+            bucket.tokens -= 1.0;
+        }
+
+        if self.config.max_in_flight == 0 {
+            return Ok(Permit { ip, limiter: None });
+        }
+        *state.in_flight.entry(ip).or_insert(0) += 1;
+        Ok(Permit {
+            ip,
+            limiter: Some(self.clone()),
+        })
+    }
+}
+
+/// Wraps a route tree: requests over the limit are rejected with `RateLimited`
+/// before any route runs. Pair it with `.recover(too_many_requests)`.
+pub fn limit<F, T>(
+    limiter: RateLimiter,
+    routes: F,
+) -> impl Filter<Extract = (T,), Error = Rejection> + Clone
+where
+    F: Filter<Extract = (T,), Error = Rejection> + Clone + Send + Sync + 'static,
+    T: Reply,
+{
+    warp::addr::remote()
+        .and_then(move |remote: Option<SocketAddr>| {
+            let limiter = limiter.clone();
+            async move {
+                // No peer address (e.g. in `warp::test`): all such requests share one bucket.
+                let ip = remote.map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |addr| addr.ip());
+                limiter
+                    .admit(ip, Instant::now())
+                    .map_err(warp::reject::custom)
+            }
+        })
+        .and(routes)
+        // The permit lives until the reply is ready, then frees the in-flight slot.
+        .map(|_permit: Permit, reply: T| reply)
+}
+
+/// For `.recover()`: renders `RateLimited` as `429` with `Retry-After` (whole seconds,
+/// rounded up). Other rejections are passed on.
+pub async fn too_many_requests(rejection: Rejection) -> Result<warp::reply::Response, Rejection> {
+    let Some(limited) = rejection.find::<RateLimited>() else {
+        return Err(rejection);
+    };
+    let seconds = limited.retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let reply = warp::reply::with_status(
+        "Too many requests, slow down.",
+        StatusCode::TOO_MANY_REQUESTS,
+    );
+    Ok(warp::reply::with_header(reply, header::RETRY_AFTER, seconds.to_string()).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::test::request;
+
+    fn config(requests_per_second: f64, burst: u32, max_in_flight: usize) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_second,
+            burst,
+            max_in_flight,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flood_is_throttled_per_ip() {
+        let limiter = RateLimiter::new(config(1.0, 5, 0));
+        let api = limit(limiter, warp::path!("ping").map(|| "ok")).recover(too_many_requests);
+        let from = |ip: &str| {
+            request()
+                .path("/ping")
+                .remote_addr(format!("{}:4000", ip).parse().unwrap())
+        };
+
+        // The burst goes through, then the flood is cut off.
+        let mut statuses = Vec::new();
+        for _ in 0..20 {
+            statuses.push(from("198.51.100.1").reply(&api).await.status());
+        }
+        assert_eq!(statuses.iter().filter(|s| **s == StatusCode::OK).count(), 5);
+        assert_eq!(statuses[5..], [StatusCode::TOO_MANY_REQUESTS; 15]);
+
+        let resp = from("198.51.100.1").reply(&api).await;
+        assert_eq!(resp.headers()["retry-after"], "1");
+        // Another client has its own bucket.
+        assert_eq!(
+            from("198.51.100.2").reply(&api).await.status(),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = RateLimiter::new(config(2.0, 2, 0));
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let start = Instant::now();
+        assert!(limiter.admit(ip, start).is_ok());
+        assert!(limiter.admit(ip, start).is_ok());
+        let limited = limiter.admit(ip, start).err().expect("bucket is empty");
+        assert_eq!(limited.retry_after, Duration::from_millis(500));
+
+        // Two tokens per second: half a second later, one request fits again.
+        assert!(limiter
+            .admit(ip, start + Duration::from_millis(500))
+            .is_ok());
+        assert!(limiter
+            .admit(ip, start + Duration::from_millis(500))
+            .is_err());
+    }
+
+    #[test]
+    fn test_in_flight_cap() {
+        let limiter = RateLimiter::new(config(0.0, 0, 2));
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let first = limiter.admit(ip, Instant::now()).unwrap();
+        let _second = limiter.admit(ip, Instant::now()).unwrap();
+        assert!(
+            limiter.admit(ip, Instant::now()).is_err(),
+            "a third concurrent request is refused"
+        );
+
+        drop(first);
+        assert!(
+            limiter.admit(ip, Instant::now()).is_ok(),
+            "finishing a request frees its slot"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::io;
+use std::path::PathBuf;
+
+/// Names the development certificate is valid for.
+const LOCAL_NAMES: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+/// HTTPS AND HTTP/2: rustls behind warp's `tls` feature
+///
+/// --- Good to know ---
+/// `warp::serve(routes).tls()` wraps each connection in rustls (pure Rust, no
+/// OpenSSL). During the handshake the client and server agree on a protocol via
+/// ALPN: warp offers `h2` first, so browsers and `curl` get HTTP/2 for free, while
+/// older clients fall back to HTTP/1.1. The routes do not change at all.
+/// Browsers only speak HTTP/2 over TLS, which is why the two come together.
+///
+/// The certificate comes from PEM files or is generated at startup.
+///
+/// Comparison:
+/// - Go: Like `http.ListenAndServeTLS(addr, certFile, keyFile, handler)` (HTTP/2 included).
+/// - Python: Like `uvicorn --ssl-certfile cert.pem --ssl-keyfile key.pem` (HTTP/1.1 only).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TlsConfig {
+    /// PEM files, e.g. from Let's Encrypt (`TLS_CERT` / `TLS_KEY`).
+    Files { cert: PathBuf, key: PathBuf },
+    /// A certificate generated at startup for `localhost` (`--self-signed`).
+    SelfSigned,
+}
+
+/// A certificate chain and its private key, both PEM-encoded.
+pub struct Identity {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+impl Identity {
+    pub fn load(config: &TlsConfig) -> io::Result<Self> {
+        match config {
+            TlsConfig::Files { cert, key } => Ok(Self {
+                cert_pem: std::fs::read(cert)?,
+                key_pem: std::fs::read(key)?,
+            }),
+            TlsConfig::SelfSigned => Self::self_signed().map_err(io::Error::other),
+        }
+    }
+
+    /// DEVELOPMENT ONLY: A self-signed certificate
+    ///
+    /// --- Good to know ---
+    /// Nobody vouches for this certificate, so browsers show a warning and `curl`
+    /// needs `-k`. It is fine for trying HTTPS and HTTP/2 locally; a public server
+    /// needs one signed by a CA. A fresh key pair is made on every start.
+    ///
+    /// Comparison:
+    /// - Go: Like the `generate_cert.go` helper shipped in `crypto/tls`.
+    /// - Python: Like `openssl req -x509 -nodes -newkey ...` (or the `trustme` package in tests).
+    pub fn self_signed() -> Result<Self, rcgen::Error> {
+        let names: Vec<String> = LOCAL_NAMES.iter().map(|name| name.to_string()).collect();
+        let certified = rcgen::generate_simple_self_signed(names)?;
+        Ok(Self {
+            cert_pem: certified.cert.pem().into_bytes(),
+            key_pem: certified.key_pair.serialize_pem().into_bytes(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+    use tokio_rustls::TlsConnector;
+    use warp::Filter;
+
+    /// A client that trusts only `identity` and offers the given ALPN protocols.
+    fn connector(identity: &Identity, alpn: &[&[u8]]) -> TlsConnector {
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut identity.cert_pem.as_slice()) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let mut config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+        TlsConnector::from(Arc::new(config))
+    }
+
+    #[tokio::test]
+    async fn test_tls_listener_starts_and_offers_http2() {
+        let identity = Identity::self_signed().unwrap();
+        let (addr, server) = warp::serve(warp::path!("hello").map(|| "secure hello"))
+            .tls()
+            .cert(&identity.cert_pem)
+            .key(&identity.key_pem)
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        let localhost = ServerName::try_from("localhost").unwrap();
+
+        // A client offering both protocols is given HTTP/2.
+        let tcp = TcpStream::connect(addr).await.unwrap();
+        let tls = connector(&identity, &[b"h2", b"http/1.1"])
+            .connect(localhost.clone(), tcp)
+            .await
+            .expect("TLS handshake with the self-signed certificate");
+        assert_eq!(tls.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+
+        // An HTTP/1.1-only client still gets served.
+        let tcp = TcpStream::connect(addr).await.unwrap();
+        let mut tls = connector(&identity, &[b"http/1.1"])
+            .connect(localhost, tcp)
+            .await
+            .unwrap();
+        tls.write_all(b"GET /hello HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        tls.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("secure hello"));
+    }
+
+    #[test]
+    fn test_load_reads_pem_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let generated = Identity::self_signed().unwrap();
+        let (cert, key) = (dir.path().join("cert.pem"), dir.path().join("key.pem"));
+        std::fs::write(&cert, &generated.cert_pem).unwrap();
+        std::fs::write(&key, &generated.key_pem).unwrap();
+
+        let loaded = Identity::load(&TlsConfig::Files { cert, key }).unwrap();
+        assert_eq!(loaded.cert_pem, generated.cert_pem);
+        assert!(Identity::load(&TlsConfig::Files {
+            cert: dir.path().join("missing.pem"),
+            key: dir.path().join("key.pem"),
+        })
+        .is_err());
+    }
+}
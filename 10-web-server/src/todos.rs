@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+/// Request bodies larger than this are rejected with `413` before being parsed.
+const MAX_BODY_BYTES: u64 = 16 * 1024;
+
+/// A todo item, as stored and as returned by the API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Todo {
+    pub id: u64,
+    pub title: String,
+    pub completed: bool,
+}
+
+/// Body of `POST /todos`: the server assigns the ID, new items start uncompleted.
+#[derive(Debug, Deserialize)]
+pub struct CreateTodo {
+    pub title: String,
+}
+
+/// Body of `PUT /todos/{id}`: a PUT replaces the whole item, so both fields are required.
+#[derive(Debug, Deserialize)]
+pub struct UpdateTodo {
+    pub title: String,
+    pub completed: bool,
+}
+
+/// Error body shared by every failed request: `{"error": "..."}`.
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// SHARED STATE: The in-memory database
+///
+/// --- Good to know ---
+/// Handlers run concurrently on Tokio's worker threads, so the map lives behind
+/// `Arc` (shared ownership) and `RwLock` (many readers OR one writer). Tokio's
+/// `RwLock` is used instead of `std`'s because the guard may be held across `.await`.
+/// Cloning a `Db` is cheap: it only bumps the reference counts.
+///
+/// Comparison:
+/// - Go: Like a struct holding a `map[uint64]Todo` and a `sync.RWMutex`.
+/// - Python: Like a module-level `dict` guarded by an `asyncio.Lock`.
+#[derive(Clone, Default)]
+pub struct Db {
+    todos: Arc<RwLock<HashMap<u64, Todo>>>,
+    // IDs are never reused, even after a delete, so a stale ID never points at a new item.
+    next_id: Arc<AtomicU64>,
+}
+
+impl Db {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// All `/todos` routes. Each route is its own filter; `.or()` tries them in order.
+pub fn routes(db: Db) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    list(db.clone())
+        .or(get(db.clone()))
+        .or(create(db.clone()))
+        .or(update(db.clone()))
+        .or(delete(db))
+}
+
+/// GET /todos
+fn list(db: Db) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("todos")
+        .and(warp::get())
+        .and(with_db(db))
+        .and_then(list_todos)
+}
+
+/// GET /todos/{id}
+fn get(db: Db) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("todos" / u64)
+        .and(warp::get())
+        .and(with_db(db))
+        .and_then(get_todo)
+}
+
+/// POST /todos with a JSON body
+fn create(db: Db) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("todos")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_db(db))
+        .and_then(create_todo)
+}
+
+/// PUT /todos/{id} with a JSON body
+fn update(db: Db) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("todos" / u64)
+        .and(warp::put())
+        .and(json_body())
+        .and(with_db(db))
+        .and_then(update_todo)
+}
+
+/// DELETE /todos/{id}
+fn delete(db: Db) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("todos" / u64)
+        .and(warp::delete())
+        .and(with_db(db))
+        .and_then(delete_todo)
+}
+
+/// Injects a clone of the state into each request, like a dependency.
+fn with_db(db: Db) -> impl Filter<Extract = (Db,), Error = Infallible> + Clone {
+    warp::any().map(move || db.clone())
+}
+
+/// Size limit first, then deserialization: malformed JSON is rejected with `400`.
+fn json_body<T: serde::de::DeserializeOwned + Send>(
+) -> impl Filter<Extract = (T,), Error = Rejection> + Clone {
+    warp::body::content_length_limit(MAX_BODY_BYTES).and(warp::body::json())
+}
+
+fn error(status: StatusCode, message: &str) -> warp::reply::Response {
+    let body = warp::reply::json(&ErrorBody {
+        error: message.to_string(),
+    });
+    warp::reply::with_status(body, status).into_response()
+}
+
+/// Titles are trimmed; a blank title is well-formed JSON but not a valid todo (`422`).
+fn valid_title(title: &str) -> Option<String> {
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+fn blank_title() -> warp::reply::Response {
+    error(StatusCode::UNPROCESSABLE_ENTITY, "title must not be empty")
+}
+
+// Handlers return `Infallible`: every outcome, including "not found", is a response.
+
+async fn list_todos(db: Db) -> Result<warp::reply::Response, Infallible> {
+    let mut todos: Vec<Todo> = db.todos.read().await.values().cloned().collect();
+    // HashMap iteration order is random; sort so clients get a stable list.
+    todos.sort_by_key(|todo| todo.id);
+    Ok(warp::reply::json(&todos).into_response())
+}
+
+async fn get_todo(id: u64, db: Db) -> Result<warp::reply::Response, Infallible> {
+    Ok(match db.todos.read().await.get(&id) {
+        Some(todo) => warp::reply::json(todo).into_response(),
+        None => error(StatusCode::NOT_FOUND, "todo not found"),
+    })
+}
+
+async fn create_todo(body: CreateTodo, db: Db) -> Result<warp::reply::Response, Infallible> {
+    let Some(title) = valid_title(&body.title) else {
+        return Ok(blank_title());
+    };
+    let todo = Todo {
+        id: db.next_id(),
+        title,
+        completed: false,
+    };
+    db.todos.write().await.insert(todo.id, todo.clone());
+
+    let location = format!("/todos/{}", todo.id);
+    let reply = warp::reply::with_status(warp::reply::json(&todo), StatusCode::CREATED);
+    Ok(warp::reply::with_header(reply, "location", location).into_response())
+}
+
+async fn update_todo(
+    id: u64,
+    body: UpdateTodo,
+    db: Db,
+) -> Result<warp::reply::Response, Infallible> {
+    let Some(title) = valid_title(&body.title) else {
+        return Ok(blank_title());
+    };
+    // The write guard lives until the end of the match, so the lookup and the update are atomic.
+    Ok(match db.todos.write().await.get_mut(&id) {
+        Some(todo) => {
+            todo.title = title;
+            todo.completed = body.completed;
+            warp::reply::json(todo).into_response()
+        }
+        None => error(StatusCode::NOT_FOUND, "todo not found"),
+    })
+}
+
+async fn delete_todo(id: u64, db: Db) -> Result<warp::reply::Response, Infallible> {
+    Ok(match db.todos.write().await.remove(&id) {
+        Some(_) => StatusCode::NO_CONTENT.into_response(),
+        None => error(StatusCode::NOT_FOUND, "todo not found"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::test::request;
+
+    async fn create(
+        api: &(impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone + 'static),
+        title: &str,
+    ) -> Todo {
+        let resp = request()
+            .method("POST")
+            .path("/todos")
+            .json(&serde_json::json!({ "title": title }))
+            .reply(api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        serde_json::from_slice(resp.body()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_todo_crud_round_trip() {
+        let api = routes(Db::new());
+
+        let resp = request()
+            .method("POST")
+            .path("/todos")
+            .json(&serde_json::json!({ "title": " Learn warp " }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.headers()["location"], "/todos/1");
+        let todo: Todo = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            todo,
+            Todo {
+                id: 1,
+                title: "Learn warp".to_string(),
+                completed: false
+            }
+        );
+        create(&api, "Write tests").await;
+
+        let resp = request().path("/todos").reply(&api).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let todos: Vec<Todo> = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(todos.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let resp = request()
+            .method("PUT")
+            .path("/todos/1")
+            .json(&serde_json::json!({ "title": "Learn warp filters", "completed": true }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = request().path("/todos/1").reply(&api).await;
+        let todo: Todo = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            todo,
+            Todo {
+                id: 1,
+                title: "Learn warp filters".to_string(),
+                completed: true
+            }
+        );
+
+        let resp = request()
+            .method("DELETE")
+            .path("/todos/1")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(resp.body().is_empty());
+        assert_eq!(
+            request().path("/todos/1").reply(&api).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        // IDs are not reused after a delete.
+        assert_eq!(create(&api, "Ship it").await.id, 3);
+    }
+
+    #[tokio::test]
+    async fn test_todo_error_status_codes() {
+        let api = routes(Db::new());
+
+        let resp = request().path("/todos/42").reply(&api).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.body(), r#"{"error":"todo not found"}"#);
+        let update = serde_json::json!({ "title": "x", "completed": false });
+        assert_eq!(
+            request()
+                .method("PUT")
+                .path("/todos/42")
+                .json(&update)
+                .reply(&api)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            request()
+                .method("DELETE")
+                .path("/todos/42")
+                .reply(&api)
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let blank = serde_json::json!({ "title": "   " });
+        let resp = request()
+            .method("POST")
+            .path("/todos")
+            .json(&blank)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let resp = request()
+            .method("POST")
+            .path("/todos")
+            .header("content-type", "application/json")
+            .body("{not json")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // A PUT must carry the whole item.
+        let partial = serde_json::json!({ "title": "x" });
+        let todo = create(&api, "Full replace").await;
+        let resp = request()
+            .method("PUT")
+            .path(&format!("/todos/{}", todo.id))
+            .json(&partial)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let huge = serde_json::json!({ "title": "x".repeat(MAX_BODY_BYTES as usize) });
+        let resp = request()
+            .method("POST")
+            .path("/todos")
+            .json(&huge)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        assert_eq!(
+            request()
+                .method("PATCH")
+                .path("/todos/1")
+                .reply(&api)
+                .await
+                .status(),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use futures_util::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use warp::http::StatusCode;
+use warp::hyper::body::Buf;
+use warp::multipart::{FormData, Part};
+use warp::{Filter, Rejection, Reply};
+
+/// Longest stored filename, in characters (extension included).
+const MAX_FILENAME_LEN: usize = 100;
+
+/// One stored file, as listed by `GET /files` and returned by `POST /upload`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct StoredFile {
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+fn error(status: StatusCode, message: &str) -> warp::reply::Response {
+    let body = warp::reply::json(&ErrorBody {
+        error: message.to_string(),
+    });
+    warp::reply::with_status(body, status).into_response()
+}
+
+/// Where uploads go and how big a request may be.
+#[derive(Debug, Clone)]
+pub struct Uploads {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl Uploads {
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes,
+        }
+    }
+
+    /// Streams one part to disk under a fresh name; removes the partial file on error.
+    async fn store(&self, part: Part, filename: &str) -> io::Result<StoredFile> {
+        fs::create_dir_all(&self.dir).await?;
+        let (name, mut file) = create_unique(&self.dir, filename).await?;
+        let path = self.dir.join(&name);
+
+        let written = write_stream(part, &mut file).await;
+        match written {
+            Ok(size) => Ok(StoredFile { name, size }),
+            Err(e) => {
+                drop(file);
+                let _ = fs::remove_file(&path).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Removes files stored earlier in a request that then failed: a rejected
+    /// upload leaves nothing behind.
+    async fn discard(&self, stored: &[StoredFile]) {
+        for file in stored {
+            if let Err(e) = fs::remove_file(self.dir.join(&file.name)).await {
+                eprintln!("Error removing upload {}: {}", file.name, e);
+            }
+        }
+    }
+
+    async fn list(&self) -> io::Result<Vec<StoredFile>> {
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            // Nothing uploaded yet.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_file() {
+                files.push(StoredFile {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    size: metadata.len(),
+                });
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+}
+
+/// Copies the part's chunks to the file as they arrive: memory use stays at one
+/// chunk, whatever the file size.
+async fn write_stream(part: Part, file: &mut File) -> io::Result<u64> {
+    let mut chunks = part.stream();
+    let mut size = 0;
+    while let Some(chunk) = chunks.next().await {
+        let mut chunk = chunk.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        while chunk.has_remaining() {
+            let len = chunk.chunk().len();
+            file.write_all(chunk.chunk()).await?;
+            size += len as u64;
+            chunk.advance(len);
+        }
+    }
+    file.flush().await?;
+    Ok(size)
+}
+
+/// Opens `name`, or `stem-1.ext`, `stem-2.ext`... if taken. `create_new` makes the
+/// check and the creation one atomic step, so two uploads never share a file.
+async fn create_unique(dir: &Path, name: &str) -> io::Result<(String, File)> {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    for n in 0..1000 {
+        let candidate = match n {
+            0 => name.to_string(),
+            n => format!("{}-{}{}", stem, n, extension),
+        };
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(&candidate))
+            .await
+        {
+            Ok(file) => return Ok((candidate, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "too many files with this name",
+    ))
+}
+
+/// UNTRUSTED INPUT: Filenames
+///
+/// --- Good to know ---
+/// The client picks the filename, so it may be `../../etc/passwd`, `C:\boot.ini`,
+/// `.bashrc` or 10 KB long. Only the last path component is kept, anything outside
+/// `[A-Za-z0-9._-]` becomes `_`, and leading dots are dropped, so the result can
+/// never leave the upload directory or hide itself.
+///
+/// Comparison:
+/// - Go: Like `filepath.Base` plus a character allow-list.
+/// - Python: Like Werkzeug's `secure_filename()`.
+pub fn sanitize_filename(raw: &str) -> String {
+    let base = raw.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    // Keep the end of long names: that is where the extension is.
+    let skip = cleaned.chars().count().saturating_sub(MAX_FILENAME_LEN);
+    let cleaned: String = cleaned.chars().skip(skip).collect();
+    if cleaned.is_empty() {
+        "upload".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// FILE UPLOADS: `POST /upload` (multipart/form-data) and `GET /files`
+///
+/// --- Good to know ---
+/// `warp::multipart::form()` parses the body lazily: each `Part` is a stream of
+/// chunks, written to disk as it arrives instead of being buffered. The size limit
+/// is checked against `Content-Length` before a single byte is read (`413`).
+/// Only parts with a filename are stored; plain text fields are ignored.
+/// A request that fails part-way keeps none of its files.
+///
+/// Comparison:
+/// - Go: Like `r.MultipartReader()` copying each part with `io.Copy`.
+/// - Python: Like Starlette's `UploadFile`, but never spooled to a temp file.
+pub fn routes(uploads: Uploads) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let form = warp::multipart::form().max_length(uploads.max_bytes);
+    let with_uploads = warp::any().map(move || uploads.clone());
+
+    let upload = warp::path!("upload")
+        .and(warp::post())
+        .and(with_uploads.clone())
+        .and(form)
+        .and_then(upload);
+
+    let list = warp::path!("files")
+        .and(warp::get())
+        .and(with_uploads)
+        .and_then(list_files);
+
+    upload.or(list)
+}
+
+async fn upload(uploads: Uploads, form: FormData) -> Result<warp::reply::Response, Infallible> {
+    let mut stored = Vec::new();
+    let result = store_parts(&uploads, form, &mut stored).await;
+    if result.is_err() {
+        uploads.discard(&stored).await;
+    }
+    Ok(match result {
+        Ok(()) if stored.is_empty() => error(StatusCode::BAD_REQUEST, "no file in the form"),
+        Ok(()) => warp::reply::with_status(warp::reply::json(&stored), StatusCode::CREATED)
+            .into_response(),
+        Err(response) => response,
+    })
+}
+
+/// Stores every file part, recording each in `stored` as soon as it is on disk, so
+/// the caller can remove them all if a later part fails.
+async fn store_parts(
+    uploads: &Uploads,
+    mut form: FormData,
+    stored: &mut Vec<StoredFile>,
+) -> Result<(), warp::reply::Response> {
+    loop {
+        let part = match form.try_next().await {
+            Ok(Some(part)) => part,
+            Ok(None) => break,
+            Err(e) => {
+                return Err(error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("invalid multipart body: {}", e),
+                ))
+            }
+        };
+        let Some(filename) = part.filename().map(sanitize_filename) else {
+            continue;
+        };
+        match uploads.store(part, &filename).await {
+            Ok(file) => stored.push(file),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                return Err(error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("upload interrupted: {}", e),
+                ))
+            }
+            Err(e) => {
+                eprintln!("Error storing upload: {}", e);
+                return Err(error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "could not store the file",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn list_files(uploads: Uploads) -> Result<warp::reply::Response, Infallible> {
+    Ok(match uploads.list().await {
+        Ok(files) => warp::reply::json(&files).into_response(),
+        Err(e) => {
+            eprintln!("Error listing uploads: {}", e);
+            error(StatusCode::INTERNAL_SERVER_ERROR, "could not list files")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use warp::test::request;
+
+    const BOUNDARY: &str = "XBOUNDARYX";
+
+    /// A multipart body with one part per `(field, filename, content)`.
+    fn multipart(parts: &[(&str, Option<&str>, &str)]) -> String {
+        let mut body = String::new();
+        for (field, filename, content) in parts {
+            body.push_str(&format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+                BOUNDARY, field
+            ));
+            if let Some(filename) = filename {
+                body.push_str(&format!("; filename=\"{}\"", filename));
+            }
+            body.push_str(&format!("\r\n\r\n{}\r\n", content));
+        }
+        body.push_str(&format!("--{}--\r\n", BOUNDARY));
+        body
+    }
+
+    fn post(body: String) -> warp::test::RequestBuilder {
+        request()
+            .method("POST")
+            .path("/upload")
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .header("content-length", body.len())
+            .body(body)
+    }
+
+    #[tokio::test]
+    async fn test_upload_and_list() {
+        let dir = tempdir().unwrap();
+        let api = routes(Uploads::new(dir.path().join("uploads"), 1024 * 1024));
+
+        // Nothing uploaded yet, and the directory does not even exist.
+        let resp = request().path("/files").reply(&api).await;
+        assert_eq!(resp.body(), "[]");
+
+        let body = multipart(&[
+            ("comment", None, "ignored"),
+            ("file", Some("../../notes.txt"), "hello"),
+            ("file", Some("notes.txt"), "again!"),
+        ]);
+        let resp = post(body).reply(&api).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let stored: Vec<StoredFile> = serde_json::from_slice(resp.body()).unwrap();
+        let expected = vec![
+            StoredFile {
+                name: "notes.txt".to_string(),
+                size: 5,
+            },
+            StoredFile {
+                name: "notes-1.txt".to_string(),
+                size: 6,
+            },
+        ];
+        assert_eq!(stored, expected);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("uploads/notes.txt")).unwrap(),
+            "hello"
+        );
+
+        let resp = request().path("/files").reply(&api).await;
+        let listed: Vec<StoredFile> = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            listed.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+            ["notes-1.txt", "notes.txt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_limits_and_errors() {
+        let dir = tempdir().unwrap();
+        let api = routes(Uploads::new(dir.path(), 256));
+
+        let big = multipart(&[("file", Some("big.bin"), &"x".repeat(300))]);
+        assert_eq!(
+            post(big).reply(&api).await.status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        let no_file = multipart(&[("comment", None, "just text")]);
+        assert_eq!(
+            post(no_file).reply(&api).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            std::fs::read_dir(dir.path()).unwrap().count(),
+            0,
+            "nothing was written"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_upload_removes_earlier_parts() {
+        let dir = tempdir().unwrap();
+        let api = routes(Uploads::new(dir.path(), 1024 * 1024));
+
+        // The first file arrives whole; the body then ends in the middle of the second.
+        let mut body = multipart(&[("file", Some("first.txt"), "complete")]);
+        body.truncate(body.len() - format!("--{}--\r\n", BOUNDARY).len());
+        body.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"second.txt\"\r\n\r\ncut off",
+            BOUNDARY
+        ));
+        assert_eq!(
+            post(body).reply(&api).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            std::fs::read_dir(dir.path()).unwrap().count(),
+            0,
+            "the stored first part was removed"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(
+            sanitize_filename("C:\\Users\\me\\report (final).pdf"),
+            "report__final_.pdf"
+        );
+        assert_eq!(sanitize_filename(".bashrc"), "bashrc");
+        assert_eq!(sanitize_filename(".."), "upload");
+        assert_eq!(sanitize_filename("résumé.txt"), "r_sum_.txt");
+        let long = format!("{}.tar.gz", "a".repeat(200));
+        assert_eq!(sanitize_filename(&long).len(), MAX_FILENAME_LEN);
+        assert!(sanitize_filename(&long).ends_with(".tar.gz"));
+    }
+}
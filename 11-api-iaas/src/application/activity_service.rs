@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use uuid::Uuid;
+use crate::domain::{ActivityKind, ActivityLog};
+use super::dto::ActivityPage;
+use super::errors::ServiceError;
+use super::ports::ViewActivity;
+
+/// Page size bounds for the activity feed.
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 200;
+
+/// APPLICATION SERVICE: Activity feed
+///
+/// --- Good to know ---
+/// Offset pagination (`?page=3`) skips or repeats items when new events arrive between
+/// requests. A cursor instead says "continue after *this* event": since sequence numbers
+/// only grow, a page never changes once a client has seen its cursor. That's what
+/// makes infinite scroll reliable.
+///
+/// Comparison:
+/// - Go: Like the `pageToken` / `nextPageToken` pattern of Google APIs.
+/// - Python: Like Django REST Framework's `CursorPagination`.
+pub struct ActivityService {
+    log: Arc<dyn ActivityLog>,
+}
+
+impl ActivityService {
+    pub fn new(log: Arc<dyn ActivityLog>) -> Self {
+        Self { log }
+    }
+}
+
+#[async_trait]
+impl ViewActivity for ActivityService {
+    /// Use Case: Browse Activity.
+    async fn activity(&self, cursor: Option<&str>, limit: Option<usize>) -> anyhow::Result<ActivityPage> {
+        let before = cursor.map(decode_cursor).transpose()?;
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+        // Ask for one extra event: if it exists, there is another page.
+        let mut events = self.log.list_before(before, limit + 1).await?;
+        let next_cursor = if events.len() > limit {
+            events.truncate(limit);
+            events.last().map(|event| encode_cursor(event.sequence))
+        } else {
+            None
+        };
+        Ok(ActivityPage { events, next_cursor })
+    }
+}
+
+/// Appends an event if an activity log is configured. A failed write is reported but not
+/// propagated: the change it describes has already been persisted.
+pub(super) async fn record(log: Option<&Arc<dyn ActivityLog>>, resource_id: Uuid, kind: ActivityKind) {
+    if let Some(log) = log {
+        if let Err(e) = log.append(resource_id, kind).await {
+            eprintln!("Failed to record activity for {}: {:#}", resource_id, e);
+        }
+    }
+}
+
+/// Cursors are opaque to clients, so the encoding can change without breaking them.
+pub(super) fn encode_cursor(sequence: u64) -> String {
+    URL_SAFE_NO_PAD.encode(sequence.to_string())
+}
+
+pub(super) fn decode_cursor(cursor: &str) -> anyhow::Result<u64> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| ServiceError::Invalid("invalid cursor".to_string()).into())
+}
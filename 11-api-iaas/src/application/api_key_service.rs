@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+use uuid::Uuid;
+use crate::domain::{ActivityKind, ActivityLog, ApiKey, ApiKeyRepository};
+use super::activity_service::record;
+use super::dto::{CreateApiKeyCommand, IssuedApiKey};
+use super::errors::ServiceError;
+use super::ports::{HashPasswords, ManageApiKeys};
+
+/// Every key starts with this, so leaked keys are easy to spot (e.g. by secret scanners).
+const KEY_PREFIX: &str = "iaas_";
+
+/// APPLICATION SERVICE: API keys
+///
+/// --- Good to know ---
+/// A key looks like `iaas_<key id>_<random secret>`. The ID lets us find the stored
+/// record directly instead of hashing against every key; the secret is 256 random bits,
+/// so a fast hash is enough (slow hashes only matter for guessable passwords).
+///
+/// Comparison:
+/// - Go / Python: The same scheme as GitHub (`ghp_...`) or Stripe (`sk_live_...`) keys.
+pub struct ApiKeyService {
+    repo: Arc<dyn ApiKeyRepository>,
+    hasher: Arc<dyn HashPasswords>,
+    activity: Option<Arc<dyn ActivityLog>>,
+}
+
+impl ApiKeyService {
+    pub fn new(repo: Arc<dyn ApiKeyRepository>, hasher: Arc<dyn HashPasswords>) -> Self {
+        Self { repo, hasher, activity: None }
+    }
+
+    /// Builder-style: also record key changes in an activity log.
+    pub fn with_activity_log(mut self, log: Arc<dyn ActivityLog>) -> Self {
+        self.activity = Some(log);
+        self
+    }
+}
+
+#[async_trait]
+impl ManageApiKeys for ApiKeyService {
+    /// Use Case: Create API Key.
+    /// Business Rule: a key needs a name and at least one scope.
+    async fn create_api_key(&self, cmd: CreateApiKeyCommand) -> anyhow::Result<IssuedApiKey> {
+        let name = cmd.name.trim().to_string();
+        if name.is_empty() {
+            return Err(ServiceError::Invalid("API key name must not be empty".to_string()).into());
+        }
+        if cmd.scopes.is_empty() {
+            return Err(ServiceError::Invalid("an API key needs at least one scope".to_string()).into());
+        }
+
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let secret = URL_SAFE_NO_PAD.encode(bytes);
+        let key = ApiKey::new(name, self.hasher.hash(&secret)?, cmd.scopes);
+        self.repo.save(&key).await?;
+        record(self.activity.as_ref(), key.id, ActivityKind::ApiKeyCreated { name: key.name.clone() }).await;
+
+        let secret = format!("{}{}_{}", KEY_PREFIX, key.id.simple(), secret);
+        Ok(IssuedApiKey { key, secret })
+    }
+
+    /// Use Case: List API Keys.
+    async fn list_api_keys(&self) -> anyhow::Result<Vec<ApiKey>> {
+        self.repo.list_all().await
+    }
+
+    /// Use Case: Revoke API Key. Revoking twice is harmless.
+    async fn revoke_api_key(&self, id: Uuid) -> anyhow::Result<ApiKey> {
+        let mut key = self
+            .repo
+            .find_by_id(id)
+            .await?
+            .ok_or(ServiceError::NotFound { resource: "API key", id })?;
+        if !key.revoked {
+            key.revoked = true;
+            self.repo.save(&key).await?;
+            record(self.activity.as_ref(), key.id, ActivityKind::ApiKeyRevoked).await;
+        }
+        Ok(key)
+    }
+
+    /// Use Case: Authenticate with an API key.
+    /// Malformed, unknown, revoked and wrong keys all look the same to the caller.
+    async fn authenticate_api_key(&self, presented: &str) -> anyhow::Result<Option<ApiKey>> {
+        let Some((id, secret)) = presented.strip_prefix(KEY_PREFIX).and_then(|rest| rest.split_once('_')) else {
+            return Ok(None);
+        };
+        let Ok(id) = Uuid::parse_str(id) else {
+            return Ok(None);
+        };
+        match self.repo.find_by_id(id).await? {
+            Some(key) if !key.revoked && self.hasher.verify(secret, &key.secret_hash)? => Ok(Some(key)),
+            _ => Ok(None),
+        }
+    }
+}
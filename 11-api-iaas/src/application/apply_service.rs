@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use crate::domain::{Disk, Server};
+use super::dto::{
+    ApplyReport, AttachDiskCommand, CreateServerCommand, DiskSpec, Manifest, PlanAction, PlannedChange, ServerSpec,
+    UpdateServerCommand,
+};
+use super::errors::ServiceError;
+use super::ports::{ApplyManifests, ManageServers};
+use super::service::validate_tags;
+
+/// APPLICATION SERVICE: Declarative apply
+///
+/// --- Good to know ---
+/// Instead of scripting "create this, then attach that", the client describes the end
+/// state and the service works out the steps: first a plan (desired vs. current state),
+/// then carrying it out through the regular use cases, so every rule and activity event
+/// still applies. Applying the same manifest twice changes nothing, which is also the
+/// recovery story: if an apply fails halfway, fix the cause and apply again.
+///
+/// Comparison:
+/// - Go: Like a Kubernetes controller's reconcile loop, or `terraform plan` + `apply`.
+/// - Python: Like Ansible's desired-state modules.
+pub struct ApplyService {
+    servers: Arc<dyn ManageServers>,
+    /// Two overlapping applies would each plan against a state the other is changing.
+    applying: Mutex<()>,
+}
+
+/// A plan step together with what is needed to carry it out.
+struct Step {
+    change: PlannedChange,
+    spec: Option<ServerSpec>,
+    /// Disks the server already has and keeps; only the ones after them get attached.
+    kept_disks: usize,
+}
+
+impl ApplyService {
+    pub fn new(servers: Arc<dyn ManageServers>) -> Self {
+        Self { servers, applying: Mutex::new(()) }
+    }
+
+    /// Deletes first (they free names), then replacements, creations and in-place updates.
+    async fn plan(&self, manifest: Manifest) -> anyhow::Result<Vec<Step>> {
+        let mut names = HashSet::new();
+        for spec in &manifest.servers {
+            validate_spec(spec)?;
+            if !names.insert(spec.name.as_str()) {
+                return Err(ServiceError::Invalid(format!("server '{}' is listed twice", spec.name)).into());
+            }
+        }
+
+        let current = self.servers.list_servers().await?;
+        let mut deletes = Vec::new();
+        let mut replaces = Vec::new();
+        let mut creates = Vec::new();
+        let mut updates = Vec::new();
+        for server in current.iter().filter(|s| !names.contains(s.name.as_str())) {
+            let change = planned(PlanAction::Delete, &server.name, Some(server.id), vec!["not in manifest".to_string()]);
+            deletes.push(Step { change, spec: None, kept_disks: 0 });
+        }
+        for spec in manifest.servers {
+            let Some(server) = current.iter().find(|s| s.name == spec.name) else {
+                let change = planned(PlanAction::Create, &spec.name, None, Vec::new());
+                creates.push(Step { change, spec: Some(spec), kept_disks: 0 });
+                continue;
+            };
+            match diff(server, &spec) {
+                Some((PlanAction::Replace, details)) => {
+                    let change = planned(PlanAction::Replace, &spec.name, Some(server.id), details);
+                    replaces.push(Step { change, spec: Some(spec), kept_disks: 0 });
+                }
+                Some((action, details)) => {
+                    let change = planned(action, &spec.name, Some(server.id), details);
+                    let kept_disks = server.additional_disks.len();
+                    updates.push(Step { change, spec: Some(spec), kept_disks });
+                }
+                None => {}
+            }
+        }
+        Ok(deletes.into_iter().chain(replaces).chain(creates).chain(updates).collect())
+    }
+
+    async fn execute(&self, step: Step) -> anyhow::Result<PlannedChange> {
+        let mut change = step.change;
+        match (change.action, change.server_id, step.spec) {
+            (PlanAction::Delete, Some(id), _) => self.servers.delete_server(id, false).await?,
+            (PlanAction::Replace, Some(id), Some(spec)) => {
+                self.servers.delete_server(id, false).await?;
+                change.server_id = Some(self.create(spec).await?);
+            }
+            (PlanAction::Create, _, Some(spec)) => change.server_id = Some(self.create(spec).await?),
+            (PlanAction::Update, Some(id), Some(spec)) => {
+                let cmd = UpdateServerCommand {
+                    server_id: id,
+                    description: Some(spec.description),
+                    clear_tags: true,
+                    tags: spec.tags.into_iter().map(|(key, value)| (key, Some(value))).collect(),
+                    ..Default::default()
+                };
+                self.servers.update_server(cmd).await?;
+                self.attach(id, &spec.disks[step.kept_disks..]).await?;
+            }
+            _ => unreachable!("plan steps always carry what their action needs"),
+        }
+        Ok(change)
+    }
+
+    async fn create(&self, spec: ServerSpec) -> anyhow::Result<Uuid> {
+        let cmd = CreateServerCommand {
+            name: spec.name,
+            cpu: spec.cpu,
+            ram: spec.ram,
+            storage: spec.storage,
+            description: spec.description,
+            tags: spec.tags,
+            ..Default::default()
+        };
+        let server = self.servers.create_server(cmd).await?;
+        self.attach(server.id, &spec.disks).await?;
+        Ok(server.id)
+    }
+
+    async fn attach(&self, server_id: Uuid, disks: &[DiskSpec]) -> anyhow::Result<()> {
+        for disk in disks {
+            let cmd = AttachDiskCommand {
+                server_id,
+                size_gb: disk.size_gb,
+                disk_type: disk.disk_type,
+                iops: disk.iops,
+                dry_run: false,
+            };
+            self.servers.attach_disk(cmd).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApplyManifests for ApplyService {
+    /// Use Case: Apply Manifest.
+    /// Business Rule: the manifest is the complete inventory; unlisted servers are deleted.
+    async fn apply(&self, manifest: Manifest, dry_run: bool) -> anyhow::Result<ApplyReport> {
+        let _guard = self.applying.lock().await;
+        let steps = self.plan(manifest).await?;
+        let plan: Vec<PlannedChange> = steps.iter().map(|step| step.change.clone()).collect();
+        if dry_run {
+            return Ok(ApplyReport { plan, applied: Vec::new() });
+        }
+
+        let mut applied = Vec::new();
+        for step in steps {
+            applied.push(self.execute(step).await?);
+        }
+        Ok(ApplyReport { plan, applied })
+    }
+}
+
+fn planned(action: PlanAction, name: &str, server_id: Option<Uuid>, details: Vec<String>) -> PlannedChange {
+    PlannedChange { action, name: name.to_string(), server_id, details }
+}
+
+/// Runs the same rules as the individual endpoints, so a dry run catches what the apply would hit.
+fn validate_spec(spec: &ServerSpec) -> Result<(), ServiceError> {
+    let invalid = |reason: String| ServiceError::Invalid(format!("server '{}': {}", spec.name, reason));
+    if spec.name.trim().is_empty() {
+        return Err(ServiceError::Invalid("server names must not be empty".to_string()));
+    }
+    validate_tags(&spec.tags).map_err(|e| invalid(e.to_string()))?;
+    let mut candidate = Server::new(spec.name.clone(), spec.cpu, spec.ram, spec.storage);
+    for disk in &spec.disks {
+        let disk = Disk::new(disk.size_gb, disk.disk_type, disk.iops).map_err(invalid)?;
+        candidate.attach_disk(disk).map_err(invalid)?;
+    }
+    Ok(())
+}
+
+/// What it takes to turn `server` into `spec`, or `None` if it already matches.
+/// Sizing can't change in place and disks can't be detached, so those force a replacement.
+fn diff(server: &Server, spec: &ServerSpec) -> Option<(PlanAction, Vec<String>)> {
+    let mut replace = Vec::new();
+    if (server.cpu_cores, server.ram_gb) != (spec.cpu, spec.ram) {
+        replace.push(format!("flavor c{}.r{} -> c{}.r{}", server.cpu_cores, server.ram_gb, spec.cpu, spec.ram));
+    }
+    if server.storage_gb != spec.storage {
+        replace.push(format!("storage {} GB -> {} GB", server.storage_gb, spec.storage));
+    }
+    for (i, (disk, wanted)) in server.additional_disks.iter().zip(&spec.disks).enumerate() {
+        // Without explicit IOPS, whatever the disk was provisioned with is fine.
+        let matches = disk.size_gb == wanted.size_gb
+            && disk.disk_type == wanted.disk_type
+            && wanted.iops.is_none_or(|iops| iops == disk.iops);
+        if !matches {
+            replace.push(format!("disk {} differs", i));
+        }
+    }
+    if server.additional_disks.len() > spec.disks.len() {
+        replace.push(format!("{} disk(s) would be removed", server.additional_disks.len() - spec.disks.len()));
+    }
+    if !replace.is_empty() {
+        return Some((PlanAction::Replace, replace));
+    }
+
+    let mut update = Vec::new();
+    if server.description != spec.description {
+        update.push("description".to_string());
+    }
+    if server.tags != spec.tags {
+        update.push("tags".to_string());
+    }
+    if spec.disks.len() > server.additional_disks.len() {
+        update.push(format!("attach {} disk(s)", spec.disks.len() - server.additional_disks.len()));
+    }
+    (!update.is_empty()).then_some((PlanAction::Update, update))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::DiskType;
+
+    fn spec(cpu: u32, disks: Vec<DiskSpec>) -> ServerSpec {
+        ServerSpec { name: "vm".to_string(), cpu, ram: 4, storage: 40, disks, description: None, tags: Default::default() }
+    }
+
+    fn ssd(size_gb: u32) -> DiskSpec {
+        DiskSpec { size_gb, disk_type: DiskType::Ssd, iops: None }
+    }
+
+    #[test]
+    fn test_diff_updates_in_place_or_replaces() {
+        let mut server = Server::new("vm".to_string(), 2, 4, 40);
+        server.attach_disk(Disk::new(100, DiskType::Ssd, None).unwrap()).unwrap();
+
+        assert!(diff(&server, &spec(2, vec![ssd(100)])).is_none());
+        let (action, details) = diff(&server, &spec(2, vec![ssd(100), ssd(200)])).unwrap();
+        assert_eq!(action, PlanAction::Update);
+        assert_eq!(details, vec!["attach 1 disk(s)"]);
+
+        let (action, details) = diff(&server, &spec(4, vec![ssd(100)])).unwrap();
+        assert_eq!(action, PlanAction::Replace);
+        assert_eq!(details, vec!["flavor c2.r4 -> c4.r4"]);
+        assert_eq!(diff(&server, &spec(2, Vec::new())).unwrap().0, PlanAction::Replace);
+        assert_eq!(diff(&server, &spec(2, vec![ssd(500)])).unwrap().0, PlanAction::Replace);
+    }
+}
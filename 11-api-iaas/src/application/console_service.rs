@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::domain::ServerRepository;
+use super::errors::ServiceError;
+use super::ports::{ConsoleFollow, ConsoleLog, ReadConsole};
+
+/// Upper bound for `tail`, so one request can't pull a whole rotated log into memory.
+pub const MAX_TAIL_LINES: usize = 10_000;
+
+/// APPLICATION SERVICE: Console logs
+/// Only adds what the storage adapter can't know: whether the server exists.
+pub struct ConsoleService {
+    servers: Arc<dyn ServerRepository>,
+    log: Arc<dyn ConsoleLog>,
+}
+
+impl ConsoleService {
+    pub fn new(servers: Arc<dyn ServerRepository>, log: Arc<dyn ConsoleLog>) -> Self {
+        Self { servers, log }
+    }
+
+    async fn ensure_server(&self, id: Uuid) -> anyhow::Result<()> {
+        match self.servers.find_by_id(id).await? {
+            Some(_) => Ok(()),
+            None => Err(ServiceError::NotFound { resource: "Server", id }.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl ReadConsole for ConsoleService {
+    /// Use Case: Read Console.
+    async fn tail(&self, server_id: Uuid, lines: usize) -> anyhow::Result<Vec<String>> {
+        self.ensure_server(server_id).await?;
+        self.log.tail(server_id, lines.min(MAX_TAIL_LINES)).await
+    }
+
+    /// Use Case: Follow Console.
+    async fn follow(&self, server_id: Uuid, lines: usize) -> anyhow::Result<ConsoleFollow> {
+        self.ensure_server(server_id).await?;
+        self.log.follow(server_id, lines.min(MAX_TAIL_LINES)).await
+    }
+}
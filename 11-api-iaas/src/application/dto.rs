@@ -1,4 +1,6 @@
+use std::collections::{BTreeMap, BTreeSet};
 use uuid::Uuid;
+use crate::domain::{ActivityEvent, ApiKey, DiskType, NotificationChannel, Role, Scope, Server};
 
 /// APPLICATION DTO (Data Transfer Object): CreateServerCommand
 ///
@@ -8,15 +10,167 @@ use uuid::Uuid;
 /// Comparison:
 /// - Python: Like a dedicated Pydantic class for a Service method.
 /// - Go: A custom struct passed into a service function.
+#[derive(Default)]
 pub struct CreateServerCommand {
     pub name: String,
     pub cpu: u32,
     pub ram: u32,
     pub storage: u32,
+    pub ssh_keys: Vec<String>,
+    pub user_data: Option<String>,
+    pub description: Option<String>,
+    pub tags: BTreeMap<String, String>,
+    /// Availability zone to place the server in; `None` picks the first one with room.
+    pub zone: Option<String>,
+    /// Validate and return the would-be server without storing or booting it.
+    pub dry_run: bool,
+}
+
+/// A check the stored resource must pass before a change applies.
+pub type Precondition = Box<dyn Fn(&Server) -> bool + Send + Sync>;
+
+/// APPLICATION DTO: UpdateServerCommand
+/// A partial update: every field left at its default means "leave unchanged".
+#[derive(Default)]
+pub struct UpdateServerCommand {
+    pub server_id: Uuid,
+    pub name: Option<String>,
+    /// `Some(None)` removes the description, `None` keeps it.
+    pub description: Option<Option<String>>,
+    /// Remove every tag before applying `tags`.
+    pub clear_tags: bool,
+    /// Per tag: `Some(value)` sets it, `None` removes it.
+    pub tags: BTreeMap<String, Option<String>>,
+    /// Optimistic locking: the update only applies if the stored server passes this
+    /// check (the web layer compares `If-Match` with its ETag), else `PreconditionFailed`.
+    pub precondition: Option<Precondition>,
+    pub dry_run: bool,
 }
 
 /// APPLICATION DTO: AttachDiskCommand
 pub struct AttachDiskCommand {
     pub server_id: Uuid,
     pub size_gb: u32,
+    pub disk_type: DiskType,
+    /// Provisioned IOPS; `None` uses the tier's baseline for the size.
+    pub iops: Option<u32>,
+    pub dry_run: bool,
+}
+
+/// APPLICATION DTO: Manifest
+/// The desired set of servers. Every server that is not listed gets deleted.
+pub struct Manifest {
+    pub servers: Vec<ServerSpec>,
+}
+
+/// One server as it should exist, identified by its unique name.
+#[derive(Clone)]
+pub struct ServerSpec {
+    pub name: String,
+    pub cpu: u32,
+    pub ram: u32,
+    pub storage: u32,
+    /// Additional disks, in attachment order.
+    pub disks: Vec<DiskSpec>,
+    pub description: Option<String>,
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Clone)]
+pub struct DiskSpec {
+    pub size_gb: u32,
+    pub disk_type: DiskType,
+    /// Provisioned IOPS; `None` means the tier's baseline for the size.
+    pub iops: Option<u32>,
+}
+
+/// What an apply does to one server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlanAction {
+    Create,
+    /// Change description/tags and attach new disks, in place.
+    Update,
+    /// Delete and re-create: the flavor changed or disks would have to be removed.
+    Replace,
+    Delete,
+}
+
+/// APPLICATION DTO: One step of an apply plan.
+#[derive(Debug, Clone)]
+pub struct PlannedChange {
+    pub action: PlanAction,
+    pub name: String,
+    /// The existing server; after applying a create or replace, the new one.
+    pub server_id: Option<Uuid>,
+    /// Human-readable reasons, e.g. `flavor c2.r4 -> c4.r8`.
+    pub details: Vec<String>,
+}
+
+/// APPLICATION DTO: The plan, and what of it was carried out.
+pub struct ApplyReport {
+    pub plan: Vec<PlannedChange>,
+    /// Empty for a dry run.
+    pub applied: Vec<PlannedChange>,
+}
+
+/// APPLICATION DTO: CreateVolumeCommand
+pub struct CreateVolumeCommand {
+    pub name: String,
+    pub size_gb: u32,
+    pub disk_type: DiskType,
+    /// Provisioned IOPS; `None` uses the tier's baseline for the size.
+    pub iops: Option<u32>,
+    pub dry_run: bool,
+}
+
+/// APPLICATION DTO: CreateApiKeyCommand
+pub struct CreateApiKeyCommand {
+    pub name: String,
+    pub scopes: BTreeSet<Scope>,
+}
+
+/// APPLICATION DTO: A freshly created key together with its one-time secret.
+pub struct IssuedApiKey {
+    pub key: ApiKey,
+    /// The full key to send as `x-api-key`. Never stored, never shown again.
+    pub secret: String,
+}
+
+/// APPLICATION DTO: CreateNotificationRuleCommand
+pub struct CreateNotificationRuleCommand {
+    pub name: String,
+    /// Event types to notify on, e.g. `server_deleted`.
+    pub events: BTreeSet<String>,
+    pub channel: NotificationChannel,
+}
+
+/// APPLICATION DTO: A message for a `Notifier`, independent of the channel.
+pub struct Notification {
+    pub subject: String,
+    pub text: String,
+}
+
+/// APPLICATION DTO: CreateUserCommand
+pub struct CreateUserCommand {
+    pub username: String,
+    pub password: String,
+    pub role: Role,
+}
+
+/// APPLICATION DTO: Server IDs changed since a cursor, collapsed to their net effect.
+pub struct ServerChanges {
+    pub created: Vec<Uuid>,
+    pub updated: Vec<Uuid>,
+    pub deleted: Vec<Uuid>,
+    /// Pass this as `since` next time. Always set, even when nothing changed.
+    pub next_cursor: String,
+    /// More changes are waiting: call again right away with `next_cursor`.
+    pub has_more: bool,
+}
+
+/// APPLICATION DTO: One page of the activity feed.
+pub struct ActivityPage {
+    pub events: Vec<ActivityEvent>,
+    /// Pass this back to get the next (older) page; `None` on the last page.
+    pub next_cursor: Option<String>,
 }
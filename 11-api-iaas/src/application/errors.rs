@@ -0,0 +1,57 @@
+use std::fmt;
+use uuid::Uuid;
+use crate::domain::Shortage;
+
+/// APPLICATION ERRORS
+///
+/// --- Good to know ---
+/// Use cases still return `anyhow::Result`, but business failures are raised as this
+/// typed enum. Adapters can then `downcast_ref::<ServiceError>()` to pick the right
+/// response (404, 409, ...) while unexpected errors (I/O, parsing) stay opaque.
+///
+/// Comparison:
+/// - Go: Like sentinel errors checked with `errors.As(err, &target)`.
+/// - Python: Like raising custom exception classes and catching them in the view.
+#[derive(Debug)]
+pub enum ServiceError {
+    /// No `resource` (e.g. "Server") exists with the given ID.
+    NotFound { resource: &'static str, id: Uuid },
+    /// Names are unique per resource; `existing_id` is the one already using the name.
+    NameConflict { resource: &'static str, name: String, existing_id: Uuid },
+    /// The resource's current state forbids the action (e.g. deleting an attached volume);
+    /// `conflicting_id` is the resource that blocks it.
+    Conflict { reason: String, conflicting_id: Uuid },
+    /// The command breaks a business rule (e.g. a password that is too short).
+    Invalid(String),
+    /// No zone can host the request; one `Shortage` per zone that was considered.
+    InsufficientCapacity { shortages: Vec<Shortage> },
+    /// The resource changed since the caller last read it (see `UpdateServerCommand::precondition`).
+    PreconditionFailed { resource: &'static str, id: Uuid },
+    /// Too many failed attempts (e.g. logins); the caller may retry after `retry_after`.
+    TooManyAttempts { retry_after: std::time::Duration },
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::NotFound { resource, id } => write!(f, "{} {} not found", resource, id),
+            ServiceError::NameConflict { resource, name, existing_id } => {
+                write!(f, "{} name '{}' is already used by {}", resource, name, existing_id)
+            }
+            ServiceError::Conflict { reason, .. } => write!(f, "{}", reason),
+            ServiceError::Invalid(reason) => write!(f, "{}", reason),
+            ServiceError::InsufficientCapacity { shortages } => {
+                let details: Vec<String> = shortages.iter().map(Shortage::to_string).collect();
+                write!(f, "Insufficient capacity: {}", details.join("; "))
+            }
+            ServiceError::PreconditionFailed { resource, id } => {
+                write!(f, "{} {} has changed since it was read", resource, id)
+            }
+            ServiceError::TooManyAttempts { retry_after } => {
+                write!(f, "Too many failed attempts, retry in {} seconds", retry_after.as_secs() + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
@@ -1,7 +1,34 @@
+mod activity_service;
+mod api_key_service;
+mod apply_service;
+mod changes_service;
+mod console_service;
 mod dto;
+mod errors;
+mod notification_service;
 mod ports;
 mod service;
+mod user_service;
+mod volume_service;
 
-pub use dto::{AttachDiskCommand, CreateServerCommand};
-pub use ports::ManageServers;
+pub use activity_service::ActivityService;
+pub use api_key_service::ApiKeyService;
+pub use apply_service::ApplyService;
+pub use changes_service::ChangesService;
+pub use console_service::ConsoleService;
+pub use dto::{
+    ActivityPage, ApplyReport, AttachDiskCommand, CreateApiKeyCommand, CreateServerCommand,
+    CreateNotificationRuleCommand, CreateUserCommand, CreateVolumeCommand, DiskSpec, IssuedApiKey, Manifest, Notification,
+    PlanAction, PlannedChange, Precondition,
+    ServerSpec, UpdateServerCommand,
+};
+pub use errors::ServiceError;
+pub use notification_service::{NotificationService, NotifyingActivityLog};
+pub use ports::{
+    ApplyManifests, ComponentHealth, ConsoleFollow, ConsoleLog, HashPasswords, Hypervisor, ManageApiKeys, ManageNotifications,
+    ManageServers, ManageUsers, ManageVolumes, Principal, ReadConsole, Notifier, ReportHealth, SyncServers,
+    VerifyTokens, ViewActivity,
+};
 pub use service::ServerService;
+pub use user_service::UserService;
+pub use volume_service::VolumeService;
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::domain::{
+    ActivityEvent, ActivityKind, ActivityLog, NotificationChannel, NotificationRule, NotificationRuleRepository,
+};
+use super::dto::{CreateNotificationRuleCommand, Notification};
+use super::errors::ServiceError;
+use super::ports::{ManageNotifications, Notifier};
+
+/// APPLICATION SERVICE: Notifications
+/// Owns the rules and routes each matching event to the notifier of the rule's channel.
+/// A channel without a configured notifier can't be used in new rules.
+pub struct NotificationService {
+    rules: Arc<dyn NotificationRuleRepository>,
+    email: Option<Arc<dyn Notifier>>,
+    slack: Option<Arc<dyn Notifier>>,
+}
+
+impl NotificationService {
+    pub fn new(rules: Arc<dyn NotificationRuleRepository>) -> Self {
+        Self { rules, email: None, slack: None }
+    }
+
+    /// Builder-style: enable `email` rules.
+    pub fn with_email(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.email = Some(notifier);
+        self
+    }
+
+    /// Builder-style: enable `slack` rules.
+    pub fn with_slack(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.slack = Some(notifier);
+        self
+    }
+
+    fn route<'a>(&'a self, channel: &'a NotificationChannel) -> Option<(&'a Arc<dyn Notifier>, &'a str)> {
+        match channel {
+            NotificationChannel::Email { to } => self.email.as_ref().map(|n| (n, to.as_str())),
+            NotificationChannel::Slack { webhook_url } => self.slack.as_ref().map(|n| (n, webhook_url.as_str())),
+        }
+    }
+
+    /// Sends `event` to every matching rule and returns how many notifications went out.
+    /// One failing channel doesn't keep the others from being notified.
+    pub async fn dispatch(&self, event: &ActivityEvent) -> anyhow::Result<usize> {
+        let notification = Notification {
+            subject: format!("[IaaS] {}", event.kind.describe()),
+            text: format!(
+                "{}\n\nEvent: {}\nResource: {}\nSequence: {}\nOccurred at: {} (Unix time)",
+                event.kind.describe(),
+                event.kind.event_type(),
+                event.resource_id,
+                event.sequence,
+                event.occurred_at
+            ),
+        };
+        let mut sent = 0;
+        for rule in self.rules.list_all().await?.iter().filter(|rule| rule.matches(&event.kind)) {
+            let Some((notifier, destination)) = self.route(&rule.channel) else {
+                eprintln!("Notification rule '{}' skipped: its channel is not configured", rule.name);
+                continue;
+            };
+            match notifier.notify(destination, &notification).await {
+                Ok(()) => sent += 1,
+                Err(e) => eprintln!("Notification rule '{}' failed: {:#}", rule.name, e),
+            }
+        }
+        Ok(sent)
+    }
+}
+
+#[async_trait]
+impl ManageNotifications for NotificationService {
+    /// Use Case: Create Notification Rule.
+    /// Business Rules: known event types only, and the channel must be configured and well-formed.
+    async fn create_notification_rule(&self, cmd: CreateNotificationRuleCommand) -> anyhow::Result<NotificationRule> {
+        let invalid = |reason: String| ServiceError::Invalid(reason);
+        let name = cmd.name.trim().to_string();
+        if name.is_empty() {
+            return Err(invalid("rule name must not be empty".to_string()).into());
+        }
+        if cmd.events.is_empty() {
+            return Err(invalid("a rule needs at least one event type".to_string()).into());
+        }
+        if let Some(unknown) = cmd.events.iter().find(|e| !ActivityKind::EVENT_TYPES.contains(&e.as_str())) {
+            return Err(invalid(format!("unknown event type '{}'", unknown)).into());
+        }
+        match &cmd.channel {
+            NotificationChannel::Email { to } if !to.contains('@') => {
+                return Err(invalid(format!("'{}' is not an e-mail address", to)).into());
+            }
+            // The server will POST to this URL: plain HTTP would leak events in transit.
+            NotificationChannel::Slack { webhook_url } if !webhook_url.starts_with("https://") => {
+                return Err(invalid("Slack webhook URLs must use https://".to_string()).into());
+            }
+            channel if self.route(channel).is_none() => {
+                return Err(invalid("this notification channel is not configured on the server".to_string()).into());
+            }
+            _ => {}
+        }
+
+        let rule = NotificationRule::new(name, cmd.events, cmd.channel);
+        self.rules.save(&rule).await?;
+        Ok(rule)
+    }
+
+    /// Use Case: List Notification Rules.
+    async fn list_notification_rules(&self) -> anyhow::Result<Vec<NotificationRule>> {
+        self.rules.list_all().await
+    }
+
+    /// Use Case: Delete Notification Rule.
+    async fn delete_notification_rule(&self, id: Uuid) -> anyhow::Result<()> {
+        if !self.rules.delete(id).await? {
+            return Err(ServiceError::NotFound { resource: "Notification rule", id }.into());
+        }
+        Ok(())
+    }
+}
+
+/// DECORATOR PATTERN: Notifying activity log
+///
+/// --- Good to know ---
+/// Every change in the platform already flows through the activity log, which makes it
+/// our event bus: wrapping it means no use case has to know about notifications. Sending
+/// happens on a background task, so a slow mail server never delays an API response.
+///
+/// Comparison:
+/// - Go: Like a subscriber goroutine reading from an event channel.
+/// - Python: Like a Celery task queued from a Django signal.
+pub struct NotifyingActivityLog {
+    inner: Arc<dyn ActivityLog>,
+    notifications: Arc<NotificationService>,
+}
+
+impl NotifyingActivityLog {
+    pub fn new(inner: Arc<dyn ActivityLog>, notifications: Arc<NotificationService>) -> Self {
+        Self { inner, notifications }
+    }
+}
+
+#[async_trait]
+impl ActivityLog for NotifyingActivityLog {
+    async fn append(&self, resource_id: Uuid, kind: ActivityKind) -> anyhow::Result<ActivityEvent> {
+        let event = self.inner.append(resource_id, kind).await?;
+        let notifications = Arc::clone(&self.notifications);
+        let recorded = event.clone();
+        tokio::spawn(async move {
+            if let Err(e) = notifications.dispatch(&recorded).await {
+                eprintln!("Notifications for event {} failed: {:#}", recorded.sequence, e);
+            }
+        });
+        Ok(event)
+    }
+
+    async fn list_before(&self, before: Option<u64>, limit: usize) -> anyhow::Result<Vec<ActivityEvent>> {
+        self.inner.list_before(before, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Test double: rules kept in memory.
+    #[derive(Default)]
+    struct MemoryRules(Mutex<Vec<NotificationRule>>);
+
+    #[async_trait]
+    impl NotificationRuleRepository for MemoryRules {
+        async fn save(&self, rule: &NotificationRule) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(rule.clone());
+            Ok(())
+        }
+
+        async fn list_all(&self) -> anyhow::Result<Vec<NotificationRule>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+            let mut rules = self.0.lock().unwrap();
+            let before = rules.len();
+            rules.retain(|rule| rule.id != id);
+            Ok(rules.len() < before)
+        }
+    }
+
+    /// Test double: remembers (destination, subject) of every notification.
+    #[derive(Default)]
+    struct RecordingNotifier(Mutex<Vec<(String, String)>>);
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, destination: &str, notification: &Notification) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push((destination.to_string(), notification.subject.clone()));
+            Ok(())
+        }
+    }
+
+    fn event(kind: ActivityKind) -> ActivityEvent {
+        ActivityEvent { sequence: 1, occurred_at: 100, resource_id: Uuid::new_v4(), kind }
+    }
+
+    #[tokio::test]
+    async fn test_matching_events_reach_their_channel() -> anyhow::Result<()> {
+        let slack = Arc::new(RecordingNotifier::default());
+        let service = NotificationService::new(Arc::new(MemoryRules::default())).with_slack(slack.clone());
+        let hook = "https://hooks.slack.com/services/T0/B0/x".to_string();
+        let cmd = |events: &[&str], channel: NotificationChannel| CreateNotificationRuleCommand {
+            name: "ops".to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            channel,
+        };
+        service
+            .create_notification_rule(cmd(&["server_deleted", "provisioning_failed"], NotificationChannel::Slack { webhook_url: hook.clone() }))
+            .await?;
+
+        assert_eq!(service.dispatch(&event(ActivityKind::ServerDeleted)).await?, 1);
+        assert_eq!(service.dispatch(&event(ActivityKind::VolumeDeleted)).await?, 0);
+        assert_eq!(slack.0.lock().unwrap()[0], (hook, "[IaaS] Server deleted".to_string()));
+
+        // Unknown events, plain-HTTP webhooks and unconfigured channels are refused.
+        let http = NotificationChannel::Slack { webhook_url: "http://example.com".to_string() };
+        assert!(service.create_notification_rule(cmd(&["server_deleted"], http)).await.is_err());
+        let slack_channel = NotificationChannel::Slack { webhook_url: "https://example.com".to_string() };
+        assert!(service.create_notification_rule(cmd(&["server_exploded"], slack_channel)).await.is_err());
+        let email = NotificationChannel::Email { to: "ops@example.com".to_string() };
+        assert!(service.create_notification_rule(cmd(&["server_deleted"], email)).await.is_err());
+        Ok(())
+    }
+}
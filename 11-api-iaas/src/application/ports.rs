@@ -1,6 +1,13 @@
 use async_trait::async_trait;
-use crate::domain::Server;
-use super::dto::{CreateServerCommand, AttachDiskCommand};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+use std::collections::BTreeSet;
+use crate::domain::{ApiKey, NotificationRule, Role, Scope, Server, User, Volume};
+use super::dto::{
+    ActivityPage, ApplyReport, AttachDiskCommand, CreateApiKeyCommand, CreateNotificationRuleCommand,
+    CreateServerCommand, CreateUserCommand, CreateVolumeCommand, IssuedApiKey, Manifest, Notification,
+    ServerChanges, UpdateServerCommand,
+};
 
 /// HEXAGONAL ARCHITECTURE: INBOUND PORT
 /// 
@@ -10,6 +17,9 @@ use super::dto::{CreateServerCommand, AttachDiskCommand};
 /// 
 /// If you want to add a CLI later, the CLI would talk to this interface,
 /// exactly like the Web API does now.
+///
+/// Every change can be a `dry_run`: it runs all checks and returns what the result
+/// would be, but stores nothing and records no activity.
 /// 
 /// Comparison:
 /// - Go: Like a "Service" interface definition.
@@ -18,5 +28,181 @@ use super::dto::{CreateServerCommand, AttachDiskCommand};
 pub trait ManageServers: Send + Sync {
     async fn create_server(&self, cmd: CreateServerCommand) -> anyhow::Result<Server>;
     async fn list_servers(&self) -> anyhow::Result<Vec<Server>>;
+    async fn get_server(&self, id: Uuid) -> anyhow::Result<Server>;
+    async fn find_server_by_name(&self, name: &str) -> anyhow::Result<Option<Server>>;
+    async fn update_server(&self, cmd: UpdateServerCommand) -> anyhow::Result<Server>;
     async fn attach_disk(&self, cmd: AttachDiskCommand) -> anyhow::Result<Server>;
+    /// Fails with a conflict while volumes are attached to the server.
+    async fn delete_server(&self, id: Uuid, dry_run: bool) -> anyhow::Result<()>;
+}
+
+/// INBOUND PORT: Declarative desired-state reconciliation.
+#[async_trait]
+pub trait ApplyManifests: Send + Sync {
+    /// Converges the inventory to `manifest`; with `dry_run` only the plan is returned.
+    async fn apply(&self, manifest: Manifest, dry_run: bool) -> anyhow::Result<ApplyReport>;
+}
+
+/// INBOUND PORT: Rules deciding which events are sent where.
+#[async_trait]
+pub trait ManageNotifications: Send + Sync {
+    async fn create_notification_rule(&self, cmd: CreateNotificationRuleCommand) -> anyhow::Result<NotificationRule>;
+    async fn list_notification_rules(&self) -> anyhow::Result<Vec<NotificationRule>>;
+    async fn delete_notification_rule(&self, id: Uuid) -> anyhow::Result<()>;
+}
+
+/// OUTBOUND PORT: Notifier
+///
+/// --- Good to know ---
+/// One adapter per transport (SMTP, Slack webhook, ...). The application only says
+/// "send this to that destination"; how is the adapter's business.
+///
+/// Comparison:
+/// - Go: Like a `Sender` interface with `SMTPSender` and `SlackSender` implementations.
+/// - Python: Like Apprise's per-service notification plugins.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// `destination` is channel-specific: an e-mail address, a webhook URL, ...
+    async fn notify(&self, destination: &str, notification: &Notification) -> anyhow::Result<()>;
+}
+
+/// INBOUND PORT: Delta sync of the server inventory.
+#[async_trait]
+pub trait SyncServers: Send + Sync {
+    /// `since` is a cursor from a previous call or a Unix timestamp in seconds;
+    /// `None` returns everything still in the change-log.
+    async fn changes(&self, since: Option<&str>) -> anyhow::Result<ServerChanges>;
+}
+
+/// INBOUND PORT: Block storage volumes and their attach/detach lifecycle.
+/// Like `ManageServers`, every change can be a `dry_run`.
+#[async_trait]
+pub trait ManageVolumes: Send + Sync {
+    async fn create_volume(&self, cmd: CreateVolumeCommand) -> anyhow::Result<Volume>;
+    async fn list_volumes(&self) -> anyhow::Result<Vec<Volume>>;
+    async fn get_volume(&self, id: Uuid) -> anyhow::Result<Volume>;
+    /// Fails with a conflict while the volume is attached.
+    async fn delete_volume(&self, id: Uuid, dry_run: bool) -> anyhow::Result<()>;
+    async fn attach_volume(&self, id: Uuid, server_id: Uuid, dry_run: bool) -> anyhow::Result<Volume>;
+    async fn detach_volume(&self, id: Uuid, dry_run: bool) -> anyhow::Result<Volume>;
+}
+
+/// Health of one infrastructure component (storage, message bus, ...).
+#[derive(Debug, Clone)]
+pub struct ComponentHealth {
+    pub component: String,
+    pub healthy: bool,
+    pub details: String,
+}
+
+/// HEALTH PORT
+///
+/// --- Good to know ---
+/// Infrastructure adapters (like the storage circuit breaker) implement this so the
+/// web layer can report their state on `/healthz` without depending on them directly.
+/// SOLID: Interface Segregation - the web adapter only learns "healthy or not".
+pub trait ReportHealth: Send + Sync {
+    fn check(&self) -> ComponentHealth;
+}
+
+/// INBOUND PORT: A server's serial console output.
+#[async_trait]
+pub trait ReadConsole: Send + Sync {
+    /// The last `lines` lines, oldest first.
+    async fn tail(&self, server_id: Uuid, lines: usize) -> anyhow::Result<Vec<String>>;
+    /// Like `tail`, plus every line written afterwards (without gaps or duplicates).
+    async fn follow(&self, server_id: Uuid, lines: usize) -> anyhow::Result<ConsoleFollow>;
+}
+
+/// Backlog plus a live feed, as returned by `follow`.
+pub type ConsoleFollow = (Vec<String>, broadcast::Receiver<String>);
+
+/// OUTBOUND PORT: Where servers actually run.
+///
+/// --- Good to know ---
+/// The service only says "boot this"; whether that's libvirt, Firecracker or a
+/// simulation is the adapter's business. Booting runs in the background, so `boot`
+/// returns as soon as the work is handed off.
+#[async_trait]
+pub trait Hypervisor: Send + Sync {
+    async fn boot(&self, server: &Server) -> anyhow::Result<()>;
+}
+
+/// OUTBOUND PORT: Per-server console log storage.
+#[async_trait]
+pub trait ConsoleLog: Send + Sync {
+    async fn append(&self, server_id: Uuid, line: &str) -> anyhow::Result<()>;
+    async fn tail(&self, server_id: Uuid, lines: usize) -> anyhow::Result<Vec<String>>;
+    /// `tail` and a subscription taken atomically, so no line is missed or repeated.
+    async fn follow(&self, server_id: Uuid, lines: usize) -> anyhow::Result<ConsoleFollow>;
+}
+
+/// OUTBOUND PORT: Password hashing
+///
+/// --- Good to know ---
+/// Hashing is a technical detail (argon2, bcrypt, ...), so the application only sees
+/// this trait. Swapping algorithms later touches a single adapter.
+pub trait HashPasswords: Send + Sync {
+    /// Returns a self-describing hash string (algorithm, parameters, salt and hash).
+    fn hash(&self, password: &str) -> anyhow::Result<String>;
+    /// Checks a password against a hash produced by `hash`.
+    fn verify(&self, password: &str, hash: &str) -> anyhow::Result<bool>;
+}
+
+/// INBOUND PORT: User administration use cases.
+#[async_trait]
+pub trait ManageUsers: Send + Sync {
+    async fn create_user(&self, cmd: CreateUserCommand) -> anyhow::Result<User>;
+    async fn list_users(&self) -> anyhow::Result<Vec<User>>;
+    async fn set_user_disabled(&self, id: Uuid, disabled: bool) -> anyhow::Result<User>;
+    /// Returns the user when the credentials match an enabled account.
+    async fn authenticate(&self, username: &str, password: &str) -> anyhow::Result<Option<User>>;
+}
+
+/// INBOUND PORT: API keys for scripts and automation.
+#[async_trait]
+pub trait ManageApiKeys: Send + Sync {
+    /// The returned secret is shown exactly once; only its hash is stored.
+    async fn create_api_key(&self, cmd: CreateApiKeyCommand) -> anyhow::Result<IssuedApiKey>;
+    async fn list_api_keys(&self) -> anyhow::Result<Vec<ApiKey>>;
+    async fn revoke_api_key(&self, id: Uuid) -> anyhow::Result<ApiKey>;
+    /// Returns the key when the presented secret matches an active key.
+    async fn authenticate_api_key(&self, presented: &str) -> anyhow::Result<Option<ApiKey>>;
+}
+
+/// An authenticated caller: who they are and what they may do.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// Stable identifier of the caller (the token's `sub`, or the API key's name).
+    pub subject: String,
+    pub scopes: BTreeSet<Scope>,
+}
+
+impl Principal {
+    /// A caller whose permissions come from a role (users and SSO tokens).
+    pub fn with_role(subject: String, role: Role) -> Self {
+        Self { subject, scopes: role.scopes() }
+    }
+}
+
+/// OUTBOUND PORT: Access-token verification
+///
+/// --- Good to know ---
+/// With single sign-on, an external identity provider (Keycloak, Auth0, ...) issues the
+/// tokens. The API only checks them, so verification is an adapter behind this trait.
+///
+/// Comparison:
+/// - Go: Like an `oidc.IDTokenVerifier` from `coreos/go-oidc`.
+/// - Python: Like `authlib`'s `JsonWebToken.decode` with a JWKS.
+#[async_trait]
+pub trait VerifyTokens: Send + Sync {
+    /// Validates a bearer token and maps its claims to a `Principal`.
+    async fn verify(&self, token: &str) -> anyhow::Result<Principal>;
+}
+
+/// INBOUND PORT: Reading the activity feed.
+#[async_trait]
+pub trait ViewActivity: Send + Sync {
+    /// Newest first. `cursor` is the opaque `next_cursor` of the previous page.
+    async fn activity(&self, cursor: Option<&str>, limit: Option<usize>) -> anyhow::Result<ActivityPage>;
 }
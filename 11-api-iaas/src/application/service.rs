@@ -1,9 +1,13 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use async_trait::async_trait;
+use tokio::sync::Mutex;
 use uuid::Uuid;
-use crate::domain::{Server, ServerRepository, Disk};
-use super::ports::ManageServers;
-use super::dto::{CreateServerCommand, AttachDiskCommand};
+use crate::domain::{ActivityKind, ActivityLog, Resources, Server, ServerRepository, Disk, VolumeRepository, Zone};
+use super::activity_service::record;
+use super::ports::{Hypervisor, ManageServers};
+use super::dto::{CreateServerCommand, AttachDiskCommand, UpdateServerCommand};
+use super::errors::ServiceError;
 
 /// HEXAGONAL ARCHITECTURE: APPLICATION SERVICE
 /// 
@@ -19,13 +23,116 @@ pub struct ServerService {
     /// Dependency Injection: We depend on the Interface (Trait), not a concrete class.
     /// SOLID: This is Dependency Inversion (D) in action.
     repo: Arc<dyn ServerRepository>,
+    /// Optional audit trail: when set, every change is also recorded as an activity event.
+    activity: Option<Arc<dyn ActivityLog>>,
+    /// Optional hypervisor: when set, new servers are booted right after they are stored.
+    hypervisor: Option<Arc<dyn Hypervisor>>,
+    /// Optional volume storage: when set, servers with attached volumes can't be deleted.
+    volumes: Option<Arc<dyn VolumeRepository>>,
+    /// Availability zones and their capacity. Empty means unlimited, unzoned capacity.
+    zones: Vec<Zone>,
+    /// Placement and name checks are check-then-save: two creates must not both claim
+    /// the last free cores, nor two creates or renames the same name. Shared with
+    /// volume attach/detach, so a volume can't be attached to a server being deleted.
+    placing: Arc<Mutex<()>>,
 }
 
 impl ServerService {
     /// Factory for creating the service. We "inject" the repository here.
     pub fn new(repo: Arc<dyn ServerRepository>) -> Self {
-        Self { repo }
+        Self { repo, activity: None, hypervisor: None, volumes: None, zones: Vec::new(), placing: Arc::default() }
     }
+
+    /// Builder-style: also record changes in an activity log.
+    pub fn with_activity_log(mut self, log: Arc<dyn ActivityLog>) -> Self {
+        self.activity = Some(log);
+        self
+    }
+
+    /// Builder-style: hand new servers to a hypervisor.
+    pub fn with_hypervisor(mut self, hypervisor: Arc<dyn Hypervisor>) -> Self {
+        self.hypervisor = Some(hypervisor);
+        self
+    }
+
+    /// Builder-style: protect attached volumes from losing their server.
+    pub fn with_volumes(mut self, volumes: Arc<dyn VolumeRepository>) -> Self {
+        self.volumes = Some(volumes);
+        self
+    }
+
+    /// The lock server changes hold; hand it to `VolumeService::with_server_lock`.
+    pub fn placement_lock(&self) -> Arc<Mutex<()>> {
+        Arc::clone(&self.placing)
+    }
+
+    /// Builder-style: place servers in zones of limited capacity.
+    pub fn with_zones(mut self, zones: Vec<Zone>) -> Self {
+        self.zones = zones;
+        self
+    }
+
+    /// The zone a server counts against. Servers from before zones were configured
+    /// count against the first zone.
+    fn zone_of(&self, server: &Server) -> Option<&Zone> {
+        match &server.zone {
+            Some(name) => self.zones.iter().find(|z| &z.name == name),
+            None => self.zones.first(),
+        }
+    }
+
+    /// What the servers in `zone` already use.
+    async fn usage(&self, zone: &Zone) -> anyhow::Result<Resources> {
+        Ok(self
+            .repo
+            .list_all()
+            .await?
+            .iter()
+            .filter(|s| self.zone_of(s).is_some_and(|z| z.name == zone.name))
+            .fold(Resources::default(), |used, s| used + s.resources()))
+    }
+
+    /// Business Rule: a server goes to the requested zone, or else to the first zone
+    /// (in configured order) with room for it. Returns `None` when zones aren't configured.
+    async fn place(&self, requested: Option<&str>, request: Resources) -> anyhow::Result<Option<String>> {
+        let candidates: Vec<&Zone> = match requested {
+            Some(name) => match self.zones.iter().find(|z| z.name == name) {
+                Some(zone) => vec![zone],
+                None => return Err(ServiceError::Invalid(format!("unknown zone '{}'", name)).into()),
+            },
+            None if self.zones.is_empty() => return Ok(None),
+            None => self.zones.iter().collect(),
+        };
+        let mut shortages = Vec::new();
+        for zone in candidates {
+            match zone.check(self.usage(zone).await?, request) {
+                Ok(()) => return Ok(Some(zone.name.clone())),
+                Err(shortage) => shortages.push(shortage),
+            }
+        }
+        Err(ServiceError::InsufficientCapacity { shortages }.into())
+    }
+
+    /// Business Rule: server names are unique; a taken name is reported with its owner's ID.
+    /// Call with `placing` held, or the name may be taken before the save.
+    async fn check_name_is_free(&self, name: &str) -> anyhow::Result<()> {
+        match self.repo.find_by_name(name).await? {
+            Some(existing) => {
+                let name = name.to_string();
+                Err(ServiceError::NameConflict { resource: "Server", name, existing_id: existing.id }.into())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Names are compared as stored, so surrounding whitespace is trimmed on the way in.
+fn normalize_name(name: &str) -> Result<String, ServiceError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ServiceError::Invalid("name must not be empty".to_string()));
+    }
+    Ok(name.to_string())
 }
 
 #[async_trait]
@@ -33,11 +140,41 @@ impl ServerService {
 impl ManageServers for ServerService {
     /// Use Case: Create Server. 
     /// Orchestrates creating the entity and persists it through the repository port.
+    /// Business Rule: server names are unique, so a duplicate is rejected with the existing ID.
     async fn create_server(&self, cmd: CreateServerCommand) -> anyhow::Result<Server> {
-        let server = Server::new(cmd.name, cmd.cpu, cmd.ram, cmd.storage);
+        let name = normalize_name(&cmd.name)?;
+        validate_tags(&cmd.tags)?;
+        // Held until the server is saved, so its name, address and share count for the next create.
+        let _placing = self.placing.lock().await;
+        self.check_name_is_free(&name).await?;
+
+        let mut server = Server::new(name, cmd.cpu, cmd.ram, cmd.storage);
+        server.ssh_keys = cmd.ssh_keys;
+        server.user_data = cmd.user_data;
+        server.description = cmd.description;
+        server.tags = cmd.tags;
+        server.zone = self.place(cmd.zone.as_deref(), server.resources()).await?;
+        let taken = self.repo.list_all().await?.iter().map(Server::private_ip).collect();
+        server.allocate_private_ip(&taken).map_err(anyhow::Error::msg)?;
+        if cmd.dry_run {
+            return Ok(server);
+        }
         // We '.await' the port call because persistence might involve I/O.
         self.repo.save(&server).await?;
         println!("Server {} created.", server.id);
+
+        let activity = self.activity.as_ref();
+        record(activity, server.id, ActivityKind::ServerCreated { name: server.name.clone() }).await;
+        let status = ActivityKind::StatusChanged { from: None, to: server.status.clone() };
+        record(activity, server.id, status).await;
+
+        // The server is stored either way; a failed hand-off is reported, not returned.
+        if let Some(hypervisor) = &self.hypervisor {
+            if let Err(e) = hypervisor.boot(&server).await {
+                eprintln!("Failed to boot server {}: {:#}", server.id, e);
+                record(activity, server.id, ActivityKind::ProvisioningFailed { reason: format!("{:#}", e) }).await;
+            }
+        }
         Ok(server)
     }
 
@@ -47,21 +184,147 @@ impl ManageServers for ServerService {
         self.repo.list_all().await
     }
 
+    /// Use Case: Get Server.
+    /// Turns the repository's `Option` into a business error the adapters understand.
+    async fn get_server(&self, id: Uuid) -> anyhow::Result<Server> {
+        self.repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound { resource: "Server", id }.into())
+    }
+
+    /// Use Case: Find Server by Name.
+    /// Names are unique, so this is a stable, human-friendly alternative to the UUID.
+    async fn find_server_by_name(&self, name: &str) -> anyhow::Result<Option<Server>> {
+        self.repo.find_by_name(name).await
+    }
+
+    /// Use Case: Update Server.
+    /// Applies only the fields present in the command; renames keep names unique.
+    async fn update_server(&self, cmd: UpdateServerCommand) -> anyhow::Result<Server> {
+        // Held until the server is saved, so a concurrent create or rename can't take the name.
+        let _placing = self.placing.lock().await;
+        let mut server = self
+            .repo
+            .find_by_id(cmd.server_id)
+            .await?
+            .ok_or(ServiceError::NotFound { resource: "Server", id: cmd.server_id })?;
+        if cmd.precondition.as_ref().is_some_and(|holds| !holds(&server)) {
+            return Err(ServiceError::PreconditionFailed { resource: "Server", id: server.id }.into());
+        }
+        let mut changed = Vec::new();
+
+        if let Some(name) = cmd.name {
+            let name = normalize_name(&name)?;
+            if name != server.name {
+                self.check_name_is_free(&name).await?;
+                server.name = name;
+                changed.push("name".to_string());
+            }
+        }
+
+        if let Some(description) = cmd.description {
+            if description != server.description {
+                server.description = description;
+                changed.push("description".to_string());
+            }
+        }
+
+        let mut tags = if cmd.clear_tags { BTreeMap::new() } else { server.tags.clone() };
+        for (key, value) in cmd.tags {
+            match value {
+                Some(value) => tags.insert(key, value),
+                None => tags.remove(&key),
+            };
+        }
+        validate_tags(&tags)?;
+        if tags != server.tags {
+            server.tags = tags;
+            changed.push("tags".to_string());
+        }
+
+        // An empty patch is valid and simply returns the server unchanged.
+        if !changed.is_empty() && !cmd.dry_run {
+            self.repo.save(&server).await?;
+            record(self.activity.as_ref(), server.id, ActivityKind::ServerUpdated { changed }).await;
+        }
+        Ok(server)
+    }
+
     /// Use Case: Attach Disk.
     /// 1. Finds the server. 2. Modifies it. 3. Persists it.
     async fn attach_disk(&self, cmd: AttachDiskCommand) -> anyhow::Result<Server> {
+        // Held from the read to the save, so a concurrent attach, update or delete can't be
+        // overwritten by (or undone with) a stale copy, and zone usage matches what is saved.
+        let _placing = self.placing.lock().await;
         let mut server = self.repo.find_by_id(cmd.server_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Server not found"))?;
+            .ok_or(ServiceError::NotFound { resource: "Server", id: cmd.server_id })?;
 
-        let disk = Disk {
-            id: Uuid::new_v4(),
-            size_gb: cmd.size_gb,
-        };
-        server.additional_disks.push(disk);
+        // Business rules (IOPS range, flavor compatibility) live in the domain.
+        let disk = Disk::new(cmd.size_gb, cmd.disk_type, cmd.iops).map_err(ServiceError::Invalid)?;
+        let event = ActivityKind::DiskAttached { disk_id: disk.id, size_gb: disk.size_gb };
+        server.attach_disk(disk).map_err(ServiceError::Invalid)?;
+
+        // The disk comes out of the zone the server already lives in.
+        if let Some(zone) = self.zone_of(&server) {
+            let request = Resources { disk_gb: cmd.size_gb, ..Default::default() };
+            if let Err(shortage) = zone.check(self.usage(zone).await?, request) {
+                return Err(ServiceError::InsufficientCapacity { shortages: vec![shortage] }.into());
+            }
+        }
+        if cmd.dry_run {
+            return Ok(server);
+        }
 
         // PERSISTENCE: We must call save() again to commit our changes.
         self.repo.save(&server).await?;
-        
+        record(self.activity.as_ref(), server.id, event).await;
+
         Ok(server)
     }
+
+    /// Use Case: Delete Server.
+    /// Business Rule: volumes must be detached first, so no volume points at a missing server.
+    async fn delete_server(&self, id: Uuid, dry_run: bool) -> anyhow::Result<()> {
+        // Held until the server is gone, so no volume gets attached after the check below.
+        let _placing = self.placing.lock().await;
+        if self.repo.find_by_id(id).await?.is_none() {
+            return Err(ServiceError::NotFound { resource: "Server", id }.into());
+        }
+        if let Some(volumes) = &self.volumes {
+            if let Some(volume) = volumes.list_all().await?.into_iter().find(|v| v.attached_to == Some(id)) {
+                let reason = format!("Server {} still has volume {} attached; detach it first", id, volume.id);
+                return Err(ServiceError::Conflict { reason, conflicting_id: volume.id }.into());
+            }
+        }
+        if dry_run {
+            return Ok(());
+        }
+
+        self.repo.delete(id).await?;
+        println!("Server {} deleted.", id);
+        record(self.activity.as_ref(), id, ActivityKind::ServerDeleted).await;
+        Ok(())
+    }
+}
+
+/// Tags are labels, not a document store: keep them few and short.
+pub const MAX_TAGS: usize = 50;
+pub const MAX_TAG_LEN: usize = 128;
+
+/// Business Rule: tag keys are non-empty, and keys and values stay within `MAX_TAG_LEN`.
+pub(super) fn validate_tags(tags: &BTreeMap<String, String>) -> Result<(), ServiceError> {
+    if tags.len() > MAX_TAGS {
+        return Err(ServiceError::Invalid(format!("a server can have at most {} tags", MAX_TAGS)));
+    }
+    for (key, value) in tags {
+        if key.trim().is_empty() {
+            return Err(ServiceError::Invalid("tag keys must not be empty".to_string()));
+        }
+        if key.chars().count() > MAX_TAG_LEN || value.chars().count() > MAX_TAG_LEN {
+            let reason = format!("tag '{}' is longer than {} characters", key, MAX_TAG_LEN);
+            return Err(ServiceError::Invalid(reason));
+        }
+    }
+    Ok(())
 }
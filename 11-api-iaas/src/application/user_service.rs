@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::domain::{ActivityKind, ActivityLog, User, UserRepository};
+use super::activity_service::record;
+use super::dto::CreateUserCommand;
+use super::errors::ServiceError;
+use super::ports::{HashPasswords, ManageUsers};
+
+/// Passwords shorter than this are rejected (OWASP ASVS recommends at least 12).
+pub const MIN_PASSWORD_LEN: usize = 12;
+
+/// Failed logins a username may collect before it is locked for `LOGIN_LOCKOUT`.
+pub const MAX_FAILED_LOGINS: u32 = 5;
+
+/// How long a locked username is refused, counted from its last failed login.
+pub const LOGIN_LOCKOUT: Duration = Duration::from_secs(60);
+
+/// Usernames with failed logins we keep track of; beyond this, expired ones are dropped.
+const TRACKED_USERNAMES: usize = 1024;
+
+/// Failed logins of one username since its last success.
+struct FailedLogins {
+    count: u32,
+    last: Instant,
+}
+
+/// APPLICATION SERVICE: Users
+/// Same shape as `ServerService`: depends only on ports, wired in `main.rs`.
+pub struct UserService {
+    repo: Arc<dyn UserRepository>,
+    hasher: Arc<dyn HashPasswords>,
+    activity: Option<Arc<dyn ActivityLog>>,
+    /// Keyed by username, known or not, so a lockout doesn't reveal which ones exist.
+    failed_logins: Mutex<HashMap<String, FailedLogins>>,
+    /// Checked when the username is unknown, so that costs as much as a wrong password.
+    dummy_hash: OnceLock<String>,
+}
+
+impl UserService {
+    pub fn new(repo: Arc<dyn UserRepository>, hasher: Arc<dyn HashPasswords>) -> Self {
+        Self {
+            repo,
+            hasher,
+            activity: None,
+            failed_logins: Mutex::new(HashMap::new()),
+            dummy_hash: OnceLock::new(),
+        }
+    }
+
+    /// Builder-style: also record account changes in an activity log.
+    pub fn with_activity_log(mut self, log: Arc<dyn ActivityLog>) -> Self {
+        self.activity = Some(log);
+        self
+    }
+
+    /// Fails with `TooManyAttempts` while `username` is locked out.
+    fn check_lockout(&self, username: &str) -> Result<(), ServiceError> {
+        let failed_logins = self.failed_logins.lock().unwrap();
+        match failed_logins.get(username) {
+            Some(failed) if failed.count >= MAX_FAILED_LOGINS && failed.last.elapsed() < LOGIN_LOCKOUT => {
+                Err(ServiceError::TooManyAttempts { retry_after: LOGIN_LOCKOUT - failed.last.elapsed() })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn record_failed_login(&self, username: &str) {
+        let mut failed_logins = self.failed_logins.lock().unwrap();
+        if failed_logins.len() >= TRACKED_USERNAMES {
+            failed_logins.retain(|_, failed| failed.last.elapsed() < LOGIN_LOCKOUT);
+        }
+        let failed = failed_logins
+            .entry(username.to_string())
+            .or_insert(FailedLogins { count: 0, last: Instant::now() });
+        // A lockout that ran out starts a fresh count.
+        if failed.last.elapsed() >= LOGIN_LOCKOUT {
+            failed.count = 0;
+        }
+        failed.count += 1;
+        failed.last = Instant::now();
+    }
+
+    fn dummy_hash(&self) -> anyhow::Result<&str> {
+        if let Some(hash) = self.dummy_hash.get() {
+            return Ok(hash);
+        }
+        let hash = self.hasher.hash("not the password of anyone")?;
+        Ok(self.dummy_hash.get_or_init(|| hash))
+    }
+}
+
+#[async_trait]
+impl ManageUsers for UserService {
+    /// Use Case: Create User.
+    /// Business Rules: usernames are unique and passwords have a minimum length.
+    async fn create_user(&self, cmd: CreateUserCommand) -> anyhow::Result<User> {
+        let username = cmd.username.trim().to_string();
+        if username.is_empty() {
+            return Err(ServiceError::Invalid("username must not be empty".to_string()).into());
+        }
+        if cmd.password.chars().count() < MIN_PASSWORD_LEN {
+            let reason = format!("password must be at least {} characters", MIN_PASSWORD_LEN);
+            return Err(ServiceError::Invalid(reason).into());
+        }
+        if let Some(existing) = self.repo.find_by_username(&username).await? {
+            return Err(ServiceError::NameConflict {
+                resource: "User",
+                name: username,
+                existing_id: existing.id,
+            }
+            .into());
+        }
+
+        let user = User::new(username, self.hasher.hash(&cmd.password)?, cmd.role);
+        self.repo.save(&user).await?;
+        println!("User {} ({:?}) created.", user.username, user.role);
+        let event = ActivityKind::UserCreated { username: user.username.clone() };
+        record(self.activity.as_ref(), user.id, event).await;
+        Ok(user)
+    }
+
+    /// Use Case: List Users.
+    async fn list_users(&self) -> anyhow::Result<Vec<User>> {
+        self.repo.list_all().await
+    }
+
+    /// Use Case: Disable / Enable User.
+    /// Accounts are never deleted, so past actions stay attributable.
+    async fn set_user_disabled(&self, id: Uuid, disabled: bool) -> anyhow::Result<User> {
+        let mut user = self
+            .repo
+            .find_by_id(id)
+            .await?
+            .ok_or(ServiceError::NotFound { resource: "User", id })?;
+        user.disabled = disabled;
+        self.repo.save(&user).await?;
+        let event = if disabled { ActivityKind::UserDisabled } else { ActivityKind::UserEnabled };
+        record(self.activity.as_ref(), user.id, event).await;
+        Ok(user)
+    }
+
+    /// Use Case: Authenticate.
+    /// Unknown users, wrong passwords and disabled accounts all look the same to the caller,
+    /// in both answer and timing. After `MAX_FAILED_LOGINS` failures a username is locked
+    /// for `LOGIN_LOCKOUT`, which caps online password guessing.
+    async fn authenticate(&self, username: &str, password: &str) -> anyhow::Result<Option<User>> {
+        let username = username.trim();
+        self.check_lockout(username)?;
+        let user = self.repo.find_by_username(username).await?;
+        let verified = match &user {
+            Some(user) => self.hasher.verify(password, &user.password_hash)?,
+            None => {
+                self.hasher.verify(password, self.dummy_hash()?)?;
+                false
+            }
+        };
+        match user.filter(|user| verified && !user.disabled) {
+            Some(user) => {
+                self.failed_logins.lock().unwrap().remove(username);
+                Ok(Some(user))
+            }
+            None => {
+                self.record_failed_login(username);
+                Ok(None)
+            }
+        }
+    }
+}
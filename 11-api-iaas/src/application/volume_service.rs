@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use crate::domain::{ActivityKind, ActivityLog, ServerRepository, Volume, VolumeRepository};
+use super::activity_service::record;
+use super::dto::CreateVolumeCommand;
+use super::errors::ServiceError;
+use super::ports::ManageVolumes;
+
+/// APPLICATION SERVICE: Volumes
+///
+/// --- Good to know ---
+/// Attaching is a read-check-write: "is it free? then mark it attached". Two requests
+/// doing that at the same moment could both see "free", so state changes are
+/// serialized with an async mutex (one that may be held across `.await`).
+/// Attach and detach also hold the server service's lock, so a server can't be
+/// deleted between "it exists" and "the volume points at it".
+///
+/// Comparison:
+/// - Go: Like guarding the handler with a `sync.Mutex` (or `SELECT ... FOR UPDATE` in SQL).
+/// - Python: Like an `asyncio.Lock` around the update.
+pub struct VolumeService {
+    volumes: Arc<dyn VolumeRepository>,
+    servers: Arc<dyn ServerRepository>,
+    activity: Option<Arc<dyn ActivityLog>>,
+    changes: Mutex<()>,
+    /// Shared with `ServerService` (see `with_server_lock`); always taken after `changes`.
+    server_lock: Arc<Mutex<()>>,
+}
+
+impl VolumeService {
+    pub fn new(volumes: Arc<dyn VolumeRepository>, servers: Arc<dyn ServerRepository>) -> Self {
+        Self { volumes, servers, activity: None, changes: Mutex::new(()), server_lock: Arc::default() }
+    }
+
+    /// Builder-style: serialize attach/detach with server deletes (`ServerService::placement_lock`).
+    pub fn with_server_lock(mut self, lock: Arc<Mutex<()>>) -> Self {
+        self.server_lock = lock;
+        self
+    }
+
+    /// Builder-style: also record volume changes in an activity log.
+    pub fn with_activity_log(mut self, log: Arc<dyn ActivityLog>) -> Self {
+        self.activity = Some(log);
+        self
+    }
+
+    async fn find(&self, id: Uuid) -> anyhow::Result<Volume> {
+        Ok(self
+            .volumes
+            .find_by_id(id)
+            .await?
+            .ok_or(ServiceError::NotFound { resource: "Volume", id })?)
+    }
+}
+
+#[async_trait]
+impl ManageVolumes for VolumeService {
+    /// Use Case: Create Volume.
+    /// Business Rule: volume names are unique, like server names.
+    async fn create_volume(&self, cmd: CreateVolumeCommand) -> anyhow::Result<Volume> {
+        let name = cmd.name.trim().to_string();
+        if name.is_empty() {
+            return Err(ServiceError::Invalid("volume name must not be empty".to_string()).into());
+        }
+        let _guard = self.changes.lock().await;
+        if let Some(existing) = self.volumes.list_all().await?.into_iter().find(|v| v.name == name) {
+            return Err(ServiceError::NameConflict { resource: "Volume", name, existing_id: existing.id }.into());
+        }
+
+        let volume = Volume::new(name, cmd.size_gb, cmd.disk_type, cmd.iops).map_err(ServiceError::Invalid)?;
+        if cmd.dry_run {
+            return Ok(volume);
+        }
+        self.volumes.save(&volume).await?;
+        record(self.activity.as_ref(), volume.id, ActivityKind::VolumeCreated { name: volume.name.clone() }).await;
+        Ok(volume)
+    }
+
+    /// Use Case: List Volumes.
+    async fn list_volumes(&self) -> anyhow::Result<Vec<Volume>> {
+        self.volumes.list_all().await
+    }
+
+    /// Use Case: Get Volume.
+    async fn get_volume(&self, id: Uuid) -> anyhow::Result<Volume> {
+        self.find(id).await
+    }
+
+    /// Use Case: Delete Volume.
+    /// Business Rule: an attached volume is still in use and must be detached first.
+    async fn delete_volume(&self, id: Uuid, dry_run: bool) -> anyhow::Result<()> {
+        let _guard = self.changes.lock().await;
+        let volume = self.find(id).await?;
+        if let Some(server_id) = volume.attached_to {
+            let reason = format!("Volume {} is attached to server {}; detach it first", id, server_id);
+            return Err(ServiceError::Conflict { reason, conflicting_id: server_id }.into());
+        }
+        if dry_run {
+            return Ok(());
+        }
+        self.volumes.delete(id).await?;
+        record(self.activity.as_ref(), id, ActivityKind::VolumeDeleted).await;
+        Ok(())
+    }
+
+    /// Use Case: Attach Volume.
+    /// Business Rules: a volume is attached to at most one server, and the server's
+    /// flavor must support the volume's disk type. Re-attaching to the same server is a no-op.
+    async fn attach_volume(&self, id: Uuid, server_id: Uuid, dry_run: bool) -> anyhow::Result<Volume> {
+        let _guard = self.changes.lock().await;
+        let _servers = self.server_lock.lock().await;
+        let mut volume = self.find(id).await?;
+        match volume.attached_to {
+            Some(current) if current == server_id => return Ok(volume),
+            Some(current) => {
+                let reason = format!("Volume {} is already attached to server {}", id, current);
+                return Err(ServiceError::Conflict { reason, conflicting_id: current }.into());
+            }
+            None => {}
+        }
+
+        let server = self
+            .servers
+            .find_by_id(server_id)
+            .await?
+            .ok_or(ServiceError::NotFound { resource: "Server", id: server_id })?;
+        server.supports_disk_type(volume.disk_type).map_err(ServiceError::Invalid)?;
+
+        volume.attached_to = Some(server_id);
+        if dry_run {
+            return Ok(volume);
+        }
+        self.volumes.save(&volume).await?;
+        record(self.activity.as_ref(), volume.id, ActivityKind::VolumeAttached { server_id }).await;
+        Ok(volume)
+    }
+
+    /// Use Case: Detach Volume. Detaching a detached volume is a no-op.
+    async fn detach_volume(&self, id: Uuid, dry_run: bool) -> anyhow::Result<Volume> {
+        let _guard = self.changes.lock().await;
+        let _servers = self.server_lock.lock().await;
+        let mut volume = self.find(id).await?;
+        let Some(server_id) = volume.attached_to.take() else {
+            return Ok(volume);
+        };
+        if dry_run {
+            return Ok(volume);
+        }
+        self.volumes.save(&volume).await?;
+        record(self.activity.as_ref(), volume.id, ActivityKind::VolumeDetached { server_id }).await;
+        Ok(volume)
+    }
+}
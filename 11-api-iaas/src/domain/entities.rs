@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::net::Ipv4Addr;
 use uuid::Uuid;
 
 /// DOMAIN ENTITY: Server
@@ -21,6 +23,27 @@ pub struct Server {
     /// Vector of attached disks. In Rust, Vec<T> is a growable array,
     /// similar to a slice []T in Go or a list [] in Python.
     pub additional_disks: Vec<Disk>,
+    /// Public SSH keys injected into the guest on first boot.
+    /// `#[serde(default)]` lets documents saved before this field existed still load.
+    #[serde(default)]
+    pub ssh_keys: Vec<String>,
+    /// Free-form bootstrap script (like cloud-init) handed to the guest.
+    #[serde(default)]
+    pub user_data: Option<String>,
+    /// Human-readable notes about the server.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Key/value labels (`env=prod`). A BTreeMap keeps them sorted, so output is stable.
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    /// Availability zone the server was placed in. `None` when zones aren't configured
+    /// (or weren't when the server was created).
+    #[serde(default)]
+    pub zone: Option<String>,
+    /// Private address allocated at creation. `None` for servers created before
+    /// allocation existed; those keep the address derived from their ID.
+    #[serde(default)]
+    pub private_ipv4: Option<Ipv4Addr>,
 }
 
 /// DOMAIN ENUM: ServerStatus
@@ -36,12 +59,152 @@ pub enum ServerStatus {
     Terminated,
 }
 
+impl ServerStatus {
+    /// Exhaustive `match`: if a new `ServerStatus` is added, the compiler forces us
+    /// to decide how it is presented, instead of silently leaking a `Debug` string.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServerStatus::Provisioning => "Provisioning",
+            ServerStatus::Running => "Running",
+            ServerStatus::Stopped => "Stopped",
+            ServerStatus::Terminated => "Terminated",
+        }
+    }
+}
+
 /// DOMAIN ENTITY: Disk
 /// Represents a block storage volume that can be attached to a server.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Disk {
     pub id: Uuid,
     pub size_gb: u32,
+    /// Disks saved before types existed load as `Standard`.
+    #[serde(default)]
+    pub disk_type: DiskType,
+    /// Provisioned I/O operations per second.
+    #[serde(default)]
+    pub iops: u32,
+    /// Sustained throughput in MB/s.
+    #[serde(default)]
+    pub throughput_mbps: u32,
+}
+
+/// DOMAIN ENUM: DiskType
+/// The storage tier behind a disk. Faster tiers have higher performance ceilings.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum DiskType {
+    #[default]
+    Standard,
+    Ssd,
+    Nvme,
+}
+
+impl DiskType {
+    /// Baseline IOPS for a disk of `size_gb`: per-GB rate, clamped to the tier's range.
+    pub fn baseline_iops(self, size_gb: u32) -> u32 {
+        let per_gb = match self {
+            DiskType::Standard => 1,
+            DiskType::Ssd => 3,
+            DiskType::Nvme => 10,
+        };
+        size_gb.saturating_mul(per_gb).clamp(self.min_iops(), self.max_iops())
+    }
+
+    pub fn min_iops(self) -> u32 {
+        match self {
+            DiskType::Standard => 100,
+            DiskType::Ssd => 3_000,
+            DiskType::Nvme => 10_000,
+        }
+    }
+
+    pub fn max_iops(self) -> u32 {
+        match self {
+            DiskType::Standard => 500,
+            DiskType::Ssd => 16_000,
+            DiskType::Nvme => 64_000,
+        }
+    }
+
+    pub fn throughput_mbps(self) -> u32 {
+        match self {
+            DiskType::Standard => 60,
+            DiskType::Ssd => 250,
+            DiskType::Nvme => 1_000,
+        }
+    }
+
+    /// NVMe is only wired to the larger compute flavors.
+    pub fn min_cpu_cores(self) -> u32 {
+        match self {
+            DiskType::Standard | DiskType::Ssd => 1,
+            DiskType::Nvme => 4,
+        }
+    }
+}
+
+impl DiskType {
+    /// Business Rule: provisioned IOPS must stay within the tier's range.
+    /// `None` picks the baseline for the size.
+    pub fn provision_iops(self, size_gb: u32, requested: Option<u32>) -> Result<u32, String> {
+        let iops = requested.unwrap_or_else(|| self.baseline_iops(size_gb));
+        if !(self.min_iops()..=self.max_iops()).contains(&iops) {
+            return Err(format!(
+                "{:?} disks support {} to {} IOPS, got {}",
+                self,
+                self.min_iops(),
+                self.max_iops(),
+                iops
+            ));
+        }
+        Ok(iops)
+    }
+}
+
+impl Disk {
+    /// Factory method: performance defaults to the tier's baseline for the size.
+    pub fn new(size_gb: u32, disk_type: DiskType, iops: Option<u32>) -> Result<Self, String> {
+        Ok(Self {
+            id: Uuid::new_v4(),
+            size_gb,
+            disk_type,
+            iops: disk_type.provision_iops(size_gb, iops)?,
+            throughput_mbps: disk_type.throughput_mbps(),
+        })
+    }
+}
+
+/// DOMAIN ENTITY: Volume
+///
+/// --- Good to know ---
+/// Real clouds (AWS EBS, OpenStack Cinder) treat block storage as its own resource:
+/// a volume is created on its own, attached to one server at a time, and survives
+/// that server. `attached_to` is the single source of truth for the attachment.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Volume {
+    pub id: Uuid,
+    pub name: String,
+    pub size_gb: u32,
+    pub disk_type: DiskType,
+    pub iops: u32,
+    pub throughput_mbps: u32,
+    /// The server this volume is attached to, if any.
+    pub attached_to: Option<Uuid>,
+}
+
+impl Volume {
+    /// Factory method: new volumes start detached.
+    pub fn new(name: String, size_gb: u32, disk_type: DiskType, iops: Option<u32>) -> Result<Self, String> {
+        Ok(Self {
+            id: Uuid::new_v4(),
+            name,
+            size_gb,
+            disk_type,
+            iops: disk_type.provision_iops(size_gb, iops)?,
+            throughput_mbps: disk_type.throughput_mbps(),
+            attached_to: None,
+        })
+    }
 }
 
 impl Server {
@@ -56,6 +219,456 @@ impl Server {
             storage_gb: storage,
             status: ServerStatus::Provisioning,
             additional_disks: Vec::new(),
+            ssh_keys: Vec::new(),
+            user_data: None,
+            description: None,
+            tags: BTreeMap::new(),
+            zone: None,
+            private_ipv4: None,
+        }
+    }
+
+    /// What the server occupies on its zone's hardware: the boot disk and every attached disk count.
+    pub fn resources(&self) -> Resources {
+        Resources {
+            vcpu: self.cpu_cores,
+            ram_gb: self.ram_gb,
+            disk_gb: self.storage_gb + self.additional_disks.iter().map(|d| d.size_gb).sum::<u32>(),
+        }
+    }
+
+    /// Flavor (instance type) name derived from the sizing, e.g. `c4.r16`.
+    pub fn flavor(&self) -> String {
+        format!("c{}.r{}", self.cpu_cores, self.ram_gb)
+    }
+
+    /// Business Rule: the flavor must support the disk's tier (e.g. NVMe needs 4+ cores).
+    pub fn supports_disk_type(&self, disk_type: DiskType) -> Result<(), String> {
+        if self.cpu_cores < disk_type.min_cpu_cores() {
+            return Err(format!(
+                "{:?} disks need a flavor with at least {} CPU cores, {} has {}",
+                disk_type,
+                disk_type.min_cpu_cores(),
+                self.flavor(),
+                self.cpu_cores
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn attach_disk(&mut self, disk: Disk) -> Result<(), String> {
+        self.supports_disk_type(disk.disk_type)?;
+        self.additional_disks.push(disk);
+        Ok(())
+    }
+
+    /// Private IPv4 address in `10.0.0.0/8`: the allocated one, or else one derived
+    /// from the server ID (deterministic, so it survives restarts).
+    pub fn private_ip(&self) -> Ipv4Addr {
+        if let Some(ip) = self.private_ipv4 {
+            return ip;
+        }
+        let b = self.id.as_bytes();
+        // Avoid the network (.0) and broadcast (.255) host numbers.
+        Ipv4Addr::new(10, b[13], b[14], b[15].clamp(1, 254))
+    }
+
+    /// Business Rule: private addresses are unique. Starts from the derived address
+    /// and takes the next host address not in `taken`.
+    pub fn allocate_private_ip(&mut self, taken: &HashSet<Ipv4Addr>) -> Result<Ipv4Addr, String> {
+        self.private_ipv4 = None;
+        let start = u32::from(self.private_ip());
+        // 10.0.0.0/8 holds 2^24 addresses; walk them all once, wrapping around.
+        for offset in 0..1u32 << 24 {
+            let ip = Ipv4Addr::from(0x0a00_0000 | (start.wrapping_add(offset) & 0x00ff_ffff));
+            let host = ip.octets()[3];
+            if host != 0 && host != 255 && !taken.contains(&ip) {
+                self.private_ipv4 = Some(ip);
+                return Ok(ip);
+            }
+        }
+        Err("no free private IPv4 address left in 10.0.0.0/8".to_string())
+    }
+}
+
+/// VALUE OBJECT: Resources
+/// An amount of compute and storage: a zone's capacity, its usage, or a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Resources {
+    pub vcpu: u32,
+    pub ram_gb: u32,
+    pub disk_gb: u32,
+}
+
+impl std::ops::Add for Resources {
+    type Output = Resources;
+
+    fn add(self, other: Resources) -> Resources {
+        Resources {
+            vcpu: self.vcpu + other.vcpu,
+            ram_gb: self.ram_gb + other.ram_gb,
+            disk_gb: self.disk_gb + other.disk_gb,
+        }
+    }
+}
+
+/// The capacity dimension a zone ran out of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapacityResource {
+    Vcpu,
+    Ram,
+    Disk,
+}
+
+impl CapacityResource {
+    /// Machine-readable reason, e.g. `insufficient_disk`.
+    pub fn reason(self) -> &'static str {
+        match self {
+            CapacityResource::Vcpu => "insufficient_vcpu",
+            CapacityResource::Ram => "insufficient_ram",
+            CapacityResource::Disk => "insufficient_disk",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            CapacityResource::Vcpu => "vCPU",
+            CapacityResource::Ram => "GB RAM",
+            CapacityResource::Disk => "GB disk",
+        }
+    }
+}
+
+/// DOMAIN ENTITY: Zone
+///
+/// --- Good to know ---
+/// An availability zone is a separate pool of hardware, and hardware runs out. Usage
+/// isn't kept as a counter that provisioning decrements: it is the sum of the servers
+/// placed in the zone, so deleting a server frees its share and a crash between
+/// "save" and "decrement" can never leave the numbers wrong.
+///
+/// Comparison:
+/// - Go: Like a scheduler's node-fit check in Kubernetes (`requests` vs. allocatable).
+/// - Python: Like OpenStack Nova's placement service, in a few lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub capacity: Resources,
+}
+
+/// Why a zone can't take a request: `requested` of `resource`, but only `available` left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortage {
+    pub zone: String,
+    pub resource: CapacityResource,
+    pub requested: u32,
+    pub available: u32,
+}
+
+impl std::fmt::Display for Shortage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "zone '{}' has {} {} free, {} requested",
+            self.zone,
+            self.available,
+            self.resource.unit(),
+            self.requested
+        )
+    }
+}
+
+impl Zone {
+    /// Business Rule: a request fits when every dimension stays within capacity
+    /// once `used` is taken into account. Reports the first dimension that doesn't.
+    pub fn check(&self, used: Resources, request: Resources) -> Result<(), Shortage> {
+        let dimensions = [
+            (CapacityResource::Vcpu, self.capacity.vcpu, used.vcpu, request.vcpu),
+            (CapacityResource::Ram, self.capacity.ram_gb, used.ram_gb, request.ram_gb),
+            (CapacityResource::Disk, self.capacity.disk_gb, used.disk_gb, request.disk_gb),
+        ];
+        for (resource, capacity, used, requested) in dimensions {
+            let available = capacity.saturating_sub(used);
+            if requested > available {
+                return Err(Shortage { zone: self.name.clone(), resource, requested, available });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// DOMAIN ENTITY: User
+/// An operator of the platform. Only the password *hash* is ever stored.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct User {
+    pub id: Uuid,
+    pub username: String,
+    /// PHC-formatted hash (e.g. `$argon2id$...`), never the plaintext password.
+    pub password_hash: String,
+    pub role: Role,
+    /// Disabled accounts are kept for auditability but can no longer sign in.
+    pub disabled: bool,
+}
+
+/// DOMAIN ENUM: Role
+/// What a user is allowed to do, from most to least privileged.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Role {
+    Admin,
+    Operator,
+    Viewer,
+}
+
+impl Role {
+    /// True when this role may do everything `required` may (Admin > Operator > Viewer).
+    pub fn includes(self, required: Role) -> bool {
+        self.rank() >= required.rank()
+    }
+
+    /// Parses `"admin"`, `"operator"` or `"viewer"` (case-insensitive).
+    pub fn parse(value: &str) -> Option<Role> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "admin" => Some(Role::Admin),
+            "operator" => Some(Role::Operator),
+            "viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Role::Admin => 2,
+            Role::Operator => 1,
+            Role::Viewer => 0,
         }
     }
+
+    /// The scopes a role grants, so users, SSO tokens and API keys share one check.
+    pub fn scopes(self) -> BTreeSet<Scope> {
+        let scopes: &[Scope] = match self {
+            Role::Admin => &[Scope::Admin],
+            Role::Operator => &[Scope::ServersRead, Scope::ServersWrite, Scope::DisksWrite, Scope::MetadataRead],
+            Role::Viewer => &[Scope::ServersRead],
+        };
+        scopes.iter().copied().collect()
+    }
+}
+
+/// DOMAIN ENUM: Scope
+///
+/// --- Good to know ---
+/// Roles are coarse ("operator"); scopes are fine-grained permissions per area, so an
+/// API key for a monitoring script can be read-only while a CI pipeline may only
+/// create servers. `admin` grants everything.
+///
+/// Comparison:
+/// - Go / Python: Like OAuth2 scopes (`repo:read`) on GitHub or Google tokens.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    #[serde(rename = "servers:read")]
+    ServersRead,
+    #[serde(rename = "servers:write")]
+    ServersWrite,
+    #[serde(rename = "disks:write")]
+    DisksWrite,
+    /// Instance metadata, which carries SSH keys and user-data (often with secrets in it).
+    #[serde(rename = "metadata:read")]
+    MetadataRead,
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Scope {
+    /// True when holding `granted` allows an action that requires `self`.
+    pub fn is_granted_by(self, granted: &BTreeSet<Scope>) -> bool {
+        granted.contains(&self) || granted.contains(&Scope::Admin)
+    }
+
+    /// Parses the wire names: `"servers:read"`, `"servers:write"`, `"disks:write"`,
+    /// `"metadata:read"` or `"admin"`.
+    pub fn parse(value: &str) -> Option<Scope> {
+        match value.trim() {
+            "servers:read" => Some(Scope::ServersRead),
+            "servers:write" => Some(Scope::ServersWrite),
+            "disks:write" => Some(Scope::DisksWrite),
+            "metadata:read" => Some(Scope::MetadataRead),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// DOMAIN ENTITY: ApiKey
+/// A long-lived credential for scripts and automation. Only a hash of the secret is stored.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    pub id: Uuid,
+    /// What the key is for, e.g. `ci-pipeline`.
+    pub name: String,
+    /// Hash of the secret part of the key.
+    pub secret_hash: String,
+    pub scopes: BTreeSet<Scope>,
+    /// Revoked keys are kept for auditability but no longer authenticate.
+    pub revoked: bool,
+}
+
+impl ApiKey {
+    /// Factory method: new keys start active.
+    pub fn new(name: String, secret_hash: String, scopes: BTreeSet<Scope>) -> Self {
+        Self { id: Uuid::new_v4(), name, secret_hash, scopes, revoked: false }
+    }
+}
+
+impl User {
+    /// Factory method: new accounts start enabled.
+    pub fn new(username: String, password_hash: String, role: Role) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            username,
+            password_hash,
+            role,
+            disabled: false,
+        }
+    }
+}
+
+/// DOMAIN ENTITY: ServerChange
+/// One entry in the server change-log that clients use to sync their inventory.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerChange {
+    /// Position in the log; strictly increasing.
+    pub sequence: u64,
+    /// When it happened, in seconds since the Unix epoch.
+    pub occurred_at: u64,
+    pub server_id: Uuid,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// DOMAIN ENTITY: ActivityEvent
+/// One entry in the platform's append-only activity log (audit trail + status changes).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActivityEvent {
+    /// Position in the log. Strictly increasing, so it doubles as a stable cursor.
+    pub sequence: u64,
+    /// When it happened, in seconds since the Unix epoch.
+    pub occurred_at: u64,
+    /// The server or user the event is about.
+    pub resource_id: Uuid,
+    pub kind: ActivityKind,
+}
+
+/// DOMAIN ENUM: ActivityKind
+/// Rust enums can carry data per variant, so each event only holds what it needs.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActivityKind {
+    ServerCreated { name: String },
+    /// Lists the fields that changed, e.g. `["name", "tags"]`.
+    ServerUpdated { changed: Vec<String> },
+    DiskAttached { disk_id: Uuid, size_gb: u32 },
+    /// `from` is `None` when the server has just been created.
+    StatusChanged { from: Option<ServerStatus>, to: ServerStatus },
+    ServerDeleted,
+    VolumeCreated { name: String },
+    VolumeAttached { server_id: Uuid },
+    VolumeDetached { server_id: Uuid },
+    VolumeDeleted,
+    UserCreated { username: String },
+    ApiKeyCreated { name: String },
+    ApiKeyRevoked,
+    UserDisabled,
+    UserEnabled,
+    /// The hypervisor could not boot a freshly created server.
+    ProvisioningFailed { reason: String },
+}
+
+impl ActivityKind {
+    /// Every `event_type`, for validating what clients subscribe to.
+    pub const EVENT_TYPES: &'static [&'static str] = &[
+        "server_created", "server_updated", "disk_attached", "status_changed", "server_deleted",
+        "volume_created", "volume_attached", "volume_detached", "volume_deleted", "user_created",
+        "api_key_created", "api_key_revoked", "user_disabled", "user_enabled", "provisioning_failed",
+    ];
+
+    /// Stable snake_case name, the same as the `type` tag in the stored log.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            ActivityKind::ServerCreated { .. } => "server_created",
+            ActivityKind::ServerUpdated { .. } => "server_updated",
+            ActivityKind::DiskAttached { .. } => "disk_attached",
+            ActivityKind::StatusChanged { .. } => "status_changed",
+            ActivityKind::ServerDeleted => "server_deleted",
+            ActivityKind::VolumeCreated { .. } => "volume_created",
+            ActivityKind::VolumeAttached { .. } => "volume_attached",
+            ActivityKind::VolumeDetached { .. } => "volume_detached",
+            ActivityKind::VolumeDeleted => "volume_deleted",
+            ActivityKind::UserCreated { .. } => "user_created",
+            ActivityKind::ApiKeyCreated { .. } => "api_key_created",
+            ActivityKind::ApiKeyRevoked => "api_key_revoked",
+            ActivityKind::UserDisabled => "user_disabled",
+            ActivityKind::UserEnabled => "user_enabled",
+            ActivityKind::ProvisioningFailed { .. } => "provisioning_failed",
+        }
+    }
+
+    /// One-line, human-readable description for feeds and notifications.
+    pub fn describe(&self) -> String {
+        match self {
+            ActivityKind::ServerCreated { name } => format!("Server '{}' created", name),
+            ActivityKind::ServerUpdated { changed } => format!("Server updated ({})", changed.join(", ")),
+            ActivityKind::DiskAttached { disk_id, size_gb } => format!("Disk {} ({} GB) attached", disk_id, size_gb),
+            ActivityKind::StatusChanged { from: Some(from), to } => {
+                format!("Status changed from {} to {}", from.label(), to.label())
+            }
+            ActivityKind::StatusChanged { from: None, to } => format!("Status set to {}", to.label()),
+            ActivityKind::ServerDeleted => "Server deleted".to_string(),
+            ActivityKind::VolumeCreated { name } => format!("Volume '{}' created", name),
+            ActivityKind::VolumeAttached { server_id } => format!("Volume attached to server {}", server_id),
+            ActivityKind::VolumeDetached { server_id } => format!("Volume detached from server {}", server_id),
+            ActivityKind::VolumeDeleted => "Volume deleted".to_string(),
+            ActivityKind::UserCreated { username } => format!("User '{}' created", username),
+            ActivityKind::ApiKeyCreated { name } => format!("API key '{}' created", name),
+            ActivityKind::ApiKeyRevoked => "API key revoked".to_string(),
+            ActivityKind::UserDisabled => "User disabled".to_string(),
+            ActivityKind::UserEnabled => "User enabled".to_string(),
+            ActivityKind::ProvisioningFailed { reason } => format!("Provisioning failed: {}", reason),
+        }
+    }
+}
+
+/// DOMAIN ENTITY: NotificationRule
+/// "When one of these events happens, tell this channel."
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationRule {
+    pub id: Uuid,
+    pub name: String,
+    /// `ActivityKind::event_type` names, e.g. `server_deleted`.
+    pub events: BTreeSet<String>,
+    pub channel: NotificationChannel,
+}
+
+/// Where a rule's notifications go. Each variant is served by its own `Notifier` adapter.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Email { to: String },
+    Slack { webhook_url: String },
+}
+
+impl NotificationRule {
+    pub fn new(name: String, events: BTreeSet<String>, channel: NotificationChannel) -> Self {
+        Self { id: Uuid::new_v4(), name, events, channel }
+    }
+
+    pub fn matches(&self, kind: &ActivityKind) -> bool {
+        self.events.contains(kind.event_type())
+    }
 }
@@ -1,8 +1,17 @@
 mod entities;
 mod repository;
 
-pub use entities::{Disk, Server, ServerStatus};
-pub use repository::ServerRepository;
+pub use entities::{
+    ActivityEvent, ActivityKind, ApiKey, CapacityResource, ChangeKind, Disk, DiskType, NotificationChannel,
+    NotificationRule, Resources, Role, Scope, Server, ServerChange, Shortage, User, Volume, Zone,
+};
+// Only tests build servers field by field; everything else goes through `Server`'s methods.
+#[cfg(test)]
+pub use entities::ServerStatus;
+pub use repository::{
+    ActivityLog, ApiKeyRepository, ChangeLog, NotificationRuleRepository, ServerRepository, UserRepository,
+    VolumeRepository,
+};
 
 #[cfg(test)]
 mod tests {
@@ -20,4 +29,111 @@ mod tests {
         assert_eq!(server.status, ServerStatus::Provisioning);
         assert!(server.additional_disks.is_empty());
     }
+
+    #[test]
+    fn test_server_flavor_and_private_ip() {
+        let server = Server::new("vm".to_string(), 4, 16, 100);
+        assert_eq!(server.flavor(), "c4.r16");
+
+        let ip = server.private_ip();
+        assert_eq!(ip.octets()[0], 10);
+        assert_eq!(ip, server.private_ip()); // stable for the same server
+    }
+
+    #[test]
+    fn test_allocate_private_ip_skips_taken_addresses() {
+        let mut server = Server::new("vm".to_string(), 1, 1, 10);
+        let derived = server.private_ip();
+
+        let taken = std::collections::HashSet::from([derived]);
+        let ip = server.allocate_private_ip(&taken).unwrap();
+        assert_ne!(ip, derived);
+        assert_eq!(ip.octets()[0], 10);
+        assert!(ip.octets()[3] != 0 && ip.octets()[3] != 255);
+        assert_eq!(server.private_ip(), ip);
+    }
+
+    #[test]
+    fn test_user_new_is_enabled() {
+        let user = User::new("alice".to_string(), "$argon2id$hash".to_string(), Role::Operator);
+        assert_eq!(user.username, "alice");
+        assert_eq!(user.role, Role::Operator);
+        assert!(!user.disabled);
+    }
+
+    #[test]
+    fn test_role_hierarchy_and_parsing() {
+        assert!(Role::Admin.includes(Role::Operator));
+        assert!(Role::Operator.includes(Role::Operator));
+        assert!(!Role::Viewer.includes(Role::Operator));
+        assert_eq!(Role::parse(" Admin "), Some(Role::Admin));
+        assert_eq!(Role::parse("root"), None);
+    }
+
+    #[test]
+    fn test_scopes_from_roles_and_admin_grants_all() {
+        let viewer = Role::Viewer.scopes();
+        assert!(Scope::ServersRead.is_granted_by(&viewer));
+        assert!(!Scope::ServersWrite.is_granted_by(&viewer));
+        assert!(Scope::DisksWrite.is_granted_by(&Role::Operator.scopes()));
+        assert!(!Scope::Admin.is_granted_by(&Role::Operator.scopes()));
+        assert!(Scope::DisksWrite.is_granted_by(&Role::Admin.scopes()));
+        assert_eq!(serde_json::to_string(&Scope::ServersRead).unwrap(), "\"servers:read\"");
+        assert_eq!(Scope::parse(" metadata:read"), Some(Scope::MetadataRead));
+        assert_eq!(Scope::parse("root"), None);
+    }
+
+    #[test]
+    fn test_disk_performance_and_flavor_compatibility() {
+        let ssd = Disk::new(100, DiskType::Ssd, None).unwrap();
+        assert_eq!(ssd.iops, 3_000); // 300 by size, raised to the SSD floor
+        assert_eq!(ssd.throughput_mbps, 250);
+        assert_eq!(Disk::new(10_000, DiskType::Standard, None).unwrap().iops, 500);
+        assert!(Disk::new(100, DiskType::Ssd, Some(20_000)).is_err());
+
+        let mut small = Server::new("small".to_string(), 2, 4, 20);
+        assert!(small.attach_disk(Disk::new(100, DiskType::Nvme, None).unwrap()).is_err());
+        assert!(small.additional_disks.is_empty());
+
+        let mut large = Server::new("large".to_string(), 8, 32, 100);
+        assert!(large.attach_disk(Disk::new(100, DiskType::Nvme, None).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_notification_rules_match_event_types() {
+        let channel = NotificationChannel::Slack { webhook_url: "https://hooks.slack.com/x".to_string() };
+        let rule = NotificationRule::new("ops".to_string(), ["server_deleted".to_string()].into(), channel);
+        assert!(rule.matches(&ActivityKind::ServerDeleted));
+        assert!(!rule.matches(&ActivityKind::VolumeDeleted));
+
+        // The stored tag and `event_type` must never drift apart.
+        let kind = ActivityKind::ProvisioningFailed { reason: "no capacity".to_string() };
+        let stored = serde_json::to_value(&kind).unwrap();
+        assert_eq!(stored["type"], kind.event_type());
+        assert!(ActivityKind::EVENT_TYPES.contains(&kind.event_type()));
+    }
+
+    #[test]
+    fn test_zone_check_reports_first_shortage() {
+        let zone = Zone { name: "zone-a".to_string(), capacity: Resources { vcpu: 8, ram_gb: 32, disk_gb: 500 } };
+        let mut server = Server::new("vm".to_string(), 4, 16, 100);
+        server.attach_disk(Disk::new(300, DiskType::Ssd, None).unwrap()).unwrap();
+        let used = server.resources();
+        assert_eq!(used, Resources { vcpu: 4, ram_gb: 16, disk_gb: 400 });
+
+        assert!(zone.check(used, Resources { vcpu: 4, ram_gb: 16, disk_gb: 100 }).is_ok());
+        let shortage = zone.check(used, Resources { vcpu: 2, ram_gb: 4, disk_gb: 200 }).unwrap_err();
+        assert_eq!(shortage.resource, CapacityResource::Disk);
+        assert_eq!(shortage.to_string(), "zone 'zone-a' has 100 GB disk free, 200 requested");
+        let shortage = zone.check(used, Resources { vcpu: 6, ram_gb: 64, disk_gb: 200 }).unwrap_err();
+        assert_eq!((shortage.resource.reason(), shortage.available), ("insufficient_vcpu", 4));
+    }
+
+    #[test]
+    fn test_volume_new_is_detached() {
+        let volume = Volume::new("data".to_string(), 2000, DiskType::Ssd, None).unwrap();
+        assert!(volume.attached_to.is_none());
+        assert_eq!(volume.iops, 3 * 2000);
+        assert!(Volume::new("fast".to_string(), 10, DiskType::Nvme, Some(1)).is_err());
+    }
 }
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use uuid::Uuid;
-use super::entities::Server;
+use super::entities::{
+    ActivityEvent, ActivityKind, ApiKey, ChangeKind, NotificationRule, Server, ServerChange, User, Volume,
+};
 
 /// HEXAGONAL ARCHITECTURE: OUTBOUND PORT
 /// 
@@ -17,10 +19,83 @@ pub trait ServerRepository: Send + Sync {
     /// Save a server's state. In Hexagonal, we don't care if it's JSON or SQL.
     async fn save(&self, server: &Server) -> anyhow::Result<()>;
     
-    /// Retrieve all servers currently in storage.
+    /// Retrieve all servers currently in storage, sorted by ID so every call (and every
+    /// page built on it) sees the same order.
     async fn list_all(&self) -> anyhow::Result<Vec<Server>>;
     
     /// Find a specific server by its unique ID. 
     /// Returns `Option<Server>` which is the Rust way of saying "Maybe it's there, maybe it's not".
     async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Server>>;
+
+    /// Remove a server. Returns `false` if there was nothing to delete.
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool>;
+
+    /// Find a server by its unique name.
+    ///
+    /// The default scans `list_all`; adapters should override it with an index. The
+    /// file-backed ones keep a `NameIndex` in memory, a database would use a UNIQUE index on `name`.
+    async fn find_by_name(&self, name: &str) -> anyhow::Result<Option<Server>> {
+        Ok(self.list_all().await?.into_iter().find(|s| s.name == name))
+    }
+}
+
+/// OUTBOUND PORT: User storage, kept separate from servers (Interface Segregation).
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn save(&self, user: &User) -> anyhow::Result<()>;
+    async fn list_all(&self) -> anyhow::Result<Vec<User>>;
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<User>>;
+
+    /// Usernames are unique; like `find_by_name` for servers, the default is a scan.
+    async fn find_by_username(&self, username: &str) -> anyhow::Result<Option<User>> {
+        Ok(self.list_all().await?.into_iter().find(|u| u.username == username))
+    }
+}
+
+/// OUTBOUND PORT: API key storage.
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn save(&self, key: &ApiKey) -> anyhow::Result<()>;
+    async fn list_all(&self) -> anyhow::Result<Vec<ApiKey>>;
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<ApiKey>>;
+}
+
+/// OUTBOUND PORT: Volume storage.
+#[async_trait]
+pub trait VolumeRepository: Send + Sync {
+    async fn save(&self, volume: &Volume) -> anyhow::Result<()>;
+    async fn list_all(&self) -> anyhow::Result<Vec<Volume>>;
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Volume>>;
+    /// Returns `false` if there was nothing to delete.
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool>;
+}
+
+/// OUTBOUND PORT: Append-only log of server creations, updates and deletions.
+#[async_trait]
+pub trait ChangeLog: Send + Sync {
+    async fn append(&self, server_id: Uuid, kind: ChangeKind) -> anyhow::Result<ServerChange>;
+
+    /// Oldest changes first: those with a sequence *above* `after` and, if given,
+    /// that happened at or after `since` (Unix seconds).
+    async fn list_after(&self, after: Option<u64>, since: Option<u64>, limit: usize)
+        -> anyhow::Result<Vec<ServerChange>>;
+}
+
+/// OUTBOUND PORT: Notification rule storage.
+#[async_trait]
+pub trait NotificationRuleRepository: Send + Sync {
+    async fn save(&self, rule: &NotificationRule) -> anyhow::Result<()>;
+    async fn list_all(&self) -> anyhow::Result<Vec<NotificationRule>>;
+    /// Returns whether the rule existed.
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool>;
+}
+
+/// OUTBOUND PORT: Append-only activity log.
+#[async_trait]
+pub trait ActivityLog: Send + Sync {
+    /// Records an event; the log assigns its sequence number and timestamp.
+    async fn append(&self, resource_id: Uuid, kind: ActivityKind) -> anyhow::Result<ActivityEvent>;
+
+    /// Newest events first, only those with a sequence *below* `before` (if given).
+    async fn list_before(&self, before: Option<u64>, limit: usize) -> anyhow::Result<Vec<ActivityEvent>>;
 }
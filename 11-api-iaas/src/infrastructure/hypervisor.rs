@@ -0,0 +1,69 @@
+use crate::application::{ConsoleLog, Hypervisor};
+use crate::domain::Server;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// OUTBOUND ADAPTER: Simulated hypervisor
+///
+/// --- Good to know ---
+/// There is no real VM behind a server yet, so booting means writing a believable
+/// boot log (firmware, kernel, cloud-init, login prompt) to the serial console,
+/// one line every `line_delay`. A libvirt or Firecracker adapter would instead
+/// forward the guest's real serial port into the same `ConsoleLog`.
+///
+/// Comparison:
+/// - Go: Like a fake driver started with `go func() { ... }()`.
+/// - Python: Like an `asyncio.create_task()` that writes to a log file.
+pub struct SimulatedHypervisor {
+    console: Arc<dyn ConsoleLog>,
+    line_delay: Duration,
+}
+
+impl SimulatedHypervisor {
+    pub fn new(console: Arc<dyn ConsoleLog>, line_delay: Duration) -> Self {
+        Self { console, line_delay }
+    }
+}
+
+#[async_trait]
+impl Hypervisor for SimulatedHypervisor {
+    async fn boot(&self, server: &Server) -> anyhow::Result<()> {
+        let console = Arc::clone(&self.console);
+        let delay = self.line_delay;
+        let server_id = server.id;
+        let lines = boot_log(server);
+        tokio::spawn(async move {
+            for line in lines {
+                tokio::time::sleep(delay).await;
+                if let Err(e) = console.append(server_id, &line).await {
+                    eprintln!("Console log for {} failed: {:#}", server_id, e);
+                    return;
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// The console output of a typical cloud image booting on this server's flavor.
+fn boot_log(server: &Server) -> Vec<String> {
+    let kernel = [
+        format!("Linux version 6.8.0-45-generic, {} CPUs, {} GB RAM", server.cpu_cores, server.ram_gb),
+        "Command line: BOOT_IMAGE=/vmlinuz root=/dev/vda1 ro console=ttyS0".to_string(),
+        format!("virtio_blk virtio1: [vda] {} GB root disk", server.storage_gb),
+        format!("EXT4-fs (vda1): mounted filesystem, {} additional disk(s) attached", server.additional_disks.len()),
+        "systemd[1]: Reached target Network.".to_string(),
+    ];
+    let mut lines = vec!["SeaBIOS (version 1.16.3)".to_string(), "Booting from Hard Disk...".to_string()];
+    // Kernel lines carry seconds since boot, like `dmesg`.
+    lines.extend(kernel.iter().enumerate().map(|(i, line)| format!("[{:>5}.{:06}] {}", i, i * 137_519 % 1_000_000, line)));
+    lines.push(format!("cloud-init: Set hostname to {}", server.name));
+    lines.push(format!("cloud-init: Authorized {} SSH key(s) for user 'cloud'", server.ssh_keys.len()));
+    if server.user_data.is_some() {
+        lines.push("cloud-init: Running user-data script".to_string());
+    }
+    lines.push(format!("cloud-init: Private address {}", server.private_ip()));
+    lines.push(format!("{} login:", server.name));
+    lines
+}
@@ -1,2 +1,6 @@
+pub mod hypervisor;
+pub mod notifiers;
+pub mod oidc;
+pub mod passwords;
 pub mod persistence;
 pub mod web;
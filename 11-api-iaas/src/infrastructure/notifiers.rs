@@ -0,0 +1,105 @@
+use crate::application::{Notification, Notifier};
+use anyhow::Context;
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+
+/// OUTBOUND ADAPTER: E-mail over SMTP.
+///
+/// --- Good to know ---
+/// The whole server configuration (host, port, TLS mode, credentials) fits in one URL:
+/// `smtps://` is implicit TLS, `smtp://...?tls=required` is STARTTLS. Connections are
+/// pooled by the transport, so a burst of events doesn't open a connection per mail.
+///
+/// Comparison:
+/// - Go: Like `net/smtp.SendMail` (or `go-mail`) behind an interface.
+/// - Python: Like `smtplib.SMTP_SSL(...).send_message(msg)`.
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpNotifier {
+    pub fn new(url: &str, from: &str) -> anyhow::Result<Self> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+            .context("IAAS_SMTP_URL is not a valid SMTP URL")?
+            .timeout(Some(Duration::from_secs(10)))
+            .build();
+        let from = from.parse().context("IAAS_SMTP_FROM is not a valid mailbox")?;
+        Ok(Self { transport, from })
+    }
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    /// `destination` is the recipient's address.
+    async fn notify(&self, destination: &str, notification: &Notification) -> anyhow::Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(destination.parse().with_context(|| format!("invalid recipient '{}'", destination))?)
+            .subject(&notification.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(notification.text.clone())?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// OUTBOUND ADAPTER: Slack incoming webhooks.
+/// The webhook URL is the credential: whoever holds it can post to the channel.
+pub struct SlackWebhookNotifier {
+    client: reqwest::Client,
+}
+
+impl SlackWebhookNotifier {
+    pub fn new() -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(5)).build()?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackWebhookNotifier {
+    /// `destination` is the webhook URL.
+    async fn notify(&self, destination: &str, notification: &Notification) -> anyhow::Result<()> {
+        let text = format!("*{}*\n{}", notification.subject, notification.text);
+        self.client
+            .post(destination)
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+    use warp::Filter;
+
+    #[tokio::test]
+    async fn test_slack_webhook_posts_text() -> anyhow::Result<()> {
+        let received: Arc<Mutex<Option<Value>>> = Arc::default();
+        let sink = received.clone();
+        let hook = warp::post().and(warp::path("hook")).and(warp::body::json()).map(move |body: Value| {
+            *sink.lock().unwrap() = Some(body);
+            "ok"
+        });
+        let (addr, server) = warp::serve(hook).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let notification = Notification { subject: "[IaaS] Server deleted".to_string(), text: "details".to_string() };
+        let notifier = SlackWebhookNotifier::new()?;
+        notifier.notify(&format!("http://{}/hook", addr), &notification).await?;
+        let body = received.lock().unwrap().take().unwrap();
+        assert_eq!(body["text"], "*[IaaS] Server deleted*\ndetails");
+
+        // A rejected webhook (e.g. revoked in Slack) is an error, not a silent success.
+        assert!(notifier.notify(&format!("http://{}/gone", addr), &notification).await.is_err());
+        Ok(())
+    }
+}
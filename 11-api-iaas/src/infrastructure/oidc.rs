@@ -0,0 +1,373 @@
+use crate::application::{Principal, VerifyTokens};
+use crate::domain::Role;
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long fetched signing keys are trusted before they are fetched again.
+const JWKS_TTL: Duration = Duration::from_secs(600);
+
+/// Unknown key IDs refetch the keys at most this often, so junk tokens can't make
+/// us hammer the identity provider.
+const MIN_FORCED_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Everything needed to trust tokens from one identity provider.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Expected `iss` claim, e.g. `https://sso.example.com/realms/iaas`.
+    pub issuer: String,
+    /// Expected `aud` claim (the client ID registered for this API).
+    pub audience: String,
+    /// Dotted path to the claim holding the caller's roles/groups,
+    /// e.g. `roles` or Keycloak's `realm_access.roles`.
+    pub role_claim: String,
+    /// Maps claim values to roles. When empty, the values `admin`, `operator`
+    /// and `viewer` are used as-is.
+    pub role_map: Vec<(String, Role)>,
+    /// Signature algorithms accepted from keys that don't name their own `alg`.
+    /// The token header never decides on its own.
+    pub algorithms: Vec<Algorithm>,
+}
+
+impl OidcConfig {
+    /// Parses a role map like `"iaas-admins:admin,iaas-ops:operator"`.
+    pub fn parse_role_map(spec: &str) -> anyhow::Result<Vec<(String, Role)>> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (value, role) = entry
+                    .rsplit_once(':')
+                    .ok_or_else(|| anyhow!("role map entry must look like <claim value>:<role>"))?;
+                let role = Role::parse(role).ok_or_else(|| anyhow!("unknown role '{}' in role map", role))?;
+                Ok((value.to_string(), role))
+            })
+            .collect()
+    }
+
+    /// Parses an algorithm list like `"RS256,ES256"`.
+    pub fn parse_algorithms(spec: &str) -> anyhow::Result<Vec<Algorithm>> {
+        let algorithms: Vec<Algorithm> = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| Algorithm::from_str(entry).map_err(|_| anyhow!("unknown signature algorithm '{}'", entry)))
+            .collect::<anyhow::Result<_>>()?;
+        if algorithms.is_empty() {
+            bail!("at least one signature algorithm is required");
+        }
+        Ok(algorithms)
+    }
+
+    /// The most privileged role granted by the claim values, if any.
+    fn map_roles(&self, values: &[String]) -> Option<Role> {
+        values
+            .iter()
+            .filter_map(|value| {
+                if self.role_map.is_empty() {
+                    Role::parse(value)
+                } else {
+                    self.role_map.iter().find(|(claim, _)| claim == value).map(|(_, role)| *role)
+                }
+            })
+            .reduce(|best, role| if role.includes(best) { role } else { best })
+    }
+}
+
+/// Where the identity provider's public signing keys (JWKS) come from.
+#[async_trait]
+pub trait JwksSource: Send + Sync {
+    async fn fetch(&self) -> anyhow::Result<JwkSet>;
+}
+
+/// Fetches the JWKS over HTTPS, discovering its URL from
+/// `<issuer>/.well-known/openid-configuration` unless one is configured.
+pub struct HttpJwksSource {
+    client: reqwest::Client,
+    issuer: String,
+    jwks_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DiscoveryDocument {
+    jwks_uri: String,
+}
+
+impl HttpJwksSource {
+    pub fn new(issuer: &str, jwks_url: Option<String>) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(5)).build()?;
+        Ok(Self { client, issuer: issuer.trim_end_matches('/').to_string(), jwks_url })
+    }
+
+    async fn jwks_url(&self) -> anyhow::Result<String> {
+        if let Some(url) = &self.jwks_url {
+            return Ok(url.clone());
+        }
+        let discovery_url = format!("{}/.well-known/openid-configuration", self.issuer);
+        let discovery: DiscoveryDocument = self
+            .client
+            .get(&discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("invalid OIDC discovery document at {}", discovery_url))?;
+        Ok(discovery.jwks_uri)
+    }
+}
+
+#[async_trait]
+impl JwksSource for HttpJwksSource {
+    async fn fetch(&self) -> anyhow::Result<JwkSet> {
+        let url = self.jwks_url().await?;
+        let jwks = self.client.get(&url).send().await?.error_for_status()?.json().await?;
+        Ok(jwks)
+    }
+}
+
+/// OUTBOUND ADAPTER: OpenID Connect token verification
+///
+/// --- Good to know ---
+/// An OIDC access token is a signed JWT. We check the signature against the provider's
+/// published keys (JWKS), then `iss`, `aud` and `exp`, and finally turn a roles/groups
+/// claim into one of our `Role`s. Keys are cached and re-fetched when a token names an
+/// unknown key ID, which is how providers roll their keys (at most every 30 seconds).
+/// The algorithm comes from the key, or else from the configured list, never from the
+/// token alone: trusting its header is the classic "alg confusion" attack.
+///
+/// Comparison:
+/// - Go: `coreos/go-oidc` with a `RemoteKeySet`.
+/// - Python: `PyJWT`'s `PyJWKClient` + `jwt.decode(..., audience=..., issuer=...)`.
+pub struct OidcVerifier {
+    config: OidcConfig,
+    source: Arc<dyn JwksSource>,
+    cache: Mutex<Option<(JwkSet, Instant)>>,
+    /// When the last forced refresh started.
+    forced_at: Mutex<Option<Instant>>,
+}
+
+impl OidcVerifier {
+    pub fn new(config: OidcConfig, source: Arc<dyn JwksSource>) -> Self {
+        Self { config, source, cache: Mutex::new(None), forced_at: Mutex::new(None) }
+    }
+
+    /// Returns the key set, from cache when fresh. A forced refresh skips the cache,
+    /// unless another one started less than `MIN_FORCED_REFRESH_INTERVAL` ago.
+    async fn keys(&self, force_refresh: bool) -> anyhow::Result<JwkSet> {
+        let throttled = force_refresh && {
+            let mut forced_at = self.forced_at.lock().unwrap();
+            let recent = forced_at.is_some_and(|at| at.elapsed() < MIN_FORCED_REFRESH_INTERVAL);
+            if !recent {
+                *forced_at = Some(Instant::now());
+            }
+            recent
+        };
+        if !force_refresh || throttled {
+            if let Some((keys, fetched_at)) = self.cache.lock().unwrap().as_ref() {
+                if throttled || fetched_at.elapsed() < JWKS_TTL {
+                    return Ok(keys.clone());
+                }
+            }
+        }
+        let keys = self.source.fetch().await.context("failed to fetch OIDC signing keys")?;
+        *self.cache.lock().unwrap() = Some((keys.clone(), Instant::now()));
+        Ok(keys)
+    }
+
+    /// The key for `kid` and the algorithms it may sign with.
+    async fn decoding_key(&self, kid: &str) -> anyhow::Result<(DecodingKey, Vec<Algorithm>)> {
+        let mut keys = self.keys(false).await?;
+        if keys.find(kid).is_none() {
+            // The provider may have rotated its keys since we last looked.
+            keys = self.keys(true).await?;
+        }
+        let jwk = keys.find(kid).ok_or_else(|| anyhow!("unknown signing key '{}'", kid))?;
+        let algorithms = match jwk.common.key_algorithm {
+            Some(alg) => vec![Algorithm::from_str(&alg.to_string())
+                .map_err(|_| anyhow!("signing key '{}' is not for signatures ({})", kid, alg))?],
+            None => self.config.algorithms.clone(),
+        };
+        Ok((DecodingKey::from_jwk(jwk)?, algorithms))
+    }
+}
+
+#[async_trait]
+impl VerifyTokens for OidcVerifier {
+    async fn verify(&self, token: &str) -> anyhow::Result<Principal> {
+        let header = decode_header(token)?;
+        let kid = header.kid.ok_or_else(|| anyhow!("token has no key ID"))?;
+        let (key, algorithms) = self.decoding_key(&kid).await?;
+
+        // `decode` rejects a header `alg` outside this list, and one that doesn't fit the key type.
+        let mut validation = Validation::new(algorithms[0]);
+        validation.algorithms = algorithms;
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[&self.config.audience]);
+        let claims = decode::<Value>(token, &key, &validation)?.claims;
+
+        let subject = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("token has no subject"))?
+            .to_string();
+        let values = claim_values(&claims, &self.config.role_claim);
+        let Some(role) = self.config.map_roles(&values) else {
+            bail!("token for '{}' grants no known role", subject);
+        };
+        Ok(Principal::with_role(subject, role))
+    }
+}
+
+/// Reads a string or string-array claim at a dotted path (`realm_access.roles`).
+fn claim_values(claims: &Value, path: &str) -> Vec<String> {
+    let claim = path.split('.').try_fold(claims, |value, key| value.get(key));
+    match claim {
+        Some(Value::String(value)) => vec![value.clone()],
+        Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).map(String::from).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const SECRET: &[u8] = b"test-signing-secret-of-32-bytes!";
+
+    /// Test double: serves a fixed key set and counts fetches.
+    struct StaticJwks {
+        keys: JwkSet,
+        fetches: AtomicU32,
+    }
+
+    #[async_trait]
+    impl JwksSource for StaticJwks {
+        async fn fetch(&self) -> anyhow::Result<JwkSet> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(self.keys.clone())
+        }
+    }
+
+    fn verifier(role_claim: &str, role_map: Vec<(String, Role)>) -> (OidcVerifier, Arc<StaticJwks>) {
+        let keys = serde_json::from_value(serde_json::json!({
+            "keys": [{ "kty": "oct", "kid": "k1", "k": URL_SAFE_NO_PAD.encode(SECRET) }]
+        }))
+        .unwrap();
+        let source = Arc::new(StaticJwks { keys, fetches: AtomicU32::new(0) });
+        let config = OidcConfig {
+            issuer: "https://sso.example.com".to_string(),
+            audience: "iaas-api".to_string(),
+            role_claim: role_claim.to_string(),
+            role_map,
+            algorithms: vec![Algorithm::HS256],
+        };
+        (OidcVerifier::new(config, source.clone()), source)
+    }
+
+    fn token(kid: &str, claims: Value) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(kid.to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn claims(extra: Value) -> Value {
+        let mut claims = serde_json::json!({
+            "sub": "user-42",
+            "iss": "https://sso.example.com",
+            "aud": "iaas-api",
+            "exp": jsonwebtoken::get_current_timestamp() + 300,
+        });
+        claims.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        claims
+    }
+
+    #[tokio::test]
+    async fn test_valid_token_maps_highest_role() -> anyhow::Result<()> {
+        let (verifier, source) = verifier("roles", Vec::new());
+        let principal = verifier
+            .verify(&token("k1", claims(serde_json::json!({ "roles": ["viewer", "operator"] }))))
+            .await?;
+        assert_eq!(principal.subject, "user-42");
+        assert_eq!(principal.scopes, Role::Operator.scopes());
+
+        // Keys are cached between requests.
+        verifier.verify(&token("k1", claims(serde_json::json!({ "roles": "viewer" })))).await?;
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nested_claim_with_role_map() -> anyhow::Result<()> {
+        let role_map = OidcConfig::parse_role_map("iaas-admins:admin, iaas-ops:operator")?;
+        let (verifier, _) = verifier("realm_access.roles", role_map);
+        let principal = verifier
+            .verify(&token("k1", claims(serde_json::json!({ "realm_access": { "roles": ["iaas-admins"] } }))))
+            .await?;
+        assert_eq!(principal.scopes, Role::Admin.scopes());
+
+        let no_role = claims(serde_json::json!({ "realm_access": { "roles": ["admin"] } }));
+        assert!(verifier.verify(&token("k1", no_role)).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rejects_wrong_issuer_audience_expiry_and_key() {
+        let (verifier, source) = verifier("roles", Vec::new());
+        let bad = [
+            claims(serde_json::json!({ "roles": "admin", "iss": "https://evil.example.com" })),
+            claims(serde_json::json!({ "roles": "admin", "aud": "another-api" })),
+            claims(serde_json::json!({ "roles": "admin", "exp": 1 })),
+        ];
+        for claims in bad {
+            assert!(verifier.verify(&token("k1", claims)).await.is_err());
+        }
+
+        // An unknown key ID triggers one refresh before failing; more of them within
+        // the refresh interval trigger none.
+        let before = source.fetches.load(Ordering::SeqCst);
+        for _ in 0..3 {
+            assert!(verifier.verify(&token("k9", claims(serde_json::json!({ "roles": "admin" })))).await.is_err());
+        }
+        assert_eq!(source.fetches.load(Ordering::SeqCst), before + 1);
+    }
+
+    #[tokio::test]
+    async fn test_algorithm_comes_from_key_or_config_not_token() {
+        let admin = claims(serde_json::json!({ "roles": "admin" }));
+        let mut header = Header::new(Algorithm::HS512);
+        header.kid = Some("k1".to_string());
+        let hs512 = encode(&header, &admin, &EncodingKey::from_secret(SECRET)).unwrap();
+
+        // The key names no `alg`, so the configured list (HS256 only) applies.
+        let (verifier, _) = verifier("roles", Vec::new());
+        assert!(verifier.verify(&hs512).await.is_err());
+        assert!(verifier.verify(&token("k1", admin.clone())).await.is_ok());
+
+        // A key that names its `alg` only accepts that one, whatever the config says.
+        let keys = serde_json::from_value(serde_json::json!({
+            "keys": [{ "kty": "oct", "kid": "k1", "alg": "HS512", "k": URL_SAFE_NO_PAD.encode(SECRET) }]
+        }))
+        .unwrap();
+        let pinned = OidcVerifier::new(verifier.config.clone(), Arc::new(StaticJwks { keys, fetches: AtomicU32::new(0) }));
+        assert!(pinned.verify(&hs512).await.is_ok());
+        assert!(pinned.verify(&token("k1", admin)).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_algorithms() -> anyhow::Result<()> {
+        assert_eq!(OidcConfig::parse_algorithms("RS256, ES256")?, vec![Algorithm::RS256, Algorithm::ES256]);
+        assert!(OidcConfig::parse_algorithms("none").is_err());
+        assert!(OidcConfig::parse_algorithms("").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use crate::application::HashPasswords;
+use anyhow::anyhow;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use sha2::{Digest, Sha256};
+
+/// OUTBOUND ADAPTER: Argon2 password hashing
+///
+/// --- Good to know ---
+/// Argon2id won the Password Hashing Competition and is OWASP's first choice.
+/// It is deliberately slow and memory-hungry, which makes brute-forcing leaked
+/// hashes expensive. Each hash gets a random salt, so equal passwords hash differently.
+///
+/// Comparison:
+/// - Go: `golang.org/x/crypto/argon2`.
+/// - Python: `argon2-cffi` (`PasswordHasher().hash(...)`).
+#[derive(Default)]
+pub struct Argon2PasswordHasher {
+    argon2: Argon2<'static>,
+}
+
+impl HashPasswords for Argon2PasswordHasher {
+    fn hash(&self, password: &str) -> anyhow::Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = self
+            .argon2
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| anyhow!("failed to hash password: {}", e))?;
+        // PHC string format: "$argon2id$v=19$m=...,t=...,p=...$<salt>$<hash>"
+        Ok(hash.to_string())
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> anyhow::Result<bool> {
+        let parsed = PasswordHash::new(hash).map_err(|e| anyhow!("invalid password hash: {}", e))?;
+        Ok(self.argon2.verify_password(password.as_bytes(), &parsed).is_ok())
+    }
+}
+
+/// OUTBOUND ADAPTER: SHA-256 hashing for machine-generated secrets (API keys).
+///
+/// --- Good to know ---
+/// Argon2 is slow on purpose to protect *guessable* passwords. A random 256-bit key
+/// can't be guessed, so salting and stretching add nothing but latency on every request.
+///
+/// Comparison:
+/// - Go: `sha256.Sum256` + `subtle.ConstantTimeCompare`.
+/// - Python: `hashlib.sha256(...)` + `hmac.compare_digest`.
+#[derive(Default)]
+pub struct Sha256SecretHasher;
+
+impl HashPasswords for Sha256SecretHasher {
+    fn hash(&self, secret: &str) -> anyhow::Result<String> {
+        let digest = Sha256::digest(secret.as_bytes());
+        Ok(format!("sha256:{}", digest.iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+    }
+
+    fn verify(&self, secret: &str, hash: &str) -> anyhow::Result<bool> {
+        let expected = self.hash(secret)?;
+        // Compare every byte, so the time taken doesn't reveal how much matched.
+        let same = expected.len() == hash.len()
+            && expected.bytes().zip(hash.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0;
+        Ok(same)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify() -> anyhow::Result<()> {
+        let hasher = Argon2PasswordHasher::default();
+        let hash = hasher.hash("correct horse battery staple")?;
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(hasher.verify("correct horse battery staple", &hash)?);
+        assert!(!hasher.verify("wrong password!!", &hash)?);
+        // Random salt: the same password never produces the same hash twice.
+        assert_ne!(hash, hasher.hash("correct horse battery staple")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256_secret_hasher() -> anyhow::Result<()> {
+        let hasher = Sha256SecretHasher;
+        let hash = hasher.hash("random-secret")?;
+        assert!(hash.starts_with("sha256:"));
+        assert!(hasher.verify("random-secret", &hash)?);
+        assert!(!hasher.verify("random-secreT", &hash)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use crate::domain::{ActivityEvent, ActivityKind, ActivityLog};
+use anyhow::Context;
+use async_trait::async_trait;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// OUTBOUND ADAPTER: Activity log as a JSON Lines file (`<storage_dir>/activity.jsonl`).
+///
+/// --- Good to know ---
+/// "JSON Lines" means one JSON document per line. Appending is cheap and a crash can at
+/// worst cut off the last line, which is why audit logs and log shippers love the format.
+/// Opening the log drops such a cut-off line, so the next append starts on a fresh one.
+/// The file is read once; pages are then served from memory.
+///
+/// Comparison:
+/// - Go: Like appending `json.Marshal` output to an `os.O_APPEND` file.
+/// - Python: Like `f.write(json.dumps(event) + "\n")` on a file opened with `"a"`.
+pub struct JsonActivityLog {
+    path: PathBuf,
+    /// Every event written, oldest first. The lock also serializes appends,
+    /// so two events can never share a number.
+    events: Mutex<Vec<ActivityEvent>>,
+}
+
+impl JsonActivityLog {
+    pub fn new(storage_dir: &str) -> anyhow::Result<Self> {
+        fs::create_dir_all(storage_dir)?;
+        let path = PathBuf::from(storage_dir).join("activity.jsonl");
+        let events = load_events(&path)?;
+        Ok(Self { path, events: Mutex::new(events) })
+    }
+}
+
+/// Reads every complete line. A last line without its newline is an append that a crash
+/// interrupted: it is cut off the file. A bad line anywhere else is real corruption.
+fn load_events(path: &Path) -> anyhow::Result<Vec<ActivityEvent>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let complete = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |newline| newline + 1);
+    if complete < bytes.len() {
+        eprintln!("Dropping a partly written last line from {}.", path.display());
+        OpenOptions::new().write(true).open(path)?.set_len(complete as u64)?;
+    }
+    std::str::from_utf8(&bytes[..complete])?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line).with_context(|| format!("{} line {} is corrupt", path.display(), number + 1))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl ActivityLog for JsonActivityLog {
+    async fn append(&self, resource_id: Uuid, kind: ActivityKind) -> anyhow::Result<ActivityEvent> {
+        let mut events = self.events.lock().unwrap();
+        let event = ActivityEvent {
+            sequence: events.last().map_or(0, |last| last.sequence) + 1,
+            occurred_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            resource_id,
+            kind,
+        };
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&event)?)?;
+        events.push(event.clone());
+        Ok(event)
+    }
+
+    async fn list_before(&self, before: Option<u64>, limit: usize) -> anyhow::Result<Vec<ActivityEvent>> {
+        let events = self.events.lock().unwrap();
+        // Sequences only grow, so the events below `before` are a prefix.
+        let end = before.map_or(events.len(), |before| events.partition_point(|event| event.sequence < before));
+        Ok(events[..end].iter().rev().take(limit).cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_sequences_survive_restart_and_pages_go_backwards() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let dir_path = dir.path().to_str().unwrap();
+        let log = JsonActivityLog::new(dir_path)?;
+        for _ in 0..3 {
+            log.append(Uuid::new_v4(), ActivityKind::UserDisabled).await?;
+        }
+
+        // A new instance continues the numbering instead of starting over.
+        let log = JsonActivityLog::new(dir_path)?;
+        assert_eq!(log.append(Uuid::new_v4(), ActivityKind::UserEnabled).await?.sequence, 4);
+
+        let newest: Vec<u64> = log.list_before(None, 2).await?.iter().map(|e| e.sequence).collect();
+        assert_eq!(newest, vec![4, 3]);
+        let older: Vec<u64> = log.list_before(Some(3), 10).await?.iter().map(|e| e.sequence).collect();
+        assert_eq!(older, vec![2, 1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_a_cut_off_last_line_is_dropped() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let dir_path = dir.path().to_str().unwrap();
+        let log = JsonActivityLog::new(dir_path)?;
+        log.append(Uuid::new_v4(), ActivityKind::UserDisabled).await?;
+
+        // A crash in the middle of the second append.
+        let path = dir.path().join("activity.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        write!(file, "{{\"sequence\":2,\"occurred_at\":")?;
+
+        let log = JsonActivityLog::new(dir_path)?;
+        assert_eq!(log.list_before(None, 10).await?.len(), 1);
+        assert_eq!(log.append(Uuid::new_v4(), ActivityKind::UserEnabled).await?.sequence, 2);
+        assert_eq!(JsonActivityLog::new(dir_path)?.list_before(None, 10).await?.len(), 2);
+
+        // Damage before the last line is not an interrupted write: refuse to guess.
+        fs::write(&path, "not json\n")?;
+        assert!(JsonActivityLog::new(dir_path).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use super::document_store::{DocumentStore, FileDocumentStore};
+use crate::domain::{ApiKey, ApiKeyRepository};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// OUTBOUND ADAPTER: API keys as JSON documents in `<storage_dir>/api_keys/`.
+/// Mirrors `JsonUserRepository`.
+pub struct JsonApiKeyRepository {
+    store: Arc<dyn DocumentStore>,
+}
+
+impl JsonApiKeyRepository {
+    /// Creates the repository in the `api_keys` sub-directory of `storage_dir`.
+    pub fn new(storage_dir: &str) -> anyhow::Result<Self> {
+        let keys_dir = Path::new(storage_dir).join("api_keys");
+        let store = FileDocumentStore::new(&keys_dir.to_string_lossy())?;
+        Ok(Self { store: Arc::new(store) })
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for JsonApiKeyRepository {
+    async fn save(&self, key: &ApiKey) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(key)?;
+        self.store.put(key.id, json.as_bytes())
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<ApiKey>> {
+        self.store
+            .list()?
+            .into_iter()
+            .map(|(_, content)| Ok(serde_json::from_slice(&content)?))
+            .collect()
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<ApiKey>> {
+        match self.store.get(id)? {
+            Some(content) => Ok(Some(serde_json::from_slice(&content)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::domain::{ChangeKind, ChangeLog, Server, ServerChange, ServerRepository};
+use async_trait::async_trait;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// OUTBOUND ADAPTER: Server change-log as a JSON Lines file (`<storage_dir>/changes.jsonl`).
+/// Same format and numbering scheme as `JsonActivityLog`.
+pub struct JsonChangeLog {
+    path: PathBuf,
+    /// Sequence of the last change written; the lock also serializes appends.
+    last_sequence: Mutex<u64>,
+}
+
+impl JsonChangeLog {
+    pub fn new(storage_dir: &str) -> anyhow::Result<Self> {
+        fs::create_dir_all(storage_dir)?;
+        let path = PathBuf::from(storage_dir).join("changes.jsonl");
+        let last_sequence = read_changes(&path)?.last().map_or(0, |change| change.sequence);
+        Ok(Self { path, last_sequence: Mutex::new(last_sequence) })
+    }
+}
+
+fn read_changes(path: &PathBuf) -> anyhow::Result<Vec<ServerChange>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+#[async_trait]
+impl ChangeLog for JsonChangeLog {
+    async fn append(&self, server_id: Uuid, kind: ChangeKind) -> anyhow::Result<ServerChange> {
+        let mut last_sequence = self.last_sequence.lock().unwrap();
+        let change = ServerChange {
+            sequence: *last_sequence + 1,
+            occurred_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            server_id,
+            kind,
+        };
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&change)?)?;
+        *last_sequence = change.sequence;
+        Ok(change)
+    }
+
+    async fn list_after(
+        &self,
+        after: Option<u64>,
+        since: Option<u64>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ServerChange>> {
+        let _guard = self.last_sequence.lock().unwrap();
+        Ok(read_changes(&self.path)?
+            .into_iter()
+            .filter(|change| after.is_none_or(|after| change.sequence > after))
+            .filter(|change| since.is_none_or(|since| change.occurred_at >= since))
+            .take(limit)
+            .collect())
+    }
+}
+
+/// DECORATOR PATTERN: Change tracking
+///
+/// --- Good to know ---
+/// Recording changes at the repository level means no code path can forget it:
+/// whatever saves or deletes a server (API, migrations, key rotation) lands in the log.
+/// It is the file-based cousin of a database's change data capture (CDC).
+///
+/// Comparison:
+/// - Go: Like a repository wrapper that publishes to an outbox table on every write.
+/// - Python: Like Django's `post_save` / `post_delete` signals writing to a log.
+pub struct ChangeTrackingRepository {
+    inner: Arc<dyn ServerRepository>,
+    log: Arc<dyn ChangeLog>,
+}
+
+impl ChangeTrackingRepository {
+    pub fn new(inner: Arc<dyn ServerRepository>, log: Arc<dyn ChangeLog>) -> Self {
+        Self { inner, log }
+    }
+}
+
+#[async_trait]
+impl ServerRepository for ChangeTrackingRepository {
+    async fn save(&self, server: &Server) -> anyhow::Result<()> {
+        let existed = self.inner.find_by_id(server.id).await?.is_some();
+        self.inner.save(server).await?;
+        let kind = if existed { ChangeKind::Updated } else { ChangeKind::Created };
+        self.log.append(server.id, kind).await?;
+        Ok(())
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<Server>> {
+        self.inner.list_all().await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Server>> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_by_name(&self, name: &str) -> anyhow::Result<Option<Server>> {
+        self.inner.find_by_name(name).await
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        let deleted = self.inner.delete(id).await?;
+        if deleted {
+            self.log.append(id, ChangeKind::Deleted).await?;
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::JsonServerRepository;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_saves_and_deletes_are_logged_in_order() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let dir_path = dir.path().to_str().unwrap();
+        let log = Arc::new(JsonChangeLog::new(dir_path)?);
+        let repo = ChangeTrackingRepository::new(Arc::new(JsonServerRepository::new(dir_path)?), log.clone());
+
+        let mut server = Server::new("vm".to_string(), 1, 1, 10);
+        repo.save(&server).await?;
+        server.name = "vm-renamed".to_string();
+        repo.save(&server).await?;
+        assert!(repo.delete(server.id).await?);
+        assert!(!repo.delete(server.id).await?);
+
+        let kinds: Vec<ChangeKind> = log.list_after(None, None, 10).await?.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![ChangeKind::Created, ChangeKind::Updated, ChangeKind::Deleted]);
+        assert_eq!(log.list_after(Some(2), None, 10).await?.len(), 1);
+        Ok(())
+    }
+}
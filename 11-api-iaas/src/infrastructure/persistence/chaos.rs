@@ -0,0 +1,161 @@
+use crate::domain::{Server, ServerRepository};
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Which storage operations a fault applies to.
+/// Targeting only reads or only writes simulates a *partial* outage.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultScope {
+    #[default]
+    All,
+    Reads,
+    Writes,
+}
+
+/// What to inject. The default injects nothing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FaultConfig {
+    /// Extra delay added before every affected call.
+    pub latency_ms: u64,
+    /// Probability (0.0 - 1.0) that an affected call fails.
+    pub error_rate: f64,
+    pub scope: FaultScope,
+}
+
+/// Live, shareable fault settings: the repository reads them on every call and
+/// the admin endpoint (`PUT /admin/faults`) replaces them at runtime.
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    config: RwLock<FaultConfig>,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultConfig) -> Self {
+        Self { config: RwLock::new(config) }
+    }
+
+    pub fn config(&self) -> FaultConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: FaultConfig) {
+        *self.config.write().unwrap() = config;
+    }
+}
+
+/// OUTBOUND ADAPTER DECORATOR: Chaos Engineering
+///
+/// --- Good to know ---
+/// Netflix made "Chaos Monkey" famous: break things on purpose to prove the system
+/// copes. This wrapper adds latency and random errors in front of a real repository,
+/// so you can watch retries, the circuit breaker, and API clients under degraded storage.
+///
+/// Never enable this in production!
+pub struct FaultyRepository {
+    inner: Arc<dyn ServerRepository>,
+    faults: Arc<FaultInjector>,
+}
+
+impl FaultyRepository {
+    pub fn new(inner: Arc<dyn ServerRepository>, faults: Arc<FaultInjector>) -> Self {
+        Self { inner, faults }
+    }
+
+    /// Applies the configured latency and maybe fails, if `scope` is targeted.
+    async fn inject(&self, operation: FaultScope) -> anyhow::Result<()> {
+        let config = self.faults.config();
+        if config.scope != FaultScope::All && config.scope != operation {
+            return Ok(());
+        }
+        if config.latency_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(config.latency_ms)).await;
+        }
+        if config.error_rate > 0.0 && rand::thread_rng().gen_bool(config.error_rate.min(1.0)) {
+            // An I/O error, like the disk or network failures it stands in for.
+            return Err(io::Error::other("injected storage fault").into());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ServerRepository for FaultyRepository {
+    async fn save(&self, server: &Server) -> anyhow::Result<()> {
+        self.inject(FaultScope::Writes).await?;
+        self.inner.save(server).await
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<Server>> {
+        self.inject(FaultScope::Reads).await?;
+        self.inner.list_all().await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Server>> {
+        self.inject(FaultScope::Reads).await?;
+        self.inner.find_by_id(id).await
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        self.inject(FaultScope::Writes).await?;
+        self.inner.delete(id).await
+    }
+
+    async fn find_by_name(&self, name: &str) -> anyhow::Result<Option<Server>> {
+        self.inject(FaultScope::Reads).await?;
+        self.inner.find_by_name(name).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::JsonServerRepository;
+    use std::time::Instant;
+    use tempfile::tempdir;
+
+    fn faulty(dir: &tempfile::TempDir, config: FaultConfig) -> (FaultyRepository, Arc<FaultInjector>) {
+        let inner = Arc::new(JsonServerRepository::new(dir.path().to_str().unwrap()).unwrap());
+        let faults = Arc::new(FaultInjector::new(config));
+        (FaultyRepository::new(inner, Arc::clone(&faults)), faults)
+    }
+
+    #[tokio::test]
+    async fn test_errors_can_be_switched_on_and_off() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let (repo, faults) = faulty(&dir, FaultConfig { error_rate: 1.0, ..Default::default() });
+        assert!(repo.list_all().await.is_err());
+
+        faults.set_config(FaultConfig::default());
+        assert!(repo.list_all().await.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scope_limits_faults_to_writes() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let config = FaultConfig { error_rate: 1.0, scope: FaultScope::Writes, ..Default::default() };
+        let (repo, _) = faulty(&dir, config);
+
+        let server = Server::new("vm".to_string(), 1, 1, 10);
+        assert!(repo.save(&server).await.is_err());
+        assert!(repo.list_all().await.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_latency_is_injected() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let (repo, _) = faulty(&dir, FaultConfig { latency_ms: 30, ..Default::default() });
+
+        let started = Instant::now();
+        repo.list_all().await?;
+        assert!(started.elapsed() >= Duration::from_millis(30));
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use crate::application::{ConsoleFollow, ConsoleLog};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Lines a slow follower may fall behind before it starts skipping.
+const FEED_CAPACITY: usize = 256;
+
+/// OUTBOUND ADAPTER: Console logs as plain text files (`<storage_dir>/console/<id>.log`).
+///
+/// --- Good to know ---
+/// Console output never stops growing, so files are rotated: once `<id>.log` reaches
+/// `max_bytes` it becomes `<id>.log.1` (replacing the previous one) and a fresh file
+/// starts. At most `2 * max_bytes` per server are kept. Followers get new lines
+/// through a broadcast channel instead of re-reading the file.
+///
+/// Comparison:
+/// - Go: Like `lumberjack.Logger` with `MaxBackups: 1`, plus a fan-out channel.
+/// - Python: Like `logging.handlers.RotatingFileHandler(backupCount=1)`.
+pub struct FileConsoleLog {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Live feeds per server. The lock also serializes writes and rotation.
+    feeds: Mutex<HashMap<Uuid, broadcast::Sender<String>>>,
+}
+
+impl FileConsoleLog {
+    pub fn new(storage_dir: &str, max_bytes: u64) -> anyhow::Result<Self> {
+        let dir = Path::new(storage_dir).join("console");
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_bytes, feeds: Mutex::new(HashMap::new()) })
+    }
+
+    fn current(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.log", id))
+    }
+
+    fn rotated(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.log.1", id))
+    }
+
+    /// Reads the last `lines` lines across the rotated and the current file.
+    fn read_tail(&self, id: Uuid, lines: usize) -> anyhow::Result<Vec<String>> {
+        let mut all = Vec::new();
+        for path in [self.rotated(id), self.current(id)] {
+            if path.exists() {
+                all.extend(fs::read_to_string(path)?.lines().map(String::from));
+            }
+        }
+        let skip = all.len().saturating_sub(lines);
+        Ok(all.split_off(skip))
+    }
+}
+
+#[async_trait]
+impl ConsoleLog for FileConsoleLog {
+    async fn append(&self, server_id: Uuid, line: &str) -> anyhow::Result<()> {
+        let mut feeds = self.feeds.lock().unwrap();
+        let path = self.current(server_id);
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        // One entry per line, even if the guest wrote several at once.
+        let lines: Vec<&str> = line.lines().collect();
+        for line in &lines {
+            writeln!(file, "{}", line)?;
+        }
+        if file.metadata()?.len() >= self.max_bytes {
+            fs::rename(&path, self.rotated(server_id))?;
+        }
+
+        if let Some(feed) = feeds.get(&server_id) {
+            if feed.receiver_count() == 0 {
+                feeds.remove(&server_id);
+            } else {
+                for line in lines {
+                    // Only fails without receivers, which is fine.
+                    let _ = feed.send(line.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn tail(&self, server_id: Uuid, lines: usize) -> anyhow::Result<Vec<String>> {
+        let _guard = self.feeds.lock().unwrap();
+        self.read_tail(server_id, lines)
+    }
+
+    async fn follow(&self, server_id: Uuid, lines: usize) -> anyhow::Result<ConsoleFollow> {
+        let mut feeds = self.feeds.lock().unwrap();
+        let backlog = self.read_tail(server_id, lines)?;
+        let feed = feeds.entry(server_id).or_insert_with(|| broadcast::channel(FEED_CAPACITY).0);
+        Ok((backlog, feed.subscribe()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_rotation_keeps_tail_and_follow_sees_new_lines() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let log = FileConsoleLog::new(dir.path().to_str().unwrap(), 64)?;
+        let id = Uuid::new_v4();
+        for i in 0..20 {
+            log.append(id, &format!("line {:02}", i)).await?;
+        }
+
+        // Older generations are gone, but the tail spans the rotated and the current file.
+        let tail = log.tail(id, 5).await?;
+        assert_eq!(tail, vec!["line 15", "line 16", "line 17", "line 18", "line 19"]);
+        assert!(log.tail(id, 100).await?.len() < 20);
+
+        let (backlog, mut feed) = log.follow(id, 1).await?;
+        assert_eq!(backlog, vec!["line 19"]);
+        log.append(id, "login:\nextra").await?;
+        assert_eq!(feed.recv().await?, "login:");
+        assert_eq!(feed.recv().await?, "extra");
+        Ok(())
+    }
+}
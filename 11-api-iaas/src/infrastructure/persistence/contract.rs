@@ -0,0 +1,183 @@
+use crate::domain::{Disk, DiskType, Server, ServerRepository};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Concurrent tasks per concurrency check: enough to interleave, cheap enough for file storage.
+const CONCURRENCY: usize = 16;
+
+/// CONTRACT TESTS: What every `ServerRepository` must do
+///
+/// --- Good to know ---
+/// The trait only fixes signatures; the semantics (an update replaces, a delete is
+/// reported once, readers never see half-written documents...) live here. Every
+/// adapter runs this same suite against an empty instance, so a new backend
+/// (SQLite, S3, ...) proves itself with one test instead of copying assertions.
+/// `list_all` is sorted by ID, so pagination stays stable whatever the backend.
+///
+/// Comparison:
+/// - Go: Like a shared `func TestRepository(t *testing.T, newRepo func() Repository)`.
+/// - Python: Like a pytest base class whose subclasses only provide the `repo` fixture.
+///
+/// Run it on a multi-threaded runtime, or the "concurrent" checks never overlap.
+pub async fn verify_repository_contract<R: ServerRepository + 'static>(repo: Arc<R>) -> anyhow::Result<()> {
+    assert!(repo.list_all().await?.is_empty(), "contract: the repository must start empty");
+    crud_round_trip(repo.as_ref()).await?;
+    concurrent_saves_all_land(Arc::clone(&repo)).await?;
+    concurrent_updates_never_tear(Arc::clone(&repo)).await?;
+    concurrent_reads_survive_deletes(repo).await?;
+    Ok(())
+}
+
+/// A fully populated server, so no field is silently dropped on the way to storage.
+fn sample(name: &str) -> Server {
+    let mut server = Server::new(name.to_string(), 4, 16, 80);
+    server.attach_disk(Disk::new(200, DiskType::Ssd, Some(6000)).unwrap()).unwrap();
+    server.ssh_keys = vec!["ssh-ed25519 AAAA test@example".to_string()];
+    server.user_data = Some("#cloud-config\n".to_string());
+    server.description = Some("contract test".to_string());
+    server.tags.insert("env".to_string(), "test".to_string());
+    server.zone = Some("zone-a".to_string());
+    server
+}
+
+/// Servers have no `PartialEq` (nothing in the app needs one); their JSON form does.
+fn same(a: &Server, b: &Server) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+async fn crud_round_trip(repo: &dyn ServerRepository) -> anyhow::Result<()> {
+    let missing = Uuid::new_v4();
+    assert!(repo.find_by_id(missing).await?.is_none(), "contract: unknown IDs are Ok(None), not errors");
+    assert!(repo.find_by_name("nobody").await?.is_none(), "contract: unknown names are Ok(None)");
+    assert!(!repo.delete(missing).await?, "contract: deleting an unknown ID returns false");
+
+    let mut server = sample("contract-vm");
+    repo.save(&server).await?;
+    let loaded = repo.find_by_id(server.id).await?.expect("contract: a saved server can be found by ID");
+    assert!(same(&loaded, &server), "contract: every field survives a round trip");
+
+    // An update replaces the document: still one server, only the new name resolves.
+    server.name = "contract-vm-renamed".to_string();
+    server.tags.clear();
+    repo.save(&server).await?;
+    assert_eq!(repo.list_all().await?.len(), 1, "contract: saving an existing ID updates, never duplicates");
+    assert!(same(&repo.find_by_id(server.id).await?.unwrap(), &server), "contract: the last save wins");
+    assert!(repo.find_by_name("contract-vm").await?.is_none(), "contract: old names stop resolving");
+    let by_name = repo.find_by_name("contract-vm-renamed").await?;
+    assert_eq!(by_name.map(|s| s.id), Some(server.id), "contract: servers can be found by current name");
+
+    assert!(repo.delete(server.id).await?, "contract: deleting an existing server returns true");
+    assert!(!repo.delete(server.id).await?, "contract: a delete is only reported once");
+    assert!(repo.find_by_id(server.id).await?.is_none(), "contract: deleted servers are gone");
+    assert!(repo.list_all().await?.is_empty(), "contract: deleted servers are not listed");
+    Ok(())
+}
+
+async fn concurrent_saves_all_land<R: ServerRepository + 'static>(repo: Arc<R>) -> anyhow::Result<()> {
+    let mut tasks = Vec::new();
+    for i in 0..CONCURRENCY {
+        let repo = Arc::clone(&repo);
+        tasks.push(tokio::spawn(async move {
+            let server = sample(&format!("parallel-{}", i));
+            repo.save(&server).await.map(|()| server.id)
+        }));
+    }
+    let mut saved = BTreeSet::new();
+    for task in tasks {
+        saved.insert(task.await??);
+    }
+    let listed: Vec<Uuid> = repo.list_all().await?.iter().map(|s| s.id).collect();
+    let expected: Vec<Uuid> = saved.iter().copied().collect();
+    assert_eq!(listed, expected, "contract: concurrent saves all land, each listed once, sorted by ID");
+    for id in saved {
+        repo.delete(id).await?;
+    }
+    Ok(())
+}
+
+/// Writers race on one server while readers poll it: every read must see one
+/// complete version (no torn or half-written documents), never an error.
+async fn concurrent_updates_never_tear<R: ServerRepository + 'static>(repo: Arc<R>) -> anyhow::Result<()> {
+    let server = sample("contended");
+    repo.save(&server).await?;
+    let names: Vec<String> = (0..CONCURRENCY).map(|i| format!("contended-{}", "x".repeat(i * 64))).collect();
+
+    let mut tasks = Vec::new();
+    for name in names.clone() {
+        let (writer, mut version) = (Arc::clone(&repo), server.clone());
+        version.name = name;
+        tasks.push(tokio::spawn(async move { writer.save(&version).await }));
+        let (reader, id) = (Arc::clone(&repo), server.id);
+        tasks.push(tokio::spawn(async move {
+            let read = reader.find_by_id(id).await?;
+            anyhow::ensure!(read.is_some(), "contract: a server being updated never disappears");
+            Ok(())
+        }));
+    }
+    for task in tasks {
+        task.await??;
+    }
+    let last = repo.find_by_id(server.id).await?.unwrap();
+    assert!(names.contains(&last.name), "contract: after racing saves, one of them wins intact");
+    repo.delete(server.id).await?;
+    Ok(())
+}
+
+async fn concurrent_reads_survive_deletes<R: ServerRepository + 'static>(repo: Arc<R>) -> anyhow::Result<()> {
+    let mut ids = Vec::new();
+    for i in 0..CONCURRENCY {
+        let server = sample(&format!("doomed-{}", i));
+        repo.save(&server).await?;
+        ids.push(server.id);
+    }
+    let mut tasks = Vec::new();
+    for id in ids {
+        let deleter = Arc::clone(&repo);
+        tasks.push(tokio::spawn(async move { deleter.delete(id).await.map(|_| ()) }));
+        let lister = Arc::clone(&repo);
+        tasks.push(tokio::spawn(async move { lister.list_all().await.map(|_| ()) }));
+    }
+    for task in tasks {
+        task.await?.map_err(|e| e.context("contract: listing while servers are deleted must not fail"))?;
+    }
+    assert!(repo.list_all().await?.is_empty(), "contract: every concurrent delete lands");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::{
+        ChangeTrackingRepository, CircuitBreaker, EncryptedRepository, FaultConfig, FaultInjector, FaultyRepository,
+        FileDocumentStore, JsonChangeLog, JsonServerRepository, ResilientRepository, RetryPolicy, StaticKeyProvider,
+    };
+    use tempfile::tempdir;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_json_repository_meets_contract() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        verify_repository_contract(Arc::new(JsonServerRepository::new(dir.path().to_str().unwrap())?)).await
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_encrypted_repository_meets_contract() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let store = Arc::new(FileDocumentStore::new(dir.path().to_str().unwrap())?);
+        let keys = Arc::new(StaticKeyProvider::new(vec![("k1".to_string(), [7; 32])])?);
+        verify_repository_contract(Arc::new(EncryptedRepository::new(store, keys))).await
+    }
+
+    /// The decorators must keep the contract of what they wrap.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_decorated_repositories_meet_contract() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().to_str().unwrap();
+        let json: Arc<dyn ServerRepository> = Arc::new(JsonServerRepository::new(path)?);
+        let faulty = Arc::new(FaultyRepository::new(json, Arc::new(FaultInjector::new(FaultConfig::default()))));
+        let breaker = Arc::new(CircuitBreaker::default());
+        let resilient = Arc::new(ResilientRepository::new(faulty, breaker, RetryPolicy::default()));
+        let log = Arc::new(JsonChangeLog::new(path)?);
+        verify_repository_contract(Arc::new(ChangeTrackingRepository::new(resilient, log))).await
+    }
+}
@@ -0,0 +1,127 @@
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// STORAGE SEAM: Raw document storage
+///
+/// --- Good to know ---
+/// Repositories decide *what* a document looks like (plain JSON, encrypted envelope, ...).
+/// A `DocumentStore` only decides *where* the bytes live (local files today, S3 tomorrow).
+/// Splitting the two lets a repository add behaviour such as encryption without
+/// re-implementing file handling.
+///
+/// Comparison:
+/// - Go: Like a small `BlobStore` interface with `Put`/`Get`/`List`/`Delete`.
+/// - Python: Like a minimal key/value storage backend class.
+pub trait DocumentStore: Send + Sync {
+    /// Store (or overwrite) the raw document for `id`.
+    fn put(&self, id: Uuid, bytes: &[u8]) -> anyhow::Result<()>;
+
+    /// Load the raw document for `id`, if present.
+    fn get(&self, id: Uuid) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Load every stored document as `(id, bytes)` pairs, sorted by ID.
+    fn list(&self) -> anyhow::Result<Vec<(Uuid, Vec<u8>)>>;
+
+    /// Remove the document for `id`. Returns `false` if there was none.
+    fn delete(&self, id: Uuid) -> anyhow::Result<bool>;
+}
+
+/// Stores each document as `<storage_dir>/<id>.json`.
+pub struct FileDocumentStore {
+    storage_dir: PathBuf, // PathBuf is like Python's 'pathlib.Path' - it handles OS paths safely.
+}
+
+impl FileDocumentStore {
+    /// Creates a store pointing to the specified directory, creating it if needed.
+    pub fn new(path: &str) -> anyhow::Result<Self> {
+        let storage_dir = PathBuf::from(path);
+        if !storage_dir.exists() {
+            fs::create_dir_all(&storage_dir)?;
+        }
+        Ok(Self { storage_dir })
+    }
+
+    fn path_for(&self, id: Uuid) -> PathBuf {
+        self.storage_dir.join(format!("{}.json", id))
+    }
+}
+
+/// Writes `bytes` to a new file at `path` and waits until they are on disk.
+fn write_synced(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Syncs a directory, so the names created or renamed in it are on disk. Only Unix
+/// can open a directory as a file; elsewhere the rename is as durable as it gets.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    if cfg!(unix) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+impl DocumentStore for FileDocumentStore {
+    /// Writes a temporary file, syncs it, and renames it into place. A rename is atomic,
+    /// so readers see the old or the new document, never a truncated one (which a plain
+    /// write allows). Syncing the file before the rename, and the directory after it,
+    /// makes the new document survive a crash too, not just a concurrent reader.
+    fn put(&self, id: Uuid, bytes: &[u8]) -> anyhow::Result<()> {
+        let tmp_path = self.storage_dir.join(format!("{}.json.{}.tmp", id, Uuid::new_v4()));
+        let written = write_synced(&tmp_path, bytes).and_then(|()| fs::rename(&tmp_path, self.path_for(id)));
+        if let Err(e) = written {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e.into());
+        }
+        sync_dir(&self.storage_dir)?;
+        Ok(())
+    }
+
+    fn get(&self, id: Uuid) -> anyhow::Result<Option<Vec<u8>>> {
+        // Reading and handling "not found" in one step: checking first races with deletes.
+        match fs::read(self.path_for(id)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn list(&self) -> anyhow::Result<Vec<(Uuid, Vec<u8>)>> {
+        let mut documents = Vec::new();
+        // Read directory: Like os.listdir() in Python.
+        for entry in fs::read_dir(&self.storage_dir)? {
+            let path = entry?.path();
+
+            // Filter for `<uuid>.json` files, skipping anything else in the directory.
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let id = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| Uuid::parse_str(s).ok());
+            if let Some(id) = id {
+                match fs::read(&path) {
+                    Ok(bytes) => documents.push((id, bytes)),
+                    // Deleted since the directory was read: it is simply no longer listed.
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        // Directory order is whatever the filesystem likes; callers get a stable one.
+        documents.sort_by_key(|(id, _)| *id);
+        Ok(documents)
+    }
+
+    fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        match fs::remove_file(self.path_for(id)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
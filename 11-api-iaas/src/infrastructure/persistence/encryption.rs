@@ -0,0 +1,327 @@
+use super::document_store::DocumentStore;
+use super::name_index::NameIndex;
+use crate::domain::{Server, ServerRepository};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// KEY PROVIDER PORT
+///
+/// --- Good to know ---
+/// Real deployments fetch keys from a KMS (AWS KMS, Vault, ...). Hiding that behind a
+/// trait means the repository never cares where keys come from, and tests can use
+/// a fixed in-memory key ring.
+///
+/// Comparison:
+/// - Go: A `KeyProvider` interface you would back with the AWS SDK.
+/// - Python: An ABC wrapping `boto3.client("kms")` or a dict of keys.
+pub trait KeyProvider: Send + Sync {
+    /// The key new documents are encrypted with, as `(key_id, key)`.
+    fn active_key(&self) -> anyhow::Result<(String, [u8; 32])>;
+
+    /// Look up any known key (active or retired) so older documents can still be read.
+    fn key(&self, key_id: &str) -> anyhow::Result<[u8; 32]>;
+}
+
+/// A key ring loaded from configuration. The first key is the active one.
+pub struct StaticKeyProvider {
+    active: String,
+    keys: HashMap<String, [u8; 32]>,
+}
+
+impl StaticKeyProvider {
+    /// Key IDs must be unique: a repeated ID would silently replace the earlier key
+    /// and leave every document sealed under it unreadable.
+    pub fn new(keys: Vec<(String, [u8; 32])>) -> anyhow::Result<Self> {
+        let active = keys
+            .first()
+            .map(|(id, _)| id.clone())
+            .ok_or_else(|| anyhow!("at least one encryption key is required"))?;
+        let mut ring = HashMap::new();
+        for (id, key) in keys {
+            if ring.insert(id.clone(), key).is_some() {
+                bail!("encryption key id '{}' is listed more than once", id);
+            }
+        }
+        Ok(Self { active, keys: ring })
+    }
+
+    /// Parses `"<key_id>:<base64 32-byte key>,..."`, e.g. `"k2:...,k1:..."`.
+    /// Put the newest key first; keep old ones listed until `rotate_keys` has run.
+    pub fn from_spec(spec: &str) -> anyhow::Result<Self> {
+        let mut keys = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, encoded) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("encryption key entry must look like <id>:<base64>"))?;
+            let bytes = BASE64
+                .decode(encoded)
+                .with_context(|| format!("encryption key '{}' is not valid base64", id))?;
+            let key: [u8; 32] = bytes
+                .try_into()
+                .map_err(|_| anyhow!("encryption key '{}' must be exactly 32 bytes", id))?;
+            keys.push((id.to_string(), key));
+        }
+        Self::new(keys)
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn active_key(&self) -> anyhow::Result<(String, [u8; 32])> {
+        Ok((self.active.clone(), self.keys[&self.active]))
+    }
+
+    fn key(&self, key_id: &str) -> anyhow::Result<[u8; 32]> {
+        self.keys
+            .get(key_id)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown encryption key '{}'", key_id))
+    }
+}
+
+/// What actually lands on disk: the ciphertext plus what we need to decrypt it.
+#[derive(Serialize, Deserialize)]
+struct EncryptedEnvelope {
+    key_id: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// OUTBOUND ADAPTER: Encryption at rest
+///
+/// --- Good to know ---
+/// Serializes a server to JSON, seals it with AES-256-GCM and only then hands the
+/// bytes to the `DocumentStore`. Reads reverse the process, so the application
+/// layer never sees ciphertext.
+///
+/// The server ID is used as "associated data": a document copied over another
+/// server's file fails to decrypt instead of silently loading the wrong server.
+/// Plaintext documents are refused (they would skip both checks); `rotate_keys`
+/// is the one place that still reads them, to migrate data from before encryption.
+///
+/// One key ring covers every document. Per-tenant keys are out of scope until the
+/// domain has tenants: servers carry no tenant to pick a key by.
+pub struct EncryptedRepository {
+    store: Arc<dyn DocumentStore>,
+    keys: Arc<dyn KeyProvider>,
+    names: NameIndex,
+}
+
+impl EncryptedRepository {
+    pub fn new(store: Arc<dyn DocumentStore>, keys: Arc<dyn KeyProvider>) -> Self {
+        Self { store, keys, names: NameIndex::default() }
+    }
+
+    /// Re-encrypts every document that is not sealed with the active key. Legacy
+    /// plaintext documents are encrypted too when `migrate_plaintext` is set, and
+    /// fail the rotation otherwise. Returns how many documents were rewritten.
+    pub async fn rotate_keys(&self, migrate_plaintext: bool) -> anyhow::Result<usize> {
+        let (active_id, _) = self.keys.active_key()?;
+        // Open everything before writing anything: one refused document leaves the store as it was.
+        let mut stale = Vec::new();
+        for (id, bytes) in self.store.list()? {
+            let up_to_date = serde_json::from_slice::<EncryptedEnvelope>(&bytes)
+                .map(|envelope| envelope.key_id == active_id)
+                .unwrap_or(false);
+            if !up_to_date {
+                stale.push(match migrate_plaintext {
+                    true => self.open_or_migrate(id, &bytes)?,
+                    false => self.open(id, &bytes)?,
+                });
+            }
+        }
+        for server in &stale {
+            self.store.put(server.id, &self.seal(server)?)?;
+        }
+        Ok(stale.len())
+    }
+
+    fn seal(&self, server: &Server) -> anyhow::Result<Vec<u8>> {
+        let (key_id, key) = self.keys.active_key()?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        // A fresh random nonce per write: reusing one with the same key breaks GCM.
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let plaintext = serde_json::to_vec(server)?;
+        let ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: &plaintext, aad: server.id.as_bytes() })
+            .map_err(|_| anyhow!("failed to encrypt server {}", server.id))?;
+
+        let envelope = EncryptedEnvelope {
+            key_id,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        Ok(serde_json::to_vec_pretty(&envelope)?)
+    }
+
+    /// Like `open`, but also accepts a plaintext document written before encryption
+    /// was enabled. Only for migration: anyone who can write the data dir can forge one.
+    fn open_or_migrate(&self, id: Uuid, bytes: &[u8]) -> anyhow::Result<Server> {
+        match serde_json::from_slice::<EncryptedEnvelope>(bytes) {
+            Ok(_) => self.open(id, bytes),
+            Err(_) => {
+                let server: Server = serde_json::from_slice(bytes)?;
+                if server.id != id {
+                    bail!("plaintext document {} holds server {}", id, server.id);
+                }
+                Ok(server)
+            }
+        }
+    }
+
+    fn open(&self, id: Uuid, bytes: &[u8]) -> anyhow::Result<Server> {
+        let envelope: EncryptedEnvelope = serde_json::from_slice(bytes)
+            .map_err(|_| anyhow!("server document {} is not encrypted (run key rotation to migrate it)", id))?;
+
+        let key = self.keys.key(&envelope.key_id)?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let nonce = BASE64.decode(&envelope.nonce)?;
+        if nonce.len() != 12 {
+            bail!("corrupted nonce in server document {}", id);
+        }
+        let ciphertext = BASE64.decode(&envelope.ciphertext)?;
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: id.as_bytes() })
+            .map_err(|_| anyhow!("failed to decrypt server document {} (wrong key or tampered data)", id))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+#[async_trait]
+impl ServerRepository for EncryptedRepository {
+    async fn save(&self, server: &Server) -> anyhow::Result<()> {
+        let sealed = self.seal(server)?;
+        self.names.save(server, || self.store.put(server.id, &sealed)).await
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<Server>> {
+        self.store
+            .list()?
+            .into_iter()
+            .map(|(id, bytes)| self.open(id, &bytes))
+            .collect()
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Server>> {
+        match self.store.get(id)? {
+            Some(bytes) => Ok(Some(self.open(id, &bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        self.names.delete(id, || self.store.delete(id)).await
+    }
+
+    async fn find_by_name(&self, name: &str) -> anyhow::Result<Option<Server>> {
+        match self.names.find(name, || self.list_all()).await? {
+            Some(id) => Ok(self.find_by_id(id).await?.filter(|server| server.name == name)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::{FileDocumentStore, JsonServerRepository};
+    use tempfile::tempdir;
+
+    fn key_ring(spec: &[(&str, u8)]) -> Arc<dyn KeyProvider> {
+        let keys = spec.iter().map(|(id, byte)| (id.to_string(), [*byte; 32])).collect();
+        Arc::new(StaticKeyProvider::new(keys).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_documents_are_encrypted_on_disk() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let store = Arc::new(FileDocumentStore::new(dir.path().to_str().unwrap())?);
+        let repo = EncryptedRepository::new(store, key_ring(&[("k1", 7)]));
+
+        let server = Server::new("secret-vm".to_string(), 2, 4, 40);
+        repo.save(&server).await?;
+
+        let raw = std::fs::read_to_string(dir.path().join(format!("{}.json", server.id)))?;
+        assert!(!raw.contains("secret-vm"));
+        assert!(raw.contains("\"key_id\": \"k1\""));
+
+        let loaded = repo.find_by_id(server.id).await?.unwrap();
+        assert_eq!(loaded.name, "secret-vm");
+        assert_eq!(repo.list_all().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_key_rotation_reencrypts_old_and_plaintext_documents() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let store: Arc<dyn DocumentStore> =
+            Arc::new(FileDocumentStore::new(dir.path().to_str().unwrap())?);
+
+        // One legacy plaintext document and one sealed with the old key.
+        let legacy = Server::new("legacy".to_string(), 1, 1, 10);
+        JsonServerRepository::with_store(Arc::clone(&store)).save(&legacy).await?;
+        let old = Server::new("old".to_string(), 1, 1, 10);
+        EncryptedRepository::new(Arc::clone(&store), key_ring(&[("k1", 1)])).save(&old).await?;
+
+        // Rotate to k2 while k1 is still known.
+        let rotating = EncryptedRepository::new(Arc::clone(&store), key_ring(&[("k2", 2), ("k1", 1)]));
+        assert!(rotating.rotate_keys(false).await.is_err(), "plaintext needs an explicit migration");
+        assert_eq!(rotating.rotate_keys(true).await?, 2);
+        assert_eq!(rotating.rotate_keys(false).await?, 0);
+
+        // After rotation, k1 can be retired completely.
+        let k2_only = EncryptedRepository::new(store, key_ring(&[("k2", 2)]));
+        assert_eq!(k2_only.find_by_id(legacy.id).await?.unwrap().name, "legacy");
+        assert_eq!(k2_only.find_by_id(old.id).await?.unwrap().name, "old");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_documents_are_refused_outside_rotation() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let store: Arc<dyn DocumentStore> =
+            Arc::new(FileDocumentStore::new(dir.path().to_str().unwrap())?);
+        let forged = Server::new("forged".to_string(), 64, 512, 10);
+        JsonServerRepository::with_store(Arc::clone(&store)).save(&forged).await?;
+
+        let repo = EncryptedRepository::new(store, key_ring(&[("k1", 1)]));
+        let err = repo.find_by_id(forged.id).await.unwrap_err();
+        assert!(err.to_string().contains("not encrypted"));
+        assert!(repo.list_all().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wrong_key_fails_to_decrypt() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let store: Arc<dyn DocumentStore> =
+            Arc::new(FileDocumentStore::new(dir.path().to_str().unwrap())?);
+        let server = Server::new("vm".to_string(), 1, 1, 10);
+        EncryptedRepository::new(Arc::clone(&store), key_ring(&[("k1", 1)])).save(&server).await?;
+
+        let impostor = EncryptedRepository::new(store, key_ring(&[("k1", 9)]));
+        let err = impostor.find_by_id(server.id).await.unwrap_err();
+        assert!(err.to_string().contains("failed to decrypt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_spec_parsing() {
+        let key = BASE64.encode([3u8; 32]);
+        let provider = StaticKeyProvider::from_spec(&format!("new:{key}, old:{key}")).unwrap();
+        assert_eq!(provider.active_key().unwrap().0, "new");
+        assert!(provider.key("old").is_ok());
+
+        assert!(StaticKeyProvider::from_spec("").is_err());
+        assert!(StaticKeyProvider::from_spec("k1:c2hvcnQ=").is_err()); // "short"
+        let other = BASE64.encode([4u8; 32]);
+        let err = StaticKeyProvider::from_spec(&format!("k1:{key},k1:{other}")).err().unwrap();
+        assert!(err.to_string().contains("more than once"));
+    }
+}
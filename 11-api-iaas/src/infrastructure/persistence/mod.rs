@@ -1,29 +1,59 @@
+mod activity;
+mod api_keys;
+mod changes;
+mod chaos;
+mod console;
+/// Shared `ServerRepository` test suite; new adapters should run it too.
+#[cfg(test)]
+pub(crate) mod contract;
+mod document_store;
+mod encryption;
+mod name_index;
+mod notification_rules;
+mod resilience;
+mod users;
+mod volumes;
+
 use crate::domain::{Server, ServerRepository};
 use async_trait::async_trait;
-use std::path::PathBuf;
-use std::fs;
+use name_index::NameIndex;
+use std::sync::Arc;
 use uuid::Uuid;
 
+pub use activity::JsonActivityLog;
+pub use api_keys::JsonApiKeyRepository;
+pub use changes::{ChangeTrackingRepository, JsonChangeLog};
+pub use chaos::{FaultConfig, FaultInjector, FaultScope, FaultyRepository};
+pub use console::FileConsoleLog;
+pub use document_store::{DocumentStore, FileDocumentStore};
+pub use encryption::{EncryptedRepository, StaticKeyProvider};
+pub use notification_rules::JsonNotificationRuleRepository;
+pub use resilience::{CircuitBreaker, ResilientRepository, RetryPolicy};
+pub use users::JsonUserRepository;
+pub use volumes::JsonVolumeRepository;
+
 /// HEXAGONAL ARCHITECTURE: OUTBOUND ADAPTER
 /// --- Good to know ---
-/// This is the "Driven" side. It's a concrete implementation 
+/// This is the "Driven" side. It's a concrete implementation
 /// of our storage interface (ServerRepository).
-/// 
+///
 /// In Python, this might be your Django Database Backend or a JSON mock.
 /// In Go, this is your repository struct that talks to MySQL or Files.
 pub struct JsonServerRepository {
-    storage_dir: PathBuf, // PathBuf is like Python's 'pathlib.Path' - it handles OS paths safely.
+    store: Arc<dyn DocumentStore>, // Where the JSON bytes live (files by default).
+    names: NameIndex,              // Name -> ID, so name lookups don't parse every file.
 }
 
 /// 'impl' (Implementation) block for our repository struct.
 impl JsonServerRepository {
     /// Creates a new repository instance pointing to the specified directory.
     pub fn new(path: &str) -> anyhow::Result<Self> {
-        let storage_dir = PathBuf::from(path);
-        if !storage_dir.exists() {
-            fs::create_dir_all(&storage_dir)?;
-        }
-        Ok(Self { storage_dir })
+        Ok(Self::with_store(Arc::new(FileDocumentStore::new(path)?)))
+    }
+
+    /// Creates a repository on top of any raw document store.
+    pub fn with_store(store: Arc<dyn DocumentStore>) -> Self {
+        Self { store, names: NameIndex::default() }
     }
 }
 
@@ -32,46 +62,46 @@ impl JsonServerRepository {
 impl ServerRepository for JsonServerRepository {
     /// Serializes and saves the server state to a JSON file.
     async fn save(&self, server: &Server) -> anyhow::Result<()> {
-        let file_path = self.storage_dir.join(format!("{}.json", server.id));
-        
         // Serialize: Convert Rust Struct -> JSON String.
         // Like json.dumps(server) in Python or json.Marshal(server) in Go.
         let json = serde_json::to_string_pretty(server)?;
-        
-        fs::write(file_path, json)?;
-        Ok(())
+
+        self.names.save(server, || self.store.put(server.id, json.as_bytes())).await
     }
 
     /// Asynchronously loads and parses all JSON server files in the storage directory.
     async fn list_all(&self) -> anyhow::Result<Vec<Server>> {
         let mut servers = Vec::new();
-        // Read directory: Like os.listdir() in Python.
-        for entry in fs::read_dir(&self.storage_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            // Filter for .json files
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                let content = fs::read_to_string(path)?;
-                
-                // Deserialize: Convert JSON String -> Rust Struct.
-                // Like pydantic.parse_raw() in Python or json.Unmarshal in Go.
-                let server: Server = serde_json::from_str(&content)?;
-                servers.push(server);
-            }
+        for (_, content) in self.store.list()? {
+            // Deserialize: Convert JSON String -> Rust Struct.
+            // Like pydantic.parse_raw() in Python or json.Unmarshal in Go.
+            let server: Server = serde_json::from_slice(&content)?;
+            servers.push(server);
         }
         Ok(servers)
     }
 
     /// Asynchronously searches for a specific JSON file by server ID and deserializes it.
     async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Server>> {
-        let file_path = self.storage_dir.join(format!("{}.json", id));
-        if file_path.exists() {
-            let content = fs::read_to_string(file_path)?;
-            let server: Server = serde_json::from_str(&content)?;
-            Ok(Some(server)) // Found it!
-        } else {
-            Ok(None) // Not found - perfectly normal in Hexagonal to return an Option.
+        match self.store.get(id)? {
+            Some(content) => {
+                let server: Server = serde_json::from_slice(&content)?;
+                Ok(Some(server)) // Found it!
+            }
+            None => Ok(None), // Not found - perfectly normal in Hexagonal to return an Option.
+        }
+    }
+
+    /// Removes the server's JSON file.
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        self.names.delete(id, || self.store.delete(id)).await
+    }
+
+    /// Looks the name up in the index, then loads just that one file.
+    async fn find_by_name(&self, name: &str) -> anyhow::Result<Option<Server>> {
+        match self.names.find(name, || self.list_all()).await? {
+            Some(id) => Ok(self.find_by_id(id).await?.filter(|server| server.name == name)),
+            None => Ok(None),
         }
     }
 }
@@ -0,0 +1,84 @@
+use crate::domain::Server;
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// SECONDARY INDEX: Server name -> ID
+///
+/// --- Good to know ---
+/// A document store can only look documents up by ID. Repositories on top of one
+/// keep this index in memory so `find_by_name` doesn't read and parse every document.
+/// It is built from a full listing on first use, then updated by every save and delete.
+///
+/// Writes hold the index lock while they touch the store, so the index always
+/// applies them in the order they landed (and a listing never misses one).
+///
+/// Comparison:
+/// - Go: Like a `map[string]uuid.UUID` guarded by a `sync.Mutex` next to the store.
+/// - Python: Like a `dict` cache refreshed on every write through the repository.
+#[derive(Default)]
+pub(crate) struct NameIndex {
+    names: Mutex<Option<Names>>,
+}
+
+#[derive(Default)]
+struct Names {
+    ids: HashMap<String, Uuid>,
+    names: HashMap<Uuid, String>,
+}
+
+impl Names {
+    fn insert(&mut self, id: Uuid, name: String) {
+        self.remove(id);
+        self.ids.insert(name.clone(), id);
+        self.names.insert(id, name);
+    }
+
+    fn remove(&mut self, id: Uuid) {
+        if let Some(old) = self.names.remove(&id) {
+            if self.ids.get(&old) == Some(&id) {
+                self.ids.remove(&old);
+            }
+        }
+    }
+}
+
+impl NameIndex {
+    /// The ID filed under `name`. The first lookup builds the index from `list_all`.
+    pub async fn find<F, Fut>(&self, name: &str, list_all: F) -> anyhow::Result<Option<Uuid>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Vec<Server>>>,
+    {
+        let mut names = self.names.lock().await;
+        if names.is_none() {
+            let mut loaded = Names::default();
+            for server in list_all().await? {
+                loaded.insert(server.id, server.name);
+            }
+            *names = Some(loaded);
+        }
+        Ok(names.as_ref().and_then(|names| names.ids.get(name).copied()))
+    }
+
+    /// Runs `put` (the store write for `server`) and files the server's current name.
+    pub async fn save(&self, server: &Server, put: impl FnOnce() -> anyhow::Result<()>) -> anyhow::Result<()> {
+        let mut names = self.names.lock().await;
+        put()?;
+        if let Some(names) = names.as_mut() {
+            names.insert(server.id, server.name.clone());
+        }
+        Ok(())
+    }
+
+    /// Runs `delete` (the store delete for `id`) and forgets the server's name.
+    pub async fn delete(&self, id: Uuid, delete: impl FnOnce() -> anyhow::Result<bool>) -> anyhow::Result<bool> {
+        let mut names = self.names.lock().await;
+        let deleted = delete()?;
+        if let Some(names) = names.as_mut() {
+            names.remove(id);
+        }
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,40 @@
+use super::document_store::{DocumentStore, FileDocumentStore};
+use crate::domain::{NotificationRule, NotificationRuleRepository};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// OUTBOUND ADAPTER: Notification rules as JSON documents in `<storage_dir>/notification_rules/`.
+pub struct JsonNotificationRuleRepository {
+    store: Arc<dyn DocumentStore>,
+}
+
+impl JsonNotificationRuleRepository {
+    /// Creates the repository in the `notification_rules` sub-directory of `storage_dir`.
+    pub fn new(storage_dir: &str) -> anyhow::Result<Self> {
+        let rules_dir = Path::new(storage_dir).join("notification_rules");
+        let store = FileDocumentStore::new(&rules_dir.to_string_lossy())?;
+        Ok(Self { store: Arc::new(store) })
+    }
+}
+
+#[async_trait]
+impl NotificationRuleRepository for JsonNotificationRuleRepository {
+    async fn save(&self, rule: &NotificationRule) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(rule)?;
+        self.store.put(rule.id, json.as_bytes())
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<NotificationRule>> {
+        self.store
+            .list()?
+            .into_iter()
+            .map(|(_, content)| Ok(serde_json::from_slice(&content)?))
+            .collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        self.store.delete(id)
+    }
+}
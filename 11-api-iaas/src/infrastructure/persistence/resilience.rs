@@ -0,0 +1,359 @@
+use crate::application::{ComponentHealth, ReportHealth};
+use crate::domain::{Server, ServerRepository};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::future::Future;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How often, and how patiently, a failed storage call is retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts per call, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled on every further retry.
+    pub base_delay: Duration,
+    /// Upper bound for the (exponentially growing) delay.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff: base, 2*base, 4*base, ... capped at `max_delay`.
+    fn delay_for(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// The three classic circuit breaker states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitState {
+    /// Normal operation: calls go through.
+    Closed,
+    /// Too many consecutive failures: calls fail fast without touching storage.
+    Open,
+    /// Cool-down elapsed: the next call is a trial that decides Closed vs Open.
+    HalfOpen,
+}
+
+struct BreakerInner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// A half-open trial call is running; other calls fail fast until it is done.
+    trial_running: bool,
+}
+
+/// Permission for one call. For the half-open trial, dropping it (even when the
+/// call was cancelled) lets the next call try.
+struct Permit<'a> {
+    trial: Option<&'a CircuitBreaker>,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if let Some(breaker) = self.trial {
+            breaker.inner.lock().unwrap().trial_running = false;
+        }
+    }
+}
+
+/// CIRCUIT BREAKER
+///
+/// --- Good to know ---
+/// When a backend is down, hammering it with retries makes things worse.
+/// After `failure_threshold` consecutive failures the breaker "opens" and rejects
+/// calls immediately for `open_duration`, giving the backend room to recover.
+///
+/// Comparison:
+/// - Go: Like `sony/gobreaker`.
+/// - Python: Like `pybreaker`.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_duration,
+            inner: Mutex::new(BreakerInner { consecutive_failures: 0, opened_at: None, trial_running: false }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap();
+        match inner.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.open_duration => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Lets a call through unless the circuit is open. Half-open lets exactly one
+    /// trial through at a time: a recovering backend shouldn't get the full load at once.
+    fn permit(&self) -> Option<Permit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.opened_at {
+            None => Some(Permit { trial: None }),
+            Some(opened_at) if opened_at.elapsed() < self.open_duration => None,
+            Some(_) if inner.trial_running => None,
+            Some(_) => {
+                inner.trial_running = true;
+                Some(Permit { trial: Some(self) })
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+    }
+
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        // A failed half-open trial re-opens immediately, restarting the cool-down.
+        if inner.consecutive_failures >= self.failure_threshold || inner.opened_at.is_some() {
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
+impl ReportHealth for CircuitBreaker {
+    fn check(&self) -> ComponentHealth {
+        let state = self.state();
+        ComponentHealth {
+            component: "storage".to_string(),
+            healthy: state != CircuitState::Open,
+            details: format!("circuit {:?}", state).to_lowercase(),
+        }
+    }
+}
+
+/// OUTBOUND ADAPTER DECORATOR: Resilience
+///
+/// --- Good to know ---
+/// A "Decorator" implements the same trait as the thing it wraps and adds behaviour
+/// around it. The service still just sees a `ServerRepository`; it has no idea that
+/// calls are now retried with backoff and guarded by a circuit breaker.
+///
+/// Only I/O errors (disk hiccup, network blip) are retried and count against the
+/// breaker. Anything else, such as a corrupt or undecryptable document, fails the same
+/// way every time and says nothing about the backend's health, so it is returned as is.
+pub struct ResilientRepository {
+    inner: Arc<dyn ServerRepository>,
+    breaker: Arc<CircuitBreaker>,
+    retry: RetryPolicy,
+}
+
+impl ResilientRepository {
+    pub fn new(inner: Arc<dyn ServerRepository>, breaker: Arc<CircuitBreaker>, retry: RetryPolicy) -> Self {
+        Self { inner, breaker, retry }
+    }
+
+    /// Runs `op` with retries, as long as the circuit lets us through.
+    async fn call<T, F, Fut>(&self, op: F) -> anyhow::Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let Some(_permit) = self.breaker.permit() else {
+            return Err(anyhow!("storage circuit is open, failing fast"));
+        };
+
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Ok(value) => {
+                    self.breaker.record_success();
+                    return Ok(value);
+                }
+                // The backend answered; the request itself is what's wrong.
+                Err(e) if !is_transient(&e) => {
+                    self.breaker.record_success();
+                    return Err(e);
+                }
+                Err(e) if attempt + 1 >= self.retry.max_attempts => {
+                    self.breaker.record_failure();
+                    return Err(e.context(format!("storage call failed after {} attempts", attempt + 1)));
+                }
+                Err(e) => {
+                    eprintln!("Storage call failed (attempt {}): {:#}; retrying", attempt + 1, e);
+                    tokio::time::sleep(self.retry.delay_for(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Whether an error may go away by itself: an I/O error anywhere in its chain.
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.downcast_ref::<io::Error>().is_some_and(|e| e.kind() != io::ErrorKind::InvalidData))
+}
+
+#[async_trait]
+impl ServerRepository for ResilientRepository {
+    async fn save(&self, server: &Server) -> anyhow::Result<()> {
+        self.call(|| self.inner.save(server)).await
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<Server>> {
+        self.call(|| self.inner.list_all()).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Server>> {
+        self.call(|| self.inner.find_by_id(id)).await
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        self.call(|| self.inner.delete(id)).await
+    }
+
+    async fn find_by_name(&self, name: &str) -> anyhow::Result<Option<Server>> {
+        self.call(|| self.inner.find_by_name(name)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Test double: fails the first `failures` calls, then succeeds.
+    struct FlakyRepository {
+        failures: AtomicU32,
+        calls: AtomicU32,
+    }
+
+    impl FlakyRepository {
+        fn new(failures: u32) -> Arc<Self> {
+            Arc::new(Self { failures: AtomicU32::new(failures), calls: AtomicU32::new(0) })
+        }
+    }
+
+    #[async_trait]
+    impl ServerRepository for FlakyRepository {
+        async fn save(&self, _server: &Server) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn list_all(&self) -> anyhow::Result<Vec<Server>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let remaining = self.failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                return Err(io::Error::other("disk hiccup").into());
+            }
+            Ok(Vec::new())
+        }
+
+        /// Always a corrupt document: the same answer however often it is asked.
+        async fn find_by_id(&self, _id: Uuid) -> anyhow::Result<Option<Server>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(serde_json::from_str("{ not json")?))
+        }
+
+        async fn delete(&self, _id: Uuid) -> anyhow::Result<bool> {
+            Ok(false)
+        }
+    }
+
+    fn fast_retry(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy { max_attempts, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(5) }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let flaky = FlakyRepository::new(2);
+        let breaker = Arc::new(CircuitBreaker::default());
+        let repo = ResilientRepository::new(flaky.clone(), breaker.clone(), fast_retry(3));
+
+        assert!(repo.list_all().await.is_ok());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_then_recovers_through_half_open() {
+        let flaky = FlakyRepository::new(2);
+        let breaker = Arc::new(CircuitBreaker::new(2, Duration::from_millis(30)));
+        let repo = ResilientRepository::new(flaky.clone(), breaker.clone(), fast_retry(1));
+
+        assert!(repo.list_all().await.is_err());
+        assert!(repo.list_all().await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.check().healthy);
+
+        // While open, calls fail fast without reaching storage.
+        let err = repo.list_all().await.unwrap_err();
+        assert!(err.to_string().contains("circuit is open"));
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+
+        // After the cool-down, one successful trial closes the circuit again.
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(repo.list_all().await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_errors_are_neither_retried_nor_counted() {
+        let flaky = FlakyRepository::new(0);
+        let breaker = Arc::new(CircuitBreaker::new(2, Duration::from_secs(30)));
+        let repo = ResilientRepository::new(flaky.clone(), breaker.clone(), fast_retry(3));
+
+        for _ in 0..3 {
+            assert!(repo.find_by_id(Uuid::new_v4()).await.is_err());
+        }
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_allows_one_trial_at_a_time() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let trial = breaker.permit();
+        assert!(trial.is_some());
+        assert!(breaker.permit().is_none(), "a second call fails fast while the trial runs");
+        // A trial that ends without a verdict (e.g. cancelled) frees the slot.
+        drop(trial);
+        assert!(breaker.permit().is_some());
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(35),
+        };
+        assert_eq!(policy.delay_for(0), Duration::from_millis(10));
+        assert_eq!(policy.delay_for(1), Duration::from_millis(20));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(35));
+    }
+}
@@ -0,0 +1,44 @@
+use super::document_store::{DocumentStore, FileDocumentStore};
+use crate::domain::{User, UserRepository};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// OUTBOUND ADAPTER: Users as JSON documents in `<storage_dir>/users/`.
+/// Mirrors `JsonServerRepository`; only the document type differs.
+pub struct JsonUserRepository {
+    store: Arc<dyn DocumentStore>,
+}
+
+impl JsonUserRepository {
+    /// Creates the repository in the `users` sub-directory of `storage_dir`.
+    pub fn new(storage_dir: &str) -> anyhow::Result<Self> {
+        let users_dir = Path::new(storage_dir).join("users");
+        let store = FileDocumentStore::new(&users_dir.to_string_lossy())?;
+        Ok(Self { store: Arc::new(store) })
+    }
+}
+
+#[async_trait]
+impl UserRepository for JsonUserRepository {
+    async fn save(&self, user: &User) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(user)?;
+        self.store.put(user.id, json.as_bytes())
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<User>> {
+        self.store
+            .list()?
+            .into_iter()
+            .map(|(_, content)| Ok(serde_json::from_slice(&content)?))
+            .collect()
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<User>> {
+        match self.store.get(id)? {
+            Some(content) => Ok(Some(serde_json::from_slice(&content)?)),
+            None => Ok(None),
+        }
+    }
+}
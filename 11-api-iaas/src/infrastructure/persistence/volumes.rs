@@ -0,0 +1,48 @@
+use super::document_store::{DocumentStore, FileDocumentStore};
+use crate::domain::{Volume, VolumeRepository};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// OUTBOUND ADAPTER: Volumes as JSON documents in `<storage_dir>/volumes/`.
+/// Mirrors `JsonUserRepository`.
+pub struct JsonVolumeRepository {
+    store: Arc<dyn DocumentStore>,
+}
+
+impl JsonVolumeRepository {
+    /// Creates the repository in the `volumes` sub-directory of `storage_dir`.
+    pub fn new(storage_dir: &str) -> anyhow::Result<Self> {
+        let volumes_dir = Path::new(storage_dir).join("volumes");
+        let store = FileDocumentStore::new(&volumes_dir.to_string_lossy())?;
+        Ok(Self { store: Arc::new(store) })
+    }
+}
+
+#[async_trait]
+impl VolumeRepository for JsonVolumeRepository {
+    async fn save(&self, volume: &Volume) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(volume)?;
+        self.store.put(volume.id, json.as_bytes())
+    }
+
+    async fn list_all(&self) -> anyhow::Result<Vec<Volume>> {
+        self.store
+            .list()?
+            .into_iter()
+            .map(|(_, content)| Ok(serde_json::from_slice(&content)?))
+            .collect()
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Volume>> {
+        match self.store.get(id)? {
+            Some(content) => Ok(Some(serde_json::from_slice(&content)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
+        self.store.delete(id)
+    }
+}
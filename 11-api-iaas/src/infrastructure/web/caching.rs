@@ -0,0 +1,83 @@
+use serde::Serialize;
+use warp::http::{header, Response, StatusCode};
+use warp::hyper::Body;
+use warp::{Filter, Rejection};
+
+use super::errors::ApiError;
+
+/// HTTP CACHING: ETags and conditional GETs
+///
+/// --- Good to know ---
+/// An ETag is a fingerprint of a response body. Clients send it back in
+/// `If-None-Match`; if the body would be identical, we answer `304 Not Modified`
+/// with no body at all. The server still does the work, but nothing crosses the wire.
+/// `Cache-Control: private, no-cache` lets clients keep a copy as long as they revalidate.
+///
+/// Comparison:
+/// - Go: Like hashing the body and calling `http.ServeContent`-style `checkIfNoneMatch`.
+/// - Python: Like Django's `ConditionalGetMiddleware` / `@etag` decorator.
+pub fn cached_json<T: Serialize>(value: &T, if_none_match: Option<String>) -> Result<Response<Body>, Rejection> {
+    let body = serde_json::to_vec(value).map_err(|e| warp::reject::custom(ApiError::Internal(e.to_string())))?;
+    let etag = etag_of(&body);
+    let builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, "private, no-cache");
+
+    let response = if if_none_match.as_deref().is_some_and(|header| etag_matches(header, &etag)) {
+        builder.status(StatusCode::NOT_MODIFIED).body(Body::empty())
+    } else {
+        builder.header(header::CONTENT_TYPE, "application/json").body(Body::from(body))
+    };
+    response.map_err(|e| warp::reject::custom(ApiError::Internal(e.to_string())))
+}
+
+/// The ETag `cached_json` would give `value`.
+pub fn etag_for<T: Serialize>(value: &T) -> String {
+    etag_of(&serde_json::to_vec(value).unwrap_or_default())
+}
+
+fn etag_of(body: &[u8]) -> String {
+    format!("\"{:016x}\"", fnv1a(body))
+}
+
+/// `If-Match` holds for `*` or a listed tag. It compares strongly: a weak (`W/"..."`)
+/// tag never matches, since it doesn't promise the exact same representation.
+pub fn if_match_holds(header: &str, etag: &str) -> bool {
+    header.split(',').map(str::trim).any(|tag| tag == "*" || tag == etag)
+}
+
+/// Extracts the `If-Match` request header, if any.
+pub fn with_if_match() -> impl Filter<Extract = (Option<String>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("if-match")
+}
+
+/// Extracts the `If-None-Match` request header, if any.
+pub fn with_if_none_match() -> impl Filter<Extract = (Option<String>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("if-none-match")
+}
+
+/// `If-None-Match` may list several tags, weak (`W/"..."`) ones, or `*`.
+fn etag_matches(header: &str, etag: &str) -> bool {
+    header.split(',').map(str::trim).any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// FNV-1a: tiny, fast and stable across builds, which is all a fingerprint needs
+/// (unlike `DefaultHasher`, whose output may change between Rust releases).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matching() {
+        let etag = format!("\"{:016x}\"", fnv1a(b"[]"));
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(&format!("\"other\", W/{}", etag), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"other\"", &etag));
+        assert_ne!(fnv1a(b"[]"), fnv1a(b"[{}]"));
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 // --- Inbound DTOs (Request Bodies) ---
@@ -19,11 +20,186 @@ pub struct CreateServerRequest {
     pub cpu: u32,
     pub ram: u32,
     pub storage: u32,
+    /// Public SSH keys exposed to the guest through the metadata service.
+    #[serde(default)]
+    pub ssh_keys: Vec<String>,
+    /// Bootstrap script exposed to the guest through the metadata service.
+    #[serde(default)]
+    pub user_data: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    /// Availability zone, e.g. `zone-a`. Omit it to use the first zone with room.
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+/// Body of `PATCH /servers/{id}` (`application/merge-patch+json`), for documentation:
+/// the handler reads the raw JSON so it can tell a missing field from a `null` one.
+/// Omitted fields stay unchanged; `null` removes the description or a tag.
+#[derive(ToSchema)]
+#[allow(dead_code)] // Only ever read by the OpenAPI generator.
+pub struct ServerMergePatch {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Tags to add or change; a `null` value removes that tag.
+    pub tags: Option<BTreeMap<String, Option<String>>>,
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct CreateDiskRequest {
     pub size_gb: u32,
+    /// Storage tier; defaults to `standard`. `nvme` needs a flavor with 4+ CPU cores.
+    #[serde(default)]
+    pub disk_type: DiskTypeDto,
+    /// Provisioned IOPS within the tier's range; omit for the baseline for `size_gb`.
+    #[serde(default)]
+    pub iops: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskTypeDto {
+    #[default]
+    Standard,
+    Ssd,
+    Nvme,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateVolumeRequest {
+    /// Unique volume name.
+    pub name: String,
+    pub size_gb: u32,
+    #[serde(default)]
+    pub disk_type: DiskTypeDto,
+    /// Provisioned IOPS within the tier's range; omit for the baseline for `size_gb`.
+    #[serde(default)]
+    pub iops: Option<u32>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AttachVolumeRequest {
+    pub server_id: Uuid,
+}
+
+/// Body of `POST /apply`, as JSON or YAML. Unknown fields are errors, so a typo
+/// in a manifest can't silently drop a setting.
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestRequest {
+    /// The complete desired inventory: servers not listed here are deleted.
+    pub servers: Vec<ManifestServer>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestServer {
+    /// Identifies the server across applies.
+    pub name: String,
+    pub cpu: u32,
+    pub ram: u32,
+    pub storage: u32,
+    /// Additional disks in attachment order. New ones are attached in place;
+    /// removing or changing one replaces the server.
+    #[serde(default)]
+    pub disks: Vec<CreateDiskRequest>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateUserRequest {
+    pub username: String,
+    /// Plaintext over TLS only; it is hashed with Argon2 and never stored or echoed.
+    pub password: String,
+    pub role: RoleDto,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateApiKeyRequest {
+    /// What the key is for, e.g. `ci-pipeline`.
+    pub name: String,
+    pub scopes: Vec<ScopeDto>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateNotificationRuleRequest {
+    /// What the rule is for, e.g. `on-call`.
+    pub name: String,
+    /// Activity event types, e.g. `server_deleted` or `provisioning_failed`.
+    pub events: Vec<String>,
+    pub channel: NotificationChannelDto,
+}
+
+/// Where a rule's notifications go; `email` needs `IAAS_SMTP_URL` on the server.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationChannelDto {
+    Email { to: String },
+    /// `webhook_url` must be an `https://` Slack incoming-webhook URL.
+    Slack { webhook_url: String },
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct NotificationRuleResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub events: Vec<String>,
+    pub channel: NotificationChannelDto,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// Query string of `GET /activity`.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityQuery {
+    /// `next_cursor` from the previous page; omit for the newest events.
+    pub cursor: Option<String>,
+    /// Page size (default 50, max 200).
+    pub limit: Option<usize>,
+}
+
+/// Query string of `GET /servers/changes`.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ServerChangesQuery {
+    /// `next_cursor` from the previous sync, or a Unix timestamp in seconds.
+    /// Omit for the whole change history.
+    pub since: Option<String>,
+}
+
+/// Query string of `GET /servers/{id}/logs`.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConsoleLogQuery {
+    /// Number of most recent lines (default 200, max 10000).
+    pub tail: Option<usize>,
+    /// Keep the response open and stream new lines as they are written.
+    #[serde(default)]
+    pub follow: bool,
+}
+
+/// Query string of endpoints that can preview their effect.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DryRunQuery {
+    /// Validate and plan, but change nothing.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
 }
 
 // --- Outbound DTOs (Response Bodies) ---
@@ -37,10 +213,240 @@ pub struct ServerResponse {
     pub name: String,
     pub status: String,
     pub disks: Vec<DiskResponse>,
+    pub description: Option<String>,
+    pub tags: BTreeMap<String, String>,
+    /// Availability zone; `null` when the platform has no zones configured.
+    pub zone: Option<String>,
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct DiskResponse {
     pub id: Uuid,
     pub size_gb: u32,
+    pub disk_type: DiskTypeDto,
+    pub iops: u32,
+    pub throughput_mbps: u32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ServerChangesResponse {
+    pub created: Vec<Uuid>,
+    pub updated: Vec<Uuid>,
+    pub deleted: Vec<Uuid>,
+    /// Pass as `since` on the next sync.
+    pub next_cursor: String,
+    /// More changes are waiting; sync again right away.
+    pub has_more: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VolumeResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub size_gb: u32,
+    pub disk_type: DiskTypeDto,
+    pub iops: u32,
+    pub throughput_mbps: u32,
+    /// The server the volume is attached to; `null` when it is free.
+    pub attached_to: Option<Uuid>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MaintenanceResponse {
+    pub maintenance: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `ready` when accepting all traffic, `maintenance` while draining.
+    pub status: String,
+    pub maintenance: bool,
+}
+
+/// Mirrors what cloud metadata services (EC2 IMDS, GCE metadata server) tell a guest about itself.
+#[derive(Serialize, ToSchema)]
+pub struct InstanceMetadataResponse {
+    pub instance_id: Uuid,
+    pub hostname: String,
+    pub instance_type: String,
+    pub flavor: FlavorResponse,
+    pub local_ipv4: String,
+    pub status: String,
+    pub public_keys: Vec<String>,
+    pub user_data: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FlavorResponse {
+    pub cpu_cores: u32,
+    pub ram_gb: u32,
+    pub storage_gb: u32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct HealthResponse {
+    /// `ok` when every component is healthy, `degraded` otherwise.
+    pub status: String,
+    pub components: Vec<ComponentHealthResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ComponentHealthResponse {
+    pub component: String,
+    pub healthy: bool,
+    pub details: String,
+}
+
+/// Chaos settings for the fault-injection repository (`/admin/faults`).
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct FaultConfigDto {
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Probability between 0.0 and 1.0.
+    #[serde(default)]
+    pub error_rate: f64,
+    #[serde(default)]
+    pub scope: FaultScopeDto,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultScopeDto {
+    #[default]
+    All,
+    Reads,
+    Writes,
+}
+
+/// RFC 7807 "Problem Details" - the body of EVERY error response.
+///
+/// --- Good to know ---
+/// A standard error shape means clients write one error parser for the whole API.
+/// - Python: FastAPI users often add this via `fastapi-problem`.
+/// - Go: Similar to returning a typed `ErrorResponse` struct from every handler.
+#[derive(Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI reference identifying the problem kind, e.g. `/problems/not-found`.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Short, human-readable summary of the problem kind.
+    pub title: String,
+    /// The HTTP status code, repeated for convenience.
+    pub status: u16,
+    /// Explanation specific to this occurrence.
+    pub detail: String,
+    /// URI identifying this occurrence (`urn:uuid:<request_id>`).
+    pub instance: String,
+    /// Correlation ID, also sent as the `X-Request-Id` header and written to server logs.
+    pub request_id: Uuid,
+    /// Extension member for `409 Conflict`: the resource that caused the conflict.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflicting_id: Option<Uuid>,
+    /// Extension member for capacity errors: `insufficient_vcpu`, `insufficient_ram` or `insufficient_disk`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Extension member for capacity errors: the zone that is short.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
+/// Note: no password hash here - it never leaves the server.
+#[derive(Serialize, ToSchema)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub role: RoleDto,
+    pub disabled: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoleDto {
+    Admin,
+    Operator,
+    Viewer,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub enum ScopeDto {
+    #[serde(rename = "servers:read")]
+    ServersRead,
+    #[serde(rename = "servers:write")]
+    ServersWrite,
+    #[serde(rename = "disks:write")]
+    DisksWrite,
+    /// Instance metadata, including SSH keys and user-data.
+    #[serde(rename = "metadata:read")]
+    MetadataRead,
+    /// Grants everything, including user and key administration.
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+/// Note: no secret and no hash here - the key itself is only shown once, on creation.
+#[derive(Serialize, ToSchema)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<ScopeDto>,
+    pub revoked: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreatedApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    /// The scopes actually granted to the key.
+    pub scopes: Vec<ScopeDto>,
+    /// Send this as `x-api-key`. It is shown only this once; store it safely.
+    pub key: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApplyResponse {
+    pub dry_run: bool,
+    /// Every step needed to converge, in execution order. Empty when nothing differs.
+    pub plan: Vec<PlannedChangeResponse>,
+    /// The steps carried out; empty for a dry run.
+    pub applied: Vec<PlannedChangeResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PlannedChangeResponse {
+    pub action: PlanActionDto,
+    pub name: String,
+    /// The existing server, or for applied creates/replacements the new one.
+    pub server_id: Option<Uuid>,
+    /// Why, e.g. `flavor c2.r4 -> c4.r8` or `tags`.
+    pub details: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanActionDto {
+    Create,
+    Update,
+    /// Delete and re-create: sizing changed or disks would have to be removed.
+    Replace,
+    Delete,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ActivityEventResponse {
+    pub sequence: u64,
+    /// Unix timestamp (seconds).
+    pub occurred_at: u64,
+    /// The server or user the event is about.
+    pub resource_id: Uuid,
+    /// Machine-readable event type, e.g. `server_created` or `status_changed`.
+    pub event: String,
+    /// Human-readable summary for feeds and consoles.
+    pub message: String,
+}
+
+/// One page of the feed. Keep requesting with `next_cursor` until it is `null`.
+#[derive(Serialize, ToSchema)]
+pub struct ActivityPageResponse {
+    pub items: Vec<ActivityEventResponse>,
+    pub next_cursor: Option<String>,
 }
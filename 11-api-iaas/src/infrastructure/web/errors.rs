@@ -0,0 +1,80 @@
+use crate::application::ServiceError;
+use crate::domain::CapacityResource;
+use uuid::Uuid;
+use warp::Rejection;
+
+// ERROR TRANSLATION
+//
+// --- Good to know ---
+// Handlers receive `anyhow::Error` from the application core. This module turns
+// known business failures into typed rejections that `handle_rejection` can render
+// with the right HTTP status. Anything unknown becomes a 500 without details.
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// 404: the addressed resource does not exist.
+    /// (Custom because warp ranks its built-in "not found" below other route rejections.)
+    NotFound,
+    /// 400: the request is well-formed JSON but semantically invalid.
+    BadRequest(String),
+    /// 415: the body must be sent with the `expected` media type.
+    UnsupportedMediaType { expected: &'static str },
+    /// 409: the request clashes with an existing resource.
+    Conflict { message: String, conflicting_id: Uuid },
+    /// 409 (vCPU/RAM) or 507 Insufficient Storage (disk): no zone can host the request.
+    /// `reason` and `zone` describe the first shortage.
+    InsufficientCapacity { message: String, reason: &'static str, zone: String, storage: bool },
+    /// 412: the `If-Match` precondition no longer holds.
+    PreconditionFailed(String),
+    /// 429: too many failed attempts; `retry_after_secs` goes into `Retry-After`.
+    TooManyRequests { message: String, retry_after_secs: u64 },
+    /// 500: an unexpected failure; the details are only logged.
+    Internal(String),
+}
+
+impl warp::reject::Reject for ApiError {}
+
+/// Maps an application error to a warp `Rejection`.
+pub fn into_rejection(err: anyhow::Error) -> Rejection {
+    match err.downcast_ref::<ServiceError>() {
+        Some(ServiceError::NotFound { .. }) => warp::reject::custom(ApiError::NotFound),
+        Some(invalid @ ServiceError::Invalid(_)) => {
+            warp::reject::custom(ApiError::BadRequest(invalid.to_string()))
+        }
+        Some(conflict @ ServiceError::NameConflict { existing_id, .. }) => {
+            warp::reject::custom(ApiError::Conflict {
+                message: conflict.to_string(),
+                conflicting_id: *existing_id,
+            })
+        }
+        Some(ServiceError::Conflict { reason, conflicting_id }) => {
+            warp::reject::custom(ApiError::Conflict {
+                message: reason.clone(),
+                conflicting_id: *conflicting_id,
+            })
+        }
+        Some(error @ ServiceError::InsufficientCapacity { shortages }) => {
+            let Some(first) = shortages.first() else {
+                return warp::reject::custom(ApiError::Internal(error.to_string()));
+            };
+            warp::reject::custom(ApiError::InsufficientCapacity {
+                message: error.to_string(),
+                reason: first.resource.reason(),
+                zone: first.zone.clone(),
+                // Only "out of disk" everywhere is a storage problem; a zone short on
+                // cores is a placement conflict that another zone or size may resolve.
+                storage: shortages.iter().all(|s| s.resource == CapacityResource::Disk),
+            })
+        }
+        Some(error @ ServiceError::PreconditionFailed { .. }) => {
+            warp::reject::custom(ApiError::PreconditionFailed(error.to_string()))
+        }
+        Some(error @ ServiceError::TooManyAttempts { retry_after }) => {
+            warp::reject::custom(ApiError::TooManyRequests {
+                message: error.to_string(),
+                retry_after_secs: retry_after.as_secs() + 1,
+            })
+        }
+        None => warp::reject::custom(ApiError::Internal(format!("{:#}", err))),
+    }
+}
@@ -1,16 +1,46 @@
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use warp::http::{header, Response, StatusCode};
+use warp::hyper::Body;
 use warp::{Rejection, Reply};
-use crate::application::{ManageServers, CreateServerCommand, AttachDiskCommand};
-use super::dto::{CreateServerRequest, CreateDiskRequest, ServerResponse};
-use super::mappings::map_to_response;
+use crate::application::{
+    ApplyManifests, AttachDiskCommand, CreateApiKeyCommand, CreateNotificationRuleCommand, CreateServerCommand, CreateUserCommand, CreateVolumeCommand, ManageServers,
+    ManageApiKeys, ManageNotifications, ManageUsers, ManageVolumes, ReadConsole, ReportHealth, SyncServers, ViewActivity, Precondition,
+};
+use crate::domain::Server;
+use crate::infrastructure::persistence::FaultInjector;
+use super::dto::{
+    ActivityPageResponse, ActivityQuery, ApiKeyResponse, ApplyResponse, AttachVolumeRequest, DryRunQuery,
+    ManifestRequest, CreateApiKeyRequest, CreateNotificationRuleRequest, NotificationRuleResponse,
+    CreatedApiKeyResponse, ComponentHealthResponse, ConsoleLogQuery,
+    CreateServerRequest, CreateDiskRequest, CreateUserRequest, CreateVolumeRequest, FaultConfigDto,
+    HealthResponse, InstanceMetadataResponse, LoginRequest, MaintenanceRequest, MaintenanceResponse,
+    ProblemDetails, ReadinessResponse, ServerChangesQuery, ServerChangesResponse, ServerMergePatch,
+    ServerResponse, UserResponse, VolumeResponse,
+};
+use super::caching::{cached_json, etag_for, if_match_holds};
+use super::errors::{into_rejection, ApiError};
+use super::maintenance::MaintenanceMode;
+use super::manifest::parse_manifest;
+use super::merge_patch::parse_server_patch;
+use super::security::SecurityError;
+use super::mappings::{
+    map_from_disk_type_dto, map_from_fault_dto, map_from_role_dto, map_to_activity_page, map_to_fault_dto, map_to_metadata, map_to_response,
+    map_from_manifest, map_from_notification_channel_dto, map_from_scope_dto, map_to_notification_rule_response, map_to_api_key_response, map_to_apply_response, map_to_created_api_key, map_to_user_response,
+    map_to_volume_response,
+};
 
 #[utoipa::path(
     post,
     path = "/servers",
+    params(DryRunQuery),
     request_body = CreateServerRequest,
     responses(
-        (status = 200, description = "Server created successfully", body = ServerResponse),
-        (status = 400, description = "Invalid request")
+        (status = 200, description = "Server created successfully (or, for a dry run, as it would be)", body = ServerResponse),
+        (status = 400, description = "Invalid request", body = ProblemDetails),
+        (status = 409, description = "Server name already in use (the body carries `conflicting_id`), or not enough vCPU/RAM left (`reason`, `zone`)", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails),
+        (status = 507, description = "Not enough disk capacity left; the body carries `reason` and `zone`", body = ProblemDetails)
     )
 )]
 /// WEB HANDLER: Create Server
@@ -23,6 +53,7 @@ use super::mappings::map_to_response;
 /// - Go: Like a Gin/Echo handler function.
 /// - Python: Like a FastAPI "Path Operation" function.
 pub async fn handle_create_server(
+    query: DryRunQuery,
     req: CreateServerRequest,
     port: Arc<dyn ManageServers>,
 ) -> Result<impl Reply, Rejection> {
@@ -32,31 +63,195 @@ pub async fn handle_create_server(
         cpu: req.cpu,
         ram: req.ram,
         storage: req.storage,
+        ssh_keys: req.ssh_keys,
+        user_data: req.user_data,
+        description: req.description,
+        tags: req.tags,
+        zone: req.zone,
+        dry_run: query.dry_run,
     };
     
     // 2. Call the Inbound Port (Abstract Service).
     match port.create_server(cmd).await {
         // 3. Translate the Domain Result back into a Web Response (JSON).
         Ok(server) => Ok(warp::reply::json(&map_to_response(server))),
-        Err(_) => Err(warp::reject::reject()),
+        Err(e) => Err(into_rejection(e)),
     }
 }
 
 #[utoipa::path(
     get,
     path = "/servers",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response")
+    ),
     responses(
-        (status = 200, description = "List all servers", body = [ServerResponse])
+        (status = 200, description = "List all servers (with an `ETag`)", body = [ServerResponse]),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag")
     )
 )]
 /// WEB HANDLER: List Servers
-pub async fn handle_list_servers(port: Arc<dyn ManageServers>) -> Result<impl Reply, Rejection> {
+pub async fn handle_list_servers(
+    if_none_match: Option<String>,
+    port: Arc<dyn ManageServers>,
+) -> Result<impl Reply, Rejection> {
     match port.list_servers().await {
         Ok(servers) => {
             let resp: Vec<ServerResponse> = servers.into_iter().map(map_to_response).collect();
-            Ok(warp::reply::json(&resp))
+            cached_json(&resp, if_none_match)
         },
-        Err(_) => Err(warp::reject::reject()),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/servers/changes",
+    params(ServerChangesQuery),
+    responses(
+        (status = 200, description = "Server IDs created, updated or deleted since the cursor", body = ServerChangesResponse),
+        (status = 400, description = "Invalid cursor", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Server Changes (delta sync)
+pub async fn handle_server_changes(
+    query: ServerChangesQuery,
+    sync: Arc<dyn SyncServers>,
+) -> Result<impl Reply, Rejection> {
+    match sync.changes(query.since.as_deref()).await {
+        Ok(changes) => {
+            let resp = ServerChangesResponse {
+                created: changes.created,
+                updated: changes.updated,
+                deleted: changes.deleted,
+                next_cursor: changes.next_cursor,
+                has_more: changes.has_more,
+            };
+            // A delta is only meaningful for the cursor it was asked with: never cache it.
+            Ok(warp::reply::with_header(warp::reply::json(&resp), "cache-control", "no-store"))
+        }
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/servers/by-name/{name}",
+    params(
+        ("name" = String, Path, description = "Unique server name"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous response")
+    ),
+    responses(
+        (status = 200, description = "Server found (with an `ETag`)", body = ServerResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Server not found", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Find Server by Name
+pub async fn handle_get_server_by_name(
+    name: String,
+    if_none_match: Option<String>,
+    port: Arc<dyn ManageServers>,
+) -> Result<impl Reply, Rejection> {
+    match port.find_server_by_name(&name).await {
+        Ok(Some(server)) => cached_json(&map_to_response(server), if_none_match),
+        Ok(None) => Err(warp::reject::custom(ApiError::NotFound)),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    patch,
+    path = "/servers/{id}",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Server UUID"),
+        DryRunQuery,
+        ("If-Match" = Option<String>, Header, description = "Only apply the patch if the server still has this ETag")
+    ),
+    request_body(content = ServerMergePatch, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "Server updated; `ETag` is its new version", body = ServerResponse),
+        (status = 400, description = "Invalid patch or immutable field", body = ProblemDetails),
+        (status = 404, description = "Server not found", body = ProblemDetails),
+        (status = 409, description = "New name already in use", body = ProblemDetails),
+        (status = 412, description = "The server changed since the `If-Match` ETag was read", body = ProblemDetails),
+        (status = 415, description = "Content-Type is not application/merge-patch+json", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Patch Server (JSON Merge Patch)
+pub async fn handle_patch_server(
+    server_id: uuid::Uuid,
+    query: DryRunQuery,
+    if_match: Option<String>,
+    body: warp::hyper::body::Bytes,
+    port: Arc<dyn ManageServers>,
+) -> Result<impl Reply, Rejection> {
+    let bad_request = |message: String| warp::reject::custom(ApiError::BadRequest(message));
+    let patch: serde_json::Value =
+        serde_json::from_slice(&body).map_err(|e| bad_request(format!("invalid JSON: {}", e)))?;
+    let mut cmd = parse_server_patch(server_id, &patch).map_err(bad_request)?;
+    cmd.dry_run = query.dry_run;
+    // Checked by the service against the stored server, under the same lock as the update.
+    cmd.precondition = if_match.map(|header| -> Precondition {
+        Box::new(move |server: &Server| if_match_holds(&header, &etag_for(&map_to_response(server.clone()))))
+    });
+
+    match port.update_server(cmd).await {
+        Ok(server) => cached_json(&map_to_response(server), None),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/servers/{id}",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Server UUID"),
+        DryRunQuery
+    ),
+    responses(
+        (status = 204, description = "Server deleted (or, for a dry run, could be)"),
+        (status = 404, description = "Server not found", body = ProblemDetails),
+        (status = 409, description = "Volumes are still attached; `conflicting_id` is one of them", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Delete Server
+pub async fn handle_delete_server(
+    server_id: uuid::Uuid,
+    query: DryRunQuery,
+    port: Arc<dyn ManageServers>,
+) -> Result<impl Reply, Rejection> {
+    match port.delete_server(server_id, query.dry_run).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/apply",
+    params(DryRunQuery),
+    request_body(content = ManifestRequest, content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "The plan and, unless `dry_run`, the changes applied", body = ApplyResponse),
+        (status = 400, description = "Invalid manifest; nothing was changed", body = ProblemDetails),
+        (status = 409, description = "A step conflicted (e.g. volumes attached to a server to delete); earlier steps stay applied", body = ProblemDetails),
+        (status = 415, description = "Content-Type is neither JSON nor YAML", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Apply Manifest (desired state)
+pub async fn handle_apply(
+    query: DryRunQuery,
+    content_type: Option<String>,
+    body: warp::hyper::body::Bytes,
+    port: Arc<dyn ApplyManifests>,
+) -> Result<impl Reply, Rejection> {
+    let manifest = parse_manifest(content_type.as_deref(), &body).map_err(warp::reject::custom)?;
+    match port.apply(map_from_manifest(manifest), query.dry_run).await {
+        Ok(report) => Ok(warp::reply::json(&map_to_apply_response(report, query.dry_run))),
+        Err(e) => Err(into_rejection(e)),
     }
 }
 
@@ -65,26 +260,636 @@ pub async fn handle_list_servers(port: Arc<dyn ManageServers>) -> Result<impl Re
     path = "/servers/{id}/disks",
     request_body = CreateDiskRequest,
     params(
-        ("id" = uuid::Uuid, Path, description = "Server UUID")
+        ("id" = uuid::Uuid, Path, description = "Server UUID"),
+        DryRunQuery
     ),
     responses(
         (status = 200, description = "Disk attached successfully", body = ServerResponse),
-        (status = 404, description = "Server not found")
+        (status = 400, description = "IOPS out of range, or disk type not supported by the flavor", body = ProblemDetails),
+        (status = 404, description = "Server not found", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails),
+        (status = 507, description = "The server's zone has no disk capacity left", body = ProblemDetails)
     )
 )]
 /// WEB HANDLER: Attach Disk
 pub async fn handle_attach_disk(
     server_id: uuid::Uuid,
+    query: DryRunQuery,
     req: CreateDiskRequest,
     port: Arc<dyn ManageServers>,
 ) -> Result<impl Reply, Rejection> {
     let cmd = AttachDiskCommand {
         server_id,
         size_gb: req.size_gb,
+        disk_type: map_from_disk_type_dto(req.disk_type),
+        iops: req.iops,
+        dry_run: query.dry_run,
     };
     
     match port.attach_disk(cmd).await {
         Ok(server) => Ok(warp::reply::json(&map_to_response(server))),
-        Err(_) => Err(warp::reject::reject()),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+/// Lines returned by `GET /servers/{id}/logs` without `?tail=`.
+const DEFAULT_TAIL_LINES: usize = 200;
+
+#[utoipa::path(
+    get,
+    path = "/servers/{id}/logs",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Server UUID"),
+        ConsoleLogQuery
+    ),
+    responses(
+        (status = 200, description = "Console output as plain text, one line per line; chunked and open-ended with `follow=true`", body = String, content_type = "text/plain"),
+        (status = 404, description = "Server not found", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Console Logs
+///
+/// --- Good to know ---
+/// With `follow=true` the response never "finishes": it is sent with chunked transfer
+/// encoding and each new console line is flushed as its own chunk, like `tail -f`
+/// (or `kubectl logs -f`). It ends when the client disconnects.
+pub async fn handle_console_logs(
+    server_id: uuid::Uuid,
+    query: ConsoleLogQuery,
+    console: Arc<dyn ReadConsole>,
+) -> Result<impl Reply, Rejection> {
+    let lines = query.tail.unwrap_or(DEFAULT_TAIL_LINES);
+    let body = if query.follow {
+        let (backlog, mut feed) = console.follow(server_id, lines).await.map_err(into_rejection)?;
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            let backlog: String = backlog.into_iter().map(|line| line + "\n").collect();
+            if sender.send_data(backlog.into()).await.is_err() {
+                return;
+            }
+            loop {
+                let chunk = match feed.recv().await {
+                    Ok(line) => line + "\n",
+                    Err(RecvError::Lagged(skipped)) => format!("[... {} lines skipped ...]\n", skipped),
+                    Err(RecvError::Closed) => return,
+                };
+                // Fails once the client has gone away.
+                if sender.send_data(chunk.into()).await.is_err() {
+                    return;
+                }
+            }
+        });
+        body
+    } else {
+        let lines = console.tail(server_id, lines).await.map_err(into_rejection)?;
+        Body::from(lines.into_iter().map(|line| line + "\n").collect::<String>())
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(body)
+        .map_err(|e| warp::reject::custom(ApiError::Internal(e.to_string())))
+}
+
+#[utoipa::path(
+    post,
+    path = "/volumes",
+    params(DryRunQuery),
+    request_body = CreateVolumeRequest,
+    responses(
+        (status = 200, description = "Volume created (detached)", body = VolumeResponse),
+        (status = 400, description = "Empty name or IOPS out of range", body = ProblemDetails),
+        (status = 409, description = "Volume name already in use; the body carries `conflicting_id`", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Create Volume
+pub async fn handle_create_volume(
+    query: DryRunQuery,
+    req: CreateVolumeRequest,
+    volumes: Arc<dyn ManageVolumes>,
+) -> Result<impl Reply, Rejection> {
+    let cmd = CreateVolumeCommand {
+        name: req.name,
+        size_gb: req.size_gb,
+        disk_type: map_from_disk_type_dto(req.disk_type),
+        iops: req.iops,
+        dry_run: query.dry_run,
+    };
+    match volumes.create_volume(cmd).await {
+        Ok(volume) => Ok(warp::reply::json(&map_to_volume_response(volume))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/volumes",
+    responses(
+        (status = 200, description = "List all volumes", body = [VolumeResponse])
+    )
+)]
+/// WEB HANDLER: List Volumes
+pub async fn handle_list_volumes(volumes: Arc<dyn ManageVolumes>) -> Result<impl Reply, Rejection> {
+    match volumes.list_volumes().await {
+        Ok(list) => {
+            let resp: Vec<VolumeResponse> = list.into_iter().map(map_to_volume_response).collect();
+            Ok(warp::reply::json(&resp))
+        }
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/volumes/{id}",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Volume UUID")
+    ),
+    responses(
+        (status = 200, description = "Volume found", body = VolumeResponse),
+        (status = 404, description = "Volume not found", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Get Volume
+pub async fn handle_get_volume(
+    volume_id: uuid::Uuid,
+    volumes: Arc<dyn ManageVolumes>,
+) -> Result<impl Reply, Rejection> {
+    match volumes.get_volume(volume_id).await {
+        Ok(volume) => Ok(warp::reply::json(&map_to_volume_response(volume))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/volumes/{id}",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Volume UUID"),
+        DryRunQuery
+    ),
+    responses(
+        (status = 204, description = "Volume deleted (or, for a dry run, could be)"),
+        (status = 404, description = "Volume not found", body = ProblemDetails),
+        (status = 409, description = "Volume is attached; `conflicting_id` is the server", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Delete Volume
+pub async fn handle_delete_volume(
+    volume_id: uuid::Uuid,
+    query: DryRunQuery,
+    volumes: Arc<dyn ManageVolumes>,
+) -> Result<impl Reply, Rejection> {
+    match volumes.delete_volume(volume_id, query.dry_run).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/volumes/{id}/attach",
+    request_body = AttachVolumeRequest,
+    params(
+        ("id" = uuid::Uuid, Path, description = "Volume UUID"),
+        DryRunQuery
+    ),
+    responses(
+        (status = 200, description = "Volume attached (or already attached to this server)", body = VolumeResponse),
+        (status = 400, description = "Disk type not supported by the server's flavor", body = ProblemDetails),
+        (status = 404, description = "Volume or server not found", body = ProblemDetails),
+        (status = 409, description = "Attached to another server; `conflicting_id` is that server", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Attach Volume
+pub async fn handle_attach_volume(
+    volume_id: uuid::Uuid,
+    query: DryRunQuery,
+    req: AttachVolumeRequest,
+    volumes: Arc<dyn ManageVolumes>,
+) -> Result<impl Reply, Rejection> {
+    match volumes.attach_volume(volume_id, req.server_id, query.dry_run).await {
+        Ok(volume) => Ok(warp::reply::json(&map_to_volume_response(volume))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/volumes/{id}/detach",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Volume UUID"),
+        DryRunQuery
+    ),
+    responses(
+        (status = 200, description = "Volume detached (or already detached)", body = VolumeResponse),
+        (status = 404, description = "Volume not found", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Detach Volume
+pub async fn handle_detach_volume(
+    volume_id: uuid::Uuid,
+    query: DryRunQuery,
+    volumes: Arc<dyn ManageVolumes>,
+) -> Result<impl Reply, Rejection> {
+    match volumes.detach_volume(volume_id, query.dry_run).await {
+        Ok(volume) => Ok(warp::reply::json(&map_to_volume_response(volume))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/metadata/{id}",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Server UUID")
+    ),
+    responses(
+        (status = 200, description = "Instance metadata", body = InstanceMetadataResponse),
+        (status = 404, description = "Server not found", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Instance Metadata
+///
+/// --- Good to know ---
+/// Real clouds expose this at a link-local address (`169.254.169.254`) so tools
+/// like cloud-init can discover their identity, SSH keys and user-data at boot.
+pub async fn handle_get_metadata(
+    server_id: uuid::Uuid,
+    port: Arc<dyn ManageServers>,
+) -> Result<impl Reply, Rejection> {
+    match port.get_server(server_id).await {
+        Ok(server) => Ok(warp::reply::json(&map_to_metadata(server))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/maintenance",
+    request_body = MaintenanceRequest,
+    responses(
+        (status = 200, description = "Maintenance mode updated", body = MaintenanceResponse),
+        (status = 401, description = "Invalid or missing credentials", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Toggle Maintenance Mode
+///
+/// While enabled, mutating endpoints answer `503` with `Retry-After`; reads keep working.
+pub async fn handle_set_maintenance(
+    req: MaintenanceRequest,
+    mode: Arc<MaintenanceMode>,
+) -> Result<impl Reply, Rejection> {
+    mode.set_enabled(req.enabled);
+    println!("Maintenance mode {}.", if req.enabled { "enabled" } else { "disabled" });
+    Ok(warp::reply::json(&MaintenanceResponse { maintenance: req.enabled }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = 200, description = "Ready to accept all traffic", body = ReadinessResponse),
+        (status = 503, description = "Draining for maintenance", body = ReadinessResponse)
+    )
+)]
+/// WEB HANDLER: Readiness Probe
+///
+/// --- Good to know ---
+/// Load balancers and Kubernetes poll this to decide whether to route traffic here.
+/// Answering `503` during maintenance takes the instance out of rotation.
+pub async fn handle_readyz(mode: Arc<MaintenanceMode>) -> Result<impl Reply, Rejection> {
+    let maintenance = mode.is_enabled();
+    let (status, code) = if maintenance {
+        ("maintenance", StatusCode::SERVICE_UNAVAILABLE)
+    } else {
+        ("ready", StatusCode::OK)
+    };
+    let body = ReadinessResponse { status: status.to_string(), maintenance };
+    Ok(warp::reply::with_status(warp::reply::json(&body), code))
+}
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    responses(
+        (status = 200, description = "All components healthy", body = HealthResponse),
+        (status = 503, description = "At least one component is degraded (e.g. storage circuit open)", body = HealthResponse)
+    )
+)]
+/// WEB HANDLER: Health Check
+///
+/// Reports each infrastructure component, such as the storage circuit breaker state.
+pub async fn handle_healthz(checks: Arc<Vec<Arc<dyn ReportHealth>>>) -> Result<impl Reply, Rejection> {
+    let components: Vec<ComponentHealthResponse> = checks
+        .iter()
+        .map(|check| {
+            let health = check.check();
+            ComponentHealthResponse {
+                component: health.component,
+                healthy: health.healthy,
+                details: health.details,
+            }
+        })
+        .collect();
+
+    let healthy = components.iter().all(|c| c.healthy);
+    let (status, code) = if healthy {
+        ("ok", StatusCode::OK)
+    } else {
+        ("degraded", StatusCode::SERVICE_UNAVAILABLE)
+    };
+    let body = HealthResponse { status: status.to_string(), components };
+    Ok(warp::reply::with_status(warp::reply::json(&body), code))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/faults",
+    responses(
+        (status = 200, description = "Current fault-injection settings", body = FaultConfigDto),
+        (status = 404, description = "Chaos mode is not enabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Get Fault Injection Settings
+pub async fn handle_get_faults(faults: Arc<FaultInjector>) -> Result<impl Reply, Rejection> {
+    Ok(warp::reply::json(&map_to_fault_dto(faults.config())))
+}
+
+#[utoipa::path(
+    put,
+    path = "/admin/faults",
+    request_body = FaultConfigDto,
+    responses(
+        (status = 200, description = "Fault-injection settings replaced", body = FaultConfigDto),
+        (status = 400, description = "error_rate outside 0.0 - 1.0", body = ProblemDetails),
+        (status = 404, description = "Chaos mode is not enabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Replace Fault Injection Settings
+///
+/// Takes effect on the very next storage call; send `{}` to stop injecting faults.
+pub async fn handle_set_faults(
+    req: FaultConfigDto,
+    faults: Arc<FaultInjector>,
+) -> Result<impl Reply, Rejection> {
+    if !(0.0..=1.0).contains(&req.error_rate) {
+        return Err(warp::reject::custom(ApiError::BadRequest(
+            "error_rate must be between 0.0 and 1.0".to_string(),
+        )));
+    }
+    let config = map_from_fault_dto(req);
+    println!("Fault injection updated: {:?}", config);
+    faults.set_config(config.clone());
+    Ok(warp::reply::json(&map_to_fault_dto(config)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 200, description = "User created", body = UserResponse),
+        (status = 400, description = "Empty username or password too short", body = ProblemDetails),
+        (status = 409, description = "Username already taken", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Create User
+pub async fn handle_create_user(
+    req: CreateUserRequest,
+    users: Arc<dyn ManageUsers>,
+) -> Result<impl Reply, Rejection> {
+    let cmd = CreateUserCommand {
+        username: req.username,
+        password: req.password,
+        role: map_from_role_dto(req.role),
+    };
+    match users.create_user(cmd).await {
+        Ok(user) => Ok(warp::reply::json(&map_to_user_response(user))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/users",
+    responses(
+        (status = 200, description = "List all users", body = [UserResponse])
+    )
+)]
+/// WEB HANDLER: List Users
+pub async fn handle_list_users(users: Arc<dyn ManageUsers>) -> Result<impl Reply, Rejection> {
+    match users.list_users().await {
+        Ok(list) => {
+            let resp: Vec<UserResponse> = list.into_iter().map(map_to_user_response).collect();
+            Ok(warp::reply::json(&resp))
+        }
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/{id}/{action}",
+    params(
+        ("id" = uuid::Uuid, Path, description = "User UUID"),
+        ("action" = String, Path, description = "`disable` or `enable`")
+    ),
+    responses(
+        (status = 200, description = "User updated", body = UserResponse),
+        (status = 404, description = "User not found", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Disable / Enable User
+pub async fn handle_set_user_disabled(
+    user_id: uuid::Uuid,
+    disabled: bool,
+    users: Arc<dyn ManageUsers>,
+) -> Result<impl Reply, Rejection> {
+    match users.set_user_disabled(user_id, disabled).await {
+        Ok(user) => Ok(warp::reply::json(&map_to_user_response(user))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "Key created; `key` is shown only in this response", body = CreatedApiKeyResponse),
+        (status = 400, description = "Empty name or no scopes", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Create API Key
+pub async fn handle_create_api_key(
+    req: CreateApiKeyRequest,
+    keys: Arc<dyn ManageApiKeys>,
+) -> Result<impl Reply, Rejection> {
+    let cmd = CreateApiKeyCommand {
+        name: req.name,
+        scopes: req.scopes.into_iter().map(map_from_scope_dto).collect(),
+    };
+    match keys.create_api_key(cmd).await {
+        Ok(issued) => Ok(warp::reply::json(&map_to_created_api_key(issued))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    responses(
+        (status = 200, description = "List all API keys (without secrets)", body = [ApiKeyResponse])
+    )
+)]
+/// WEB HANDLER: List API Keys
+pub async fn handle_list_api_keys(keys: Arc<dyn ManageApiKeys>) -> Result<impl Reply, Rejection> {
+    match keys.list_api_keys().await {
+        Ok(list) => {
+            let resp: Vec<ApiKeyResponse> = list.into_iter().map(map_to_api_key_response).collect();
+            Ok(warp::reply::json(&resp))
+        }
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api-keys/{id}/revoke",
+    params(
+        ("id" = uuid::Uuid, Path, description = "API key UUID")
+    ),
+    responses(
+        (status = 200, description = "Key revoked", body = ApiKeyResponse),
+        (status = 404, description = "API key not found", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Revoke API Key
+pub async fn handle_revoke_api_key(
+    key_id: uuid::Uuid,
+    keys: Arc<dyn ManageApiKeys>,
+) -> Result<impl Reply, Rejection> {
+    match keys.revoke_api_key(key_id).await {
+        Ok(key) => Ok(warp::reply::json(&map_to_api_key_response(key))),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications/rules",
+    request_body = CreateNotificationRuleRequest,
+    responses(
+        (status = 201, description = "Rule created", body = NotificationRuleResponse),
+        (status = 400, description = "Unknown event type, bad address or URL, or channel not configured", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Create Notification Rule
+pub async fn handle_create_notification_rule(
+    req: CreateNotificationRuleRequest,
+    notifications: Arc<dyn ManageNotifications>,
+) -> Result<impl Reply, Rejection> {
+    let cmd = CreateNotificationRuleCommand {
+        name: req.name,
+        events: req.events.into_iter().collect(),
+        channel: map_from_notification_channel_dto(req.channel),
+    };
+    match notifications.create_notification_rule(cmd).await {
+        Ok(rule) => Ok(warp::reply::with_status(
+            warp::reply::json(&map_to_notification_rule_response(rule)),
+            StatusCode::CREATED,
+        )),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/notifications/rules",
+    responses(
+        (status = 200, description = "List all notification rules", body = [NotificationRuleResponse])
+    )
+)]
+/// WEB HANDLER: List Notification Rules
+pub async fn handle_list_notification_rules(
+    notifications: Arc<dyn ManageNotifications>,
+) -> Result<impl Reply, Rejection> {
+    match notifications.list_notification_rules().await {
+        Ok(rules) => {
+            let resp: Vec<NotificationRuleResponse> = rules.into_iter().map(map_to_notification_rule_response).collect();
+            Ok(warp::reply::json(&resp))
+        }
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/notifications/rules/{id}",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Notification rule UUID")
+    ),
+    responses(
+        (status = 204, description = "Rule deleted"),
+        (status = 404, description = "Notification rule not found", body = ProblemDetails),
+        (status = 503, description = "Maintenance mode: writes are disabled", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Delete Notification Rule
+pub async fn handle_delete_notification_rule(
+    rule_id: uuid::Uuid,
+    notifications: Arc<dyn ManageNotifications>,
+) -> Result<impl Reply, Rejection> {
+    match notifications.delete_notification_rule(rule_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Credentials are valid", body = UserResponse),
+        (status = 401, description = "Invalid username or password, or account disabled", body = ProblemDetails),
+        (status = 429, description = "Too many failed logins for this username; see Retry-After", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Verify Credentials
+///
+/// --- Good to know ---
+/// Only checks the password for now; issuing a token (JWT) on success is the next step.
+pub async fn handle_login(req: LoginRequest, users: Arc<dyn ManageUsers>) -> Result<impl Reply, Rejection> {
+    match users.authenticate(&req.username, &req.password).await {
+        Ok(Some(user)) => Ok(warp::reply::json(&map_to_user_response(user))),
+        Ok(None) => Err(warp::reject::custom(SecurityError::InvalidCredentials)),
+        Err(e) => Err(into_rejection(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/activity",
+    params(ActivityQuery),
+    responses(
+        (status = 200, description = "A page of activity, newest first", body = ActivityPageResponse),
+        (status = 400, description = "Invalid cursor", body = ProblemDetails),
+        (status = 403, description = "Requires the admin scope", body = ProblemDetails)
+    )
+)]
+/// WEB HANDLER: Activity Feed
+pub async fn handle_activity(
+    query: ActivityQuery,
+    activity: Arc<dyn ViewActivity>,
+) -> Result<impl Reply, Rejection> {
+    match activity.activity(query.cursor.as_deref(), query.limit).await {
+        Ok(page) => Ok(warp::reply::json(&map_to_activity_page(page))),
+        Err(e) => Err(into_rejection(e)),
     }
 }
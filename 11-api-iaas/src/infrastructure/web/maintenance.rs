@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use warp::{Filter, Rejection};
+
+// MAINTENANCE MODULE
+//
+// --- Good to know ---
+// Maintenance mode lets an operator "drain" the API before risky work such as
+// a storage migration: reads keep flowing, but every write is refused with
+// `503 Service Unavailable` and a `Retry-After` hint so well-behaved clients back off.
+// SOLID: The switch lives here so handlers and security stay unaware of it.
+
+/// How long (in seconds) clients are told to wait before retrying a write.
+pub const RETRY_AFTER_SECS: u64 = 120;
+
+/// Shared on/off switch flipped by `POST /admin/maintenance`.
+///
+/// --- Good to know ---
+/// `AtomicBool` is a lock-free boolean that is safe to share between threads.
+/// - Go: Like `atomic.Bool` from `sync/atomic`.
+/// - Python: Like a `threading.Event`, but without any locking.
+#[derive(Debug, Default)]
+pub struct MaintenanceMode {
+    enabled: AtomicBool,
+}
+
+impl MaintenanceMode {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug)]
+pub struct UnderMaintenance;
+
+impl warp::reject::Reject for UnderMaintenance {}
+
+/// Guard placed in front of every mutating route.
+/// It rejects the request while maintenance mode is on, letting reads pass untouched.
+pub fn with_write_guard(
+    mode: Arc<MaintenanceMode>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::any()
+        .and_then(move || {
+            let mode = Arc::clone(&mode);
+            async move {
+                if mode.is_enabled() {
+                    Err(warp::reject::custom(UnderMaintenance))
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Helper to inject the shared maintenance switch into handlers.
+pub fn with_maintenance(
+    mode: Arc<MaintenanceMode>,
+) -> impl Filter<Extract = (Arc<MaintenanceMode>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || Arc::clone(&mode))
+}
@@ -0,0 +1,55 @@
+use super::dto::ManifestRequest;
+use super::errors::ApiError;
+
+/// Media types `POST /apply` understands, for the 415 message.
+pub const MANIFEST_MEDIA_TYPES: &str = "application/json or application/yaml";
+
+/// MANIFEST FORMATS
+///
+/// --- Good to know ---
+/// People write manifests in YAML; tools generate JSON. Both map onto the same serde
+/// DTO, so the only difference is which parser runs, picked from `Content-Type`
+/// (YAML has no single registered type in practice, hence the aliases).
+///
+/// Comparison:
+/// - Go: Like `kubectl apply` reading YAML or JSON through `sigs.k8s.io/yaml`.
+/// - Python: Like choosing between `json.loads` and `yaml.safe_load`.
+pub fn parse_manifest(content_type: Option<&str>, body: &[u8]) -> Result<ManifestRequest, ApiError> {
+    let media_type = content_type.and_then(|v| v.split(';').next()).map(|m| m.trim().to_ascii_lowercase());
+    let bad_request = |format: &str, e: &dyn std::fmt::Display| ApiError::BadRequest(format!("invalid {} manifest: {}", format, e));
+    match media_type.as_deref() {
+        Some("application/json") => serde_json::from_slice(body).map_err(|e| bad_request("JSON", &e)),
+        Some("application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml") => {
+            serde_yaml::from_slice(body).map_err(|e| bad_request("YAML", &e))
+        }
+        _ => Err(ApiError::UnsupportedMediaType { expected: MANIFEST_MEDIA_TYPES }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_and_json_manifests_parse_alike() {
+        let yaml = b"servers:\n  - name: web-1\n    cpu: 2\n    ram: 4\n    storage: 40\n    disks:\n      - size_gb: 100\n        disk_type: ssd\n    tags:\n      env: prod\n";
+        let json = br#"{"servers": [{"name": "web-1", "cpu": 2, "ram": 4, "storage": 40, "disks": [{"size_gb": 100, "disk_type": "ssd"}], "tags": {"env": "prod"}}]}"#;
+        for (content_type, body) in [("application/yaml", &yaml[..]), ("application/json; charset=utf-8", &json[..])] {
+            let manifest = parse_manifest(Some(content_type), body).unwrap();
+            assert_eq!(manifest.servers[0].name, "web-1");
+            assert_eq!(manifest.servers[0].disks[0].size_gb, 100);
+            assert_eq!(manifest.servers[0].tags["env"], "prod");
+        }
+    }
+
+    #[test]
+    fn test_unknown_fields_and_media_types_are_rejected() {
+        let typo = b"servers:\n  - name: web-1\n    cpu: 2\n    ram: 4\n    storage: 40\n    tag: {}\n";
+        assert!(matches!(parse_manifest(Some("text/yaml"), typo), Err(ApiError::BadRequest(_))));
+        assert!(matches!(
+            parse_manifest(Some("text/plain"), b"servers: []"),
+            Err(ApiError::UnsupportedMediaType { .. })
+        ));
+        assert!(matches!(parse_manifest(None, b"{}"), Err(ApiError::UnsupportedMediaType { .. })));
+    }
+}
@@ -1,5 +1,15 @@
-use super::dto::{DiskResponse, ServerResponse};
-use crate::domain::Server;
+use super::dto::{
+    ActivityEventResponse, ActivityPageResponse, ApiKeyResponse, ApplyResponse, ManifestRequest,
+    NotificationChannelDto, NotificationRuleResponse, PlanActionDto, PlannedChangeResponse, CreatedApiKeyResponse, DiskResponse, DiskTypeDto, FaultConfigDto, FaultScopeDto, FlavorResponse, InstanceMetadataResponse,
+    RoleDto, ScopeDto, ServerResponse, UserResponse, VolumeResponse,
+};
+use crate::application::{
+    ActivityPage, ApplyReport, DiskSpec, IssuedApiKey, Manifest, PlanAction, PlannedChange, ServerSpec,
+};
+use crate::domain::{
+    ActivityEvent, ApiKey, DiskType, NotificationChannel, NotificationRule, Role, Scope, Server, User, Volume,
+};
+use crate::infrastructure::persistence::{FaultConfig, FaultScope};
 
 /// MAPPER PATTERN
 ///
@@ -16,14 +26,228 @@ pub fn map_to_response(server: Server) -> ServerResponse {
         id: server.id,
         name: server.name,
         // We convert our internal Enum to a String for the outside world.
-        status: format!("{:?}", server.status),
+        status: server.status.label().to_string(),
         disks: server
             .additional_disks
             .into_iter()
             .map(|d| DiskResponse {
                 id: d.id,
                 size_gb: d.size_gb,
+                disk_type: map_to_disk_type_dto(d.disk_type),
+                iops: d.iops,
+                throughput_mbps: d.throughput_mbps,
             })
             .collect(),
+        description: server.description,
+        tags: server.tags,
+        zone: server.zone,
+    }
+}
+
+/// Builds the guest-facing view of a server, shaped like a cloud metadata document.
+pub fn map_to_metadata(server: Server) -> InstanceMetadataResponse {
+    InstanceMetadataResponse {
+        instance_id: server.id,
+        instance_type: server.flavor(),
+        local_ipv4: server.private_ip().to_string(),
+        status: server.status.label().to_string(),
+        flavor: FlavorResponse {
+            cpu_cores: server.cpu_cores,
+            ram_gb: server.ram_gb,
+            storage_gb: server.storage_gb,
+        },
+        hostname: server.name,
+        public_keys: server.ssh_keys,
+        user_data: server.user_data,
+    }
+}
+
+pub fn map_to_volume_response(volume: Volume) -> VolumeResponse {
+    VolumeResponse {
+        id: volume.id,
+        name: volume.name,
+        size_gb: volume.size_gb,
+        disk_type: map_to_disk_type_dto(volume.disk_type),
+        iops: volume.iops,
+        throughput_mbps: volume.throughput_mbps,
+        attached_to: volume.attached_to,
+    }
+}
+
+pub fn map_from_disk_type_dto(disk_type: DiskTypeDto) -> DiskType {
+    match disk_type {
+        DiskTypeDto::Standard => DiskType::Standard,
+        DiskTypeDto::Ssd => DiskType::Ssd,
+        DiskTypeDto::Nvme => DiskType::Nvme,
+    }
+}
+
+fn map_to_disk_type_dto(disk_type: DiskType) -> DiskTypeDto {
+    match disk_type {
+        DiskType::Standard => DiskTypeDto::Standard,
+        DiskType::Ssd => DiskTypeDto::Ssd,
+        DiskType::Nvme => DiskTypeDto::Nvme,
+    }
+}
+
+pub fn map_from_manifest(manifest: ManifestRequest) -> Manifest {
+    let servers = manifest
+        .servers
+        .into_iter()
+        .map(|server| ServerSpec {
+            name: server.name,
+            cpu: server.cpu,
+            ram: server.ram,
+            storage: server.storage,
+            disks: server
+                .disks
+                .into_iter()
+                .map(|disk| DiskSpec {
+                    size_gb: disk.size_gb,
+                    disk_type: map_from_disk_type_dto(disk.disk_type),
+                    iops: disk.iops,
+                })
+                .collect(),
+            description: server.description,
+            tags: server.tags,
+        })
+        .collect();
+    Manifest { servers }
+}
+
+pub fn map_to_apply_response(report: ApplyReport, dry_run: bool) -> ApplyResponse {
+    ApplyResponse {
+        dry_run,
+        plan: report.plan.into_iter().map(map_to_planned_change).collect(),
+        applied: report.applied.into_iter().map(map_to_planned_change).collect(),
+    }
+}
+
+fn map_to_planned_change(change: PlannedChange) -> PlannedChangeResponse {
+    let action = match change.action {
+        PlanAction::Create => PlanActionDto::Create,
+        PlanAction::Update => PlanActionDto::Update,
+        PlanAction::Replace => PlanActionDto::Replace,
+        PlanAction::Delete => PlanActionDto::Delete,
+    };
+    PlannedChangeResponse { action, name: change.name, server_id: change.server_id, details: change.details }
+}
+
+pub fn map_to_user_response(user: User) -> UserResponse {
+    UserResponse {
+        id: user.id,
+        username: user.username,
+        role: map_to_role_dto(user.role),
+        disabled: user.disabled,
+    }
+}
+
+pub fn map_from_role_dto(role: RoleDto) -> Role {
+    match role {
+        RoleDto::Admin => Role::Admin,
+        RoleDto::Operator => Role::Operator,
+        RoleDto::Viewer => Role::Viewer,
+    }
+}
+
+fn map_to_role_dto(role: Role) -> RoleDto {
+    match role {
+        Role::Admin => RoleDto::Admin,
+        Role::Operator => RoleDto::Operator,
+        Role::Viewer => RoleDto::Viewer,
+    }
+}
+
+pub fn map_to_api_key_response(key: ApiKey) -> ApiKeyResponse {
+    ApiKeyResponse {
+        id: key.id,
+        name: key.name,
+        scopes: key.scopes.into_iter().map(map_to_scope_dto).collect(),
+        revoked: key.revoked,
+    }
+}
+
+pub fn map_to_created_api_key(issued: IssuedApiKey) -> CreatedApiKeyResponse {
+    CreatedApiKeyResponse {
+        id: issued.key.id,
+        name: issued.key.name,
+        scopes: issued.key.scopes.into_iter().map(map_to_scope_dto).collect(),
+        key: issued.secret,
+    }
+}
+
+pub fn map_to_notification_rule_response(rule: NotificationRule) -> NotificationRuleResponse {
+    let channel = match rule.channel {
+        NotificationChannel::Email { to } => NotificationChannelDto::Email { to },
+        NotificationChannel::Slack { webhook_url } => NotificationChannelDto::Slack { webhook_url },
+    };
+    NotificationRuleResponse { id: rule.id, name: rule.name, events: rule.events.into_iter().collect(), channel }
+}
+
+pub fn map_from_notification_channel_dto(channel: NotificationChannelDto) -> NotificationChannel {
+    match channel {
+        NotificationChannelDto::Email { to } => NotificationChannel::Email { to },
+        NotificationChannelDto::Slack { webhook_url } => NotificationChannel::Slack { webhook_url },
+    }
+}
+
+pub fn map_from_scope_dto(scope: ScopeDto) -> Scope {
+    match scope {
+        ScopeDto::ServersRead => Scope::ServersRead,
+        ScopeDto::ServersWrite => Scope::ServersWrite,
+        ScopeDto::DisksWrite => Scope::DisksWrite,
+        ScopeDto::MetadataRead => Scope::MetadataRead,
+        ScopeDto::Admin => Scope::Admin,
+    }
+}
+
+fn map_to_scope_dto(scope: Scope) -> ScopeDto {
+    match scope {
+        Scope::ServersRead => ScopeDto::ServersRead,
+        Scope::ServersWrite => ScopeDto::ServersWrite,
+        Scope::DisksWrite => ScopeDto::DisksWrite,
+        Scope::MetadataRead => ScopeDto::MetadataRead,
+        Scope::Admin => ScopeDto::Admin,
+    }
+}
+
+pub fn map_to_activity_page(page: ActivityPage) -> ActivityPageResponse {
+    ActivityPageResponse {
+        items: page.events.into_iter().map(map_to_activity_event).collect(),
+        next_cursor: page.next_cursor,
+    }
+}
+
+fn map_to_activity_event(event: ActivityEvent) -> ActivityEventResponse {
+    ActivityEventResponse {
+        sequence: event.sequence,
+        occurred_at: event.occurred_at,
+        resource_id: event.resource_id,
+        event: event.kind.event_type().to_string(),
+        message: event.kind.describe(),
+    }
+}
+
+pub fn map_from_fault_dto(dto: FaultConfigDto) -> FaultConfig {
+    FaultConfig {
+        latency_ms: dto.latency_ms,
+        error_rate: dto.error_rate,
+        scope: match dto.scope {
+            FaultScopeDto::All => FaultScope::All,
+            FaultScopeDto::Reads => FaultScope::Reads,
+            FaultScopeDto::Writes => FaultScope::Writes,
+        },
+    }
+}
+
+pub fn map_to_fault_dto(config: FaultConfig) -> FaultConfigDto {
+    FaultConfigDto {
+        latency_ms: config.latency_ms,
+        error_rate: config.error_rate,
+        scope: match config.scope {
+            FaultScope::All => FaultScopeDto::All,
+            FaultScope::Reads => FaultScopeDto::Reads,
+            FaultScope::Writes => FaultScopeDto::Writes,
+        },
     }
 }
@@ -0,0 +1,117 @@
+use crate::application::UpdateServerCommand;
+use serde_json::Value;
+use uuid::Uuid;
+use warp::{Filter, Rejection};
+
+use super::errors::ApiError;
+
+/// Media type for JSON Merge Patch documents (RFC 7386).
+pub const MERGE_PATCH: &str = "application/merge-patch+json";
+
+/// Read-only server fields: patching them is an error rather than silently ignored.
+/// Covers what responses show, what only creation sets (with both its request and
+/// stored names), and `tenant`, which servers will carry once the platform has tenants.
+const IMMUTABLE_FIELDS: &[&str] = &[
+    "id", "status", "disks", "additional_disks", "zone", "cpu", "cpu_cores", "ram", "ram_gb", "storage",
+    "storage_gb", "ssh_keys", "user_data", "private_ipv4", "tenant",
+];
+
+/// JSON MERGE PATCH (RFC 7386)
+///
+/// --- Good to know ---
+/// A merge patch looks like the resource itself, but only with the fields to change:
+/// `{"description": "db", "tags": {"env": "prod", "old": null}}`. Objects merge
+/// recursively and `null` means "remove". Clients never have to send the whole
+/// resource back, so two clients editing different fields don't overwrite each other.
+/// Clients editing the *same* field send `If-Match` with the server's ETag: the patch
+/// then only applies to the version they saw, and otherwise fails with `412`.
+///
+/// Comparison:
+/// - Go: Like `evanphx/json-patch`'s `MergePatch`.
+/// - Python: Like the `json-merge-patch` package.
+///
+/// The JSON format is a transport detail, so the patch is translated into an
+/// `UpdateServerCommand` here and the application never sees raw JSON.
+pub fn parse_server_patch(server_id: Uuid, patch: &Value) -> Result<UpdateServerCommand, String> {
+    let fields = patch.as_object().ok_or("a merge patch must be a JSON object")?;
+    let mut cmd = UpdateServerCommand { server_id, ..Default::default() };
+
+    for (field, value) in fields {
+        match (field.as_str(), value) {
+            ("name", Value::String(name)) => cmd.name = Some(name.clone()),
+            ("name", Value::Null) => return Err("name cannot be removed".to_string()),
+            ("description", Value::String(text)) => cmd.description = Some(Some(text.clone())),
+            ("description", Value::Null) => cmd.description = Some(None),
+            ("tags", Value::Null) => cmd.clear_tags = true,
+            ("tags", Value::Object(tags)) => {
+                for (key, value) in tags {
+                    let value = match value {
+                        Value::String(value) => Some(value.clone()),
+                        Value::Null => None,
+                        _ => return Err(format!("tag '{}' must be a string or null", key)),
+                    };
+                    cmd.tags.insert(key.clone(), value);
+                }
+            }
+            (field, _) if IMMUTABLE_FIELDS.contains(&field) => {
+                return Err(format!("field '{}' is immutable", field));
+            }
+            ("name" | "description" | "tags", _) => {
+                return Err(format!("field '{}' has the wrong type", field));
+            }
+            (field, _) => return Err(format!("unknown field '{}'", field)),
+        }
+    }
+    Ok(cmd)
+}
+
+/// Requires `Content-Type: application/merge-patch+json` (parameters like `charset` are fine).
+pub fn with_merge_patch_content_type() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("content-type")
+        .and_then(|content_type: Option<String>| async move {
+            let media_type = content_type.as_deref().and_then(|v| v.split(';').next()).map(str::trim);
+            if media_type.is_some_and(|m| m.eq_ignore_ascii_case(MERGE_PATCH)) {
+                Ok(())
+            } else {
+                Err(warp::reject::custom(ApiError::UnsupportedMediaType { expected: MERGE_PATCH }))
+            }
+        })
+        .untuple_one()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_patch_translates_to_command() {
+        let id = Uuid::new_v4();
+        let patch = json!({ "description": null, "tags": { "env": "prod", "old": null } });
+        let cmd = parse_server_patch(id, &patch).unwrap();
+        assert_eq!(cmd.server_id, id);
+        assert!(cmd.name.is_none());
+        assert_eq!(cmd.description, Some(None));
+        assert!(!cmd.clear_tags);
+        assert_eq!(cmd.tags.get("env"), Some(&Some("prod".to_string())));
+        assert_eq!(cmd.tags.get("old"), Some(&None));
+
+        assert!(parse_server_patch(id, &json!({ "tags": null })).unwrap().clear_tags);
+    }
+
+    #[test]
+    fn test_patch_rejects_immutable_unknown_and_mistyped_fields() {
+        let id = Uuid::new_v4();
+        let err = parse_server_patch(id, &json!({ "id": Uuid::new_v4() })).err().unwrap();
+        assert!(err.contains("immutable"));
+        for field in ["tenant", "cpu", "zone", "ssh_keys"] {
+            let err = parse_server_patch(id, &json!({ field: "x" })).err().unwrap();
+            assert!(err.contains("immutable"), "{}", field);
+        }
+        let err = parse_server_patch(id, &json!({ "colour": "blue" })).err().unwrap();
+        assert!(err.contains("unknown"));
+        assert!(parse_server_patch(id, &json!({ "name": 42 })).is_err());
+        assert!(parse_server_patch(id, &json!({ "name": null })).is_err());
+        assert!(parse_server_patch(id, &json!(["not", "an", "object"])).is_err());
+    }
+}
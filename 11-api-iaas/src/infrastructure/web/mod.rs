@@ -1,17 +1,52 @@
+mod caching;
 mod dto;
+mod errors;
 mod handlers;
+mod maintenance;
+mod manifest;
 mod mappings;
+mod merge_patch;
 mod security;
+mod ui;
 
-use crate::application::ManageServers;
+use crate::application::{
+    ApplyManifests, ManageNotifications, ManageServers, ManageUsers, ManageVolumes, ReadConsole, ReportHealth, SyncServers, ViewActivity,
+};
+use crate::domain::Scope;
+use crate::infrastructure::persistence::FaultInjector;
 use std::sync::Arc;
 use utoipa::OpenApi;
 use uuid::Uuid;
 use warp::{Filter, Rejection, Reply};
 
-use self::dto::{CreateDiskRequest, CreateServerRequest, DiskResponse, ServerResponse};
-use self::handlers::{handle_attach_disk, handle_create_server, handle_list_servers};
+use self::dto::{
+    ActivityEventResponse, ActivityPageResponse, ActivityQuery, ApiKeyResponse, ApplyResponse, AttachVolumeRequest,
+    DryRunQuery, ManifestRequest, ManifestServer, PlanActionDto, PlannedChangeResponse,
+    CreateApiKeyRequest, CreatedApiKeyResponse, ScopeDto, CreateNotificationRuleRequest, NotificationChannelDto,
+    NotificationRuleResponse,
+    ComponentHealthResponse, ConsoleLogQuery, CreateDiskRequest, CreateServerRequest, CreateUserRequest,
+    CreateVolumeRequest, DiskResponse, DiskTypeDto, FaultConfigDto, FaultScopeDto, FlavorResponse,
+    HealthResponse, InstanceMetadataResponse, LoginRequest, MaintenanceRequest,
+    MaintenanceResponse, ProblemDetails, ReadinessResponse, RoleDto, ServerChangesQuery,
+    ServerChangesResponse, ServerMergePatch, ServerResponse, UserResponse, VolumeResponse,
+};
+use self::handlers::{
+    handle_activity, handle_apply, handle_attach_disk, handle_create_api_key, handle_list_api_keys,
+    handle_revoke_api_key, handle_create_notification_rule, handle_list_notification_rules,
+    handle_delete_notification_rule, handle_attach_volume, handle_console_logs, handle_create_server,
+    handle_create_user, handle_create_volume, handle_delete_server, handle_delete_volume, handle_detach_volume,
+    handle_get_faults, handle_get_metadata, handle_get_server_by_name, handle_get_volume,
+    handle_healthz, handle_list_servers, handle_list_users, handle_list_volumes, handle_login,
+    handle_patch_server, handle_readyz, handle_server_changes, handle_set_faults, handle_set_maintenance,
+    handle_set_user_disabled,
+};
+use self::caching::{with_if_match, with_if_none_match};
+use self::maintenance::{with_maintenance, with_write_guard, MaintenanceMode};
+use self::merge_patch::with_merge_patch_content_type;
 use self::security::{handle_rejection, with_auth};
+use self::ui::ui_routes;
+
+pub use self::security::{AuthConfig, StaticApiKey};
 
 /// HEXAGONAL ARCHITECTURE: INBOUND ADAPTER (Web)
 ///
@@ -27,10 +62,78 @@ use self::security::{handle_rejection, with_auth};
     paths(
         handlers::handle_create_server,
         handlers::handle_list_servers,
+        handlers::handle_get_server_by_name,
+        handlers::handle_patch_server,
+        handlers::handle_delete_server,
+        handlers::handle_server_changes,
         handlers::handle_attach_disk,
+        handlers::handle_apply,
+        handlers::handle_console_logs,
+        handlers::handle_create_volume,
+        handlers::handle_list_volumes,
+        handlers::handle_get_volume,
+        handlers::handle_delete_volume,
+        handlers::handle_attach_volume,
+        handlers::handle_detach_volume,
+        handlers::handle_get_metadata,
+        handlers::handle_set_maintenance,
+        handlers::handle_readyz,
+        handlers::handle_healthz,
+        handlers::handle_get_faults,
+        handlers::handle_set_faults,
+        handlers::handle_create_user,
+        handlers::handle_list_users,
+        handlers::handle_set_user_disabled,
+        handlers::handle_login,
+        handlers::handle_create_api_key,
+        handlers::handle_list_api_keys,
+        handlers::handle_revoke_api_key,
+        handlers::handle_create_notification_rule,
+        handlers::handle_list_notification_rules,
+        handlers::handle_delete_notification_rule,
+        handlers::handle_activity,
     ),
     components(
-        schemas(CreateServerRequest, CreateDiskRequest, ServerResponse, DiskResponse)
+        schemas(
+            CreateServerRequest,
+            CreateDiskRequest,
+            ServerResponse,
+            ServerChangesResponse,
+            DiskResponse,
+            CreateVolumeRequest,
+            AttachVolumeRequest,
+            VolumeResponse,
+            InstanceMetadataResponse,
+            FlavorResponse,
+            MaintenanceRequest,
+            MaintenanceResponse,
+            ReadinessResponse,
+            HealthResponse,
+            ComponentHealthResponse,
+            FaultConfigDto,
+            FaultScopeDto,
+            ProblemDetails,
+            CreateUserRequest,
+            UserResponse,
+            RoleDto,
+            LoginRequest,
+            CreateApiKeyRequest,
+            CreatedApiKeyResponse,
+            ApiKeyResponse,
+            ScopeDto,
+            CreateNotificationRuleRequest,
+            NotificationChannelDto,
+            NotificationRuleResponse,
+            ActivityEventResponse,
+            ActivityPageResponse,
+            ServerMergePatch,
+            ManifestRequest,
+            ManifestServer,
+            ApplyResponse,
+            PlannedChangeResponse,
+            PlanActionDto,
+            DiskTypeDto
+        )
     ),
     tags(
         (name = "IaaS API", description = "Server management endpoints")
@@ -38,6 +141,35 @@ use self::security::{handle_rejection, with_auth};
 )]
 pub struct ApiDoc;
 
+/// Settings for the web adapter. `Default` is the locked-down production setup.
+#[derive(Clone, Default)]
+pub struct WebConfig {
+    /// Also serve instance metadata WITHOUT an API key at `/latest/meta-data/{id}`,
+    /// like the link-local metadata endpoint of a real cloud. Only for labs/demos.
+    pub metadata_link_local: bool,
+    /// Components reported by `/healthz` (e.g. the storage circuit breaker).
+    pub health_checks: Vec<Arc<dyn ReportHealth>>,
+    /// Live fault-injection settings. When set, `/admin/faults` is exposed (chaos testing only).
+    pub faults: Option<Arc<FaultInjector>>,
+    /// User administration use cases. When set, `/users` is exposed.
+    pub users: Option<Arc<dyn ManageUsers>>,
+    /// Accepted credentials: API key and/or SSO bearer tokens. When `auth.api_keys`
+    /// is set, `/api-keys` is exposed to manage them.
+    pub auth: AuthConfig,
+    /// Activity feed. When set, `/activity` is exposed.
+    pub activity: Option<Arc<dyn ViewActivity>>,
+    /// Standalone block storage. When set, `/volumes` is exposed.
+    pub volumes: Option<Arc<dyn ManageVolumes>>,
+    /// Serial console output. When set, `/servers/{id}/logs` is exposed.
+    pub console: Option<Arc<dyn ReadConsole>>,
+    /// Inventory delta sync. When set, `/servers/changes` is exposed.
+    pub changes: Option<Arc<dyn SyncServers>>,
+    /// Desired-state reconciliation. When set, `/apply` is exposed.
+    pub apply: Option<Arc<dyn ApplyManifests>>,
+    /// Event notification rules. When set, `/notifications/rules` is exposed.
+    pub notifications: Option<Arc<dyn ManageNotifications>>,
+}
+
 /// Helper to inject the shared Core Service (Port) into our routes.
 fn with_port(
     port: Arc<dyn ManageServers>,
@@ -45,6 +177,17 @@ fn with_port(
     warp::any().map(move || Arc::clone(&port))
 }
 
+/// Injects an optional feature (fault injector, users port, ...), or rejects with
+/// 404 when it is not configured - the routes then behave as if they didn't exist.
+fn with_optional<T: ?Sized + Send + Sync + 'static>(
+    value: Option<Arc<T>>,
+) -> impl Filter<Extract = (Arc<T>,), Error = Rejection> + Clone {
+    warp::any().and_then(move || {
+        let value = value.clone();
+        async move { value.ok_or_else(warp::reject::not_found) }
+    })
+}
+
 /// Main entry point for the Web API.
 /// Orchestrates routes, security, CORS, and OpenAPI spec.
 ///
@@ -53,13 +196,20 @@ fn with_port(
 /// - Python: Like the `app = FastAPI()` setup and route registrations.
 pub fn routes(
     port: Arc<dyn ManageServers>,
+    config: WebConfig,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
-    // POST /servers
+    // Shared switch: every mutating route consults it, the admin route flips it.
+    let maintenance = Arc::new(MaintenanceMode::default());
+
+    // POST /servers?dry_run=true
     // We use .and() and other filters to build a declarative "Pipeline".
+    // Every mutating route takes `?dry_run=true`: all checks run, nothing is stored.
     let create_server = warp::post()
         .and(warp::path("servers"))
         .and(warp::path::end())
-        .and(with_auth()) // Inbound Auth Middleware
+        .and(with_auth(&config.auth, Scope::ServersWrite)) // Inbound Auth Middleware
+        .and(with_write_guard(Arc::clone(&maintenance))) // Drain writes during maintenance
+        .and(warp::query::<DryRunQuery>())
         .and(warp::body::content_length_limit(1024 * 16)) // Security: Max Payload
         .and(warp::body::json())
         .and(with_port(Arc::clone(&port))) // Dependency Injection
@@ -69,33 +219,359 @@ pub fn routes(
     let list_servers = warp::get()
         .and(warp::path("servers"))
         .and(warp::path::end())
-        .and(with_auth())
+        .and(with_auth(&config.auth, Scope::ServersRead))
+        .and(with_if_none_match())
         .and(with_port(Arc::clone(&port)))
         .and_then(handle_list_servers);
 
+    // GET /servers/changes?since=...
+    let server_changes = warp::get()
+        .and(warp::path!("servers" / "changes"))
+        .and(with_auth(&config.auth, Scope::ServersRead))
+        .and(warp::query::<ServerChangesQuery>())
+        .and(with_optional(config.changes.clone()))
+        .and_then(handle_server_changes);
+
+    // GET /servers/by-name/{name}
+    let get_server_by_name = warp::get()
+        .and(warp::path!("servers" / "by-name" / String))
+        .and(with_auth(&config.auth, Scope::ServersRead))
+        .and(with_if_none_match())
+        .and(with_port(Arc::clone(&port)))
+        .and_then(handle_get_server_by_name);
+
+    // PATCH /servers/{id} (JSON Merge Patch)
+    let patch_server = warp::patch()
+        .and(warp::path!("servers" / Uuid))
+        .and(with_auth(&config.auth, Scope::ServersWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
+        .and(with_merge_patch_content_type())
+        .and(with_if_match())
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::bytes())
+        .and(with_port(Arc::clone(&port)))
+        .and_then(handle_patch_server);
+
+    // DELETE /servers/{id}
+    let delete_server = warp::delete()
+        .and(warp::path!("servers" / Uuid))
+        .and(with_auth(&config.auth, Scope::ServersWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
+        .and(with_port(Arc::clone(&port)))
+        .and_then(handle_delete_server);
+
     // POST /servers/{id}/disks
     let attach_disk = warp::post()
         .and(warp::path!("servers" / Uuid / "disks"))
-        .and(with_auth())
+        .and(with_auth(&config.auth, Scope::DisksWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
         .and(warp::body::content_length_limit(1024 * 16))
         .and(warp::body::json())
         .and(with_port(Arc::clone(&port)))
         .and_then(handle_attach_disk);
 
+    // POST /apply?dry_run=true
+    // An apply attaches disks as well, so it needs both write scopes.
+    let apply = warp::post()
+        .and(warp::path("apply"))
+        .and(warp::path::end())
+        .and(with_auth(&config.auth, Scope::ServersWrite))
+        .and(with_auth(&config.auth, Scope::DisksWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
+        .and(warp::header::optional::<String>("content-type"))
+        .and(warp::body::content_length_limit(1024 * 256))
+        .and(warp::body::bytes())
+        .and(with_optional(config.apply.clone()))
+        .and_then(handle_apply);
+
+    // GET /servers/{id}/logs?tail=200&follow=true
+    let console_logs = warp::get()
+        .and(warp::path!("servers" / Uuid / "logs"))
+        .and(with_auth(&config.auth, Scope::ServersRead))
+        .and(warp::query::<ConsoleLogQuery>())
+        .and(with_optional(config.console.clone()))
+        .and_then(handle_console_logs);
+
+    // POST /volumes
+    let create_volume = warp::post()
+        .and(warp::path("volumes"))
+        .and(warp::path::end())
+        .and(with_auth(&config.auth, Scope::DisksWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_optional(config.volumes.clone()))
+        .and_then(handle_create_volume);
+
+    // GET /volumes
+    let list_volumes = warp::get()
+        .and(warp::path("volumes"))
+        .and(warp::path::end())
+        .and(with_auth(&config.auth, Scope::ServersRead))
+        .and(with_optional(config.volumes.clone()))
+        .and_then(handle_list_volumes);
+
+    // GET /volumes/{id}
+    let get_volume = warp::get()
+        .and(warp::path!("volumes" / Uuid))
+        .and(with_auth(&config.auth, Scope::ServersRead))
+        .and(with_optional(config.volumes.clone()))
+        .and_then(handle_get_volume);
+
+    // DELETE /volumes/{id}
+    let delete_volume = warp::delete()
+        .and(warp::path!("volumes" / Uuid))
+        .and(with_auth(&config.auth, Scope::DisksWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
+        .and(with_optional(config.volumes.clone()))
+        .and_then(handle_delete_volume);
+
+    // POST /volumes/{id}/attach
+    let attach_volume = warp::post()
+        .and(warp::path!("volumes" / Uuid / "attach"))
+        .and(with_auth(&config.auth, Scope::DisksWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_optional(config.volumes.clone()))
+        .and_then(handle_attach_volume);
+
+    // POST /volumes/{id}/detach
+    let detach_volume = warp::post()
+        .and(warp::path!("volumes" / Uuid / "detach"))
+        .and(with_auth(&config.auth, Scope::DisksWrite))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::query::<DryRunQuery>())
+        .and(with_optional(config.volumes.clone()))
+        .and_then(handle_detach_volume);
+
+    // GET /metadata/{id}
+    let metadata = warp::get()
+        .and(warp::path!("metadata" / Uuid))
+        .and(with_auth(&config.auth, Scope::MetadataRead))
+        .and(with_port(Arc::clone(&port)))
+        .and_then(handle_get_metadata);
+
+    // GET /latest/meta-data/{id} (unauthenticated, opt-in for labs)
+    // When disabled, the filter rejects up-front so the route simply doesn't exist.
+    let link_local_enabled = config.metadata_link_local;
+    let link_local_metadata = warp::get()
+        .and(warp::path!("latest" / "meta-data" / Uuid))
+        .and(
+            warp::any()
+                .and_then(move || async move {
+                    if link_local_enabled {
+                        Ok(())
+                    } else {
+                        Err(warp::reject::not_found())
+                    }
+                })
+                .untuple_one(),
+        )
+        .and(with_port(Arc::clone(&port)))
+        .and_then(handle_get_metadata);
+
+    // POST /admin/maintenance
+    // Deliberately NOT guarded: operators must be able to switch maintenance off again.
+    let set_maintenance = warp::post()
+        .and(warp::path!("admin" / "maintenance"))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_maintenance(Arc::clone(&maintenance)))
+        .and_then(handle_set_maintenance);
+
+    // GET / PUT /admin/faults (only when chaos mode is enabled)
+    let get_faults = warp::get()
+        .and(warp::path!("admin" / "faults"))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_optional(config.faults.clone()))
+        .and_then(handle_get_faults);
+
+    let set_faults = warp::put()
+        .and(warp::path!("admin" / "faults"))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_optional(config.faults.clone()))
+        .and_then(handle_set_faults);
+
+    // POST /users
+    let create_user = warp::post()
+        .and(warp::path("users"))
+        .and(warp::path::end())
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_optional(config.users.clone()))
+        .and_then(handle_create_user);
+
+    // GET /users
+    let list_users = warp::get()
+        .and(warp::path("users"))
+        .and(warp::path::end())
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_optional(config.users.clone()))
+        .and_then(handle_list_users);
+
+    // POST /users/{id}/disable and /users/{id}/enable
+    let disable_user = warp::path!("users" / Uuid / "disable").map(|id| (id, true)).untuple_one();
+    let enable_user = warp::path!("users" / Uuid / "enable").map(|id| (id, false)).untuple_one();
+    let set_user_disabled = warp::post()
+        .and(disable_user.or(enable_user).unify())
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(with_optional(config.users.clone()))
+        .and_then(handle_set_user_disabled);
+
+    // POST /api-keys
+    let create_api_key = warp::post()
+        .and(warp::path("api-keys"))
+        .and(warp::path::end())
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_optional(config.auth.api_keys.clone()))
+        .and_then(handle_create_api_key);
+
+    // GET /api-keys
+    let list_api_keys = warp::get()
+        .and(warp::path("api-keys"))
+        .and(warp::path::end())
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_optional(config.auth.api_keys.clone()))
+        .and_then(handle_list_api_keys);
+
+    // POST /api-keys/{id}/revoke
+    let revoke_api_key = warp::post()
+        .and(warp::path!("api-keys" / Uuid / "revoke"))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(with_optional(config.auth.api_keys.clone()))
+        .and_then(handle_revoke_api_key);
+
+    // POST /notifications/rules
+    let create_notification_rule = warp::post()
+        .and(warp::path!("notifications" / "rules"))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_optional(config.notifications.clone()))
+        .and_then(handle_create_notification_rule);
+
+    // GET /notifications/rules
+    let list_notification_rules = warp::get()
+        .and(warp::path!("notifications" / "rules"))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_optional(config.notifications.clone()))
+        .and_then(handle_list_notification_rules);
+
+    // DELETE /notifications/rules/{id}
+    let delete_notification_rule = warp::delete()
+        .and(warp::path!("notifications" / "rules" / Uuid))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(with_write_guard(Arc::clone(&maintenance)))
+        .and(with_optional(config.notifications.clone()))
+        .and_then(handle_delete_notification_rule);
+
+    // GET /activity?cursor=...&limit=... (admin: the feed includes user and API key events)
+    let activity = warp::get()
+        .and(warp::path!("activity"))
+        .and(with_auth(&config.auth, Scope::Admin))
+        .and(warp::query::<ActivityQuery>())
+        .and(with_optional(config.activity.clone()))
+        .and_then(handle_activity);
+
+    // POST /auth/login (unauthenticated by design: this is how credentials get checked)
+    let login = warp::post()
+        .and(warp::path!("auth" / "login"))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_optional(config.users.clone()))
+        .and_then(handle_login);
+
+    // GET /healthz (unauthenticated, polled by monitoring)
+    let health_checks = Arc::new(config.health_checks);
+    let healthz = warp::get()
+        .and(warp::path!("healthz"))
+        .and(warp::any().map(move || Arc::clone(&health_checks)))
+        .and_then(handle_healthz);
+
+    // GET /readyz (unauthenticated, polled by load balancers)
+    let readyz = warp::get()
+        .and(warp::path!("readyz"))
+        .and(with_maintenance(Arc::clone(&maintenance)))
+        .and_then(handle_readyz);
+
     // Route for OpenAPI spec
     let openapi_json =
         warp::path!("api-doc" / "openapi.json").map(|| warp::reply::json(&ApiDoc::openapi()));
 
+    // GET /ui (unauthenticated static files; the dashboard asks for an API key)
+    let ui = ui_routes();
+
     // CORS configuration: Inproduction, restrict origins!
     let cors = warp::cors()
         .allow_any_origin()
-        .allow_headers(vec!["x-api-key", "content-type"])
-        .allow_methods(vec!["GET", "POST", "OPTIONS"]);
+        .allow_headers(vec!["x-api-key", "authorization", "content-type"])
+        .allow_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"]);
 
-    let api = create_server
+    // Routes are grouped and boxed per area: one long `.or()` chain nests its type
+    // so deeply that the compiler gives up (it hits its recursion limit).
+    let server_routes = create_server
         .or(list_servers)
+        .or(server_changes)
+        .or(get_server_by_name)
+        .or(patch_server)
+        .or(delete_server)
         .or(attach_disk)
+        .or(console_logs)
+        .or(apply)
+        .boxed();
+    let volume_routes = create_volume
+        .or(list_volumes)
+        .or(get_volume)
+        .or(delete_volume)
+        .or(attach_volume)
+        .or(detach_volume)
+        .boxed();
+    let identity_routes = create_user
+        .or(list_users)
+        .or(set_user_disabled)
+        .or(login)
+        .or(create_api_key)
+        .or(list_api_keys)
+        .or(revoke_api_key)
+        .boxed();
+    let notification_routes = create_notification_rule
+        .or(list_notification_rules)
+        .or(delete_notification_rule)
+        .boxed();
+
+    let api = server_routes
+        .or(volume_routes)
+        .or(metadata)
+        .or(link_local_metadata)
+        .or(set_maintenance)
+        .or(get_faults)
+        .or(set_faults)
+        .or(identity_routes)
+        .or(notification_routes)
+        .or(activity)
+        .or(readyz)
+        .or(healthz)
         .or(openapi_json)
+        .or(ui)
         .recover(handle_rejection) // Global Error Handler
         .with(cors);
 
@@ -107,7 +583,7 @@ pub fn routes(
 mod tests {
     use self::mappings::map_to_response;
     use super::*;
-    use crate::domain::{Disk, ServerStatus};
+    use crate::domain::{Disk, DiskType, ServerStatus};
 
     #[test]
     fn test_map_to_response() {
@@ -118,10 +594,13 @@ mod tests {
             ram_gb: 4,
             storage_gb: 40,
             status: ServerStatus::Running,
-            additional_disks: vec![Disk {
-                id: Uuid::new_v4(),
-                size_gb: 100,
-            }],
+            additional_disks: vec![Disk::new(100, DiskType::Ssd, None).unwrap()],
+            ssh_keys: Vec::new(),
+            user_data: None,
+            description: Some("test box".to_string()),
+            tags: Default::default(),
+            zone: None,
+            private_ipv4: None,
         };
 
         let response = map_to_response(server.clone());
@@ -130,6 +609,7 @@ mod tests {
         assert_eq!(response.name, server.name);
         assert_eq!(response.status, "Running");
         assert_eq!(response.disks.len(), 1);
+        assert_eq!(response.description.as_deref(), Some("test box"));
         assert_eq!(response.disks[0].size_gb, 100);
     }
 }
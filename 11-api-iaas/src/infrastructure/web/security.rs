@@ -1,37 +1,134 @@
-use warp::{Filter, Rejection, Reply, http::StatusCode};
+use warp::{Filter, Rejection, Reply, http::{header, HeaderValue, StatusCode}};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
 use std::convert::Infallible;
-use serde_json::json;
+use std::sync::Arc;
+use crate::application::{ManageApiKeys, Principal, VerifyTokens};
+use crate::domain::Scope;
+use uuid::Uuid;
+use super::dto::ProblemDetails;
+use super::errors::ApiError;
+use super::maintenance::{UnderMaintenance, RETRY_AFTER_SECS};
 
-/// SECURITY MODULE
-/// 
-/// --- Good to know ---
-/// This module implements OWASP Top 10 API Security protections.
-/// SOLID: By moving security logic here, we keep our `mod.rs` clean and focused.
+// SECURITY MODULE
+//
+// --- Good to know ---
+// This module implements OWASP Top 10 API Security protections.
+// SOLID: By moving security logic here, we keep our `mod.rs` clean and focused.
+
+/// A static API key set by the operator (`IAAS_API_KEY`), with the scopes it grants.
+/// Only its SHA-256 digest is kept, so comparing takes the same time whatever is presented.
+#[derive(Clone)]
+pub struct StaticApiKey {
+    digest: Vec<u8>,
+    scopes: BTreeSet<Scope>,
+}
+
+impl StaticApiKey {
+    pub fn new(secret: &str, scopes: BTreeSet<Scope>) -> Self {
+        Self { digest: Sha256::digest(secret.as_bytes()).to_vec(), scopes }
+    }
+
+    /// Compare every byte of the digests, so the time taken doesn't reveal how much matched.
+    fn matches(&self, presented: &str) -> bool {
+        let presented = Sha256::digest(presented.as_bytes());
+        self.digest.iter().zip(presented.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+/// Which credentials the API accepts. The default accepts `x-api-key` but has no
+/// static key, so only managed keys (when configured) get in.
+#[derive(Clone)]
+pub struct AuthConfig {
+    /// Accept `x-api-key`: the static key and managed keys.
+    pub api_key: bool,
+    /// The operator's static key, if one is configured.
+    pub static_key: Option<StaticApiKey>,
+    /// Managed, scoped API keys created through `/api-keys`.
+    pub api_keys: Option<Arc<dyn ManageApiKeys>>,
+    /// Accept `Authorization: Bearer <token>` checked by an SSO token verifier.
+    pub tokens: Option<Arc<dyn VerifyTokens>>,
+}
 
-pub const API_KEY: &str = "iaas-secret-key-123";
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self { api_key: true, static_key: None, api_keys: None, tokens: None }
+    }
+}
+
+impl AuthConfig {
+    /// Resolves the request's credentials to a caller, if any are valid.
+    async fn authenticate(&self, api_key: Option<String>, authorization: Option<String>) -> Option<Principal> {
+        if let Some(key) = api_key.filter(|_| self.api_key) {
+            if let Some(static_key) = self.static_key.as_ref().filter(|static_key| static_key.matches(&key)) {
+                return Some(Principal { subject: "api-key".to_string(), scopes: static_key.scopes.clone() });
+            }
+            return match self.api_keys.as_ref()?.authenticate_api_key(&key).await {
+                Ok(Some(key)) => Some(Principal { subject: format!("api-key:{}", key.name), scopes: key.scopes }),
+                Ok(None) => None,
+                Err(e) => {
+                    eprintln!("API key lookup failed: {:#}", e);
+                    None
+                }
+            };
+        }
+        let token = authorization.as_deref().and_then(|value| value.strip_prefix("Bearer "))?;
+        match self.tokens.as_ref()?.verify(token.trim()).await {
+            Ok(principal) => Some(principal),
+            Err(e) => {
+                // Why the token failed stays in our logs; the client only gets a 401.
+                eprintln!("Rejected bearer token: {:#}", e);
+                None
+            }
+        }
+    }
+}
 
 /// OWASP API-2: BROKEN AUTHENTICATION
 /// 
-/// This "Filter" acts like a piece of Middleware. It checks for a secure header
-/// before allowing the request to reach the logic.
+/// This "Filter" acts like a piece of Middleware. It checks for valid credentials
+/// (API key or SSO bearer token) before allowing the request to reach the logic,
+/// and then authorizes the route's `required` scope (OWASP API-5: function-level
+/// authorization). Roles of users and SSO tokens are translated into scopes, so API
+/// keys and people go through the same check.
 /// 
 /// Comparison:
 /// - Go: Like a Middleware function wrapping a `http.Handler`.
 /// - Python: Similar to a FastAPI `Depends` dependency or a Flask decorator.
-pub fn with_auth() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+pub fn with_auth(auth: &AuthConfig, required: Scope) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let auth = auth.clone();
     warp::header::optional::<String>("x-api-key")
-        .and_then(|key: Option<String>| async move {
-            match key {
-                Some(k) if k == API_KEY => Ok(()),
-                _ => Err(warp::reject::custom(SecurityError::Unauthorized)),
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(move |key: Option<String>, authorization: Option<String>| {
+            let auth = auth.clone();
+            async move {
+                let principal = auth
+                    .authenticate(key, authorization)
+                    .await
+                    .ok_or_else(|| warp::reject::custom(SecurityError::Unauthorized))?;
+                authorize(&principal, required)
             }
         })
         .untuple_one()
 }
 
+/// The authorization step on its own: does the caller hold the route's scope?
+fn authorize(principal: &Principal, required: Scope) -> Result<(), Rejection> {
+    if required.is_granted_by(&principal.scopes) {
+        Ok(())
+    } else {
+        eprintln!("Denied '{}' ({:?}): {:?} scope required", principal.subject, principal.scopes, required);
+        Err(warp::reject::custom(SecurityError::Forbidden))
+    }
+}
+
 #[derive(Debug)]
 pub enum SecurityError {
     Unauthorized,
+    /// Unknown user, wrong password, or a disabled account - deliberately indistinguishable.
+    InvalidCredentials,
+    /// Authenticated, but the caller lacks the scope this route requires.
+    Forbidden,
 }
 
 impl warp::reject::Reject for SecurityError {}
@@ -42,22 +139,96 @@ impl warp::reject::Reject for SecurityError {}
 /// into clean, sanitized JSON responses.
 /// 
 /// Why: We never want to leak database strings or stack traces to an attacker.
+///
+/// --- Good to know ---
+/// Every error uses the RFC 7807 `application/problem+json` format, so clients can
+/// handle all failures with one parser. The `request_id` is also logged server-side,
+/// letting support correlate a user's error report with our logs.
 pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
-    let (code, message) = if err.is_not_found() {
-        (StatusCode::NOT_FOUND, "Resource not found")
+    let request_id = Uuid::new_v4();
+    let mut conflicting_id = None;
+    let mut capacity = None;
+    let mut retry_after = None;
+
+    // Our own rejections are checked first: warp merges the rejections of every route
+    // that didn't match, so a built-in one (e.g. "method not allowed") may sit beside them.
+    let (code, kind, detail) = if let Some(ApiError::Conflict { message, conflicting_id: id }) = err.find() {
+        // A conflict carries extra data: the ID of the resource that caused it.
+        conflicting_id = Some(*id);
+        (StatusCode::CONFLICT, "conflict", message.clone())
+    } else if let Some(ApiError::InsufficientCapacity { message, reason, zone, storage }) = err.find() {
+        capacity = Some((reason.to_string(), zone.clone()));
+        let code = if *storage { StatusCode::INSUFFICIENT_STORAGE } else { StatusCode::CONFLICT };
+        (code, "insufficient-capacity", message.clone())
+    } else if let Some(ApiError::TooManyRequests { message, retry_after_secs }) = err.find() {
+        retry_after = Some(*retry_after_secs);
+        (StatusCode::TOO_MANY_REQUESTS, "too-many-requests", message.clone())
+    } else if let Some(ApiError::PreconditionFailed(message)) = err.find() {
+        (StatusCode::PRECONDITION_FAILED, "precondition-failed", message.clone())
+    } else if let Some(ApiError::BadRequest(message)) = err.find() {
+        (StatusCode::BAD_REQUEST, "invalid-request", message.clone())
+    } else if let Some(ApiError::UnsupportedMediaType { expected }) = err.find() {
+        let detail = format!("Content-Type must be {}", expected);
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported-media-type", detail)
+    } else if let Some(ApiError::NotFound) = err.find() {
+        (StatusCode::NOT_FOUND, "not-found", "Resource not found".to_string())
     } else if let Some(SecurityError::Unauthorized) = err.find() {
-        (StatusCode::UNAUTHORIZED, "Invalid or missing API Key")
-    } else if let Some(_) = err.find::<warp::reject::PayloadTooLarge>() {
-        (StatusCode::PAYLOAD_TOO_LARGE, "Payload too large")
-    } else {
+        (StatusCode::UNAUTHORIZED, "unauthorized", "Invalid or missing credentials".to_string())
+    } else if let Some(SecurityError::InvalidCredentials) = err.find() {
+        (StatusCode::UNAUTHORIZED, "invalid-credentials", "Invalid username or password".to_string())
+    } else if let Some(SecurityError::Forbidden) = err.find() {
+        (StatusCode::FORBIDDEN, "forbidden", "Your role or API key scopes do not allow this operation".to_string())
+    } else if err.find::<UnderMaintenance>().is_some() {
+        let detail = "Service is in maintenance mode, writes are temporarily disabled";
+        (StatusCode::SERVICE_UNAVAILABLE, "maintenance", detail.to_string())
+    } else if let Some(ApiError::Internal(details)) = err.find() {
         // We log the error internally for us to debug...
-        eprintln!("Unhandled error: {:?}", err);
+        eprintln!("[{}] Internal error: {}", request_id, details);
         // ...but we only send a generic "Internal Error" to the user.
-        (StatusCode::INTERNAL_SERVER_ERROR, "An internal error occurred")
+        (StatusCode::INTERNAL_SERVER_ERROR, "internal", "An internal error occurred".to_string())
+    } else if err.is_not_found() {
+        (StatusCode::NOT_FOUND, "not-found", "Resource not found".to_string())
+    } else if err.find::<warp::reject::PayloadTooLarge>().is_some() {
+        (StatusCode::PAYLOAD_TOO_LARGE, "payload-too-large", "Payload too large".to_string())
+    } else if let Some(e) = err.find::<warp::filters::body::BodyDeserializeError>() {
+        // Serde's message names the offending field, never internal state.
+        (StatusCode::BAD_REQUEST, "invalid-body", e.to_string())
+    } else if err.find::<warp::reject::InvalidQuery>().is_some() {
+        (StatusCode::BAD_REQUEST, "invalid-query", "Invalid query string".to_string())
+    } else if err.find::<warp::reject::UnsupportedMediaType>().is_some() {
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported-media-type", "Unsupported content type".to_string())
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        (StatusCode::METHOD_NOT_ALLOWED, "method-not-allowed", "Method not allowed".to_string())
+    } else {
+        eprintln!("[{}] Unhandled error: {:?}", request_id, err);
+        (StatusCode::INTERNAL_SERVER_ERROR, "internal", "An internal error occurred".to_string())
+    };
+
+    let problem = ProblemDetails {
+        problem_type: format!("/problems/{}", kind),
+        title: code.canonical_reason().unwrap_or("Error").to_string(),
+        status: code.as_u16(),
+        detail,
+        instance: format!("urn:uuid:{}", request_id),
+        request_id,
+        conflicting_id,
+        reason: capacity.as_ref().map(|(reason, _)| reason.clone()),
+        zone: capacity.map(|(_, zone)| zone),
     };
 
-    let json = warp::reply::json(&json!({ "error": message }));
-    Ok(warp::reply::with_status(json, code))
+    let mut resp = warp::reply::with_status(warp::reply::json(&problem), code).into_response();
+    let headers = resp.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/problem+json"));
+    if let Ok(value) = HeaderValue::from_str(&request_id.to_string()) {
+        headers.insert("x-request-id", value);
+    }
+    if code == StatusCode::SERVICE_UNAVAILABLE {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+    }
+    if let Some(secs) = retry_after {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(secs));
+    }
+    Ok(resp)
 }
 
 /// OWASP API-8: SECURITY MISCONFIGURATION (Secure Headers)
@@ -70,5 +241,6 @@ pub fn apply_security_headers<F: Filter<Extract = (R,), Error = Rejection> + Clo
         .with(warp::reply::with::header("X-Content-Type-Options", "nosniff"))
         .with(warp::reply::with::header("X-Frame-Options", "DENY"))
         .with(warp::reply::with::header("X-XSS-Protection", "1; mode=block"))
-        .with(warp::reply::with::header("Content-Security-Policy", "default-src 'none'"))
+        // A default: the `/ui` dashboard sets its own, slightly wider policy.
+        .with(warp::reply::with::default_header("Content-Security-Policy", "default-src 'none'"))
 }
@@ -0,0 +1,49 @@
+use rust_embed::RustEmbed;
+use warp::http::{header, Response};
+use warp::hyper::Body;
+use warp::path::Tail;
+use warp::{Filter, Rejection};
+
+use super::errors::ApiError;
+
+/// The dashboard only talks to this API, and only from its own files. The API's
+/// blanket `default-src 'none'` would block the page's own script and stylesheet.
+const UI_CONTENT_SECURITY_POLICY: &str =
+    "default-src 'none'; script-src 'self'; style-src 'self'; connect-src 'self'; form-action 'none'; frame-ancestors 'none'";
+
+/// EMBEDDED ASSETS: The `/ui` dashboard
+///
+/// --- Good to know ---
+/// `#[derive(RustEmbed)]` reads the `ui/` folder at compile time and bakes every file
+/// into the binary, so deploying the dashboard means deploying the API, nothing else.
+/// In debug builds the files are read from disk instead, so HTML/JS edits show up
+/// on reload without recompiling.
+///
+/// Comparison:
+/// - Go: Like `//go:embed ui` into an `embed.FS` served by `http.FileServer`.
+/// - Python: Like FastAPI's `StaticFiles`, but shipped inside the executable.
+#[derive(RustEmbed)]
+#[folder = "ui/"]
+struct UiAssets;
+
+/// GET /ui and /ui/{file}. Unauthenticated: the files hold no data; the page asks for
+/// an API key and every call it makes goes through the regular, authorized endpoints.
+pub fn ui_routes() -> impl Filter<Extract = (Response<Body>,), Error = Rejection> + Clone {
+    warp::get().and(warp::path("ui")).and(warp::path::tail()).and_then(serve_asset)
+}
+
+async fn serve_asset(tail: Tail) -> Result<Response<Body>, Rejection> {
+    let path = match tail.as_str() {
+        "" => "index.html",
+        path => path,
+    };
+    // A custom rejection: a plain `not_found` would lose to other routes' `405`.
+    let asset = UiAssets::get(path).ok_or_else(|| warp::reject::custom(ApiError::NotFound))?;
+    Response::builder()
+        .header(header::CONTENT_TYPE, asset.metadata.mimetype())
+        .header(header::CONTENT_SECURITY_POLICY, UI_CONTENT_SECURITY_POLICY)
+        // Revalidate on every load, so a new release never runs against a stale script.
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(asset.data.into_owned()))
+        .map_err(|e| warp::reject::custom(ApiError::Internal(e.to_string())))
+}
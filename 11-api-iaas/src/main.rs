@@ -1,11 +1,27 @@
+mod config;
 mod domain;
 mod application;
 mod infrastructure;
 
 use std::sync::Arc;
-use crate::application::{ServerService, ManageServers};
-use crate::infrastructure::persistence::JsonServerRepository;
-use crate::infrastructure::web::routes;
+use std::time::Duration;
+use crate::application::{
+    ActivityService, ApiKeyService, ApplyManifests, ApplyService, ChangesService, ConsoleLog, ConsoleService, ManageApiKeys, ManageNotifications, ManageServers,
+    ManageUsers, ManageVolumes, NotificationService, NotifyingActivityLog, ReadConsole, ServerService, SyncServers, UserService, VerifyTokens, ViewActivity,
+    VolumeService,
+};
+use crate::config::AppConfig;
+use crate::domain::{ActivityLog, ServerRepository, VolumeRepository};
+use crate::infrastructure::hypervisor::SimulatedHypervisor;
+use crate::infrastructure::notifiers::{SlackWebhookNotifier, SmtpNotifier};
+use crate::infrastructure::oidc::{HttpJwksSource, OidcConfig, OidcVerifier};
+use crate::infrastructure::passwords::{Argon2PasswordHasher, Sha256SecretHasher};
+use crate::infrastructure::persistence::{
+    ChangeTrackingRepository, CircuitBreaker, JsonApiKeyRepository, EncryptedRepository, FaultConfig, FaultInjector, FaultyRepository,
+    FileConsoleLog, FileDocumentStore, JsonActivityLog, JsonChangeLog, JsonNotificationRuleRepository, JsonServerRepository, JsonUserRepository, JsonVolumeRepository,
+    ResilientRepository, RetryPolicy, StaticKeyProvider,
+};
+use crate::infrastructure::web::{routes, AuthConfig, StaticApiKey, WebConfig};
 
 /// THE ENTRY POINT
 /// --- Good to know ---
@@ -22,22 +38,146 @@ use crate::infrastructure::web::routes;
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     
+    let config = AppConfig::from_env()?;
+
     // 1. Initialize Infrastructure (The OUTSIDE world)
-    let repo = JsonServerRepository::new("./storage")?;
+    // With encryption keys configured, documents are sealed before they touch the disk.
+    let repo: Arc<dyn ServerRepository> = match &config.encryption_keys {
+        Some(spec) => {
+            let store = Arc::new(FileDocumentStore::new(&config.storage_dir)?);
+            let keys = Arc::new(StaticKeyProvider::from_spec(spec)?);
+            let encrypted = EncryptedRepository::new(store, keys);
+            // Re-seal anything written with a retired key (or, if asked to, before encryption was on).
+            let rotated = encrypted.rotate_keys(config.encryption_migrate_plaintext).await?;
+            println!("Encryption at rest enabled ({} documents re-encrypted).", rotated);
+            Arc::new(encrypted)
+        }
+        None => Arc::new(JsonServerRepository::new(&config.storage_dir)?),
+    };
+
+    // Chaos mode: inject faults *below* the resilience layer, so we can watch it react.
+    let faults = config.chaos_enabled.then(|| {
+        Arc::new(FaultInjector::new(FaultConfig {
+            latency_ms: config.chaos_latency_ms,
+            error_rate: config.chaos_error_rate,
+            ..Default::default()
+        }))
+    });
+    let repo: Arc<dyn ServerRepository> = match &faults {
+        Some(faults) => {
+            println!("WARNING: chaos mode enabled, storage faults will be injected.");
+            Arc::new(FaultyRepository::new(repo, Arc::clone(faults)))
+        }
+        None => repo,
+    };
+
+    // Decorate storage with retries + a circuit breaker; /healthz reports the breaker.
+    let breaker = Arc::new(CircuitBreaker::default());
+    let repo: Arc<dyn ServerRepository> =
+        Arc::new(ResilientRepository::new(repo, Arc::clone(&breaker), RetryPolicy::default()));
+
+    // Outermost: only writes that finally succeeded (after retries) end up in the change-log.
+    let change_log = Arc::new(JsonChangeLog::new(&config.storage_dir)?);
+    let repo: Arc<dyn ServerRepository> = Arc::new(ChangeTrackingRepository::new(repo, change_log.clone()));
     
     // 2. Initialize Application Core (The INSIDE world)
     // Dependency Injection: We create the Service and "inject" the repository into it.
     // In Python, you'd just pass the repo to the constructor. 
     // In Go, you'd pass a struct that satisfies the interface.
     // In Rust, we wrap it in Arc (Atomic Reference Counter) so it can be shared safely with the web server.
-    let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(Arc::new(repo)));
+    // Every change is also appended to the activity log behind GET /activity,
+    // which doubles as the event stream that notification rules listen to.
+    let mut notifications = NotificationService::new(Arc::new(JsonNotificationRuleRepository::new(&config.storage_dir)?))
+        .with_slack(Arc::new(SlackWebhookNotifier::new()?));
+    if let Some(smtp_url) = &config.smtp_url {
+        notifications = notifications.with_email(Arc::new(SmtpNotifier::new(smtp_url, &config.smtp_from)?));
+        println!("E-mail notifications enabled (from {}).", config.smtp_from);
+    }
+    let notifications = Arc::new(notifications);
+    let activity_log: Arc<dyn ActivityLog> = Arc::new(NotifyingActivityLog::new(
+        Arc::new(JsonActivityLog::new(&config.storage_dir)?),
+        Arc::clone(&notifications),
+    ));
+    // New servers are "booted" by a simulated hypervisor that writes their console log.
+    let console_log: Arc<dyn ConsoleLog> =
+        Arc::new(FileConsoleLog::new(&config.storage_dir, config.console_log_max_bytes)?);
+    let hypervisor = Arc::new(SimulatedHypervisor::new(Arc::clone(&console_log), Duration::from_millis(250)));
+    let volume_repo: Arc<dyn VolumeRepository> = Arc::new(JsonVolumeRepository::new(&config.storage_dir)?);
+    let servers = ServerService::new(Arc::clone(&repo))
+        .with_activity_log(activity_log.clone())
+        .with_hypervisor(hypervisor)
+        .with_volumes(Arc::clone(&volume_repo))
+        .with_zones(config.zones.clone());
+    // Volume attach/detach share the servers' lock, so deletes can't strand a volume.
+    let server_lock = servers.placement_lock();
+    let service: Arc<dyn ManageServers> = Arc::new(servers);
+    // Manifests are applied through the same use cases (and rules) as single requests.
+    let apply: Arc<dyn ApplyManifests> = Arc::new(ApplyService::new(Arc::clone(&service)));
+    let console: Arc<dyn ReadConsole> = Arc::new(ConsoleService::new(Arc::clone(&repo), console_log));
+    // Volumes live on their own but look servers up (through the same resilient chain) to attach.
+    let volumes: Arc<dyn ManageVolumes> = Arc::new(
+        VolumeService::new(volume_repo, repo)
+            .with_server_lock(server_lock)
+            .with_activity_log(activity_log.clone()),
+    );
+    let users: Arc<dyn ManageUsers> = Arc::new(
+        UserService::new(
+            Arc::new(JsonUserRepository::new(&config.storage_dir)?),
+            Arc::new(Argon2PasswordHasher::default()),
+        )
+        .with_activity_log(activity_log.clone()),
+    );
+    // Managed keys are long random secrets, so a fast hash is enough (unlike passwords).
+    let api_keys: Arc<dyn ManageApiKeys> = Arc::new(
+        ApiKeyService::new(Arc::new(JsonApiKeyRepository::new(&config.storage_dir)?), Arc::new(Sha256SecretHasher))
+            .with_activity_log(activity_log.clone()),
+    );
+    let activity: Arc<dyn ViewActivity> = Arc::new(ActivityService::new(activity_log));
+    let changes: Arc<dyn SyncServers> = Arc::new(ChangesService::new(change_log));
     
+    // SSO: bearer tokens are checked against the identity provider's published keys.
+    let tokens = match &config.oidc {
+        Some(oidc) => {
+            let jwks = Arc::new(HttpJwksSource::new(&oidc.issuer, oidc.jwks_url.clone())?);
+            let oidc_config = OidcConfig {
+                issuer: oidc.issuer.clone(),
+                audience: oidc.audience.clone(),
+                role_claim: oidc.role_claim.clone(),
+                role_map: OidcConfig::parse_role_map(&oidc.role_map)?,
+                algorithms: OidcConfig::parse_algorithms(&oidc.algorithms)?,
+            };
+            println!("OIDC authentication enabled (issuer {}).", oidc.issuer);
+            Some(Arc::new(OidcVerifier::new(oidc_config, jwks)) as Arc<dyn VerifyTokens>)
+        }
+        None => None,
+    };
+
     // 3. Setup the Driving Adapter (The WEB server)
-    let api = routes(service);
+    let web_config = WebConfig {
+        metadata_link_local: config.metadata_link_local,
+        health_checks: vec![breaker],
+        faults,
+        users: Some(users),
+        auth: AuthConfig {
+            api_key: config.auth_mode.accepts_api_key(),
+            static_key: config.api_key.as_ref().map(|key| StaticApiKey::new(&key.secret, key.scopes.clone())),
+            api_keys: Some(api_keys),
+            tokens,
+        },
+        activity: Some(activity),
+        volumes: Some(volumes),
+        console: Some(console),
+        changes: Some(changes),
+        apply: Some(apply),
+        notifications: Some(notifications as Arc<dyn ManageNotifications>),
+    };
+    let api = routes(service, web_config);
     
     println!("IaaS Platform API running at http://127.0.0.1:8080");
     println!("- POST /servers : Create a server");
     println!("- GET  /servers : List all servers");
+    println!("- GET  /readyz  : Readiness probe");
+    println!("- GET  /healthz : Component health (storage circuit breaker)");
     
     // 4. Start Server: This is a blocking call (Infinite loop).
     warp::serve(api)
@@ -52,6 +192,17 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
     use crate::application::{CreateServerCommand, AttachDiskCommand};
+    use crate::domain::Scope;
+
+    /// The static key the tests authenticate with, configured as an admin key.
+    fn test_auth() -> AuthConfig {
+        let static_key = StaticApiKey::new("iaas-secret-key-123", [Scope::Admin].into());
+        AuthConfig { static_key: Some(static_key), ..Default::default() }
+    }
+
+    fn test_web_config() -> WebConfig {
+        WebConfig { auth: test_auth(), ..Default::default() }
+    }
     
     /// Integration Test: Verifies that the whole chain (Core -> Repo -> Filesystem) works.
     #[tokio::test]
@@ -69,6 +220,7 @@ mod tests {
             cpu: 4,
             ram: 16,
             storage: 250,
+            ..Default::default()
         };
         let server = service.create_server(cmd).await?;
 
@@ -84,7 +236,7 @@ mod tests {
     }
 
     /// Integration Test: Verifies that attaching a disk persists the state correctly.
-    #[tokio::test]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn test_disk_attachment() -> anyhow::Result<()> {
         let test_dir = tempdir()?;
         let test_dir_path = test_dir.path().to_str().unwrap();
@@ -97,6 +249,7 @@ mod tests {
             cpu: 2,
             ram: 4,
             storage: 40,
+            ..Default::default()
         };
         let server = service.create_server(create_cmd).await?;
 
@@ -104,6 +257,9 @@ mod tests {
         let attach_cmd = AttachDiskCommand {
             server_id: server.id,
             size_gb: 100,
+            disk_type: crate::domain::DiskType::Standard,
+            iops: None,
+            dry_run: false,
         };
         let updated_server = service.attach_disk(attach_cmd).await?;
 
@@ -111,6 +267,58 @@ mod tests {
         assert_eq!(updated_server.additional_disks.len(), 1);
         assert_eq!(updated_server.additional_disks[0].size_gb, 100);
 
+        // Racing attaches each add their disk; none is lost to a stale copy.
+        let mut racers = Vec::new();
+        for size_gb in [20, 30, 40] {
+            let service = Arc::clone(&service);
+            let disk_type = crate::domain::DiskType::Standard;
+            let cmd = AttachDiskCommand { server_id: server.id, size_gb, disk_type, iops: None, dry_run: false };
+            racers.push(tokio::spawn(async move { service.attach_disk(cmd).await }));
+        }
+        for racer in racers {
+            racer.await??;
+        }
+        let stored = service.get_server(server.id).await?;
+        let mut sizes: Vec<u32> = stored.additional_disks.iter().map(|d| d.size_gb).collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![20, 30, 40, 100]);
+
+        Ok(())
+    }
+
+    /// Disk Types Test: The attach endpoint accepts a tier, reports performance and enforces flavor rules.
+    #[tokio::test]
+    async fn test_attach_typed_disks_via_api() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let repo = Arc::new(JsonServerRepository::new(test_dir.path().to_str().unwrap())?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let server = service
+            .create_server(CreateServerCommand { name: "small".to_string(), cpu: 2, ram: 4, storage: 20, ..Default::default() })
+            .await?;
+        let api = routes(Arc::clone(&service), test_web_config());
+
+        let attach = |body: serde_json::Value| {
+            warp::test::request()
+                .method("POST")
+                .header("x-api-key", "iaas-secret-key-123")
+                .path(&format!("/servers/{}/disks", server.id))
+                .json(&body)
+        };
+
+        let resp = attach(serde_json::json!({ "size_gb": 200, "disk_type": "ssd", "iops": 6000 })).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["disks"][0]["disk_type"], "ssd");
+        assert_eq!(body["disks"][0]["iops"], 6000);
+        assert_eq!(body["disks"][0]["throughput_mbps"], 250);
+
+        // NVMe needs a bigger flavor; out-of-range IOPS are rejected too.
+        let resp = attach(serde_json::json!({ "size_gb": 200, "disk_type": "nvme" })).reply(&api).await;
+        assert_eq!(resp.status(), 400);
+        let resp = attach(serde_json::json!({ "size_gb": 200, "iops": 9000 })).reply(&api).await;
+        assert_eq!(resp.status(), 400);
+
+        assert_eq!(service.get_server(server.id).await?.additional_disks.len(), 1);
         Ok(())
     }
 
@@ -122,7 +330,7 @@ mod tests {
         let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
         let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
         
-        let api = routes(service);
+        let api = routes(service, test_web_config());
 
         // Request the OpenAPI JSON
         let resp = warp::test::request()
@@ -143,6 +351,29 @@ mod tests {
         Ok(())
     }
 
+    /// Dashboard Test: The embedded UI is served without a key and may run its own script.
+    #[tokio::test]
+    async fn test_dashboard_is_served() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let repo = Arc::new(JsonServerRepository::new(test_dir.path().to_str().unwrap())?);
+        let api = routes(Arc::new(ServerService::new(repo)), test_web_config());
+
+        let resp = warp::test::request().path("/ui").reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        assert!(resp.headers()["content-type"].to_str()?.starts_with("text/html"));
+        assert!(resp.headers()["content-security-policy"].to_str()?.contains("script-src 'self'"));
+        assert!(std::str::from_utf8(resp.body())?.contains("/ui/app.js"));
+
+        let resp = warp::test::request().path("/ui/app.js").reply(&api).await;
+        assert!(resp.headers()["content-type"].to_str()?.contains("javascript"));
+        assert_eq!(warp::test::request().path("/ui/missing.js").reply(&api).await.status(), 404);
+
+        // The API itself keeps the strict policy.
+        let resp = warp::test::request().path("/healthz").reply(&api).await;
+        assert_eq!(resp.headers()["content-security-policy"], "default-src 'none'");
+        Ok(())
+    }
+
     /// Security Test: Verifies that missing API Key results in 401 Unauthorized.
     #[tokio::test]
     async fn test_security_unauthorized() -> anyhow::Result<()> {
@@ -150,7 +381,7 @@ mod tests {
         let test_dir_path = test_dir.path().to_str().unwrap();
         let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
         let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
-        let api = routes(service);
+        let api = routes(service, test_web_config());
 
         // Request WITHOUT the x-api-key header
         let resp = warp::test::request()
@@ -162,4 +393,1137 @@ mod tests {
         assert_eq!(resp.status(), 401);
         Ok(())
     }
+
+    /// Static Key Test: The configured key grants only its scopes; no key is built in.
+    #[tokio::test]
+    async fn test_static_api_key_has_explicit_scopes() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let static_key = StaticApiKey::new("ci-reader-key", [Scope::ServersRead].into());
+        let auth = AuthConfig { static_key: Some(static_key), ..Default::default() };
+        let api = routes(Arc::clone(&service), WebConfig { auth, ..Default::default() });
+
+        let list = |key: &str| warp::test::request().method("GET").header("x-api-key", key).path("/servers");
+        assert_eq!(list("ci-reader-key").reply(&api).await.status(), 200);
+        assert_eq!(list("ci-reader-ke").reply(&api).await.status(), 401);
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "ci-reader-key")
+            .path("/servers")
+            .json(&serde_json::json!({ "name": "vm-ci", "cpu": 1, "ram": 1, "storage": 10 }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 403);
+
+        // Without configuration there is no static key at all.
+        let api = routes(service, WebConfig::default());
+        assert_eq!(list("iaas-secret-key-123").reply(&api).await.status(), 401);
+        Ok(())
+    }
+
+    /// Maintenance Test: Writes are drained with 503 + Retry-After, reads keep working.
+    #[tokio::test]
+    async fn test_maintenance_mode_drains_writes() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let api = routes(service, test_web_config());
+
+        // Turn maintenance on
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/admin/maintenance")
+            .json(&serde_json::json!({ "enabled": true }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        // Writes are refused with a retry hint
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers")
+            .json(&serde_json::json!({ "name": "vm", "cpu": 1, "ram": 1, "storage": 10 }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 503);
+        assert!(resp.headers().contains_key("retry-after"));
+
+        // Reads continue
+        let resp = warp::test::request()
+            .method("GET")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        // Readiness reports the drain
+        let resp = warp::test::request().method("GET").path("/readyz").reply(&api).await;
+        assert_eq!(resp.status(), 503);
+        assert!(std::str::from_utf8(resp.body())?.contains("maintenance"));
+
+        // Turn maintenance off again
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/admin/maintenance")
+            .json(&serde_json::json!({ "enabled": false }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request().method("GET").path("/readyz").reply(&api).await;
+        assert_eq!(resp.status(), 200);
+
+        Ok(())
+    }
+
+    /// Uniqueness Test: A duplicate name yields 409 with the conflicting ID; lookup by name works.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_unique_names_and_lookup_by_name() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let api = routes(Arc::clone(&service), test_web_config());
+
+        let first = service
+            .create_server(CreateServerCommand {
+                name: "web-01".to_string(),
+                cpu: 1,
+                ram: 1,
+                storage: 10,
+                ..Default::default()
+            })
+            .await?;
+
+        // Creating the same name again is a conflict that points at the original.
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers")
+            .json(&serde_json::json!({ "name": " web-01 ", "cpu": 2, "ram": 2, "storage": 20 }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 409);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["conflicting_id"], first.id.to_string());
+
+        // Racing creates of one name: exactly one wins.
+        let mut racers = Vec::new();
+        for _ in 0..8 {
+            let service = Arc::clone(&service);
+            racers.push(tokio::spawn(async move {
+                let cmd = CreateServerCommand { name: "web-02".to_string(), cpu: 1, ram: 1, storage: 10, ..Default::default() };
+                service.create_server(cmd).await.is_ok()
+            }));
+        }
+        let mut created = 0;
+        for racer in racers {
+            created += usize::from(racer.await?);
+        }
+        assert_eq!(created, 1);
+
+        // Lookup by name
+        let resp = warp::test::request()
+            .method("GET")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers/by-name/web-01")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["id"], first.id.to_string());
+
+        let resp = warp::test::request()
+            .method("GET")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers/by-name/missing")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        Ok(())
+    }
+
+    /// Metadata Test: Guest-facing metadata, authenticated by default and opt-in without a key.
+    #[tokio::test]
+    async fn test_instance_metadata() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+
+        let server = service
+            .create_server(CreateServerCommand {
+                name: "meta-vm".to_string(),
+                cpu: 2,
+                ram: 8,
+                storage: 50,
+                ssh_keys: vec!["ssh-ed25519 AAAA demo@laptop".to_string()],
+                user_data: Some("#!/bin/sh\necho hi".to_string()),
+                ..Default::default()
+            })
+            .await?;
+
+        let api = routes(Arc::clone(&service), test_web_config());
+        let resp = warp::test::request()
+            .method("GET")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path(&format!("/metadata/{}", server.id))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["hostname"], "meta-vm");
+        assert_eq!(body["instance_type"], "c2.r8");
+        assert_eq!(body["public_keys"][0], "ssh-ed25519 AAAA demo@laptop");
+        assert_eq!(body["local_ipv4"], server.private_ip().to_string());
+
+        // The link-local variant is off by default...
+        let link_local_path = format!("/latest/meta-data/{}", server.id);
+        let resp = warp::test::request().method("GET").path(&link_local_path).reply(&api).await;
+        assert_ne!(resp.status(), 200);
+
+        // ...and needs no API key once enabled.
+        let lab_api = routes(service, WebConfig { metadata_link_local: true, ..test_web_config() });
+        let resp = warp::test::request().method("GET").path(&link_local_path).reply(&lab_api).await;
+        assert_eq!(resp.status(), 200);
+
+        Ok(())
+    }
+
+    /// Health Test: /healthz reports the storage circuit breaker.
+    #[tokio::test]
+    async fn test_healthz_reports_storage_circuit() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let breaker = Arc::new(CircuitBreaker::default());
+        let repo = Arc::new(ResilientRepository::new(
+            Arc::new(JsonServerRepository::new(test_dir_path)?),
+            Arc::clone(&breaker),
+            RetryPolicy::default(),
+        ));
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let api = routes(service, WebConfig { health_checks: vec![breaker], ..test_web_config() });
+
+        let resp = warp::test::request().method("GET").path("/healthz").reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["components"][0]["component"], "storage");
+        assert_eq!(body["components"][0]["details"], "circuit closed");
+
+        Ok(())
+    }
+
+    /// Chaos Test: Faults configured through the admin endpoint reach the storage layer.
+    #[tokio::test]
+    async fn test_fault_injection_via_admin_endpoint() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let faults = Arc::new(FaultInjector::default());
+        let repo = Arc::new(FaultyRepository::new(
+            Arc::new(JsonServerRepository::new(test_dir_path)?),
+            Arc::clone(&faults),
+        ));
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let api = routes(service, WebConfig { faults: Some(faults), ..test_web_config() });
+
+        // Break writes only.
+        let resp = warp::test::request()
+            .method("PUT")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/admin/faults")
+            .json(&serde_json::json!({ "error_rate": 1.0, "scope": "writes" }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers")
+            .json(&serde_json::json!({ "name": "vm", "cpu": 1, "ram": 1, "storage": 10 }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 500);
+
+        let resp = warp::test::request()
+            .method("GET")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        // Out-of-range settings are rejected.
+        let resp = warp::test::request()
+            .method("PUT")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/admin/faults")
+            .json(&serde_json::json!({ "error_rate": 2.0 }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        Ok(())
+    }
+
+    /// Error Format Test: Failures are RFC 7807 problem documents with a request ID.
+    #[tokio::test]
+    async fn test_errors_use_problem_json() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let api = routes(service, test_web_config());
+
+        let resp = warp::test::request().method("GET").path("/servers").reply(&api).await;
+        assert_eq!(resp.status(), 401);
+        assert_eq!(resp.headers()["content-type"], "application/problem+json");
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["type"], "/problems/unauthorized");
+        assert_eq!(body["title"], "Unauthorized");
+        assert_eq!(body["status"], 401);
+        assert_eq!(resp.headers()["x-request-id"], body["request_id"].as_str().unwrap());
+        assert_eq!(body["instance"], format!("urn:uuid:{}", body["request_id"].as_str().unwrap()));
+
+        // Malformed bodies are a client error, not a 500.
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/servers")
+            .json(&serde_json::json!({ "name": "missing-sizes" }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 400);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["type"], "/problems/invalid-body");
+
+        Ok(())
+    }
+
+    /// User Management Test: Create, reject duplicates/weak passwords, disable and re-enable.
+    #[tokio::test]
+    async fn test_user_management() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let users: Arc<dyn ManageUsers> = Arc::new(UserService::new(
+            Arc::new(JsonUserRepository::new(test_dir_path)?),
+            Arc::new(Argon2PasswordHasher::default()),
+        ));
+        let api = routes(service, WebConfig { users: Some(users), ..test_web_config() });
+
+        let create = |body: serde_json::Value| {
+            warp::test::request()
+                .method("POST")
+                .header("x-api-key", "iaas-secret-key-123")
+                .path("/users")
+                .json(&body)
+        };
+
+        let resp = create(serde_json::json!({ "username": "alice", "password": "a-long-passphrase", "role": "operator" }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["role"], "operator");
+        assert!(body.get("password_hash").is_none());
+        let alice_id = body["id"].as_str().unwrap().to_string();
+
+        // The hash, not the password, is what lands on disk.
+        let stored = std::fs::read_to_string(test_dir.path().join("users").join(format!("{}.json", alice_id)))?;
+        assert!(stored.contains("$argon2id$"));
+        assert!(!stored.contains("a-long-passphrase"));
+
+        let resp = create(serde_json::json!({ "username": "alice", "password": "another-long-one", "role": "viewer" }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 409);
+
+        let resp = create(serde_json::json!({ "username": "bob", "password": "short", "role": "viewer" }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        // Disabled accounts can no longer authenticate.
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path(&format!("/users/{}/disable", alice_id))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let login = |password: &str| {
+            warp::test::request()
+                .method("POST")
+                .path("/auth/login")
+                .json(&serde_json::json!({ "username": "alice", "password": password }))
+        };
+        assert_eq!(login("a-long-passphrase").reply(&api).await.status(), 401);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path(&format!("/users/{}/enable", alice_id))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(login("a-long-passphrase").reply(&api).await.status(), 200);
+        assert_eq!(login("wrong-passphrase").reply(&api).await.status(), 401);
+
+        // Five failures (MAX_FAILED_LOGINS) lock the username, even for the right password.
+        for _ in 1..5 {
+            assert_eq!(login("wrong-passphrase").reply(&api).await.status(), 401);
+        }
+        let resp = login("a-long-passphrase").reply(&api).await;
+        assert_eq!(resp.status(), 429);
+        assert!(resp.headers().contains_key("retry-after"));
+
+        // Unknown usernames fail (and lock) the same way, so they can't be told apart.
+        let stranger = |password: &str| {
+            warp::test::request()
+                .method("POST")
+                .path("/auth/login")
+                .json(&serde_json::json!({ "username": " mallory ", "password": password }))
+        };
+        for _ in 0..5 {
+            assert_eq!(stranger("a-long-passphrase").reply(&api).await.status(), 401);
+        }
+        assert_eq!(stranger("a-long-passphrase").reply(&api).await.status(), 429);
+
+        Ok(())
+    }
+
+    /// API Key Scopes Test: Keys only open the routes their scopes cover, until revoked.
+    #[tokio::test]
+    async fn test_api_key_scopes() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let api_keys: Arc<dyn ManageApiKeys> = Arc::new(ApiKeyService::new(
+            Arc::new(JsonApiKeyRepository::new(test_dir_path)?),
+            Arc::new(Sha256SecretHasher),
+        ));
+        let auth = AuthConfig { api_keys: Some(api_keys), ..test_auth() };
+        let api = routes(service, WebConfig { auth, ..test_web_config() });
+
+        let issue = |scopes: serde_json::Value| {
+            warp::test::request()
+                .method("POST")
+                .header("x-api-key", "iaas-secret-key-123")
+                .path("/api-keys")
+                .json(&serde_json::json!({ "name": "ci", "scopes": scopes }))
+        };
+        let resp = issue(serde_json::json!(["servers:read"])).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["scopes"], serde_json::json!(["servers:read"]));
+        let reader = body["key"].as_str().unwrap().to_string();
+        assert_eq!(issue(serde_json::json!([])).reply(&api).await.status(), 400);
+
+        let create = |key: &str| {
+            warp::test::request()
+                .method("POST")
+                .header("x-api-key", key)
+                .path("/servers")
+                .json(&serde_json::json!({ "name": "vm-ci", "cpu": 1, "ram": 1, "storage": 10 }))
+        };
+        let list = |key: &str| warp::test::request().method("GET").header("x-api-key", key).path("/servers");
+        assert_eq!(list(&reader).reply(&api).await.status(), 200);
+        assert_eq!(create(&reader).reply(&api).await.status(), 403);
+        let resp = warp::test::request().method("GET").header("x-api-key", &reader).path("/api-keys").reply(&api).await;
+        assert_eq!(resp.status(), 403);
+        // The activity feed shows user and key events, so it is admin-only too.
+        let resp = warp::test::request().method("GET").header("x-api-key", &reader).path("/activity").reply(&api).await;
+        assert_eq!(resp.status(), 403);
+        // Metadata carries SSH keys and user-data, so reading servers isn't enough.
+        let metadata_path = format!("/metadata/{}", uuid::Uuid::new_v4());
+        let resp = warp::test::request().method("GET").header("x-api-key", &reader).path(&metadata_path).reply(&api).await;
+        assert_eq!(resp.status(), 403);
+
+        let resp = issue(serde_json::json!(["servers:write"])).reply(&api).await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        let writer = body["key"].as_str().unwrap().to_string();
+        assert_eq!(create(&writer).reply(&api).await.status(), 200);
+
+        // Listing never shows secrets; a revoked key stops authenticating at all.
+        let resp = warp::test::request()
+            .method("GET")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path("/api-keys")
+            .reply(&api)
+            .await;
+        let keys: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(keys.as_array().unwrap().len(), 2);
+        assert!(keys[0].get("key").is_none() && keys[0].get("secret_hash").is_none());
+        let resp = warp::test::request()
+            .method("POST")
+            .header("x-api-key", "iaas-secret-key-123")
+            .path(&format!("/api-keys/{}/revoke", body["id"].as_str().unwrap()))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(list(&writer).reply(&api).await.status(), 401);
+        Ok(())
+    }
+
+    /// Notification Rules Test: Admins manage rules; bad events and channels are rejected.
+    #[tokio::test]
+    async fn test_notification_rules() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(Arc::new(JsonServerRepository::new(test_dir_path)?)));
+        let notifications: Arc<dyn ManageNotifications> = Arc::new(
+            NotificationService::new(Arc::new(JsonNotificationRuleRepository::new(test_dir_path)?))
+                .with_slack(Arc::new(SlackWebhookNotifier::new()?)),
+        );
+        let api = routes(service, WebConfig { notifications: Some(notifications), ..test_web_config() });
+
+        let create = |events: serde_json::Value, channel: serde_json::Value| {
+            warp::test::request()
+                .method("POST")
+                .header("x-api-key", "iaas-secret-key-123")
+                .path("/notifications/rules")
+                .json(&serde_json::json!({ "name": "on-call", "events": events, "channel": channel }))
+        };
+        let slack = serde_json::json!({ "type": "slack", "webhook_url": "https://hooks.slack.com/services/T0/B0/x" });
+        let resp = create(serde_json::json!(["server_deleted", "provisioning_failed"]), slack.clone()).reply(&api).await;
+        assert_eq!(resp.status(), 201);
+        let rule: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(rule["channel"]["type"], "slack");
+
+        assert_eq!(create(serde_json::json!(["server_exploded"]), slack).reply(&api).await.status(), 400);
+        let http = serde_json::json!({ "type": "slack", "webhook_url": "http://example.com/hook" });
+        assert_eq!(create(serde_json::json!(["server_deleted"]), http).reply(&api).await.status(), 400);
+        // No SMTP server configured, so e-mail rules can't be delivered and are refused.
+        let email = serde_json::json!({ "type": "email", "to": "ops@example.com" });
+        assert_eq!(create(serde_json::json!(["server_deleted"]), email).reply(&api).await.status(), 400);
+
+        let list = warp::test::request().header("x-api-key", "iaas-secret-key-123").path("/notifications/rules");
+        let rules: serde_json::Value = serde_json::from_slice(list.reply(&api).await.body())?;
+        assert_eq!(rules.as_array().unwrap().len(), 1);
+        let delete = || {
+            warp::test::request()
+                .method("DELETE")
+                .header("x-api-key", "iaas-secret-key-123")
+                .path(&format!("/notifications/rules/{}", rule["id"].as_str().unwrap()))
+        };
+        assert_eq!(delete().reply(&api).await.status(), 204);
+        assert_eq!(delete().reply(&api).await.status(), 404);
+        Ok(())
+    }
+
+    /// Activity Feed Test: Events are recorded on change and paged with stable cursors.
+    #[tokio::test]
+    async fn test_activity_feed_cursor_pagination() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let log = Arc::new(JsonActivityLog::new(test_dir_path)?);
+        let repo = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo).with_activity_log(log.clone()));
+        let activity: Arc<dyn ViewActivity> = Arc::new(ActivityService::new(log));
+        let api = routes(Arc::clone(&service), WebConfig { activity: Some(activity), ..test_web_config() });
+
+        // Each create records "server_created" + "status_changed": 4 events.
+        for name in ["vm-a", "vm-b"] {
+            service
+                .create_server(CreateServerCommand { name: name.to_string(), cpu: 1, ram: 1, storage: 10, ..Default::default() })
+                .await?;
+        }
+
+        let page = |query: String| {
+            warp::test::request()
+                .method("GET")
+                .header("x-api-key", "iaas-secret-key-123")
+                .path(&format!("/activity{}", query))
+        };
+        let resp = page("?limit=3".to_string()).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let first: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(first["items"].as_array().unwrap().len(), 3);
+        assert_eq!(first["items"][0]["event"], "status_changed");
+        assert_eq!(first["items"][1]["message"], "Server 'vm-b' created");
+        let cursor = first["next_cursor"].as_str().unwrap().to_string();
+
+        // New activity does not shift the next page: the cursor is anchored to an event.
+        service
+            .create_server(CreateServerCommand { name: "vm-c".to_string(), cpu: 1, ram: 1, storage: 10, ..Default::default() })
+            .await?;
+        let resp = page(format!("?limit=3&cursor={}", cursor)).reply(&api).await;
+        let second: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(second["items"].as_array().unwrap().len(), 1);
+        assert_eq!(second["items"][0]["message"], "Server 'vm-a' created");
+        assert!(second["next_cursor"].is_null());
+
+        let resp = page("?cursor=not-a-cursor".to_string()).reply(&api).await;
+        assert_eq!(resp.status(), 400);
+        Ok(())
+    }
+
+    /// Volume Test: A volume attaches to one server at a time and can't be deleted while attached.
+    #[tokio::test]
+    async fn test_volume_lifecycle() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo: Arc<dyn ServerRepository> = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(Arc::clone(&repo)));
+        let volumes: Arc<dyn ManageVolumes> =
+            Arc::new(VolumeService::new(Arc::new(JsonVolumeRepository::new(test_dir_path)?), repo));
+        let api = routes(Arc::clone(&service), WebConfig { volumes: Some(volumes), ..test_web_config() });
+
+        let mut servers = Vec::new();
+        for name in ["app-1", "app-2"] {
+            let cmd = CreateServerCommand { name: name.to_string(), cpu: 2, ram: 4, storage: 40, ..Default::default() };
+            servers.push(service.create_server(cmd).await?.id);
+        }
+        let call = |method: &str, path: String, body: serde_json::Value| {
+            warp::test::request()
+                .method(method)
+                .header("x-api-key", "iaas-secret-key-123")
+                .path(&path)
+                .json(&body)
+        };
+
+        let resp = call("POST", "/volumes".to_string(), serde_json::json!({ "name": "data", "size_gb": 100, "disk_type": "ssd" }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let volume: serde_json::Value = serde_json::from_slice(resp.body())?;
+        let volume_id = volume["id"].as_str().unwrap().to_string();
+        assert!(volume["attached_to"].is_null());
+
+        let attach = |server_id: uuid::Uuid| {
+            call("POST", format!("/volumes/{}/attach", volume_id), serde_json::json!({ "server_id": server_id }))
+        };
+        let resp = attach(servers[0]).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let attached: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(attached["attached_to"], servers[0].to_string());
+        // Re-attaching to the same server is idempotent.
+        assert_eq!(attach(servers[0]).reply(&api).await.status(), 200);
+
+        // A second server can't grab it; the problem names the current owner.
+        let resp = attach(servers[1]).reply(&api).await;
+        assert_eq!(resp.status(), 409);
+        let problem: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(problem["conflicting_id"], servers[0].to_string());
+
+        let delete = || call("DELETE", format!("/volumes/{}", volume_id), serde_json::json!({}));
+        assert_eq!(delete().reply(&api).await.status(), 409);
+
+        let resp = call("POST", format!("/volumes/{}/detach", volume_id), serde_json::json!({})).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(attach(servers[1]).reply(&api).await.status(), 200);
+        call("POST", format!("/volumes/{}/detach", volume_id), serde_json::json!({})).reply(&api).await;
+
+        assert_eq!(delete().reply(&api).await.status(), 204);
+        let resp = call("GET", format!("/volumes/{}", volume_id), serde_json::json!({})).reply(&api).await;
+        assert_eq!(resp.status(), 404);
+        Ok(())
+    }
+
+    /// Volume Race Test: An attach racing a server delete never leaves the volume on a missing server.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_volume_attach_racing_server_delete() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo: Arc<dyn ServerRepository> = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let volume_repo: Arc<dyn VolumeRepository> = Arc::new(JsonVolumeRepository::new(test_dir_path)?);
+        let servers = ServerService::new(Arc::clone(&repo)).with_volumes(Arc::clone(&volume_repo));
+        let volumes: Arc<dyn ManageVolumes> =
+            Arc::new(VolumeService::new(volume_repo, repo).with_server_lock(servers.placement_lock()));
+        let service: Arc<dyn ManageServers> = Arc::new(servers);
+
+        for round in 0..20 {
+            let cmd = CreateServerCommand { name: format!("vm-{}", round), cpu: 2, ram: 4, storage: 40, ..Default::default() };
+            let server_id = service.create_server(cmd).await?.id;
+            let cmd = crate::application::CreateVolumeCommand {
+                name: format!("vol-{}", round),
+                size_gb: 10,
+                disk_type: crate::domain::DiskType::Standard,
+                iops: None,
+                dry_run: false,
+            };
+            let volume_id = volumes.create_volume(cmd).await?.id;
+
+            let deleting = {
+                let service = Arc::clone(&service);
+                tokio::spawn(async move { service.delete_server(server_id, false).await })
+            };
+            let attaching = {
+                let volumes = Arc::clone(&volumes);
+                tokio::spawn(async move { volumes.attach_volume(volume_id, server_id, false).await })
+            };
+            let (deleted, _) = (deleting.await?, attaching.await?);
+
+            // Exactly one side wins: attached and kept, or deleted and left unattached.
+            let attached_to = volumes.get_volume(volume_id).await?.attached_to;
+            assert_eq!(deleted.is_ok(), attached_to.is_none());
+            assert_eq!(service.get_server(server_id).await.is_ok(), attached_to.is_some());
+        }
+        Ok(())
+    }
+
+    /// Console Test: Booting writes a console log that can be tailed and followed.
+    #[tokio::test]
+    async fn test_console_logs_tail_and_follow() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo: Arc<dyn ServerRepository> = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let console_log: Arc<dyn ConsoleLog> = Arc::new(FileConsoleLog::new(test_dir_path, 1024 * 1024)?);
+        let hypervisor = Arc::new(SimulatedHypervisor::new(Arc::clone(&console_log), Duration::ZERO));
+        let service: Arc<dyn ManageServers> =
+            Arc::new(ServerService::new(Arc::clone(&repo)).with_hypervisor(hypervisor));
+        let console: Arc<dyn ReadConsole> = Arc::new(ConsoleService::new(repo, Arc::clone(&console_log)));
+        let api = routes(Arc::clone(&service), WebConfig { console: Some(console), ..test_web_config() });
+
+        let server = service
+            .create_server(CreateServerCommand { name: "web-1".to_string(), cpu: 2, ram: 4, storage: 40, ..Default::default() })
+            .await?;
+        // Booting happens in the background; wait for the login prompt.
+        for _ in 0..100 {
+            if console_log.tail(server.id, 1).await?.first().is_some_and(|l| l.ends_with("login:")) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let get = |path: String| warp::test::request().header("x-api-key", "iaas-secret-key-123").path(&path);
+        let resp = get(format!("/servers/{}/logs?tail=2", server.id)).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        assert!(resp.headers()["content-type"].to_str()?.starts_with("text/plain"));
+        let text = String::from_utf8(resp.body().to_vec())?;
+        assert_eq!(text.lines().count(), 2);
+        assert_eq!(text.lines().last(), Some("web-1 login:"));
+        let resp = get(format!("/servers/{}/logs", uuid::Uuid::new_v4())).reply(&api).await;
+        assert_eq!(resp.status(), 404);
+
+        // Follow over a real connection: the backlog arrives first, then new lines as chunks.
+        let (addr, serving) = warp::serve(api).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(serving);
+        let mut resp = reqwest::Client::new()
+            .get(format!("http://{}/servers/{}/logs?tail=1&follow=true", addr, server.id))
+            .header("x-api-key", "iaas-secret-key-123")
+            .send()
+            .await?;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.chunk().await?.as_deref(), Some(&b"web-1 login:\n"[..]));
+        console_log.append(server.id, "web-1 login: root").await?;
+        assert_eq!(resp.chunk().await?.as_deref(), Some(&b"web-1 login: root\n"[..]));
+        Ok(())
+    }
+
+    /// Delta Sync Test: Agents only fetch what changed, and unchanged lists answer 304.
+    #[tokio::test]
+    async fn test_server_changes_and_conditional_get() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let change_log = Arc::new(JsonChangeLog::new(test_dir_path)?);
+        let repo: Arc<dyn ServerRepository> = Arc::new(ChangeTrackingRepository::new(
+            Arc::new(JsonServerRepository::new(test_dir_path)?),
+            change_log.clone(),
+        ));
+        let volume_repo: Arc<dyn VolumeRepository> = Arc::new(JsonVolumeRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> =
+            Arc::new(ServerService::new(Arc::clone(&repo)).with_volumes(Arc::clone(&volume_repo)));
+        let volumes: Arc<dyn ManageVolumes> = Arc::new(VolumeService::new(volume_repo, repo));
+        let changes: Arc<dyn SyncServers> = Arc::new(ChangesService::new(change_log));
+        let api = routes(
+            Arc::clone(&service),
+            WebConfig { changes: Some(changes), volumes: Some(volumes.clone()), ..test_web_config() },
+        );
+
+        let new_server = |name: &str| CreateServerCommand { name: name.to_string(), cpu: 1, ram: 1, storage: 10, ..Default::default() };
+        let kept = service.create_server(new_server("kept")).await?;
+        let doomed = service.create_server(new_server("doomed")).await?;
+
+        let request = |method: &str, path: String| {
+            warp::test::request().method(method).header("x-api-key", "iaas-secret-key-123").path(&path)
+        };
+        let resp = request("GET", "/servers/changes".to_string()).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["cache-control"], "no-store");
+        let sync: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(sync["created"].as_array().unwrap().len(), 2);
+        let cursor = sync["next_cursor"].as_str().unwrap().to_string();
+
+        // A server with an attached volume can't be deleted.
+        let volume = volumes
+            .create_volume(crate::application::CreateVolumeCommand {
+                name: "data".to_string(),
+                size_gb: 10,
+                disk_type: crate::domain::DiskType::Standard,
+                iops: None,
+                dry_run: false,
+            })
+            .await?;
+        volumes.attach_volume(volume.id, doomed.id, false).await?;
+        assert_eq!(request("DELETE", format!("/servers/{}", doomed.id)).reply(&api).await.status(), 409);
+        volumes.detach_volume(volume.id, false).await?;
+        assert_eq!(request("DELETE", format!("/servers/{}", doomed.id)).reply(&api).await.status(), 204);
+        assert_eq!(request("DELETE", format!("/servers/{}", doomed.id)).reply(&api).await.status(), 404);
+        service
+            .update_server(crate::application::UpdateServerCommand {
+                server_id: kept.id,
+                description: Some(Some("still here".to_string())),
+                ..Default::default()
+            })
+            .await?;
+
+        let resp = request("GET", format!("/servers/changes?since={}", cursor)).reply(&api).await;
+        let delta: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(delta["created"], serde_json::json!([]));
+        assert_eq!(delta["updated"], serde_json::json!([kept.id]));
+        assert_eq!(delta["deleted"], serde_json::json!([doomed.id]));
+        let resp = request("GET", "/servers/changes?since=garbage".to_string()).reply(&api).await;
+        assert_eq!(resp.status(), 400);
+
+        // Conditional GET: the same list revalidates to 304 until something changes.
+        let resp = request("GET", "/servers".to_string()).reply(&api).await;
+        let etag = resp.headers()["etag"].to_str()?.to_string();
+        let resp = request("GET", "/servers".to_string()).header("if-none-match", &etag).reply(&api).await;
+        assert_eq!(resp.status(), 304);
+        assert!(resp.body().is_empty());
+        service.create_server(new_server("another")).await?;
+        let resp = request("GET", "/servers".to_string()).header("if-none-match", &etag).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        Ok(())
+    }
+
+    /// Merge Patch Test: Partial updates, removals via null, and rejected immutable fields.
+    #[tokio::test]
+    async fn test_patch_server_with_merge_patch() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let repo = Arc::new(JsonServerRepository::new(test_dir.path().to_str().unwrap())?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let api = routes(Arc::clone(&service), test_web_config());
+
+        let mut tags = std::collections::BTreeMap::new();
+        tags.insert("team".to_string(), "core".to_string());
+        let server = service
+            .create_server(CreateServerCommand { name: "db-1".to_string(), cpu: 2, ram: 4, storage: 40, tags, ..Default::default() })
+            .await?;
+        service
+            .create_server(CreateServerCommand { name: "db-2".to_string(), cpu: 2, ram: 4, storage: 40, ..Default::default() })
+            .await?;
+
+        let patch = |body: serde_json::Value, content_type: &str| {
+            warp::test::request()
+                .method("PATCH")
+                .header("x-api-key", "iaas-secret-key-123")
+                .header("content-type", content_type)
+                .path(&format!("/servers/{}", server.id))
+                .body(body.to_string())
+        };
+        let merge_patch = "application/merge-patch+json";
+
+        let resp = patch(serde_json::json!({ "description": "primary", "tags": { "env": "prod" } }), merge_patch)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["name"], "db-1");
+        assert_eq!(body["description"], "primary");
+        assert_eq!(body["tags"], serde_json::json!({ "env": "prod", "team": "core" }));
+
+        // null removes; untouched fields survive.
+        let resp = patch(serde_json::json!({ "description": null, "tags": { "team": null } }), merge_patch)
+            .reply(&api)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert!(body["description"].is_null());
+        assert_eq!(body["tags"], serde_json::json!({ "env": "prod" }));
+
+        let resp = patch(serde_json::json!({ "id": uuid::Uuid::new_v4() }), merge_patch).reply(&api).await;
+        assert_eq!(resp.status(), 400);
+        let resp = patch(serde_json::json!({ "name": "db-2" }), merge_patch).reply(&api).await;
+        assert_eq!(resp.status(), 409);
+        let resp = patch(serde_json::json!({ "name": "db-primary" }), "application/json").reply(&api).await;
+        assert_eq!(resp.status(), 415);
+
+        assert_eq!(service.get_server(server.id).await?.name, "db-1");
+
+        // If-Match: a patch based on the current ETag applies, a stale one gets 412.
+        let resp = patch(serde_json::json!({}), merge_patch).reply(&api).await;
+        let etag = resp.headers()["etag"].to_str()?.to_string();
+        let conditional = |body: serde_json::Value, if_match: &str| patch(body, merge_patch).header("if-match", if_match);
+        let resp = conditional(serde_json::json!({ "description": "mine" }), &etag).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let resp = conditional(serde_json::json!({ "description": "theirs" }), &etag).reply(&api).await;
+        assert_eq!(resp.status(), 412);
+        assert_eq!(service.get_server(server.id).await?.description.as_deref(), Some("mine"));
+        Ok(())
+    }
+
+    /// Capacity Test: Servers fill zones in order; shortages are 409 (compute) or 507 (disk).
+    #[tokio::test]
+    async fn test_zone_capacity_and_placement_errors() -> anyhow::Result<()> {
+        use crate::domain::{Resources, Zone};
+        let test_dir = tempdir()?;
+        let repo = Arc::new(JsonServerRepository::new(test_dir.path().to_str().unwrap())?);
+        let zone = |name: &str, vcpu, ram_gb, disk_gb| Zone { name: name.to_string(), capacity: Resources { vcpu, ram_gb, disk_gb } };
+        let service: Arc<dyn ManageServers> =
+            Arc::new(ServerService::new(repo).with_zones(vec![zone("zone-a", 4, 8, 100), zone("zone-b", 2, 4, 50)]));
+        let api = routes(Arc::clone(&service), test_web_config());
+
+        let create = |body: serde_json::Value| {
+            warp::test::request().method("POST").header("x-api-key", "iaas-secret-key-123").path("/servers").json(&body)
+        };
+        let spec = |name: &str, cpu: u32, storage: u32| serde_json::json!({ "name": name, "cpu": cpu, "ram": cpu * 2, "storage": storage });
+        let resp = create(spec("big", 4, 50)).reply(&api).await;
+        let big: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(big["zone"], "zone-a");
+        let resp = create(spec("small", 2, 40)).reply(&api).await;
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(resp.body())?["zone"], "zone-b");
+
+        // Both zones are out of cores: a conflict, with the first shortage spelled out.
+        let resp = create(spec("one-more", 1, 10)).reply(&api).await;
+        assert_eq!(resp.status(), 409);
+        let problem: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!((problem["reason"].as_str(), problem["zone"].as_str()), (Some("insufficient_vcpu"), Some("zone-a")));
+        assert!(problem["detail"].as_str().unwrap().contains("zone 'zone-b' has 0 vCPU free"));
+
+        // Disks come out of the server's zone; zone-a has 50 GB left.
+        let attach = |size_gb: u32| {
+            warp::test::request()
+                .method("POST")
+                .header("x-api-key", "iaas-secret-key-123")
+                .path(&format!("/servers/{}/disks", big["id"].as_str().unwrap()))
+                .json(&serde_json::json!({ "size_gb": size_gb }))
+        };
+        let resp = attach(60).reply(&api).await;
+        assert_eq!(resp.status(), 507);
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(resp.body())?["reason"], "insufficient_disk");
+        assert_eq!(attach(50).reply(&api).await.status(), 200);
+
+        let unknown = serde_json::json!({ "name": "x", "cpu": 1, "ram": 1, "storage": 10, "zone": "zone-z" });
+        assert_eq!(create(unknown).reply(&api).await.status(), 400);
+
+        // Deleting frees the zone's share again.
+        service.delete_server(big["id"].as_str().unwrap().parse()?, false).await?;
+        let resp = create(spec("one-more", 1, 10)).reply(&api).await;
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(resp.body())?["zone"], "zone-a");
+        Ok(())
+    }
+
+    /// Dry Run Test: Mutating endpoints validate and preview, but persist nothing.
+    #[tokio::test]
+    async fn test_dry_run_changes_nothing() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let test_dir_path = test_dir.path().to_str().unwrap();
+        let repo: Arc<dyn ServerRepository> = Arc::new(JsonServerRepository::new(test_dir_path)?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(Arc::clone(&repo)));
+        let volumes: Arc<dyn ManageVolumes> =
+            Arc::new(VolumeService::new(Arc::new(JsonVolumeRepository::new(test_dir_path)?), repo));
+        let api = routes(Arc::clone(&service), WebConfig { volumes: Some(Arc::clone(&volumes)), ..test_web_config() });
+        let call = |method: &str, path: String, body: serde_json::Value| {
+            warp::test::request()
+                .method(method)
+                .header("x-api-key", "iaas-secret-key-123")
+                .path(&path)
+                .json(&body)
+        };
+
+        let new_server = serde_json::json!({ "name": "db", "cpu": 2, "ram": 4, "storage": 40 });
+        let resp = call("POST", "/servers?dry_run=true".to_string(), new_server.clone()).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let preview: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(preview["name"], "db");
+        assert!(service.list_servers().await?.is_empty());
+
+        let server = service
+            .create_server(CreateServerCommand { name: "db".to_string(), cpu: 2, ram: 4, storage: 40, ..Default::default() })
+            .await?;
+        assert_eq!(call("POST", "/servers?dry_run=true".to_string(), new_server).reply(&api).await.status(), 409);
+
+        // Placement rules still apply: NVMe needs a bigger flavor.
+        let disks = format!("/servers/{}/disks?dry_run=true", server.id);
+        let resp = call("POST", disks.clone(), serde_json::json!({ "size_gb": 100, "disk_type": "nvme" })).reply(&api).await;
+        assert_eq!(resp.status(), 400);
+        let resp = call("POST", disks, serde_json::json!({ "size_gb": 100, "disk_type": "ssd" })).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let preview: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(preview["disks"].as_array().unwrap().len(), 1);
+
+        let resp = warp::test::request()
+            .method("PATCH")
+            .header("x-api-key", "iaas-secret-key-123")
+            .header("content-type", "application/merge-patch+json")
+            .path(&format!("/servers/{}?dry_run=true", server.id))
+            .body(r#"{"name": "db-renamed"}"#)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let preview: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(preview["name"], "db-renamed");
+
+        let volume_body = serde_json::json!({ "name": "data", "size_gb": 10 });
+        assert_eq!(call("POST", "/volumes?dry_run=true".to_string(), volume_body).reply(&api).await.status(), 200);
+        assert!(volumes.list_volumes().await?.is_empty());
+        let volume = volumes
+            .create_volume(crate::application::CreateVolumeCommand {
+                name: "data".to_string(),
+                size_gb: 10,
+                disk_type: crate::domain::DiskType::Standard,
+                iops: None,
+                dry_run: false,
+            })
+            .await?;
+        let attach = format!("/volumes/{}/attach?dry_run=true", volume.id);
+        let resp = call("POST", attach, serde_json::json!({ "server_id": server.id })).reply(&api).await;
+        let preview: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(preview["attached_to"], server.id.to_string());
+        let delete = |path: String| warp::test::request().method("DELETE").header("x-api-key", "iaas-secret-key-123").path(&path);
+        assert_eq!(delete(format!("/volumes/{}?dry_run=true", volume.id)).reply(&api).await.status(), 204);
+        assert_eq!(delete(format!("/servers/{}?dry_run=true", server.id)).reply(&api).await.status(), 204);
+
+        // Nothing above was stored.
+        let stored = service.get_server(server.id).await?;
+        assert_eq!(stored.name, "db");
+        assert!(stored.additional_disks.is_empty());
+        assert!(volumes.get_volume(volume.id).await?.attached_to.is_none());
+        Ok(())
+    }
+
+    /// Apply Test: A manifest is planned, applied, converges, and is validated up front.
+    #[tokio::test]
+    async fn test_apply_manifest() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let repo = Arc::new(JsonServerRepository::new(test_dir.path().to_str().unwrap())?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let legacy = service
+            .create_server(CreateServerCommand { name: "legacy".to_string(), cpu: 1, ram: 1, storage: 10, ..Default::default() })
+            .await?;
+        let apply: Arc<dyn ApplyManifests> = Arc::new(ApplyService::new(Arc::clone(&service)));
+        let api = routes(Arc::clone(&service), WebConfig { apply: Some(apply), ..test_web_config() });
+
+        let post = |path: &str, content_type: &str, body: &str| {
+            warp::test::request()
+                .method("POST")
+                .header("x-api-key", "iaas-secret-key-123")
+                .header("content-type", content_type)
+                .path(path)
+                .body(body)
+        };
+        let manifest = "
+servers:
+  - name: web-1
+    cpu: 2
+    ram: 4
+    storage: 40
+    disks:
+      - size_gb: 100
+        disk_type: ssd
+  - name: db-1
+    cpu: 4
+    ram: 16
+    storage: 100
+";
+
+        // Dry run: the full plan, nothing touched.
+        let resp = post("/apply?dry_run=true", "application/yaml", manifest).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        let actions: Vec<(&str, &str)> = body["plan"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| (c["action"].as_str().unwrap(), c["name"].as_str().unwrap()))
+            .collect();
+        assert_eq!(actions, vec![("delete", "legacy"), ("create", "web-1"), ("create", "db-1")]);
+        assert_eq!(body["plan"][0]["server_id"], legacy.id.to_string());
+        assert!(body["applied"].as_array().unwrap().is_empty());
+        assert_eq!(service.list_servers().await?.len(), 1);
+
+        let resp = post("/apply", "application/yaml", manifest).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["applied"].as_array().unwrap().len(), 3);
+        let web = service.find_server_by_name("web-1").await?.unwrap();
+        assert_eq!(web.additional_disks.len(), 1);
+        let db = service.find_server_by_name("db-1").await?.unwrap();
+        assert!(service.find_server_by_name("legacy").await?.is_none());
+
+        // Converged: applying again plans nothing.
+        let resp = post("/apply", "application/yaml", manifest).reply(&api).await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert!(body["plan"].as_array().unwrap().is_empty());
+
+        // Tags and a new disk are applied in place; a new flavor replaces the server.
+        let manifest = serde_json::json!({ "servers": [
+            { "name": "web-1", "cpu": 2, "ram": 4, "storage": 40, "tags": { "env": "prod" },
+              "disks": [{ "size_gb": 100, "disk_type": "ssd" }, { "size_gb": 50 }] },
+            { "name": "db-1", "cpu": 8, "ram": 32, "storage": 100 }
+        ]});
+        let resp = post("/apply", "application/json", &manifest.to_string()).reply(&api).await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body())?;
+        assert_eq!(body["applied"][0]["action"], "replace");
+        assert_eq!(body["applied"][0]["details"][0], "flavor c4.r16 -> c8.r32");
+        assert_eq!(body["applied"][1]["action"], "update");
+        let updated = service.get_server(web.id).await?;
+        assert_eq!(updated.additional_disks.len(), 2);
+        assert_eq!(updated.tags["env"], "prod");
+        let replaced = service.find_server_by_name("db-1").await?.unwrap();
+        assert_ne!(replaced.id, db.id);
+        assert_eq!(body["applied"][0]["server_id"], replaced.id.to_string());
+
+        // Invalid manifests are rejected before anything changes.
+        let nvme = "servers:\n  - name: small\n    cpu: 1\n    ram: 1\n    storage: 10\n    disks:\n      - size_gb: 100\n        disk_type: nvme\n";
+        assert_eq!(post("/apply", "application/yaml", nvme).reply(&api).await.status(), 400);
+        assert_eq!(post("/apply", "text/plain", nvme).reply(&api).await.status(), 415);
+        assert_eq!(service.list_servers().await?.len(), 2);
+        Ok(())
+    }
+
+    /// Test double for the identity provider: the token *is* the role name.
+    struct FakeTokens;
+
+    #[async_trait::async_trait]
+    impl VerifyTokens for FakeTokens {
+        async fn verify(&self, token: &str) -> anyhow::Result<application::Principal> {
+            let role = crate::domain::Role::parse(token).ok_or_else(|| anyhow::anyhow!("bad token"))?;
+            Ok(application::Principal::with_role(format!("{}-user", token), role))
+        }
+    }
+
+    /// SSO Test: Bearer tokens are accepted, roles are enforced, and the API key can be switched off.
+    #[tokio::test]
+    async fn test_oidc_bearer_tokens_and_roles() -> anyhow::Result<()> {
+        let test_dir = tempdir()?;
+        let repo = Arc::new(JsonServerRepository::new(test_dir.path().to_str().unwrap())?);
+        let service: Arc<dyn ManageServers> = Arc::new(ServerService::new(repo));
+        let auth = AuthConfig { api_key: false, static_key: None, api_keys: None, tokens: Some(Arc::new(FakeTokens)) };
+        let api = routes(service, WebConfig { auth, ..test_web_config() });
+
+        let create = |token: &str| {
+            warp::test::request()
+                .method("POST")
+                .header("authorization", format!("Bearer {}", token))
+                .path("/servers")
+                .json(&serde_json::json!({ "name": format!("vm-{}", token), "cpu": 1, "ram": 1, "storage": 10 }))
+        };
+        assert_eq!(create("operator").reply(&api).await.status(), 200);
+        assert_eq!(create("viewer").reply(&api).await.status(), 403);
+        assert_eq!(create("forged").reply(&api).await.status(), 401);
+
+        let list = |request: warp::test::RequestBuilder| request.method("GET").path("/servers");
+        let viewer = list(warp::test::request().header("authorization", "Bearer viewer"));
+        assert_eq!(viewer.reply(&api).await.status(), 200);
+
+        // In pure OIDC mode the static API key no longer works.
+        let api_key = list(warp::test::request().header("x-api-key", "iaas-secret-key-123"));
+        assert_eq!(api_key.reply(&api).await.status(), 401);
+        Ok(())
+    }
 }
@@ -0,0 +1,119 @@
+// Minimal dashboard: plain fetch() against the same API, no build step, no framework.
+// The API key is kept in sessionStorage, so it is forgotten when the tab closes.
+"use strict";
+
+const keyInput = document.getElementById("api-key");
+const message = document.getElementById("message");
+const serversBody = document.getElementById("servers");
+
+keyInput.value = sessionStorage.getItem("iaas-api-key") || "";
+
+function show(text, isError) {
+  message.textContent = text;
+  message.className = isError ? "error" : "";
+}
+
+// Errors are RFC 7807 problem+json; `detail` says what went wrong.
+async function api(method, path, body) {
+  const headers = { "x-api-key": keyInput.value };
+  if (body !== undefined) {
+    headers["content-type"] = "application/json";
+  }
+  const resp = await fetch(path, { method, headers, body: body === undefined ? undefined : JSON.stringify(body) });
+  if (!resp.ok) {
+    const problem = await resp.json().catch(() => ({}));
+    throw new Error(problem.detail || problem.title || `HTTP ${resp.status}`);
+  }
+  return resp.status === 204 ? null : resp.json();
+}
+
+function cell(text, className) {
+  const td = document.createElement("td");
+  td.textContent = text;
+  if (className) {
+    td.className = className;
+  }
+  return td;
+}
+
+function render(servers) {
+  serversBody.replaceChildren();
+  for (const server of servers) {
+    const row = document.createElement("tr");
+    const status = document.createElement("span");
+    status.textContent = server.status;
+    status.className = `status status-${server.status.toLowerCase()}`;
+    const statusCell = cell("");
+    statusCell.append(status);
+
+    const remove = document.createElement("button");
+    remove.type = "button";
+    remove.textContent = "Delete";
+    remove.addEventListener("click", () => deleteServer(server));
+    const actions = cell("");
+    actions.append(remove);
+
+    row.append(cell(server.name), statusCell, cell(String(server.disks.length)), cell(server.description || ""), cell(server.id, "id"), actions);
+    serversBody.append(row);
+  }
+}
+
+async function refresh() {
+  if (!keyInput.value) {
+    show("Enter an API key to connect.", true);
+    return;
+  }
+  try {
+    render(await api("GET", "/servers"));
+  } catch (e) {
+    show(`Could not list servers: ${e.message}`, true);
+  }
+}
+
+async function deleteServer(server) {
+  if (!confirm(`Delete server '${server.name}'?`)) {
+    return;
+  }
+  try {
+    await api("DELETE", `/servers/${server.id}`);
+    show(`Server '${server.name}' deleted.`);
+    await refresh();
+  } catch (e) {
+    show(`Could not delete '${server.name}': ${e.message}`, true);
+  }
+}
+
+document.getElementById("login").addEventListener("submit", (event) => {
+  event.preventDefault();
+  sessionStorage.setItem("iaas-api-key", keyInput.value);
+  show("");
+  refresh();
+});
+
+document.getElementById("refresh").addEventListener("click", refresh);
+
+document.getElementById("create").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  const form = new FormData(event.target);
+  const body = {
+    name: form.get("name"),
+    cpu: Number(form.get("cpu")),
+    ram: Number(form.get("ram")),
+    storage: Number(form.get("storage")),
+  };
+  if (form.get("description")) {
+    body.description = form.get("description");
+  }
+  try {
+    const server = await api("POST", "/servers", body);
+    show(`Server '${server.name}' created.`);
+    event.target.reset();
+    await refresh();
+  } catch (e) {
+    show(`Could not create server: ${e.message}`, true);
+  }
+});
+
+if (keyInput.value) {
+  refresh();
+}